@@ -0,0 +1,90 @@
+// font-kit/examples/bench-rasterize.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Times bilevel against grayscale rasterization of the printable ASCII glyphs, drawn at integer
+//! origins into canvases sized with `raster_bounds()`, as a terminal in bitmap mode would.
+//!
+//! Run it with `--release`, since the numbers mean little otherwise. Bilevel isn't always the
+//! faster of the two: FreeType's monochrome rasterizer, for one, is slower than its antialiased
+//! one on unhinted outlines.
+
+extern crate font_kit;
+
+use font_kit::prelude::*;
+use std::env;
+use std::time::{Duration, Instant};
+
+#[cfg(any(target_family = "windows", target_os = "macos"))]
+static SANS_SERIF_FONT_REGULAR_POSTSCRIPT_NAME: &str = "ArialMT";
+#[cfg(not(any(target_family = "windows", target_os = "macos")))]
+static SANS_SERIF_FONT_REGULAR_POSTSCRIPT_NAME: &str = "DejaVuSans";
+
+const ITERATIONS: u32 = 200;
+
+fn rasterize_all(
+    font: &Font,
+    glyph_ids: &[u32],
+    point_size: f32,
+    rasterization_options: RasterizationOptions,
+) -> Result<Duration, Box<dyn std::error::Error>> {
+    let start = Instant::now();
+    for &glyph_id in glyph_ids {
+        let bounds = font.raster_bounds(
+            glyph_id,
+            point_size,
+            Transform2F::default(),
+            HintingOptions::None,
+            rasterization_options,
+        )?;
+        let mut canvas = Canvas::new(bounds.size(), Format::A8);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            point_size,
+            Transform2F::from_translation(-bounds.origin().to_f32()),
+            HintingOptions::None,
+            rasterization_options,
+        )?;
+    }
+    Ok(start.elapsed())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<_> = env::args().collect();
+    let postscript_name = args
+        .get(1)
+        .map_or(SANS_SERIF_FONT_REGULAR_POSTSCRIPT_NAME, |arg| arg.as_str());
+    let point_size = match args.get(2) {
+        Some(arg) => arg.parse()?,
+        None => 12.0,
+    };
+
+    let font = SystemSource::new()
+        .select_by_postscript_name(postscript_name)?
+        .load()?;
+    let glyph_ids: Vec<u32> = (' '..='~')
+        .filter_map(|character| font.glyph_for_char(character))
+        .collect();
+
+    for (name, rasterization_options) in [
+        ("bilevel", RasterizationOptions::Bilevel),
+        ("grayscale", RasterizationOptions::GrayscaleAa),
+    ] {
+        // Warm up any caches the loader keeps before timing.
+        rasterize_all(&font, &glyph_ids, point_size, rasterization_options)?;
+        let mut elapsed = Duration::default();
+        for _ in 0..ITERATIONS {
+            elapsed += rasterize_all(&font, &glyph_ids, point_size, rasterization_options)?;
+        }
+        let per_glyph = elapsed / (ITERATIONS * glyph_ids.len() as u32);
+        println!("{:>9}: {:?} per glyph", name, per_glyph);
+    }
+    Ok(())
+}
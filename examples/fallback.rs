@@ -13,8 +13,7 @@ extern crate font_kit;
 
 use clap::{Arg, ArgMatches, Command};
 
-use font_kit::loader::Loader;
-use font_kit::source::SystemSource;
+use font_kit::prelude::*;
 
 #[cfg(any(target_family = "windows", target_os = "macos"))]
 static SANS_SERIF_FONT_REGULAR_POSTSCRIPT_NAME: &'static str = "ArialMT";
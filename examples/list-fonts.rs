@@ -14,7 +14,7 @@ extern crate font_kit;
 extern crate pbr;
 extern crate prettytable;
 
-use font_kit::source::SystemSource;
+use font_kit::prelude::*;
 use pbr::ProgressBar;
 use prettytable::{Attr, Cell, Row, Table};
 
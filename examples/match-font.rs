@@ -12,10 +12,7 @@
 
 extern crate font_kit;
 
-use font_kit::family_name::FamilyName;
-use font_kit::handle::Handle;
-use font_kit::properties::Properties;
-use font_kit::source::SystemSource;
+use font_kit::prelude::*;
 use std::env;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -0,0 +1,93 @@
+// font-kit/examples/print-outline.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Prints the outline of a character as SVG path data, using only the prelude.
+
+extern crate font_kit;
+
+use font_kit::prelude::*;
+use std::env;
+use std::fmt::Write;
+
+struct SvgPath(String);
+
+impl OutlineSink for SvgPath {
+    fn move_to(&mut self, to: Vector2F) {
+        write!(self.0, "M {} {} ", to.x(), -to.y()).unwrap();
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        write!(self.0, "L {} {} ", to.x(), -to.y()).unwrap();
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        write!(
+            self.0,
+            "Q {} {} {} {} ",
+            ctrl.x(),
+            -ctrl.y(),
+            to.x(),
+            -to.y()
+        )
+        .unwrap();
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        let (ctrl0, ctrl1) = (ctrl.from(), ctrl.to());
+        write!(
+            self.0,
+            "C {} {} {} {} {} {} ",
+            ctrl0.x(),
+            -ctrl0.y(),
+            ctrl1.x(),
+            -ctrl1.y(),
+            to.x(),
+            -to.y()
+        )
+        .unwrap();
+    }
+
+    fn close(&mut self) {
+        self.0.push_str("Z ");
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<_> = env::args().collect();
+    let character = match args.get(2).and_then(|arg| arg.chars().next()) {
+        Some(character) if args.len() == 3 => character,
+        _ => {
+            println!("Usage:\n\tprint-outline \"Times New Roman\" A");
+            std::process::exit(1);
+        }
+    };
+
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::Title(args[1].clone())], &Properties::new())?
+        .load()?;
+    let glyph_id = font
+        .glyph_for_char(character)
+        .ok_or("the font has no glyph for the character")?;
+
+    // The outline is in font units with y up, so it's flipped to SVG's y-down coordinates.
+    let mut path = SvgPath(String::new());
+    font.outline(glyph_id, HintingOptions::None, &mut path)?;
+    let bounds = font.typographic_bounds(glyph_id)?;
+    println!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+        bounds.origin_x(),
+        -bounds.max_y(),
+        bounds.width(),
+        bounds.height()
+    );
+    println!("  <path d=\"{}\"/>", path.0.trim_end());
+    println!("</svg>");
+    Ok(())
+}
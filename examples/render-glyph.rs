@@ -11,14 +11,10 @@
 extern crate clap;
 extern crate colored;
 extern crate font_kit;
-extern crate pathfinder_geometry;
 
 use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
 use colored::Colorize;
-use font_kit::canvas::{Canvas, Format, RasterizationOptions};
-use font_kit::hinting::HintingOptions;
-use font_kit::source::SystemSource;
-use pathfinder_geometry::transform2d::Transform2F;
+use font_kit::prelude::*;
 use std::fmt::Write;
 
 #[cfg(any(target_family = "windows", target_os = "macos"))]
@@ -53,19 +49,29 @@ fn get_args() -> ArgMatches {
         .long("subpixel")
         .action(ArgAction::SetTrue);
     let hinting_value_parser =
-        clap::builder::PossibleValuesParser::new(["none", "vertical", "full"]);
+        clap::builder::PossibleValuesParser::new(["none", "vertical", "full", "font-default"]);
     let hinting_arg = Arg::new("hinting")
         .help("Select hinting type")
         .short('H')
         .long("hinting")
         .value_parser(hinting_value_parser)
-        .value_names(&["TYPE"]);
+        .value_names(["TYPE"]);
     let transform_arg = Arg::new("transform")
         .help("Transform to apply to glyph when rendering")
         .long("transform")
         .num_args(4);
+    let scale_arg = Arg::new("scale")
+        .help("Device pixel ratio to render at, such as 1.5 or 2")
+        .long("scale")
+        .default_value("1")
+        .value_names(["RATIO"]);
+    let underline_arg = Arg::new("underline")
+        .help("Draw the font's underline below the glyph")
+        .short('u')
+        .long("underline")
+        .action(ArgAction::SetTrue);
     let rasterization_mode_group =
-        ArgGroup::new("rasterization-mode").args(&["grayscale", "bilevel", "subpixel"]);
+        ArgGroup::new("rasterization-mode").args(["grayscale", "bilevel", "subpixel"]);
     Command::new("render-glyph")
         .version("0.1")
         .author("The Pathfinder Project Developers")
@@ -79,6 +85,8 @@ fn get_args() -> ArgMatches {
         .group(rasterization_mode_group)
         .arg(hinting_arg)
         .arg(transform_arg)
+        .arg(scale_arg)
+        .arg(underline_arg)
         .get_matches()
 }
 
@@ -102,6 +110,12 @@ fn main() {
         .unwrap()
         .parse()
         .unwrap();
+    let scale: f32 = matches
+        .get_one::<String>("scale")
+        .map(|s| s.as_str())
+        .unwrap()
+        .parse()
+        .unwrap();
 
     let (canvas_format, rasterization_options) = if matches.get_flag("bilevel") {
         (Format::A8, RasterizationOptions::Bilevel)
@@ -121,6 +135,7 @@ fn main() {
     let hinting_options = match matches.get_one::<String>("hinting").map(|s| s.as_str()) {
         Some("vertical") => HintingOptions::Vertical(size),
         Some("full") => HintingOptions::Full(size),
+        Some("font-default") => HintingOptions::FontDefault(size),
         _ => HintingOptions::None,
     };
 
@@ -131,28 +146,87 @@ fn main() {
         .unwrap();
     let glyph_id = font.glyph_for_char(character).unwrap();
 
-    let raster_rect = font
-        .raster_bounds(
+    // The size and hinting are in layout pixels; the glyph is rasterized in device pixels.
+    let mut raster_rect = font
+        .raster_bounds_with_pixels_per_dip(
             glyph_id,
             size,
+            scale,
             transform,
             hinting_options,
             rasterization_options,
         )
         .unwrap();
 
+    // The underline spans the glyph's advance, with its top `underline_position` above the
+    // baseline.
+    let underline = if matches.get_flag("underline") {
+        let metrics = font.metrics();
+        let unit_scale = metrics.scale_for(size * scale);
+        let advance = unit_scale
+            .to_px(font.advance(glyph_id).unwrap().x())
+            .round() as i32;
+        let top = -unit_scale.to_px(metrics.underline_position);
+        let thickness = unit_scale.to_px(metrics.underline_thickness);
+        let underline_rect = RectF::new(
+            Vector2F::new(0.0, top),
+            Vector2F::new(advance as f32, thickness.max(1.0)),
+        );
+        raster_rect = raster_rect
+            .to_f32()
+            .union_rect(underline_rect)
+            .round_out()
+            .to_i32();
+        Some((advance, top, thickness))
+    } else {
+        None
+    };
+
     let mut canvas = Canvas::new(raster_rect.size(), canvas_format);
-    font.rasterize_glyph(
-        &mut canvas,
-        glyph_id,
-        size,
-        Transform2F::from_translation(-raster_rect.origin().to_f32()) * transform,
-        hinting_options,
-        rasterization_options,
-    )
-    .unwrap();
-
-    println!("glyph {}:", glyph_id);
+    let rasterized_glyph = font
+        .rasterize_glyph_with_pixels_per_dip(
+            &mut canvas,
+            glyph_id,
+            size,
+            scale,
+            Transform2F::from_translation(-raster_rect.origin().to_f32()) * transform,
+            hinting_options,
+            rasterization_options,
+        )
+        .unwrap();
+
+    if let Some((advance, top, thickness)) = underline {
+        let origin = raster_rect.origin();
+        let pixel = match canvas.format {
+            Format::A8 => &[0xff][..],
+            Format::A16 => &[0xff, 0xff][..],
+            Format::Rgb24 => &[0xff, 0xff, 0xff][..],
+            Format::Rgba32 => &[0xff, 0xff, 0xff, 0xff][..],
+        };
+        canvas.fill_horizontal_line(
+            -origin.x(),
+            advance - origin.x(),
+            top - origin.y() as f32,
+            thickness,
+            pixel,
+        );
+    }
+
+    println!(
+        "glyph {} ({:?}, ink rect {:?}):",
+        glyph_id, rasterized_glyph.source, rasterized_glyph.ink_rect
+    );
+    if scale != 1.0 {
+        let layout_origin = raster_rect.origin().to_f32() * (1.0 / scale);
+        println!(
+            "{}x{} device pixels at {}x, placed at ({}, {}) layout pixels from the origin",
+            raster_rect.width(),
+            raster_rect.height(),
+            scale,
+            layout_origin.x(),
+            layout_origin.y()
+        );
+    }
     for y in 0..raster_rect.height() {
         let mut line = String::new();
         let (row_start, row_end) = (y as usize * canvas.stride, (y + 1) as usize * canvas.stride);
@@ -175,6 +249,11 @@ fn main() {
                     line.push(shade);
                     line.push(shade);
                 }
+                Format::A16 => {
+                    let shade = shade(row[x as usize * 2 + 1]);
+                    line.push(shade);
+                    line.push(shade);
+                }
             }
         }
         println!("{}", line);
@@ -0,0 +1,118 @@
+// font-kit/src/cache.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Optional per-font caches for glyph data that doesn't change over the life of a font.
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Mutex;
+
+/// A lazily-populated map from glyph IDs to values, which can be disabled to save memory.
+///
+/// Disabled caches, which are the default, compute every value afresh.
+pub(crate) struct GlyphCache<V> {
+    entries: Option<Mutex<HashMap<u32, V>>>,
+}
+
+impl<V> GlyphCache<V>
+where
+    V: Clone,
+{
+    /// Creates an empty cache that remembers values.
+    pub(crate) fn enabled() -> GlyphCache<V> {
+        GlyphCache {
+            entries: Some(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Creates a cache that doesn't remember anything.
+    pub(crate) fn disabled() -> GlyphCache<V> {
+        GlyphCache { entries: None }
+    }
+
+    /// Returns a new empty cache that remembers values if this one does.
+    #[inline]
+    pub(crate) fn emptied(&self) -> GlyphCache<V> {
+        if self.is_enabled() {
+            GlyphCache::enabled()
+        } else {
+            GlyphCache::disabled()
+        }
+    }
+
+    /// Returns true if this cache remembers values.
+    #[inline]
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.entries.is_some()
+    }
+
+    /// Forgets every remembered value, leaving the cache enabled if it was.
+    pub(crate) fn clear(&self) {
+        if let Some(ref entries) = self.entries {
+            entries.lock().unwrap().clear();
+        }
+    }
+
+    /// Returns the cached value for `glyph_id`, calling `compute` to produce and remember it if
+    /// there isn't one.
+    ///
+    /// Errors aren't cached. The lock isn't held while `compute` runs, so it may call back into
+    /// the font.
+    pub(crate) fn get_or_try_insert_with<E, F>(&self, glyph_id: u32, compute: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        let entries = match self.entries {
+            None => return compute(),
+            Some(ref entries) => entries,
+        };
+        if let Some(value) = entries.lock().unwrap().get(&glyph_id) {
+            return Ok((*value).clone());
+        }
+        let value = compute()?;
+        entries.lock().unwrap().insert(glyph_id, value.clone());
+        Ok(value)
+    }
+}
+
+impl<V> Clone for GlyphCache<V>
+where
+    V: Clone,
+{
+    fn clone(&self) -> GlyphCache<V> {
+        GlyphCache {
+            entries: self
+                .entries
+                .as_ref()
+                .map(|entries| Mutex::new((*entries.lock().unwrap()).clone())),
+        }
+    }
+}
+
+impl<V> Default for GlyphCache<V>
+where
+    V: Clone,
+{
+    #[inline]
+    fn default() -> GlyphCache<V> {
+        GlyphCache::disabled()
+    }
+}
+
+impl<V> Debug for GlyphCache<V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self.entries {
+            None => fmt.write_str("GlyphCache(disabled)"),
+            Some(ref entries) => {
+                write!(fmt, "GlyphCache({} entries)", entries.lock().unwrap().len())
+            }
+        }
+    }
+}
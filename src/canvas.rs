@@ -12,23 +12,23 @@
 
 use lazy_static::lazy_static;
 use pathfinder_geometry::rect::RectI;
-use pathfinder_geometry::vector::Vector2I;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use std::cmp;
 use std::fmt;
 
+use crate::error::CanvasError;
+use crate::hinting::HintingOptions;
 use crate::utils;
 
 lazy_static! {
     static ref BITMAP_1BPP_TO_8BPP_LUT: [[u8; 8]; 256] = {
         let mut lut = [[0; 8]; 256];
-        for byte in 0..0x100 {
-            let mut value = [0; 8];
-            for bit in 0..8 {
+        for (byte, value) in lut.iter_mut().enumerate() {
+            for (bit, pixel) in value.iter_mut().enumerate() {
                 if (byte & (0x80 >> bit)) != 0 {
-                    value[bit] = 0xff;
+                    *pixel = 0xff;
                 }
             }
-            lut[byte] = value
         }
         lut
     };
@@ -47,8 +47,15 @@ pub struct Canvas {
 }
 
 impl Canvas {
+    /// The largest pixel buffer, in bytes, that `try_new()` will allocate: enough for a
+    /// 16384 by 16384 `Rgba32` canvas.
+    pub const MAX_BYTES: usize = 1 << 30;
+
     /// Creates a new blank canvas with the given pixel size and format.
     ///
+    /// This panics or aborts if the size is negative or too large to allocate; use `try_new()`
+    /// for sizes that come from untrusted fonts.
+    ///
     /// Stride is automatically calculated from width.
     ///
     /// The canvas is initialized with transparent black (all values 0).
@@ -61,6 +68,36 @@ impl Canvas {
         )
     }
 
+    /// Creates a new blank canvas with the given pixel size and format, returning an error
+    /// instead of panicking if the size is negative or the pixels would take more than
+    /// `MAX_BYTES` bytes.
+    ///
+    /// Raster bounds computed from a malicious or corrupt font can be absurd, so servers that
+    /// render untrusted fonts should create canvases this way.
+    pub fn try_new(size: Vector2I, format: Format) -> Result<Canvas, CanvasError> {
+        if size.x() < 0 || size.y() < 0 {
+            return Err(CanvasError::InvalidSize);
+        }
+        let stride = (size.x() as usize)
+            .checked_mul(format.bytes_per_pixel() as usize)
+            .ok_or(CanvasError::TooLarge)?;
+        let len = stride
+            .checked_mul(size.y() as usize)
+            .filter(|&len| len <= Canvas::MAX_BYTES)
+            .ok_or(CanvasError::TooLarge)?;
+        let mut pixels = vec![];
+        pixels
+            .try_reserve_exact(len)
+            .map_err(|_| CanvasError::TooLarge)?;
+        pixels.resize(len, 0);
+        Ok(Canvas {
+            pixels,
+            size,
+            stride,
+            format,
+        })
+    }
+
     /// Creates a new blank canvas with the given pixel size, stride (number of bytes between
     /// successive rows), and format.
     ///
@@ -74,6 +111,357 @@ impl Canvas {
         }
     }
 
+    /// Composites `src` over this canvas, placing its top left corner at `dest_origin`.
+    ///
+    /// `Rgba32` canvases are blended with the Porter-Duff source-over operator on premultiplied
+    /// pixels. For the coverage formats `A8`, `A16`, and `Rgb24`, each channel takes the maximum
+    /// of the two coverage values. The parts of `src` that fall outside this canvas are clipped.
+    ///
+    /// Both canvases must have the same format.
+    #[inline]
+    pub fn composite(&mut self, src: &Canvas, dest_origin: Vector2I) {
+        self.composite_in(src, dest_origin, BlendSpace::Srgb)
+    }
+
+    /// Composites `src` over this canvas like `composite()`, blending `Rgba32` pixels in the
+    /// given color space.
+    ///
+    /// `BlendSpace::Linear` avoids the dark fringes that sRGB blending leaves around antialiased
+    /// edges, at the cost of converting every partially transparent pixel to linear light and
+    /// back. The coverage formats `A8`, `A16`, and `Rgb24` are unaffected by `blend_space`.
+    pub fn composite_in(&mut self, src: &Canvas, dest_origin: Vector2I, blend_space: BlendSpace) {
+        assert_eq!(
+            self.format, src.format,
+            "Compositing between different canvas formats is unsupported!"
+        );
+        match (self.format, blend_space) {
+            (Format::Rgba32, BlendSpace::Srgb) => {
+                self.composite_with::<CompositeSourceOver>(src, dest_origin)
+            }
+            (Format::Rgba32, BlendSpace::Linear) => {
+                self.composite_with::<CompositeSourceOverLinear>(src, dest_origin)
+            }
+            (Format::Rgb24, _) | (Format::A8, _) => {
+                self.composite_with::<CompositeMax>(src, dest_origin)
+            }
+            (Format::A16, _) => self.composite_with::<CompositeMaxA16>(src, dest_origin),
+        }
+    }
+
+    fn composite_with<C: Blit>(&mut self, src: &Canvas, dest_origin: Vector2I) {
+        let dest_rect = RectI::new(dest_origin, src.size);
+        let dest_rect = match dest_rect.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(dest_rect) => dest_rect,
+            None => return,
+        };
+        let src_origin = dest_rect.origin() - dest_origin;
+        let bytes_per_pixel = self.format.bytes_per_pixel() as usize;
+        let row_length = dest_rect.width() as usize * bytes_per_pixel;
+
+        for y in 0..dest_rect.height() {
+            let dest_row_start = (y + dest_rect.origin_y()) as usize * self.stride
+                + dest_rect.origin_x() as usize * bytes_per_pixel;
+            let src_row_start = (y + src_origin.y()) as usize * src.stride
+                + src_origin.x() as usize * bytes_per_pixel;
+            C::blit(
+                &mut self.pixels[dest_row_start..(dest_row_start + row_length)],
+                &src.pixels[src_row_start..(src_row_start + row_length)],
+            )
+        }
+    }
+
+    /// Fills `rect` with a single pixel value, clipping it to the canvas.
+    ///
+    /// `pixel` holds one pixel in this canvas's format: a coverage value for `A8`, a little-endian
+    /// coverage value for `A16`, per-channel coverage for `Rgb24`, and a premultiplied color for
+    /// `Rgba32`. It is composited with the same operators as `composite()`, so coverage takes the
+    /// maximum and colors are blended source-over in sRGB space. This is useful for drawing
+    /// backgrounds, underlines, and strikeouts next to rasterized glyphs.
+    #[inline]
+    pub fn fill_rect(&mut self, rect: RectI, pixel: &[u8]) {
+        self.fill_rect_in(rect, pixel, BlendSpace::Srgb)
+    }
+
+    /// Fills `rect` with a single pixel value like `fill_rect()`, blending `Rgba32` pixels in the
+    /// given color space.
+    pub fn fill_rect_in(&mut self, rect: RectI, pixel: &[u8], blend_space: BlendSpace) {
+        assert_eq!(
+            pixel.len(),
+            self.format.bytes_per_pixel() as usize,
+            "The fill pixel must match the canvas format!"
+        );
+        match (self.format, blend_space) {
+            (Format::Rgba32, BlendSpace::Srgb) => {
+                self.fill_rect_with::<CompositeSourceOver>(rect, pixel)
+            }
+            (Format::Rgba32, BlendSpace::Linear) => {
+                self.fill_rect_with::<CompositeSourceOverLinear>(rect, pixel)
+            }
+            (Format::Rgb24, _) | (Format::A8, _) => {
+                self.fill_rect_with::<CompositeMax>(rect, pixel)
+            }
+            (Format::A16, _) => self.fill_rect_with::<CompositeMaxA16>(rect, pixel),
+        }
+    }
+
+    /// Draws a horizontal line, such as an underline or strikeout, spanning `min_x..max_x`.
+    ///
+    /// `top` and `thickness` are in pixels, with `y` pointing down. The line is snapped to whole
+    /// rows and is always at least one pixel thick. `pixel` is interpreted as in `fill_rect()`.
+    pub fn fill_horizontal_line(
+        &mut self,
+        min_x: i32,
+        max_x: i32,
+        top: f32,
+        thickness: f32,
+        pixel: &[u8],
+    ) {
+        let min_y = top.round() as i32;
+        let height = cmp::max(thickness.round() as i32, 1);
+        let rect = RectI::new(
+            Vector2I::new(min_x, min_y),
+            Vector2I::new(max_x - min_x, height),
+        );
+        self.fill_rect(rect, pixel)
+    }
+
+    fn fill_rect_with<C: Blit>(&mut self, rect: RectI, pixel: &[u8]) {
+        let rect = match rect.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(rect) => rect,
+            None => return,
+        };
+        let bytes_per_pixel = pixel.len();
+        let row: Vec<u8> = pixel
+            .iter()
+            .cycle()
+            .take(rect.width() as usize * bytes_per_pixel)
+            .cloned()
+            .collect();
+        for y in rect.min_y()..rect.max_y() {
+            let row_start = y as usize * self.stride + rect.min_x() as usize * bytes_per_pixel;
+            C::blit(&mut self.pixels[row_start..(row_start + row.len())], &row)
+        }
+    }
+
+    /// Returns a new `A8` canvas holding one channel of this canvas.
+    ///
+    /// This turns a glyph rasterized in color or with subpixel antialiasing into a coverage mask;
+    /// the green channel is a good proxy for the luminance of `Rgb24` subpixel coverage. `A8` and
+    /// `A16` canvases have a single coverage channel, which any `channel` selects, narrowed to 8
+    /// bits for `A16`. The new canvas has the minimum stride for its width.
+    ///
+    /// `Rgb24` canvases have no alpha channel, so they are treated as opaque: their alpha channel
+    /// is 0xff everywhere.
+    pub fn extract_channel(&self, channel: Channel) -> Canvas {
+        let channel_offset = match (self.format, channel) {
+            (Format::Rgba32, Channel::Alpha) => 3,
+            (Format::Rgb24, Channel::Alpha) => {
+                let mut dest = Canvas::new(self.size, Format::A8);
+                dest.pixels.fill(0xff);
+                return dest;
+            }
+            (Format::Rgba32, _) | (Format::Rgb24, _) => channel as usize,
+            (Format::A8, _) | (Format::A16, _) => 0,
+        };
+        let bytes_per_pixel = self.format.bytes_per_pixel() as usize;
+        let mut dest = Canvas::new(self.size, Format::A8);
+        for y in 0..self.size.y() as usize {
+            let src_row = &self.pixels[(y * self.stride)..];
+            let dest_row = &mut dest.pixels[(y * dest.stride)..((y + 1) * dest.stride)];
+            for (dest, src) in dest_row.iter_mut().zip(src_row.chunks(bytes_per_pixel)) {
+                *dest = match self.format {
+                    Format::A16 => a16_to_a8(u16::from_le_bytes([src[0], src[1]])),
+                    Format::Rgba32 | Format::Rgb24 | Format::A8 => src[channel_offset],
+                };
+            }
+        }
+        dest
+    }
+
+    /// Returns a new canvas holding the pixels of this canvas within `rect`, in the same format
+    /// and with the minimum stride for its width.
+    ///
+    /// `rect` is clipped to the canvas, so the new canvas is smaller than `rect` if it extends
+    /// past the edges, and empty if it lies entirely outside. This extracts the ink rect of
+    /// glyphs rasterized into a larger scratch canvas, ready to upload.
+    pub fn copy_rect(&self, rect: RectI) -> Canvas {
+        let rect = self.clip_rect(rect);
+        let mut dest = Canvas::new(rect.size(), self.format);
+        let dest_stride = dest.stride;
+        self.copy_rect_into(rect, &mut dest.pixels, dest_stride);
+        dest
+    }
+
+    /// Copies the pixels of this canvas within `rect` into `dest`, whose rows are `dest_stride`
+    /// bytes apart, and returns the part of `rect` that was copied.
+    ///
+    /// `rect` is clipped to the canvas as in `copy_rect()`, and the top left pixel of what remains
+    /// is copied to the start of `dest`. Pixels keep this canvas's format. Bytes of `dest` past
+    /// the end of each copied row are left untouched, so it can be a region of a larger image.
+    ///
+    /// Panics if `dest_stride` is smaller than a row of the clipped rect or `dest` is too small to
+    /// hold all of its rows.
+    pub fn copy_rect_into(&self, rect: RectI, dest: &mut [u8], dest_stride: usize) -> RectI {
+        let rect = self.clip_rect(rect);
+        let bytes_per_pixel = self.format.bytes_per_pixel() as usize;
+        let row_length = rect.width() as usize * bytes_per_pixel;
+        if rect.height() == 0 {
+            return rect;
+        }
+        assert!(
+            dest_stride >= row_length,
+            "The destination stride is too small for the rect!"
+        );
+        assert!(
+            dest.len() >= (rect.height() as usize - 1) * dest_stride + row_length,
+            "The destination is too small for the rect!"
+        );
+        for y in 0..rect.height() as usize {
+            let src_row_start =
+                (rect.min_y() as usize + y) * self.stride + rect.min_x() as usize * bytes_per_pixel;
+            let dest_row_start = y * dest_stride;
+            dest[dest_row_start..(dest_row_start + row_length)]
+                .copy_from_slice(&self.pixels[src_row_start..(src_row_start + row_length)]);
+        }
+        rect
+    }
+
+    // Clips `rect` to the canvas, returning an empty rect at the origin if nothing is left.
+    fn clip_rect(&self, rect: RectI) -> RectI {
+        rect.intersection(RectI::new(Vector2I::default(), self.size))
+            .filter(|rect| rect.width() > 0 && rect.height() > 0)
+            .unwrap_or_default()
+    }
+
+    /// Converts the pixels of an `Rgba32` canvas within `rect` from premultiplied to straight
+    /// alpha.
+    ///
+    /// Fully transparent pixels become transparent black. This does nothing for other formats.
+    pub fn unpremultiply_alpha(&mut self, rect: RectI) {
+        self.convert_alpha(rect, |color, alpha| {
+            if alpha == 0 {
+                0
+            } else {
+                cmp::min(255, (color as u32 * 255 + alpha as u32 / 2) / alpha as u32) as u8
+            }
+        })
+    }
+
+    /// Converts the pixels of an `Rgba32` canvas within `rect` from straight to premultiplied
+    /// alpha.
+    ///
+    /// This does nothing for other formats.
+    pub fn premultiply_alpha(&mut self, rect: RectI) {
+        self.convert_alpha(rect, |color, alpha| {
+            ((color as u32 * alpha as u32 + 127) / 255) as u8
+        })
+    }
+
+    fn convert_alpha<F>(&mut self, rect: RectI, convert: F)
+    where
+        F: Fn(u8, u8) -> u8,
+    {
+        if self.format != Format::Rgba32 {
+            return;
+        }
+        let rect = match rect.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(rect) => rect,
+            None => return,
+        };
+        for y in rect.min_y()..rect.max_y() {
+            let row_start = y as usize * self.stride + rect.min_x() as usize * 4;
+            let row_end = row_start + rect.width() as usize * 4;
+            for pixel in self.pixels[row_start..row_end].chunks_mut(4) {
+                let alpha = pixel[3];
+                for color in &mut pixel[0..3] {
+                    *color = convert(*color, alpha);
+                }
+            }
+        }
+    }
+
+    /// Mirrors the contents of the canvas in place, horizontally, vertically, or both.
+    ///
+    /// The channels of each pixel keep their order. For subpixel coverage mirrored along the axis
+    /// of its subpixels, follow this with `reverse_subpixels()`.
+    #[cfg(any(
+        not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+        feature = "loader-freetype"
+    ))]
+    pub(crate) fn flip(&mut self, horizontal: bool, vertical: bool) {
+        let bytes_per_pixel = self.format.bytes_per_pixel() as usize;
+        let row_length = self.size.x() as usize * bytes_per_pixel;
+        let height = self.size.y() as usize;
+        if horizontal {
+            for row in self.pixels.chunks_mut(self.stride).take(height) {
+                let row = &mut row[..row_length];
+                row.reverse();
+                // Reversing the bytes also reversed the channels within each pixel.
+                if bytes_per_pixel > 1 {
+                    for pixel in row.chunks_mut(bytes_per_pixel) {
+                        pixel.reverse();
+                    }
+                }
+            }
+        }
+        if vertical {
+            for y in 0..(height / 2) {
+                let (upper, lower) = self.pixels.split_at_mut((height - 1 - y) * self.stride);
+                let upper_row_start = y * self.stride;
+                upper[upper_row_start..(upper_row_start + row_length)]
+                    .swap_with_slice(&mut lower[..row_length]);
+            }
+        }
+    }
+
+    /// Reverses the order of the subpixels of each pixel of an `Rgb24` canvas that holds subpixel
+    /// coverage, so that red coverage becomes blue coverage and vice versa.
+    ///
+    /// Mirroring a glyph moves what was its rightmost (or bottommost) subpixel to the left (or
+    /// top), while the display's subpixel layout stays the same. Rendering for a display with
+    /// `SubpixelGeometry::Bgr` also needs the coverage of its leftmost subpixel in blue.
+    pub(crate) fn reverse_subpixels(&mut self) {
+        debug_assert_eq!(self.format, Format::Rgb24);
+        let row_length = self.size.x() as usize * 3;
+        for row in self.pixels.chunks_mut(self.stride) {
+            for pixel in row[..row_length].chunks_mut(3) {
+                pixel.swap(0, 2);
+            }
+        }
+    }
+
+    /// Applies a gamma and contrast adjustment to the coverage of an `A8`, `A16`, or `Rgb24`
+    /// canvas, as described on `RenderSettings`. `Rgba32` canvases are left unchanged.
+    pub(crate) fn adjust_coverage(&mut self, gamma: f32, contrast: f32) {
+        if gamma == 1.0 && contrast == 0.0 {
+            return;
+        }
+        let contrast = contrast.clamp(0.0, 1.0);
+        let adjust = |coverage: f32| {
+            let coverage = coverage + contrast * coverage * (1.0 - coverage);
+            coverage.powf(1.0 / gamma).clamp(0.0, 1.0)
+        };
+        match self.format {
+            Format::A8 | Format::Rgb24 => {
+                let mut table = [0; 256];
+                for (value, adjusted) in table.iter_mut().enumerate() {
+                    *adjusted = (adjust(value as f32 / 255.0) * 255.0).round() as u8;
+                }
+                for value in &mut self.pixels {
+                    *value = table[*value as usize];
+                }
+            }
+            Format::A16 => {
+                for value in self.pixels.chunks_mut(2) {
+                    let coverage = u16::from_le_bytes([value[0], value[1]]) as f32 / 65535.0;
+                    let adjusted = (adjust(coverage) * 65535.0).round() as u16;
+                    value.copy_from_slice(&adjusted.to_le_bytes());
+                }
+            }
+            Format::Rgba32 => {}
+        }
+    }
+
     #[allow(dead_code)]
     pub(crate) fn blit_from_canvas(&mut self, src: &Canvas) {
         self.blit_from(
@@ -101,8 +489,15 @@ impl Canvas {
             None => return,
         };
 
+        // Skip the parts of the source that fall above or to the left of the canvas.
+        let src_bytes_per_pixel = src_format.bytes_per_pixel() as usize;
+        let src_offset = dst_rect.origin() - dst_point;
+        let src_bytes = &src_bytes[(src_offset.y() as usize * src_stride
+            + src_offset.x() as usize * src_bytes_per_pixel)..];
+
         match (self.format, src_format) {
             (Format::A8, Format::A8)
+            | (Format::A16, Format::A16)
             | (Format::Rgb24, Format::Rgb24)
             | (Format::Rgba32, Format::Rgba32) => {
                 self.blit_from_with::<BlitMemcpy>(dst_rect, src_bytes, src_stride, src_format)
@@ -117,8 +512,69 @@ impl Canvas {
                 .blit_from_with::<BlitRgba32ToRgb24>(dst_rect, src_bytes, src_stride, src_format),
             (Format::Rgba32, Format::Rgb24) => self
                 .blit_from_with::<BlitRgb24ToRgba32>(dst_rect, src_bytes, src_stride, src_format),
-            (Format::Rgba32, Format::A8) | (Format::A8, Format::Rgba32) => unimplemented!(),
+            (Format::A16, Format::A8) => {
+                self.blit_from_with::<BlitA8ToA16>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A8, Format::A16) => {
+                self.blit_from_with::<BlitA16ToA8>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A16, Format::Rgb24) => {
+                self.blit_from_with::<BlitRgb24ToA16>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::Rgb24, Format::A16) => {
+                self.blit_from_with::<BlitA16ToRgb24>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::Rgba32, Format::A8) => {
+                self.blit_from_with::<BlitA8ToRgba32>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A8, Format::Rgba32) => {
+                self.blit_from_with::<BlitRgba32ToA8>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::Rgba32, Format::A16) => {
+                self.blit_from_with::<BlitA16ToRgba32>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A16, Format::Rgba32) => {
+                self.blit_from_with::<BlitRgba32ToA16>(dst_rect, src_bytes, src_stride, src_format)
+            }
+        }
+    }
+
+    // Like `blit_from()`, but takes ownership of the source pixels. If they would exactly replace
+    // the contents of the canvas, they become its buffer and nothing is copied.
+    #[cfg(any(all(target_family = "windows", feature = "directwrite"), test))]
+    pub(crate) fn blit_from_owned(
+        &mut self,
+        dst_point: Vector2I,
+        src_bytes: Vec<u8>,
+        src_size: Vector2I,
+        src_stride: usize,
+        src_format: Format,
+    ) {
+        if dst_point == Vector2I::default()
+            && src_size == self.size
+            && src_stride == self.stride
+            && src_format == self.format
+            && src_bytes.len() == self.pixels.len()
+        {
+            self.pixels = src_bytes;
+            return;
         }
+        self.blit_from(dst_point, &src_bytes, src_size, src_stride, src_format)
+    }
+
+    // Combines the coverage of the `A8` canvas `src`, which is the same size as this `Rgba32`
+    // canvas, into this canvas within `rect`: each pixel keeps the larger of the two coverages as
+    // its alpha, so overlapping glyphs don't erase each other, and its color channels become white.
+    pub(crate) fn combine_white_coverage(&mut self, rect: RectI, src: &Canvas) {
+        debug_assert!(self.format == Format::Rgba32 && src.format == Format::A8);
+        debug_assert_eq!(self.size, src.size);
+        let rect = match rect.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(rect) => rect,
+            None => return,
+        };
+        let src_bytes =
+            &src.pixels[(rect.origin_y() as usize * src.stride + rect.origin_x() as usize)..];
+        self.blit_from_with::<CombineA8ToWhiteRgba32>(rect, src_bytes, src.stride, Format::A8)
     }
 
     #[allow(dead_code)]
@@ -130,7 +586,17 @@ impl Canvas {
         src_stride: usize,
     ) {
         if self.format != Format::A8 {
-            unimplemented!()
+            // Unpack to 8 bits first, then convert.
+            let mut a8_canvas = Canvas::new(src_size, Format::A8);
+            a8_canvas.blit_from_bitmap_1bpp(Vector2I::default(), src_bytes, src_size, src_stride);
+            self.blit_from(
+                dst_point,
+                &a8_canvas.pixels,
+                src_size,
+                a8_canvas.stride,
+                Format::A8,
+            );
+            return;
         }
 
         let dst_rect = RectI::new(dst_point, src_size);
@@ -146,6 +612,28 @@ impl Canvas {
         let dest_row_stride = size.x() as usize * dest_bytes_per_pixel;
         let src_row_stride = utils::div_round_up(size.x() as usize, 8);
 
+        // Skip the parts of the source that fall above or to the left of the canvas. If that
+        // doesn't leave the source byte-aligned, unpack one bit at a time.
+        let src_offset = dst_rect.origin() - dst_point;
+        let src_bytes = &src_bytes[src_offset.y() as usize * src_stride..];
+        let src_bit_offset = src_offset.x() as usize;
+        if src_bit_offset % 8 != 0 {
+            for y in 0..size.y() as usize {
+                let dest_row_start = (y + dst_rect.origin_y() as usize) * self.stride
+                    + dst_rect.origin_x() as usize * dest_bytes_per_pixel;
+                let src_row_pixels = &src_bytes[(y * src_stride)..];
+                let dest_row_pixels =
+                    &mut self.pixels[dest_row_start..(dest_row_start + dest_row_stride)];
+                for (x, dest_pixel) in dest_row_pixels.iter_mut().enumerate() {
+                    let src_x = x + src_bit_offset;
+                    let bit = src_row_pixels[src_x / 8] & (0x80 >> (src_x % 8));
+                    *dest_pixel = if bit != 0 { 0xff } else { 0 };
+                }
+            }
+            return;
+        }
+        let src_bytes = &src_bytes[(src_bit_offset / 8)..];
+
         for y in 0..size.y() {
             let (dest_row_start, src_row_start) = (
                 (y + dst_rect.origin_y()) as usize * self.stride
@@ -205,12 +693,21 @@ impl fmt::Debug for Canvas {
 /// The image format for the canvas.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Format {
-    /// Premultiplied R8G8B8A8, little-endian.
+    /// R8G8B8A8, little-endian.
+    ///
+    /// Loaders always produce premultiplied alpha in this format. Use
+    /// `Loader::rasterize_glyph_with_alpha_mode()` to request straight alpha instead.
     Rgba32,
     /// R8G8B8, little-endian.
     Rgb24,
     /// A8.
     A8,
+    /// A16, little-endian.
+    ///
+    /// Native rasterizers produce only 8 bits of coverage, which is widened when stored in this
+    /// format. It is mainly useful as a target for rasterizers and distance fields that compute
+    /// coverage with more precision.
+    A16,
 }
 
 impl Format {
@@ -221,6 +718,7 @@ impl Format {
             Format::Rgba32 => 32,
             Format::Rgb24 => 24,
             Format::A8 => 8,
+            Format::A16 => 16,
         }
     }
 
@@ -230,7 +728,7 @@ impl Format {
         match self {
             Format::Rgba32 => 4,
             Format::Rgb24 => 3,
-            Format::A8 => 1,
+            Format::A8 | Format::A16 => 1,
         }
     }
 
@@ -247,6 +745,19 @@ impl Format {
     }
 }
 
+/// A color channel of a canvas, as passed to `Canvas::extract_channel()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// The red channel.
+    Red = 0,
+    /// The green channel.
+    Green = 1,
+    /// The blue channel.
+    Blue = 2,
+    /// The alpha channel.
+    Alpha = 3,
+}
+
 /// The antialiasing strategy that should be used when rasterizing glyphs.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum RasterizationOptions {
@@ -258,6 +769,236 @@ pub enum RasterizationOptions {
     SubpixelAa,
 }
 
+/// Describes the result of rasterizing a glyph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RasterizedGlyph {
+    /// The rectangle of the canvas that the rasterizer wrote to, in canvas coordinates.
+    ///
+    /// This is clipped to the canvas bounds and is empty if nothing was drawn.
+    pub ink_rect: RectI,
+    /// Where the rendered image came from.
+    pub source: GlyphSource,
+    /// True if the canvas is in `Format::Rgba32` and the color channels are premultiplied by
+    /// alpha. This is always false for the other formats, which carry coverage only.
+    pub premultiplied: bool,
+}
+
+impl RasterizedGlyph {
+    pub(crate) fn new(canvas: &Canvas, ink_rect: RectI, source: GlyphSource) -> RasterizedGlyph {
+        let canvas_rect = RectI::new(Vector2I::default(), canvas.size);
+        RasterizedGlyph {
+            ink_rect: ink_rect.intersection(canvas_rect).unwrap_or_default(),
+            source,
+            premultiplied: canvas.format == Format::Rgba32,
+        }
+    }
+}
+
+/// How the color channels of `Rgba32` output relate to the alpha channel.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum AlphaMode {
+    /// Color channels are multiplied by alpha. No color value exceeds its alpha.
+    #[default]
+    Premultiplied,
+    /// Color channels are independent of alpha. Fully transparent pixels are transparent black.
+    Straight,
+    /// Color channels are white and alpha holds the glyph's coverage, even where it is zero.
+    ///
+    /// This is the texture that text shaders multiplying coverage by a per-vertex color expect.
+    /// Coverage is a single channel, so subpixel antialiasing is reduced to its green channel.
+    WhiteCoverage,
+}
+
+/// The color space in which `Rgba32` canvases are blended when compositing.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum BlendSpace {
+    /// Blend the stored sRGB-encoded values directly. This is fast but darkens antialiased edges.
+    #[default]
+    Srgb,
+    /// Convert to linear light, blend, and convert back to sRGB.
+    Linear,
+}
+
+/// The order of the subpixels of the pixels of a display, which subpixel antialiasing has to match.
+///
+/// The subpixels are vertical stripes laid out horizontally.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SubpixelGeometry {
+    /// Red, green, and blue from left to right, as on most LCD displays.
+    #[default]
+    Rgb,
+    /// Blue, green, and red from left to right.
+    Bgr,
+}
+
+/// The kind of glyph data a rasterized image was produced from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GlyphSource {
+    /// The glyph was rendered from its vector outline.
+    Outline,
+    /// The glyph was copied (and possibly scaled) from a bitmap strike embedded in the font.
+    EmbeddedBitmap,
+    /// The glyph was composited from color layers, as with `COLR` fonts.
+    ColorLayers,
+}
+
+/// The options that control how `Loader::render()` draws a glyph, bundled so that they can be
+/// configured once and reused.
+///
+/// This object supports a method chaining style for idiomatic initialization; e.g.
+///
+///     # use font_kit::canvas::{Format, RasterizationOptions, RenderSettings};
+///     let mut settings = RenderSettings::new();
+///     settings
+///         .rasterization_options(RasterizationOptions::SubpixelAa)
+///         .format(Format::Rgb24)
+///         .pixels_per_dip(2.0);
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderSettings {
+    /// The grid fitting to perform. Sizes in it are in device-independent pixels.
+    pub hinting_options: HintingOptions,
+    /// The antialiasing strategy.
+    pub rasterization_options: RasterizationOptions,
+    /// The subpixel layout of the display, which `RasterizationOptions::SubpixelAa` renders for.
+    pub subpixel_geometry: SubpixelGeometry,
+    /// The gamma that coverage is encoded with, which must be positive: each coverage value `c`
+    /// becomes `c^(1 / gamma)`. Values above 1.0 raise partial coverage, so text looks heavier,
+    /// and values below 1.0 make it lighter. 1.0 leaves coverage unchanged.
+    pub gamma: f32,
+    /// How much to raise partial coverage before applying `gamma`, from 0.0 to 1.0: each coverage
+    /// value `c` becomes `c + contrast * c * (1 - c)`, which sharpens and thickens antialiased
+    /// edges. 0.0 leaves coverage unchanged.
+    pub contrast: f32,
+    /// The format of the canvas to render into.
+    pub format: Format,
+    /// How the color channels of `Rgba32` output relate to the alpha channel.
+    pub alpha_mode: AlphaMode,
+    /// The number of physical pixels per device-independent pixel, such as 2.0 on a display
+    /// scaled to 200%.
+    pub pixels_per_dip: f32,
+}
+
+impl Default for RenderSettings {
+    #[inline]
+    fn default() -> RenderSettings {
+        RenderSettings {
+            hinting_options: HintingOptions::None,
+            rasterization_options: RasterizationOptions::GrayscaleAa,
+            subpixel_geometry: SubpixelGeometry::Rgb,
+            gamma: 1.0,
+            contrast: 0.0,
+            format: Format::A8,
+            alpha_mode: AlphaMode::Premultiplied,
+            pixels_per_dip: 1.0,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Initializes the settings to their default values: no hinting, grayscale antialiasing for an
+    /// RGB display into an `A8` canvas with no gamma or contrast adjustment, premultiplied alpha,
+    /// and one physical pixel per device-independent pixel.
+    #[inline]
+    pub fn new() -> RenderSettings {
+        RenderSettings::default()
+    }
+
+    /// Sets the hinting options and returns these settings for method chaining.
+    #[inline]
+    pub fn hinting_options(&mut self, hinting_options: HintingOptions) -> &mut RenderSettings {
+        self.hinting_options = hinting_options;
+        self
+    }
+
+    /// Sets the rasterization options and returns these settings for method chaining.
+    #[inline]
+    pub fn rasterization_options(
+        &mut self,
+        rasterization_options: RasterizationOptions,
+    ) -> &mut RenderSettings {
+        self.rasterization_options = rasterization_options;
+        self
+    }
+
+    /// Sets the subpixel layout of the display and returns these settings for method chaining.
+    #[inline]
+    pub fn subpixel_geometry(
+        &mut self,
+        subpixel_geometry: SubpixelGeometry,
+    ) -> &mut RenderSettings {
+        self.subpixel_geometry = subpixel_geometry;
+        self
+    }
+
+    /// Sets the gamma that coverage is encoded with and returns these settings for method
+    /// chaining.
+    #[inline]
+    pub fn gamma(&mut self, gamma: f32) -> &mut RenderSettings {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Sets the contrast adjustment of coverage and returns these settings for method chaining.
+    #[inline]
+    pub fn contrast(&mut self, contrast: f32) -> &mut RenderSettings {
+        self.contrast = contrast;
+        self
+    }
+
+    /// Sets the canvas format and returns these settings for method chaining.
+    #[inline]
+    pub fn format(&mut self, format: Format) -> &mut RenderSettings {
+        self.format = format;
+        self
+    }
+
+    /// Sets the alpha mode and returns these settings for method chaining.
+    #[inline]
+    pub fn alpha_mode(&mut self, alpha_mode: AlphaMode) -> &mut RenderSettings {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Sets the number of physical pixels per device-independent pixel and returns these settings
+    /// for method chaining.
+    #[inline]
+    pub fn pixels_per_dip(&mut self, pixels_per_dip: f32) -> &mut RenderSettings {
+        self.pixels_per_dip = pixels_per_dip;
+        self
+    }
+}
+
+/// A glyph drawn by `Loader::render()` into a canvas of its own.
+#[derive(Debug)]
+pub struct RenderedGlyph {
+    /// A canvas just large enough to hold the glyph.
+    pub canvas: Canvas,
+    /// The pixels that the canvas covers, relative to the glyph origin, with the y axis pointing
+    /// down. Draw the canvas at the pen position plus the origin of this rectangle.
+    pub bounds: RectI,
+    /// A description of what was rendered. Its ink rect is in canvas coordinates.
+    pub rasterized_glyph: RasterizedGlyph,
+}
+
+/// A glyph drawn by `Loader::render_at()`, along with where to draw it.
+///
+/// Layout pixels are the device-independent pixels that text is laid out in; device pixels are
+/// layout pixels multiplied by the `pixels_per_dip` of the render settings.
+#[derive(Debug)]
+pub struct PlacedGlyph {
+    /// The rendered glyph. Its bounds are relative to `device_origin - bounds.origin()`, the
+    /// device pixel that contains the pen position.
+    pub rendered_glyph: RenderedGlyph,
+    /// Where to draw the top left of the canvas, in device pixels.
+    pub device_origin: Vector2I,
+    /// Where to draw the top left of the canvas, in layout pixels. This is `device_origin`
+    /// divided by `pixels_per_dip`, so it may be fractional.
+    pub layout_origin: Vector2F,
+    /// The fractional part of the pen position in device pixels, which was rendered into the
+    /// glyph image as a subpixel offset.
+    pub residue: Vector2F,
+}
+
 trait Blit {
     fn blit(dest: &mut [u8], src: &[u8]);
 }
@@ -296,6 +1037,60 @@ impl Blit for BlitA8ToRgb24 {
     }
 }
 
+// Widens 8-bit coverage so that 0xff maps to 0xffff.
+struct BlitA8ToA16;
+
+impl Blit for BlitA8ToA16 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, &src) in dest.chunks_mut(2).zip(src.iter()) {
+            dest.copy_from_slice(&(src as u16 * 257).to_le_bytes())
+        }
+    }
+}
+
+struct BlitA16ToA8;
+
+impl Blit for BlitA16ToA8 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.iter_mut().zip(src.chunks(2)) {
+            *dest = a16_to_a8(u16::from_le_bytes([src[0], src[1]]))
+        }
+    }
+}
+
+struct BlitRgb24ToA16;
+
+impl Blit for BlitRgb24ToA16 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(2).zip(src.chunks(3)) {
+            dest.copy_from_slice(&(src[1] as u16 * 257).to_le_bytes())
+        }
+    }
+}
+
+struct BlitA16ToRgb24;
+
+impl Blit for BlitA16ToRgb24 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(3).zip(src.chunks(2)) {
+            let value = a16_to_a8(u16::from_le_bytes([src[0], src[1]]));
+            dest[0] = value;
+            dest[1] = value;
+            dest[2] = value;
+        }
+    }
+}
+
+// Narrows 16-bit coverage to the nearest 8-bit value.
+#[inline]
+fn a16_to_a8(value: u16) -> u8 {
+    ((value as u32 + 128) / 257) as u8
+}
+
 struct BlitRgba32ToRgb24;
 
 impl Blit for BlitRgba32ToRgb24 {
@@ -320,3 +1115,301 @@ impl Blit for BlitRgb24ToRgba32 {
         }
     }
 }
+
+// Coverage becomes premultiplied white, and back again through the alpha channel.
+struct BlitA8ToRgba32;
+
+impl Blit for BlitA8ToRgba32 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, &src) in dest.chunks_mut(4).zip(src.iter()) {
+            dest.copy_from_slice(&[src; 4]);
+        }
+    }
+}
+
+struct BlitRgba32ToA8;
+
+impl Blit for BlitRgba32ToA8 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.iter_mut().zip(src.chunks(4)) {
+            *dest = src[3]
+        }
+    }
+}
+
+struct BlitA16ToRgba32;
+
+impl Blit for BlitA16ToRgba32 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(4).zip(src.chunks(2)) {
+            let value = a16_to_a8(u16::from_le_bytes([src[0], src[1]]));
+            dest.copy_from_slice(&[value; 4]);
+        }
+    }
+}
+
+struct BlitRgba32ToA16;
+
+impl Blit for BlitRgba32ToA16 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(2).zip(src.chunks(4)) {
+            dest.copy_from_slice(&(src[3] as u16 * 257).to_le_bytes())
+        }
+    }
+}
+
+struct CombineA8ToWhiteRgba32;
+
+impl Blit for CombineA8ToWhiteRgba32 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, &src) in dest.chunks_mut(4).zip(src.iter()) {
+            let alpha = dest[3].max(src);
+            dest.copy_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+}
+
+struct CompositeSourceOver;
+
+impl Blit for CompositeSourceOver {
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(4).zip(src.chunks(4)) {
+            let inverse_alpha = 255 - src[3] as u32;
+            for (dest, &src) in dest.iter_mut().zip(src.iter()) {
+                // Computes `src + dest * (1 - src_alpha)` with correct rounding.
+                let product = *dest as u32 * inverse_alpha + 128;
+                let blended = src as u32 + ((product + (product >> 8)) >> 8);
+                *dest = cmp::min(blended, 255) as u8;
+            }
+        }
+    }
+}
+
+struct CompositeSourceOverLinear;
+
+impl Blit for CompositeSourceOverLinear {
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(4).zip(src.chunks(4)) {
+            match src[3] {
+                0 => continue,
+                255 => {
+                    dest.copy_from_slice(src);
+                    continue;
+                }
+                _ => {}
+            }
+            let src_alpha = src[3] as f32 / 255.0;
+            let dest_alpha = dest[3] as f32 / 255.0;
+            let alpha = src_alpha + dest_alpha * (1.0 - src_alpha);
+            for (dest, &src) in dest[0..3].iter_mut().zip(src[0..3].iter()) {
+                let src_linear = premultiplied_srgb_to_linear(src, src_alpha);
+                let dest_linear = premultiplied_srgb_to_linear(*dest, dest_alpha);
+                let blended = src_linear + dest_linear * (1.0 - src_alpha);
+                let color = linear_to_srgb((blended / alpha).min(1.0)) * alpha;
+                *dest = (color * 255.0).round() as u8;
+            }
+            dest[3] = (alpha * 255.0).round() as u8;
+        }
+    }
+}
+
+// Returns the premultiplied linear-light value of a premultiplied sRGB-encoded channel.
+fn premultiplied_srgb_to_linear(color: u8, alpha: f32) -> f32 {
+    if alpha == 0.0 {
+        return 0.0;
+    }
+    srgb_to_linear((color as f32 / 255.0 / alpha).min(1.0)) * alpha
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+struct CompositeMax;
+
+impl Blit for CompositeMax {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, &src) in dest.iter_mut().zip(src.iter()) {
+            *dest = cmp::max(*dest, src)
+        }
+    }
+}
+
+struct CompositeMaxA16;
+
+impl Blit for CompositeMaxA16 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(2).zip(src.chunks(2)) {
+            let value = cmp::max(
+                u16::from_le_bytes([dest[0], dest[1]]),
+                u16::from_le_bytes([src[0], src[1]]),
+            );
+            dest.copy_from_slice(&value.to_le_bytes())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Canvas, Channel, Format};
+    use pathfinder_geometry::vector::Vector2I;
+
+    // A source placed partly above and to the left of the canvas lands with the matching part of
+    // it in the top left corner.
+    #[test]
+    fn blit_from_clipped_at_top_left() {
+        let mut canvas = Canvas::new(Vector2I::splat(3), Format::A8);
+        let src: Vec<u8> = (1..=9).collect();
+        canvas.blit_from(
+            Vector2I::new(-1, -2),
+            &src,
+            Vector2I::splat(3),
+            3,
+            Format::A8,
+        );
+        assert_eq!(canvas.pixels, [8, 9, 0, 0, 0, 0, 0, 0, 0]);
+
+        let mut canvas = Canvas::new(Vector2I::splat(2), Format::Rgb24);
+        let src: Vec<u8> = (1..=12).collect();
+        canvas.blit_from(
+            Vector2I::new(-1, 0),
+            &src,
+            Vector2I::splat(2),
+            6,
+            Format::Rgb24,
+        );
+        assert_eq!(canvas.pixels, [4, 5, 6, 0, 0, 0, 10, 11, 12, 0, 0, 0]);
+    }
+
+    // Pixels that exactly replace the canvas become its buffer; anything else is copied in.
+    #[test]
+    fn blit_from_owned_moves_or_copies() {
+        let mut canvas = Canvas::new(Vector2I::splat(2), Format::A8);
+        let src = vec![1, 2, 3, 4];
+        let src_pointer = src.as_ptr();
+        canvas.blit_from_owned(Vector2I::default(), src, Vector2I::splat(2), 2, Format::A8);
+        assert_eq!(canvas.pixels, [1, 2, 3, 4]);
+        assert_eq!(canvas.pixels.as_ptr(), src_pointer);
+
+        // An offset source is clipped to the canvas.
+        let mut canvas = Canvas::new(Vector2I::splat(2), Format::A8);
+        canvas.blit_from_owned(
+            Vector2I::new(1, 0),
+            vec![1, 2, 3, 4],
+            Vector2I::splat(2),
+            2,
+            Format::A8,
+        );
+        assert_eq!(canvas.pixels, [0, 1, 0, 3]);
+
+        // A source in another format is converted.
+        let mut canvas = Canvas::new(Vector2I::new(1, 1), Format::Rgb24);
+        canvas.blit_from_owned(
+            Vector2I::default(),
+            vec![128],
+            Vector2I::splat(1),
+            1,
+            Format::A8,
+        );
+        assert_eq!(canvas.pixels, [128, 128, 128]);
+    }
+
+    // Coverage converts to and from premultiplied white in `Rgba32` canvases.
+    #[test]
+    fn blit_coverage_to_and_from_rgba32() {
+        let mut rgba_canvas = Canvas::new(Vector2I::new(2, 1), Format::Rgba32);
+        rgba_canvas.blit_from(
+            Vector2I::default(),
+            &[0x40, 0xff],
+            Vector2I::new(2, 1),
+            2,
+            Format::A8,
+        );
+        assert_eq!(
+            rgba_canvas.pixels,
+            [0x40, 0x40, 0x40, 0x40, 0xff, 0xff, 0xff, 0xff]
+        );
+
+        let mut a16_canvas = Canvas::new(Vector2I::new(2, 1), Format::A16);
+        a16_canvas.blit_from_canvas(&rgba_canvas);
+        assert_eq!(a16_canvas.pixels, [0x40, 0x40, 0xff, 0xff]);
+        let mut a8_canvas = Canvas::new(Vector2I::new(2, 1), Format::A8);
+        a8_canvas.blit_from_canvas(&rgba_canvas);
+        assert_eq!(a8_canvas.pixels, [0x40, 0xff]);
+
+        let mut rgba_canvas = Canvas::new(Vector2I::new(2, 1), Format::Rgba32);
+        rgba_canvas.blit_from_canvas(&a16_canvas);
+        assert_eq!(
+            rgba_canvas.pixels,
+            [0x40, 0x40, 0x40, 0x40, 0xff, 0xff, 0xff, 0xff]
+        );
+    }
+
+    // 1bpp sources can be drawn into canvases of any format.
+    #[test]
+    fn blit_from_bitmap_1bpp_to_any_format() {
+        for &format in &[Format::Rgba32, Format::Rgb24, Format::A8, Format::A16] {
+            let mut canvas = Canvas::new(Vector2I::new(2, 1), format);
+            canvas.blit_from_bitmap_1bpp(
+                Vector2I::default(),
+                &[0b0100_0000],
+                Vector2I::new(2, 1),
+                1,
+            );
+            let coverage = canvas.extract_channel(Channel::Green);
+            assert_eq!(coverage.pixels, [0, 0xff], "{:?}", format);
+        }
+    }
+
+    // Clipping a 1bpp source on the left by other than a whole byte unpacks it bit by bit.
+    #[test]
+    fn blit_from_bitmap_1bpp_clipped_at_top_left() {
+        let mut canvas = Canvas::new(Vector2I::new(4, 2), Format::A8);
+        let src = [0b1010_0000, 0b0101_0000, 0b1100_0000];
+        canvas.blit_from_bitmap_1bpp(Vector2I::new(-1, -1), &src, Vector2I::new(4, 3), 1);
+        assert_eq!(canvas.pixels, [0xff, 0, 0xff, 0, 0xff, 0, 0, 0]);
+
+        let mut canvas = Canvas::new(Vector2I::new(8, 1), Format::A8);
+        let src = [0x00, 0b1000_0001];
+        canvas.blit_from_bitmap_1bpp(Vector2I::new(-8, 0), &src, Vector2I::new(16, 1), 2);
+        assert_eq!(canvas.pixels, [0xff, 0, 0, 0, 0, 0, 0, 0xff]);
+    }
+
+    // Mirroring subpixel coverage horizontally reverses the subpixels across the whole row, not
+    // just the order of the pixels.
+    #[cfg(any(
+        not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+        feature = "loader-freetype"
+    ))]
+    #[test]
+    fn flip_subpixel_coverage() {
+        let mut canvas = Canvas::new(Vector2I::new(2, 2), Format::Rgb24);
+        canvas.pixels = (1..=12).collect();
+        canvas.flip(true, false);
+        assert_eq!(canvas.pixels, [4, 5, 6, 1, 2, 3, 10, 11, 12, 7, 8, 9]);
+        canvas.reverse_subpixels();
+        assert_eq!(canvas.pixels, [6, 5, 4, 3, 2, 1, 12, 11, 10, 9, 8, 7]);
+
+        canvas.flip(false, true);
+        assert_eq!(canvas.pixels, [12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    }
+}
@@ -0,0 +1,988 @@
+// font-kit/src/color.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Color glyphs, as described by the `COLR` and `CPAL` tables.
+//!
+//! Version 0 color glyphs are stacks of glyphs filled with solid palette colors. Version 1 color
+//! glyphs are graphs of paints that add gradients, affine transforms, and compositing modes.
+//! Both are rendered portably by `Loader::rasterize_color_glyph()`, using the loader's own
+//! rasterizer for the glyph outlines.
+
+use crate::canvas::{Canvas, Format, GlyphSource, RasterizationOptions, RasterizedGlyph};
+use crate::error::GlyphLoadingError;
+use crate::hinting::HintingOptions;
+use crate::loader::{self, Loader};
+use crate::tables;
+use crate::tables::colr::{ColorLine, CompositeMode, Extend, Paint, PaletteColor};
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use std::collections::HashMap;
+
+/// The palette index that stands for the color of the surrounding text rather than a palette
+/// entry.
+pub const FOREGROUND_PALETTE_INDEX: u16 = 0xffff;
+
+/// An 8-bit-per-channel color with straight (not premultiplied) alpha.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ColorU {
+    /// The red channel.
+    pub r: u8,
+    /// The green channel.
+    pub g: u8,
+    /// The blue channel.
+    pub b: u8,
+    /// The alpha channel.
+    pub a: u8,
+}
+
+impl ColorU {
+    /// Creates a color from its channels.
+    #[inline]
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> ColorU {
+        ColorU { r, g, b, a }
+    }
+
+    /// Opaque black.
+    #[inline]
+    pub const fn black() -> ColorU {
+        ColorU::new(0, 0, 0, 255)
+    }
+
+    /// Opaque white.
+    #[inline]
+    pub const fn white() -> ColorU {
+        ColorU::new(255, 255, 255, 255)
+    }
+
+    /// Fully transparent black.
+    #[inline]
+    pub const fn transparent_black() -> ColorU {
+        ColorU::new(0, 0, 0, 0)
+    }
+}
+
+/// Which palette of the `CPAL` table color glyphs are drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteChoice {
+    /// The palette at this index.
+    Index(u16),
+    /// The first palette that the font marks as usable with a light background, or palette 0 if
+    /// none is marked.
+    UsableWithLightBackground,
+    /// The first palette that the font marks as usable with a dark background, or palette 0 if
+    /// none is marked.
+    UsableWithDarkBackground,
+}
+
+impl Default for PaletteChoice {
+    #[inline]
+    fn default() -> PaletteChoice {
+        PaletteChoice::Index(0)
+    }
+}
+
+/// Chooses the colors that color glyphs are drawn with: a palette of the font, replacements for
+/// some of its entries, and the color of the surrounding text.
+///
+/// This object supports a method chaining style for idiomatic initialization; e.g.
+///
+///     # use font_kit::color::{ColorU, PaletteChoice, PaletteSelector};
+///     let mut selector = PaletteSelector::new();
+///     selector
+///         .palette(PaletteChoice::UsableWithDarkBackground)
+///         .override_color(2, ColorU::new(0x33, 0x99, 0xff, 0xff))
+///         .foreground(ColorU::black());
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaletteSelector {
+    /// The palette to start from.
+    pub palette: PaletteChoice,
+    /// Colors that replace entries of the palette, by palette entry index.
+    ///
+    /// Entries past the end of the palette extend it; any entries skipped over are transparent.
+    pub overrides: HashMap<u16, ColorU>,
+    /// The color drawn for `FOREGROUND_PALETTE_INDEX`.
+    pub foreground: ColorU,
+}
+
+impl Default for PaletteSelector {
+    #[inline]
+    fn default() -> PaletteSelector {
+        PaletteSelector {
+            palette: PaletteChoice::default(),
+            overrides: HashMap::new(),
+            foreground: ColorU::white(),
+        }
+    }
+}
+
+impl PaletteSelector {
+    /// Creates a selector for palette 0 without overrides and with an opaque white foreground,
+    /// which is how `Loader::rasterize_color_glyph()` draws.
+    #[inline]
+    pub fn new() -> PaletteSelector {
+        PaletteSelector::default()
+    }
+
+    /// Sets the palette to start from and returns this selector for method chaining.
+    #[inline]
+    pub fn palette(&mut self, palette: PaletteChoice) -> &mut PaletteSelector {
+        self.palette = palette;
+        self
+    }
+
+    /// Replaces the palette entry at `palette_index` with `color` and returns this selector for
+    /// method chaining.
+    #[inline]
+    pub fn override_color(&mut self, palette_index: u16, color: ColorU) -> &mut PaletteSelector {
+        self.overrides.insert(palette_index, color);
+        self
+    }
+
+    /// Sets the foreground color and returns this selector for method chaining.
+    #[inline]
+    pub fn foreground(&mut self, foreground: ColorU) -> &mut PaletteSelector {
+        self.foreground = foreground;
+        self
+    }
+}
+
+// Returns the palette that `selector` picks from the font, with its overrides applied.
+pub(crate) fn selected_palette<L>(font: &L, selector: &PaletteSelector) -> Vec<ColorU>
+where
+    L: Loader,
+{
+    let cpal_table = font.load_font_table(tables::cpal::TAG);
+    let palette_index = match selector.palette {
+        PaletteChoice::Index(palette_index) => Some(palette_index),
+        PaletteChoice::UsableWithLightBackground => cpal_table.as_ref().and_then(|table| {
+            tables::cpal::palette_with_type(table, tables::cpal::USABLE_WITH_LIGHT_BACKGROUND)
+        }),
+        PaletteChoice::UsableWithDarkBackground => cpal_table.as_ref().and_then(|table| {
+            tables::cpal::palette_with_type(table, tables::cpal::USABLE_WITH_DARK_BACKGROUND)
+        }),
+    };
+    let mut palette = cpal_table
+        .and_then(|table| tables::cpal::palette(&table, palette_index.unwrap_or(0)))
+        .unwrap_or_default();
+    for (&palette_index, &color) in &selector.overrides {
+        if palette_index == FOREGROUND_PALETTE_INDEX {
+            continue;
+        }
+        let palette_index = palette_index as usize;
+        if palette_index >= palette.len() {
+            palette.resize(palette_index + 1, ColorU::transparent_black());
+        }
+        palette[palette_index] = color;
+    }
+    palette
+}
+
+/// One layer of a version 0 color glyph: a glyph filled with a palette color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorLayer {
+    /// The glyph whose outline this layer fills.
+    pub glyph_id: u32,
+    /// The entry of the palette to fill it with, or `FOREGROUND_PALETTE_INDEX` for the text color.
+    pub palette_index: u16,
+}
+
+// A premultiplied color with channels from 0 to 1.
+type Pixel = [f32; 4];
+
+const TRANSPARENT: Pixel = [0.0; 4];
+
+// Renders the `COLR` glyph `glyph_id` like `Loader::rasterize_color_glyph()`. Returns `None` if
+// the font has no color data for the glyph or the canvas isn't `Rgba32`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn rasterize_color_glyph<L>(
+    font: &L,
+    canvas: &mut Canvas,
+    glyph_id: u32,
+    point_size: f32,
+    transform: Transform2F,
+    hinting_options: HintingOptions,
+    rasterization_options: RasterizationOptions,
+    palette_selector: &PaletteSelector,
+) -> Result<Option<RasterizedGlyph>, GlyphLoadingError>
+where
+    L: Loader,
+{
+    if canvas.format != Format::Rgba32 {
+        return Ok(None);
+    }
+    let glyph_index = loader::checked_glyph_id(font, glyph_id)?;
+    let colr_table = match font.load_font_table(tables::colr::TAG) {
+        Some(colr_table) => colr_table,
+        None => return Ok(None),
+    };
+
+    // Version 1 paint graphs take precedence over the version 0 layers of the same glyph, which
+    // fonts keep for older renderers.
+    let color_glyph = match tables::colr::base_glyph_paint(&colr_table, glyph_index) {
+        Some(paint) => ColorGlyph::Paint(paint, tables::colr::clip_box(&colr_table, glyph_index)),
+        None => match tables::colr::base_glyph_layers(&colr_table, glyph_index) {
+            Some(layers) => ColorGlyph::Layers(layers),
+            None => return Ok(None),
+        },
+    };
+
+    let renderer = Renderer {
+        font,
+        palette: selected_palette(font, palette_selector),
+        foreground: palette_selector.foreground,
+        size: canvas.size,
+        point_size,
+        hinting_options,
+        rasterization_options: match rasterization_options {
+            RasterizationOptions::Bilevel => RasterizationOptions::Bilevel,
+            _ => RasterizationOptions::GrayscaleAa,
+        },
+    };
+
+    // Color glyphs are in font units with the y axis pointing up, like outlines.
+    let scale = font.metrics().scale_for(point_size).pixels_per_unit();
+    let ctm = transform * Transform2F::from_scale(Vector2F::new(scale, -scale));
+    let pixels = match color_glyph {
+        ColorGlyph::Paint(paint, clip_box) => {
+            let clip = match clip_box {
+                Some(clip_box) => renderer.clip_box_mask(clip_box, ctm),
+                None => Mask::covering(RectI::new(Vector2I::default(), renderer.size)),
+            };
+            renderer.paint(&paint, ctm, &clip)?
+        }
+        ColorGlyph::Layers(layers) => renderer.layers(&layers, ctm)?,
+    };
+
+    let ink_rect = renderer.composite_onto(canvas, &pixels);
+    Ok(Some(RasterizedGlyph::new(
+        canvas,
+        ink_rect,
+        GlyphSource::ColorLayers,
+    )))
+}
+
+enum ColorGlyph {
+    // The paint graph, and the box in font units that the font clips it to, if any.
+    Paint(Paint, Option<RectF>),
+    Layers(Vec<ColorLayer>),
+}
+
+// The coverage of the canvas pixels within `rect`, row by row. Pixels outside `rect` aren't
+// covered, so masks of glyphs and clip boxes only cover their own bounds.
+struct Mask {
+    rect: RectI,
+    coverage: Vec<f32>,
+}
+
+impl Mask {
+    fn covering(rect: RectI) -> Mask {
+        Mask {
+            rect,
+            coverage: vec![1.0; rect.width() as usize * rect.height() as usize],
+        }
+    }
+
+    fn at(&self, position: Vector2I) -> f32 {
+        if !self.rect.contains_point(position) {
+            return 0.0;
+        }
+        let offset = position - self.rect.origin();
+        self.coverage[offset.y() as usize * self.rect.width() as usize + offset.x() as usize]
+    }
+
+    // Returns the positions of the pixels within `rect`, with their coverage.
+    fn pixels(&self) -> impl Iterator<Item = (Vector2I, f32)> + '_ {
+        let (origin, width) = (self.rect.origin(), self.rect.width().max(1) as usize);
+        self.coverage
+            .iter()
+            .enumerate()
+            .map(move |(index, &coverage)| {
+                let offset = Vector2I::new((index % width) as i32, (index / width) as i32);
+                (origin + offset, coverage)
+            })
+    }
+
+    fn intersection(&self, other: &Mask) -> Mask {
+        let rect = self.rect.intersection(other.rect).unwrap_or_default();
+        let mut mask = Mask::covering(rect);
+        for (coverage, (position, _)) in mask.coverage.iter_mut().zip(Mask::covering(rect).pixels())
+        {
+            *coverage = self.at(position) * other.at(position);
+        }
+        mask
+    }
+}
+
+struct Renderer<'a, L> {
+    font: &'a L,
+    palette: Vec<ColorU>,
+    foreground: ColorU,
+    size: Vector2I,
+    point_size: f32,
+    hinting_options: HintingOptions,
+    rasterization_options: RasterizationOptions,
+}
+
+impl<'a, L> Renderer<'a, L>
+where
+    L: Loader,
+{
+    #[inline]
+    fn pixel_count(&self) -> usize {
+        self.size.x() as usize * self.size.y() as usize
+    }
+
+    #[inline]
+    fn pixel_index(&self, position: Vector2I) -> usize {
+        position.y() as usize * self.size.x() as usize + position.x() as usize
+    }
+
+    // The simple path for version 0 glyphs: each layer's outline is filled with a solid color and
+    // composited over the layers below.
+    fn layers(
+        &self,
+        layers: &[ColorLayer],
+        ctm: Transform2F,
+    ) -> Result<Vec<Pixel>, GlyphLoadingError> {
+        let mut pixels = vec![TRANSPARENT; self.pixel_count()];
+        for layer in layers {
+            let color = self.color(PaletteColor {
+                palette_index: layer.palette_index,
+                alpha: 1.0,
+            });
+            let mask = self.glyph_mask(layer.glyph_id, ctm)?;
+            for (position, coverage) in mask.pixels() {
+                if coverage > 0.0 {
+                    let pixel = &mut pixels[self.pixel_index(position)];
+                    *pixel = source_over(scale_pixel(color, coverage), *pixel);
+                }
+            }
+        }
+        Ok(pixels)
+    }
+
+    // Renders `paint` in the coordinate space given by `ctm`, which maps font units to canvas
+    // pixels, with the coverage `clip` applied.
+    fn paint(
+        &self,
+        paint: &Paint,
+        ctm: Transform2F,
+        clip: &Mask,
+    ) -> Result<Vec<Pixel>, GlyphLoadingError> {
+        match *paint {
+            Paint::Layers(ref layers) => {
+                let mut pixels = vec![TRANSPARENT; self.pixel_count()];
+                for layer in layers {
+                    let layer_pixels = self.paint(layer, ctm, clip)?;
+                    for (pixel, layer_pixel) in pixels.iter_mut().zip(layer_pixels) {
+                        *pixel = source_over(layer_pixel, *pixel);
+                    }
+                }
+                Ok(pixels)
+            }
+            Paint::Solid(color) => {
+                let color = self.color(color);
+                Ok(self.fill(ctm, clip, |_| color))
+            }
+            Paint::LinearGradient {
+                ref color_line,
+                p0,
+                p1,
+                p2,
+            } => {
+                // The gradient runs from `p0` toward `p1`, with its lines of equal color parallel
+                // to `p0 -> p2`.
+                let normal = p2 - p0;
+                let normal = Vector2F::new(normal.y(), -normal.x());
+                let p3 = if normal.square_length() == 0.0 {
+                    p1
+                } else {
+                    p0 + normal * ((p1 - p0).dot(normal) / normal.square_length())
+                };
+                let direction = p3 - p0;
+                let length_squared = direction.square_length();
+                if length_squared == 0.0 {
+                    return Ok(vec![TRANSPARENT; self.pixel_count()]);
+                }
+                Ok(self.fill(ctm, clip, |point| {
+                    let t = (point - p0).dot(direction) / length_squared;
+                    self.color_line_at(color_line, t)
+                }))
+            }
+            Paint::RadialGradient {
+                ref color_line,
+                c0,
+                r0,
+                c1,
+                r1,
+            } => Ok(self.fill(ctm, clip, |point| {
+                match radial_gradient_position(point, c0, r0, c1, r1) {
+                    Some(t) => self.color_line_at(color_line, t),
+                    None => TRANSPARENT,
+                }
+            })),
+            Paint::SweepGradient {
+                ref color_line,
+                center,
+                start_angle,
+                end_angle,
+            } => {
+                if start_angle == end_angle {
+                    return Ok(vec![TRANSPARENT; self.pixel_count()]);
+                }
+                Ok(self.fill(ctm, clip, |point| {
+                    let offset = point - center;
+                    let angle = offset.y().atan2(offset.x()).to_degrees().rem_euclid(360.0);
+                    let t = (angle - start_angle) / (end_angle - start_angle);
+                    self.color_line_at(color_line, t)
+                }))
+            }
+            Paint::Glyph {
+                glyph_id,
+                ref paint,
+            } => {
+                let clip = clip.intersection(&self.glyph_mask(glyph_id as u32, ctm)?);
+                self.paint(paint, ctm, &clip)
+            }
+            Paint::Transform {
+                transform,
+                ref paint,
+            } => self.paint(paint, ctm * transform, clip),
+            Paint::Composite {
+                ref source,
+                mode,
+                ref backdrop,
+            } => {
+                let mut pixels = self.paint(backdrop, ctm, clip)?;
+                let source_pixels = self.paint(source, ctm, clip)?;
+                for (pixel, source_pixel) in pixels.iter_mut().zip(source_pixels) {
+                    *pixel = composite(source_pixel, *pixel, mode);
+                }
+                Ok(pixels)
+            }
+        }
+    }
+
+    // Fills the pixels inside `clip` with the color that `shader` returns for the center of each
+    // pixel in font units.
+    fn fill<F>(&self, ctm: Transform2F, clip: &Mask, shader: F) -> Vec<Pixel>
+    where
+        F: Fn(Vector2F) -> Pixel,
+    {
+        let mut pixels = vec![TRANSPARENT; self.pixel_count()];
+        let inverse = ctm.inverse();
+        for (position, coverage) in clip.pixels() {
+            if coverage > 0.0 {
+                let point = inverse * (position.to_f32() + Vector2F::splat(0.5));
+                pixels[self.pixel_index(position)] = scale_pixel(shader(point), coverage);
+            }
+        }
+        pixels
+    }
+
+    // Rasterizes the outline of `glyph_id` within its raster bounds, returning its coverage.
+    fn glyph_mask(&self, glyph_id: u32, ctm: Transform2F) -> Result<Mask, GlyphLoadingError> {
+        let scale = self
+            .font
+            .metrics()
+            .scale_for(self.point_size)
+            .pixels_per_unit();
+        let transform = ctm * Transform2F::from_scale(Vector2F::new(1.0 / scale, -1.0 / scale));
+        let bounds = self.font.raster_bounds(
+            glyph_id,
+            self.point_size,
+            transform,
+            self.hinting_options,
+            self.rasterization_options,
+        )?;
+        let rect = match bounds.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(rect) => rect,
+            None => return Ok(Mask::covering(RectI::default())),
+        };
+
+        let mut canvas = Canvas::new(rect.size(), Format::A8);
+        self.font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            self.point_size,
+            Transform2F::from_translation(-rect.origin().to_f32()) * transform,
+            self.hinting_options,
+            self.rasterization_options,
+        )?;
+        let width = rect.width() as usize;
+        let mut coverage = Vec::with_capacity(width * rect.height() as usize);
+        for row in canvas
+            .pixels
+            .chunks(canvas.stride.max(1))
+            .take(rect.height() as usize)
+        {
+            coverage.extend(row[..width].iter().map(|&value| value as f32 / 255.0));
+        }
+        Ok(Mask { rect, coverage })
+    }
+
+    // Returns the pixels whose centers lie inside `clip_box`, which is in font units.
+    fn clip_box_mask(&self, clip_box: RectF, ctm: Transform2F) -> Mask {
+        let corners = [
+            clip_box.origin(),
+            clip_box.upper_right(),
+            clip_box.lower_left(),
+            clip_box.lower_right(),
+        ]
+        .map(|corner| ctm * corner);
+        let (min, max) = corners[1..]
+            .iter()
+            .fold((corners[0], corners[0]), |(min, max), &corner| {
+                (min.min(corner), max.max(corner))
+            });
+        let bounds = RectF::from_points(min, max).round_out().to_i32();
+        let rect = bounds
+            .intersection(RectI::new(Vector2I::default(), self.size))
+            .unwrap_or_default();
+
+        let inverse = ctm.inverse();
+        let mut mask = Mask::covering(rect);
+        for (coverage, (position, _)) in mask.coverage.iter_mut().zip(Mask::covering(rect).pixels())
+        {
+            let point = inverse * (position.to_f32() + Vector2F::splat(0.5));
+            if !clip_box.contains_point(point) {
+                *coverage = 0.0;
+            }
+        }
+        mask
+    }
+
+    // Resolves a palette color to a premultiplied pixel. Indices past the end of the palette are
+    // transparent.
+    fn color(&self, color: PaletteColor) -> Pixel {
+        let base = if color.palette_index == FOREGROUND_PALETTE_INDEX {
+            self.foreground
+        } else {
+            match self.palette.get(color.palette_index as usize) {
+                Some(&base) => base,
+                None => return TRANSPARENT,
+            }
+        };
+        let alpha = base.a as f32 / 255.0 * color.alpha.clamp(0.0, 1.0);
+        [
+            base.r as f32 / 255.0 * alpha,
+            base.g as f32 / 255.0 * alpha,
+            base.b as f32 / 255.0 * alpha,
+            alpha,
+        ]
+    }
+
+    // Returns the color at position `t` along a color line, interpolating between stops without
+    // premultiplication.
+    fn color_line_at(&self, color_line: &ColorLine, t: f32) -> Pixel {
+        let stops = &color_line.stops;
+        let (first, last) = match (stops.first(), stops.last()) {
+            (Some(first), Some(last)) => (first.offset, last.offset),
+            _ => return TRANSPARENT,
+        };
+
+        let length = last - first;
+        let t = if length <= 0.0 || !t.is_finite() {
+            t
+        } else {
+            match color_line.extend {
+                Extend::Pad => t,
+                Extend::Repeat => first + (t - first).rem_euclid(length),
+                Extend::Reflect => {
+                    let position = (t - first).rem_euclid(2.0 * length);
+                    first
+                        + if position > length {
+                            2.0 * length - position
+                        } else {
+                            position
+                        }
+                }
+            }
+        };
+
+        let unpremultiplied = |color: PaletteColor| {
+            let [r, g, b, a] = self.color(color);
+            if a == 0.0 {
+                TRANSPARENT
+            } else {
+                [r / a, g / a, b / a, a]
+            }
+        };
+        let premultiplied = |[r, g, b, a]: Pixel| [r * a, g * a, b * a, a];
+
+        if t <= first {
+            return self.color(stops[0].color);
+        }
+        for pair in stops.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            if t < end.offset {
+                let fraction = (t - start.offset) / (end.offset - start.offset);
+                let (start, end) = (unpremultiplied(start.color), unpremultiplied(end.color));
+                let mut color = TRANSPARENT;
+                for channel in 0..4 {
+                    color[channel] = start[channel] + (end[channel] - start[channel]) * fraction;
+                }
+                return premultiplied(color);
+            }
+        }
+        self.color(stops[stops.len() - 1].color)
+    }
+
+    // Composites `pixels` over the canvas, returning the rectangle that has ink.
+    fn composite_onto(&self, canvas: &mut Canvas, pixels: &[Pixel]) -> RectI {
+        let width = self.size.x() as usize;
+        let (mut min, mut max) = (self.size, Vector2I::default());
+        for (index, &pixel) in pixels.iter().enumerate() {
+            if pixel[3] <= 0.0 {
+                continue;
+            }
+            let (x, y) = (index % width, index / width);
+            let offset = y * canvas.stride + x * 4;
+            let dest = &mut canvas.pixels[offset..(offset + 4)];
+            let backdrop = [
+                dest[0] as f32 / 255.0,
+                dest[1] as f32 / 255.0,
+                dest[2] as f32 / 255.0,
+                dest[3] as f32 / 255.0,
+            ];
+            let result = source_over(pixel, backdrop);
+            for (dest, value) in dest.iter_mut().zip(result) {
+                *dest = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+            if dest[3] > 0 {
+                min = min.min(Vector2I::new(x as i32, y as i32));
+                max = max.max(Vector2I::new(x as i32 + 1, y as i32 + 1));
+            }
+        }
+        if min.x() >= max.x() {
+            return RectI::default();
+        }
+        RectI::from_points(min, max)
+    }
+}
+
+// Returns the largest `t` for which the point lies on the circle interpolated between the two
+// circles of a two-point conical gradient with a nonnegative radius.
+fn radial_gradient_position(
+    point: Vector2F,
+    c0: Vector2F,
+    r0: f32,
+    c1: Vector2F,
+    r1: f32,
+) -> Option<f32> {
+    let (center_delta, radius_delta, point_delta) = (c1 - c0, r1 - r0, point - c0);
+    let a = center_delta.square_length() - radius_delta * radius_delta;
+    let b = point_delta.dot(center_delta) + r0 * radius_delta;
+    let c = point_delta.square_length() - r0 * r0;
+    if a.abs() < 1e-6 {
+        if b == 0.0 {
+            return None;
+        }
+        let t = c / (2.0 * b);
+        return if r0 + t * radius_delta >= 0.0 {
+            Some(t)
+        } else {
+            None
+        };
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let (t0, t1) = ((b + root) / a, (b - root) / a);
+    let (larger, smaller) = if t0 > t1 { (t0, t1) } else { (t1, t0) };
+    [larger, smaller]
+        .iter()
+        .cloned()
+        .find(|&t| r0 + t * radius_delta >= 0.0)
+}
+
+#[inline]
+fn scale_pixel(pixel: Pixel, factor: f32) -> Pixel {
+    [
+        pixel[0] * factor,
+        pixel[1] * factor,
+        pixel[2] * factor,
+        pixel[3] * factor,
+    ]
+}
+
+#[inline]
+fn source_over(source: Pixel, backdrop: Pixel) -> Pixel {
+    composite(source, backdrop, CompositeMode::SrcOver)
+}
+
+// Combines premultiplied pixels as described in the Compositing and Blending specification.
+fn composite(source: Pixel, backdrop: Pixel, mode: CompositeMode) -> Pixel {
+    use CompositeMode::*;
+
+    let (source_alpha, backdrop_alpha) = (source[3], backdrop[3]);
+    let porter_duff = |source_factor: f32, backdrop_factor: f32| {
+        let mut result = TRANSPARENT;
+        for channel in 0..4 {
+            result[channel] = source[channel] * source_factor + backdrop[channel] * backdrop_factor;
+        }
+        result
+    };
+
+    match mode {
+        Clear => TRANSPARENT,
+        Src => source,
+        Dest => backdrop,
+        SrcOver => porter_duff(1.0, 1.0 - source_alpha),
+        DestOver => porter_duff(1.0 - backdrop_alpha, 1.0),
+        SrcIn => porter_duff(backdrop_alpha, 0.0),
+        DestIn => porter_duff(0.0, source_alpha),
+        SrcOut => porter_duff(1.0 - backdrop_alpha, 0.0),
+        DestOut => porter_duff(0.0, 1.0 - source_alpha),
+        SrcAtop => porter_duff(backdrop_alpha, 1.0 - source_alpha),
+        DestAtop => porter_duff(1.0 - backdrop_alpha, source_alpha),
+        Xor => porter_duff(1.0 - backdrop_alpha, 1.0 - source_alpha),
+        Plus => {
+            let mut result = porter_duff(1.0, 1.0);
+            for value in &mut result {
+                *value = value.min(1.0);
+            }
+            result
+        }
+        _ => {
+            // Blend the unpremultiplied colors, then composite source-over.
+            let unpremultiply = |pixel: Pixel| {
+                if pixel[3] == 0.0 {
+                    [0.0; 3]
+                } else {
+                    [
+                        pixel[0] / pixel[3],
+                        pixel[1] / pixel[3],
+                        pixel[2] / pixel[3],
+                    ]
+                }
+            };
+            let (source_color, backdrop_color) = (unpremultiply(source), unpremultiply(backdrop));
+            let blended = blend(source_color, backdrop_color, mode);
+            let alpha = source_alpha + backdrop_alpha - source_alpha * backdrop_alpha;
+            let mut result = [0.0, 0.0, 0.0, alpha];
+            for channel in 0..3 {
+                result[channel] = source[channel] * (1.0 - backdrop_alpha)
+                    + backdrop[channel] * (1.0 - source_alpha)
+                    + source_alpha * backdrop_alpha * blended[channel];
+            }
+            result
+        }
+    }
+}
+
+fn blend(source: [f32; 3], backdrop: [f32; 3], mode: CompositeMode) -> [f32; 3] {
+    use CompositeMode::*;
+
+    let separable = |function: fn(f32, f32) -> f32| {
+        [
+            function(source[0], backdrop[0]),
+            function(source[1], backdrop[1]),
+            function(source[2], backdrop[2]),
+        ]
+    };
+    match mode {
+        Screen => separable(screen),
+        Overlay => separable(|source, backdrop| hard_light(backdrop, source)),
+        Darken => separable(f32::min),
+        Lighten => separable(f32::max),
+        ColorDodge => separable(|source, backdrop| {
+            if backdrop == 0.0 {
+                0.0
+            } else if source >= 1.0 {
+                1.0
+            } else {
+                (backdrop / (1.0 - source)).min(1.0)
+            }
+        }),
+        ColorBurn => separable(|source, backdrop| {
+            if backdrop >= 1.0 {
+                1.0
+            } else if source == 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - backdrop) / source).min(1.0)
+            }
+        }),
+        HardLight => separable(hard_light),
+        SoftLight => separable(|source, backdrop| {
+            if source <= 0.5 {
+                backdrop - (1.0 - 2.0 * source) * backdrop * (1.0 - backdrop)
+            } else {
+                let d = if backdrop <= 0.25 {
+                    ((16.0 * backdrop - 12.0) * backdrop + 4.0) * backdrop
+                } else {
+                    backdrop.sqrt()
+                };
+                backdrop + (2.0 * source - 1.0) * (d - backdrop)
+            }
+        }),
+        Difference => separable(|source, backdrop| (source - backdrop).abs()),
+        Exclusion => separable(|source, backdrop| source + backdrop - 2.0 * source * backdrop),
+        Multiply => separable(|source, backdrop| source * backdrop),
+        Hue => set_luminosity(
+            set_saturation(source, saturation(backdrop)),
+            luminosity(backdrop),
+        ),
+        Saturation => set_luminosity(
+            set_saturation(backdrop, saturation(source)),
+            luminosity(backdrop),
+        ),
+        Color => set_luminosity(source, luminosity(backdrop)),
+        Luminosity => set_luminosity(backdrop, luminosity(source)),
+        _ => source,
+    }
+}
+
+#[inline]
+fn screen(source: f32, backdrop: f32) -> f32 {
+    source + backdrop - source * backdrop
+}
+
+#[inline]
+fn hard_light(source: f32, backdrop: f32) -> f32 {
+    if source <= 0.5 {
+        backdrop * 2.0 * source
+    } else {
+        screen(backdrop, 2.0 * source - 1.0)
+    }
+}
+
+#[inline]
+fn luminosity(color: [f32; 3]) -> f32 {
+    0.3 * color[0] + 0.59 * color[1] + 0.11 * color[2]
+}
+
+fn set_luminosity(color: [f32; 3], target: f32) -> [f32; 3] {
+    let delta = target - luminosity(color);
+    let color = [color[0] + delta, color[1] + delta, color[2] + delta];
+    let lum = luminosity(color);
+    let min = color[0].min(color[1]).min(color[2]);
+    let max = color[0].max(color[1]).max(color[2]);
+    let mut result = color;
+    for value in &mut result {
+        if min < 0.0 && lum != min {
+            *value = lum + (*value - lum) * lum / (lum - min);
+        }
+        if max > 1.0 && max != lum {
+            *value = lum + (*value - lum) * (1.0 - lum) / (max - lum);
+        }
+    }
+    result
+}
+
+#[inline]
+fn saturation(color: [f32; 3]) -> f32 {
+    color[0].max(color[1]).max(color[2]) - color[0].min(color[1]).min(color[2])
+}
+
+fn set_saturation(color: [f32; 3], target: f32) -> [f32; 3] {
+    let max = color[0].max(color[1]).max(color[2]);
+    let min = color[0].min(color[1]).min(color[2]);
+    if max <= min {
+        return [0.0; 3];
+    }
+    let mut result = [0.0; 3];
+    for (result, &value) in result.iter_mut().zip(color.iter()) {
+        *result = (value - min) * target / (max - min);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::{composite, radial_gradient_position, Pixel};
+    use crate::tables::colr::CompositeMode;
+    use pathfinder_geometry::vector::Vector2F;
+
+    fn assert_pixel_eq(actual: Pixel, expected: Pixel) {
+        for channel in 0..4 {
+            assert!(
+                (actual[channel] - expected[channel]).abs() < 0.001,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn composite_modes() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let half_blue = [0.0, 0.0, 0.5, 0.5];
+        assert_pixel_eq(
+            composite(half_blue, red, CompositeMode::SrcOver),
+            [0.5, 0.0, 0.5, 1.0],
+        );
+        assert_pixel_eq(composite(half_blue, red, CompositeMode::DestOver), red);
+        assert_pixel_eq(composite(half_blue, red, CompositeMode::SrcIn), half_blue);
+        assert_pixel_eq(
+            composite(red, half_blue, CompositeMode::SrcIn),
+            [0.5, 0.0, 0.0, 0.5],
+        );
+        assert_pixel_eq(
+            composite(half_blue, red, CompositeMode::DestOut),
+            [0.5, 0.0, 0.0, 0.5],
+        );
+        assert_pixel_eq(
+            composite(half_blue, red, CompositeMode::Xor),
+            [0.5, 0.0, 0.0, 0.5],
+        );
+        assert_pixel_eq(composite(half_blue, red, CompositeMode::Clear), [0.0; 4]);
+
+        // Multiplying red by blue gives black where both are opaque.
+        let blue = [0.0, 0.0, 1.0, 1.0];
+        assert_pixel_eq(
+            composite(blue, red, CompositeMode::Multiply),
+            [0.0, 0.0, 0.0, 1.0],
+        );
+        assert_pixel_eq(
+            composite(blue, red, CompositeMode::Screen),
+            [1.0, 0.0, 1.0, 1.0],
+        );
+        assert_pixel_eq(
+            composite(blue, red, CompositeMode::Difference),
+            [1.0, 0.0, 1.0, 1.0],
+        );
+        let gray = [0.5, 0.5, 0.5, 1.0];
+        assert_pixel_eq(
+            composite(gray, red, CompositeMode::Luminosity),
+            [1.0, 0.285714, 0.285714, 1.0],
+        );
+    }
+
+    #[test]
+    fn radial_gradient_positions() {
+        // Concentric circles from radius 10 to radius 20.
+        let center = Vector2F::new(0.0, 0.0);
+        let position =
+            |x| radial_gradient_position(Vector2F::new(x, 0.0), center, 10.0, center, 20.0);
+        assert_eq!(position(10.0), Some(0.0));
+        assert_eq!(position(15.0), Some(0.5));
+        assert_eq!(position(30.0), Some(2.0));
+        assert_eq!(position(0.0), Some(-1.0));
+
+        // A cone from a point, which doesn't cover the space behind it.
+        let position = |x| {
+            radial_gradient_position(
+                Vector2F::new(x, 0.0),
+                center,
+                0.0,
+                Vector2F::new(10.0, 0.0),
+                5.0,
+            )
+        };
+        assert_eq!(position(15.0), Some(3.0));
+        assert_eq!(position(-5.0), None);
+    }
+}
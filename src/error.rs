@@ -13,7 +13,10 @@
 use std::borrow::Cow;
 use std::convert::From;
 use std::error::Error;
+use std::fmt::{self, Display, Formatter};
 use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 macro_rules! impl_display {
     ($enum:ident, {$($variant:pat => $fmt_string:expr),+$(,)* }) => {
@@ -39,7 +42,7 @@ pub enum FontLoadingError {
     /// Attempted to load an invalid index in a TrueType or OpenType font collection.
     ///
     /// For example, if a `.ttc` file has 2 fonts in it, and you ask for the 5th one, you'll get
-    /// this error.
+    /// this error. It's also returned for named instances that a variable font doesn't have.
     NoSuchFontInCollection,
     /// Attempted to load a malformed or corrupted font.
     Parse,
@@ -50,7 +53,14 @@ pub enum FontLoadingError {
     Io(io::Error),
 }
 
-impl Error for FontLoadingError {}
+impl Error for FontLoadingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FontLoadingError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
 
 impl_display! { FontLoadingError, {
         UnknownFormat => "unknown format",
@@ -67,32 +77,110 @@ impl From<io::Error> for FontLoadingError {
     }
 }
 
+/// Wraps an I/O error so that its message names the file it happened on.
+///
+/// The returned error keeps the kind of `error`, and `error` itself remains reachable through
+/// `source()`.
+pub(crate) fn io_error_with_path(path: &Path, error: io::Error) -> io::Error {
+    io::Error::new(
+        error.kind(),
+        PathIoError {
+            path: path.to_owned(),
+            error,
+        },
+    )
+}
+
+#[derive(Debug)]
+struct PathIoError {
+    path: PathBuf,
+    error: io::Error,
+}
+
+impl Display for PathIoError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+impl Error for PathIoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 /// Reasons why a font might fail to load a glyph.
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum GlyphLoadingError {
     /// The font didn't contain a glyph with that ID.
     NoSuchGlyph,
-    /// A platform function returned an error.
+    /// The glyph exists but has no vector outline, as in bitmap-only fonts. Rasterize it instead.
+    NoOutline,
+    /// A platform function returned an error without an error code.
     PlatformError,
+    /// The glyph data is malformed; for example, a composite glyph refers to itself or nests
+    /// more deeply than the font allows.
+    Parse,
+    /// A platform function failed with an error code, which `source()` also returns.
+    PlatformErrorCode(PlatformErrorCode),
 }
 
-impl Error for GlyphLoadingError {}
+impl Error for GlyphLoadingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GlyphLoadingError::PlatformErrorCode(code) => Some(code),
+            _ => None,
+        }
+    }
+}
 
 impl_display! { GlyphLoadingError, {
         NoSuchGlyph => "no such glyph",
+        NoOutline => "glyph has no outline",
         PlatformError => "platform error",
+        Parse => "parse error",
+        PlatformErrorCode(code) => format!("platform error: {}", code),
     }
 }
 
 #[cfg(target_family = "windows")]
 impl From<winapi::um::winnt::HRESULT> for GlyphLoadingError {
-    fn from(_err: winapi::um::winnt::HRESULT) -> GlyphLoadingError {
-        GlyphLoadingError::PlatformError
+    fn from(err: winapi::um::winnt::HRESULT) -> GlyphLoadingError {
+        GlyphLoadingError::PlatformErrorCode(PlatformErrorCode(err))
+    }
+}
+
+/// An error code returned by a platform function, such as a Windows `HRESULT`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PlatformErrorCode(pub i32);
+
+impl Error for PlatformErrorCode {}
+
+impl Display for PlatformErrorCode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "error code {:#010x}", self.0)
+    }
+}
+
+/// Reasons why a canvas might fail to be created.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CanvasError {
+    /// The size has a negative dimension.
+    InvalidSize,
+    /// The pixels would take more than `Canvas::MAX_BYTES` bytes, or couldn't be allocated.
+    TooLarge,
+}
+
+impl Error for CanvasError {}
+
+impl_display! { CanvasError, {
+        InvalidSize => "invalid canvas size",
+        TooLarge => "canvas too large",
     }
 }
 
 /// Reasons why a source might fail to look up a font or fonts.
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, Debug)]
 pub enum SelectionError {
     /// No font matching the given query was found.
     NotFound,
@@ -101,12 +189,47 @@ pub enum SelectionError {
         /// Additional diagnostic information may include file name
         reason: Option<Cow<'static, str>>,
     },
+    /// The source was inaccessible because of this I/O error, which `source()` also returns.
+    Io(Arc<io::Error>),
 }
 
-impl Error for SelectionError {}
+impl Error for SelectionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SelectionError::Io(error) => Some(&**error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SelectionError {
+    fn from(error: io::Error) -> SelectionError {
+        SelectionError::Io(Arc::new(error))
+    }
+}
+
+// I/O errors can't be compared, so they're equal if they're of the same kind with the same message.
+impl PartialEq for SelectionError {
+    fn eq(&self, other: &SelectionError) -> bool {
+        match (self, other) {
+            (SelectionError::NotFound, SelectionError::NotFound) => true,
+            (
+                SelectionError::CannotAccessSource { reason },
+                SelectionError::CannotAccessSource {
+                    reason: other_reason,
+                },
+            ) => reason == other_reason,
+            (SelectionError::Io(error), SelectionError::Io(other_error)) => {
+                error.kind() == other_error.kind() && error.to_string() == other_error.to_string()
+            }
+            _ => false,
+        }
+    }
+}
 
 impl_display! { SelectionError, {
         NotFound => "no font found",
-        CannotAccessSource { reason: ref maybe_cow } => maybe_cow.as_deref().unwrap_or("failed to access source")
+        CannotAccessSource { reason: ref maybe_cow } => maybe_cow.as_deref().unwrap_or("failed to access source"),
+        Io(e) => format!("I/O error: {}", e),
     }
 }
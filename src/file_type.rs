@@ -8,7 +8,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-//! The type of a font file: either a single font or a TrueType/OpenType collection.
+//! The type of a font file: either a single font or a TrueType/OpenType collection, and its
+//! format.
+
+use crate::error::FontLoadingError;
+use crate::tables::TableData;
+use crate::utils::SFNT_VERSIONS;
 
 /// The type of a font file: either a single font or a TrueType/OpenType collection.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -18,3 +23,71 @@ pub enum FileType {
     /// The font file represents a collection of fonts (`.ttc`, `.otc`, etc.)
     Collection(u32),
 }
+
+/// The format of a font file, as identified by its first four bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FontFormat {
+    /// A TrueType font (`.ttf`).
+    TrueType,
+    /// An OpenType font with CFF outlines (`.otf`).
+    OpenTypeCff,
+    /// A PostScript Type 1 font wrapped in an sfnt, as once used on the Mac.
+    Type1Sfnt,
+    /// A TrueType or OpenType collection (`.ttc`, `.otc`).
+    Collection,
+    /// A font compressed with WOFF 1.0 (`.woff`).
+    Woff,
+    /// A font compressed with WOFF 2.0 (`.woff2`).
+    Woff2,
+}
+
+/// Identifies the format of font data from its first four bytes.
+///
+/// Only the magic number is checked, so the rest of the data may still fail to load. Returns
+/// `FontLoadingError::UnknownFormat` if the magic number isn't one of the formats above.
+pub fn font_format(data: &[u8]) -> Result<FontFormat, FontLoadingError> {
+    match data.get(0..4).ok_or(FontLoadingError::UnknownFormat)? {
+        [0x00, 0x01, 0x00, 0x00] | b"true" => Ok(FontFormat::TrueType),
+        b"OTTO" => Ok(FontFormat::OpenTypeCff),
+        b"typ1" => Ok(FontFormat::Type1Sfnt),
+        b"ttcf" => Ok(FontFormat::Collection),
+        b"wOFF" => Ok(FontFormat::Woff),
+        b"wOF2" => Ok(FontFormat::Woff2),
+        _ => Err(FontLoadingError::UnknownFormat),
+    }
+}
+
+/// Returns the byte offset of the table directory (the sfnt header) of each font in `data`, in
+/// collection index order.
+///
+/// Some libraries identify a font in a collection by this offset rather than by its index. A
+/// single font has one table directory, at offset 0. Returns `FontLoadingError::UnknownFormat` if
+/// `data` is neither a font nor a collection, and `FontLoadingError::Parse` if the collection
+/// header is truncated or an offset doesn't point at a table directory.
+pub fn collection_face_offsets(data: &[u8]) -> Result<Vec<u32>, FontLoadingError> {
+    let table = TableData(data);
+    let tag = table.bytes(0, 4).ok_or(FontLoadingError::UnknownFormat)?;
+    if SFNT_VERSIONS.iter().any(|version| version == tag) {
+        return Ok(vec![0]);
+    }
+    if tag != b"ttcf" {
+        return Err(FontLoadingError::UnknownFormat);
+    }
+
+    let font_count = table.u32(8).ok_or(FontLoadingError::Parse)? as usize;
+    // Check the length up front so that a bogus count can't cause a huge allocation.
+    if table.bytes(12, font_count.saturating_mul(4)).is_none() {
+        return Err(FontLoadingError::Parse);
+    }
+    (0..font_count)
+        .map(|font_index| {
+            let offset = table.u32(12 + font_index * 4)?;
+            let version = table.bytes(offset as usize, 12)?.get(0..4)?;
+            SFNT_VERSIONS
+                .iter()
+                .any(|known_version| known_version == version)
+                .then_some(offset)
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(FontLoadingError::Parse)
+}
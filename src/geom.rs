@@ -0,0 +1,20 @@
+// font-kit/src/geom.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The geometry types that appear in `font-kit`'s API.
+//!
+//! These are re-exported from the version of `pathfinder_geometry` that `font-kit` was built
+//! against, so code that uses them from here doesn't need its own matching dependency on that
+//! crate.
+
+pub use pathfinder_geometry::line_segment::LineSegment2F;
+pub use pathfinder_geometry::rect::{RectF, RectI};
+pub use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
+pub use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector2I};
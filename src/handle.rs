@@ -15,10 +15,15 @@
 //! To open the font referenced by a handle, use a loader.
 
 use std::any::Any;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Read;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::error::FontLoadingError;
+use crate::file_type::{self, FontFormat};
 use crate::font::Font;
 use crate::loader::Loader;
 
@@ -92,9 +97,63 @@ impl Handle {
             None
         }
     }
+    /// Identifies the format of the font data that this handle refers to, without loading it.
+    ///
+    /// Only the first four bytes are read, so this is cheap enough to triage many fonts, for
+    /// example to find the compressed ones. See `file_type::font_format()` for the details. Native
+    /// fonts have no data to look at, so they return `FontLoadingError::UnknownFormat`.
+    pub fn format(&self) -> Result<FontFormat, FontLoadingError> {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Handle::Path { path, .. } => {
+                let mut magic = Vec::with_capacity(4);
+                File::open(path)
+                    .and_then(|file| file.take(4).read_to_end(&mut magic))
+                    .map_err(|e| crate::error::io_error_with_path(path, e))?;
+                file_type::font_format(&magic)
+            }
+            #[cfg(target_arch = "wasm32")]
+            Handle::Path { .. } => Err(FontLoadingError::NoFilesystem),
+            Handle::Memory { bytes, .. } => file_type::font_format(bytes),
+            Handle::Native { .. } => Err(FontLoadingError::UnknownFormat),
+        }
+    }
+
     /// A convenience method to load this handle with the default loader, producing a Font.
     #[inline]
     pub fn load(&self) -> Result<Font, FontLoadingError> {
         Font::from_handle(self)
     }
 }
+
+/// Handles are equal if they refer to the same font. Data in memory is compared by contents, and
+/// native fonts by identity.
+impl PartialEq for Handle {
+    fn eq(&self, other: &Handle) -> bool {
+        match (self, other) {
+            (
+                Handle::Path { path, font_index },
+                Handle::Path {
+                    path: other_path,
+                    font_index: other_font_index,
+                },
+            ) => path == other_path && font_index == other_font_index,
+            (
+                Handle::Memory { bytes, font_index },
+                Handle::Memory {
+                    bytes: other_bytes,
+                    font_index: other_font_index,
+                },
+            ) => {
+                font_index == other_font_index
+                    && (Arc::ptr_eq(bytes, other_bytes) || bytes == other_bytes)
+            }
+            (Handle::Native { inner }, Handle::Native { inner: other_inner }) => {
+                Arc::ptr_eq(inner, other_inner)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Handle {}
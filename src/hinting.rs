@@ -40,6 +40,14 @@ pub enum HintingOptions {
     /// This corresponds to what GDI in non-ClearType modes and FreeType in its normal hinting mode
     /// do.
     Full(f32),
+
+    /// Hinting and antialiasing follow the recommendations in the font's `gasp` table at the
+    /// specified point size, which is also used for grid fitting.
+    ///
+    /// If the font recommends grid fitting at this size, the glyph is hinted as with `Full`;
+    /// otherwise, it isn't hinted. If the font recommends against grayscale rendering, antialiased
+    /// rasterization is replaced with bilevel rasterization. See `Loader::gasp_behavior()`.
+    FontDefault(f32),
 }
 
 impl HintingOptions {
@@ -50,7 +58,52 @@ impl HintingOptions {
             HintingOptions::None => None,
             HintingOptions::Vertical(size)
             | HintingOptions::VerticalSubpixel(size)
-            | HintingOptions::Full(size) => Some(size),
+            | HintingOptions::Full(size)
+            | HintingOptions::FontDefault(size) => Some(size),
+        }
+    }
+
+    // Returns these options with the grid-fitting size multiplied by `factor`.
+    pub(crate) fn scaled(self, factor: f32) -> HintingOptions {
+        match self {
+            HintingOptions::None => HintingOptions::None,
+            HintingOptions::Vertical(size) => HintingOptions::Vertical(size * factor),
+            HintingOptions::VerticalSubpixel(size) => {
+                HintingOptions::VerticalSubpixel(size * factor)
+            }
+            HintingOptions::Full(size) => HintingOptions::Full(size * factor),
+            HintingOptions::FontDefault(size) => HintingOptions::FontDefault(size * factor),
+        }
+    }
+}
+
+/// How a font recommends that its glyphs be rendered at a particular size, from its `gasp` table.
+///
+/// Fonts without a `gasp` table get the default, which is grid fitting with grayscale
+/// antialiasing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GaspBehavior {
+    /// Glyphs should be grid-fitted (hinted).
+    pub grid_fit: bool,
+    /// Glyphs should be rendered with grayscale antialiasing. If false, they should be rendered
+    /// bilevel.
+    pub grayscale: bool,
+    /// Glyphs should be grid-fitted symmetrically, as ClearType does. Only set by version 1
+    /// tables.
+    pub symmetric_grid_fit: bool,
+    /// Glyphs should be smoothed in both directions, as ClearType does. Only set by version 1
+    /// tables.
+    pub symmetric_smoothing: bool,
+}
+
+impl Default for GaspBehavior {
+    #[inline]
+    fn default() -> GaspBehavior {
+        GaspBehavior {
+            grid_fit: true,
+            grayscale: true,
+            symmetric_grid_fit: false,
+            symmetric_smoothing: false,
         }
     }
 }
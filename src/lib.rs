@@ -15,15 +15,8 @@
 //! ## Synopsis
 //!
 //!     # extern crate font_kit;
-//!     # extern crate pathfinder_geometry;
 //!     #
-//!     use font_kit::canvas::{Canvas, Format, RasterizationOptions};
-//!     use font_kit::family_name::FamilyName;
-//!     use font_kit::hinting::HintingOptions;
-//!     use font_kit::properties::Properties;
-//!     use font_kit::source::SystemSource;
-//!     use pathfinder_geometry::transform2d::Transform2F;
-//!     use pathfinder_geometry::vector::{Vector2F, Vector2I};
+//!     use font_kit::prelude::*;
 //!
 //!     let font = SystemSource::new().select_best_match(&[FamilyName::SansSerif],
 //!                                                      &Properties::new())
@@ -40,6 +33,10 @@
 //!                          RasterizationOptions::GrayscaleAa)
 //!         .unwrap();
 //!
+//! The geometry types in the prelude come from [`geom`], which re-exports them from the version of
+//! `pathfinder_geometry` that `font-kit` is built against. Using them from there avoids needing a
+//! matching dependency on that crate.
+//!
 //! ## Backends
 //!
 //! `font-kit` delegates to system libraries to perform tasks. It has two types of backends: a
@@ -81,6 +78,12 @@
 //! `source-fontconfig-default` Cargo features respectively. Beware that
 //! `source-fontconfig-default` is rarely what you want on those two platforms!
 //!
+//! The native Core Text and DirectWrite backends are controlled by the default `coretext` and
+//! `directwrite` Cargo features, which gate both the loader and the source along with their
+//! bindings. Turning one off on its platform makes FreeType the default loader, which then has to
+//! be built with the `loader-freetype` (or `freetype`) feature, and makes the filesystem source
+//! the system source.
+//!
 //! ## Features
 //!
 //! `font-kit` is capable of doing the following:
@@ -124,19 +127,23 @@
 #[macro_use]
 extern crate bitflags;
 
+mod cache;
 pub mod canvas;
+pub mod color;
 pub mod error;
 pub mod family;
 pub mod family_handle;
 pub mod family_name;
 pub mod file_type;
 pub mod font;
+pub mod geom;
 pub mod handle;
 pub mod hinting;
 pub mod loader;
 pub mod loaders;
 pub mod metrics;
 pub mod outline;
+pub mod prelude;
 pub mod properties;
 
 #[cfg(feature = "source")]
@@ -145,4 +152,5 @@ pub mod source;
 pub mod sources;
 
 pub mod matching;
+mod tables;
 mod utils;
@@ -14,25 +14,62 @@
 use log::warn;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::Vector2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use std::cell::OnceCell;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Read, Seek};
+use std::mem;
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::SystemTime;
 
-use crate::canvas::{Canvas, RasterizationOptions};
+use crate::canvas::{
+    AlphaMode, Canvas, Format, PlacedGlyph, RasterizationOptions, RasterizedGlyph, RenderSettings,
+    RenderedGlyph, SubpixelGeometry,
+};
+use crate::color::{self, ColorLayer, ColorU, PaletteSelector};
 use crate::error::{FontLoadingError, GlyphLoadingError};
-use crate::file_type::FileType;
+use crate::file_type::{self, FileType};
 use crate::handle::Handle;
-use crate::hinting::HintingOptions;
-use crate::metrics::Metrics;
-use crate::outline::OutlineSink;
-use crate::properties::Properties;
+use crate::hinting::{GaspBehavior, HintingOptions};
+use crate::metrics::{CaretMetrics, Metrics, MetricsSource, ScriptMetrics};
+use crate::outline::{
+    Component, Contour, EmptinessSink, ImpliedPoints, IndexedOutline, Outline, OutlineBoundsSink,
+    OutlineSink, ScaledOutlineSink, TransformedOutlineSink,
+};
+use crate::properties::{Properties, StatAxisValue, Stretch, Weight};
+use crate::tables;
+use crate::utils;
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
-#[cfg(not(target_arch = "wasm32"))]
 use std::path::Path;
 
+// The proportions of the placeholder box drawn by `synthesize_notdef()`, in ems.
+const NOTDEF_ADVANCE: f32 = 0.5;
+const NOTDEF_SIDE_BEARING: f32 = 0.05;
+const NOTDEF_STROKE: f32 = 0.05;
+// The height of the box for fonts with no usable ascent.
+const NOTDEF_DEFAULT_HEIGHT: f32 = 0.8;
+
 /// Provides a common interface to the platform-specific API that loads, parses, and rasterizes
 /// fonts.
+///
+/// # Native handles
+///
+/// Native fonts are reference counted, and a loader owns exactly one reference to the native font
+/// it wraps:
+///
+/// * `from_native_font()` takes ownership of one reference to the handle it is given, which the
+///   loader releases when it is dropped. The caller must not release that reference itself: Core
+///   Text and DirectWrite handles that release themselves on drop must be passed to
+///   `mem::forget()`, and FreeType faces must not be passed to `FT_Done_Face()`.
+/// * `native_font()` returns a new reference, which the caller owns. Core Text and DirectWrite
+///   handles release it when they are dropped; FreeType faces must be released with
+///   `FT_Done_Face()`.
+/// * Cloning a loader retains another reference to the same native font, and dropping a loader
+///   releases exactly the reference that it holds.
 pub trait Loader: Clone + Sized {
     /// The handle that the API natively uses to represent a font.
     type NativeFont: 'static;
@@ -41,8 +78,33 @@ pub trait Loader: Clone + Sized {
     ///
     /// If the data represents a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index
     /// of the font to load from it. If the data represents a single font, pass 0 for `font_index`.
+    ///
+    /// `font_index` means the same with every loader: it selects a member of a collection and
+    /// nothing else. It never selects a named instance of a variable font, as the upper 16 bits of
+    /// a FreeType face index do, or DirectWrite's simulated bold and oblique faces. Use
+    /// `with_named_instance()` for named instances. Indices past the end of a collection, nonzero
+    /// indices of single fonts, and indices of 0x10000 and up return
+    /// `FontLoadingError::NoSuchFontInCollection`.
+    ///
+    /// Loaders keep a reference to `font_data` instead of copying it wherever the platform allows,
+    /// and return that same buffer from `copy_font_data()`, so fonts loaded from
+    /// `Handle::Memory` share their bytes with the handle.
     fn from_bytes(font_data: Arc<Vec<u8>>, font_index: u32) -> Result<Self, FontLoadingError>;
 
+    /// Loads a font from a seekable stream, such as an entry in an archive, by reading all of it
+    /// into memory.
+    ///
+    /// The stream is rewound first, so its position on entry does not matter. If the stream holds
+    /// a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the font to load
+    /// from it. If it holds a single font, pass 0 for `font_index`.
+    fn from_stream<R>(stream: &mut R, font_index: u32) -> Result<Self, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        let font_data = Arc::new(utils::slurp_stream(stream)?);
+        Self::from_bytes(font_data, font_index)
+    }
+
     /// Loads a font from a `.ttf`/`.otf`/etc. file.
     ///
     /// If the file is a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the
@@ -59,10 +121,20 @@ pub trait Loader: Clone + Sized {
     where
         P: AsRef<Path>,
     {
-        Loader::from_file(&mut File::open(path)?, font_index)
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|e| crate::error::io_error_with_path(path, e))?;
+        Loader::from_file(&mut file, font_index)
     }
 
     /// Creates a font from a native API handle.
+    ///
+    /// The font takes ownership of one reference to `native_font`; see the
+    /// [trait documentation](Loader#native-handles).
+    ///
+    /// # Safety
+    ///
+    /// `native_font` must be a valid handle for the native API backing this loader, and the caller
+    /// must give up one reference to it.
     unsafe fn from_native_font(native_font: &Self::NativeFont) -> Self;
 
     /// Loads the font pointed to by a handle.
@@ -75,7 +147,10 @@ pub trait Loader: Clone + Sized {
             Handle::Path { .. } => Err(FontLoadingError::NoFilesystem),
             Handle::Native { .. } => {
                 if let Some(native) = handle.native_as::<Self::NativeFont>() {
-                    unsafe { Ok(Self::from_native_font(native)) }
+                    // The font takes over the handle's reference, so give the handle a new one.
+                    let font = unsafe { Self::from_native_font(native) };
+                    mem::forget(font.native_font());
+                    Ok(font)
                 } else {
                     Err(FontLoadingError::UnknownFormat)
                 }
@@ -83,10 +158,108 @@ pub trait Loader: Clone + Sized {
         }
     }
 
+    /// Enables caching of glyph advances, returning the modified font.
+    ///
+    /// Advances never change for a given glyph, so with the cache enabled each glyph's advance is
+    /// fetched from the native font at most once. This speeds up text measurement loops that
+    /// revisit the same glyphs, at the cost of memory proportional to the number of distinct
+    /// glyphs measured. The cache is off by default. Loaders without a cache return the font
+    /// unchanged.
+    fn with_advance_cache(self) -> Self {
+        self
+    }
+
+    /// Returns a copy of this font instanced at the given variation axis values.
+    ///
+    /// `variations` holds `(tag, value)` pairs in user units, with each four-letter axis tag
+    /// packed big-endian into a `u32`, as in `Selection::variations`. Outlines, metrics, advances,
+    /// and rasterization of the returned font all reflect the variations. Axes that aren't listed
+    /// keep their default values, values are clamped to each axis's range, and tags that the font
+    /// has no axis for are ignored. Fonts without variation axes are returned unchanged.
+    ///
+    /// The values are handed to the backend in user units, so it applies the font's `avar`
+    /// mapping the same way `normalize_variation()` does.
+    ///
+    /// The default implementation is for loaders that can't instance fonts: it returns a copy of
+    /// the font if `variations` is empty or the font has no variation axes, and
+    /// `FontLoadingError::UnknownFormat` otherwise.
+    fn with_variations(&self, variations: &[(u32, f32)]) -> Result<Self, FontLoadingError> {
+        if variations.is_empty() || resolve_variations(self, variations).is_none() {
+            return Ok(self.clone());
+        }
+        Err(FontLoadingError::UnknownFormat)
+    }
+
+    /// Returns a copy of this font instanced at the named instance `instance_index` of its `fvar`
+    /// table, such as "Bold" or "Condensed Light".
+    ///
+    /// This is the way to select named instances: the `font_index` that fonts are loaded with only
+    /// ever selects a member of a collection, unlike a FreeType face index, whose upper 16 bits
+    /// select a named instance. Returns `FontLoadingError::NoSuchFontInCollection` if the font has
+    /// no such instance.
+    fn with_named_instance(&self, instance_index: u16) -> Result<Self, FontLoadingError> {
+        let variations = self
+            .load_font_table(tables::fvar::TAG)
+            .and_then(|table| tables::fvar::instance_coordinates(&table, instance_index))
+            .ok_or(FontLoadingError::NoSuchFontInCollection)?;
+        self.with_variations(&variations)
+    }
+
+    /// Returns the current value of every variation axis, in the order that the font lists its
+    /// axes, both in user units and normalized.
+    ///
+    /// Axes are at their defaults unless the font was instanced with `with_variations()`. Fonts
+    /// without variation axes return an empty list.
+    ///
+    /// The default implementation is for loaders that can't instance fonts: it reports every axis
+    /// at its default value.
+    fn variation_coordinates(&self) -> Vec<VariationCoordinate> {
+        variation_coordinates(self, &[])
+    }
+
+    /// Converts a value of the variation axis `tag` from user units to the normalized coordinate
+    /// that the font's variation data is interpolated with.
+    ///
+    /// The value is clamped to the axis range and mapped linearly to -1..1, with 0 at the axis
+    /// default, then through the axis's `avar` segment map if the font has one, and finally
+    /// rounded to 2.14 fixed point as the rasterizers do. Axes that the font doesn't have give 0.
+    fn normalize_variation(&self, tag: u32, user_value: f32) -> f32 {
+        match variation_axis(self, tag) {
+            Some((axis, segment_map)) => {
+                tables::avar::normalize(&axis, segment_map.as_ref(), user_value)
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Converts a normalized coordinate of the variation axis `tag` back to user units, undoing
+    /// `normalize_variation()`.
+    ///
+    /// Returns `None` if the font doesn't have the axis.
+    fn denormalize_variation(&self, tag: u32, normalized_value: f32) -> Option<f32> {
+        let (axis, segment_map) = variation_axis(self, tag)?;
+        Some(tables::avar::denormalize(
+            &axis,
+            segment_map.as_ref(),
+            normalized_value,
+        ))
+    }
+
     /// Determines whether a blob of raw font data represents a supported font, and, if so, what
     /// type of font it is.
     fn analyze_bytes(font_data: Arc<Vec<u8>>) -> Result<FileType, FontLoadingError>;
 
+    /// Determines whether a seekable stream holds a supported font, and, if so, what type of font
+    /// it is.
+    ///
+    /// The stream is rewound first and read into memory in its entirety.
+    fn analyze_stream<R>(stream: &mut R) -> Result<FileType, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        Self::analyze_bytes(Arc::new(utils::slurp_stream(stream)?))
+    }
+
     /// Determines whether a file represents a supported font, and, if so, what type of font it is.
     #[cfg(not(target_arch = "wasm32"))]
     fn analyze_file(file: &mut File) -> Result<FileType, FontLoadingError>;
@@ -98,10 +271,12 @@ pub trait Loader: Clone + Sized {
     where
         P: AsRef<Path>,
     {
-        <Self as Loader>::analyze_file(&mut File::open(path)?)
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|e| crate::error::io_error_with_path(path, e))?;
+        <Self as Loader>::analyze_file(&mut file)
     }
 
-    /// Returns the wrapped native font handle.
+    /// Returns a new reference to the wrapped native font handle, which the caller owns.
     fn native_font(&self) -> Self::NativeFont;
 
     /// Returns the PostScript name of the font. This should be globally unique.
@@ -114,9 +289,40 @@ pub trait Loader: Clone + Sized {
     fn family_name(&self) -> String;
 
     /// Returns true if and only if the font is monospace (fixed-width).
-    fn is_monospace(&self) -> bool;
+    ///
+    /// Every loader trusts the `isFixedPitch` field of the `post` table, which is cheap to read.
+    /// Fonts without one, such as bitmap fonts, are judged by the platform's own flag. To check
+    /// the font's advances as well, use `pitch_info()` and `PitchInfo::is_monospace()`.
+    #[inline]
+    fn is_monospace(&self) -> bool {
+        post_fixed_pitch(self).unwrap_or(false)
+    }
+
+    /// Returns the raw signals from which a font can be judged monospace, for callers that want
+    /// to apply their own judgment. See `PitchInfo::is_monospace()`.
+    ///
+    /// The advances of the glyphs for the ASCII letters are measured; letters that the font
+    /// doesn't map are skipped. This looks up about fifty glyphs, so loaders compute it once per
+    /// font.
+    fn pitch_info(&self) -> PitchInfo {
+        measure_pitch_info(self)
+    }
+
+    /// Returns the string with the given name ID from the font's `name` table, such as the
+    /// version string (5), manufacturer (8), designer (9), or unique ID (3).
+    ///
+    /// The string is in the language of the system locale if the font has one in that language,
+    /// and otherwise preferably in English. Returns `None` if the font has no such string.
+    fn name_string(&self, name_id: u16) -> Option<String> {
+        let table = self.load_font_table(tables::name::TAG)?;
+        tables::name::lookup_for_language(&table, name_id, utils::system_language().as_deref())
+    }
 
     /// Returns the values of various font properties, corresponding to those defined in CSS.
+    ///
+    /// For variable fonts, the weight and stretch come from the current values of the `wght` and
+    /// `wdth` axes if the font has been varied, or if its `OS/2` table is missing or has no usable
+    /// weight or width class.
     fn properties(&self) -> Properties;
 
     /// Returns the number of glyphs in the font.
@@ -131,6 +337,49 @@ pub trait Loader: Clone + Sized {
     /// use cases like "what does character X look like on its own".
     fn glyph_for_char(&self, character: char) -> Option<u32>;
 
+    /// Returns the usual glyph ID for each Unicode scalar value in `text`, in order, as
+    /// `glyph_for_char()` would.
+    ///
+    /// This is the usual starting point for drawing unshaped text; the same caveats as for
+    /// `glyph_for_char()` apply. Loaders whose native API can map many characters in one call do
+    /// so here.
+    fn glyphs_for_str(&self, text: &str) -> Vec<Option<u32>> {
+        text.chars()
+            .map(|character| self.glyph_for_char(character))
+            .collect()
+    }
+
+    /// Returns every Unicode code point that the font maps to a glyph, paired with that glyph's
+    /// ID and sorted by code point.
+    ///
+    /// This reads the `cmap` table in one pass, which is much faster than calling
+    /// `glyph_for_char()` on each character when building a reverse map or a coverage report.
+    /// Code points mapped to `.notdef` are omitted; surrogate code points are kept. Ranges that the
+    /// subtable lists out of order are sorted, and where they overlap, the one listed first wins.
+    /// Fonts without a Unicode `cmap` subtable in format 4, 6, 12, or 13, or whose subtable is
+    /// truncated, return an empty list.
+    fn char_to_glyph_mappings(&self) -> Vec<(u32, u32)> {
+        self.load_font_table(tables::cmap::TAG)
+            .and_then(|cmap| tables::cmap::mappings(&cmap))
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over every character that the font maps to a glyph, paired with that
+    /// glyph's ID, in ascending order.
+    ///
+    /// This is the lazy form of `char_to_glyph_mappings()`: the `cmap` table is loaded once and
+    /// then walked as the iterator advances, so fonts whose format 12 or 13 subtables map huge
+    /// ranges of characters can be visited without building the whole list. Surrogates and
+    /// characters mapped to `.notdef` are skipped.
+    ///
+    /// Unlike `char_to_glyph_mappings()`, this relies on the subtable listing its ranges in
+    /// ascending order, as the specification requires: a range that goes backward only contributes
+    /// the characters past those already returned, and a truncated subtable ends the iteration
+    /// after the mappings read so far.
+    fn char_mappings(&self) -> CharMappingIter {
+        CharMappingIter::new(self.load_font_table(tables::cmap::TAG))
+    }
+
     /// Returns the glyph ID for the specified glyph name.
     #[inline]
     fn glyph_by_name(&self, _name: &str) -> Option<u32> {
@@ -140,6 +389,9 @@ pub trait Loader: Clone + Sized {
 
     /// Sends the vector path for a glyph to a sink.
     ///
+    /// The path is in font units, with y pointing up. To draw it where `rasterize_glyph()` puts
+    /// the glyph, use `outline_at_size()`.
+    ///
     /// If `hinting_mode` is not None, this function performs grid-fitting as requested before
     /// sending the hinding outlines to the builder.
     ///
@@ -153,10 +405,295 @@ pub trait Loader: Clone + Sized {
     where
         S: OutlineSink;
 
+    /// Sends the vector path for a glyph to a sink, in the pixel coordinates that
+    /// `rasterize_glyph()` draws it in, so that overlays line up with the rasterized glyph.
+    ///
+    /// Rasterizing with `point_size` and `Transform2F::from_translation(origin)` covers exactly
+    /// this path. A point `p` of `outline()`, in font units with y up, lands at
+    /// `origin + vec2f(p.x(), -p.y()) * point_size / units_per_em`, in canvas pixels with y
+    /// down.
+    ///
+    /// The rasterizer grid-fits at `point_size` whatever size `hinting` names, so this does too.
+    /// `FontDefault` still looks its size up in the `gasp` table. The hinting matches grayscale
+    /// rasterization; loaders that hint bilevel or subpixel glyphs differently may diverge from
+    /// those by a fraction of a pixel.
+    fn outline_at_size<S>(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        origin: Vector2F,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        let (hinting, _) =
+            resolve_font_default_hinting(self, hinting, RasterizationOptions::GrayscaleAa);
+        let hinting = match hinting.grid_fitting_size() {
+            Some(size) if size > 0.0 => hinting.scaled(point_size / size),
+            _ => hinting,
+        };
+        let scale = point_size / self.metrics().units_per_em as f32;
+        let transform = Transform2F::from_translation(origin)
+            * Transform2F::from_scale(Vector2F::new(scale, -scale));
+        self.outline(
+            glyph_id,
+            hinting,
+            &mut TransformedOutlineSink::new(sink, transform),
+        )
+    }
+
+    /// Returns the font revision from the `head` table, such as 2.001.
+    ///
+    /// This is set by the font's manufacturer and is useful for telling builds of a font apart.
+    fn font_revision(&self) -> Option<f32> {
+        tables::head::font_revision(&self.load_font_table(tables::head::TAG)?)
+    }
+
+    /// Returns the date the font was created, from the `head` table.
+    ///
+    /// Returns `None` if the date is unset or implausible.
+    fn created_date(&self) -> Option<SystemTime> {
+        tables::head::created(&self.load_font_table(tables::head::TAG)?)
+    }
+
+    /// Returns the date the font was last modified, from the `head` table.
+    ///
+    /// This is a convenient key for invalidating caches derived from the font. Returns `None` if
+    /// the date is unset or implausible.
+    fn modified_date(&self) -> Option<SystemTime> {
+        tables::head::modified(&self.load_font_table(tables::head::TAG)?)
+    }
+
+    /// Returns the components of a TrueType composite glyph, each with the transform from the
+    /// `glyf` table that places it, without flattening them into one outline.
+    ///
+    /// This is intended for font editing and subsetting tools. Simple glyphs, glyphs in fonts
+    /// without a `glyf` table (such as CFF-based fonts), and glyphs that don't exist return `None`.
+    fn composite_components(&self, glyph_id: u32) -> Option<Vec<Component>> {
+        let (glyf, glyph_range) = load_glyf_glyph(self, glyph_id)?;
+        tables::glyf::components(glyf.get(glyph_range)?)
+    }
+
+    /// Returns the contours of a simple TrueType glyph as stored in the `glyf` table, in font
+    /// units with the origin at the bottom left.
+    ///
+    /// On-curve points have no flags, and off-curve points are marked `CONTROL_POINT_0`. Each
+    /// contour is closed by joining its last point to its first. Two off-curve points may be
+    /// adjacent, with the on-curve point between them left implied, unless `implied_points` is
+    /// `ImpliedPoints::Explicit`.
+    ///
+    /// Composite glyphs (see `composite_components()` and `glyf_outline()`), glyphs in fonts
+    /// without a `glyf` table, and glyphs that don't exist return `None`.
+    fn glyf_contours(&self, glyph_id: u32, implied_points: ImpliedPoints) -> Option<Outline> {
+        let (glyf, glyph_range) = load_glyf_glyph(self, glyph_id)?;
+        let contours = tables::glyf::simple_contours(glyf.get(glyph_range)?)?;
+        Some(glyf_outline_from_contours(contours, implied_points))
+    }
+
+    /// Returns the contours of a simple TrueType glyph like `glyf_contours()`, along with the
+    /// index of each point in the `glyf` point ordering that hinting instructions refer to.
+    ///
+    /// The indices don't include the four phantom points that follow the glyph's own points.
+    /// Returns `None` in the same cases as `glyf_contours()`, including for CFF-based fonts,
+    /// which have no point numbering.
+    fn glyf_contours_with_point_indices(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Option<IndexedOutline> {
+        let (glyf, glyph_range) = load_glyf_glyph(self, glyph_id)?;
+        let contours = tables::glyf::simple_contours(glyf.get(glyph_range)?)?;
+        let point_indices =
+            tables::glyf::point_indices(&contours, implied_points == ImpliedPoints::Explicit);
+        Some(IndexedOutline {
+            outline: glyf_outline_from_contours(contours, implied_points),
+            point_indices,
+        })
+    }
+
+    /// Returns the outline of a TrueType glyph from the `glyf` table, flattening composite glyphs
+    /// by transforming each component into place, in font units with the origin at the bottom
+    /// left.
+    ///
+    /// This reads the font data directly, so every loader returns the same unhinted outline. The
+    /// points are laid out as in `glyf_contours()`. Component transforms are applied exactly,
+    /// without rounding to whole font units, and composites may nest as deeply as the `maxp` table
+    /// allows.
+    ///
+    /// Fonts without a `glyf` table (such as CFF-based fonts) return
+    /// `GlyphLoadingError::NoOutline`. Composite glyphs that refer to themselves, nest too deeply,
+    /// or are otherwise malformed return `GlyphLoadingError::Parse`.
+    fn glyf_outline(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Result<Outline, GlyphLoadingError> {
+        let glyf = self
+            .load_font_table(tables::glyf::TAG)
+            .ok_or(GlyphLoadingError::NoOutline)?;
+        let loca = self
+            .load_font_table(tables::loca::TAG)
+            .ok_or(GlyphLoadingError::Parse)?;
+        let long_offsets = self
+            .load_font_table(tables::head::TAG)
+            .and_then(|head| tables::head::has_long_loca_offsets(&head))
+            .ok_or(GlyphLoadingError::Parse)?;
+        let max_depth = self
+            .load_font_table(tables::maxp::TAG)
+            .and_then(|maxp| tables::maxp::max_component_depth(&maxp))
+            .filter(|&depth| depth > 0)
+            .map_or(tables::glyf::MAX_COMPONENT_DEPTH, |depth| {
+                depth.min(tables::glyf::MAX_COMPONENT_DEPTH)
+            });
+        let contours = tables::glyf::flattened_contours(glyph_id, max_depth, |glyph_id| {
+            glyf.get(tables::loca::glyph_range(&loca, long_offsets, glyph_id)?)
+        })?;
+        Ok(glyf_outline_from_contours(contours, implied_points))
+    }
+
     /// Returns the boundaries of a glyph in font units. The origin of the coordinate
     /// space is at the bottom left.
     fn typographic_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError>;
 
+    /// Returns the exact bounding box of the glyph outline in font units.
+    ///
+    /// Unlike `typographic_bounds()`, which comes from the font's metrics tables, this walks the
+    /// unhinted outline and takes curve extrema into account, so it tightly encloses the ink
+    /// (including overshoots). Glyphs without an outline return an empty rectangle.
+    fn outline_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        let mut sink = OutlineBoundsSink::new();
+        self.outline(glyph_id, HintingOptions::None, &mut sink)?;
+        Ok(sink.into_bounds())
+    }
+
+    /// Returns true if the glyph has no contours, as for the space, so that `outline()` would send
+    /// nothing to its sink.
+    ///
+    /// This is meant for skipping whitespace-like glyphs during layout, so the loaders answer it
+    /// without walking the outline where they can, from the glyph's contour count, its `glyf`
+    /// entry, or a platform query. The default implementation walks the outline. Fonts without
+    /// outlines return `GlyphLoadingError::NoOutline`, as `outline()` does.
+    fn glyph_is_empty(&self, glyph_id: u32) -> Result<bool, GlyphLoadingError> {
+        outline_is_empty(self, glyph_id)
+    }
+
+    /// Returns the tight bounding box of the ink of `text` set on a single line at `point_size`,
+    /// in points.
+    ///
+    /// Glyphs are placed one after another by their advances, starting with the origin of the
+    /// first at (0, 0), and their outline bounds are unioned. As with `outline_bounds()`, the y
+    /// axis points up. If `apply_kerning` is true, the adjustments from `pair_kerning()` are added
+    /// between adjacent glyphs. Characters that the font has no glyph for are skipped. Strings
+    /// without ink return an empty rectangle.
+    ///
+    /// This does no shaping, so it is only exact for scripts that don't need it.
+    fn string_ink_bounds(&self, text: &str, point_size: f32, apply_kerning: bool) -> RectF {
+        let scale = self.metrics().scale_for(point_size);
+        let kerning_tables =
+            (apply_kerning && self.has_kerning()).then(|| KerningTables::new(self));
+        let (mut bounds, mut pen, mut previous_glyph_id): (Option<RectF>, f32, Option<u32>) =
+            (None, 0.0, None);
+        for glyph_id in text
+            .chars()
+            .filter_map(|character| self.glyph_for_char(character))
+        {
+            if let (Some(kerning_tables), Some(previous_glyph_id)) =
+                (&kerning_tables, previous_glyph_id)
+            {
+                pen += kerning_tables
+                    .pair_kerning(previous_glyph_id, glyph_id)
+                    .unwrap_or(0.0);
+            }
+            if let Ok(glyph_bounds) = self.outline_bounds(glyph_id) {
+                if glyph_bounds.width() > 0.0 || glyph_bounds.height() > 0.0 {
+                    let glyph_bounds = glyph_bounds + Vector2F::new(pen, 0.0);
+                    bounds = Some(match bounds {
+                        Some(bounds) => bounds.union_rect(glyph_bounds),
+                        None => glyph_bounds,
+                    });
+                }
+            }
+            pen += self
+                .advance(glyph_id)
+                .map(|advance| advance.x())
+                .unwrap_or(0.0);
+            previous_glyph_id = Some(glyph_id);
+        }
+        scale.to_px_rect(bounds.unwrap_or_default())
+    }
+
+    /// Returns the ID of the `.notdef` glyph, which is always 0.
+    ///
+    /// This is the glyph to draw, usually a box ("tofu"), for characters that no font supports.
+    /// It is rasterized and outlined like any other glyph, so passing it to `rasterize_glyph()`
+    /// renders the font's own missing-glyph indicator.
+    #[inline]
+    fn notdef_glyph(&self) -> u32 {
+        0
+    }
+
+    /// Returns true if the `.notdef` glyph, which is drawn for characters that no font supports,
+    /// has no ink.
+    ///
+    /// Some fonts ship an empty `.notdef`, which makes missing text invisible. In that case, draw
+    /// the box from `synthesize_notdef()` instead.
+    fn notdef_is_blank(&self) -> bool {
+        let notdef = self.notdef_glyph();
+        let bounds = match self.outline_bounds(notdef) {
+            Ok(bounds) => bounds,
+            Err(GlyphLoadingError::NoOutline) => match self.typographic_bounds(notdef) {
+                Ok(bounds) => bounds,
+                Err(_) => return true,
+            },
+            Err(_) => return true,
+        };
+        bounds.width() <= 0.0 || bounds.height() <= 0.0
+    }
+
+    /// Sends the outline of a conventional missing-glyph placeholder, a hollow box, to a path
+    /// builder, and returns the box's advance.
+    ///
+    /// The box sits between the baseline and the ascender, with an advance of half an em, side
+    /// bearings of 1/20 em, and strokes 1/20 em thick. Like `outline()`, the y axis points up with
+    /// the origin on the baseline, but coordinates are scaled to `point_size` rather than being in
+    /// font units.
+    fn synthesize_notdef<S>(&self, point_size: f32, sink: &mut S) -> Vector2F
+    where
+        S: OutlineSink,
+    {
+        let metrics = self.metrics();
+        let height = if metrics.ascent > 0.0 && metrics.units_per_em > 0 {
+            metrics.ascent_px(point_size)
+        } else {
+            point_size * NOTDEF_DEFAULT_HEIGHT
+        };
+        let advance = point_size * NOTDEF_ADVANCE;
+        let side_bearing = point_size * NOTDEF_SIDE_BEARING;
+        let stroke = point_size * NOTDEF_STROKE;
+
+        // The outer contour runs clockwise and the inner one counterclockwise, so that the inside
+        // of the box is unfilled under both the nonzero and even-odd rules.
+        let outer = RectF::from_points(
+            Vector2F::new(side_bearing, 0.0),
+            Vector2F::new(advance - side_bearing, height),
+        );
+        let inner = outer.contract(stroke);
+        sink.move_to(outer.origin());
+        sink.line_to(outer.lower_left());
+        sink.line_to(outer.lower_right());
+        sink.line_to(outer.upper_right());
+        sink.close();
+        sink.move_to(inner.origin());
+        sink.line_to(inner.upper_right());
+        sink.line_to(inner.lower_right());
+        sink.line_to(inner.lower_left());
+        sink.close();
+
+        Vector2F::new(advance, 0.0)
+    }
+
     /// Returns the distance from the origin of the glyph with the given ID to the next, in font
     /// units.
     fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError>;
@@ -167,33 +704,261 @@ pub trait Loader: Clone + Sized {
     /// Retrieves various metrics that apply to the entire font.
     fn metrics(&self) -> Metrics;
 
+    /// Returns the font's metrics rescaled to `units_per_em` units per em.
+    ///
+    /// See `Metrics::normalized()`.
+    #[inline]
+    fn metrics_normalized(&self, units_per_em: u32) -> Metrics {
+        self.metrics().normalized(units_per_em)
+    }
+
+    /// Returns the font's metrics, measuring the cap height and x-height from glyphs if the font
+    /// doesn't declare them.
+    ///
+    /// Many fonts leave `sCapHeight` and `sxHeight` in the `OS/2` table at 0, or have a table too
+    /// old to hold them. As browsers do, this then takes the top of the ink of 'H' and 'x',
+    /// falling back to 'O' and 'o'. The returned `MetricsSource` says which heights were measured.
+    /// A height that can't be measured, because the font has none of those glyphs, is left as
+    /// `metrics()` reports it.
+    fn metrics_with_fallbacks(&self) -> (Metrics, MetricsSource) {
+        let mut metrics = self.metrics();
+        let (x_height, cap_height) = self
+            .load_font_table(tables::os2::TAG)
+            .and_then(|os2| tables::os2::x_and_cap_heights(&os2))
+            .unwrap_or((0, 0));
+        let mut source = MetricsSource::default();
+        if cap_height <= 0 {
+            if let Some(cap_height) = ink_top(self, &['H', 'O']) {
+                metrics.cap_height = cap_height;
+                source.cap_height_measured = true;
+            }
+        }
+        if x_height <= 0 {
+            if let Some(x_height) = ink_top(self, &['x', 'o']) {
+                metrics.x_height = x_height;
+                source.x_height_measured = true;
+            }
+        }
+        (metrics, source)
+    }
+
+    /// Returns a view of this font that reports its metrics, glyph bounds, advances, kerning, and
+    /// outlines in units of an em `units_per_em` units wide, rather than in the font's own units.
+    ///
+    /// This lets fonts with different em sizes be measured against each other without
+    /// converting every value by hand.
+    #[inline]
+    fn normalized(&self, units_per_em: u32) -> NormalizedFont<'_, Self> {
+        NormalizedFont::new(self, units_per_em)
+    }
+
+    /// Returns the slope and offset of the text cursor for this font.
+    ///
+    /// Italic fonts typically specify a slanted caret. Fonts that don't specify one, as well as
+    /// upright fonts, report a rise of 1 and a run of 0.
+    fn caret_metrics(&self) -> CaretMetrics {
+        self.load_font_table(tables::hhea::TAG)
+            .and_then(|table| tables::hhea::caret_metrics(&table))
+            .unwrap_or_default()
+    }
+
+    /// Returns the sizes and offsets that the font recommends for subscripts and superscripts.
+    ///
+    /// Returns `None` if the font has no `OS/2` table, in which case callers should fall back to
+    /// their own heuristics.
+    fn script_metrics(&self) -> Option<ScriptMetrics> {
+        self.load_font_table(tables::os2::TAG)
+            .and_then(|table| tables::os2::script_metrics(&table))
+    }
+
+    /// Returns the horizontal kerning to add between the glyphs `left` and `right`, in font units.
+    ///
+    /// This reads the pair adjustments of the `kern` feature in the `GPOS` table, regardless of
+    /// script or language. If that has no kerning for the pair, as in AAT fonts, the format 0
+    /// subtables of the `kerx` table and then the classic `kern` table are consulted. Returns
+    /// `None` if the font has no kerning for the pair.
+    fn pair_kerning(&self, left: u32, right: u32) -> Option<f32> {
+        KerningTables::new(self).pair_kerning(left, right)
+    }
+
+    /// Returns true if the font has any kerning data that `pair_kerning()` reads: pair
+    /// adjustments in the `kern` feature of the `GPOS` table, or format 0 pairs in the `kerx` or
+    /// classic `kern` table.
+    ///
+    /// Text layout can skip looking up kerning for every glyph pair of fonts for which this
+    /// returns false. The loaders compute this once per font and cache it.
+    fn has_kerning(&self) -> bool {
+        has_kerning_data(self)
+    }
+
+    /// Reports which OpenType and AAT layout tables the font contains, so that shapers can decide
+    /// how to lay out text with it.
+    fn shaping_tables(&self) -> ShapingTables {
+        let has_table = |code: &[u8; 4]| self.has_font_table(tables::tag(code));
+        ShapingTables {
+            gsub: has_table(b"GSUB"),
+            gpos: has_table(b"GPOS"),
+            morx: has_table(b"morx"),
+            kerx: has_table(b"kerx"),
+            kern: has_table(b"kern"),
+        }
+    }
+
+    /// Returns the size-dependent tracking adjustment from the AAT `trak` table, in font units.
+    ///
+    /// The value for the normal track is interpolated between the sizes listed in the table.
+    /// Callers should add it to the advance of every glyph (after scaling it to the point size, as
+    /// with any other font-unit value) to match native macOS text layout. Fonts without a `trak`
+    /// table return `None`.
+    fn tracking(&self, point_size: f32) -> Option<f32> {
+        let table = self.load_font_table(tables::trak::TAG)?;
+        tables::trak::tracking(&table, point_size)
+    }
+
+    /// Returns the named values on this font's design axes, from the OpenType `STAT` table.
+    ///
+    /// These are the authoritative source of style names for variable fonts: callers can label an
+    /// instance such as "Bold Condensed" by combining the names of the values that match it on
+    /// each axis. Names are resolved from the `name` table, preferring US English. Fonts without a
+    /// `STAT` table return an empty list.
+    fn style_attributes(&self) -> Vec<StatAxisValue> {
+        let mut values = match self.load_font_table(tables::stat::TAG) {
+            Some(table) => tables::stat::axis_values(&table).unwrap_or_default(),
+            None => return vec![],
+        };
+        if let Some(name_table) = self.load_font_table(tables::name::TAG) {
+            for value in &mut values {
+                value.name = tables::name::lookup(&name_table, value.name_id);
+            }
+        }
+        values
+    }
+
     /// Returns a handle to this font, if possible.
     ///
-    /// This is useful if you want to open the font with a different loader.
+    /// This is useful if you want to open the font with a different loader, or to load it again
+    /// later. Fonts loaded from a path or from memory, directly or through a `Handle`, return a
+    /// handle to that same path or data and font index, which fonts derived from them with
+    /// `with_variations()` keep. Other fonts return a handle to a copy of their data, if the
+    /// loader can get at it.
     fn handle(&self) -> Option<Handle> {
         // FIXME(pcwalton): This doesn't handle font collections!
         self.copy_font_data()
             .map(|font_data| Handle::from_memory(font_data, 0))
     }
 
+    /// Returns the path of the file that this font was loaded from, if it was loaded from a path
+    /// or from a `Handle::Path`.
+    fn source_path(&self) -> Option<&Path> {
+        None
+    }
+
     /// Attempts to return the raw font data (contents of the font file).
     ///
     /// If this font is a member of a collection, this function returns the data for the entire
     /// collection.
     fn copy_font_data(&self) -> Option<Arc<Vec<u8>>>;
 
+    /// Returns the byte offset of this font's table directory (the sfnt header) within the data
+    /// that `copy_font_data()` returns.
+    ///
+    /// This is 0 unless the font is a member of a collection. It is useful for passing the font to
+    /// libraries that locate faces by offset rather than by collection index. Returns `None` if
+    /// the font data is unavailable.
+    ///
+    /// The default implementation doesn't know which font of a collection this is, so it returns
+    /// `None` for collections of more than one font.
+    fn face_byte_offset(&self) -> Option<u32> {
+        let font_data = self.copy_font_data()?;
+        match file_type::collection_face_offsets(&font_data).ok()?[..] {
+            [offset] => Some(offset),
+            _ => None,
+        }
+    }
+
     /// Returns true if and only if the font loader can perform hinting in the requested way.
     ///
     /// Some APIs support only rasterizing glyphs with hinting, not retrieving hinted outlines. If
     /// `for_rasterization` is false, this function returns true if and only if the loader supports
     /// retrieval of hinted *outlines*. If `for_rasterization` is true, this function returns true
     /// if and only if the loader supports *rasterizing* hinted glyphs.
+    ///
+    /// Options that this returns false for are accepted but have no effect, so UI can use this to
+    /// decide whether to offer a hinting setting. FreeType supports every option; DirectWrite
+    /// supports only vertical hinting, and only for rasterization; Core Text supports none.
     fn supports_hinting_options(
         &self,
         hinting_options: HintingOptions,
         for_rasterization: bool,
     ) -> bool;
 
+    /// Returns how the font recommends that glyphs be rendered at `pixels_per_em`, from its `gasp`
+    /// table.
+    ///
+    /// Fonts without a `gasp` table, or whose table doesn't cover the size, get the default of
+    /// grid fitting with grayscale antialiasing. `HintingOptions::FontDefault` applies this
+    /// recommendation automatically.
+    fn gasp_behavior(&self, pixels_per_em: u16) -> GaspBehavior {
+        self.load_font_table(tables::gasp::TAG)
+            .and_then(|gasp| tables::gasp::behavior(&gasp, pixels_per_em))
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the font has TrueType hinting instructions: a font program (`fpgm`), a
+    /// control value program (`prep`), or instructions attached to any glyph in the `glyf` table.
+    ///
+    /// Renderers can use this to choose between the font's own hinting and an autohinter. Fonts
+    /// whose instructions have been stripped, as is common for web fonts, return false, as do
+    /// CFF-based fonts, whose hints aren't bytecode.
+    ///
+    /// Fonts without programs have every glyph in the `glyf` table scanned, so the loaders compute
+    /// this once per font and cache it.
+    fn has_hinting_instructions(&self) -> bool {
+        has_hinting_data(self)
+    }
+
+    /// Returns the advance of the glyph in whole pixels at `pixels_per_em` from the font's `hdmx`
+    /// table, which records the advances that hinting produces at the sizes it lists.
+    ///
+    /// Returns `None` if the font has no `hdmx` table or the table doesn't list the size.
+    fn hdmx_advance(&self, glyph_id: u32, pixels_per_em: u8) -> Option<u8> {
+        let glyph_id = checked_glyph_id(self, glyph_id).ok()?;
+        let table = self.load_font_table(tables::hdmx::TAG)?;
+        tables::hdmx::advance(&table, glyph_id, pixels_per_em)
+    }
+
+    /// Returns the maximum and minimum y values, in pixels, of the font's glyphs at
+    /// `pixels_per_em` from its `VDMX` table, for a display with square pixels.
+    ///
+    /// GDI uses these in place of the scaled ascent and descent, which hinting can exceed. The
+    /// minimum is negative below the baseline. Returns `None` if the font has no `VDMX` table or
+    /// the table doesn't list the size.
+    fn vdmx_metrics(&self, pixels_per_em: u16) -> Option<(i16, i16)> {
+        let table = self.load_font_table(tables::vdmx::TAG)?;
+        tables::vdmx::metrics(&table, pixels_per_em)
+    }
+
+    /// Returns the advance of the glyph in whole pixels at `pixels_per_em`, as GDI-compatible
+    /// layout computes it.
+    ///
+    /// This is the `hdmx` advance if the font lists one for the size. Otherwise, the DirectWrite
+    /// loader asks DirectWrite for its GDI-compatible metrics, and other loaders round the scaled
+    /// advance.
+    fn gdi_compatible_advance(
+        &self,
+        glyph_id: u32,
+        pixels_per_em: u16,
+    ) -> Result<f32, GlyphLoadingError> {
+        if let Ok(hdmx_pixels_per_em) = u8::try_from(pixels_per_em) {
+            if let Some(advance) = self.hdmx_advance(glyph_id, hdmx_pixels_per_em) {
+                return Ok(advance as f32);
+            }
+        }
+        let advance = self.advance(glyph_id)?.x();
+        let scale = self.metrics().scale_for(pixels_per_em as f32);
+        Ok(scale.to_px(advance).round())
+    }
+
     /// Returns the pixel boundaries that the glyph will take up when rendered using this loader's
     /// rasterizer at the given `point_size` and `transform`. The origin of the coordinate space is
     /// at the top left.
@@ -205,17 +970,7 @@ pub trait Loader: Clone + Sized {
         _: HintingOptions,
         _: RasterizationOptions,
     ) -> Result<RectI, GlyphLoadingError> {
-        let typographic_bounds = self.typographic_bounds(glyph_id)?;
-        let typographic_raster_bounds =
-            typographic_bounds * (point_size / self.metrics().units_per_em as f32);
-
-        // Translate the origin to "origin is top left" coordinate system.
-        let new_origin = Vector2F::new(
-            typographic_raster_bounds.origin_x(),
-            -typographic_raster_bounds.origin_y() - typographic_raster_bounds.height(),
-        );
-        let typographic_raster_bounds = RectF::new(new_origin, typographic_raster_bounds.size());
-        Ok((transform * typographic_raster_bounds).round_out().to_i32())
+        typographic_raster_bounds(self, glyph_id, point_size, transform)
     }
 
     /// Rasterizes a glyph to a canvas with the given size and transform.
@@ -227,6 +982,11 @@ pub trait Loader: Clone + Sized {
     /// loader.
     ///
     /// If `hinting_options` is not None, the requested grid fitting is performed.
+    ///
+    /// Glyph 0 renders the font's `.notdef` glyph; see `notdef_glyph()`.
+    ///
+    /// On success, returns a description of what was rendered, including the ink rectangle within
+    /// the canvas.
     fn rasterize_glyph(
         &self,
         canvas: &mut Canvas,
@@ -235,15 +995,1442 @@ pub trait Loader: Clone + Sized {
         transform: Transform2F,
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
-    ) -> Result<(), GlyphLoadingError>;
+    ) -> Result<RasterizedGlyph, GlyphLoadingError>;
 
-    /// Get font fallback results for the given text and locale.
+    /// Rasterizes a glyph like `rasterize_glyph()`, choosing whether to draw it from a bitmap
+    /// strike embedded in the font.
     ///
-    /// The `locale` argument is a language tag such as `"en-US"` or `"zh-Hans-CN"`.
-    fn get_fallbacks(&self, text: &str, locale: &str) -> FallbackResult<Self>;
+    /// If `prefer_bitmaps` is true, the glyph is copied from the strike whose size matches the
+    /// requested pixel size, rounded to whole pixels, and its outline is rendered when no strike
+    /// matches. This is how operating systems draw fonts with hand-tuned bitmaps at small sizes.
+    /// If it's false, scalable fonts are always rendered from their outlines. Bitmap-only fonts
+    /// always use their strikes.
+    ///
+    /// The FreeType loader's `rasterize_glyph()` prefers bitmaps. The default implementation is
+    /// for loaders whose rasterizers choose strikes on their own, such as Core Text and
+    /// DirectWrite: it ignores `prefer_bitmaps` and calls `rasterize_glyph()`.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_glyph_with_bitmap_preference(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        _prefer_bitmaps: bool,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
 
-    /// Returns the OpenType font table with the given tag, if the table exists.
-    fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>>;
+    /// Rasterizes a glyph like `rasterize_glyph()`, producing `Rgba32` output with the requested
+    /// alpha representation.
+    ///
+    /// Loaders rasterize to premultiplied alpha; if straight alpha is requested, the touched part
+    /// of the canvas is converted afterward. For `AlphaMode::WhiteCoverage`, the glyph's coverage
+    /// is rasterized into a separate `A8` canvas of the same size and combined with the canvas
+    /// within the glyph's ink rectangle: the alpha channel keeps the larger coverage, so glyphs
+    /// drawn over each other in an atlas or a run all stay visible, and the color channels become
+    /// white. The `premultiplied` field of the result records what was actually produced. For formats
+    /// other than `Rgba32`, `alpha_mode` has no effect.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_glyph_with_alpha_mode(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        alpha_mode: AlphaMode,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        if canvas.format == Format::Rgba32 && alpha_mode == AlphaMode::WhiteCoverage {
+            let mut coverage = Canvas::new(canvas.size, Format::A8);
+            let rasterized_glyph = self.rasterize_glyph(
+                &mut coverage,
+                glyph_id,
+                point_size,
+                transform,
+                hinting_options,
+                rasterization_options,
+            )?;
+            let ink_rect = rasterized_glyph
+                .ink_rect
+                .intersection(RectI::new(Vector2I::default(), canvas.size))
+                .unwrap_or_default();
+            canvas.combine_white_coverage(ink_rect, &coverage);
+            return Ok(RasterizedGlyph {
+                ink_rect,
+                source: rasterized_glyph.source,
+                premultiplied: false,
+            });
+        }
+
+        let mut rasterized_glyph = self.rasterize_glyph(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )?;
+        if canvas.format == Format::Rgba32
+            && alpha_mode == AlphaMode::Straight
+            && rasterized_glyph.premultiplied
+        {
+            canvas.unpremultiply_alpha(rasterized_glyph.ink_rect);
+            rasterized_glyph.premultiplied = false;
+        }
+        Ok(rasterized_glyph)
+    }
+
+    /// Returns the layers of the glyph if it is a version 0 color glyph from the `COLR` table.
+    ///
+    /// Glyphs that aren't color glyphs, including those that only have a version 1 paint graph,
+    /// return `None`.
+    fn color_glyph_layers(&self, glyph_id: u32) -> Option<Vec<ColorLayer>> {
+        if glyph_id > u16::MAX as u32 {
+            return None;
+        }
+        let table = self.load_font_table(tables::colr::TAG)?;
+        tables::colr::base_glyph_layers(&table, glyph_id as u16)
+    }
+
+    /// Returns the colors of the palette at `palette_index` in the `CPAL` table, or `None` if the
+    /// font has no such palette.
+    fn palette(&self, palette_index: u16) -> Option<Vec<ColorU>> {
+        let table = self.load_font_table(tables::cpal::TAG)?;
+        tables::cpal::palette(&table, palette_index)
+    }
+
+    /// Returns the colors that `selector` picks: the chosen `CPAL` palette with the selector's
+    /// overrides applied.
+    ///
+    /// Palette types are only recorded in version 1 `CPAL` tables. If no palette has the requested
+    /// type, or the requested palette doesn't exist, palette 0 is used. Fonts without a `CPAL`
+    /// table start from an empty palette.
+    fn selected_palette(&self, selector: &PaletteSelector) -> Vec<ColorU> {
+        color::selected_palette(self, selector)
+    }
+
+    /// Returns the layers of a version 0 color glyph like `color_glyph_layers()`, each paired with
+    /// the color it is filled with when drawn with the colors that `selector` picks.
+    ///
+    /// Layers using `FOREGROUND_PALETTE_INDEX` get the selector's foreground color, and layers
+    /// using entries past the end of the palette are transparent.
+    fn color_glyph_layers_with_palette(
+        &self,
+        glyph_id: u32,
+        selector: &PaletteSelector,
+    ) -> Option<Vec<(ColorLayer, ColorU)>> {
+        let layers = self.color_glyph_layers(glyph_id)?;
+        let palette = self.selected_palette(selector);
+        Some(
+            layers
+                .into_iter()
+                .map(|layer| {
+                    let color = if layer.palette_index == color::FOREGROUND_PALETTE_INDEX {
+                        selector.foreground
+                    } else {
+                        palette
+                            .get(layer.palette_index as usize)
+                            .cloned()
+                            .unwrap_or_else(ColorU::transparent_black)
+                    };
+                    (layer, color)
+                })
+                .collect(),
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, drawing it in color if the font's `COLR`
+    /// table describes it.
+    ///
+    /// Version 1 paint graphs, with their gradients, transforms, and compositing modes, are
+    /// rendered when present; otherwise the solid layers of version 0 are. Colors come from the
+    /// first `CPAL` palette, and the foreground color is opaque white, matching the coverage that
+    /// `rasterize_glyph()` produces. The glyph is composited over the existing contents of the
+    /// canvas with premultiplied alpha.
+    ///
+    /// Color glyphs are only drawn to `Rgba32` canvases. Other canvases, and glyphs without color
+    /// data, are passed to `rasterize_glyph()`.
+    fn rasterize_color_glyph(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_color_glyph_with_palette(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            &PaletteSelector::default(),
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_color_glyph()`, drawing color glyphs with the colors
+    /// that `selector` picks instead of the first palette and a white foreground.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_color_glyph_with_palette(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        selector: &PaletteSelector,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        let rasterized_glyph = color::rasterize_color_glyph(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            selector,
+        )?;
+        match rasterized_glyph {
+            Some(rasterized_glyph) => Ok(rasterized_glyph),
+            None => self.rasterize_glyph(
+                canvas,
+                glyph_id,
+                point_size,
+                transform,
+                hinting_options,
+                rasterization_options,
+            ),
+        }
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, touching only the pixels inside `clip`.
+    ///
+    /// The part of the glyph that falls within `clip` is rendered to a scratch canvas, and its
+    /// pixels are copied into `canvas`, replacing what was there as `rasterize_glyph()` does.
+    /// Pixels outside `clip` are left untouched, which makes this suitable for redrawing a damaged
+    /// region. The ink rect of the result is restricted to `clip`. Passing `None` is equivalent to
+    /// calling `rasterize_glyph()`.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_glyph_clipped(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        clip: Option<RectI>,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        let clip = match clip {
+            None => {
+                return self.rasterize_glyph(
+                    canvas,
+                    glyph_id,
+                    point_size,
+                    transform,
+                    hinting_options,
+                    rasterization_options,
+                )
+            }
+            Some(clip) => clip,
+        };
+
+        let glyph_bounds = self.raster_bounds(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )?;
+        let region = clip
+            .intersection(RectI::new(Vector2I::default(), canvas.size))
+            .and_then(|region| region.intersection(glyph_bounds))
+            .unwrap_or_else(|| RectI::new(clip.origin(), Vector2I::default()));
+
+        // Render at least one pixel so that loaders always have a valid canvas to draw into.
+        let mut scratch_canvas = Canvas::new(region.size().max(Vector2I::splat(1)), canvas.format);
+        let scratch_transform =
+            Transform2F::from_translation(-region.origin().to_f32()) * transform;
+        let rasterized_glyph = self.rasterize_glyph(
+            &mut scratch_canvas,
+            glyph_id,
+            point_size,
+            scratch_transform,
+            hinting_options,
+            rasterization_options,
+        )?;
+
+        if region.width() == 0 || region.height() == 0 {
+            return Ok(RasterizedGlyph {
+                ink_rect: region,
+                ..rasterized_glyph
+            });
+        }
+        canvas.blit_from(
+            region.origin(),
+            &scratch_canvas.pixels,
+            region.size(),
+            scratch_canvas.stride,
+            scratch_canvas.format,
+        );
+        let ink_rect = RectI::new(
+            rasterized_glyph.ink_rect.origin() + region.origin(),
+            rasterized_glyph.ink_rect.size(),
+        );
+        let ink_rect = ink_rect
+            .intersection(region)
+            .unwrap_or_else(|| RectI::new(region.origin(), Vector2I::default()));
+        Ok(RasterizedGlyph {
+            ink_rect,
+            ..rasterized_glyph
+        })
+    }
+
+    /// Returns the pixel boundaries that the glyph will take up when rendered using this loader's
+    /// rasterizer at the given per-axis `point_size` and `transform`. The origin of the coordinate
+    /// space is at the top left.
+    ///
+    /// This is the non-square counterpart of `raster_bounds()`: the x component of `point_size`
+    /// is the horizontal size and the y component is the vertical size.
+    fn raster_bounds_scaled(
+        &self,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        let (point_size, transform) = anisotropic_point_size_to_transform(point_size, transform);
+        self.raster_bounds(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph to a canvas with the given per-axis size and transform.
+    ///
+    /// The x component of `point_size` is the horizontal size and the y component is the vertical
+    /// size. Unlike scaling the output of `rasterize_glyph()`, this lets the rasterizer hint the
+    /// glyph at its final dimensions where the loader supports it.
+    ///
+    /// By default, the horizontal stretch is folded into `transform` and the glyph is rasterized
+    /// at the vertical size.
+    fn rasterize_glyph_scaled(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        let (point_size, transform) = anisotropic_point_size_to_transform(point_size, transform);
+        self.rasterize_glyph(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the pixel boundaries that the glyph will take up when rendered like
+    /// `rasterize_glyph_with_pixels_per_dip()`.
+    fn raster_bounds_with_pixels_per_dip(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        self.raster_bounds(
+            glyph_id,
+            point_size * pixels_per_dip,
+            transform,
+            hinting_options.scaled(pixels_per_dip),
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph for a display with `pixels_per_dip` physical pixels per
+    /// device-independent pixel, such as 1.5 or 2.0 on a display scaled to 150% or 200%.
+    ///
+    /// `point_size` and the size in `hinting_options` are in device-independent pixels, and the
+    /// glyph is grid-fitted to the physical pixel grid, so that it stays sharp on high-DPI
+    /// displays. As with `rasterize_glyph()`, `transform` is applied after the glyph has been
+    /// scaled, so its translation is in physical pixels.
+    ///
+    /// By default, the sizes are multiplied by `pixels_per_dip` and the glyph is rasterized with
+    /// `rasterize_glyph()`. The DirectWrite loader passes the scale to DirectWrite instead, which
+    /// is what GDI-compatible rendering expects.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_glyph_with_pixels_per_dip(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph(
+            canvas,
+            glyph_id,
+            point_size * pixels_per_dip,
+            transform,
+            hinting_options.scaled(pixels_per_dip),
+            rasterization_options,
+        )
+    }
+
+    /// Renders a glyph into a new canvas sized to fit it, with the options in `settings`.
+    ///
+    /// `point_size` is in device-independent pixels, and `origin` is the position of the glyph
+    /// origin within the pixel grid, in physical pixels; its fractional part positions the glyph
+    /// at a subpixel offset. This combines `raster_bounds_with_pixels_per_dip()`,
+    /// `rasterize_glyph_with_pixels_per_dip()`, and the alpha conversion of
+    /// `rasterize_glyph_with_alpha_mode()`, which remain available for finer control. The gamma
+    /// and contrast adjustment applies to coverage, not to color glyphs in `Rgba32` canvases, and
+    /// subpixel coverage is reordered for `SubpixelGeometry::Bgr` displays.
+    fn render(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        origin: Vector2F,
+        settings: &RenderSettings,
+    ) -> Result<RenderedGlyph, GlyphLoadingError> {
+        let transform = Transform2F::from_translation(origin);
+        let bounds = self.raster_bounds_with_pixels_per_dip(
+            glyph_id,
+            point_size,
+            settings.pixels_per_dip,
+            transform,
+            settings.hinting_options,
+            settings.rasterization_options,
+        )?;
+
+        // Render at least one pixel so that loaders always have a valid canvas to draw into.
+        let mut canvas = Canvas::new(bounds.size().max(Vector2I::splat(1)), settings.format);
+        let rasterize = |canvas: &mut Canvas| {
+            self.rasterize_glyph_with_pixels_per_dip(
+                canvas,
+                glyph_id,
+                point_size,
+                settings.pixels_per_dip,
+                Transform2F::from_translation(-bounds.origin().to_f32()) * transform,
+                settings.hinting_options,
+                settings.rasterization_options,
+            )
+        };
+        if canvas.format == Format::Rgba32 && settings.alpha_mode == AlphaMode::WhiteCoverage {
+            let mut coverage = Canvas::new(canvas.size, Format::A8);
+            let rasterized_glyph = rasterize(&mut coverage)?;
+            coverage.adjust_coverage(settings.gamma, settings.contrast);
+            canvas.combine_white_coverage(RectI::new(Vector2I::default(), canvas.size), &coverage);
+            return Ok(RenderedGlyph {
+                canvas,
+                bounds,
+                rasterized_glyph: RasterizedGlyph {
+                    premultiplied: false,
+                    ..rasterized_glyph
+                },
+            });
+        }
+        let mut rasterized_glyph = rasterize(&mut canvas)?;
+        canvas.adjust_coverage(settings.gamma, settings.contrast);
+        if canvas.format == Format::Rgb24
+            && settings.rasterization_options == RasterizationOptions::SubpixelAa
+            && settings.subpixel_geometry == SubpixelGeometry::Bgr
+        {
+            canvas.reverse_subpixels();
+        }
+        if canvas.format == Format::Rgba32
+            && settings.alpha_mode == AlphaMode::Straight
+            && rasterized_glyph.premultiplied
+        {
+            canvas.unpremultiply_alpha(rasterized_glyph.ink_rect);
+            rasterized_glyph.premultiplied = false;
+        }
+        Ok(RenderedGlyph {
+            canvas,
+            bounds,
+            rasterized_glyph,
+        })
+    }
+
+    /// Renders a glyph whose origin is at `position` in layout (device-independent) pixels, for
+    /// display at the `pixels_per_dip` in `settings`.
+    ///
+    /// The position is scaled to device pixels before it is split into a whole pixel and a
+    /// subpixel residue, and the bounds and hinting are computed in device pixels as by
+    /// `render()`. So glyphs placed at fractional scales such as 1.25 land exactly where their
+    /// layout positions say, rather than each picking up its own rounding error.
+    fn render_at(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        position: Vector2F,
+        settings: &RenderSettings,
+    ) -> Result<PlacedGlyph, GlyphLoadingError> {
+        let device_position = position * settings.pixels_per_dip;
+        let pixel = device_position.floor();
+        let residue = device_position - pixel;
+        let rendered_glyph = self.render(glyph_id, point_size, residue, settings)?;
+        let device_origin = pixel.to_i32() + rendered_glyph.bounds.origin();
+        Ok(PlacedGlyph {
+            layout_origin: device_origin.to_f32() * (1.0 / settings.pixels_per_dip),
+            rendered_glyph,
+            device_origin,
+            residue,
+        })
+    }
+
+    /// Get font fallback results for the given text and locale.
+    ///
+    /// The `locale` argument is a language tag such as `"en-US"` or `"zh-Hans-CN"`.
+    fn get_fallbacks(&self, text: &str, locale: &str) -> FallbackResult<Self>;
+
+    /// Returns the OpenType font table with the given tag, if the table exists.
+    fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>>;
+
+    /// Returns true if the font has an OpenType table with the given tag.
+    ///
+    /// Unlike `load_font_table()`, this doesn't copy the table. The default implementation loads
+    /// it, so loaders override this with a cheaper lookup.
+    fn has_font_table(&self, table_tag: u32) -> bool {
+        self.load_font_table(table_tag).is_some()
+    }
+}
+
+/// Replaces `HintingOptions::FontDefault` with the hinting and rasterization that the font's `gasp`
+/// table recommends at its size. Other hinting options are returned unchanged.
+pub(crate) fn resolve_font_default_hinting<L>(
+    font: &L,
+    hinting_options: HintingOptions,
+    rasterization_options: RasterizationOptions,
+) -> (HintingOptions, RasterizationOptions)
+where
+    L: Loader,
+{
+    let size = match hinting_options {
+        HintingOptions::FontDefault(size) => size,
+        _ => return (hinting_options, rasterization_options),
+    };
+    let behavior = font.gasp_behavior(size.round().clamp(0.0, u16::MAX as f32) as u16);
+    let hinting_options = if behavior.grid_fit {
+        HintingOptions::Full(size)
+    } else {
+        HintingOptions::None
+    };
+    let rasterization_options = if behavior.grayscale {
+        rasterization_options
+    } else {
+        RasterizationOptions::Bilevel
+    };
+    (hinting_options, rasterization_options)
+}
+
+/// Replaces the weight and stretch in `properties` with the current values of the font's `wght`
+/// and `wdth` variation axes, for loaders that cache the result of `Loader::properties()`.
+///
+/// The `OS/2` table describes only the default instance, so the axis values win wherever the font
+/// has been varied away from it. At the default instance, they are used only where the `OS/2` table
+/// doesn't give usable values: minimal variable fonts may have no `OS/2` table, or one whose weight
+/// and width classes are zero, so the loaders fall back to the normal weight and stretch for them.
+pub(crate) fn properties_with_variations<L>(font: &L, mut properties: Properties) -> Properties
+where
+    L: Loader,
+{
+    let coordinates = font.variation_coordinates();
+    if coordinates.is_empty() {
+        return properties;
+    }
+
+    let classes = font
+        .load_font_table(tables::os2::TAG)
+        .and_then(|table| tables::os2::weight_and_width_classes(&table));
+    let (weight_valid, width_valid) = match classes {
+        Some((weight_class, width_class)) => (
+            (1..=1000).contains(&weight_class),
+            Stretch::from_width_class(width_class).is_some(),
+        ),
+        None => (false, false),
+    };
+    for coordinate in coordinates {
+        let varied = coordinate.normalized_value != 0.0;
+        if coordinate.tag == tables::fvar::WEIGHT_AXIS && (varied || !weight_valid) {
+            properties.weight = Weight(coordinate.user_value.clamp(1.0, 1000.0));
+        } else if coordinate.tag == tables::fvar::WIDTH_AXIS && (varied || !width_valid) {
+            let stretch = Stretch::from_percentage(coordinate.user_value).0;
+            properties.stretch =
+                Stretch(stretch.clamp(Stretch::ULTRA_CONDENSED.0, Stretch::ULTRA_EXPANDED.0));
+        }
+    }
+    properties
+}
+
+/// Formats a font for `Debug` output, showing its names and properties along with other facts that
+/// are cheap to look up, so that `dbg!()` stays fast.
+///
+/// The properties are cached by every loader after the first call. `is_color` and `is_monospace`
+/// are platform flags that the loader passes in, so that formatting copies no tables and measures
+/// no glyphs; `is_monospace` may therefore differ from `Loader::is_monospace()`, which prefers the
+/// `post` table's flag.
+pub(crate) fn fmt_debug<L>(
+    font: &L,
+    is_color: bool,
+    is_monospace: bool,
+    fmt: &mut fmt::Formatter,
+) -> fmt::Result
+where
+    L: Loader,
+{
+    fmt.debug_struct("Font")
+        .field("family_name", &font.family_name())
+        .field("postscript_name", &font.postscript_name())
+        .field("properties", &font.properties())
+        .field("units_per_em", &font.metrics().units_per_em)
+        .field("is_variable", &font.has_font_table(tables::fvar::TAG))
+        .field("is_color", &is_color)
+        .field("is_monospace", &is_monospace)
+        .finish()
+}
+
+/// Returns true if the font contains vector glyph data (a `glyf`, `CFF `, or `CFF2` table).
+///
+/// Fonts without any are bitmap-only, such as OTB conversions of bitmap fonts and emoji fonts that
+/// only have `CBDT` or `sbix` images.
+#[cfg(any(
+    all(any(target_os = "macos", target_os = "ios"), feature = "coretext"),
+    all(target_family = "windows", feature = "directwrite"),
+))]
+pub(crate) fn has_outline_tables<L>(font: &L) -> bool
+where
+    L: Loader,
+{
+    [b"glyf", b"CFF ", b"CFF2"]
+        .iter()
+        .any(|table_tag| font.has_font_table(tables::tag(table_tag)))
+}
+
+// The tables that `Loader::pair_kerning()` reads, each loaded on first use, so that a run of text
+// can look up all of its pairs without copying the tables again for each one.
+struct KerningTables<'a, L> {
+    font: &'a L,
+    gpos: OnceCell<Option<Box<[u8]>>>,
+    kerx: OnceCell<Option<Box<[u8]>>>,
+    kern: OnceCell<Option<Box<[u8]>>>,
+}
+
+impl<'a, L> KerningTables<'a, L>
+where
+    L: Loader,
+{
+    fn new(font: &'a L) -> KerningTables<'a, L> {
+        KerningTables {
+            font,
+            gpos: OnceCell::new(),
+            kerx: OnceCell::new(),
+            kern: OnceCell::new(),
+        }
+    }
+
+    fn pair_kerning(&self, left: u32, right: u32) -> Option<f32> {
+        if left > u16::MAX as u32 || right > u16::MAX as u32 {
+            return None;
+        }
+        let (left, right) = (left as u16, right as u16);
+        self.table(&self.gpos, tables::gpos::TAG)
+            .and_then(|table| tables::gpos::pair_kerning(table, left, right))
+            .or_else(|| {
+                let table = self.table(&self.kerx, tables::kern::KERX_TAG)?;
+                tables::kern::kerx_pair_kerning(table, left, right)
+            })
+            .or_else(|| {
+                let table = self.table(&self.kern, tables::kern::KERN_TAG)?;
+                tables::kern::kern_pair_kerning(table, left, right)
+            })
+    }
+
+    fn table<'b>(&self, cell: &'b OnceCell<Option<Box<[u8]>>>, tag: u32) -> Option<&'b [u8]> {
+        cell.get_or_init(|| self.font.load_font_table(tag))
+            .as_deref()
+    }
+}
+
+/// Reads the `isFixedPitch` field of the font's `post` table, which `Loader::is_monospace()`
+/// trusts. Returns `None` if the font has no `post` table.
+pub(crate) fn post_fixed_pitch<L>(font: &L) -> Option<bool>
+where
+    L: Loader,
+{
+    tables::post::is_fixed_pitch(&font.load_font_table(tables::post::TAG)?)
+}
+
+/// Gathers the signals in `Loader::pitch_info()`, for loaders that cache the result.
+pub(crate) fn measure_pitch_info<L>(font: &L) -> PitchInfo
+where
+    L: Loader,
+{
+    let post_flag = post_fixed_pitch(font);
+    let os2_panose_mono = font
+        .load_font_table(tables::os2::TAG)
+        .and_then(|table| tables::os2::panose_monospaced(&table));
+
+    // Digits and punctuation are left out, since coding fonts sometimes make them proportional.
+    let mut advances = ('A'..='Z')
+        .chain('a'..='z')
+        .filter_map(|character| font.glyph_for_char(character))
+        .filter_map(|glyph_id| font.advance(glyph_id).ok())
+        .map(|advance| advance.x());
+    let measured_uniform = advances.next().and_then(|first| {
+        let mut measured = 1;
+        let uniform = advances.all(|advance| {
+            measured += 1;
+            (advance - first).abs() < 0.5
+        });
+        if measured > 1 {
+            Some(uniform)
+        } else {
+            None
+        }
+    });
+
+    PitchInfo {
+        post_flag,
+        os2_panose_mono,
+        measured_uniform,
+    }
+}
+
+/// Looks for the TrueType programs and glyph instructions that `Loader::has_hinting_instructions()`
+/// reports, for loaders that cache its result.
+pub(crate) fn has_hinting_data<L>(font: &L) -> bool
+where
+    L: Loader,
+{
+    let has_program = [b"fpgm", b"prep"].iter().any(|table_tag| {
+        font.load_font_table(tables::tag(table_tag))
+            .is_some_and(|table| !table.is_empty())
+    });
+    has_program || has_glyph_instructions(font).unwrap_or(false)
+}
+
+/// Looks for the kerning data that `Loader::pair_kerning()` reads, for loaders that cache the
+/// result of `Loader::has_kerning()`.
+pub(crate) fn has_kerning_data<L>(font: &L) -> bool
+where
+    L: Loader,
+{
+    let has_pairs = |tag, has_pairs: fn(&[u8]) -> bool| {
+        font.load_font_table(tag)
+            .is_some_and(|table| has_pairs(&table))
+    };
+    has_pairs(tables::gpos::TAG, tables::gpos::has_pair_kerning)
+        || has_pairs(tables::kern::KERX_TAG, tables::kern::kerx_has_pairs)
+        || has_pairs(tables::kern::KERN_TAG, tables::kern::kern_has_pairs)
+}
+
+/// Returns true if the `head` tables of two fonts have the same fingerprint, so that they are
+/// almost certainly the same build of the same font.
+///
+/// Loaders use this to compare fonts loaded from separate copies of their data without comparing
+/// every byte. Fonts without a `head` table never match.
+pub(crate) fn same_head_fingerprint<L>(font: &L, other: &L) -> bool
+where
+    L: Loader,
+{
+    match (
+        font.load_font_table(tables::head::TAG),
+        other.load_font_table(tables::head::TAG),
+    ) {
+        (Some(head), Some(other_head)) => {
+            match (
+                tables::head::fingerprint(&head),
+                tables::head::fingerprint(&other_head),
+            ) {
+                (Some(fingerprint), Some(other_fingerprint)) => fingerprint == other_fingerprint,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Resolves the `(tag, value)` pairs passed to `Loader::with_variations()` against the font's
+/// `fvar` axes, returning a value for every axis in the order the font lists them.
+///
+/// Unlisted axes take their default values, values are clamped to each axis's range, and unknown
+/// tags are ignored. If a tag is listed more than once, the last value wins. Returns `None` for
+/// fonts without variation axes.
+pub(crate) fn resolve_variations<L>(font: &L, variations: &[(u32, f32)]) -> Option<Vec<(u32, f32)>>
+where
+    L: Loader,
+{
+    let axes = tables::fvar::axes(&font.load_font_table(tables::fvar::TAG)?)?;
+    if axes.is_empty() {
+        return None;
+    }
+    Some(
+        axes.iter()
+            .map(|axis| {
+                let value = variations
+                    .iter()
+                    .rev()
+                    .find(|&&(tag, _)| tag == axis.tag)
+                    .map_or(axis.default_value, |&(_, value)| {
+                        value.max(axis.min_value).min(axis.max_value)
+                    });
+                (axis.tag, value)
+            })
+            .collect(),
+    )
+}
+
+/// Builds the coordinates of every variation axis of `font` from the `(tag, value)` pairs in user
+/// units that its loader reports. Axes that aren't listed are at their defaults.
+pub(crate) fn variation_coordinates<L>(
+    font: &L,
+    user_values: &[(u32, f32)],
+) -> Vec<VariationCoordinate>
+where
+    L: Loader,
+{
+    let axes = match font
+        .load_font_table(tables::fvar::TAG)
+        .and_then(|fvar| tables::fvar::axes(&fvar))
+    {
+        Some(axes) => axes,
+        None => return vec![],
+    };
+    let segment_maps = segment_maps(font, axes.len());
+    axes.iter()
+        .enumerate()
+        .map(|(axis_index, axis)| {
+            let user_value = user_values
+                .iter()
+                .find(|&&(tag, _)| tag == axis.tag)
+                .map_or(axis.default_value, |&(_, value)| value);
+            VariationCoordinate {
+                tag: axis.tag,
+                user_value,
+                normalized_value: tables::avar::normalize(
+                    axis,
+                    segment_maps.get(axis_index),
+                    user_value,
+                ),
+            }
+        })
+        .collect()
+}
+
+// Returns the `fvar` axis with the given tag along with its `avar` segment map, if it has one.
+fn variation_axis<L>(
+    font: &L,
+    tag: u32,
+) -> Option<(tables::fvar::Axis, Option<tables::avar::SegmentMap>)>
+where
+    L: Loader,
+{
+    let axes = tables::fvar::axes(&font.load_font_table(tables::fvar::TAG)?)?;
+    let axis_index = axes.iter().position(|axis| axis.tag == tag)?;
+    let segment_map = segment_maps(font, axes.len()).into_iter().nth(axis_index);
+    Some((axes[axis_index], segment_map))
+}
+
+// Returns the `avar` segment maps, which are ignored unless there is one for each of the
+// `axis_count` axes.
+fn segment_maps<L>(font: &L, axis_count: usize) -> Vec<tables::avar::SegmentMap>
+where
+    L: Loader,
+{
+    font.load_font_table(tables::avar::TAG)
+        .and_then(|avar| tables::avar::segment_maps(&avar))
+        .filter(|segment_maps| segment_maps.len() == axis_count)
+        .unwrap_or_default()
+}
+
+fn glyf_outline_from_contours(contours: Vec<Contour>, implied_points: ImpliedPoints) -> Outline {
+    let contours = match implied_points {
+        ImpliedPoints::AsStored => contours,
+        ImpliedPoints::Explicit => contours
+            .iter()
+            .map(tables::glyf::insert_implied_points)
+            .collect(),
+    };
+    Outline { contours }
+}
+
+// Returns the top of the ink of the first of `characters` that the font has an outline with ink
+// for, in font units.
+fn ink_top<L>(font: &L, characters: &[char]) -> Option<f32>
+where
+    L: Loader,
+{
+    characters
+        .iter()
+        .filter_map(|&character| font.glyph_for_char(character))
+        .filter_map(|glyph_id| font.outline_bounds(glyph_id).ok())
+        .find(|bounds| bounds.height() > 0.0)
+        .map(|bounds| bounds.max_y())
+}
+
+// Returns the `glyf` table along with the byte range of `glyph_id` within it.
+fn load_glyf_glyph<L>(font: &L, glyph_id: u32) -> Option<(Box<[u8]>, Range<usize>)>
+where
+    L: Loader,
+{
+    let head = font.load_font_table(tables::head::TAG)?;
+    let loca = font.load_font_table(tables::loca::TAG)?;
+    let long_offsets = tables::head::has_long_loca_offsets(&head)?;
+    let glyph_range = tables::loca::glyph_range(&loca, long_offsets, glyph_id)?;
+    Some((font.load_font_table(tables::glyf::TAG)?, glyph_range))
+}
+
+// Returns true if any glyph in the `glyf` table has instructions, or `None` if the font has no
+// `glyf` table or its `loca` table can't be read.
+fn has_glyph_instructions<L>(font: &L) -> Option<bool>
+where
+    L: Loader,
+{
+    let head = font.load_font_table(tables::head::TAG)?;
+    let loca = font.load_font_table(tables::loca::TAG)?;
+    let glyf = font.load_font_table(tables::glyf::TAG)?;
+    let long_offsets = tables::head::has_long_loca_offsets(&head)?;
+    Some((0..font.glyph_count()).any(|glyph_id| {
+        tables::loca::glyph_range(&loca, long_offsets, glyph_id)
+            .and_then(|glyph_range| glyf.get(glyph_range))
+            .and_then(tables::glyf::instruction_length)
+            .is_some_and(|instruction_length| instruction_length > 0)
+    }))
+}
+
+/// Checks that `font_index` selects a font in `font_data`.
+///
+/// Font indices only ever select a member of a collection, so a single font only has index 0.
+/// Indices of 0x10000 and up, which FreeType would read as selecting a named instance, are always
+/// rejected. The loader is left to check data in formats other than OpenType and its collections.
+pub(crate) fn check_font_index(font_data: &[u8], font_index: u32) -> Result<(), FontLoadingError> {
+    if font_index > 0xffff {
+        return Err(FontLoadingError::NoSuchFontInCollection);
+    }
+    match file_type::collection_face_offsets(font_data) {
+        Ok(offsets) if font_index as usize >= offsets.len() => {
+            Err(FontLoadingError::NoSuchFontInCollection)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the offset of the table directory of the font at `font_index` in `font_data`, which may
+/// be a single font or a collection.
+pub(crate) fn face_byte_offset(font_data: &[u8], font_index: u32) -> Option<u32> {
+    file_type::collection_face_offsets(font_data)
+        .ok()?
+        .get(font_index as usize)
+        .copied()
+}
+
+/// Returns `glyph_id` as the 16-bit glyph index that font tables and platform APIs use, or
+/// `GlyphLoadingError::NoSuchGlyph` if the font has no such glyph.
+///
+/// Fonts can't have more than 65,535 glyphs, so larger IDs are always errors. Truncating them
+/// instead would silently pick some other glyph.
+pub(crate) fn checked_glyph_id<L>(font: &L, glyph_id: u32) -> Result<u16, GlyphLoadingError>
+where
+    L: Loader,
+{
+    match u16::try_from(glyph_id) {
+        Ok(glyph_index) if glyph_id < font.glyph_count() => Ok(glyph_index),
+        _ => Err(GlyphLoadingError::NoSuchGlyph),
+    }
+}
+
+/// Returns true if `outline()` sends nothing to its sink for the glyph.
+///
+/// This is what `Loader::glyph_is_empty()` does by default, and where loaders that override it
+/// fall back to for glyphs that their cheaper checks can't decide.
+pub(crate) fn outline_is_empty<L>(font: &L, glyph_id: u32) -> Result<bool, GlyphLoadingError>
+where
+    L: Loader,
+{
+    checked_glyph_id(font, glyph_id)?;
+    let mut sink = EmptinessSink::default();
+    font.outline(glyph_id, HintingOptions::None, &mut sink)?;
+    Ok(!sink.received_commands)
+}
+
+/// Returns the pixels covered by the glyph's typographic bounds at `point_size` after applying
+/// `transform`, with the origin at the top left.
+///
+/// This is what `Loader::raster_bounds()` returns by default. It doesn't account for hinting or
+/// for anything a rasterizer draws outside the outline.
+pub(crate) fn typographic_raster_bounds<L>(
+    font: &L,
+    glyph_id: u32,
+    point_size: f32,
+    transform: Transform2F,
+) -> Result<RectI, GlyphLoadingError>
+where
+    L: Loader,
+{
+    let typographic_bounds = font.typographic_bounds(glyph_id)?;
+    let typographic_raster_bounds = font
+        .metrics()
+        .scale_for(point_size)
+        .to_px_rect(typographic_bounds);
+
+    // Translate the origin to "origin is top left" coordinate system.
+    let new_origin = Vector2F::new(
+        typographic_raster_bounds.origin_x(),
+        -typographic_raster_bounds.origin_y() - typographic_raster_bounds.height(),
+    );
+    let typographic_raster_bounds = RectF::new(new_origin, typographic_raster_bounds.size());
+    Ok(round_out_to_i32(transform * typographic_raster_bounds))
+}
+
+/// Rounds `rect` out to whole pixels, clamping it to a range whose size still fits in an `i32`.
+///
+/// `RectF::to_i32()` turns out-of-range coordinates into `i32::MIN`, which gives a rectangle of
+/// negative size that `Canvas::new()` can't allocate.
+pub(crate) fn round_out_to_i32(rect: RectF) -> RectI {
+    const LIMIT: f32 = (1 << 30) as f32;
+    let rect = rect.round_out();
+    let clamp = |value: f32| value.clamp(-LIMIT, LIMIT) as i32;
+    RectI::from_points(
+        Vector2I::new(clamp(rect.min_x()), clamp(rect.min_y())),
+        Vector2I::new(clamp(rect.max_x()), clamp(rect.max_y())),
+    )
+}
+
+/// The units per em substituted for fonts that report 0. This is the usual value for CFF fonts.
+pub(crate) const FALLBACK_UNITS_PER_EM: u32 = 1000;
+
+/// Returns `units_per_em`, or `FALLBACK_UNITS_PER_EM` if it is 0, so that it is safe to divide by.
+///
+/// Only corrupt fonts report 0, so this logs a warning when it substitutes the fallback.
+pub(crate) fn sanitize_units_per_em(units_per_em: u32) -> u32 {
+    if units_per_em != 0 {
+        return units_per_em;
+    }
+    warn!(
+        "font reports 0 units per em; assuming {}",
+        FALLBACK_UNITS_PER_EM
+    );
+    FALLBACK_UNITS_PER_EM
+}
+
+/// Reads the Windows clipping metrics from the `OS/2` table, falling back to the `hhea` ascent
+/// and descent. Returns `None` if neither table is usable.
+pub(crate) fn win_metrics<L>(font: &L) -> Option<(f32, f32)>
+where
+    L: Loader,
+{
+    font.load_font_table(tables::os2::TAG)
+        .and_then(|os2| tables::os2::win_metrics(&os2))
+        .or_else(|| {
+            font.load_font_table(tables::hhea::TAG)
+                .and_then(|hhea| tables::hhea::ascent_descent(&hhea))
+        })
+}
+
+/// Splits a per-axis point size into the vertical point size and a transform that applies the
+/// horizontal stretch before `transform`.
+pub(crate) fn anisotropic_point_size_to_transform(
+    point_size: Vector2F,
+    transform: Transform2F,
+) -> (f32, Transform2F) {
+    if point_size.x() == point_size.y() || point_size.y() == 0.0 {
+        return (point_size.y(), transform);
+    }
+    let stretch = Vector2F::new(point_size.x() / point_size.y(), 1.0);
+    (point_size.y(), transform * Transform2F::from_scale(stretch))
+}
+
+/// Which layout tables a font contains, as returned by `Loader::shaping_tables()`.
+///
+/// Fonts made for Apple platforms often use the AAT `morx` and `kerx` tables in place of the
+/// OpenType `GSUB` and `GPOS` tables.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShapingTables {
+    /// The font has an OpenType glyph substitution (`GSUB`) table.
+    pub gsub: bool,
+    /// The font has an OpenType glyph positioning (`GPOS`) table.
+    pub gpos: bool,
+    /// The font has an AAT extended glyph metamorphosis (`morx`) table.
+    pub morx: bool,
+    /// The font has an AAT extended kerning (`kerx`) table.
+    pub kerx: bool,
+    /// The font has a classic kerning (`kern`) table.
+    pub kern: bool,
+}
+
+impl ShapingTables {
+    /// Returns true if the font should be shaped with AAT rather than OpenType layout, which is
+    /// the case when it has a `morx` table but no `GSUB` table.
+    #[inline]
+    pub fn is_aat(&self) -> bool {
+        self.morx && !self.gsub
+    }
+}
+
+/// An iterator over the characters that a font maps to glyphs and their glyph IDs, as returned by
+/// `Loader::char_mappings()`.
+#[derive(Clone)]
+pub struct CharMappingIter {
+    cmap: Box<[u8]>,
+    cursor: Option<tables::cmap::Cursor>,
+}
+
+impl CharMappingIter {
+    fn new(cmap: Option<Box<[u8]>>) -> CharMappingIter {
+        let cmap = cmap.unwrap_or_default();
+        let cursor = tables::cmap::Cursor::new(&cmap);
+        CharMappingIter { cmap, cursor }
+    }
+}
+
+impl Iterator for CharMappingIter {
+    type Item = (char, u32);
+
+    #[inline]
+    fn next(&mut self) -> Option<(char, u32)> {
+        self.cursor.as_mut()?.next(&self.cmap)
+    }
+}
+
+impl fmt::Debug for CharMappingIter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CharMappingIter")
+            .field("cmap_len", &self.cmap.len())
+            .finish()
+    }
+}
+
+/// The signals that indicate whether a font is monospaced, as returned by
+/// `Loader::pitch_info()`.
+///
+/// Each is `None` if the font doesn't provide it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PitchInfo {
+    /// The `isFixedPitch` field of the `post` table.
+    pub post_flag: Option<bool>,
+    /// Whether the PANOSE classification in the `OS/2` table is monospaced. This is `None` for
+    /// PANOSE families that don't classify spacing.
+    pub os2_panose_mono: Option<bool>,
+    /// Whether the glyphs for the ASCII letters all have the same advance. This is `None` if the
+    /// font maps fewer than two of them.
+    pub measured_uniform: Option<bool>,
+}
+
+impl PitchInfo {
+    /// Decides whether the font is monospaced.
+    ///
+    /// The `post` table's flag is trusted first, but a font that sets it is only monospaced if
+    /// the advances of its letters don't turn out to differ. Digits and punctuation aren't
+    /// measured, so coding fonts with proportional digits still count as monospaced. Fonts without
+    /// a `post` table, such as bitmap fonts, are judged by their advances, falling back to the
+    /// PANOSE classification if they map no ASCII letters.
+    pub fn is_monospace(&self) -> bool {
+        match self.post_flag {
+            Some(true) => self.measured_uniform != Some(false),
+            Some(false) => false,
+            None => self
+                .measured_uniform
+                .or(self.os2_panose_mono)
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// A view of a font that scales everything it reports in font units to a common em size, as
+/// returned by `Loader::normalized()`.
+///
+/// Glyph IDs, names, and other unitless queries are unaffected; use `font()` for those. Queries
+/// whose results are scaled to a point size, such as `raster_bounds()`, are passed through as is.
+pub struct NormalizedFont<'a, F> {
+    font: &'a F,
+    units_per_em: u32,
+    scale: f32,
+}
+
+impl<F> Clone for NormalizedFont<'_, F> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for NormalizedFont<'_, F> {}
+
+impl<F> fmt::Debug for NormalizedFont<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NormalizedFont")
+            .field("units_per_em", &self.units_per_em)
+            .field("scale", &self.scale)
+            .finish()
+    }
+}
+
+impl<'a, F> NormalizedFont<'a, F>
+where
+    F: Loader,
+{
+    /// Creates a view of `font` in units of an em `units_per_em` units wide.
+    ///
+    /// A `units_per_em` of 0 is treated as 1000, as `Metrics::units_per_em` is.
+    pub fn new(font: &'a F, units_per_em: u32) -> NormalizedFont<'a, F> {
+        let units_per_em = sanitize_units_per_em(units_per_em);
+        NormalizedFont {
+            font,
+            units_per_em,
+            scale: units_per_em as f32 / font.metrics().units_per_em as f32,
+        }
+    }
+
+    /// Returns the underlying font, whose queries are in its own units.
+    #[inline]
+    pub fn font(&self) -> &'a F {
+        self.font
+    }
+
+    /// Returns the number of units per em that this view reports values in.
+    #[inline]
+    pub fn units_per_em(&self) -> u32 {
+        self.units_per_em
+    }
+
+    /// Returns the factor that converts the font's own units to the units of this view.
+    #[inline]
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Returns the font's metrics in the units of this view.
+    #[inline]
+    pub fn metrics(&self) -> Metrics {
+        self.font.metrics_normalized(self.units_per_em)
+    }
+
+    /// Returns the font's metrics in the units of this view, measuring the cap height and
+    /// x-height from glyphs if the font doesn't declare them. See
+    /// `Loader::metrics_with_fallbacks()`.
+    #[inline]
+    pub fn metrics_with_fallbacks(&self) -> (Metrics, MetricsSource) {
+        let (metrics, source) = self.font.metrics_with_fallbacks();
+        (metrics.normalized(self.units_per_em), source)
+    }
+
+    /// Returns the typographic bounds of a glyph in the units of this view.
+    #[inline]
+    pub fn typographic_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        Ok(self.font.typographic_bounds(glyph_id)? * self.scale)
+    }
+
+    /// Returns the exact bounding box of a glyph outline in the units of this view.
+    #[inline]
+    pub fn outline_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        Ok(self.font.outline_bounds(glyph_id)? * self.scale)
+    }
+
+    /// Returns the advance of a glyph in the units of this view.
+    #[inline]
+    pub fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+        Ok(self.font.advance(glyph_id)? * self.scale)
+    }
+
+    /// Returns the origin of a glyph in the units of this view.
+    #[inline]
+    pub fn origin(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+        Ok(self.font.origin(glyph_id)? * self.scale)
+    }
+
+    /// Sends the vector path for a glyph to a sink, with its coordinates in the units of this
+    /// view.
+    pub fn outline<S>(
+        &self,
+        glyph_id: u32,
+        hinting_mode: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        self.font.outline(
+            glyph_id,
+            hinting_mode,
+            &mut ScaledOutlineSink::new(sink, self.scale),
+        )
+    }
+
+    /// Returns the components of a TrueType composite glyph, with their offsets in the units of
+    /// this view. See `Loader::composite_components()`.
+    pub fn composite_components(&self, glyph_id: u32) -> Option<Vec<Component>> {
+        let mut components = self.font.composite_components(glyph_id)?;
+        for component in &mut components {
+            component.transform.vector *= self.scale;
+        }
+        Some(components)
+    }
+
+    /// Returns the contours of a simple TrueType glyph in the units of this view. See
+    /// `Loader::glyf_contours()`.
+    #[inline]
+    pub fn glyf_contours(&self, glyph_id: u32, implied_points: ImpliedPoints) -> Option<Outline> {
+        let outline = self.font.glyf_contours(glyph_id, implied_points)?;
+        Some(self.scale_outline(outline))
+    }
+
+    /// Returns the contours of a simple TrueType glyph in the units of this view, along with the
+    /// index of each point. See `Loader::glyf_contours_with_point_indices()`.
+    pub fn glyf_contours_with_point_indices(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Option<IndexedOutline> {
+        let indexed_outline = self
+            .font
+            .glyf_contours_with_point_indices(glyph_id, implied_points)?;
+        Some(IndexedOutline {
+            outline: self.scale_outline(indexed_outline.outline),
+            ..indexed_outline
+        })
+    }
+
+    /// Returns the flattened outline of a TrueType glyph in the units of this view. See
+    /// `Loader::glyf_outline()`.
+    #[inline]
+    pub fn glyf_outline(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Result<Outline, GlyphLoadingError> {
+        let outline = self.font.glyf_outline(glyph_id, implied_points)?;
+        Ok(self.scale_outline(outline))
+    }
+
+    /// Returns the ink bounds of `text` at `point_size`, in points. Since the result is already
+    /// scaled to the point size, it is the same as the font's. See `Loader::string_ink_bounds()`.
+    #[inline]
+    pub fn string_ink_bounds(&self, text: &str, point_size: f32, apply_kerning: bool) -> RectF {
+        self.font.string_ink_bounds(text, point_size, apply_kerning)
+    }
+
+    /// Sends the outline of a missing-glyph placeholder to a path builder, and returns its
+    /// advance. Both are scaled to `point_size`, so they are the same as the font's. See
+    /// `Loader::synthesize_notdef()`.
+    #[inline]
+    pub fn synthesize_notdef<S>(&self, point_size: f32, sink: &mut S) -> Vector2F
+    where
+        S: OutlineSink,
+    {
+        self.font.synthesize_notdef(point_size, sink)
+    }
+
+    /// Returns the pixel boundaries of a rendered glyph, which are the same as the font's. See
+    /// `Loader::raster_bounds()`.
+    #[inline]
+    pub fn raster_bounds(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        self.font.raster_bounds(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the horizontal kerning between two glyphs in the units of this view.
+    #[inline]
+    pub fn pair_kerning(&self, left: u32, right: u32) -> Option<f32> {
+        self.font
+            .pair_kerning(left, right)
+            .map(|kerning| kerning * self.scale)
+    }
+
+    /// Returns the tracking adjustment at `point_size` in the units of this view.
+    #[inline]
+    pub fn tracking(&self, point_size: f32) -> Option<f32> {
+        self.font
+            .tracking(point_size)
+            .map(|tracking| tracking * self.scale)
+    }
+
+    /// Returns the caret metrics, with the offset in the units of this view. The slope is a ratio
+    /// and so is unchanged.
+    #[inline]
+    pub fn caret_metrics(&self) -> CaretMetrics {
+        let caret_metrics = self.font.caret_metrics();
+        CaretMetrics {
+            offset: caret_metrics.offset * self.scale,
+            ..caret_metrics
+        }
+    }
+
+    /// Returns the recommended subscript and superscript sizes and offsets in the units of this
+    /// view.
+    pub fn script_metrics(&self) -> Option<ScriptMetrics> {
+        let script_metrics = self.font.script_metrics()?;
+        Some(ScriptMetrics {
+            subscript_x_size: script_metrics.subscript_x_size * self.scale,
+            subscript_y_size: script_metrics.subscript_y_size * self.scale,
+            subscript_x_offset: script_metrics.subscript_x_offset * self.scale,
+            subscript_y_offset: script_metrics.subscript_y_offset * self.scale,
+            superscript_x_size: script_metrics.superscript_x_size * self.scale,
+            superscript_y_size: script_metrics.superscript_y_size * self.scale,
+            superscript_x_offset: script_metrics.superscript_x_offset * self.scale,
+            superscript_y_offset: script_metrics.superscript_y_offset * self.scale,
+        })
+    }
+
+    fn scale_outline(&self, mut outline: Outline) -> Outline {
+        for contour in &mut outline.contours {
+            for position in &mut contour.positions {
+                *position *= self.scale;
+            }
+        }
+        outline
+    }
+}
+
+/// The current value of a variation axis, as returned by `Loader::variation_coordinates()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VariationCoordinate {
+    /// The axis tag, with its four letters packed big-endian, as in `with_variations()`.
+    pub tag: u32,
+    /// The value in user units, such as 700 for a bold weight.
+    pub user_value: f32,
+    /// The normalized coordinate in -1..1, after the `avar` mapping. See
+    /// `Loader::normalize_variation()`.
+    pub normalized_value: f32,
 }
 
 /// The result of a fallback query.
@@ -11,41 +11,59 @@
 //! A loader that uses Apple's Core Text API to load and rasterize fonts.
 
 use byteorder::{BigEndian, ReadBytesExt};
+use core_foundation::array::CFArray;
+use core_foundation::base::TCFType;
+use core_foundation::data::CFData;
+use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+use core_foundation::number::{CFNumber, CFNumberRef};
+use core_foundation::string::CFString;
 use core_graphics::base::{kCGImageAlphaPremultipliedLast, CGFloat};
 use core_graphics::color_space::CGColorSpace;
 use core_graphics::context::{CGContext, CGTextDrawingMode};
-use core_graphics::font::{CGFont, CGGlyph};
+use core_graphics::font::CGFont;
 use core_graphics::geometry::{CGAffineTransform, CGPoint, CGRect, CGSize};
 use core_graphics::geometry::{CG_AFFINE_TRANSFORM_IDENTITY, CG_ZERO_POINT, CG_ZERO_SIZE};
 use core_graphics::path::CGPathElementType;
 use core_text;
-use core_text::font::CTFont;
-use core_text::font_descriptor::kCTFontDefaultOrientation;
+use core_text::font::{CTFont, CTFontRef};
+use core_text::font_descriptor::{self, kCTFontVariationAttribute, CTFontDescriptorRef};
+use core_text::font_descriptor::{kCTFontColorGlyphsTrait, kCTFontDefaultOrientation};
 use core_text::font_descriptor::{SymbolicTraitAccessors, TraitAccessors};
-use log::warn;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::Vector2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_simd::default::F32x4;
 use std::cmp::Ordering;
 use std::f32;
 use std::fmt::{self, Debug, Formatter};
 use std::fs::File;
-use std::io::{Seek, SeekFrom};
+use std::io::{Read, Seek};
 use std::ops::Deref;
 use std::path::Path;
-use std::sync::Arc;
-
-use crate::canvas::{Canvas, Format, RasterizationOptions};
+use std::ptr;
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+use crate::cache::GlyphCache;
+use crate::canvas::{
+    AlphaMode, Canvas, Format, GlyphSource, PlacedGlyph, RasterizationOptions, RasterizedGlyph,
+    RenderSettings, RenderedGlyph,
+};
+use crate::color::{ColorLayer, ColorU, PaletteSelector};
 use crate::error::{FontLoadingError, GlyphLoadingError};
 use crate::file_type::FileType;
 use crate::handle::Handle;
-use crate::hinting::HintingOptions;
-use crate::loader::{FallbackResult, Loader};
-use crate::metrics::Metrics;
-use crate::outline::OutlineSink;
-use crate::properties::{Properties, Stretch, Style, Weight};
+use crate::hinting::{GaspBehavior, HintingOptions};
+use crate::loader::{
+    self, CharMappingIter, FallbackFont, FallbackResult, Loader, NormalizedFont, PitchInfo,
+    ShapingTables, VariationCoordinate,
+};
+use crate::metrics::{CaretMetrics, Metrics, MetricsSource, ScriptMetrics};
+use crate::outline::{
+    Component, ImpliedPoints, IndexedOutline, Outline, OutlineBuilder, OutlineSink,
+};
+use crate::properties::{Properties, StatAxisValue, Stretch, Style, Weight};
 use crate::utils;
 
 const TTC_TAG: [u8; 4] = [b't', b't', b'c', b'f'];
@@ -54,6 +72,7 @@ const OTTO_HEX: u32 = 0x4f54544f; // 'OTTO'
 const TRUE_HEX: u32 = 0x74727565; // 'true'
 const TYP1_HEX: u32 = 0x74797031; // 'typ1'
 const SFNT_HEX: u32 = 0x73666e74; // 'sfnt'
+const SBIX_HEX: u32 = 0x73626978; // 'sbix'
 
 #[allow(non_upper_case_globals)]
 const kCGImageAlphaOnly: u32 = 7;
@@ -63,11 +82,35 @@ pub(crate) static FONT_WEIGHT_MAPPING: [f32; 9] = [-0.7, -0.5, -0.23, 0.0, 0.2,
 /// Core Text's representation of a font.
 pub type NativeFont = CTFont;
 
+// Not exposed by the `core-text` crate.
+#[link(name = "CoreText", kind = "framework")]
+extern "C" {
+    fn CTFontCreateCopyWithAttributes(
+        font: CTFontRef,
+        size: CGFloat,
+        matrix: *const CGAffineTransform,
+        attributes: CTFontDescriptorRef,
+    ) -> CTFontRef;
+    fn CTFontCopyVariation(font: CTFontRef) -> CFDictionaryRef;
+}
+
 /// A loader that uses Apple's Core Text API to load and rasterize fonts.
 #[derive(Clone)]
 pub struct Font {
     core_text_font: CTFont,
     font_data: FontData,
+    advance_cache: GlyphCache<Vector2F>,
+    outline_cache: GlyphCache<Arc<Outline>>,
+    // The path or data, and font index, that the font was loaded from.
+    handle: Option<Handle>,
+    // Whether the font has any kerning data, computed on first use.
+    has_kerning: OnceLock<bool>,
+    // Whether the font has TrueType hinting instructions, computed on first use.
+    has_hinting_instructions: OnceLock<bool>,
+    // The signals for `pitch_info()`, measured on first use.
+    pitch_info: OnceLock<PitchInfo>,
+    // The font's properties, read on first use.
+    properties: OnceLock<Properties>,
 }
 
 impl Font {
@@ -75,10 +118,17 @@ impl Font {
     ///
     /// If the data represents a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index
     /// of the font to load from it. If the data represents a single font, pass 0 for `font_index`.
+    ///
+    /// Core Text reads single fonts from `font_data` without copying it. Collections and data fork
+    /// fonts are copied once, since Core Text can't load them directly and they must be
+    /// rearranged first.
     pub fn from_bytes(
         mut font_data: Arc<Vec<u8>>,
         font_index: u32,
     ) -> Result<Font, FontLoadingError> {
+        loader::check_font_index(&font_data, font_index)?;
+        let handle = Handle::from_memory(font_data.clone(), font_index);
+
         // Sadly, there's no API to load OpenType collections on macOS, I don't believe…
         // If not otf/ttf or otc/ttc, we unpack it as data fork font.
         if !font_is_single_otf(&*font_data) && !font_is_collection(&*font_data) {
@@ -91,48 +141,250 @@ impl Font {
             font_data = Arc::new(new_font_data);
         }
 
-        let core_text_font = match core_text::font::new_from_buffer(&*font_data) {
-            Ok(ct_font) => ct_font,
-            Err(_) => return Err(FontLoadingError::Parse),
-        };
+        let core_text_font = new_core_text_font_from_data(&font_data)?;
 
         Ok(Font {
             core_text_font,
             font_data: FontData::Memory(font_data),
+            advance_cache: GlyphCache::disabled(),
+            outline_cache: GlyphCache::disabled(),
+            handle: Some(handle),
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
         })
     }
 
+    /// Loads a font from a seekable stream, such as an entry in an archive, by reading all of it
+    /// into memory.
+    ///
+    /// The stream is rewound first, so its position on entry does not matter. If the stream holds
+    /// a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the font to load
+    /// from it. If it holds a single font, pass 0 for `font_index`.
+    #[inline]
+    pub fn from_stream<R>(stream: &mut R, font_index: u32) -> Result<Font, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        <Self as Loader>::from_stream(stream, font_index)
+    }
+
     /// Loads a font from a `.ttf`/`.otf`/etc. file.
     ///
     /// If the file is a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the
     /// font to load from it. If the file represents a single font, pass 0 for `font_index`.
+    #[inline]
     pub fn from_file(file: &mut File, font_index: u32) -> Result<Font, FontLoadingError> {
-        file.seek(SeekFrom::Start(0))?;
-        let font_data = Arc::new(utils::slurp_file(file).map_err(FontLoadingError::Io)?);
-        Font::from_bytes(font_data, font_index)
+        Font::from_stream(file, font_index)
     }
 
     /// Loads a font from the path to a `.ttf`/`.otf`/etc. file.
     ///
     /// If the file is a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the
     /// font to load from it. If the file represents a single font, pass 0 for `font_index`.
-    #[inline]
     pub fn from_path<P: AsRef<Path>>(path: P, font_index: u32) -> Result<Font, FontLoadingError> {
-        <Font as Loader>::from_path(path, font_index)
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|e| crate::error::io_error_with_path(path, e))?;
+        let mut font = Font::from_file(&mut file, font_index)?;
+        font.handle = Some(Handle::from_path(path.to_owned(), font_index));
+        Ok(font)
     }
 
     /// Creates a font from a native API handle.
+    ///
+    /// The font takes ownership of the reference held by `core_text_font`, so the caller must pass
+    /// `core_text_font` to `mem::forget()` afterward. Core Text doesn't expose the data that a font was created from, so
+    /// `copy_font_data()` returns `None`; use `from_native_font_with_data()` if you have the data
+    /// at hand.
+    ///
+    /// # Safety
+    ///
+    /// The caller must give up its reference to `core_text_font`, which the font releases when it
+    /// is dropped.
     pub unsafe fn from_native_font(core_text_font: &NativeFont) -> Font {
-        Font::from_core_text_font_no_path(core_text_font.clone())
+        Font::from_core_text_font_no_path(CTFont::wrap_under_create_rule(
+            core_text_font.as_concrete_TypeRef(),
+        ))
+    }
+
+    /// Creates a font from a native API handle and the contents of the file that it was created
+    /// from, which `copy_font_data()` then returns.
+    ///
+    /// If the file is a collection, `font_data` is the whole collection. Returns an error if
+    /// `font_data` isn't a font file that Core Text recognizes. Unlike `from_native_font()`, this
+    /// retains a reference of its own, and the caller keeps `core_text_font`.
+    pub fn from_native_font_with_data(
+        core_text_font: &NativeFont,
+        font_data: Arc<Vec<u8>>,
+    ) -> Result<Font, FontLoadingError> {
+        Font::analyze_bytes(font_data.clone())?;
+        Ok(Font {
+            core_text_font: core_text_font.clone(),
+            font_data: FontData::Memory(font_data),
+            advance_cache: GlyphCache::disabled(),
+            outline_cache: GlyphCache::disabled(),
+            handle: None,
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
+        })
     }
+
     /// Creates a font from a native API handle, without performing a lookup on the disk.
+    ///
+    /// The font owns `core_text_font` and releases its reference when the font is dropped.
     pub unsafe fn from_core_text_font_no_path(core_text_font: NativeFont) -> Font {
         Font {
             core_text_font,
             font_data: FontData::Unavailable,
+            advance_cache: GlyphCache::disabled(),
+            outline_cache: GlyphCache::disabled(),
+            handle: None,
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
+        }
+    }
+
+    /// Enables caching of glyph advances, returning the modified font.
+    ///
+    /// See `Loader::with_advance_cache()`.
+    #[inline]
+    pub fn with_advance_cache(mut self) -> Font {
+        self.advance_cache = GlyphCache::enabled();
+        self
+    }
+
+    /// Enables caching of glyph outlines, returning the modified font.
+    ///
+    /// With the cache enabled, each glyph's outline is fetched from the Core Text font at most once
+    /// and replayed from memory afterward, which speeds up drawing the same glyphs repeatedly. The
+    /// cache is off by default.
+    #[inline]
+    pub fn with_outline_cache(mut self) -> Font {
+        self.outline_cache = GlyphCache::enabled();
+        self
+    }
+
+    /// Forgets every cached glyph outline.
+    ///
+    /// Call this after anything that changes the shapes of the glyphs, such as setting variation
+    /// axis values, so that stale outlines aren't drawn. This does nothing if the outline cache is
+    /// disabled.
+    #[inline]
+    pub fn clear_outline_cache(&self) {
+        self.outline_cache.clear()
+    }
+
+    /// Returns a copy of this font instanced at the given variation axis values, as `(tag, value)`
+    /// pairs in user units.
+    ///
+    /// The variations are applied to the Core Text font itself, from which outlines, metrics,
+    /// advances, and rasterization are all derived, so they agree with each other. Axes that aren't
+    /// listed keep their default values. Fonts without variation axes are returned unchanged.
+    pub fn with_variations(&self, variations: &[(u32, f32)]) -> Result<Font, FontLoadingError> {
+        let variations = match loader::resolve_variations(self, variations) {
+            Some(variations) => variations,
+            None => return Ok(self.clone()),
+        };
+        // Core Text identifies axes by their tags as numbers.
+        let variation_pairs: Vec<(CFNumber, CFNumber)> = variations
+            .iter()
+            .map(|&(tag, value)| (CFNumber::from(tag as i64), CFNumber::from(value as f64)))
+            .collect();
+        let variation_dictionary = CFDictionary::from_CFType_pairs(&variation_pairs);
+        let attributes = CFDictionary::from_CFType_pairs(&[(
+            unsafe { CFString::wrap_under_get_rule(kCTFontVariationAttribute) },
+            variation_dictionary.as_CFType(),
+        )]);
+        let descriptor = font_descriptor::new_from_attributes(&attributes);
+        let core_text_font = unsafe {
+            // A size of 0 keeps the size of the original font.
+            let core_text_font_ref = CTFontCreateCopyWithAttributes(
+                self.core_text_font.as_concrete_TypeRef(),
+                0.0,
+                ptr::null(),
+                descriptor.as_concrete_TypeRef(),
+            );
+            if core_text_font_ref.is_null() {
+                return Err(FontLoadingError::Parse);
+            }
+            CTFont::wrap_under_create_rule(core_text_font_ref)
+        };
+        Ok(Font {
+            core_text_font,
+            font_data: self.font_data.clone(),
+            advance_cache: self.advance_cache.emptied(),
+            outline_cache: self.outline_cache.emptied(),
+            handle: self.handle.clone(),
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
+        })
+    }
+
+    /// Returns a copy of this font instanced at the named instance `instance_index` of its `fvar`
+    /// table.
+    #[inline]
+    pub fn with_named_instance(&self, instance_index: u16) -> Result<Font, FontLoadingError> {
+        <Self as Loader>::with_named_instance(self, instance_index)
+    }
+
+    // Returns the variation axis values of the font, keyed by axis tag, or `None` if it has no
+    // variation axes.
+    fn variation(&self) -> Option<CFDictionary<CFNumber, CFNumber>> {
+        unsafe {
+            let variation = CTFontCopyVariation(self.core_text_font.as_concrete_TypeRef());
+            if variation.is_null() {
+                None
+            } else {
+                Some(CFDictionary::wrap_under_create_rule(variation))
+            }
         }
     }
 
+    // Returns the current values of the variation axes in user units, as `(tag, value)` pairs.
+    fn variation_values(&self) -> Vec<(u32, f32)> {
+        let variation = match self.variation() {
+            Some(variation) => variation,
+            None => return vec![],
+        };
+        let (tags, values) = variation.get_keys_and_values();
+        tags.into_iter()
+            .zip(values)
+            .filter_map(|(tag, value)| unsafe {
+                let tag = CFNumber::wrap_under_get_rule(tag as CFNumberRef);
+                let value = CFNumber::wrap_under_get_rule(value as CFNumberRef);
+                Some((tag.to_i64()? as u32, value.to_f64()? as f32))
+            })
+            .collect()
+    }
+
+    /// Returns the current value of every variation axis, in user units and normalized.
+    ///
+    /// Axes are at their defaults unless the font was instanced with `with_variations()`.
+    pub fn variation_coordinates(&self) -> Vec<VariationCoordinate> {
+        loader::variation_coordinates(self, &self.variation_values())
+    }
+
+    /// Converts a value of the variation axis `tag` from user units to the normalized coordinate,
+    /// applying the font's `avar` mapping. Axes that the font doesn't have give 0.
+    #[inline]
+    pub fn normalize_variation(&self, tag: u32, user_value: f32) -> f32 {
+        <Self as Loader>::normalize_variation(self, tag, user_value)
+    }
+
+    /// Converts a normalized coordinate of the variation axis `tag` back to user units, or returns
+    /// `None` if the font doesn't have the axis.
+    #[inline]
+    pub fn denormalize_variation(&self, tag: u32, normalized_value: f32) -> Option<f32> {
+        <Self as Loader>::denormalize_variation(self, tag, normalized_value)
+    }
+
     /// Creates a font from a Core Graphics font handle.
     ///
     /// This function is only available on the Core Text backend.
@@ -156,25 +408,26 @@ impl Font {
         if let Ok(font_count) = read_number_of_fonts_from_otc_header(&font_data) {
             return Ok(FileType::Collection(font_count));
         }
-        match core_text::font::new_from_buffer(&*font_data) {
-            Ok(_) => Ok(FileType::Single),
-            Err(_) => Err(FontLoadingError::Parse),
-        }
+        new_core_text_font_from_data(&font_data)?;
+        Ok(FileType::Single)
+    }
+
+    /// Determines whether a seekable stream holds a supported font, and, if so, what type of font
+    /// it is.
+    ///
+    /// The stream is rewound first and read into memory in its entirety.
+    #[inline]
+    pub fn analyze_stream<R>(stream: &mut R) -> Result<FileType, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        <Self as Loader>::analyze_stream(stream)
     }
 
     /// Determines whether a file represents a supported font, and if so, what type of font it is.
+    #[inline]
     pub fn analyze_file(file: &mut File) -> Result<FileType, FontLoadingError> {
-        file.seek(SeekFrom::Start(0))?;
-
-        let font_data = Arc::new(utils::slurp_file(file).map_err(FontLoadingError::Io)?);
-        if let Ok(font_count) = read_number_of_fonts_from_otc_header(&font_data) {
-            return Ok(FileType::Collection(font_count));
-        }
-
-        match core_text::font::new_from_buffer(&*font_data) {
-            Ok(_) => Ok(FileType::Single),
-            Err(_) => Err(FontLoadingError::Parse),
-        }
+        Font::analyze_stream(file)
     }
 
     /// Determines whether a path points to a supported font, and if so, what type of font it is.
@@ -183,7 +436,8 @@ impl Font {
         <Self as Loader>::analyze_path(path)
     }
 
-    /// Returns the wrapped native font handle.
+    /// Returns a new reference to the wrapped native font handle, which is released when it is
+    /// dropped.
     #[inline]
     pub fn native_font(&self) -> NativeFont {
         self.core_text_font.clone()
@@ -216,13 +470,42 @@ impl Font {
     }
 
     /// Returns true if and only if the font is monospace (fixed-width).
-    #[inline]
+    ///
+    /// This trusts the `post` table's `isFixedPitch` field, and Core Text's symbolic traits for
+    /// fonts without a `post` table.
     pub fn is_monospace(&self) -> bool {
-        self.core_text_font.symbolic_traits().is_monospace()
+        loader::post_fixed_pitch(self)
+            .unwrap_or_else(|| self.core_text_font.symbolic_traits().is_monospace())
+    }
+
+    /// Returns the raw signals from which a font can be judged monospace, for callers that want
+    /// to apply their own judgment. See `PitchInfo::is_monospace()`.
+    ///
+    /// These are measured on first use and then cached.
+    #[inline]
+    pub fn pitch_info(&self) -> PitchInfo {
+        *self
+            .pitch_info
+            .get_or_init(|| loader::measure_pitch_info(self))
+    }
+
+    /// Returns the string with the given name ID from the font's `name` table, in the language
+    /// of the system locale if the font has it.
+    #[inline]
+    pub fn name_string(&self, name_id: u16) -> Option<String> {
+        <Self as Loader>::name_string(self, name_id)
     }
 
     /// Returns the values of various font properties, corresponding to those defined in CSS.
+    ///
+    /// These are read on first use and then cached.
+    #[inline]
     pub fn properties(&self) -> Properties {
+        *self.properties.get_or_init(|| self.read_properties())
+    }
+
+    // Reads the properties that `properties()` caches.
+    fn read_properties(&self) -> Properties {
         let symbolic_traits = self.core_text_font.symbolic_traits();
         let all_traits = self.core_text_font.all_traits();
 
@@ -237,11 +520,14 @@ impl Font {
         let weight = core_text_to_css_font_weight(all_traits.normalized_weight() as f32);
         let stretch = core_text_width_to_css_stretchiness(all_traits.normalized_width() as f32);
 
-        Properties {
-            style,
-            weight,
-            stretch,
-        }
+        loader::properties_with_variations(
+            self,
+            Properties {
+                style,
+                weight,
+                stretch,
+            },
+        )
     }
 
     /// Returns the number of glyphs in the font.
@@ -272,6 +558,35 @@ impl Font {
         }
     }
 
+    /// Returns the usual glyph ID for each Unicode scalar value in `text`, in order.
+    ///
+    /// The whole string is mapped with a single call to Core Text.
+    pub fn glyphs_for_str(&self, text: &str) -> Vec<Option<u32>> {
+        let characters: Vec<u16> = text.encode_utf16().collect();
+        let mut glyphs = vec![0; characters.len()];
+        unsafe {
+            self.core_text_font.get_glyphs_for_characters(
+                characters.as_ptr(),
+                glyphs.as_mut_ptr(),
+                characters.len() as _,
+            );
+        }
+
+        // The glyph for a surrogate pair is stored at the index of its first code unit.
+        let mut index = 0;
+        text.chars()
+            .map(|character| {
+                let id = glyphs[index] as u32;
+                index += character.len_utf16();
+                if id != 0 {
+                    Some(id)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
     /// Returns the glyph ID for the specified glyph name.
     #[inline]
     pub fn glyph_by_name(&self, name: &str) -> Option<u32> {
@@ -287,6 +602,47 @@ impl Font {
     ///
     /// TODO(pcwalton): What should we do for bitmap glyphs?
     pub fn outline<S>(
+        &self,
+        glyph_id: u32,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        if !self.outline_cache.is_enabled() {
+            return self.outline_uncached(glyph_id, hinting, sink);
+        }
+        let outline = self.outline_cache.get_or_try_insert_with(glyph_id, || {
+            let mut builder = OutlineBuilder::new();
+            self.outline_uncached(glyph_id, hinting, &mut builder)?;
+            Ok(Arc::new(builder.into_outline()))
+        })?;
+        outline.copy_to(sink);
+        Ok(())
+    }
+
+    /// Sends the vector path for a glyph to a sink, in the pixel coordinates that
+    /// `rasterize_glyph()` draws it in, so that overlays line up with the rasterized glyph.
+    ///
+    /// See `Loader::outline_at_size()` for how the path relates to `outline()`.
+    #[inline]
+    pub fn outline_at_size<S>(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        origin: Vector2F,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        <Self as Loader>::outline_at_size(self, glyph_id, point_size, origin, hinting, sink)
+    }
+
+    // Sends the outline of a glyph to `sink`, bypassing the outline cache.
+    fn outline_uncached<S>(
         &self,
         glyph_id: u32,
         _: HintingOptions,
@@ -295,37 +651,44 @@ impl Font {
     where
         S: OutlineSink,
     {
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
         let path = match self
             .core_text_font
-            .create_path_for_glyph(glyph_id as u16, &CG_AFFINE_TRANSFORM_IDENTITY)
+            .create_path_for_glyph(glyph_index, &CG_AFFINE_TRANSFORM_IDENTITY)
         {
             Ok(path) => path,
             Err(_) => {
                 // This will happen if the path is empty (rdar://42832439). To distinguish this
                 // case from the case in which the glyph does not exist, call another API.
                 self.typographic_bounds(glyph_id)?;
+                // Glyphs in bitmap-only fonts have no path either.
+                if !loader::has_outline_tables(self) {
+                    return Err(GlyphLoadingError::NoOutline);
+                }
                 return Ok(());
             }
         };
 
-        let units_per_point = self.units_per_point() as f32;
+        let units_per_point = self.units_per_point();
         path.apply(&|element| {
             let points = element.points();
             match element.element_type {
                 CGPathElementType::MoveToPoint => {
-                    sink.move_to(points[0].to_vector() * units_per_point)
+                    sink.move_to(points[0].to_vector_scaled(units_per_point))
                 }
                 CGPathElementType::AddLineToPoint => {
-                    sink.line_to(points[0].to_vector() * units_per_point)
+                    sink.line_to(points[0].to_vector_scaled(units_per_point))
                 }
                 CGPathElementType::AddQuadCurveToPoint => sink.quadratic_curve_to(
-                    points[0].to_vector() * units_per_point,
-                    points[1].to_vector() * units_per_point,
+                    points[0].to_vector_scaled(units_per_point),
+                    points[1].to_vector_scaled(units_per_point),
                 ),
                 CGPathElementType::AddCurveToPoint => {
-                    let ctrl = LineSegment2F::new(points[0].to_vector(), points[1].to_vector())
-                        * units_per_point;
-                    sink.cubic_curve_to(ctrl, points[2].to_vector() * units_per_point)
+                    let ctrl = LineSegment2F::new(
+                        points[0].to_vector_scaled(units_per_point),
+                        points[1].to_vector_scaled(units_per_point),
+                    );
+                    sink.cubic_curve_to(ctrl, points[2].to_vector_scaled(units_per_point))
                 }
                 CGPathElementType::CloseSubpath => sink.close(),
             }
@@ -335,68 +698,115 @@ impl Font {
 
     /// Returns the boundaries of a glyph in font units.
     pub fn typographic_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
         let rect = self
             .core_text_font
-            .get_bounding_rects_for_glyphs(kCTFontDefaultOrientation, &[glyph_id as u16]);
-        let rect = RectF::new(
-            Vector2F::new(rect.origin.x as f32, rect.origin.y as f32),
-            Vector2F::new(rect.size.width as f32, rect.size.height as f32),
-        );
-        Ok(rect * self.units_per_point() as f32)
+            .get_bounding_rects_for_glyphs(kCTFontDefaultOrientation, &[glyph_index]);
+        Ok(cg_rect_to_units(&rect, self.units_per_point()))
+    }
+
+    /// Returns the exact bounding box of the glyph outline in font units.
+    ///
+    /// Unlike `typographic_bounds()`, this walks the outline, so it tightly encloses the ink.
+    #[inline]
+    pub fn outline_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        <Self as Loader>::outline_bounds(self, glyph_id)
+    }
+
+    /// Returns true if the glyph has no contours, as for the space, so that `outline()` would send
+    /// nothing to its sink.
+    ///
+    /// Core Text has no path for such glyphs, so this only asks for the path, without walking it.
+    pub fn glyph_is_empty(&self, glyph_id: u32) -> Result<bool, GlyphLoadingError> {
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
+        match self
+            .core_text_font
+            .create_path_for_glyph(glyph_index, &CG_AFFINE_TRANSFORM_IDENTITY)
+        {
+            Ok(_) => Ok(false),
+            // Glyphs in bitmap-only fonts have no path either.
+            Err(_) if !loader::has_outline_tables(self) => Err(GlyphLoadingError::NoOutline),
+            Err(_) => Ok(true),
+        }
+    }
+
+    /// Returns the tight bounding box of the ink of `text` set on a single line at `point_size`,
+    /// in points, optionally applying `pair_kerning()` between glyphs.
+    #[inline]
+    pub fn string_ink_bounds(&self, text: &str, point_size: f32, apply_kerning: bool) -> RectF {
+        <Self as Loader>::string_ink_bounds(self, text, point_size, apply_kerning)
+    }
+
+    /// Returns the horizontal kerning to add between the glyphs `left` and `right`, in font units,
+    /// from the `GPOS`, `kerx`, or `kern` table.
+    #[inline]
+    pub fn pair_kerning(&self, left: u32, right: u32) -> Option<f32> {
+        <Self as Loader>::pair_kerning(self, left, right)
+    }
+
+    /// Returns true if the font has any kerning data in its `GPOS`, `kerx`, or `kern` table.
+    #[inline]
+    pub fn has_kerning(&self) -> bool {
+        *self
+            .has_kerning
+            .get_or_init(|| loader::has_kerning_data(self))
+    }
+
+    /// Reports which OpenType and AAT layout tables the font contains.
+    #[inline]
+    pub fn shaping_tables(&self) -> ShapingTables {
+        <Self as Loader>::shaping_tables(self)
     }
 
     /// Returns the distance from the origin of the glyph with the given ID to the next, in font
     /// units.
     pub fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
-        // FIXME(pcwalton): Apple's docs don't say what happens when the glyph is out of range!
-        unsafe {
-            let (glyph_id, mut advance) = (glyph_id as u16, CG_ZERO_SIZE);
-            self.core_text_font.get_advances_for_glyphs(
-                kCTFontDefaultOrientation,
-                &glyph_id,
-                &mut advance,
-                1,
-            );
-            let advance = Vector2F::new(advance.width as f32, advance.height as f32);
-            Ok(advance * self.units_per_point() as f32)
-        }
+        self.advance_cache
+            .get_or_try_insert_with(glyph_id, || unsafe {
+                let glyph_id = loader::checked_glyph_id(self, glyph_id)?;
+                let mut advance = CG_ZERO_SIZE;
+                self.core_text_font.get_advances_for_glyphs(
+                    kCTFontDefaultOrientation,
+                    &glyph_id,
+                    &mut advance,
+                    1,
+                );
+                Ok(advance.to_vector_scaled(self.units_per_point()))
+            })
     }
 
     /// Returns the amount that the given glyph should be displaced from the origin.
     pub fn origin(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+        let glyph_id = loader::checked_glyph_id(self, glyph_id)?;
         unsafe {
-            // FIXME(pcwalton): Apple's docs don't say what happens when the glyph is out of range!
-            let (glyph_id, mut translation) = (glyph_id as u16, CG_ZERO_SIZE);
+            let mut translation = CG_ZERO_SIZE;
             self.core_text_font.get_vertical_translations_for_glyphs(
                 kCTFontDefaultOrientation,
                 &glyph_id,
                 &mut translation,
                 1,
             );
-            let translation = Vector2F::new(translation.width as f32, translation.height as f32);
-            Ok(translation * self.units_per_point() as f32)
+            Ok(translation.to_vector_scaled(self.units_per_point()))
         }
     }
 
     /// Retrieves various metrics that apply to the entire font.
     pub fn metrics(&self) -> Metrics {
-        let units_per_em = self.core_text_font.units_per_em();
+        let units_per_em = loader::sanitize_units_per_em(self.core_text_font.units_per_em());
         let units_per_point = (units_per_em as f64) / self.core_text_font.pt_size();
 
-        let bounding_box = self.core_text_font.bounding_box();
-        let bounding_box = RectF::new(
-            Vector2F::new(bounding_box.origin.x as f32, bounding_box.origin.y as f32),
-            Vector2F::new(
-                bounding_box.size.width as f32,
-                bounding_box.size.height as f32,
-            ),
-        );
-        let bounding_box = bounding_box * units_per_point as f32;
+        let bounding_box = cg_rect_to_units(&self.core_text_font.bounding_box(), units_per_point);
+
+        let ascent = (self.core_text_font.ascent() * units_per_point) as f32;
+        let descent = (-self.core_text_font.descent() * units_per_point) as f32;
+        let (win_ascent, win_descent) = loader::win_metrics(self).unwrap_or((ascent, descent));
 
         Metrics {
             units_per_em,
-            ascent: (self.core_text_font.ascent() * units_per_point) as f32,
-            descent: (-self.core_text_font.descent() * units_per_point) as f32,
+            ascent,
+            descent,
+            win_ascent,
+            win_descent,
             line_gap: (self.core_text_font.leading() * units_per_point) as f32,
             underline_position: (self.core_text_font.underline_position() * units_per_point) as f32,
             underline_thickness: (self.core_text_font.underline_thickness() * units_per_point)
@@ -407,12 +817,45 @@ impl Font {
         }
     }
 
+    /// Returns the font's metrics, measuring the cap height and x-height from glyphs if the font
+    /// doesn't declare them.
+    ///
+    /// See `Loader::metrics_with_fallbacks()`.
+    #[inline]
+    pub fn metrics_with_fallbacks(&self) -> (Metrics, MetricsSource) {
+        <Self as Loader>::metrics_with_fallbacks(self)
+    }
+
+    /// Returns the font's metrics rescaled to `units_per_em` units per em.
+    #[inline]
+    pub fn metrics_normalized(&self, units_per_em: u32) -> Metrics {
+        <Self as Loader>::metrics_normalized(self, units_per_em)
+    }
+
+    /// Returns a view of this font that reports values in font units for an em `units_per_em`
+    /// units wide.
+    #[inline]
+    pub fn normalized(&self, units_per_em: u32) -> NormalizedFont<'_, Font> {
+        <Self as Loader>::normalized(self, units_per_em)
+    }
+
     /// Returns a handle to this font, if possible.
     ///
     /// This is useful if you want to open the font with a different loader.
     #[inline]
     pub fn handle(&self) -> Option<Handle> {
-        <Self as Loader>::handle(self)
+        self.handle
+            .clone()
+            .or_else(|| <Self as Loader>::handle(self))
+    }
+
+    /// Returns the path of the file that this font was loaded from, if it was loaded from a path.
+    #[inline]
+    pub fn source_path(&self) -> Option<&Path> {
+        match self.handle {
+            Some(Handle::Path { ref path, .. }) => Some(path),
+            _ => None,
+        }
     }
 
     /// Attempts to return the raw font data (contents of the font file).
@@ -426,9 +869,24 @@ impl Font {
         }
     }
 
+    /// Returns the byte offset of this font's table directory within the data that
+    /// `copy_font_data()` returns.
+    ///
+    /// Core Text can't load collections, so this loader moves the table directory of the chosen
+    /// font to the start of its copy of the data, and this returns 0 for fonts loaded from
+    /// collections too.
+    #[inline]
+    pub fn face_byte_offset(&self) -> Option<u32> {
+        <Self as Loader>::face_byte_offset(self)
+    }
+
     /// Returns the pixel boundaries that the glyph will take up when rendered using this loader's
     /// rasterizer at the given size and transform.
-    #[inline]
+    ///
+    /// Core Graphics doesn't report what it draws, so these are the glyph's typographic bounds,
+    /// grown by a pixel on each side when font smoothing is on. That margin is an estimate: font
+    /// smoothing emboldens glyphs by less than a pixel at text sizes, so the bounds may be a
+    /// pixel larger than the ink, but they shouldn't clip it.
     pub fn raster_bounds(
         &self,
         glyph_id: u32,
@@ -437,14 +895,16 @@ impl Font {
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
     ) -> Result<RectI, GlyphLoadingError> {
-        <Self as Loader>::raster_bounds(
-            self,
-            glyph_id,
-            point_size,
-            transform,
-            hinting_options,
-            rasterization_options,
-        )
+        let (_, rasterization_options) =
+            loader::resolve_font_default_hinting(self, hinting_options, rasterization_options);
+        let bounds = loader::typographic_raster_bounds(self, glyph_id, point_size, transform)?;
+        match rasterization_options {
+            RasterizationOptions::Bilevel => Ok(bounds),
+            // Font smoothing emboldens the glyph, which can spill a pixel past its outline.
+            RasterizationOptions::GrayscaleAa | RasterizationOptions::SubpixelAa => {
+                Ok(bounds.contract(Vector2I::splat(-1)))
+            }
+        }
     }
 
     /// Rasterizes a glyph to a canvas with the given size and origin.
@@ -467,9 +927,13 @@ impl Font {
         transform: Transform2F,
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
-    ) -> Result<(), GlyphLoadingError> {
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
+        let (hinting_options, rasterization_options) =
+            loader::resolve_font_default_hinting(self, hinting_options, rasterization_options);
+        let source = self.glyph_source();
         if canvas.size.x() == 0 || canvas.size.y() == 0 {
-            return Ok(());
+            return Ok(RasterizedGlyph::new(canvas, RectI::default(), source));
         }
 
         let (cg_color_space, cg_image_format) =
@@ -480,8 +944,12 @@ impl Font {
                     //
                     // FIXME(pcwalton): Could improve this by only allocating a canvas with a tight
                     // bounding rect and blitting only that part.
-                    let mut temp_canvas = Canvas::new(canvas.size, Format::Rgba32);
-                    self.rasterize_glyph(
+                    let temp_format = match canvas.format {
+                        Format::A16 => Format::A8,
+                        _ => Format::Rgba32,
+                    };
+                    let mut temp_canvas = Canvas::new(canvas.size, temp_format);
+                    let rasterized_glyph = self.rasterize_glyph(
                         &mut temp_canvas,
                         glyph_id,
                         point_size,
@@ -490,7 +958,11 @@ impl Font {
                         rasterization_options,
                     )?;
                     canvas.blit_from_canvas(&temp_canvas);
-                    return Ok(());
+                    return Ok(RasterizedGlyph::new(
+                        canvas,
+                        rasterized_glyph.ink_rect,
+                        rasterized_glyph.source,
+                    ));
                 }
                 Some(cg_color_space_and_format) => cg_color_space_and_format,
             };
@@ -509,7 +981,7 @@ impl Font {
             Format::Rgba32 | Format::Rgb24 => {
                 core_graphics_context.set_rgb_fill_color(0.0, 0.0, 0.0, 0.0);
             }
-            Format::A8 => core_graphics_context.set_gray_fill_color(0.0, 0.0),
+            Format::A8 | Format::A16 => core_graphics_context.set_gray_fill_color(0.0, 0.0),
         }
 
         let core_graphics_size = CGSize::new(canvas.size.x() as f64, canvas.size.y() as f64);
@@ -517,9 +989,15 @@ impl Font {
 
         match rasterization_options {
             RasterizationOptions::Bilevel => {
+                // Aliased glyphs gain nothing from subpixel positioning, and leaving it on would
+                // shift glyphs drawn at integer origins off the pixel grid.
                 core_graphics_context.set_allows_font_smoothing(false);
                 core_graphics_context.set_should_smooth_fonts(false);
                 core_graphics_context.set_should_antialias(false);
+                core_graphics_context.set_allows_font_subpixel_positioning(false);
+                core_graphics_context.set_should_subpixel_position_fonts(false);
+                core_graphics_context.set_allows_font_subpixel_quantization(false);
+                core_graphics_context.set_should_subpixel_quantize_fonts(false);
             }
             RasterizationOptions::GrayscaleAa | RasterizationOptions::SubpixelAa => {
                 // FIXME(pcwalton): These shouldn't be handled the same!
@@ -533,13 +1011,11 @@ impl Font {
             Format::Rgba32 | Format::Rgb24 => {
                 core_graphics_context.set_rgb_fill_color(1.0, 1.0, 1.0, 1.0);
             }
-            Format::A8 => core_graphics_context.set_gray_fill_color(1.0, 1.0),
+            Format::A8 | Format::A16 => core_graphics_context.set_gray_fill_color(1.0, 1.0),
         }
 
         // CoreGraphics origin is in the bottom left. This makes behavior consistent.
         core_graphics_context.translate(0.0, canvas.size.y() as CGFloat);
-        core_graphics_context.set_font(&self.core_text_font.copy_to_CGFont());
-        core_graphics_context.set_font_size(point_size as CGFloat);
         core_graphics_context.set_text_drawing_mode(CGTextDrawingMode::CGTextFill);
         let matrix = transform.matrix.0 * F32x4::new(1.0, -1.0, -1.0, 1.0);
         core_graphics_context.set_text_matrix(&CGAffineTransform {
@@ -550,10 +1026,82 @@ impl Font {
             tx: transform.vector.x() as CGFloat,
             ty: -transform.vector.y() as CGFloat,
         });
+        // Draw with the Core Text font rather than a `CGFont` copied from it, which would lose
+        // any variations and disagree with the outlines and advances.
         let origin = CGPoint::new(0.0, 0.0);
-        core_graphics_context.show_glyphs_at_positions(&[glyph_id as CGGlyph], &[origin]);
+        self.core_text_font
+            .clone_with_font_size(point_size as CGFloat)
+            .draw_glyphs(&[glyph_index], &[origin], core_graphics_context.clone());
 
-        Ok(())
+        // Core Graphics doesn't report what it touched, so use the bounds that the glyph is
+        // expected to cover.
+        let ink_rect = self.raster_bounds(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )?;
+        Ok(RasterizedGlyph::new(canvas, ink_rect, source))
+    }
+
+    // Core Text doesn't tell us how it rendered a glyph, so infer it from the font's tables.
+    fn glyph_source(&self) -> GlyphSource {
+        if (self.core_text_font.symbolic_traits() & kCTFontColorGlyphsTrait) == 0 {
+            GlyphSource::Outline
+        } else if self.core_text_font.get_font_table(SBIX_HEX).is_some() {
+            GlyphSource::EmbeddedBitmap
+        } else {
+            GlyphSource::ColorLayers
+        }
+    }
+
+    /// Returns the pixel boundaries that the glyph will take up when rendered using this loader's
+    /// rasterizer at the given per-axis size and transform.
+    #[inline]
+    pub fn raster_bounds_scaled(
+        &self,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        <Self as Loader>::raster_bounds_scaled(
+            self,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph to a canvas with the given per-axis size and transform.
+    ///
+    /// The x component of `point_size` is the horizontal size and the y component is the vertical
+    /// size. The glyph is drawn at the vertical size, and the horizontal stretch is folded into
+    /// `transform` and so into the text matrix. Core Graphics doesn't hint, so this looks the same
+    /// as scaling the outline.
+    #[inline]
+    pub fn rasterize_glyph_scaled(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_scaled(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
     }
 
     /// Returns true if and only if the font loader can perform hinting in the requested way.
@@ -568,38 +1116,499 @@ impl Font {
             HintingOptions::None => true,
             HintingOptions::Vertical(..)
             | HintingOptions::VerticalSubpixel(..)
-            | HintingOptions::Full(..) => false,
+            | HintingOptions::Full(..)
+            | HintingOptions::FontDefault(..) => false,
         }
     }
 
     /// Get font fallback results for the given text and locale.
     ///
-    /// Note: this is currently just a stub implementation, a proper implementation
-    /// would use CTFontCopyDefaultCascadeListForLanguages.
-    fn get_fallbacks(&self, text: &str, _locale: &str) -> FallbackResult<Font> {
-        warn!("unsupported");
-        FallbackResult {
-            fonts: Vec::new(),
-            valid_len: text.len(),
+    /// The `locale` argument is a language tag such as `"en-US"` or `"zh-Hans-CN"`, which selects
+    /// the language-specific cascade list (for example, PingFang SC versus Hiragino Sans for Han
+    /// characters). Pass an empty string to use the user's preferred languages.
+    ///
+    /// The fonts are the entries of the cascade list that support the first character of `text`,
+    /// in cascade order. The result is valid for the run of characters the first of them supports.
+    fn get_fallbacks(&self, text: &str, locale: &str) -> FallbackResult<Font> {
+        let first_char = match text.chars().next() {
+            Some(first_char) => first_char,
+            None => {
+                return FallbackResult {
+                    fonts: Vec::new(),
+                    valid_len: 0,
+                }
+            }
+        };
+
+        let languages = if locale.is_empty() {
+            CFArray::from_CFTypes(&[])
+        } else {
+            CFArray::from_CFTypes(&[CFString::new(locale)])
+        };
+        let cascade_list =
+            core_text::font::cascade_list_for_languages(&self.core_text_font, &languages);
+
+        let mut fonts = vec![];
+        for descriptor in cascade_list.iter() {
+            let core_text_font =
+                core_text::font::new_from_descriptor(&descriptor, self.core_text_font.pt_size());
+            let font = unsafe { Font::from_core_text_font_no_path(core_text_font) };
+            if font.glyph_for_char(first_char).is_some() {
+                fonts.push(FallbackFont { font, scale: 1.0 });
+            }
         }
+
+        let valid_len = match fonts.first() {
+            Some(fallback_font) => text
+                .char_indices()
+                .find(|&(_, character)| fallback_font.font.glyph_for_char(character).is_none())
+                .map_or(text.len(), |(index, _)| index),
+            None => first_char.len_utf8(),
+        };
+        FallbackResult { fonts, valid_len }
     }
 
     #[inline]
     fn units_per_point(&self) -> f64 {
-        (self.core_text_font.units_per_em() as f64) / self.core_text_font.pt_size()
+        let units_per_em = loader::sanitize_units_per_em(self.core_text_font.units_per_em());
+        (units_per_em as f64) / self.core_text_font.pt_size()
     }
 
-    /// Returns the raw contents of the OpenType table with the given tag.
-    ///
-    /// Tags are four-character codes. A list of tags can be found in the [OpenType specification].
+    /// Returns the size-dependent tracking adjustment from the AAT `trak` table, in font units.
     ///
-    /// [OpenType specification]: https://docs.microsoft.com/en-us/typography/opentype/spec/
+    /// Add this to the advance of every glyph to match native macOS text layout.
     #[inline]
-    pub fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>> {
-        self.core_text_font
-            .get_font_table(table_tag)
+    pub fn tracking(&self, point_size: f32) -> Option<f32> {
+        <Self as Loader>::tracking(self, point_size)
+    }
+
+    /// Returns the slope and offset of the text cursor for this font.
+    #[inline]
+    pub fn caret_metrics(&self) -> CaretMetrics {
+        <Self as Loader>::caret_metrics(self)
+    }
+
+    /// Returns the sizes and offsets that the font recommends for subscripts and superscripts, or
+    /// `None` if the font has no `OS/2` table.
+    #[inline]
+    pub fn script_metrics(&self) -> Option<ScriptMetrics> {
+        <Self as Loader>::script_metrics(self)
+    }
+
+    /// Returns the pixel boundaries that the glyph will take up when rendered like
+    /// `rasterize_glyph_with_pixels_per_dip()`.
+    #[inline]
+    pub fn raster_bounds_with_pixels_per_dip(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        <Self as Loader>::raster_bounds_with_pixels_per_dip(
+            self,
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph for a display with `pixels_per_dip` physical pixels per
+    /// device-independent pixel, grid-fitting it to the physical pixel grid.
+    ///
+    /// `point_size` and the size in `hinting_options` are in device-independent pixels.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_pixels_per_dip(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_pixels_per_dip(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Renders a glyph into a new canvas sized to fit it, with the options in `settings`.
+    ///
+    /// `origin` is the position of the glyph origin within the pixel grid, in physical pixels.
+    #[inline]
+    pub fn render(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        origin: Vector2F,
+        settings: &RenderSettings,
+    ) -> Result<RenderedGlyph, GlyphLoadingError> {
+        <Self as Loader>::render(self, glyph_id, point_size, origin, settings)
+    }
+
+    /// Renders a glyph whose origin is at `position` in layout (device-independent) pixels, for
+    /// display at the `pixels_per_dip` in `settings`.
+    #[inline]
+    pub fn render_at(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        position: Vector2F,
+        settings: &RenderSettings,
+    ) -> Result<PlacedGlyph, GlyphLoadingError> {
+        <Self as Loader>::render_at(self, glyph_id, point_size, position, settings)
+    }
+
+    /// Returns the layers of the glyph if it is a version 0 color glyph from the `COLR` table.
+    #[inline]
+    pub fn color_glyph_layers(&self, glyph_id: u32) -> Option<Vec<ColorLayer>> {
+        <Self as Loader>::color_glyph_layers(self, glyph_id)
+    }
+
+    /// Returns the colors of the palette at `palette_index` in the `CPAL` table.
+    #[inline]
+    pub fn palette(&self, palette_index: u16) -> Option<Vec<ColorU>> {
+        <Self as Loader>::palette(self, palette_index)
+    }
+
+    /// Returns the colors that `selector` picks: the chosen `CPAL` palette with the selector's
+    /// overrides applied.
+    #[inline]
+    pub fn selected_palette(&self, selector: &PaletteSelector) -> Vec<ColorU> {
+        <Self as Loader>::selected_palette(self, selector)
+    }
+
+    /// Returns the layers of a version 0 color glyph, each paired with the color that `selector`
+    /// picks for it.
+    #[inline]
+    pub fn color_glyph_layers_with_palette(
+        &self,
+        glyph_id: u32,
+        selector: &PaletteSelector,
+    ) -> Option<Vec<(ColorLayer, ColorU)>> {
+        <Self as Loader>::color_glyph_layers_with_palette(self, glyph_id, selector)
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, drawing `COLR` color glyphs (including
+    /// version 1 gradients, transforms, and compositing) in color on `Rgba32` canvases.
+    #[inline]
+    pub fn rasterize_color_glyph(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_color_glyph(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_color_glyph()`, drawing color glyphs with the colors
+    /// that `selector` picks.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_color_glyph_with_palette(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        selector: &PaletteSelector,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_color_glyph_with_palette(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            selector,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, choosing whether to draw it from an embedded
+    /// bitmap strike. Core Text chooses strikes on its own,
+    /// so `prefer_bitmaps` has no effect.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_bitmap_preference(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        prefer_bitmaps: bool,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_bitmap_preference(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            prefer_bitmaps,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, producing `Rgba32` output with the requested
+    /// alpha representation.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_alpha_mode(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        alpha_mode: AlphaMode,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_alpha_mode(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            alpha_mode,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, touching only the pixels inside `clip`.
+    ///
+    /// Passing `None` for `clip` is equivalent to calling `rasterize_glyph()`.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_clipped(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        clip: Option<RectI>,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_clipped(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            clip,
+        )
+    }
+
+    /// Returns the named values on this font's design axes, from the OpenType `STAT` table.
+    ///
+    /// Fonts without a `STAT` table return an empty list.
+    #[inline]
+    pub fn style_attributes(&self) -> Vec<StatAxisValue> {
+        <Self as Loader>::style_attributes(self)
+    }
+
+    /// Returns the components of a TrueType composite glyph, each with the transform that places
+    /// it.
+    ///
+    /// Simple glyphs and glyphs in fonts without a `glyf` table return `None`.
+    #[inline]
+    pub fn composite_components(&self, glyph_id: u32) -> Option<Vec<Component>> {
+        <Self as Loader>::composite_components(self, glyph_id)
+    }
+
+    /// Returns the contours of a simple TrueType glyph as stored in the `glyf` table, in font
+    /// units, optionally with the implied on-curve points inserted.
+    ///
+    /// Composite glyphs and glyphs in fonts without a `glyf` table return `None`.
+    #[inline]
+    pub fn glyf_contours(&self, glyph_id: u32, implied_points: ImpliedPoints) -> Option<Outline> {
+        <Self as Loader>::glyf_contours(self, glyph_id, implied_points)
+    }
+
+    /// Returns the contours of a simple TrueType glyph along with the index of each point in the
+    /// `glyf` point ordering that hinting instructions refer to.
+    #[inline]
+    pub fn glyf_contours_with_point_indices(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Option<IndexedOutline> {
+        <Self as Loader>::glyf_contours_with_point_indices(self, glyph_id, implied_points)
+    }
+
+    /// Returns the outline of a TrueType glyph from the `glyf` table in font units, with
+    /// composite glyphs flattened by transforming their components into place.
+    ///
+    /// Fonts without a `glyf` table return `GlyphLoadingError::NoOutline`, and malformed composite
+    /// glyphs, including ones that refer to themselves, return `GlyphLoadingError::Parse`.
+    #[inline]
+    pub fn glyf_outline(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Result<Outline, GlyphLoadingError> {
+        <Self as Loader>::glyf_outline(self, glyph_id, implied_points)
+    }
+
+    /// Returns every Unicode code point that the font maps to a glyph, paired with that glyph's
+    /// ID and sorted by code point.
+    ///
+    /// Code points mapped to `.notdef` are omitted.
+    #[inline]
+    pub fn char_to_glyph_mappings(&self) -> Vec<(u32, u32)> {
+        <Self as Loader>::char_to_glyph_mappings(self)
+    }
+
+    /// Returns an iterator over every character that the font maps to a glyph, paired with that
+    /// glyph's ID, in ascending order.
+    #[inline]
+    pub fn char_mappings(&self) -> CharMappingIter {
+        <Self as Loader>::char_mappings(self)
+    }
+
+    /// Returns the font revision from the `head` table.
+    #[inline]
+    pub fn font_revision(&self) -> Option<f32> {
+        <Self as Loader>::font_revision(self)
+    }
+
+    /// Returns the date the font was created, from the `head` table.
+    #[inline]
+    pub fn created_date(&self) -> Option<SystemTime> {
+        <Self as Loader>::created_date(self)
+    }
+
+    /// Returns the date the font was last modified, from the `head` table.
+    #[inline]
+    pub fn modified_date(&self) -> Option<SystemTime> {
+        <Self as Loader>::modified_date(self)
+    }
+
+    /// Returns the ID of the `.notdef` glyph, which is always 0.
+    #[inline]
+    pub fn notdef_glyph(&self) -> u32 {
+        <Self as Loader>::notdef_glyph(self)
+    }
+
+    /// Returns true if the `.notdef` glyph, which is drawn for characters that no font supports,
+    /// has no ink.
+    #[inline]
+    pub fn notdef_is_blank(&self) -> bool {
+        <Self as Loader>::notdef_is_blank(self)
+    }
+
+    /// Sends the outline of a conventional missing-glyph placeholder, a hollow box scaled to
+    /// `point_size`, to a path builder, and returns the box's advance.
+    #[inline]
+    pub fn synthesize_notdef<S>(&self, point_size: f32, sink: &mut S) -> Vector2F
+    where
+        S: OutlineSink,
+    {
+        <Self as Loader>::synthesize_notdef(self, point_size, sink)
+    }
+
+    /// Returns how the font recommends that glyphs be rendered at `pixels_per_em`, from its `gasp`
+    /// table.
+    ///
+    /// Fonts without a `gasp` table get the default of grid fitting with grayscale antialiasing.
+    #[inline]
+    pub fn gasp_behavior(&self, pixels_per_em: u16) -> GaspBehavior {
+        <Self as Loader>::gasp_behavior(self, pixels_per_em)
+    }
+
+    /// Returns true if the font has TrueType hinting instructions in its `fpgm`, `prep`, or
+    /// `glyf` table.
+    ///
+    /// This is computed on first use and then cached.
+    #[inline]
+    pub fn has_hinting_instructions(&self) -> bool {
+        *self
+            .has_hinting_instructions
+            .get_or_init(|| loader::has_hinting_data(self))
+    }
+
+    /// Returns the advance of the glyph in whole pixels at `pixels_per_em` from the font's `hdmx`
+    /// table.
+    #[inline]
+    pub fn hdmx_advance(&self, glyph_id: u32, pixels_per_em: u8) -> Option<u8> {
+        <Self as Loader>::hdmx_advance(self, glyph_id, pixels_per_em)
+    }
+
+    /// Returns the maximum and minimum y values, in pixels, of the font's glyphs at
+    /// `pixels_per_em` from its `VDMX` table.
+    #[inline]
+    pub fn vdmx_metrics(&self, pixels_per_em: u16) -> Option<(i16, i16)> {
+        <Self as Loader>::vdmx_metrics(self, pixels_per_em)
+    }
+
+    /// Returns the advance of the glyph in whole pixels at `pixels_per_em`, as GDI-compatible
+    /// layout computes it.
+    #[inline]
+    pub fn gdi_compatible_advance(
+        &self,
+        glyph_id: u32,
+        pixels_per_em: u16,
+    ) -> Result<f32, GlyphLoadingError> {
+        <Self as Loader>::gdi_compatible_advance(self, glyph_id, pixels_per_em)
+    }
+
+    /// Returns the raw contents of the OpenType table with the given tag.
+    ///
+    /// Tags are four-character codes. A list of tags can be found in the [OpenType specification].
+    ///
+    /// [OpenType specification]: https://docs.microsoft.com/en-us/typography/opentype/spec/
+    #[inline]
+    pub fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>> {
+        self.core_text_font
+            .get_font_table(table_tag)
             .map(|data| data.bytes().into())
     }
+
+    /// Returns true if the font has an OpenType table with the given tag, without copying it.
+    pub fn has_font_table(&self, table_tag: u32) -> bool {
+        // The array holds the tags themselves rather than CF objects.
+        self.core_text_font
+            .get_available_font_tables()
+            .is_some_and(|table_tags| {
+                table_tags
+                    .get_all_values()
+                    .iter()
+                    .any(|&tag| tag as usize as u32 == table_tag)
+            })
+    }
 }
 
 impl Loader for Font {
@@ -615,11 +1624,34 @@ impl Loader for Font {
         Font::from_file(file, font_index)
     }
 
+    #[inline]
+    fn from_path<P>(path: P, font_index: u32) -> Result<Font, FontLoadingError>
+    where
+        P: AsRef<Path>,
+    {
+        Font::from_path(path, font_index)
+    }
+
     #[inline]
     unsafe fn from_native_font(native_font: &Self::NativeFont) -> Self {
         Font::from_native_font(native_font)
     }
 
+    #[inline]
+    fn with_advance_cache(self) -> Self {
+        self.with_advance_cache()
+    }
+
+    #[inline]
+    fn with_variations(&self, variations: &[(u32, f32)]) -> Result<Font, FontLoadingError> {
+        self.with_variations(variations)
+    }
+
+    #[inline]
+    fn variation_coordinates(&self) -> Vec<VariationCoordinate> {
+        self.variation_coordinates()
+    }
+
     #[inline]
     fn analyze_bytes(font_data: Arc<Vec<u8>>) -> Result<FileType, FontLoadingError> {
         Font::analyze_bytes(font_data)
@@ -655,6 +1687,11 @@ impl Loader for Font {
         self.is_monospace()
     }
 
+    #[inline]
+    fn pitch_info(&self) -> PitchInfo {
+        self.pitch_info()
+    }
+
     #[inline]
     fn properties(&self) -> Properties {
         self.properties()
@@ -665,6 +1702,11 @@ impl Loader for Font {
         self.glyph_for_char(character)
     }
 
+    #[inline]
+    fn glyphs_for_str(&self, text: &str) -> Vec<Option<u32>> {
+        self.glyphs_for_str(text)
+    }
+
     #[inline]
     fn glyph_by_name(&self, name: &str) -> Option<u32> {
         self.glyph_by_name(name)
@@ -693,6 +1735,11 @@ impl Loader for Font {
         self.typographic_bounds(glyph_id)
     }
 
+    #[inline]
+    fn glyph_is_empty(&self, glyph_id: u32) -> Result<bool, GlyphLoadingError> {
+        self.glyph_is_empty(glyph_id)
+    }
+
     #[inline]
     fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
         self.advance(glyph_id)
@@ -713,6 +1760,26 @@ impl Loader for Font {
         self.copy_font_data()
     }
 
+    #[inline]
+    fn handle(&self) -> Option<Handle> {
+        self.handle()
+    }
+
+    #[inline]
+    fn source_path(&self) -> Option<&Path> {
+        self.source_path()
+    }
+
+    #[inline]
+    fn has_kerning(&self) -> bool {
+        self.has_kerning()
+    }
+
+    #[inline]
+    fn has_hinting_instructions(&self) -> bool {
+        self.has_hinting_instructions()
+    }
+
     #[inline]
     fn supports_hinting_options(
         &self,
@@ -722,6 +1789,24 @@ impl Loader for Font {
         self.supports_hinting_options(hinting_options, for_rasterization)
     }
 
+    #[inline]
+    fn raster_bounds(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        self.raster_bounds(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
     #[inline]
     fn rasterize_glyph(
         &self,
@@ -731,7 +1816,7 @@ impl Loader for Font {
         transform: Transform2F,
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
-    ) -> Result<(), GlyphLoadingError> {
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
         self.rasterize_glyph(
             canvas,
             glyph_id,
@@ -751,14 +1836,44 @@ impl Loader for Font {
     fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>> {
         self.load_font_table(table_tag)
     }
+
+    #[inline]
+    fn has_font_table(&self, table_tag: u32) -> bool {
+        self.has_font_table(table_tag)
+    }
 }
 
 impl Debug for Font {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), fmt::Error> {
-        self.full_name().fmt(fmt)
+        let symbolic_traits = self.core_text_font.symbolic_traits();
+        let is_color = (symbolic_traits & kCTFontColorGlyphsTrait) != 0;
+        loader::fmt_debug(self, is_color, symbolic_traits.is_monospace(), fmt)
+    }
+}
+
+// Fonts are equal if they are the same face: Core Text considers their fonts equal, as it does for
+// clones, or they load the same in-memory data at the same variation values. Separate copies of
+// the data are compared by their length and `head` table fingerprint rather than byte by byte.
+// Caches don't take part.
+impl PartialEq for Font {
+    fn eq(&self, other: &Font) -> bool {
+        if self.core_text_font == other.core_text_font {
+            return true;
+        }
+        match (&self.font_data, &other.font_data) {
+            (FontData::Memory(data), FontData::Memory(other_data)) => {
+                (Arc::ptr_eq(data, other_data)
+                    || (data.len() == other_data.len()
+                        && loader::same_head_fingerprint(self, other)))
+                    && self.variation() == other.variation()
+            }
+            _ => false,
+        }
     }
 }
 
+impl Eq for Font {}
+
 #[derive(Clone)]
 enum FontData {
     Unavailable,
@@ -775,17 +1890,37 @@ impl Deref for FontData {
     }
 }
 
+// These scale before narrowing to `f32`, so that fonts with large ems don't lose precision.
 trait CGPointExt {
-    fn to_vector(&self) -> Vector2F;
+    fn to_vector_scaled(&self, scale: f64) -> Vector2F;
 }
 
 impl CGPointExt for CGPoint {
     #[inline]
-    fn to_vector(&self) -> Vector2F {
-        Vector2F::new(self.x as f32, self.y as f32)
+    fn to_vector_scaled(&self, scale: f64) -> Vector2F {
+        Vector2F::new((self.x * scale) as f32, (self.y * scale) as f32)
+    }
+}
+
+trait CGSizeExt {
+    fn to_vector_scaled(&self, scale: f64) -> Vector2F;
+}
+
+impl CGSizeExt for CGSize {
+    #[inline]
+    fn to_vector_scaled(&self, scale: f64) -> Vector2F {
+        Vector2F::new((self.width * scale) as f32, (self.height * scale) as f32)
     }
 }
 
+// Converts a rectangle in points to font units.
+fn cg_rect_to_units(rect: &CGRect, units_per_point: f64) -> RectF {
+    RectF::new(
+        rect.origin.to_vector_scaled(units_per_point),
+        rect.size.to_vector_scaled(units_per_point),
+    )
+}
+
 fn core_text_to_css_font_weight(core_text_weight: f32) -> Weight {
     let index = piecewise_linear_find_index(core_text_weight, &FONT_WEIGHT_MAPPING);
 
@@ -799,6 +1934,16 @@ fn core_text_width_to_css_stretchiness(core_text_width: f32) -> Stretch {
     ))
 }
 
+// Creates a Core Text font that reads from `font_data` itself, rather than from a copy as
+// `core_text::font::new_from_buffer()` would.
+fn new_core_text_font_from_data(font_data: &Arc<Vec<u8>>) -> Result<CTFont, FontLoadingError> {
+    let cf_data = CFData::from_arc(font_data.clone());
+    match core_text::font_manager::create_font_descriptor_with_data(cf_data) {
+        Ok(descriptor) => Ok(core_text::font::new_from_descriptor(&descriptor, 16.0)),
+        Err(_) => Err(FontLoadingError::Parse),
+    }
+}
+
 fn font_is_collection(header: &[u8]) -> bool {
     header.len() >= 4 && header[0..4] == TTC_TAG
 }
@@ -841,7 +1986,7 @@ fn unpack_otc_font(data: &mut [u8], font_index: u32) -> Result<(), FontLoadingEr
 // NB: This assumes little-endian, but that's true for all extant Apple hardware.
 fn format_to_cg_color_space_and_image_format(format: Format) -> Option<(CGColorSpace, u32)> {
     match format {
-        Format::Rgb24 => {
+        Format::Rgb24 | Format::A16 => {
             // Unsupported by Core Graphics.
             None
         }
@@ -914,12 +2059,37 @@ fn unpack_data_fork_font(data: &mut [u8]) -> Result<(), FontLoadingError> {
 mod test {
     use super::Font;
     use crate::properties::{Stretch, Weight};
+    use core_foundation::base::TCFType;
+    use std::mem;
 
     #[cfg(feature = "source")]
     use crate::source::SystemSource;
 
     static TEST_FONT_POSTSCRIPT_NAME: &'static str = "ArialMT";
 
+    #[test]
+    fn native_font_retain_counts() {
+        let core_text_font = core_text::font::new_from_name("Helvetica", 16.0).unwrap();
+        let observer = core_text_font.clone();
+        let initial_retain_count = observer.retain_count();
+
+        // The font takes over the reference held by `core_text_font`.
+        let font = unsafe { Font::from_native_font(&core_text_font) };
+        mem::forget(core_text_font);
+        assert_eq!(observer.retain_count(), initial_retain_count);
+        let font_clone = font.clone();
+        assert_eq!(observer.retain_count(), initial_retain_count + 1);
+        let native_font = font.native_font();
+        assert_eq!(observer.retain_count(), initial_retain_count + 2);
+
+        drop(font);
+        assert_eq!(observer.retain_count(), initial_retain_count + 1);
+        assert!(font_clone.postscript_name().is_some());
+        drop(native_font);
+        drop(font_clone);
+        assert_eq!(observer.retain_count(), initial_retain_count - 1);
+    }
+
     #[cfg(feature = "source")]
     #[test]
     fn test_from_core_graphics_font() {
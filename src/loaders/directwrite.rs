@@ -24,36 +24,55 @@ use dwrote::GlyphRunAnalysis as DWriteGlyphRunAnalysis;
 use dwrote::InformationalStringId as DWriteInformationalStringId;
 use dwrote::OutlineBuilder as DWriteOutlineBuilder;
 use dwrote::{DWRITE_TEXTURE_ALIASED_1x1, DWRITE_TEXTURE_CLEARTYPE_3x1};
+use dwrote::{DWRITE_FONT_AXIS_VALUE, DWRITE_FONT_SIMULATIONS, DWRITE_FONT_SIMULATIONS_NONE};
 use dwrote::{DWRITE_GLYPH_RUN, DWRITE_MEASURING_MODE_NATURAL};
 use dwrote::{DWRITE_RENDERING_MODE_ALIASED, DWRITE_RENDERING_MODE_NATURAL};
+use log::warn;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::ffi::OsString;
 use std::fmt::{self, Debug, Formatter};
-use std::fs::File;
-use std::io::{self, Read, Seek, SeekFrom};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek};
 use std::os::windows::ffi::OsStringExt;
 use std::os::windows::io::AsRawHandle;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::ptr;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
 use winapi::shared::minwindef::{FALSE, MAX_PATH};
+use winapi::shared::windef::RECT;
 use winapi::um::dwrite::DWRITE_NUMBER_SUBSTITUTION_METHOD_NONE;
 use winapi::um::dwrite::DWRITE_READING_DIRECTION;
 use winapi::um::dwrite::DWRITE_READING_DIRECTION_LEFT_TO_RIGHT;
+use winapi::um::dwrite::DWRITE_TEXTURE_TYPE;
 use winapi::um::fileapi;
 
-use crate::canvas::{Canvas, Format, RasterizationOptions};
+use crate::cache::GlyphCache;
+use crate::canvas::{
+    AlphaMode, Canvas, Format, GlyphSource, PlacedGlyph, RasterizationOptions, RasterizedGlyph,
+    RenderSettings, RenderedGlyph,
+};
+use crate::color::{ColorLayer, ColorU, PaletteSelector};
 use crate::error::{FontLoadingError, GlyphLoadingError};
 use crate::file_type::FileType;
 use crate::handle::Handle;
-use crate::hinting::HintingOptions;
-use crate::loader::{FallbackFont, FallbackResult, Loader};
-use crate::metrics::Metrics;
-use crate::outline::{OutlineBuilder, OutlineSink};
-use crate::properties::{Properties, Stretch, Style, Weight};
+use crate::hinting::{GaspBehavior, HintingOptions};
+use crate::loader::{
+    self, CharMappingIter, FallbackFont, FallbackResult, Loader, NormalizedFont, PitchInfo,
+    ShapingTables, VariationCoordinate,
+};
+use crate::metrics::{CaretMetrics, Metrics, MetricsSource, ScriptMetrics};
+use crate::outline::{
+    Component, ImpliedPoints, IndexedOutline, Outline, OutlineBuilder, OutlineSink,
+};
+use crate::properties::{Properties, StatAxisValue, Stretch, Style, Weight};
+use crate::tables;
 
 const ERROR_BOUND: f32 = 0.0001;
 
@@ -74,6 +93,40 @@ pub struct Font {
     dwrite_font: DWriteFont,
     dwrite_font_face: DWriteFontFace,
     cached_data: Mutex<Option<Arc<Vec<u8>>>>,
+    advance_cache: GlyphCache<Vector2F>,
+    outline_cache: GlyphCache<Arc<Outline>>,
+    glyph_analysis_cache: Mutex<Option<CachedGlyphAnalysis>>,
+    // The path or data, and font index, that the font was loaded from.
+    handle: Option<Handle>,
+    // Whether the font has any kerning data, computed on first use.
+    has_kerning: OnceLock<bool>,
+    // Whether the font has TrueType hinting instructions, computed on first use.
+    has_hinting_instructions: OnceLock<bool>,
+    // The signals for `pitch_info()`, measured on first use.
+    pitch_info: OnceLock<PitchInfo>,
+    // The font's properties, read on first use.
+    properties: OnceLock<Properties>,
+}
+
+// The most recent glyph run analysis, kept so that rasterizing a glyph right after asking for its
+// raster bounds analyzes it only once.
+struct CachedGlyphAnalysis {
+    key: GlyphAnalysisKey,
+    analysis: Rc<DWriteGlyphRunAnalysis>,
+    texture_bounds: RECT,
+}
+
+// The parameters that a glyph run analysis was built with. Only the fractional part of the
+// translation is passed to DirectWrite: whole pixels just move the texture, so analyses for the
+// bounds and the canvas of a glyph match even though they are offset from each other.
+#[derive(Clone, Copy, PartialEq)]
+struct GlyphAnalysisKey {
+    glyph_id: u32,
+    point_size: f32,
+    pixels_per_dip: f32,
+    matrix: [f32; 4],
+    subpixel_offset: Vector2F,
+    rasterization_options: RasterizationOptions,
 }
 
 struct MyTextAnalysisSource {
@@ -92,40 +145,63 @@ impl dwrote::TextAnalysisSourceMethods for MyTextAnalysisSource {
 }
 
 impl Font {
+    // Loads the face at `font_index` in the file, which is its index in the collection. The
+    // collection that the file is wrapped in groups faces by family, so its order can differ.
     fn from_dwrite_font_file(
         font_file: DWriteFontFile,
-        mut font_index: u32,
+        font_index: u32,
         font_data: Option<Arc<Vec<u8>>>,
     ) -> Result<Font, FontLoadingError> {
-        let collection_loader = CustomFontCollectionLoaderImpl::new(&[font_file.clone()]);
+        let dwrite_font_face = font_file
+            .create_face(font_index, DWRITE_FONT_SIMULATIONS_NONE)
+            .map_err(|_| FontLoadingError::NoSuchFontInCollection)?;
+        let collection_loader = CustomFontCollectionLoaderImpl::new(&[font_file]);
         let collection = DWriteFontCollection::from_loader(collection_loader);
-        let families = collection.families_iter();
-        for family in families {
-            for family_font_index in 0..family.get_font_count() {
-                if font_index > 0 {
-                    font_index -= 1;
-                    continue;
-                }
-                let dwrite_font = family.get_font(family_font_index);
-                let dwrite_font_face = dwrite_font.create_font_face();
-                return Ok(Font {
-                    dwrite_font,
-                    dwrite_font_face,
-                    cached_data: Mutex::new(font_data),
-                });
-            }
-        }
-        Err(FontLoadingError::NoSuchFontInCollection)
+        let dwrite_font = collection
+            .font_from_face(&dwrite_font_face)
+            .map_err(|_| FontLoadingError::Parse)?;
+        Ok(Font {
+            dwrite_font,
+            dwrite_font_face,
+            cached_data: Mutex::new(font_data),
+            advance_cache: GlyphCache::disabled(),
+            outline_cache: GlyphCache::disabled(),
+            glyph_analysis_cache: Mutex::new(None),
+            handle: None,
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
+        })
     }
 
     /// Loads a font from raw font data (the contents of a `.ttf`/`.otf`/etc. file).
     ///
     /// If the data represents a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index
     /// of the font to load from it. If the data represents a single font, pass 0 for `font_index`.
+    ///
+    /// DirectWrite reads the font from `font_data` itself through a custom font file loader, so
+    /// no copy is made.
     pub fn from_bytes(font_data: Arc<Vec<u8>>, font_index: u32) -> Result<Font, FontLoadingError> {
         let font_file =
             DWriteFontFile::new_from_data(font_data.clone()).ok_or(FontLoadingError::Parse)?;
-        Font::from_dwrite_font_file(font_file, font_index, Some(font_data))
+        let mut font = Font::from_dwrite_font_file(font_file, font_index, Some(font_data.clone()))?;
+        font.handle = Some(Handle::from_memory(font_data, font_index));
+        Ok(font)
+    }
+
+    /// Loads a font from a seekable stream, such as an entry in an archive, by reading all of it
+    /// into memory.
+    ///
+    /// The stream is rewound first, so its position on entry does not matter. If the stream holds
+    /// a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the font to load
+    /// from it. If it holds a single font, pass 0 for `font_index`.
+    #[inline]
+    pub fn from_stream<R>(stream: &mut R, font_index: u32) -> Result<Font, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        <Self as Loader>::from_stream(stream, font_index)
     }
 
     /// Loads a font from a `.ttf`/`.otf`/etc. file.
@@ -155,21 +231,175 @@ impl Font {
     /// font to load from it. If the file represents a single font, pass 0 for `font_index`.
     #[inline]
     pub fn from_path<P: AsRef<Path>>(path: P, font_index: u32) -> Result<Font, FontLoadingError> {
+        // DirectWrite doesn't say why it couldn't open a file, so report I/O errors ourselves.
+        let path = path.as_ref();
+        fs::metadata(path).map_err(|e| crate::error::io_error_with_path(path, e))?;
         let font_file = DWriteFontFile::new_from_path(path).ok_or(FontLoadingError::Parse)?;
-        Font::from_dwrite_font_file(font_file, font_index, None)
+        let mut font = Font::from_dwrite_font_file(font_file, font_index, None)?;
+        font.handle = Some(Handle::from_path(path.to_owned(), font_index));
+        Ok(font)
     }
 
     /// Creates a font from a native API handle.
+    ///
+    /// The font takes ownership of the references to the DirectWrite objects in `native_font`, so
+    /// the caller must pass `native_font` to `mem::forget()` afterward. The font data isn't known, so `copy_font_data()`
+    /// reads it back from the file of the font face, which fails for faces spread over several
+    /// files and may fail for faces from custom loaders; use `from_native_font_with_data()` if you
+    /// have the data at hand.
+    ///
+    /// # Safety
+    ///
+    /// `native_font.dwrite_font_face` must be the face created from `native_font.dwrite_font`.
+    /// The font reads metrics and names from the font and glyphs from the face, so mismatched
+    /// objects produce inconsistent results. The caller must give up its references to both.
     #[inline]
     pub unsafe fn from_native_font(native_font: &NativeFont) -> Font {
-        let native_font = native_font.clone();
+        Font::from_native_font_and_data(ptr::read(native_font), None)
+    }
+
+    /// Creates a font from a native API handle and the contents of the file that it was loaded
+    /// from, which `copy_font_data()` then returns.
+    ///
+    /// If the file is a collection, `font_data` is the whole collection. Returns an error if
+    /// `font_data` isn't a font file that DirectWrite recognizes or has no face at the index of
+    /// `native_font`'s face. Unlike `from_native_font()`, this adds references of its own, and the
+    /// caller keeps `native_font`.
+    pub fn from_native_font_with_data(
+        native_font: &NativeFont,
+        font_data: Arc<Vec<u8>>,
+    ) -> Result<Font, FontLoadingError> {
+        let font_count = match Font::analyze_bytes(font_data.clone())? {
+            FileType::Single => 1,
+            FileType::Collection(font_count) => font_count,
+        };
+        if native_font.dwrite_font_face.get_index() >= font_count {
+            return Err(FontLoadingError::NoSuchFontInCollection);
+        }
+        Ok(Font::from_native_font_and_data(
+            native_font.clone(),
+            Some(font_data),
+        ))
+    }
+
+    fn from_native_font_and_data(native_font: NativeFont, font_data: Option<Arc<Vec<u8>>>) -> Font {
         Font {
             dwrite_font: native_font.dwrite_font,
             dwrite_font_face: native_font.dwrite_font_face,
-            cached_data: Mutex::new(None),
+            cached_data: Mutex::new(font_data),
+            advance_cache: GlyphCache::disabled(),
+            outline_cache: GlyphCache::disabled(),
+            glyph_analysis_cache: Mutex::new(None),
+            handle: None,
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
         }
     }
 
+    /// Enables caching of glyph advances, returning the modified font.
+    ///
+    /// See `Loader::with_advance_cache()`.
+    #[inline]
+    pub fn with_advance_cache(mut self) -> Font {
+        self.advance_cache = GlyphCache::enabled();
+        self
+    }
+
+    /// Enables caching of glyph outlines, returning the modified font.
+    ///
+    /// With the cache enabled, each glyph's outline is fetched from the DirectWrite font face at
+    /// most once and replayed from memory afterward, which speeds up drawing the same glyphs
+    /// repeatedly. The cache is off by default.
+    #[inline]
+    pub fn with_outline_cache(mut self) -> Font {
+        self.outline_cache = GlyphCache::enabled();
+        self
+    }
+
+    /// Forgets every cached glyph outline.
+    ///
+    /// Call this after anything that changes the shapes of the glyphs, such as setting variation
+    /// axis values, so that stale outlines aren't drawn. This does nothing if the outline cache is
+    /// disabled.
+    #[inline]
+    pub fn clear_outline_cache(&self) {
+        self.outline_cache.clear()
+    }
+
+    /// Returns a copy of this font instanced at the given variation axis values, as `(tag, value)`
+    /// pairs in user units.
+    ///
+    /// Axes that aren't listed keep their default values. Fonts without variation axes are
+    /// returned unchanged. Instancing requires Windows 10 1809 or later; on earlier versions this
+    /// fails with `FontLoadingError::UnknownFormat`.
+    pub fn with_variations(&self, variations: &[(u32, f32)]) -> Result<Font, FontLoadingError> {
+        let variations = match loader::resolve_variations(self, variations) {
+            Some(variations) => variations,
+            None => return Ok(self.clone()),
+        };
+        // DirectWrite packs axis tags little-endian.
+        let axis_values: Vec<DWRITE_FONT_AXIS_VALUE> = variations
+            .iter()
+            .map(|&(tag, value)| DWRITE_FONT_AXIS_VALUE {
+                axisTag: tag.swap_bytes(),
+                value,
+            })
+            .collect();
+        let simulations = self.dwrite_font_face.simulations() as DWRITE_FONT_SIMULATIONS;
+        let dwrite_font_face = self
+            .dwrite_font_face
+            .create_font_face_with_variations(simulations, &axis_values)
+            .ok_or(FontLoadingError::UnknownFormat)?;
+        Ok(Font {
+            dwrite_font: self.dwrite_font.clone(),
+            dwrite_font_face,
+            cached_data: Mutex::new((*self.cached_data.lock().unwrap()).clone()),
+            advance_cache: self.advance_cache.emptied(),
+            outline_cache: self.outline_cache.emptied(),
+            glyph_analysis_cache: Mutex::new(None),
+            handle: self.handle.clone(),
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
+        })
+    }
+
+    /// Returns a copy of this font instanced at the named instance `instance_index` of its `fvar`
+    /// table.
+    #[inline]
+    pub fn with_named_instance(&self, instance_index: u16) -> Result<Font, FontLoadingError> {
+        <Self as Loader>::with_named_instance(self, instance_index)
+    }
+
+    // Returns the current values of the variation axes in user units, as `(tag, value)` pairs.
+    fn variation_values(&self) -> Vec<(u32, f32)> {
+        axis_values(&self.dwrite_font_face)
+    }
+
+    /// Returns the current value of every variation axis, in user units and normalized.
+    ///
+    /// Axes are at their defaults unless the font was instanced with `with_variations()`.
+    pub fn variation_coordinates(&self) -> Vec<VariationCoordinate> {
+        loader::variation_coordinates(self, &self.variation_values())
+    }
+
+    /// Converts a value of the variation axis `tag` from user units to the normalized coordinate,
+    /// applying the font's `avar` mapping. Axes that the font doesn't have give 0.
+    #[inline]
+    pub fn normalize_variation(&self, tag: u32, user_value: f32) -> f32 {
+        <Self as Loader>::normalize_variation(self, tag, user_value)
+    }
+
+    /// Converts a normalized coordinate of the variation axis `tag` back to user units, or returns
+    /// `None` if the font doesn't have the axis.
+    #[inline]
+    pub fn denormalize_variation(&self, tag: u32, normalized_value: f32) -> Option<f32> {
+        <Self as Loader>::denormalize_variation(self, tag, normalized_value)
+    }
+
     /// Loads the font pointed to by a handle.
     #[inline]
     pub fn from_handle(handle: &Handle) -> Result<Self, FontLoadingError> {
@@ -186,18 +416,26 @@ impl Font {
         }
     }
 
+    /// Determines whether a seekable stream holds a supported font, and, if so, what type of font
+    /// it is.
+    ///
+    /// The stream is rewound first and read into memory in its entirety.
+    #[inline]
+    pub fn analyze_stream<R>(stream: &mut R) -> Result<FileType, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        <Self as Loader>::analyze_stream(stream)
+    }
+
     /// Determines whether a file represents a supported font, and, if so, what type of font it is.
+    #[inline]
     pub fn analyze_file(file: &mut File) -> Result<FileType, FontLoadingError> {
-        let mut font_data = vec![];
-        file.seek(SeekFrom::Start(0))
-            .map_err(FontLoadingError::Io)?;
-        match file.read_to_end(&mut font_data) {
-            Err(io_error) => Err(FontLoadingError::Io(io_error)),
-            Ok(_) => Font::analyze_bytes(Arc::new(font_data)),
-        }
+        Font::analyze_stream(file)
     }
 
-    /// Returns the wrapped native font handle.
+    /// Returns new references to the wrapped DirectWrite objects, which are released when they are
+    /// dropped.
     pub fn native_font(&self) -> NativeFont {
         NativeFont {
             dwrite_font: self.dwrite_font.clone(),
@@ -234,19 +472,53 @@ impl Font {
     }
 
     /// Returns true if and only if the font is monospace (fixed-width).
-    #[inline]
+    ///
+    /// This trusts the `post` table's `isFixedPitch` field, and DirectWrite's own flag for fonts
+    /// without a `post` table.
     pub fn is_monospace(&self) -> bool {
-        self.dwrite_font.is_monospace().unwrap_or(false)
+        loader::post_fixed_pitch(self)
+            .unwrap_or_else(|| self.dwrite_font.is_monospace().unwrap_or(false))
+    }
+
+    /// Returns the raw signals from which a font can be judged monospace, for callers that want
+    /// to apply their own judgment. See `PitchInfo::is_monospace()`.
+    ///
+    /// These are measured on first use and then cached.
+    #[inline]
+    pub fn pitch_info(&self) -> PitchInfo {
+        *self
+            .pitch_info
+            .get_or_init(|| loader::measure_pitch_info(self))
+    }
+
+    /// Returns the string with the given name ID from the font's `name` table, in the language
+    /// of the system locale if the font has it.
+    #[inline]
+    pub fn name_string(&self, name_id: u16) -> Option<String> {
+        <Self as Loader>::name_string(self, name_id)
     }
 
     /// Returns the values of various font properties, corresponding to those defined in CSS.
+    ///
+    /// These are read on first use and then cached.
+    #[inline]
     pub fn properties(&self) -> Properties {
+        *self.properties.get_or_init(|| self.read_properties())
+    }
+
+    // Reads the properties that `properties()` caches.
+    fn read_properties(&self) -> Properties {
         let dwrite_font = &self.dwrite_font;
-        Properties {
-            style: style_for_dwrite_style(dwrite_font.style()),
-            stretch: Stretch(Stretch::MAPPING[(dwrite_font.stretch() as usize) - 1]),
-            weight: Weight(dwrite_font.weight().to_u32() as f32),
-        }
+        loader::properties_with_variations(
+            self,
+            Properties {
+                style: style_for_dwrite_style(dwrite_font.style()),
+                // The stretch is undefined (0) for some fonts.
+                stretch: Stretch::from_width_class(dwrite_font.stretch() as u16)
+                    .unwrap_or_default(),
+                weight: Weight(dwrite_font.weight().to_u32() as f32),
+            },
+        )
     }
 
     /// Returns the usual glyph ID for a Unicode character.
@@ -255,6 +527,8 @@ impl Font {
     /// done using a *shaper* such as HarfBuzz. This function is only useful for best-effort simple
     /// use cases like "what does character X look like on its own".
     pub fn glyph_for_char(&self, character: char) -> Option<u32> {
+        // `GetGlyphIndices()` takes UTF-32 code points, so characters outside the Basic
+        // Multilingual Plane need no surrogate handling.
         let chars = [character as u32];
         self.dwrite_font_face
             .get_glyph_indices(&chars)
@@ -271,6 +545,21 @@ impl Font {
             })
     }
 
+    /// Returns the usual glyph ID for each Unicode scalar value in `text`, in order.
+    ///
+    /// The whole string is mapped with a single call to `GetGlyphIndices()`.
+    pub fn glyphs_for_str(&self, text: &str) -> Vec<Option<u32>> {
+        let chars: Vec<u32> = text.chars().map(|character| character as u32).collect();
+        if chars.is_empty() {
+            return vec![];
+        }
+        self.dwrite_font_face
+            .get_glyph_indices(&chars)
+            .into_iter()
+            .map(|g| if g != 0 { Some(g as u32) } else { None })
+            .collect()
+    }
+
     /// Returns the number of glyphs in the font.
     ///
     /// Glyph IDs range from 0 inclusive to this value exclusive.
@@ -286,6 +575,47 @@ impl Font {
     ///
     /// TODO(pcwalton): What should we do for bitmap glyphs?
     pub fn outline<S>(
+        &self,
+        glyph_id: u32,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        if !self.outline_cache.is_enabled() {
+            return self.outline_uncached(glyph_id, hinting, sink);
+        }
+        let outline = self.outline_cache.get_or_try_insert_with(glyph_id, || {
+            let mut builder = OutlineBuilder::new();
+            self.outline_uncached(glyph_id, hinting, &mut builder)?;
+            Ok(Arc::new(builder.into_outline()))
+        })?;
+        outline.copy_to(sink);
+        Ok(())
+    }
+
+    /// Sends the vector path for a glyph to a sink, in the pixel coordinates that
+    /// `rasterize_glyph()` draws it in, so that overlays line up with the rasterized glyph.
+    ///
+    /// See `Loader::outline_at_size()` for how the path relates to `outline()`.
+    #[inline]
+    pub fn outline_at_size<S>(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        origin: Vector2F,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        <Self as Loader>::outline_at_size(self, glyph_id, point_size, origin, hinting, sink)
+    }
+
+    // Sends the outline of a glyph to `sink`, bypassing the outline cache.
+    fn outline_uncached<S>(
         &self,
         glyph_id: u32,
         _: HintingOptions,
@@ -294,31 +624,34 @@ impl Font {
     where
         S: OutlineSink,
     {
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
         let outline_sink = OutlineCanonicalizer::new();
+        // An em size equal to the units per em makes DirectWrite's DIPs font units, so the path
+        // comes back unscaled for any em, without rounding.
         self.dwrite_font_face.get_glyph_run_outline(
             self.metrics().units_per_em as f32,
-            &[glyph_id as u16],
+            &[glyph_index],
             None,
             None,
             false,
             false,
             Box::new(outline_sink.clone()),
         );
-        outline_sink
-            .0
-            .lock()
-            .unwrap()
-            .builder
-            .take_outline()
-            .copy_to(&mut *sink);
+        let outline = outline_sink.0.lock().unwrap().builder.take_outline();
+        // DirectWrite returns an empty outline for glyphs in bitmap-only fonts.
+        if outline.contours.is_empty() && !loader::has_outline_tables(self) {
+            return Err(GlyphLoadingError::NoOutline);
+        }
+        outline.copy_to(&mut *sink);
         Ok(())
     }
 
     /// Returns the boundaries of a glyph in font units.
     pub fn typographic_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
         let metrics = self
             .dwrite_font_face
-            .get_design_glyph_metrics(&[glyph_id as u16], false);
+            .get_design_glyph_metrics(&[glyph_index], false);
 
         let metrics = &metrics[0];
         let advance_width = metrics.advanceWidth as i32;
@@ -327,12 +660,18 @@ impl Font {
         let right_side_bearing = metrics.rightSideBearing as i32;
         let top_side_bearing = metrics.topSideBearing as i32;
         let bottom_side_bearing = metrics.bottomSideBearing as i32;
-        let vertical_origin_y = metrics.verticalOriginY as i32;
 
-        let y_offset = vertical_origin_y + bottom_side_bearing - advance_height;
         let width = advance_width - (left_side_bearing + right_side_bearing);
         let height = advance_height - (top_side_bearing + bottom_side_bearing);
 
+        // DirectWrite defines `verticalOriginY` as the top of the black box plus the top side
+        // bearing, so the top of the black box relative to the baseline is the difference of the
+        // two. The bottom then follows from the black box height. Note that we can't use the
+        // bottom side bearing directly, as it is measured from the bottom of the vertical advance,
+        // which is only meaningful for vertical layout.
+        let top = metrics.verticalOriginY as i32 - top_side_bearing;
+        let y_offset = top - height;
+
         Ok(RectI::new(
             Vector2I::new(left_side_bearing, y_offset),
             Vector2I::new(width, height),
@@ -340,21 +679,82 @@ impl Font {
         .to_f32())
     }
 
-    /// Returns the distance from the origin of the glyph with the given ID to the next, in font
-    /// units.
-    pub fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+    /// Returns the exact bounding box of the glyph outline in font units.
+    ///
+    /// Unlike `typographic_bounds()`, this walks the outline, so it tightly encloses the ink.
+    #[inline]
+    pub fn outline_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        <Self as Loader>::outline_bounds(self, glyph_id)
+    }
+
+    /// Returns true if the glyph has no contours, as for the space, so that `outline()` would send
+    /// nothing to its sink.
+    ///
+    /// A glyph whose black box in the design metrics has area must have contours, so only the
+    /// others, such as whitespace, are checked more closely.
+    pub fn glyph_is_empty(&self, glyph_id: u32) -> Result<bool, GlyphLoadingError> {
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
         let metrics = self
             .dwrite_font_face
-            .get_design_glyph_metrics(&[glyph_id as u16], false);
+            .get_design_glyph_metrics(&[glyph_index], false);
         let metrics = &metrics[0];
-        Ok(Vector2F::new(metrics.advanceWidth as f32, 0.0))
+        let width =
+            metrics.advanceWidth as i32 - (metrics.leftSideBearing + metrics.rightSideBearing);
+        let height =
+            metrics.advanceHeight as i32 - (metrics.topSideBearing + metrics.bottomSideBearing);
+        if width > 0 && height > 0 {
+            return Ok(false);
+        }
+        loader::outline_is_empty(self, glyph_id)
+    }
+
+    /// Returns the tight bounding box of the ink of `text` set on a single line at `point_size`,
+    /// in points, optionally applying `pair_kerning()` between glyphs.
+    #[inline]
+    pub fn string_ink_bounds(&self, text: &str, point_size: f32, apply_kerning: bool) -> RectF {
+        <Self as Loader>::string_ink_bounds(self, text, point_size, apply_kerning)
+    }
+
+    /// Returns the horizontal kerning to add between the glyphs `left` and `right`, in font units,
+    /// from the `GPOS`, `kerx`, or `kern` table.
+    #[inline]
+    pub fn pair_kerning(&self, left: u32, right: u32) -> Option<f32> {
+        <Self as Loader>::pair_kerning(self, left, right)
+    }
+
+    /// Returns true if the font has any kerning data in its `GPOS`, `kerx`, or `kern` table.
+    #[inline]
+    pub fn has_kerning(&self) -> bool {
+        *self
+            .has_kerning
+            .get_or_init(|| loader::has_kerning_data(self))
+    }
+
+    /// Reports which OpenType and AAT layout tables the font contains.
+    #[inline]
+    pub fn shaping_tables(&self) -> ShapingTables {
+        <Self as Loader>::shaping_tables(self)
+    }
+
+    /// Returns the distance from the origin of the glyph with the given ID to the next, in font
+    /// units.
+    pub fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+        self.advance_cache.get_or_try_insert_with(glyph_id, || {
+            let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
+            let metrics = self
+                .dwrite_font_face
+                .get_design_glyph_metrics(&[glyph_index], false);
+            let metrics = &metrics[0];
+            Ok(Vector2F::new(metrics.advanceWidth as f32, 0.0))
+        })
     }
 
     /// Returns the amount that the given glyph should be displaced from the origin.
-    pub fn origin(&self, glyph: u32) -> Result<Vector2F, GlyphLoadingError> {
+    pub fn origin(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
         let metrics = self
             .dwrite_font_face
-            .get_design_glyph_metrics(&[glyph as u16], false);
+            .get_design_glyph_metrics(&[glyph_index], false);
         Ok(Vector2I::new(
             metrics[0].leftSideBearing,
             metrics[0].verticalOriginY + metrics[0].bottomSideBearing,
@@ -365,15 +765,18 @@ impl Font {
     /// Retrieves various metrics that apply to the entire font.
     pub fn metrics(&self) -> Metrics {
         let dwrite_font = &self.dwrite_font;
+        let win_metrics = loader::win_metrics(self);
 
         // Unfortunately, the bounding box info is Windows 8 only, so we need a fallback. First,
         // try to grab it from the font. If that fails, we try the `head` table. If there's no
         // `head` table, we give up.
         match dwrite_font.metrics() {
             DWriteFontMetrics::Metrics1(metrics) => Metrics {
-                units_per_em: metrics.designUnitsPerEm as u32,
+                units_per_em: loader::sanitize_units_per_em(metrics.designUnitsPerEm as u32),
                 ascent: metrics.ascent as f32,
                 descent: -(metrics.descent as f32),
+                win_ascent: win_metrics.map_or(metrics.ascent as f32, |metrics| metrics.0),
+                win_descent: win_metrics.map_or(-(metrics.descent as f32), |metrics| metrics.1),
                 line_gap: metrics.lineGap as f32,
                 cap_height: metrics.capHeight as f32,
                 x_height: metrics.xHeight as f32,
@@ -408,9 +811,11 @@ impl Font {
                     None => RectF::default(),
                 };
                 Metrics {
-                    units_per_em: metrics.designUnitsPerEm as u32,
+                    units_per_em: loader::sanitize_units_per_em(metrics.designUnitsPerEm as u32),
                     ascent: metrics.ascent as f32,
                     descent: -(metrics.descent as f32),
+                    win_ascent: win_metrics.map_or(metrics.ascent as f32, |metrics| metrics.0),
+                    win_descent: win_metrics.map_or(-(metrics.descent as f32), |metrics| metrics.1),
                     line_gap: metrics.lineGap as f32,
                     cap_height: metrics.capHeight as f32,
                     x_height: metrics.xHeight as f32,
@@ -422,30 +827,77 @@ impl Font {
         }
     }
 
+    /// Returns the font's metrics, measuring the cap height and x-height from glyphs if the font
+    /// doesn't declare them.
+    ///
+    /// See `Loader::metrics_with_fallbacks()`.
+    #[inline]
+    pub fn metrics_with_fallbacks(&self) -> (Metrics, MetricsSource) {
+        <Self as Loader>::metrics_with_fallbacks(self)
+    }
+
+    /// Returns the font's metrics rescaled to `units_per_em` units per em.
+    #[inline]
+    pub fn metrics_normalized(&self, units_per_em: u32) -> Metrics {
+        <Self as Loader>::metrics_normalized(self, units_per_em)
+    }
+
+    /// Returns a view of this font that reports values in font units for an em `units_per_em`
+    /// units wide.
+    #[inline]
+    pub fn normalized(&self, units_per_em: u32) -> NormalizedFont<'_, Font> {
+        <Self as Loader>::normalized(self, units_per_em)
+    }
+
     /// Returns a handle to this font, if possible.
     ///
     /// This is useful if you want to open the font with a different loader.
     #[inline]
     pub fn handle(&self) -> Option<Handle> {
-        <Self as Loader>::handle(self)
+        self.handle
+            .clone()
+            .or_else(|| <Self as Loader>::handle(self))
+    }
+
+    /// Returns the path of the file that this font was loaded from, if it was loaded from a path.
+    #[inline]
+    pub fn source_path(&self) -> Option<&Path> {
+        match self.handle {
+            Some(Handle::Path { ref path, .. }) => Some(path),
+            _ => None,
+        }
     }
 
     /// Attempts to return the raw font data (contents of the font file).
     ///
     /// If this font is a member of a collection, this function returns the data for the entire
     /// collection.
+    ///
+    /// Unless the data was supplied when the font was created, it is read back from the font
+    /// face's file. OpenType and TrueType faces always have exactly one file. Only Type 1 faces
+    /// are split over several (the metrics in a `.pfm` file and the outlines in a `.pfb` file),
+    /// and as those files can't be combined into a single font file, this returns `None` for them.
     pub fn copy_font_data(&self) -> Option<Arc<Vec<u8>>> {
         let mut font_data = self.cached_data.lock().unwrap();
         if font_data.is_none() {
-            let files = self.dwrite_font_face.get_files();
-            // FIXME(pcwalton): Is this right? When can a font have multiple files?
-            if let Some(file) = files.get(0) {
-                *font_data = Some(Arc::new(file.get_font_file_bytes()))
+            match &self.dwrite_font_face.get_files()[..] {
+                [file] => *font_data = Some(Arc::new(file.get_font_file_bytes())),
+                files => warn!(
+                    "font face is stored in {} files, not one; its data can't be copied",
+                    files.len()
+                ),
             }
         }
         (*font_data).clone()
     }
 
+    /// Returns the byte offset of this font's table directory within the data that
+    /// `copy_font_data()` returns: 0, unless the font is a member of a collection.
+    pub fn face_byte_offset(&self) -> Option<u32> {
+        let font_data = self.copy_font_data()?;
+        loader::face_byte_offset(&font_data, self.dwrite_font_face.get_index())
+    }
+
     /// Returns the pixel boundaries that the glyph will take up when rendered using this loader's
     /// rasterizer at the given size and origin.
     #[inline]
@@ -457,29 +909,14 @@ impl Font {
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
     ) -> Result<RectI, GlyphLoadingError> {
-        let dwrite_analysis = self.build_glyph_analysis(
+        self.raster_bounds_with_pixels_per_dip(
             glyph_id,
             point_size,
+            1.0,
             transform,
             hinting_options,
             rasterization_options,
-        )?;
-
-        let texture_type = match rasterization_options {
-            RasterizationOptions::Bilevel => DWRITE_TEXTURE_ALIASED_1x1,
-            RasterizationOptions::GrayscaleAa | RasterizationOptions::SubpixelAa => {
-                DWRITE_TEXTURE_CLEARTYPE_3x1
-            }
-        };
-
-        let texture_bounds = dwrite_analysis.get_alpha_texture_bounds(texture_type)?;
-        let texture_width = texture_bounds.right - texture_bounds.left;
-        let texture_height = texture_bounds.bottom - texture_bounds.top;
-
-        Ok(RectI::new(
-            Vector2I::new(texture_bounds.left, texture_bounds.top),
-            Vector2I::new(texture_width, texture_height),
-        ))
+        )
     }
 
     /// Rasterizes a glyph to a canvas with the given size and origin.
@@ -499,34 +936,95 @@ impl Font {
         transform: Transform2F,
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
-    ) -> Result<(), GlyphLoadingError> {
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph_with_pixels_per_dip(
+            canvas,
+            glyph_id,
+            point_size,
+            1.0,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the pixel boundaries that the glyph will take up when rendered like
+    /// `rasterize_glyph_with_pixels_per_dip()`.
+    pub fn raster_bounds_with_pixels_per_dip(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        let (_, rasterization_options) = loader::resolve_font_default_hinting(
+            self,
+            hinting_options.scaled(pixels_per_dip),
+            rasterization_options,
+        );
+        let (_, texture_bounds, offset) = self.glyph_analysis(
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            transform,
+            rasterization_options,
+        )?;
+        let texture_width = texture_bounds.right - texture_bounds.left;
+        let texture_height = texture_bounds.bottom - texture_bounds.top;
+
+        Ok(RectI::new(
+            Vector2I::new(texture_bounds.left, texture_bounds.top) + offset,
+            Vector2I::new(texture_width, texture_height),
+        ))
+    }
+
+    /// Rasterizes a glyph for a display with `pixels_per_dip` physical pixels per
+    /// device-independent pixel, grid-fitting it to the physical pixel grid.
+    ///
+    /// `point_size` and the size in `hinting_options` are in device-independent pixels. The scale
+    /// is passed to DirectWrite's glyph run analysis, so the glyph is rendered as GDI-compatible
+    /// text on a display at that scale would be.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_pixels_per_dip(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
         // TODO(pcwalton): This is woefully incomplete. See WebRender's code for a more complete
         // implementation.
 
-        let dwrite_analysis = self.build_glyph_analysis(
+        let (_, rasterization_options) = loader::resolve_font_default_hinting(
+            self,
+            hinting_options.scaled(pixels_per_dip),
+            rasterization_options,
+        );
+        let (dwrite_analysis, texture_bounds, offset) = self.glyph_analysis(
             glyph_id,
             point_size,
+            pixels_per_dip,
             transform,
-            hinting_options,
             rasterization_options,
         )?;
+        let texture_type = texture_type(rasterization_options);
 
-        let texture_type = match rasterization_options {
-            RasterizationOptions::Bilevel => DWRITE_TEXTURE_ALIASED_1x1,
-            RasterizationOptions::GrayscaleAa | RasterizationOptions::SubpixelAa => {
-                DWRITE_TEXTURE_CLEARTYPE_3x1
-            }
-        };
-
-        // TODO(pcwalton): Avoid a copy in some cases by writing directly to the canvas.
-        let texture_bounds = dwrite_analysis.get_alpha_texture_bounds(texture_type)?;
         let texture_width = texture_bounds.right - texture_bounds.left;
         let texture_height = texture_bounds.bottom - texture_bounds.top;
 
         // 'Returns an empty rectangle if there are no glyphs of the specified texture type.'
         // https://docs.microsoft.com/en-us/windows/win32/api/dwrite/nf-dwrite-idwriteglyphrunanalysis-getalphatexturebounds
         if texture_width == 0 || texture_height == 0 {
-            return Ok(());
+            return Ok(RasterizedGlyph::new(
+                canvas,
+                RectI::default(),
+                GlyphSource::Outline,
+            ));
         }
 
         let texture_format = if texture_type == DWRITE_TEXTURE_ALIASED_1x1 {
@@ -539,56 +1037,197 @@ impl Font {
         let texture_size = Vector2I::new(texture_width, texture_height);
         let texture_stride = texture_width as usize * texture_bytes_per_pixel;
 
-        let mut texture_bytes =
-            dwrite_analysis.create_alpha_texture(texture_type, texture_bounds)?;
-        canvas.blit_from(
-            Vector2I::new(texture_bounds.left, texture_bounds.top),
-            &mut texture_bytes,
+        // When the canvas has exactly the texture's bounds, format, and stride, as it does when it
+        // was sized with `raster_bounds()`, the texture becomes the canvas buffer without a copy.
+        let texture_bytes = dwrite_analysis.create_alpha_texture(texture_type, texture_bounds)?;
+        let texture_origin = Vector2I::new(texture_bounds.left, texture_bounds.top) + offset;
+        canvas.blit_from_owned(
+            texture_origin,
+            texture_bytes,
             texture_size,
             texture_stride,
             texture_format,
         );
 
-        Ok(())
+        Ok(RasterizedGlyph::new(
+            canvas,
+            RectI::new(texture_origin, texture_size),
+            GlyphSource::Outline,
+        ))
     }
 
-    /// Returns true if and only if the font loader can perform hinting in the requested way.
+    /// Renders a glyph into a new canvas sized to fit it, with the options in `settings`.
     ///
-    /// Some APIs support only rasterizing glyphs with hinting, not retrieving hinted outlines. If
-    /// `for_rasterization` is false, this function returns true if and only if the loader supports
-    /// retrieval of hinted *outlines*. If `for_rasterization` is true, this function returns true
-    /// if and only if the loader supports *rasterizing* hinted glyphs.
-    pub fn supports_hinting_options(
+    /// `origin` is the position of the glyph origin within the pixel grid, in physical pixels.
+    #[inline]
+    pub fn render(
         &self,
-        hinting_options: HintingOptions,
-        for_rasterization: bool,
-    ) -> bool {
-        match (hinting_options, for_rasterization) {
-            (HintingOptions::None, _)
-            | (HintingOptions::Vertical(_), true)
-            | (HintingOptions::VerticalSubpixel(_), true) => true,
-            (HintingOptions::Vertical(_), false)
-            | (HintingOptions::VerticalSubpixel(_), false)
-            | (HintingOptions::Full(_), _) => false,
-        }
+        glyph_id: u32,
+        point_size: f32,
+        origin: Vector2F,
+        settings: &RenderSettings,
+    ) -> Result<RenderedGlyph, GlyphLoadingError> {
+        <Self as Loader>::render(self, glyph_id, point_size, origin, settings)
     }
 
-    fn build_glyph_analysis(
+    /// Renders a glyph whose origin is at `position` in layout (device-independent) pixels, for
+    /// display at the `pixels_per_dip` in `settings`.
+    #[inline]
+    pub fn render_at(
         &self,
         glyph_id: u32,
         point_size: f32,
+        position: Vector2F,
+        settings: &RenderSettings,
+    ) -> Result<PlacedGlyph, GlyphLoadingError> {
+        <Self as Loader>::render_at(self, glyph_id, point_size, position, settings)
+    }
+
+    /// Returns the pixel boundaries that the glyph will take up when rendered using this loader's
+    /// rasterizer at the given per-axis size and transform.
+    #[inline]
+    pub fn raster_bounds_scaled(
+        &self,
+        glyph_id: u32,
+        point_size: Vector2F,
         transform: Transform2F,
-        _hinting_options: HintingOptions,
+        hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
-    ) -> Result<DWriteGlyphRunAnalysis, GlyphLoadingError> {
-        unsafe {
-            let glyph_id = glyph_id as u16;
-            let advance = 0.0;
-            let offset = DWriteGlyphOffset {
-                advanceOffset: 0.0,
-                ascenderOffset: 0.0,
-            };
-            let glyph_run = DWRITE_GLYPH_RUN {
+    ) -> Result<RectI, GlyphLoadingError> {
+        let (point_size, transform) =
+            loader::anisotropic_point_size_to_transform(point_size, transform);
+        self.raster_bounds(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph to a canvas with the given per-axis size and transform.
+    ///
+    /// The x component of `point_size` is the horizontal size and the y component is the vertical
+    /// size. The horizontal stretch is passed to DirectWrite's glyph run analysis as part of its
+    /// transform, so the glyph is rasterized directly at its final dimensions.
+    #[inline]
+    pub fn rasterize_glyph_scaled(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        let (point_size, transform) =
+            loader::anisotropic_point_size_to_transform(point_size, transform);
+        self.rasterize_glyph(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns true if and only if the font loader can perform hinting in the requested way.
+    ///
+    /// Some APIs support only rasterizing glyphs with hinting, not retrieving hinted outlines. If
+    /// `for_rasterization` is false, this function returns true if and only if the loader supports
+    /// retrieval of hinted *outlines*. If `for_rasterization` is true, this function returns true
+    /// if and only if the loader supports *rasterizing* hinted glyphs.
+    pub fn supports_hinting_options(
+        &self,
+        hinting_options: HintingOptions,
+        for_rasterization: bool,
+    ) -> bool {
+        // Outlines are never hinted. The natural rendering mode grid-fits glyphs vertically, but
+        // not horizontally.
+        match (hinting_options, for_rasterization) {
+            (HintingOptions::None, _)
+            | (HintingOptions::Vertical(_), true)
+            | (HintingOptions::VerticalSubpixel(_), true) => true,
+            (HintingOptions::Vertical(_), false)
+            | (HintingOptions::VerticalSubpixel(_), false)
+            | (HintingOptions::Full(_), _)
+            | (HintingOptions::FontDefault(_), _) => false,
+        }
+    }
+
+    // Returns the glyph run analysis for a glyph, its alpha texture bounds, and the whole-pixel
+    // offset to add to those bounds. The last analysis is reused if it has the same parameters,
+    // so `raster_bounds()` followed by `rasterize_glyph()` only analyzes the glyph once.
+    fn glyph_analysis(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(Rc<DWriteGlyphRunAnalysis>, RECT, Vector2I), GlyphLoadingError> {
+        let whole_pixels = transform.vector.floor();
+        let key = GlyphAnalysisKey {
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            matrix: [
+                transform.m11(),
+                transform.m12(),
+                transform.m21(),
+                transform.m22(),
+            ],
+            subpixel_offset: transform.vector - whole_pixels,
+            rasterization_options,
+        };
+        let offset = whole_pixels.to_i32();
+
+        if let Some(ref cached) = *self.glyph_analysis_cache.lock().unwrap() {
+            if cached.key == key {
+                return Ok((cached.analysis.clone(), cached.texture_bounds, offset));
+            }
+        }
+
+        let subpixel_transform = Transform2F {
+            matrix: transform.matrix,
+            vector: key.subpixel_offset,
+        };
+        let analysis = Rc::new(self.build_glyph_analysis(
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            subpixel_transform,
+            rasterization_options,
+        )?);
+        let texture_bounds =
+            analysis.get_alpha_texture_bounds(texture_type(rasterization_options))?;
+        *self.glyph_analysis_cache.lock().unwrap() = Some(CachedGlyphAnalysis {
+            key,
+            analysis: analysis.clone(),
+            texture_bounds,
+        });
+        Ok((analysis, texture_bounds, offset))
+    }
+
+    // DirectWrite applies no hinting beyond what the rendering mode implies, so the analysis
+    // depends only on the rasterization options.
+    fn build_glyph_analysis(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<DWriteGlyphRunAnalysis, GlyphLoadingError> {
+        let glyph_id = loader::checked_glyph_id(self, glyph_id)?;
+        unsafe {
+            let advance = 0.0;
+            let offset = DWriteGlyphOffset {
+                advanceOffset: 0.0,
+                ascenderOffset: 0.0,
+            };
+            let glyph_run = DWRITE_GLYPH_RUN {
                 fontFace: self.dwrite_font_face.as_ptr(),
                 fontEmSize: point_size,
                 glyphCount: 1,
@@ -608,7 +1247,7 @@ impl Font {
 
             Ok(DWriteGlyphRunAnalysis::create(
                 &glyph_run,
-                1.0,
+                pixels_per_dip,
                 Some(dwrote::DWRITE_MATRIX {
                     m11: transform.m11(),
                     m12: transform.m12(),
@@ -670,6 +1309,14 @@ impl Font {
                 dwrite_font,
                 dwrite_font_face,
                 cached_data: Mutex::new(None),
+                advance_cache: GlyphCache::disabled(),
+                outline_cache: GlyphCache::disabled(),
+                glyph_analysis_cache: Mutex::new(None),
+                handle: None,
+                has_kerning: OnceLock::new(),
+                has_hinting_instructions: OnceLock::new(),
+                pitch_info: OnceLock::new(),
+                properties: OnceLock::new(),
             };
             let fallback_font = FallbackFont {
                 font,
@@ -682,6 +1329,360 @@ impl Font {
         FallbackResult { fonts, valid_len }
     }
 
+    /// Returns the size-dependent tracking adjustment from the AAT `trak` table, in font units.
+    ///
+    /// Add this to the advance of every glyph to match native macOS text layout.
+    #[inline]
+    pub fn tracking(&self, point_size: f32) -> Option<f32> {
+        <Self as Loader>::tracking(self, point_size)
+    }
+
+    /// Returns the slope and offset of the text cursor for this font.
+    #[inline]
+    pub fn caret_metrics(&self) -> CaretMetrics {
+        <Self as Loader>::caret_metrics(self)
+    }
+
+    /// Returns the sizes and offsets that the font recommends for subscripts and superscripts, or
+    /// `None` if the font has no `OS/2` table.
+    #[inline]
+    pub fn script_metrics(&self) -> Option<ScriptMetrics> {
+        <Self as Loader>::script_metrics(self)
+    }
+
+    /// Returns the layers of the glyph if it is a version 0 color glyph from the `COLR` table.
+    #[inline]
+    pub fn color_glyph_layers(&self, glyph_id: u32) -> Option<Vec<ColorLayer>> {
+        <Self as Loader>::color_glyph_layers(self, glyph_id)
+    }
+
+    /// Returns the colors of the palette at `palette_index` in the `CPAL` table.
+    #[inline]
+    pub fn palette(&self, palette_index: u16) -> Option<Vec<ColorU>> {
+        <Self as Loader>::palette(self, palette_index)
+    }
+
+    /// Returns the colors that `selector` picks: the chosen `CPAL` palette with the selector's
+    /// overrides applied.
+    #[inline]
+    pub fn selected_palette(&self, selector: &PaletteSelector) -> Vec<ColorU> {
+        <Self as Loader>::selected_palette(self, selector)
+    }
+
+    /// Returns the layers of a version 0 color glyph, each paired with the color that `selector`
+    /// picks for it.
+    #[inline]
+    pub fn color_glyph_layers_with_palette(
+        &self,
+        glyph_id: u32,
+        selector: &PaletteSelector,
+    ) -> Option<Vec<(ColorLayer, ColorU)>> {
+        <Self as Loader>::color_glyph_layers_with_palette(self, glyph_id, selector)
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, drawing `COLR` color glyphs (including
+    /// version 1 gradients, transforms, and compositing) in color on `Rgba32` canvases.
+    #[inline]
+    pub fn rasterize_color_glyph(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_color_glyph(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_color_glyph()`, drawing color glyphs with the colors
+    /// that `selector` picks.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_color_glyph_with_palette(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        selector: &PaletteSelector,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_color_glyph_with_palette(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            selector,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, choosing whether to draw it from an embedded
+    /// bitmap strike. DirectWrite chooses strikes on its
+    /// own, so `prefer_bitmaps` has no effect.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_bitmap_preference(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        prefer_bitmaps: bool,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_bitmap_preference(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            prefer_bitmaps,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, producing `Rgba32` output with the requested
+    /// alpha representation.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_alpha_mode(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        alpha_mode: AlphaMode,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_alpha_mode(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            alpha_mode,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, touching only the pixels inside `clip`.
+    ///
+    /// Passing `None` for `clip` is equivalent to calling `rasterize_glyph()`.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_clipped(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        clip: Option<RectI>,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_clipped(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            clip,
+        )
+    }
+
+    /// Returns the named values on this font's design axes, from the OpenType `STAT` table.
+    ///
+    /// Fonts without a `STAT` table return an empty list.
+    #[inline]
+    pub fn style_attributes(&self) -> Vec<StatAxisValue> {
+        <Self as Loader>::style_attributes(self)
+    }
+
+    /// Returns the components of a TrueType composite glyph, each with the transform that places
+    /// it.
+    ///
+    /// Simple glyphs and glyphs in fonts without a `glyf` table return `None`.
+    #[inline]
+    pub fn composite_components(&self, glyph_id: u32) -> Option<Vec<Component>> {
+        <Self as Loader>::composite_components(self, glyph_id)
+    }
+
+    /// Returns the contours of a simple TrueType glyph as stored in the `glyf` table, in font
+    /// units, optionally with the implied on-curve points inserted.
+    ///
+    /// Composite glyphs and glyphs in fonts without a `glyf` table return `None`.
+    #[inline]
+    pub fn glyf_contours(&self, glyph_id: u32, implied_points: ImpliedPoints) -> Option<Outline> {
+        <Self as Loader>::glyf_contours(self, glyph_id, implied_points)
+    }
+
+    /// Returns the contours of a simple TrueType glyph along with the index of each point in the
+    /// `glyf` point ordering that hinting instructions refer to.
+    #[inline]
+    pub fn glyf_contours_with_point_indices(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Option<IndexedOutline> {
+        <Self as Loader>::glyf_contours_with_point_indices(self, glyph_id, implied_points)
+    }
+
+    /// Returns the outline of a TrueType glyph from the `glyf` table in font units, with
+    /// composite glyphs flattened by transforming their components into place.
+    ///
+    /// Fonts without a `glyf` table return `GlyphLoadingError::NoOutline`, and malformed composite
+    /// glyphs, including ones that refer to themselves, return `GlyphLoadingError::Parse`.
+    #[inline]
+    pub fn glyf_outline(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Result<Outline, GlyphLoadingError> {
+        <Self as Loader>::glyf_outline(self, glyph_id, implied_points)
+    }
+
+    /// Returns every Unicode code point that the font maps to a glyph, paired with that glyph's
+    /// ID and sorted by code point.
+    ///
+    /// Code points mapped to `.notdef` are omitted.
+    #[inline]
+    pub fn char_to_glyph_mappings(&self) -> Vec<(u32, u32)> {
+        <Self as Loader>::char_to_glyph_mappings(self)
+    }
+
+    /// Returns an iterator over every character that the font maps to a glyph, paired with that
+    /// glyph's ID, in ascending order.
+    #[inline]
+    pub fn char_mappings(&self) -> CharMappingIter {
+        <Self as Loader>::char_mappings(self)
+    }
+
+    /// Returns the font revision from the `head` table.
+    #[inline]
+    pub fn font_revision(&self) -> Option<f32> {
+        <Self as Loader>::font_revision(self)
+    }
+
+    /// Returns the date the font was created, from the `head` table.
+    #[inline]
+    pub fn created_date(&self) -> Option<SystemTime> {
+        <Self as Loader>::created_date(self)
+    }
+
+    /// Returns the date the font was last modified, from the `head` table.
+    #[inline]
+    pub fn modified_date(&self) -> Option<SystemTime> {
+        <Self as Loader>::modified_date(self)
+    }
+
+    /// Returns the ID of the `.notdef` glyph, which is always 0.
+    #[inline]
+    pub fn notdef_glyph(&self) -> u32 {
+        <Self as Loader>::notdef_glyph(self)
+    }
+
+    /// Returns true if the `.notdef` glyph, which is drawn for characters that no font supports,
+    /// has no ink.
+    #[inline]
+    pub fn notdef_is_blank(&self) -> bool {
+        <Self as Loader>::notdef_is_blank(self)
+    }
+
+    /// Sends the outline of a conventional missing-glyph placeholder, a hollow box scaled to
+    /// `point_size`, to a path builder, and returns the box's advance.
+    #[inline]
+    pub fn synthesize_notdef<S>(&self, point_size: f32, sink: &mut S) -> Vector2F
+    where
+        S: OutlineSink,
+    {
+        <Self as Loader>::synthesize_notdef(self, point_size, sink)
+    }
+
+    /// Returns how the font recommends that glyphs be rendered at `pixels_per_em`, from its `gasp`
+    /// table.
+    ///
+    /// Fonts without a `gasp` table get the default of grid fitting with grayscale antialiasing.
+    #[inline]
+    pub fn gasp_behavior(&self, pixels_per_em: u16) -> GaspBehavior {
+        <Self as Loader>::gasp_behavior(self, pixels_per_em)
+    }
+
+    /// Returns true if the font has TrueType hinting instructions in its `fpgm`, `prep`, or
+    /// `glyf` table.
+    ///
+    /// This is computed on first use and then cached.
+    #[inline]
+    pub fn has_hinting_instructions(&self) -> bool {
+        *self
+            .has_hinting_instructions
+            .get_or_init(|| loader::has_hinting_data(self))
+    }
+
+    /// Returns the advance of the glyph in whole pixels at `pixels_per_em` from the font's `hdmx`
+    /// table.
+    #[inline]
+    pub fn hdmx_advance(&self, glyph_id: u32, pixels_per_em: u8) -> Option<u8> {
+        <Self as Loader>::hdmx_advance(self, glyph_id, pixels_per_em)
+    }
+
+    /// Returns the maximum and minimum y values, in pixels, of the font's glyphs at
+    /// `pixels_per_em` from its `VDMX` table.
+    #[inline]
+    pub fn vdmx_metrics(&self, pixels_per_em: u16) -> Option<(i16, i16)> {
+        <Self as Loader>::vdmx_metrics(self, pixels_per_em)
+    }
+
+    /// Returns the advance of the glyph in whole pixels at `pixels_per_em`, as GDI-compatible
+    /// layout computes it.
+    ///
+    /// This is the `hdmx` advance if the font lists one for the size, and DirectWrite's
+    /// GDI-compatible advance otherwise.
+    pub fn gdi_compatible_advance(
+        &self,
+        glyph_id: u32,
+        pixels_per_em: u16,
+    ) -> Result<f32, GlyphLoadingError> {
+        if let Ok(hdmx_pixels_per_em) = u8::try_from(pixels_per_em) {
+            if let Some(advance) = self.hdmx_advance(glyph_id, hdmx_pixels_per_em) {
+                return Ok(advance as f32);
+            }
+        }
+        let glyph_index = loader::checked_glyph_id(self, glyph_id)?;
+        let metrics = self
+            .dwrite_font_face
+            .gdi_compatible_glyph_metrics(
+                pixels_per_em as f32,
+                1.0,
+                ptr::null(),
+                false,
+                &[glyph_index],
+                false,
+            )
+            .map_err(GlyphLoadingError::from)?;
+        // The metrics are in font units, chosen so that they scale to whole pixels.
+        let scale = self.metrics().scale_for(pixels_per_em as f32);
+        Ok(scale.to_px(metrics[0].advanceWidth as f32).round())
+    }
+
     /// Returns the raw contents of the OpenType table with the given tag.
     ///
     /// Tags are four-character codes. A list of tags can be found in the [OpenType specification].
@@ -692,6 +1693,12 @@ impl Font {
             .get_font_table(table_tag.swap_bytes())
             .map(|v| v.into())
     }
+
+    /// Returns true if the font has an OpenType table with the given tag, without copying it.
+    #[inline]
+    pub fn has_font_table(&self, table_tag: u32) -> bool {
+        has_table(&self.dwrite_font_face, table_tag)
+    }
 }
 
 // There might well be a more efficient impl that doesn't fully decode the text,
@@ -711,6 +1718,17 @@ fn convert_len_utf16_to_utf8(text: &str, len_utf16: usize) -> usize {
     l_utf8
 }
 
+// Bilevel glyphs come straight from the aliased 1x1 texture, which is already A8 coverage, so
+// they never go through ClearType's 3x1 texture or its filtering.
+fn texture_type(rasterization_options: RasterizationOptions) -> DWRITE_TEXTURE_TYPE {
+    match rasterization_options {
+        RasterizationOptions::Bilevel => DWRITE_TEXTURE_ALIASED_1x1,
+        RasterizationOptions::GrayscaleAa | RasterizationOptions::SubpixelAa => {
+            DWRITE_TEXTURE_CLEARTYPE_3x1
+        }
+    }
+}
+
 impl Clone for Font {
     #[inline]
     fn clone(&self) -> Font {
@@ -718,16 +1736,111 @@ impl Clone for Font {
             dwrite_font: self.dwrite_font.clone(),
             dwrite_font_face: self.dwrite_font_face.clone(),
             cached_data: Mutex::new((*self.cached_data.lock().unwrap()).clone()),
+            advance_cache: self.advance_cache.clone(),
+            outline_cache: self.outline_cache.clone(),
+            glyph_analysis_cache: Mutex::new(None),
+            handle: self.handle.clone(),
+            has_kerning: self.has_kerning.clone(),
+            has_hinting_instructions: self.has_hinting_instructions.clone(),
+            pitch_info: self.pitch_info.clone(),
+            properties: self.properties.clone(),
         }
     }
 }
 
 impl Debug for Font {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), fmt::Error> {
-        self.family_name().fmt(fmt)
+        loader::fmt_debug(
+            self,
+            has_color_tables(&self.dwrite_font_face),
+            self.dwrite_font.is_monospace().unwrap_or(false),
+            fmt,
+        )
     }
 }
 
+// Fonts are equal if they are the same face: they share a DirectWrite font face, as clones do, or
+// their faces have the same index, simulations, and variation values in the same files. Files in
+// memory are compared by the fonts' `head` table fingerprints rather than byte by byte.
+// `cached_data` only holds a copy of the file contents, so whether it has been filled in doesn't
+// take part, and neither do the glyph caches.
+impl PartialEq for Font {
+    fn eq(&self, other: &Font) -> bool {
+        let (face, other_face) = (&self.dwrite_font_face, &other.dwrite_font_face);
+        if unsafe { face.as_ptr() == other_face.as_ptr() } {
+            return true;
+        }
+        face.get_index() == other_face.get_index()
+            && face.simulations() == other_face.simulations()
+            && axis_values(face) == axis_values(other_face)
+            && same_font_files(face, other_face)
+                .unwrap_or_else(|| loader::same_head_fingerprint(self, other))
+    }
+}
+
+impl Eq for Font {}
+
+// Returns true if the face has any of the tables that hold color glyphs.
+fn has_color_tables(face: &DWriteFontFace) -> bool {
+    [b"COLR", b"CBDT", b"sbix", b"SVG "]
+        .iter()
+        .any(|table_tag| has_table(face, tables::tag(table_tag)))
+}
+
+// Returns true if the face has the table with the given tag. The table is only looked up, not
+// copied, so this is cheap.
+fn has_table(face: &DWriteFontFace, table_tag: u32) -> bool {
+    unsafe {
+        let (mut data, mut size, mut context, mut exists) =
+            (ptr::null(), 0, ptr::null_mut(), FALSE);
+        let result = (*face.as_ptr()).TryGetFontTable(
+            table_tag.swap_bytes(),
+            &mut data,
+            &mut size,
+            &mut context,
+            &mut exists,
+        );
+        if result < 0 {
+            return false;
+        }
+        if exists != FALSE {
+            (*face.as_ptr()).ReleaseFontTable(context);
+        }
+        exists != FALSE
+    }
+}
+
+fn axis_values(face: &DWriteFontFace) -> Vec<(u32, f32)> {
+    face.variations()
+        .unwrap_or_default()
+        .iter()
+        // DirectWrite packs axis tags little-endian.
+        .map(|axis_value| (axis_value.axisTag.swap_bytes(), axis_value.value))
+        .collect()
+}
+
+// Returns whether the faces load the same files, which are the same if they have the same path.
+// Returns `None` if any of the files are in memory, where telling whether they're the same would
+// mean copying them.
+fn same_font_files(face: &DWriteFontFace, other_face: &DWriteFontFace) -> Option<bool> {
+    let (files, other_files) = match (face.files(), other_face.files()) {
+        (Ok(files), Ok(other_files)) => (files, other_files),
+        _ => return Some(false),
+    };
+    if files.len() != other_files.len() {
+        return Some(false);
+    }
+    let mut in_memory = false;
+    for (file, other_file) in files.iter().zip(other_files.iter()) {
+        match (file.font_file_path(), other_file.font_file_path()) {
+            (Ok(path), Ok(other_path)) if path == other_path => {}
+            (Err(_), Err(_)) => in_memory = true,
+            _ => return Some(false),
+        }
+    }
+    (!in_memory).then_some(true)
+}
+
 impl Loader for Font {
     type NativeFont = NativeFont;
 
@@ -753,6 +1866,21 @@ impl Loader for Font {
         Font::from_native_font(native_font)
     }
 
+    #[inline]
+    fn with_advance_cache(self) -> Self {
+        self.with_advance_cache()
+    }
+
+    #[inline]
+    fn with_variations(&self, variations: &[(u32, f32)]) -> Result<Font, FontLoadingError> {
+        self.with_variations(variations)
+    }
+
+    #[inline]
+    fn variation_coordinates(&self) -> Vec<VariationCoordinate> {
+        self.variation_coordinates()
+    }
+
     #[inline]
     fn analyze_bytes(font_data: Arc<Vec<u8>>) -> Result<FileType, FontLoadingError> {
         Font::analyze_bytes(font_data)
@@ -788,6 +1916,11 @@ impl Loader for Font {
         self.is_monospace()
     }
 
+    #[inline]
+    fn pitch_info(&self) -> PitchInfo {
+        self.pitch_info()
+    }
+
     #[inline]
     fn properties(&self) -> Properties {
         self.properties()
@@ -798,6 +1931,11 @@ impl Loader for Font {
         self.glyph_for_char(character)
     }
 
+    #[inline]
+    fn glyphs_for_str(&self, text: &str) -> Vec<Option<u32>> {
+        self.glyphs_for_str(text)
+    }
+
     #[inline]
     fn glyph_count(&self) -> u32 {
         self.glyph_count()
@@ -821,6 +1959,11 @@ impl Loader for Font {
         self.typographic_bounds(glyph_id)
     }
 
+    #[inline]
+    fn glyph_is_empty(&self, glyph_id: u32) -> Result<bool, GlyphLoadingError> {
+        self.glyph_is_empty(glyph_id)
+    }
+
     #[inline]
     fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
         self.advance(glyph_id)
@@ -845,11 +1988,45 @@ impl Loader for Font {
         self.supports_hinting_options(hinting_options, for_rasterization)
     }
 
+    #[inline]
+    fn gdi_compatible_advance(
+        &self,
+        glyph_id: u32,
+        pixels_per_em: u16,
+    ) -> Result<f32, GlyphLoadingError> {
+        self.gdi_compatible_advance(glyph_id, pixels_per_em)
+    }
+
     #[inline]
     fn copy_font_data(&self) -> Option<Arc<Vec<u8>>> {
         self.copy_font_data()
     }
 
+    #[inline]
+    fn handle(&self) -> Option<Handle> {
+        self.handle()
+    }
+
+    #[inline]
+    fn source_path(&self) -> Option<&Path> {
+        self.source_path()
+    }
+
+    #[inline]
+    fn has_kerning(&self) -> bool {
+        self.has_kerning()
+    }
+
+    #[inline]
+    fn has_hinting_instructions(&self) -> bool {
+        self.has_hinting_instructions()
+    }
+
+    #[inline]
+    fn face_byte_offset(&self) -> Option<u32> {
+        self.face_byte_offset()
+    }
+
     #[inline]
     fn rasterize_glyph(
         &self,
@@ -859,7 +2036,7 @@ impl Loader for Font {
         transform: Transform2F,
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
-    ) -> Result<(), GlyphLoadingError> {
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
         self.rasterize_glyph(
             canvas,
             glyph_id,
@@ -870,6 +2047,86 @@ impl Loader for Font {
         )
     }
 
+    #[inline]
+    fn raster_bounds_scaled(
+        &self,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        self.raster_bounds_scaled(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    #[inline]
+    fn rasterize_glyph_scaled(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph_scaled(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    #[inline]
+    fn raster_bounds_with_pixels_per_dip(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        self.raster_bounds_with_pixels_per_dip(
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    #[inline]
+    fn rasterize_glyph_with_pixels_per_dip(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph_with_pixels_per_dip(
+            canvas,
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
     #[inline]
     fn get_fallbacks(&self, text: &str, locale: &str) -> FallbackResult<Self> {
         self.get_fallbacks(text, locale)
@@ -879,6 +2136,11 @@ impl Loader for Font {
     fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>> {
         self.load_font_table(table_tag)
     }
+
+    #[inline]
+    fn has_font_table(&self, table_tag: u32) -> bool {
+        self.has_font_table(table_tag)
+    }
 }
 
 #[derive(Clone)]
@@ -962,3 +2224,84 @@ fn style_for_dwrite_style(style: DWriteFontStyle) -> Style {
         DWriteFontStyle::Italic => Style::Italic,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Font;
+    use crate::canvas::RasterizationOptions;
+    use dwrote::FontFace as DWriteFontFace;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use std::mem;
+    use std::rc::Rc;
+
+    static TEST_FONT_FILE_PATH: &str = "resources/tests/eb-garamond/EBGaramond12-Regular.otf";
+
+    // Returns the number of references to `font_face`, including the one held by the argument.
+    fn reference_count(font_face: &DWriteFontFace) -> u32 {
+        unsafe {
+            let font_face = font_face.as_ptr();
+            (*font_face).AddRef();
+            (*font_face).Release()
+        }
+    }
+
+    #[test]
+    fn native_font_reference_counts() {
+        let native_font = Font::from_path(TEST_FONT_FILE_PATH, 0)
+            .unwrap()
+            .native_font();
+        let observer = native_font.dwrite_font_face.clone();
+        let initial_count = reference_count(&observer);
+
+        // The font takes over the references held by `native_font`.
+        let font = unsafe { Font::from_native_font(&native_font) };
+        mem::forget(native_font);
+        assert_eq!(reference_count(&observer), initial_count);
+        let font_clone = font.clone();
+        assert_eq!(reference_count(&observer), initial_count + 1);
+        let native_font_copy = font.native_font();
+        assert_eq!(reference_count(&observer), initial_count + 2);
+
+        drop(font);
+        assert!(font_clone.postscript_name().is_some());
+        drop(native_font_copy);
+        drop(font_clone);
+        assert_eq!(reference_count(&observer), initial_count - 1);
+    }
+
+    #[test]
+    fn reuse_glyph_analysis() {
+        let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+        let glyph_id = font.glyph_for_char('g').unwrap();
+        let analyze = |transform: Transform2F| {
+            font.glyph_analysis(
+                glyph_id,
+                16.0,
+                1.0,
+                transform,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap()
+        };
+        let transform = Transform2F::from_translation(Vector2F::new(3.25, 7.0));
+        let (analysis, bounds, offset) = analyze(transform);
+        assert_eq!(offset, Vector2I::new(3, 7));
+
+        // Moving the glyph by whole pixels, as drawing it into a canvas at its raster bounds does,
+        // reuses the analysis and only changes the offset.
+        let moved_transform = Transform2F::from_translation(Vector2F::new(-4.0, -2.0)) * transform;
+        let (moved_analysis, moved_bounds, moved_offset) = analyze(moved_transform);
+        assert!(Rc::ptr_eq(&analysis, &moved_analysis));
+        assert_eq!(
+            (moved_bounds.left, moved_bounds.top),
+            (bounds.left, bounds.top)
+        );
+        assert_eq!(moved_offset, Vector2I::new(-1, 5));
+
+        // A different subpixel position needs a new analysis.
+        let (shifted_analysis, _, _) =
+            analyze(Transform2F::from_translation(Vector2F::new(3.5, 7.0)));
+        assert!(!Rc::ptr_eq(&analysis, &shifted_analysis));
+    }
+}
@@ -13,17 +13,22 @@
 //! On macOS and Windows, the Cargo feature `loader-freetype-default` can be used to opt into this
 //! loader by default.
 
-use byteorder::{BigEndian, ReadBytesExt};
 use freetype_sys::{
-    ft_sfnt_os2, FT_Byte, FT_Done_Face, FT_Done_FreeType, FT_Error, FT_Face, FT_Fixed,
-    FT_Get_Char_Index, FT_Get_Name_Index, FT_Get_Postscript_Name, FT_Get_Sfnt_Name,
-    FT_Get_Sfnt_Name_Count, FT_Get_Sfnt_Table, FT_Init_FreeType, FT_Library,
-    FT_Library_SetLcdFilter, FT_Load_Glyph, FT_Long, FT_Matrix, FT_New_Memory_Face, FT_Pos,
-    FT_Reference_Face, FT_Set_Char_Size, FT_Set_Transform, FT_UInt, FT_ULong, FT_Vector,
-    FT_FACE_FLAG_FIXED_WIDTH, FT_LCD_FILTER_DEFAULT, FT_LOAD_DEFAULT, FT_LOAD_MONOCHROME,
-    FT_LOAD_NO_HINTING, FT_LOAD_RENDER, FT_LOAD_TARGET_LCD, FT_LOAD_TARGET_LIGHT,
-    FT_LOAD_TARGET_MONO, FT_LOAD_TARGET_NORMAL, FT_PIXEL_MODE_GRAY, FT_PIXEL_MODE_LCD,
-    FT_PIXEL_MODE_LCD_V, FT_PIXEL_MODE_MONO, FT_STYLE_FLAG_ITALIC, TT_OS2,
+    ft_sfnt_os2, FT_BBox, FT_Bitmap, FT_Byte, FT_Done_Face, FT_Error, FT_Face, FT_Fixed,
+    FT_Generic, FT_Get_Char_Index, FT_Get_Name_Index, FT_Get_Postscript_Name, FT_Get_Sfnt_Table,
+    FT_Get_Var_Design_Coordinates, FT_GlyphSlot, FT_Init_FreeType, FT_Int, FT_Library,
+    FT_Library_SetLcdFilter, FT_Load_Glyph, FT_Long, FT_Matrix, FT_New_Memory_Face,
+    FT_Outline_Get_CBox, FT_Pos, FT_Reference_Face, FT_Render_Glyph, FT_Render_Mode,
+    FT_Select_Size, FT_Set_Char_Size, FT_Set_Transform, FT_Set_Var_Design_Coordinates, FT_UInt,
+    FT_ULong, FT_Vector, FT_FACE_FLAG_CID_KEYED, FT_FACE_FLAG_COLOR, FT_FACE_FLAG_FIXED_SIZES,
+    FT_FACE_FLAG_FIXED_WIDTH, FT_FACE_FLAG_GLYPH_NAMES, FT_FACE_FLAG_HINTER,
+    FT_FACE_FLAG_HORIZONTAL, FT_FACE_FLAG_KERNING, FT_FACE_FLAG_MULTIPLE_MASTERS,
+    FT_FACE_FLAG_SCALABLE, FT_FACE_FLAG_SFNT, FT_FACE_FLAG_TRICKY, FT_FACE_FLAG_VERTICAL,
+    FT_GLYPH_FORMAT_BITMAP, FT_GLYPH_FORMAT_OUTLINE, FT_LCD_FILTER_DEFAULT, FT_LOAD_DEFAULT,
+    FT_LOAD_MONOCHROME, FT_LOAD_NO_BITMAP, FT_LOAD_NO_HINTING, FT_LOAD_NO_SCALE,
+    FT_LOAD_TARGET_LCD, FT_LOAD_TARGET_LIGHT, FT_LOAD_TARGET_MONO, FT_LOAD_TARGET_NORMAL,
+    FT_PIXEL_MODE_GRAY, FT_PIXEL_MODE_LCD, FT_PIXEL_MODE_LCD_V, FT_PIXEL_MODE_MONO,
+    FT_RENDER_MODE_LCD, FT_RENDER_MODE_LCD_V, FT_RENDER_MODE_MONO, FT_STYLE_FLAG_ITALIC, TT_OS2,
 };
 use log::warn;
 use pathfinder_geometry::line_segment::LineSegment2F;
@@ -34,24 +39,34 @@ use pathfinder_simd::default::F32x4;
 use std::f32;
 use std::ffi::{CStr, CString};
 use std::fmt::{self, Debug, Formatter};
-use std::io::{Seek, SeekFrom};
-use std::iter;
+use std::io::{Read, Seek};
 use std::mem;
 use std::os::raw::{c_char, c_void};
 use std::ptr;
 use std::slice;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::SystemTime;
 
-use crate::canvas::{Canvas, Format, RasterizationOptions};
+use crate::cache::GlyphCache;
+use crate::canvas::{
+    AlphaMode, Canvas, Format, GlyphSource, PlacedGlyph, RasterizationOptions, RasterizedGlyph,
+    RenderSettings, RenderedGlyph,
+};
+use crate::color::{ColorLayer, ColorU, PaletteSelector};
 use crate::error::{FontLoadingError, GlyphLoadingError};
 use crate::file_type::FileType;
 use crate::handle::Handle;
-use crate::hinting::HintingOptions;
-use crate::loader::{FallbackResult, Loader};
-use crate::metrics::Metrics;
-use crate::outline::OutlineSink;
-use crate::properties::{Properties, Stretch, Style, Weight};
-use crate::utils;
+use crate::hinting::{GaspBehavior, HintingOptions};
+use crate::loader::{
+    self, CharMappingIter, FallbackResult, Loader, NormalizedFont, PitchInfo, ShapingTables,
+    VariationCoordinate,
+};
+use crate::metrics::{CaretMetrics, Metrics, MetricsSource, ScriptMetrics};
+use crate::outline::{
+    Component, ImpliedPoints, IndexedOutline, Outline, OutlineBuilder, OutlineSink,
+};
+use crate::properties::{Properties, StatAxisValue, Stretch, Style, Weight};
+use crate::tables;
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
@@ -61,8 +76,6 @@ use std::path::Path;
 const PS_DICT_FULL_NAME: u32 = 38;
 const TT_NAME_ID_FULL_NAME: u16 = 4;
 
-const TT_PLATFORM_APPLE_UNICODE: u16 = 0;
-
 const FT_POINT_TAG_ON_CURVE: c_char = 0x01;
 const FT_POINT_TAG_CUBIC_CONTROL: c_char = 0x02;
 
@@ -78,28 +91,32 @@ const BDF_PROPERTY_TYPE_INTEGER: BDF_PropertyType = 2;
 #[allow(dead_code)]
 const BDF_PROPERTY_TYPE_CARDINAL: BDF_PropertyType = 3;
 
-thread_local! {
-    static FREETYPE_LIBRARY: FtLibrary = {
-        unsafe {
-            let mut library = ptr::null_mut();
-            assert_eq!(FT_Init_FreeType(&mut library), 0);
-            FT_Library_SetLcdFilter(library, FT_LCD_FILTER_DEFAULT);
-            FtLibrary(library)
-        }
-    };
-}
+// All fonts that aren't created with `Font::from_bytes_with_library()` share this library, which
+// is created on first use and lives for the rest of the process.
+//
+// FreeType allows a library to be used from multiple threads as long as face creation and
+// destruction are serialized, so the mutex is held only around `FT_New_Memory_Face()`,
+// `FT_Reference_Face()`, and `FT_Done_Face()`. Everything else operates on a single face, and
+// `Font` isn't `Send`, so a font and its clones only use their face from the thread that created
+// it. A face returned by `Font::native_font()` is the exception: its caller may release it with
+// `FT_Done_Face()` from any thread.
+static FREETYPE_LIBRARY: OnceLock<Mutex<FtLibrary>> = OnceLock::new();
 
-#[repr(transparent)]
 struct FtLibrary(FT_Library);
 
-impl Drop for FtLibrary {
-    fn drop(&mut self) {
-        unsafe {
+// The raw pointer is only dereferenced by FreeType, with the mutex held where FreeType requires it.
+unsafe impl Send for FtLibrary {}
+
+fn lock_freetype_library() -> MutexGuard<'static, FtLibrary> {
+    FREETYPE_LIBRARY
+        .get_or_init(|| unsafe {
             let mut library = ptr::null_mut();
-            mem::swap(&mut library, &mut self.0);
-            FT_Done_FreeType(library);
-        }
-    }
+            assert_eq!(FT_Init_FreeType(&mut library), 0);
+            FT_Library_SetLcdFilter(library, FT_LCD_FILTER_DEFAULT);
+            Mutex::new(FtLibrary(library))
+        })
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
 }
 
 /// The handle that the FreeType API natively uses to represent a font.
@@ -116,14 +133,108 @@ struct BDF_PropertyRec {
     value: *const c_char,
 }
 
+// Not in our FreeType bindings, so we define these ourselves. Only the leading fields that we read
+// are declared; a face's `driver` points to a structure that begins with an `FT_ModuleRec`.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct FT_Module_Class {
+    module_flags: FT_ULong,
+    module_size: FT_Long,
+    module_name: *const c_char,
+}
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct FT_ModuleRec {
+    clazz: *const FT_Module_Class,
+}
+
+/// Returns the version of the FreeType library that font-kit is linked against, as a
+/// `(major, minor, patch)` triple.
+///
+/// This is specific to the FreeType loader.
+pub fn freetype_version() -> (i32, i32, i32) {
+    let library = lock_freetype_library();
+    let (mut major, mut minor, mut patch) = (0, 0, 0);
+    unsafe {
+        FT_Library_Version(library.0, &mut major, &mut minor, &mut patch);
+    }
+    (major, minor, patch)
+}
+
+/// Information about how FreeType loaded a font, useful for diagnosing rendering differences
+/// between systems.
+///
+/// This is specific to the FreeType loader; see `Font::diagnostics()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FreeTypeDiagnostics {
+    /// The face contains outlines and can be scaled to any size (`FT_FACE_FLAG_SCALABLE`).
+    pub scalable: bool,
+    /// The face contains embedded bitmap strikes (`FT_FACE_FLAG_FIXED_SIZES`).
+    pub fixed_sizes: bool,
+    /// All glyphs in the face have the same advance width (`FT_FACE_FLAG_FIXED_WIDTH`).
+    pub fixed_width: bool,
+    /// The face is stored in the SFNT (TrueType or OpenType) format (`FT_FACE_FLAG_SFNT`).
+    pub sfnt: bool,
+    /// The face contains horizontal metrics (`FT_FACE_FLAG_HORIZONTAL`).
+    pub horizontal: bool,
+    /// The face contains vertical metrics (`FT_FACE_FLAG_VERTICAL`).
+    pub vertical: bool,
+    /// The face contains kerning information that FreeType can read (`FT_FACE_FLAG_KERNING`).
+    pub kerning: bool,
+    /// The face has variation axes (`FT_FACE_FLAG_MULTIPLE_MASTERS`).
+    pub multiple_masters: bool,
+    /// The face contains glyph names (`FT_FACE_FLAG_GLYPH_NAMES`).
+    pub glyph_names: bool,
+    /// The face's driver has its own hinter, such as the TrueType bytecode interpreter
+    /// (`FT_FACE_FLAG_HINTER`).
+    pub hinter: bool,
+    /// The face is CID-keyed (`FT_FACE_FLAG_CID_KEYED`).
+    pub cid_keyed: bool,
+    /// The face is on FreeType's list of fonts that need special handling to render correctly
+    /// (`FT_FACE_FLAG_TRICKY`).
+    pub tricky: bool,
+    /// The face contains color glyphs (`FT_FACE_FLAG_COLOR`).
+    pub color: bool,
+    /// The name of the FreeType driver module that loaded the face (for example, `truetype` or
+    /// `cff`).
+    pub driver_name: Option<String>,
+    /// The font format as reported by FreeType (for example, `TrueType`, `CFF`, or `PCF`).
+    pub font_format: Option<String>,
+    /// The number of embedded bitmap strikes.
+    pub num_fixed_sizes: u32,
+    /// The version of the FreeType library that loaded the face, as a `(major, minor, patch)`
+    /// triple.
+    pub freetype_version: (i32, i32, i32),
+}
+
 /// A cross-platform loader that uses the FreeType library to load and rasterize fonts.
 ///
 ///
 /// On macOS and Windows, the Cargo feature `loader-freetype-default` can be used to opt into this
 /// loader by default.
+///
+/// Fonts share a single process-wide FreeType library, created lazily, whose face creation and
+/// destruction are guarded by a mutex. Fonts can therefore be created and dropped on any number of
+/// threads at once, although each font stays on the thread that created it.
 pub struct Font {
     freetype_face: FT_Face,
     font_data: Arc<Vec<u8>>,
+    advance_cache: GlyphCache<Vector2F>,
+    outline_cache: GlyphCache<Arc<Outline>>,
+    // True if the face belongs to a library supplied to `from_bytes_with_library()`, in which case
+    // the caller, not the shared library's mutex, is responsible for synchronization.
+    external_library: bool,
+    // The path or data, and font index, that the font was loaded from.
+    handle: Option<Handle>,
+    // Whether the font has any kerning data, computed on first use.
+    has_kerning: OnceLock<bool>,
+    // Whether the font has TrueType hinting instructions, computed on first use.
+    has_hinting_instructions: OnceLock<bool>,
+    // The signals for `pitch_info()`, measured on first use.
+    pitch_info: OnceLock<PitchInfo>,
+    // The font's properties, read on first use.
+    properties: OnceLock<Properties>,
 }
 
 impl Font {
@@ -132,26 +243,79 @@ impl Font {
     /// If the data represents a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index
     /// of the font to load from it. If the data represents a single font, pass 0 for `font_index`.
     pub fn from_bytes(font_data: Arc<Vec<u8>>, font_index: u32) -> Result<Font, FontLoadingError> {
-        FREETYPE_LIBRARY.with(|freetype_library| unsafe {
-            let mut freetype_face = ptr::null_mut();
-            if FT_New_Memory_Face(
-                freetype_library.0,
-                (*font_data).as_ptr(),
-                font_data.len() as FT_Long,
-                font_index as FT_Long,
-                &mut freetype_face,
-            ) != 0
-            {
-                return Err(FontLoadingError::Parse);
-            }
+        let freetype_library = lock_freetype_library();
+        unsafe {
+            let freetype_face = new_memory_face(freetype_library.0, &font_data, font_index)?;
+            drop(freetype_library);
+            let mut font = Font::from_freetype_face(freetype_face, font_data.clone(), false);
+            font.handle = Some(Handle::from_memory(font_data, font_index));
+            Ok(font)
+        }
+    }
 
-            setup_freetype_face(freetype_face);
+    /// Loads a font from raw font data using a FreeType library owned by the caller, instead of
+    /// the library that font-kit shares among all other fonts.
+    ///
+    /// This is useful for embedders that already manage a FreeType library and want faces to
+    /// share its caches and configuration. font-kit doesn't configure the library in any way; in
+    /// particular, LCD filtering is left as the caller set it.
+    ///
+    /// # Safety
+    ///
+    /// `freetype_library` must be a valid FreeType library that outlives the returned font and
+    /// all of its clones. FreeType requires that faces not be created or destroyed on the same
+    /// library from multiple threads at once, so the caller must ensure that this function, and
+    /// cloning and dropping the returned font, don't race with any other face creation or
+    /// destruction on `freetype_library`.
+    pub unsafe fn from_bytes_with_library(
+        freetype_library: FT_Library,
+        font_data: Arc<Vec<u8>>,
+        font_index: u32,
+    ) -> Result<Font, FontLoadingError> {
+        let freetype_face = new_memory_face(freetype_library, &font_data, font_index)?;
+        let mut font = Font::from_freetype_face(freetype_face, font_data.clone(), true);
+        font.handle = Some(Handle::from_memory(font_data, font_index));
+        Ok(font)
+    }
+
+    unsafe fn from_freetype_face(
+        freetype_face: FT_Face,
+        font_data: Arc<Vec<u8>>,
+        external_library: bool,
+    ) -> Font {
+        setup_freetype_face(freetype_face);
+        // The face points into `font_data`, so let it hold a reference of its own. Otherwise a
+        // face returned by `native_font()` would dangle once every `Font` using it was dropped.
+        (*freetype_face).generic = FT_Generic {
+            data: Arc::into_raw(font_data.clone()) as *mut c_void,
+            finalizer: release_face_font_data,
+        };
+        Font {
+            freetype_face,
+            font_data,
+            advance_cache: GlyphCache::disabled(),
+            outline_cache: GlyphCache::disabled(),
+            external_library,
+            handle: None,
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
+        }
+    }
 
-            Ok(Font {
-                freetype_face,
-                font_data,
-            })
-        })
+    /// Loads a font from a seekable stream, such as an entry in an archive, by reading all of it
+    /// into memory.
+    ///
+    /// The stream is rewound first, so its position on entry does not matter. If the stream holds
+    /// a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the font to load
+    /// from it. If it holds a single font, pass 0 for `font_index`.
+    #[inline]
+    pub fn from_stream<R>(stream: &mut R, font_index: u32) -> Result<Font, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        <Self as Loader>::from_stream(stream, font_index)
     }
 
     /// Loads a font from a `.ttf`/`.otf`/etc. file.
@@ -159,48 +323,202 @@ impl Font {
     /// If the file is a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the
     /// font to load from it. If the file represents a single font, pass 0 for `font_index`.
     #[cfg(not(target_arch = "wasm32"))]
+    #[inline]
     pub fn from_file(file: &mut File, font_index: u32) -> Result<Font, FontLoadingError> {
-        file.seek(SeekFrom::Start(0))?;
-        let font_data = Arc::new(utils::slurp_file(file).map_err(FontLoadingError::Io)?);
-        Font::from_bytes(font_data, font_index)
+        Font::from_stream(file, font_index)
     }
 
     /// Loads a font from the path to a `.ttf`/`.otf`/etc. file.
     ///
     /// If the file is a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the
     /// font to load from it. If the file represents a single font, pass 0 for `font_index`.
-    #[inline]
     #[cfg(not(target_arch = "wasm32"))]
     pub fn from_path<P>(path: P, font_index: u32) -> Result<Font, FontLoadingError>
     where
         P: AsRef<Path>,
     {
         // TODO(pcwalton): Perhaps use the native FreeType support for opening paths?
-        <Font as Loader>::from_path(path, font_index)
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|e| crate::error::io_error_with_path(path, e))?;
+        let mut font = Font::from_file(&mut file, font_index)?;
+        font.handle = Some(Handle::from_path(path.to_owned(), font_index));
+        Ok(font)
     }
 
     /// Creates a font from a native API handle.
+    ///
+    /// The font takes ownership of the caller's reference to `freetype_face` and releases it with
+    /// `FT_Done_Face()` when dropped, so the caller must not release it itself. The font resets
+    /// the face's character size, which other users of the face may notice.
+    ///
+    /// # Safety
+    ///
+    /// `freetype_face` must be a valid, live FreeType face, and the caller must give up one
+    /// reference to it. The memory that the face reads from must outlive it. If the face belongs
+    /// to a library other than font-kit's, the caller has the same obligations as for
+    /// `from_bytes_with_library()`.
     pub unsafe fn from_native_font(freetype_face: &NativeFont) -> Font {
-        // We make an in-memory copy of the underlying font data. This is because the native font
-        // does not necessarily hold a strong reference to the memory backing it.
+        // We make an in-memory copy of the underlying font data for reading tables, since the face
+        // doesn't necessarily hold a strong reference to the memory backing it.
         let freetype_face = *freetype_face;
-        const CHUNK_SIZE: usize = 4096;
-        let mut font_data = vec![];
-        loop {
-            font_data.extend(iter::repeat(0).take(CHUNK_SIZE));
-            let freetype_stream = (*freetype_face).stream;
+        let freetype_stream = (*freetype_face).stream;
+        let font_data = if !(*freetype_stream).base.is_null() {
+            // Memory-based (and memory-mapped) streams have no read function.
+            slice::from_raw_parts((*freetype_stream).base, (*freetype_stream).size as usize)
+                .to_vec()
+        } else {
+            let mut font_data = vec![0; (*freetype_stream).size as usize];
             let n_read = ((*freetype_stream).read)(
                 freetype_stream,
-                font_data.len() as FT_ULong,
+                0,
                 font_data.as_mut_ptr(),
-                CHUNK_SIZE as FT_ULong,
+                font_data.len() as FT_ULong,
             );
-            if n_read < CHUNK_SIZE as FT_ULong {
-                break;
+            font_data.truncate(n_read as usize);
+            font_data
+        };
+
+        // The face keeps its own `generic` field, which may already hold the caller's data, and
+        // any named instance that it was opened with.
+        reset_freetype_face_char_size(freetype_face);
+        let external_library = (*(*freetype_face).glyph).library != lock_freetype_library().0;
+        Font {
+            freetype_face,
+            font_data: Arc::new(font_data),
+            advance_cache: GlyphCache::disabled(),
+            outline_cache: GlyphCache::disabled(),
+            external_library,
+            handle: None,
+            has_kerning: OnceLock::new(),
+            has_hinting_instructions: OnceLock::new(),
+            pitch_info: OnceLock::new(),
+            properties: OnceLock::new(),
+        }
+    }
+
+    /// Enables caching of glyph advances, returning the modified font.
+    ///
+    /// See `Loader::with_advance_cache()`.
+    #[inline]
+    pub fn with_advance_cache(mut self) -> Font {
+        self.advance_cache = GlyphCache::enabled();
+        self
+    }
+
+    /// Enables caching of glyph outlines, returning the modified font.
+    ///
+    /// With the cache enabled, each glyph's outline is fetched from the FreeType face at most once
+    /// and replayed from memory afterward, which speeds up drawing the same glyphs repeatedly. Only
+    /// unhinted outlines are cached; hinted ones depend on the grid-fitting size and are always
+    /// fetched afresh. The cache is off by default.
+    #[inline]
+    pub fn with_outline_cache(mut self) -> Font {
+        self.outline_cache = GlyphCache::enabled();
+        self
+    }
+
+    /// Forgets every cached glyph outline.
+    ///
+    /// Call this after anything that changes the shapes of the glyphs, such as setting variation
+    /// axis values, so that stale outlines aren't drawn. This does nothing if the outline cache is
+    /// disabled.
+    #[inline]
+    pub fn clear_outline_cache(&self) {
+        self.outline_cache.clear()
+    }
+
+    /// Returns a copy of this font instanced at the given variation axis values, as `(tag, value)`
+    /// pairs in user units.
+    ///
+    /// The copy has a FreeType face of its own, since variations are set on the face and clones
+    /// share theirs. Axes that aren't listed keep their default values. Fonts without variation
+    /// axes are returned unchanged.
+    pub fn with_variations(&self, variations: &[(u32, f32)]) -> Result<Font, FontLoadingError> {
+        let variations = match loader::resolve_variations(self, variations) {
+            Some(variations) => variations,
+            None => return Ok(self.clone()),
+        };
+        unsafe {
+            // The upper bits of the face index select a named instance, which the coordinates
+            // below replace.
+            let font_index = ((*self.freetype_face).face_index & 0xffff) as u32;
+            let mut font = if self.external_library {
+                let freetype_library = (*(*self.freetype_face).glyph).library;
+                Font::from_bytes_with_library(freetype_library, self.font_data.clone(), font_index)?
+            } else {
+                Font::from_bytes(self.font_data.clone(), font_index)?
+            };
+            let coordinates: Vec<FT_Fixed> = variations
+                .iter()
+                .map(|&(_, value)| (value * 65536.0).round() as FT_Fixed)
+                .collect();
+            if FT_Set_Var_Design_Coordinates(
+                font.freetype_face,
+                coordinates.len() as FT_UInt,
+                coordinates.as_ptr(),
+            ) != 0
+            {
+                return Err(FontLoadingError::Parse);
+            }
+            font.advance_cache = self.advance_cache.emptied();
+            font.outline_cache = self.outline_cache.emptied();
+            font.handle = self.handle.clone();
+            Ok(font)
+        }
+    }
+
+    /// Returns a copy of this font instanced at the named instance `instance_index` of its `fvar`
+    /// table.
+    #[inline]
+    pub fn with_named_instance(&self, instance_index: u16) -> Result<Font, FontLoadingError> {
+        <Self as Loader>::with_named_instance(self, instance_index)
+    }
+
+    // Returns the current values of the variation axes in user units, as `(tag, value)` pairs.
+    fn variation_values(&self) -> Vec<(u32, f32)> {
+        let axes = match self
+            .load_font_table(tables::fvar::TAG)
+            .and_then(|fvar| tables::fvar::axes(&fvar))
+        {
+            Some(axes) if !axes.is_empty() => axes,
+            _ => return vec![],
+        };
+        let mut coordinates: Vec<FT_Fixed> = vec![0; axes.len()];
+        unsafe {
+            if FT_Get_Var_Design_Coordinates(
+                self.freetype_face,
+                coordinates.len() as FT_UInt,
+                coordinates.as_mut_ptr(),
+            ) != 0
+            {
+                return vec![];
             }
         }
+        axes.iter()
+            .zip(coordinates)
+            .map(|(axis, coordinate)| (axis.tag, coordinate as f32 / 65536.0))
+            .collect()
+    }
+
+    /// Returns the current value of every variation axis, in user units and normalized.
+    ///
+    /// Axes are at their defaults unless the font was instanced with `with_variations()`.
+    pub fn variation_coordinates(&self) -> Vec<VariationCoordinate> {
+        loader::variation_coordinates(self, &self.variation_values())
+    }
+
+    /// Converts a value of the variation axis `tag` from user units to the normalized coordinate,
+    /// applying the font's `avar` mapping. Axes that the font doesn't have give 0.
+    #[inline]
+    pub fn normalize_variation(&self, tag: u32, user_value: f32) -> f32 {
+        <Self as Loader>::normalize_variation(self, tag, user_value)
+    }
 
-        Font::from_bytes(Arc::new(font_data), (*freetype_face).face_index as u32).unwrap()
+    /// Converts a normalized coordinate of the variation axis `tag` back to user units, or returns
+    /// `None` if the font doesn't have the axis.
+    #[inline]
+    pub fn denormalize_variation(&self, tag: u32, normalized_value: f32) -> Option<f32> {
+        <Self as Loader>::denormalize_variation(self, tag, normalized_value)
     }
 
     /// Loads the font pointed to by a handle.
@@ -212,54 +530,35 @@ impl Font {
     /// Determines whether a blob of raw font data represents a supported font, and, if so, what
     /// type of font it is.
     pub fn analyze_bytes(font_data: Arc<Vec<u8>>) -> Result<FileType, FontLoadingError> {
-        FREETYPE_LIBRARY.with(|freetype_library| unsafe {
-            let mut freetype_face = ptr::null_mut();
-            if FT_New_Memory_Face(
-                freetype_library.0,
-                (*font_data).as_ptr(),
-                font_data.len() as FT_Long,
-                0,
-                &mut freetype_face,
-            ) != 0
-            {
-                return Err(FontLoadingError::Parse);
-            }
-
+        let freetype_library = lock_freetype_library();
+        unsafe {
+            let freetype_face = new_memory_face(freetype_library.0, &font_data, 0)?;
             let font_type = match (*freetype_face).num_faces {
                 1 => FileType::Single,
                 num_faces => FileType::Collection(num_faces as u32),
             };
             FT_Done_Face(freetype_face);
             Ok(font_type)
-        })
+        }
+    }
+
+    /// Determines whether a seekable stream holds a supported font, and, if so, what type of font
+    /// it is.
+    ///
+    /// The stream is rewound first and read into memory in its entirety.
+    #[inline]
+    pub fn analyze_stream<R>(stream: &mut R) -> Result<FileType, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        <Self as Loader>::analyze_stream(stream)
     }
 
     /// Determines whether a file represents a supported font, and, if so, what type of font it is.
     #[cfg(not(target_arch = "wasm32"))]
+    #[inline]
     pub fn analyze_file(file: &mut File) -> Result<FileType, FontLoadingError> {
-        FREETYPE_LIBRARY.with(|freetype_library| unsafe {
-            file.seek(SeekFrom::Start(0))?;
-            let font_data = Arc::new(utils::slurp_file(file).map_err(FontLoadingError::Io)?);
-
-            let mut freetype_face = ptr::null_mut();
-            if FT_New_Memory_Face(
-                freetype_library.0,
-                (*font_data).as_ptr(),
-                font_data.len() as FT_Long,
-                0,
-                &mut freetype_face,
-            ) != 0
-            {
-                return Err(FontLoadingError::Parse);
-            }
-
-            let font_type = match (*freetype_face).num_faces {
-                1 => FileType::Single,
-                num_faces => FileType::Collection(num_faces as u32),
-            };
-            FT_Done_Face(freetype_face);
-            Ok(font_type)
-        })
+        Font::analyze_stream(file)
     }
 
     /// Determines whether a path points to a supported font, and, if so, what type of font it is.
@@ -275,12 +574,18 @@ impl Font {
     /// Returns the wrapped native font handle.
     ///
     /// This function increments the reference count of the FreeType face before returning it.
-    /// Therefore, it is the caller's responsibility to free it with `FT_Done_Face`.
+    /// Therefore, it is the caller's responsibility to free it with `FT_Done_Face`. The face,
+    /// along with the font data it reads from, stays valid until then, even if this font is
+    /// dropped first.
     pub fn native_font(&self) -> NativeFont {
+        // The caller may release the returned face with `FT_Done_Face()` on another thread, so
+        // take the reference under the same lock that serializes face destruction.
+        let freetype_library = (!self.external_library).then(lock_freetype_library);
         unsafe {
             assert_eq!(FT_Reference_Face(self.freetype_face), 0);
-            self.freetype_face
         }
+        drop(freetype_library);
+        self.freetype_face
     }
 
     /// Returns the PostScript name of the font. This should be globally unique.
@@ -337,13 +642,87 @@ impl Font {
         }
     }
 
+    // Returns false for bitmap-only fonts.
+    fn is_scalable(&self) -> bool {
+        unsafe { (*self.freetype_face).face_flags & FT_FACE_FLAG_SCALABLE != 0 }
+    }
+
     /// Returns true if and only if the font is monospace (fixed-width).
+    ///
+    /// FreeType sets its fixed-width flag from the `post` table's `isFixedPitch` field, or from
+    /// the font's own spacing property for fonts without a `post` table, such as PCF fonts.
+    #[inline]
     pub fn is_monospace(&self) -> bool {
         unsafe { (*self.freetype_face).face_flags & (FT_FACE_FLAG_FIXED_WIDTH as FT_Long) != 0 }
     }
 
+    /// Returns the raw signals from which a font can be judged monospace, for callers that want
+    /// to apply their own judgment. See `PitchInfo::is_monospace()`.
+    ///
+    /// These are measured on first use and then cached.
+    #[inline]
+    pub fn pitch_info(&self) -> PitchInfo {
+        *self
+            .pitch_info
+            .get_or_init(|| loader::measure_pitch_info(self))
+    }
+
+    /// Returns information about how FreeType loaded this font: its face flags, the driver that
+    /// loaded it, and the FreeType version.
+    ///
+    /// This is specific to the FreeType loader and is meant for diagnostics, such as bug reports
+    /// about rendering that differs between systems.
+    pub fn diagnostics(&self) -> FreeTypeDiagnostics {
+        unsafe {
+            let face = &*self.freetype_face;
+            let has_flag = |flag: FT_Long| face.face_flags & flag != 0;
+
+            let driver = face.driver as *const FT_ModuleRec;
+            let driver_name = if driver.is_null() || (*driver).clazz.is_null() {
+                None
+            } else {
+                c_string_to_owned((*(*driver).clazz).module_name)
+            };
+
+            FreeTypeDiagnostics {
+                scalable: has_flag(FT_FACE_FLAG_SCALABLE),
+                fixed_sizes: has_flag(FT_FACE_FLAG_FIXED_SIZES),
+                fixed_width: has_flag(FT_FACE_FLAG_FIXED_WIDTH),
+                sfnt: has_flag(FT_FACE_FLAG_SFNT),
+                horizontal: has_flag(FT_FACE_FLAG_HORIZONTAL),
+                vertical: has_flag(FT_FACE_FLAG_VERTICAL),
+                kerning: has_flag(FT_FACE_FLAG_KERNING),
+                multiple_masters: has_flag(FT_FACE_FLAG_MULTIPLE_MASTERS),
+                glyph_names: has_flag(FT_FACE_FLAG_GLYPH_NAMES),
+                hinter: has_flag(FT_FACE_FLAG_HINTER),
+                cid_keyed: has_flag(FT_FACE_FLAG_CID_KEYED),
+                tricky: has_flag(FT_FACE_FLAG_TRICKY),
+                color: has_flag(FT_FACE_FLAG_COLOR),
+                driver_name,
+                font_format: c_string_to_owned(FT_Get_Font_Format(self.freetype_face)),
+                num_fixed_sizes: face.num_fixed_sizes.max(0) as u32,
+                freetype_version: freetype_version(),
+            }
+        }
+    }
+
+    /// Returns the string with the given name ID from the font's `name` table, in the language
+    /// of the system locale if the font has it.
+    #[inline]
+    pub fn name_string(&self, name_id: u16) -> Option<String> {
+        <Self as Loader>::name_string(self, name_id)
+    }
+
     /// Returns the values of various font properties, corresponding to those defined in CSS.
+    ///
+    /// These are read on first use and then cached.
+    #[inline]
     pub fn properties(&self) -> Properties {
+        *self.properties.get_or_init(|| self.read_properties())
+    }
+
+    // Reads the properties that `properties()` caches.
+    fn read_properties(&self) -> Properties {
         unsafe {
             let os2_table = self.get_os2_table();
             let style = match os2_table {
@@ -357,21 +736,21 @@ impl Font {
                 }
                 _ => Style::Normal,
             };
-            let stretch = match os2_table {
-                Some(os2_table) if (1..=9).contains(&(*os2_table).usWidthClass) => {
-                    Stretch(Stretch::MAPPING[((*os2_table).usWidthClass as usize) - 1])
-                }
-                _ => Stretch::NORMAL,
-            };
+            let stretch = os2_table
+                .and_then(|os2_table| Stretch::from_width_class((*os2_table).usWidthClass))
+                .unwrap_or_default();
             let weight = match os2_table {
                 None => Weight::NORMAL,
                 Some(os2_table) => Weight((*os2_table).usWeightClass as f32),
             };
-            Properties {
-                style,
-                stretch,
-                weight,
-            }
+            loader::properties_with_variations(
+                self,
+                Properties {
+                    style,
+                    stretch,
+                    weight,
+                },
+            )
         }
     }
 
@@ -391,6 +770,12 @@ impl Font {
         }
     }
 
+    /// Returns the usual glyph ID for each Unicode scalar value in `text`, in order.
+    #[inline]
+    pub fn glyphs_for_str(&self, text: &str) -> Vec<Option<u32>> {
+        <Self as Loader>::glyphs_for_str(self, text)
+    }
+
     /// Returns the glyph ID for the specified glyph name.
     #[inline]
     pub fn glyph_by_name(&self, name: &str) -> Option<u32> {
@@ -418,7 +803,8 @@ impl Font {
     /// If `hinting_mode` is not None, this function performs grid-fitting as requested before
     /// sending the hinding outlines to the builder.
     ///
-    /// TODO(pcwalton): What should we do for bitmap glyphs?
+    /// Glyphs that only have embedded bitmaps, such as those in bitmap-only fonts, return
+    /// `GlyphLoadingError::NoOutline`.
     pub fn outline<S>(
         &self,
         glyph_id: u32,
@@ -428,13 +814,63 @@ impl Font {
     where
         S: OutlineSink,
     {
+        let (hinting, _) =
+            loader::resolve_font_default_hinting(self, hinting, RasterizationOptions::GrayscaleAa);
+        if !self.outline_cache.is_enabled() || hinting.grid_fitting_size().is_some() {
+            return self.outline_uncached(glyph_id, hinting, sink);
+        }
+        let outline = self.outline_cache.get_or_try_insert_with(glyph_id, || {
+            let mut builder = OutlineBuilder::new();
+            self.outline_uncached(glyph_id, hinting, &mut builder)?;
+            Ok(Arc::new(builder.into_outline()))
+        })?;
+        outline.copy_to(sink);
+        Ok(())
+    }
+
+    /// Sends the vector path for a glyph to a sink, in the pixel coordinates that
+    /// `rasterize_glyph()` draws it in, so that overlays line up with the rasterized glyph.
+    ///
+    /// See `Loader::outline_at_size()` for how the path relates to `outline()`.
+    #[inline]
+    pub fn outline_at_size<S>(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        origin: Vector2F,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        <Self as Loader>::outline_at_size(self, glyph_id, point_size, origin, hinting, sink)
+    }
+
+    // Sends the outline of a glyph to `sink`, bypassing the outline cache.
+    fn outline_uncached<S>(
+        &self,
+        glyph_id: u32,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        loader::checked_glyph_id(self, glyph_id)?;
         unsafe {
+            if !self.is_scalable() {
+                return Err(GlyphLoadingError::NoOutline);
+            }
+
             let rasterization_options = RasterizationOptions::GrayscaleAa;
             let load_flags = self
-                .hinting_and_rasterization_options_to_load_flags(hinting, rasterization_options);
+                .hinting_and_rasterization_options_to_load_flags(hinting, rasterization_options)
+                | FT_LOAD_NO_BITMAP;
 
             let units_per_em = (*self.freetype_face).units_per_EM;
-            let grid_fitting_size = hinting.grid_fitting_size();
+            // Grid fitting at a size of 0 would scale the hinted outline back up by infinity.
+            let grid_fitting_size = hinting.grid_fitting_size().filter(|&size| size > 0.0);
             if let Some(size) = grid_fitting_size {
                 assert_eq!(
                     FT_Set_Char_Size(self.freetype_face, size.f32_to_ft_fixed_26_6(), 0, 0, 0),
@@ -445,6 +881,12 @@ impl Font {
             if FT_Load_Glyph(self.freetype_face, glyph_id, load_flags) != 0 {
                 return Err(GlyphLoadingError::NoSuchGlyph);
             }
+            if (*(*self.freetype_face).glyph).format != FT_GLYPH_FORMAT_OUTLINE {
+                if grid_fitting_size.is_some() {
+                    reset_freetype_face_char_size(self.freetype_face)
+                }
+                return Err(GlyphLoadingError::NoOutline);
+            }
 
             let outline = &(*(*self.freetype_face).glyph).outline;
             if outline.n_contours == 0 {
@@ -555,7 +997,7 @@ impl Font {
                 sink.close();
             }
 
-            if hinting.grid_fitting_size().is_some() {
+            if grid_fitting_size.is_some() {
                 reset_freetype_face_char_size(self.freetype_face)
             }
         }
@@ -587,6 +1029,7 @@ impl Font {
 
     /// Returns the boundaries of a glyph in font units.
     pub fn typographic_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        loader::checked_glyph_id(self, glyph_id)?;
         unsafe {
             if FT_Load_Glyph(
                 self.freetype_face,
@@ -609,34 +1052,101 @@ impl Font {
         }
     }
 
-    /// Returns the distance from the origin of the glyph with the given ID to the next, in font
-    /// units.
-    pub fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+    /// Returns the exact bounding box of the glyph outline in font units.
+    ///
+    /// Unlike `typographic_bounds()`, this walks the outline, so it tightly encloses the ink.
+    #[inline]
+    pub fn outline_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
+        <Self as Loader>::outline_bounds(self, glyph_id)
+    }
+
+    /// Returns true if the glyph has no contours, as for the space, so that `outline()` would send
+    /// nothing to its sink.
+    ///
+    /// This loads the glyph unscaled and checks its contour count, without walking the outline.
+    pub fn glyph_is_empty(&self, glyph_id: u32) -> Result<bool, GlyphLoadingError> {
+        loader::checked_glyph_id(self, glyph_id)?;
         unsafe {
-            if FT_Load_Glyph(
-                self.freetype_face,
-                glyph_id,
-                FT_LOAD_DEFAULT | FT_LOAD_NO_HINTING,
-            ) != 0
-            {
+            if !self.is_scalable() {
+                return Err(GlyphLoadingError::NoOutline);
+            }
+            if FT_Load_Glyph(self.freetype_face, glyph_id, FT_LOAD_NO_SCALE) != 0 {
                 return Err(GlyphLoadingError::NoSuchGlyph);
             }
-
-            let advance = (*(*self.freetype_face).glyph).advance;
-            Ok(Vector2I::new(advance.x as i32, advance.y as i32).ft_fixed_26_6_to_f32())
+            let glyph_slot = (*self.freetype_face).glyph;
+            if (*glyph_slot).format != FT_GLYPH_FORMAT_OUTLINE {
+                return Err(GlyphLoadingError::NoOutline);
+            }
+            Ok((*glyph_slot).outline.n_contours == 0)
         }
     }
 
+    /// Returns the tight bounding box of the ink of `text` set on a single line at `point_size`,
+    /// in points, optionally applying `pair_kerning()` between glyphs.
+    #[inline]
+    pub fn string_ink_bounds(&self, text: &str, point_size: f32, apply_kerning: bool) -> RectF {
+        <Self as Loader>::string_ink_bounds(self, text, point_size, apply_kerning)
+    }
+
+    /// Returns the horizontal kerning to add between the glyphs `left` and `right`, in font units,
+    /// from the `GPOS`, `kerx`, or `kern` table.
+    #[inline]
+    pub fn pair_kerning(&self, left: u32, right: u32) -> Option<f32> {
+        <Self as Loader>::pair_kerning(self, left, right)
+    }
+
+    /// Returns true if the font has any kerning data in its `GPOS`, `kerx`, or `kern` table.
+    #[inline]
+    pub fn has_kerning(&self) -> bool {
+        *self
+            .has_kerning
+            .get_or_init(|| loader::has_kerning_data(self))
+    }
+
+    /// Reports which OpenType and AAT layout tables the font contains.
+    #[inline]
+    pub fn shaping_tables(&self) -> ShapingTables {
+        <Self as Loader>::shaping_tables(self)
+    }
+
+    /// Returns the distance from the origin of the glyph with the given ID to the next, in font
+    /// units.
+    pub fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+        self.advance_cache
+            .get_or_try_insert_with(glyph_id, || unsafe {
+                loader::checked_glyph_id(self, glyph_id)?;
+                if FT_Load_Glyph(
+                    self.freetype_face,
+                    glyph_id,
+                    FT_LOAD_DEFAULT | FT_LOAD_NO_HINTING,
+                ) != 0
+                {
+                    return Err(GlyphLoadingError::NoSuchGlyph);
+                }
+
+                let advance = (*(*self.freetype_face).glyph).advance;
+                Ok(Vector2I::new(advance.x as i32, advance.y as i32).ft_fixed_26_6_to_f32())
+            })
+    }
+
     /// Returns the amount that the given glyph should be displaced from the origin.
     ///
     /// FIXME(pcwalton): This always returns zero on FreeType.
-    pub fn origin(&self, _: u32) -> Result<Vector2F, GlyphLoadingError> {
+    pub fn origin(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
+        loader::checked_glyph_id(self, glyph_id)?;
         warn!("unimplemented");
         Ok(Vector2F::default())
     }
 
     /// Retrieves various metrics that apply to the entire font.
+    ///
+    /// Bitmap-only fonts have no units per em of their own, so their metrics are given in pixels
+    /// of the first embedded strike, and `units_per_em` is that strike's pixel size.
     pub fn metrics(&self) -> Metrics {
+        if let Some(metrics) = self.bitmap_strike_metrics() {
+            return metrics;
+        }
+
         let os2_table = self.get_os2_table();
         unsafe {
             let ascender = (*self.freetype_face).ascender;
@@ -648,11 +1158,17 @@ impl Font {
             let bounding_box_origin = Vector2I::new(bbox.xMin as i32, bbox.yMin as i32);
             let bounding_box_lower_right = Vector2I::new(bbox.xMax as i32, bbox.yMax as i32);
             let bounding_box = RectI::from_points(bounding_box_origin, bounding_box_lower_right);
+            let (win_ascent, win_descent) =
+                loader::win_metrics(self).unwrap_or((ascender as f32, descender as f32));
 
             Metrics {
-                units_per_em: (*self.freetype_face).units_per_EM as u32,
+                units_per_em: loader::sanitize_units_per_em(
+                    (*self.freetype_face).units_per_EM as u32,
+                ),
                 ascent: ascender as f32,
                 descent: descender as f32,
+                win_ascent,
+                win_descent,
                 line_gap: ((*self.freetype_face).height + descender - ascender) as f32,
                 underline_position: (underline_position + underline_thickness / 2) as f32,
                 underline_thickness: underline_thickness as f32,
@@ -667,32 +1183,77 @@ impl Font {
         }
     }
 
-    /// Returns true if and only if the font loader can perform hinting in the requested way.
+    /// Returns the font's metrics, measuring the cap height and x-height from glyphs if the font
+    /// doesn't declare them.
     ///
-    /// Some APIs support only rasterizing glyphs with hinting, not retrieving hinted outlines. If
-    /// `for_rasterization` is false, this function returns true if and only if the loader supports
-    /// retrieval of hinted *outlines*. If `for_rasterization` is true, this function returns true
-    /// if and only if the loader supports *rasterizing* hinted glyphs.
+    /// See `Loader::metrics_with_fallbacks()`.
     #[inline]
-    pub fn supports_hinting_options(
-        &self,
-        hinting_options: HintingOptions,
-        for_rasterization: bool,
-    ) -> bool {
-        match (hinting_options, for_rasterization) {
-            (HintingOptions::None, _)
-            | (HintingOptions::Vertical(_), true)
-            | (HintingOptions::VerticalSubpixel(_), true)
-            | (HintingOptions::Full(_), true) => true,
-            (HintingOptions::Vertical(_), false)
-            | (HintingOptions::VerticalSubpixel(_), false)
-            | (HintingOptions::Full(_), false) => false,
-        }
+    pub fn metrics_with_fallbacks(&self) -> (Metrics, MetricsSource) {
+        <Self as Loader>::metrics_with_fallbacks(self)
     }
 
-    fn get_type_1_or_sfnt_name(&self, type_1_id: u32, sfnt_id: u16) -> Option<String> {
+    /// Returns the font's metrics rescaled to `units_per_em` units per em.
+    #[inline]
+    pub fn metrics_normalized(&self, units_per_em: u32) -> Metrics {
+        <Self as Loader>::metrics_normalized(self, units_per_em)
+    }
+
+    /// Returns a view of this font that reports values in font units for an em `units_per_em`
+    /// units wide.
+    #[inline]
+    pub fn normalized(&self, units_per_em: u32) -> NormalizedFont<'_, Font> {
+        <Self as Loader>::normalized(self, units_per_em)
+    }
+
+    // Synthesizes metrics for bitmap-only fonts from the currently selected strike, which is the
+    // first one except during rasterization.
+    fn bitmap_strike_metrics(&self) -> Option<Metrics> {
         unsafe {
-            let ps_value_size =
+            if (*self.freetype_face).units_per_EM != 0 || (*self.freetype_face).size.is_null() {
+                return None;
+            }
+            let size_metrics = &(*(*self.freetype_face).size).metrics;
+            if size_metrics.y_ppem == 0 {
+                return None;
+            }
+
+            let ascent = size_metrics.ascender as f32 / 64.0;
+            let descent = size_metrics.descender as f32 / 64.0;
+            let max_advance = size_metrics.max_advance as f32 / 64.0;
+            Some(Metrics {
+                units_per_em: size_metrics.y_ppem as u32,
+                ascent,
+                descent,
+                win_ascent: ascent,
+                win_descent: descent,
+                line_gap: size_metrics.height as f32 / 64.0 - (ascent - descent),
+                underline_position: 0.0,
+                underline_thickness: 0.0,
+                cap_height: 0.0,
+                x_height: 0.0,
+                bounding_box: RectF::from_points(
+                    Vector2F::new(0.0, descent),
+                    Vector2F::new(max_advance, ascent),
+                ),
+            })
+        }
+    }
+
+    /// Returns true if and only if the font loader can perform hinting in the requested way.
+    ///
+    /// Some APIs support only rasterizing glyphs with hinting, not retrieving hinted outlines. If
+    /// `for_rasterization` is false, this function returns true if and only if the loader supports
+    /// retrieval of hinted *outlines*. If `for_rasterization` is true, this function returns true
+    /// if and only if the loader supports *rasterizing* hinted glyphs.
+    #[inline]
+    pub fn supports_hinting_options(&self, _: HintingOptions, _: bool) -> bool {
+        // FreeType hints outlines and rasterized glyphs alike.
+        true
+    }
+
+    fn get_type_1_or_sfnt_name(&self, type_1_id: u32, sfnt_id: u16) -> Option<String> {
+        unsafe {
+            let ps_value_size =
                 FT_Get_PS_Font_Value(self.freetype_face, type_1_id, 0, ptr::null_mut(), 0);
             if ps_value_size > 0 {
                 let mut buffer = vec![0; ps_value_size as usize];
@@ -707,42 +1268,10 @@ impl Font {
                     return String::from_utf8(buffer).ok();
                 }
             }
-
-            let sfnt_name_count = FT_Get_Sfnt_Name_Count(self.freetype_face);
-            let mut sfnt_name = mem::zeroed();
-            for sfnt_name_index in 0..sfnt_name_count {
-                assert_eq!(
-                    FT_Get_Sfnt_Name(self.freetype_face, sfnt_name_index, &mut sfnt_name),
-                    0
-                );
-                if sfnt_name.name_id != sfnt_id {
-                    continue;
-                }
-
-                match (sfnt_name.platform_id, sfnt_name.encoding_id) {
-                    (TT_PLATFORM_APPLE_UNICODE, _) => {
-                        let mut sfnt_name_bytes =
-                            slice::from_raw_parts(sfnt_name.string, sfnt_name.string_len as usize);
-                        let mut sfnt_name_string = Vec::with_capacity(sfnt_name_bytes.len() / 2);
-                        while !sfnt_name_bytes.is_empty() {
-                            sfnt_name_string.push(sfnt_name_bytes.read_u16::<BigEndian>().unwrap())
-                        }
-                        if let Ok(result) = String::from_utf16(&sfnt_name_string) {
-                            return Some(result);
-                        }
-                    }
-                    (platform_id, _) => {
-                        warn!(
-                            "get_type_1_or_sfnt_name(): found invalid platform ID {}",
-                            platform_id
-                        );
-                        // TODO(pcwalton)
-                    }
-                }
-            }
-
-            None
         }
+        // Unlike `name_string()`, this ignores the system locale, as Core Text and DirectWrite do.
+        let name_table = self.load_font_table(tables::name::TAG)?;
+        tables::name::lookup(&name_table, sfnt_id)
     }
 
     fn get_os2_table(&self) -> Option<*const TT_OS2> {
@@ -767,10 +1296,9 @@ impl Font {
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
     ) -> Result<RectI, GlyphLoadingError> {
-        <Self as Loader>::raster_bounds(
-            self,
+        self.raster_bounds_scaled(
             glyph_id,
-            point_size,
+            Vector2F::splat(point_size),
             transform,
             hinting_options,
             rasterization_options,
@@ -786,6 +1314,7 @@ impl Font {
     /// loader.
     ///
     /// If `hinting_options` is not None, the requested grid fitting is performed.
+    #[inline]
     pub fn rasterize_glyph(
         &self,
         canvas: &mut Canvas,
@@ -794,7 +1323,208 @@ impl Font {
         transform: Transform2F,
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
-    ) -> Result<(), GlyphLoadingError> {
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph_scaled(
+            canvas,
+            glyph_id,
+            Vector2F::splat(point_size),
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the pixel boundaries that the glyph will take up when rendered using this loader's
+    /// rasterizer at the given per-axis size and transform.
+    ///
+    /// The glyph is loaded and hinted exactly as `rasterize_glyph_scaled()` loads it, and the
+    /// bounds come from its control box, so the glyph isn't rendered.
+    pub fn raster_bounds_scaled(
+        &self,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        self.load_glyph(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            true,
+            |load_flags| unsafe { Ok(self.loaded_glyph_raster_bounds(transform, load_flags)) },
+        )
+    }
+
+    /// Rasterizes a glyph to a canvas with the given per-axis size and transform.
+    ///
+    /// The x component of `point_size` is the horizontal size and the y component is the vertical
+    /// size. FreeType scales (and hints) the glyph to these dimensions directly, which gives
+    /// better results than stretching the rasterized bitmap.
+    #[inline]
+    pub fn rasterize_glyph_scaled(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph_scaled_with_bitmap_preference(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            true,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, choosing whether to draw it from an embedded
+    /// bitmap strike.
+    ///
+    /// If `prefer_bitmaps` is true, as it is for `rasterize_glyph()`, the glyph is copied from the
+    /// strike whose size matches the requested pixel size, rounded to whole pixels, and its
+    /// outline is rendered when no strike matches. If it's false, scalable fonts are always
+    /// rendered from their outlines. Bitmap-only fonts always use their strikes.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_bitmap_preference(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        prefer_bitmaps: bool,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph_scaled_with_bitmap_preference(
+            canvas,
+            glyph_id,
+            Vector2F::splat(point_size),
+            transform,
+            hinting_options,
+            rasterization_options,
+            prefer_bitmaps,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_glyph_scaled_with_bitmap_preference(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        prefer_bitmaps: bool,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.render_glyph(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            prefer_bitmaps,
+            |rendered| {
+                let rasterized_glyph = RasterizedGlyph::new(canvas, rendered.rect, rendered.source);
+                if rendered.buffer.is_empty() {
+                    // Nothing was drawn, as for a space.
+                } else if rendered.flip_x || rendered.flip_y {
+                    let format = match rendered.pixel_mode {
+                        FT_PIXEL_MODE_LCD | FT_PIXEL_MODE_LCD_V => Format::Rgb24,
+                        _ => Format::A8,
+                    };
+                    let mut bitmap_canvas = Canvas::new(rendered.rect.size(), format);
+                    blit_bitmap(
+                        &mut bitmap_canvas,
+                        Vector2I::zero(),
+                        rendered.buffer,
+                        rendered.bitmap_size,
+                        rendered.bitmap_stride,
+                        rendered.pixel_mode,
+                    );
+                    bitmap_canvas.flip(rendered.flip_x, rendered.flip_y);
+                    if (rendered.pixel_mode == FT_PIXEL_MODE_LCD && rendered.flip_x)
+                        || (rendered.pixel_mode == FT_PIXEL_MODE_LCD_V && rendered.flip_y)
+                    {
+                        bitmap_canvas.reverse_subpixels();
+                    }
+                    canvas.blit_from(
+                        rendered.rect.origin(),
+                        &bitmap_canvas.pixels,
+                        bitmap_canvas.size,
+                        bitmap_canvas.stride,
+                        format,
+                    );
+                } else {
+                    blit_bitmap(
+                        canvas,
+                        rendered.rect.origin(),
+                        rendered.buffer,
+                        rendered.bitmap_size,
+                        rendered.bitmap_stride,
+                        rendered.pixel_mode,
+                    );
+                }
+                rasterized_glyph
+            },
+        )
+    }
+
+    // Loads and renders a glyph the way `rasterize_glyph_scaled()` does, and passes the bitmap
+    // and where it lands on the canvas to `f`.
+    #[allow(clippy::too_many_arguments)]
+    fn render_glyph<R>(
+        &self,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        prefer_bitmaps: bool,
+        f: impl FnOnce(&GlyphBitmap) -> R,
+    ) -> Result<R, GlyphLoadingError> {
+        self.load_glyph(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            prefer_bitmaps,
+            |load_flags| unsafe {
+                // The bitmap belongs to the glyph slot, so use it before the face is reset.
+                self.render_loaded_glyph(transform, load_flags)
+                    .map(|rendered| f(&rendered))
+            },
+        )
+    }
+
+    // Sets the face up for `point_size` and `transform`, loads the glyph into the glyph slot with
+    // the flags the rasterizer uses, and calls `f` with those flags. Both the raster bounds and
+    // the rasterizer go through here, so that they agree on the hinting and placement. Unless
+    // `prefer_bitmaps` is set, scalable fonts are loaded from their outlines.
+    #[allow(clippy::too_many_arguments)]
+    fn load_glyph<R>(
+        &self,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        prefer_bitmaps: bool,
+        f: impl FnOnce(i32) -> Result<R, GlyphLoadingError>,
+    ) -> Result<R, GlyphLoadingError> {
+        loader::checked_glyph_id(self, glyph_id)?;
+        let (hinting_options, rasterization_options) =
+            loader::resolve_font_default_hinting(self, hinting_options, rasterization_options);
+
         // TODO(pcwalton): This is woefully incomplete. See WebRender's code for a more complete
         // implementation.
         unsafe {
@@ -814,73 +1544,130 @@ impl Font {
             };
             FT_Set_Transform(self.freetype_face, &mut ft_shape, &mut delta);
 
-            assert_eq!(
-                FT_Set_Char_Size(
-                    self.freetype_face,
-                    point_size.f32_to_ft_fixed_26_6(),
-                    0,
-                    0,
+            if self.is_scalable() {
+                assert_eq!(
+                    FT_Set_Char_Size(
+                        self.freetype_face,
+                        point_size.x().f32_to_ft_fixed_26_6(),
+                        point_size.y().f32_to_ft_fixed_26_6(),
+                        0,
+                        0
+                    ),
                     0
-                ),
-                0
-            );
+                );
+            } else {
+                // Bitmap-only fonts can only be drawn at the sizes of their strikes, so draw the
+                // closest one unscaled.
+                select_nearest_strike(self.freetype_face, point_size.y());
+            }
 
-            let mut load_flags = FT_LOAD_DEFAULT | FT_LOAD_RENDER;
+            let mut load_flags = FT_LOAD_DEFAULT;
             load_flags |= self.hinting_and_rasterization_options_to_load_flags(
                 hinting_options,
                 rasterization_options,
             );
-            if FT_Load_Glyph(self.freetype_face, glyph_id, load_flags) != 0 {
-                return Err(GlyphLoadingError::NoSuchGlyph);
+            // FreeType loads the strike matching the requested size, if there is one, by default.
+            if !prefer_bitmaps && self.is_scalable() {
+                load_flags |= FT_LOAD_NO_BITMAP;
             }
-
-            // TODO(pcwalton): Use the FreeType "direct" API to save a copy here. Note that we will
-            // need to keep this around for bilevel rendering, as the direct API doesn't work with
-            // that mode.
-            let bitmap = &(*(*self.freetype_face).glyph).bitmap;
-            let bitmap_stride = bitmap.pitch as usize;
-            let bitmap_width = bitmap.width;
-            let bitmap_height = bitmap.rows;
-            let bitmap_size = Vector2I::new(bitmap_width, bitmap_height);
-            let bitmap_buffer = bitmap.buffer as *const i8 as *const u8;
-            let bitmap_length = bitmap_stride * bitmap_height as usize;
-            if bitmap_buffer.is_null() {
-                assert_eq!(
-                    bitmap_length, 0,
-                    "bitmap length should be 0 when bitmap_buffer is nullptr"
-                );
+            let result = if FT_Load_Glyph(self.freetype_face, glyph_id, load_flags) != 0 {
+                Err(GlyphLoadingError::NoSuchGlyph)
             } else {
-                let buffer = slice::from_raw_parts(bitmap_buffer, bitmap_length);
-                let dst_point = Vector2I::new(
-                    (*(*self.freetype_face).glyph).bitmap_left,
-                    -(*(*self.freetype_face).glyph).bitmap_top,
-                );
-
-                // FIXME(pcwalton): This function should return a Result instead.
-                match bitmap.pixel_mode as u32 {
-                    FT_PIXEL_MODE_GRAY => {
-                        canvas.blit_from(dst_point, buffer, bitmap_size, bitmap_stride, Format::A8);
-                    }
-                    FT_PIXEL_MODE_LCD | FT_PIXEL_MODE_LCD_V => {
-                        canvas.blit_from(
-                            dst_point,
-                            buffer,
-                            bitmap_size,
-                            bitmap_stride,
-                            Format::Rgb24,
-                        );
-                    }
-                    FT_PIXEL_MODE_MONO => {
-                        canvas.blit_from_bitmap_1bpp(dst_point, buffer, bitmap_size, bitmap_stride);
-                    }
-                    _ => panic!("Unexpected FreeType pixel mode!"),
-                }
-            }
+                f(load_flags)
+            };
 
             FT_Set_Transform(self.freetype_face, ptr::null_mut(), ptr::null_mut());
             reset_freetype_face_char_size(self.freetype_face);
-            Ok(())
+            result
+        }
+    }
+
+    // Returns the pixels that the glyph in the glyph slot will cover once it's rendered.
+    unsafe fn loaded_glyph_raster_bounds(&self, transform: Transform2F, load_flags: i32) -> RectI {
+        let glyph_slot = (*self.freetype_face).glyph;
+        if (*glyph_slot).format == FT_GLYPH_FORMAT_BITMAP {
+            return bitmap_placement(glyph_slot, transform).0;
         }
+
+        // The outline has already been scaled, hinted and transformed, so its control box,
+        // rounded out to whole pixels, is what the smooth and monochrome renderers fill.
+        let mut cbox: FT_BBox = mem::zeroed();
+        FT_Outline_Get_CBox(&(*glyph_slot).outline, &mut cbox);
+        let (mut min_x, mut max_x) = ((cbox.xMin >> 6) as i32, ((cbox.xMax + 63) >> 6) as i32);
+        let (mut min_y, mut max_y) = ((cbox.yMin >> 6) as i32, ((cbox.yMax + 63) >> 6) as i32);
+
+        // The LCD filter spreads coverage into the neighboring pixels.
+        match render_mode_for_load_flags(load_flags) {
+            FT_RENDER_MODE_LCD => {
+                min_x -= 1;
+                max_x += 1;
+            }
+            FT_RENDER_MODE_LCD_V => {
+                min_y -= 1;
+                max_y += 1;
+            }
+            _ => {}
+        }
+        RectI::from_points(Vector2I::new(min_x, -max_y), Vector2I::new(max_x, -min_y))
+    }
+
+    unsafe fn render_loaded_glyph(
+        &self,
+        transform: Transform2F,
+        load_flags: i32,
+    ) -> Result<GlyphBitmap<'_>, GlyphLoadingError> {
+        // Render separately from loading (rather than passing `FT_LOAD_RENDER`) so that we can
+        // tell embedded bitmaps apart from outlines. The render mode is chosen the same way
+        // `FT_Load_Glyph()` would.
+        let glyph_slot = (*self.freetype_face).glyph;
+        let source = if (*glyph_slot).format == FT_GLYPH_FORMAT_BITMAP {
+            GlyphSource::EmbeddedBitmap
+        } else {
+            if FT_Render_Glyph(glyph_slot, render_mode_for_load_flags(load_flags)) != 0 {
+                return Err(GlyphLoadingError::NoSuchGlyph);
+            }
+            GlyphSource::Outline
+        };
+
+        // TODO(pcwalton): Use the FreeType "direct" API to save a copy here. Note that we will
+        // need to keep this around for bilevel rendering, as the direct API doesn't work with
+        // that mode.
+        let bitmap = &(*glyph_slot).bitmap;
+        let bitmap_stride = bitmap.pitch as usize;
+        let bitmap_width = bitmap.width;
+        let bitmap_height = bitmap.rows;
+        let bitmap_size = Vector2I::new(bitmap_width, bitmap_height);
+        let bitmap_buffer = bitmap.buffer as *const i8 as *const u8;
+        let bitmap_length = bitmap_stride * bitmap_height as usize;
+        let (rect, flip_x, flip_y) = match source {
+            GlyphSource::EmbeddedBitmap => bitmap_placement(glyph_slot, transform),
+            _ => {
+                let (bitmap_left, bitmap_top) =
+                    ((*glyph_slot).bitmap_left, (*glyph_slot).bitmap_top);
+                let origin = Vector2I::new(bitmap_left, -bitmap_top);
+                (RectI::new(origin, bitmap_ink_size(bitmap)), false, false)
+            }
+        };
+
+        let buffer = if bitmap_buffer.is_null() {
+            assert_eq!(
+                bitmap_length, 0,
+                "bitmap length should be 0 when bitmap_buffer is nullptr"
+            );
+            &[][..]
+        } else {
+            slice::from_raw_parts(bitmap_buffer, bitmap_length)
+        };
+        Ok(GlyphBitmap {
+            buffer,
+            bitmap_size,
+            bitmap_stride,
+            pixel_mode: bitmap.pixel_mode as u32,
+            rect,
+            source,
+            flip_x,
+            flip_y,
+        })
     }
 
     fn hinting_and_rasterization_options_to_load_flags(
@@ -894,9 +1681,14 @@ impl Font {
             }
             (HintingOptions::None, _) => FT_LOAD_TARGET_NORMAL | FT_LOAD_NO_HINTING,
             (HintingOptions::Vertical(_), RasterizationOptions::Bilevel)
-            | (HintingOptions::Full(_), RasterizationOptions::Bilevel) => FT_LOAD_TARGET_MONO,
+            | (HintingOptions::Full(_), RasterizationOptions::Bilevel)
+            | (HintingOptions::FontDefault(_), RasterizationOptions::Bilevel) => {
+                FT_LOAD_TARGET_MONO
+            }
             (HintingOptions::Vertical(_), _) => FT_LOAD_TARGET_LIGHT,
-            (HintingOptions::Full(_), _) => FT_LOAD_TARGET_NORMAL,
+            (HintingOptions::Full(_), _) | (HintingOptions::FontDefault(_), _) => {
+                FT_LOAD_TARGET_NORMAL
+            }
         };
         if rasterization == RasterizationOptions::Bilevel {
             options |= FT_LOAD_MONOCHROME
@@ -904,33 +1696,436 @@ impl Font {
         options
     }
 
-    /// Returns a handle to this font, if possible.
-    ///
-    /// This is useful if you want to open the font with a different loader.
+    /// Returns a handle to this font, if possible.
+    ///
+    /// This is useful if you want to open the font with a different loader.
+    #[inline]
+    pub fn handle(&self) -> Option<Handle> {
+        self.handle
+            .clone()
+            .or_else(|| <Self as Loader>::handle(self))
+    }
+
+    /// Returns the path of the file that this font was loaded from, if it was loaded from a path.
+    #[inline]
+    pub fn source_path(&self) -> Option<&Path> {
+        match self.handle {
+            Some(Handle::Path { ref path, .. }) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Attempts to return the raw font data (contents of the font file).
+    ///
+    /// If this font is a member of a collection, this function returns the data for the entire
+    /// collection.
+    pub fn copy_font_data(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.font_data.clone())
+    }
+
+    /// Returns the byte offset of this font's table directory within the data that
+    /// `copy_font_data()` returns: 0, unless the font is a member of a collection.
+    pub fn face_byte_offset(&self) -> Option<u32> {
+        // The upper bits of the face index select a named instance of a variable font.
+        let font_index = unsafe { ((*self.freetype_face).face_index & 0xffff) as u32 };
+        loader::face_byte_offset(&self.font_data, font_index)
+    }
+
+    /// Get font fallback results for the given text and locale.
+    ///
+    /// Note: this is currently just a stub implementation, a proper implementation
+    /// would likely use FontConfig, at least on Linux. It's not clear what a
+    /// FreeType loader with a non-FreeType source should do.
+    fn get_fallbacks(&self, text: &str, _locale: &str) -> FallbackResult<Font> {
+        warn!("unsupported");
+        FallbackResult {
+            fonts: Vec::new(),
+            valid_len: text.len(),
+        }
+    }
+
+    /// Returns the size-dependent tracking adjustment from the AAT `trak` table, in font units.
+    ///
+    /// Add this to the advance of every glyph to match native macOS text layout.
+    #[inline]
+    pub fn tracking(&self, point_size: f32) -> Option<f32> {
+        <Self as Loader>::tracking(self, point_size)
+    }
+
+    /// Returns the slope and offset of the text cursor for this font.
+    #[inline]
+    pub fn caret_metrics(&self) -> CaretMetrics {
+        <Self as Loader>::caret_metrics(self)
+    }
+
+    /// Returns the sizes and offsets that the font recommends for subscripts and superscripts, or
+    /// `None` if the font has no `OS/2` table.
+    #[inline]
+    pub fn script_metrics(&self) -> Option<ScriptMetrics> {
+        <Self as Loader>::script_metrics(self)
+    }
+
+    /// Returns the pixel boundaries that the glyph will take up when rendered like
+    /// `rasterize_glyph_with_pixels_per_dip()`.
+    #[inline]
+    pub fn raster_bounds_with_pixels_per_dip(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        <Self as Loader>::raster_bounds_with_pixels_per_dip(
+            self,
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph for a display with `pixels_per_dip` physical pixels per
+    /// device-independent pixel, grid-fitting it to the physical pixel grid.
+    ///
+    /// `point_size` and the size in `hinting_options` are in device-independent pixels.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_pixels_per_dip(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        pixels_per_dip: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_pixels_per_dip(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            pixels_per_dip,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Renders a glyph into a new canvas sized to fit it, with the options in `settings`.
+    ///
+    /// `origin` is the position of the glyph origin within the pixel grid, in physical pixels.
+    #[inline]
+    pub fn render(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        origin: Vector2F,
+        settings: &RenderSettings,
+    ) -> Result<RenderedGlyph, GlyphLoadingError> {
+        <Self as Loader>::render(self, glyph_id, point_size, origin, settings)
+    }
+
+    /// Renders a glyph whose origin is at `position` in layout (device-independent) pixels, for
+    /// display at the `pixels_per_dip` in `settings`.
+    #[inline]
+    pub fn render_at(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        position: Vector2F,
+        settings: &RenderSettings,
+    ) -> Result<PlacedGlyph, GlyphLoadingError> {
+        <Self as Loader>::render_at(self, glyph_id, point_size, position, settings)
+    }
+
+    /// Returns the layers of the glyph if it is a version 0 color glyph from the `COLR` table.
+    #[inline]
+    pub fn color_glyph_layers(&self, glyph_id: u32) -> Option<Vec<ColorLayer>> {
+        <Self as Loader>::color_glyph_layers(self, glyph_id)
+    }
+
+    /// Returns the colors of the palette at `palette_index` in the `CPAL` table.
+    #[inline]
+    pub fn palette(&self, palette_index: u16) -> Option<Vec<ColorU>> {
+        <Self as Loader>::palette(self, palette_index)
+    }
+
+    /// Returns the colors that `selector` picks: the chosen `CPAL` palette with the selector's
+    /// overrides applied.
+    #[inline]
+    pub fn selected_palette(&self, selector: &PaletteSelector) -> Vec<ColorU> {
+        <Self as Loader>::selected_palette(self, selector)
+    }
+
+    /// Returns the layers of a version 0 color glyph, each paired with the color that `selector`
+    /// picks for it.
+    #[inline]
+    pub fn color_glyph_layers_with_palette(
+        &self,
+        glyph_id: u32,
+        selector: &PaletteSelector,
+    ) -> Option<Vec<(ColorLayer, ColorU)>> {
+        <Self as Loader>::color_glyph_layers_with_palette(self, glyph_id, selector)
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, drawing `COLR` color glyphs (including
+    /// version 1 gradients, transforms, and compositing) in color on `Rgba32` canvases.
+    #[inline]
+    pub fn rasterize_color_glyph(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_color_glyph(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_color_glyph()`, drawing color glyphs with the colors
+    /// that `selector` picks.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_color_glyph_with_palette(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        selector: &PaletteSelector,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_color_glyph_with_palette(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            selector,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, producing `Rgba32` output with the requested
+    /// alpha representation.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_alpha_mode(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        alpha_mode: AlphaMode,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_alpha_mode(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            alpha_mode,
+        )
+    }
+
+    /// Rasterizes a glyph like `rasterize_glyph()`, touching only the pixels inside `clip`.
+    ///
+    /// Passing `None` for `clip` is equivalent to calling `rasterize_glyph()`.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_clipped(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        clip: Option<RectI>,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_clipped(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            clip,
+        )
+    }
+
+    /// Returns the named values on this font's design axes, from the OpenType `STAT` table.
+    ///
+    /// Fonts without a `STAT` table return an empty list.
+    #[inline]
+    pub fn style_attributes(&self) -> Vec<StatAxisValue> {
+        <Self as Loader>::style_attributes(self)
+    }
+
+    /// Returns the components of a TrueType composite glyph, each with the transform that places
+    /// it.
+    ///
+    /// Simple glyphs and glyphs in fonts without a `glyf` table return `None`.
+    #[inline]
+    pub fn composite_components(&self, glyph_id: u32) -> Option<Vec<Component>> {
+        <Self as Loader>::composite_components(self, glyph_id)
+    }
+
+    /// Returns the contours of a simple TrueType glyph as stored in the `glyf` table, in font
+    /// units, optionally with the implied on-curve points inserted.
+    ///
+    /// Composite glyphs and glyphs in fonts without a `glyf` table return `None`.
+    #[inline]
+    pub fn glyf_contours(&self, glyph_id: u32, implied_points: ImpliedPoints) -> Option<Outline> {
+        <Self as Loader>::glyf_contours(self, glyph_id, implied_points)
+    }
+
+    /// Returns the contours of a simple TrueType glyph along with the index of each point in the
+    /// `glyf` point ordering that hinting instructions refer to.
+    #[inline]
+    pub fn glyf_contours_with_point_indices(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Option<IndexedOutline> {
+        <Self as Loader>::glyf_contours_with_point_indices(self, glyph_id, implied_points)
+    }
+
+    /// Returns the outline of a TrueType glyph from the `glyf` table in font units, with
+    /// composite glyphs flattened by transforming their components into place.
+    ///
+    /// Fonts without a `glyf` table return `GlyphLoadingError::NoOutline`, and malformed composite
+    /// glyphs, including ones that refer to themselves, return `GlyphLoadingError::Parse`.
+    #[inline]
+    pub fn glyf_outline(
+        &self,
+        glyph_id: u32,
+        implied_points: ImpliedPoints,
+    ) -> Result<Outline, GlyphLoadingError> {
+        <Self as Loader>::glyf_outline(self, glyph_id, implied_points)
+    }
+
+    /// Returns every Unicode code point that the font maps to a glyph, paired with that glyph's
+    /// ID and sorted by code point.
+    ///
+    /// Code points mapped to `.notdef` are omitted.
+    #[inline]
+    pub fn char_to_glyph_mappings(&self) -> Vec<(u32, u32)> {
+        <Self as Loader>::char_to_glyph_mappings(self)
+    }
+
+    /// Returns an iterator over every character that the font maps to a glyph, paired with that
+    /// glyph's ID, in ascending order.
+    #[inline]
+    pub fn char_mappings(&self) -> CharMappingIter {
+        <Self as Loader>::char_mappings(self)
+    }
+
+    /// Returns the font revision from the `head` table.
+    #[inline]
+    pub fn font_revision(&self) -> Option<f32> {
+        <Self as Loader>::font_revision(self)
+    }
+
+    /// Returns the date the font was created, from the `head` table.
+    #[inline]
+    pub fn created_date(&self) -> Option<SystemTime> {
+        <Self as Loader>::created_date(self)
+    }
+
+    /// Returns the date the font was last modified, from the `head` table.
+    #[inline]
+    pub fn modified_date(&self) -> Option<SystemTime> {
+        <Self as Loader>::modified_date(self)
+    }
+
+    /// Returns the ID of the `.notdef` glyph, which is always 0.
     #[inline]
-    pub fn handle(&self) -> Option<Handle> {
-        <Self as Loader>::handle(self)
+    pub fn notdef_glyph(&self) -> u32 {
+        <Self as Loader>::notdef_glyph(self)
     }
 
-    /// Attempts to return the raw font data (contents of the font file).
+    /// Returns true if the `.notdef` glyph, which is drawn for characters that no font supports,
+    /// has no ink.
+    #[inline]
+    pub fn notdef_is_blank(&self) -> bool {
+        <Self as Loader>::notdef_is_blank(self)
+    }
+
+    /// Sends the outline of a conventional missing-glyph placeholder, a hollow box scaled to
+    /// `point_size`, to a path builder, and returns the box's advance.
+    #[inline]
+    pub fn synthesize_notdef<S>(&self, point_size: f32, sink: &mut S) -> Vector2F
+    where
+        S: OutlineSink,
+    {
+        <Self as Loader>::synthesize_notdef(self, point_size, sink)
+    }
+
+    /// Returns how the font recommends that glyphs be rendered at `pixels_per_em`, from its `gasp`
+    /// table.
     ///
-    /// If this font is a member of a collection, this function returns the data for the entire
-    /// collection.
-    pub fn copy_font_data(&self) -> Option<Arc<Vec<u8>>> {
-        Some(self.font_data.clone())
+    /// Fonts without a `gasp` table get the default of grid fitting with grayscale antialiasing.
+    #[inline]
+    pub fn gasp_behavior(&self, pixels_per_em: u16) -> GaspBehavior {
+        <Self as Loader>::gasp_behavior(self, pixels_per_em)
     }
 
-    /// Get font fallback results for the given text and locale.
+    /// Returns true if the font has TrueType hinting instructions in its `fpgm`, `prep`, or
+    /// `glyf` table.
     ///
-    /// Note: this is currently just a stub implementation, a proper implementation
-    /// would likely use FontConfig, at least on Linux. It's not clear what a
-    /// FreeType loader with a non-FreeType source should do.
-    fn get_fallbacks(&self, text: &str, _locale: &str) -> FallbackResult<Font> {
-        warn!("unsupported");
-        FallbackResult {
-            fonts: Vec::new(),
-            valid_len: text.len(),
-        }
+    /// This is computed on first use and then cached.
+    #[inline]
+    pub fn has_hinting_instructions(&self) -> bool {
+        *self
+            .has_hinting_instructions
+            .get_or_init(|| loader::has_hinting_data(self))
+    }
+
+    /// Returns the advance of the glyph in whole pixels at `pixels_per_em` from the font's `hdmx`
+    /// table.
+    #[inline]
+    pub fn hdmx_advance(&self, glyph_id: u32, pixels_per_em: u8) -> Option<u8> {
+        <Self as Loader>::hdmx_advance(self, glyph_id, pixels_per_em)
+    }
+
+    /// Returns the maximum and minimum y values, in pixels, of the font's glyphs at
+    /// `pixels_per_em` from its `VDMX` table.
+    #[inline]
+    pub fn vdmx_metrics(&self, pixels_per_em: u16) -> Option<(i16, i16)> {
+        <Self as Loader>::vdmx_metrics(self, pixels_per_em)
+    }
+
+    /// Returns the advance of the glyph in whole pixels at `pixels_per_em`, as GDI-compatible
+    /// layout computes it.
+    #[inline]
+    pub fn gdi_compatible_advance(
+        &self,
+        glyph_id: u32,
+        pixels_per_em: u16,
+    ) -> Result<f32, GlyphLoadingError> {
+        <Self as Loader>::gdi_compatible_advance(self, glyph_id, pixels_per_em)
     }
 
     /// Returns the raw contents of the OpenType table with the given tag.
@@ -966,36 +2161,82 @@ impl Font {
             Some(buf)
         }
     }
+
+    /// Returns true if the font has an OpenType table with the given tag, without copying it.
+    pub fn has_font_table(&self, table_tag: u32) -> bool {
+        let mut len = 0;
+        unsafe {
+            FT_Load_Sfnt_Table(
+                self.freetype_face,
+                table_tag as FT_ULong,
+                0,
+                ptr::null_mut(),
+                &mut len,
+            ) == 0
+        }
+    }
 }
 
 impl Clone for Font {
     fn clone(&self) -> Font {
         unsafe {
+            let freetype_library = (!self.external_library).then(lock_freetype_library);
             assert_eq!(FT_Reference_Face(self.freetype_face), 0);
+            drop(freetype_library);
             Font {
                 freetype_face: self.freetype_face,
                 font_data: self.font_data.clone(),
+                advance_cache: self.advance_cache.clone(),
+                outline_cache: self.outline_cache.clone(),
+                external_library: self.external_library,
+                handle: self.handle.clone(),
+                has_kerning: self.has_kerning.clone(),
+                has_hinting_instructions: self.has_hinting_instructions.clone(),
+                pitch_info: self.pitch_info.clone(),
+                properties: self.properties.clone(),
             }
         }
     }
 }
 
+// Fonts are equal if they are the same face: they share a FreeType face, as clones do, or load the
+// same face of the same data at the same variation coordinates. Separate copies of the data are
+// compared by their length and `head` table fingerprint rather than byte by byte. Caches don't
+// take part.
+impl PartialEq for Font {
+    fn eq(&self, other: &Font) -> bool {
+        if self.freetype_face == other.freetype_face {
+            return true;
+        }
+        unsafe {
+            (*self.freetype_face).face_index == (*other.freetype_face).face_index
+                && (Arc::ptr_eq(&self.font_data, &other.font_data)
+                    || (self.font_data.len() == other.font_data.len()
+                        && loader::same_head_fingerprint(self, other)))
+                && self.variation_values() == other.variation_values()
+        }
+    }
+}
+
+impl Eq for Font {}
+
 impl Drop for Font {
     fn drop(&mut self) {
-        // The AccessError can be ignored, as it means FREETYPE_LIBRARY has already been
-        // destroyed, and it already destroys all FreeType resources.
-        // https://freetype.org/freetype2/docs/reference/ft2-module_management.html#ft_done_library
-        let _ = FREETYPE_LIBRARY.try_with(|freetype_library| unsafe {
-            if !freetype_library.0.is_null() && !self.freetype_face.is_null() {
-                assert_eq!(FT_Done_Face(self.freetype_face), 0);
-            }
-        });
+        if self.freetype_face.is_null() {
+            return;
+        }
+        let freetype_library = (!self.external_library).then(lock_freetype_library);
+        unsafe {
+            assert_eq!(FT_Done_Face(self.freetype_face), 0);
+        }
+        drop(freetype_library);
     }
 }
 
 impl Debug for Font {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), fmt::Error> {
-        self.family_name().fmt(fmt)
+        let is_color = unsafe { (*self.freetype_face).face_flags & FT_FACE_FLAG_COLOR != 0 };
+        loader::fmt_debug(self, is_color, self.is_monospace(), fmt)
     }
 }
 
@@ -1013,6 +2254,30 @@ impl Loader for Font {
         Font::from_file(file, font_index)
     }
 
+    #[inline]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_path<P>(path: P, font_index: u32) -> Result<Font, FontLoadingError>
+    where
+        P: AsRef<Path>,
+    {
+        Font::from_path(path, font_index)
+    }
+
+    #[inline]
+    fn with_advance_cache(self) -> Self {
+        self.with_advance_cache()
+    }
+
+    #[inline]
+    fn with_variations(&self, variations: &[(u32, f32)]) -> Result<Font, FontLoadingError> {
+        self.with_variations(variations)
+    }
+
+    #[inline]
+    fn variation_coordinates(&self) -> Vec<VariationCoordinate> {
+        self.variation_coordinates()
+    }
+
     #[inline]
     fn analyze_bytes(font_data: Arc<Vec<u8>>) -> Result<FileType, FontLoadingError> {
         Font::analyze_bytes(font_data)
@@ -1053,6 +2318,11 @@ impl Loader for Font {
         self.is_monospace()
     }
 
+    #[inline]
+    fn pitch_info(&self) -> PitchInfo {
+        self.pitch_info()
+    }
+
     #[inline]
     fn properties(&self) -> Properties {
         self.properties()
@@ -1091,6 +2361,11 @@ impl Loader for Font {
         self.typographic_bounds(glyph_id)
     }
 
+    #[inline]
+    fn glyph_is_empty(&self, glyph_id: u32) -> Result<bool, GlyphLoadingError> {
+        self.glyph_is_empty(glyph_id)
+    }
+
     #[inline]
     fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError> {
         self.advance(glyph_id)
@@ -1111,6 +2386,31 @@ impl Loader for Font {
         self.copy_font_data()
     }
 
+    #[inline]
+    fn handle(&self) -> Option<Handle> {
+        self.handle()
+    }
+
+    #[inline]
+    fn source_path(&self) -> Option<&Path> {
+        self.source_path()
+    }
+
+    #[inline]
+    fn has_kerning(&self) -> bool {
+        self.has_kerning()
+    }
+
+    #[inline]
+    fn has_hinting_instructions(&self) -> bool {
+        self.has_hinting_instructions()
+    }
+
+    #[inline]
+    fn face_byte_offset(&self) -> Option<u32> {
+        self.face_byte_offset()
+    }
+
     #[inline]
     fn supports_hinting_options(
         &self,
@@ -1120,6 +2420,42 @@ impl Loader for Font {
         self.supports_hinting_options(hinting_options, for_rasterization)
     }
 
+    #[inline]
+    fn raster_bounds(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        self.raster_bounds(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    #[inline]
+    fn raster_bounds_scaled(
+        &self,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RectI, GlyphLoadingError> {
+        self.raster_bounds_scaled(
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
     #[inline]
     fn rasterize_glyph(
         &self,
@@ -1129,7 +2465,7 @@ impl Loader for Font {
         transform: Transform2F,
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
-    ) -> Result<(), GlyphLoadingError> {
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
         self.rasterize_glyph(
             canvas,
             glyph_id,
@@ -1140,6 +2476,48 @@ impl Loader for Font {
         )
     }
 
+    #[inline]
+    fn rasterize_glyph_with_bitmap_preference(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        prefer_bitmaps: bool,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph_with_bitmap_preference(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            prefer_bitmaps,
+        )
+    }
+
+    #[inline]
+    fn rasterize_glyph_scaled(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: Vector2F,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<RasterizedGlyph, GlyphLoadingError> {
+        self.rasterize_glyph_scaled(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
     #[inline]
     fn get_fallbacks(&self, text: &str, locale: &str) -> FallbackResult<Self> {
         self.get_fallbacks(text, locale)
@@ -1149,6 +2527,41 @@ impl Loader for Font {
     fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>> {
         self.load_font_table(table_tag)
     }
+
+    #[inline]
+    fn has_font_table(&self, table_tag: u32) -> bool {
+        self.has_font_table(table_tag)
+    }
+}
+
+// Creates a face from font data. The caller must hold whatever lock guards `freetype_library`.
+unsafe fn new_memory_face(
+    freetype_library: FT_Library,
+    font_data: &[u8],
+    font_index: u32,
+) -> Result<FT_Face, FontLoadingError> {
+    loader::check_font_index(font_data, font_index)?;
+    let mut freetype_face = ptr::null_mut();
+    if FT_New_Memory_Face(
+        freetype_library,
+        font_data.as_ptr(),
+        font_data.len() as FT_Long,
+        font_index as FT_Long,
+        &mut freetype_face,
+    ) != 0
+    {
+        return Err(FontLoadingError::Parse);
+    }
+    Ok(freetype_face)
+}
+
+// Called by FreeType with the face when it is destroyed, to release the font data that
+// `Font::from_freetype_face()` attached to it.
+extern "C" fn release_face_font_data(face: *mut c_void) {
+    unsafe {
+        let face = face as FT_Face;
+        drop(Arc::from_raw((*face).generic.data as *const Vec<u8>));
+    }
 }
 
 unsafe fn setup_freetype_face(face: FT_Face) {
@@ -1163,6 +2576,131 @@ unsafe fn reset_freetype_face_char_size(face: FT_Face) {
             FT_Set_Char_Size(face, ((*face).units_per_EM as FT_Long) << 6, 0, 0, 0),
             0
         );
+    } else if (*face).num_fixed_sizes > 0 {
+        // Bitmap-only fonts measure everything in pixels of the first strike.
+        FT_Select_Size(face, 0);
+    }
+}
+
+// Returns where the embedded bitmap in `glyph_slot` lands on the canvas, and whether it has to be
+// mirrored horizontally and vertically. FreeType only applies the transform to outlines, so
+// bitmaps are positioned here, and mirrored if the transform flips an axis.
+unsafe fn bitmap_placement(
+    glyph_slot: FT_GlyphSlot,
+    transform: Transform2F,
+) -> (RectI, bool, bool) {
+    let bitmap = &(*glyph_slot).bitmap;
+    let ink_size = bitmap_ink_size(bitmap);
+    let (bitmap_left, bitmap_top) = ((*glyph_slot).bitmap_left, (*glyph_slot).bitmap_top);
+    let mut dst_point = Vector2I::new(bitmap_left, -bitmap_top);
+    let (mut flip_x, mut flip_y) = (false, false);
+    if matches!(
+        bitmap.pixel_mode as u32,
+        FT_PIXEL_MODE_GRAY | FT_PIXEL_MODE_MONO | FT_PIXEL_MODE_LCD | FT_PIXEL_MODE_LCD_V
+    ) {
+        flip_x = transform.m11() < 0.0;
+        flip_y = transform.m22() < 0.0;
+    }
+    if flip_x {
+        dst_point.set_x(-(bitmap_left + ink_size.x()));
+    }
+    if flip_y {
+        dst_point.set_y(bitmap_top - ink_size.y());
+    }
+    dst_point += transform.vector.round().to_i32();
+    (RectI::new(dst_point, ink_size), flip_x, flip_y)
+}
+
+// Returns the size in pixels of a FreeType bitmap, which for LCD bitmaps is a third of its size
+// in subpixels.
+fn bitmap_ink_size(bitmap: &FT_Bitmap) -> Vector2I {
+    let bitmap_size = Vector2I::new(bitmap.width, bitmap.rows);
+    match bitmap.pixel_mode as u32 {
+        FT_PIXEL_MODE_LCD => Vector2I::new(bitmap_size.x() / 3, bitmap_size.y()),
+        FT_PIXEL_MODE_LCD_V => Vector2I::new(bitmap_size.x(), bitmap_size.y() / 3),
+        _ => bitmap_size,
+    }
+}
+
+// Returns the render mode that `FT_Load_Glyph()` would use with `FT_LOAD_RENDER` and these flags.
+fn render_mode_for_load_flags(load_flags: i32) -> FT_Render_Mode {
+    if (load_flags & FT_LOAD_MONOCHROME) != 0 {
+        FT_RENDER_MODE_MONO
+    } else {
+        ((load_flags >> 16) & 0xf) as FT_Render_Mode
+    }
+}
+
+// A glyph bitmap in FreeType's glyph slot, and the rectangle it covers on the canvas.
+struct GlyphBitmap<'a> {
+    buffer: &'a [u8],
+    bitmap_size: Vector2I,
+    bitmap_stride: usize,
+    pixel_mode: u32,
+    rect: RectI,
+    source: GlyphSource,
+    flip_x: bool,
+    flip_y: bool,
+}
+
+// Copies a FreeType bitmap into the canvas with its top left corner at `dst_point`.
+fn blit_bitmap(
+    canvas: &mut Canvas,
+    dst_point: Vector2I,
+    buffer: &[u8],
+    bitmap_size: Vector2I,
+    bitmap_stride: usize,
+    pixel_mode: u32,
+) {
+    // FIXME(pcwalton): This function should return a Result instead.
+    match pixel_mode {
+        FT_PIXEL_MODE_GRAY => {
+            canvas.blit_from(dst_point, buffer, bitmap_size, bitmap_stride, Format::A8);
+        }
+        FT_PIXEL_MODE_LCD => {
+            // Each pixel is three consecutive bytes, one per subpixel.
+            let size = Vector2I::new(bitmap_size.x() / 3, bitmap_size.y());
+            canvas.blit_from(dst_point, buffer, size, bitmap_stride, Format::Rgb24);
+        }
+        FT_PIXEL_MODE_LCD_V => {
+            // Each pixel is three rows tall, one per subpixel, so interleave the rows.
+            let size = Vector2I::new(bitmap_size.x(), bitmap_size.y() / 3);
+            let row_length = size.x() as usize * 3;
+            let mut pixels = vec![0; row_length * size.y() as usize];
+            for (y, row) in pixels.chunks_mut(row_length).enumerate() {
+                for (x, pixel) in row.chunks_mut(3).enumerate() {
+                    for (channel, value) in pixel.iter_mut().enumerate() {
+                        *value = buffer[(y * 3 + channel) * bitmap_stride + x];
+                    }
+                }
+            }
+            canvas.blit_from(dst_point, &pixels, size, row_length, Format::Rgb24);
+        }
+        FT_PIXEL_MODE_MONO => {
+            canvas.blit_from_bitmap_1bpp(dst_point, buffer, bitmap_size, bitmap_stride);
+        }
+        _ => panic!("Unexpected FreeType pixel mode!"),
+    }
+}
+
+// Selects the embedded bitmap strike whose pixel size is closest to `pixel_size`.
+unsafe fn select_nearest_strike(face: FT_Face, pixel_size: f32) {
+    let strikes = if (*face).available_sizes.is_null() {
+        &[][..]
+    } else {
+        slice::from_raw_parts(
+            (*face).available_sizes,
+            (*face).num_fixed_sizes.max(0) as usize,
+        )
+    };
+    let nearest_strike = strikes
+        .iter()
+        .map(|strike| (strike.y_ppem as f32 / 64.0 - pixel_size).abs())
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index);
+    if let Some(index) = nearest_strike {
+        FT_Select_Size(face, index as FT_Int);
     }
 }
 
@@ -1208,7 +2746,21 @@ impl FtFixedToF32 for RectI {
     }
 }
 
+unsafe fn c_string_to_owned(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    }
+}
+
 extern "C" {
+    fn FT_Library_Version(
+        library: FT_Library,
+        amajor: *mut FT_Int,
+        aminor: *mut FT_Int,
+        apatch: *mut FT_Int,
+    );
     fn FT_Get_Font_Format(face: FT_Face) -> *const c_char;
     fn FT_Get_BDF_Property(
         face: FT_Face,
@@ -1233,14 +2785,242 @@ extern "C" {
 
 #[cfg(test)]
 mod test {
-    use crate::loaders::freetype::Font;
+    use crate::canvas::{Canvas, Format, GlyphSource, RasterizationOptions};
+    use crate::error::GlyphLoadingError;
+    use crate::hinting::HintingOptions;
+    use crate::loaders::freetype::{
+        blit_bitmap, freetype_version, lock_freetype_library, Font, FT_PIXEL_MODE_LCD,
+        FT_PIXEL_MODE_LCD_V,
+    };
+    use crate::outline::OutlineBuilder;
+    use freetype_sys::{FT_Done_Face, FT_Done_FreeType, FT_Init_FreeType};
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use std::fs;
+    use std::ptr;
+    use std::sync::Arc;
 
     static PCF_FONT_PATH: &str = "resources/tests/times-roman-pcf/timR12.pcf";
     static PCF_FONT_POSTSCRIPT_NAME: &str = "Times-Roman";
+    const PCF_FONT_STRIKE_SIZE: u32 = 17;
+    static TTF_FONT_PATH: &str = "resources/tests/eb-garamond/EBGaramond12-Regular.ttf";
+
+    // The oldest FreeType that `freetype-sys` accepts when linking against the system library.
+    const MIN_FREETYPE_VERSION: (i32, i32, i32) = (2, 12, 1);
 
     #[test]
     fn get_pcf_postscript_name() {
         let font = Font::from_path(PCF_FONT_PATH, 0).unwrap();
         assert_eq!(font.postscript_name().unwrap(), PCF_FONT_POSTSCRIPT_NAME);
     }
+
+    #[test]
+    fn blit_lcd_bitmaps() {
+        // A 2×2 pixel bitmap with its subpixels laid out side by side, padded to a stride of 8.
+        let buffer = [1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12, 0, 0];
+        let mut canvas = Canvas::new(Vector2I::splat(2), Format::Rgb24);
+        blit_bitmap(
+            &mut canvas,
+            Vector2I::zero(),
+            &buffer,
+            Vector2I::new(6, 2),
+            8,
+            FT_PIXEL_MODE_LCD,
+        );
+        assert_eq!(canvas.pixels, (1..=12).collect::<Vec<u8>>());
+
+        // The same pixels with their subpixels stacked in rows, padded to a stride of 4.
+        let buffer = [
+            1, 4, 0, 0, 2, 5, 0, 0, 3, 6, 0, 0, 7, 10, 0, 0, 8, 11, 0, 0, 9, 12, 0, 0,
+        ];
+        let mut canvas = Canvas::new(Vector2I::splat(2), Format::Rgb24);
+        blit_bitmap(
+            &mut canvas,
+            Vector2I::zero(),
+            &buffer,
+            Vector2I::new(2, 6),
+            4,
+            FT_PIXEL_MODE_LCD_V,
+        );
+        assert_eq!(canvas.pixels, (1..=12).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn load_font_with_external_library() {
+        let font_data = Arc::new(fs::read(PCF_FONT_PATH).unwrap());
+        unsafe {
+            let mut library = ptr::null_mut();
+            assert_eq!(FT_Init_FreeType(&mut library), 0);
+            {
+                let font = Font::from_bytes_with_library(library, font_data, 0).unwrap();
+                let font_clone = font.clone();
+                drop(font);
+                assert_eq!(
+                    font_clone.postscript_name().unwrap(),
+                    PCF_FONT_POSTSCRIPT_NAME
+                );
+            }
+            assert_eq!(FT_Done_FreeType(library), 0);
+        }
+    }
+
+    #[test]
+    fn native_font_references() {
+        let font = Font::from_path(TTF_FONT_PATH, 0).unwrap();
+        let native_font = font.native_font();
+        let font_clone = font.clone();
+        drop(font);
+        drop(font_clone);
+
+        unsafe {
+            // The reference returned by `native_font()` keeps the face alive after the fonts are
+            // gone, and a font created from it takes that reference over.
+            assert!((*native_font).num_glyphs > 0);
+            let font = Font::from_native_font(&native_font);
+            let native_font_copy = font.native_font();
+            assert_eq!(native_font_copy, native_font);
+            {
+                let _library = lock_freetype_library();
+                assert_eq!(FT_Done_Face(native_font_copy), 0);
+            }
+            let font_clone = font.clone();
+            drop(font);
+            assert_eq!(
+                font_clone.postscript_name().unwrap(),
+                "EBGaramond12-Regular"
+            );
+        }
+    }
+
+    #[test]
+    fn get_diagnostics() {
+        let font = Font::from_path(TTF_FONT_PATH, 0).unwrap();
+        let diagnostics = font.diagnostics();
+        assert!(diagnostics.scalable);
+        assert!(diagnostics.sfnt);
+        assert!(!diagnostics.fixed_sizes);
+        assert_eq!(diagnostics.num_fixed_sizes, 0);
+        assert_eq!(diagnostics.driver_name.as_deref(), Some("truetype"));
+        assert_eq!(diagnostics.font_format.as_deref(), Some("TrueType"));
+        assert!(diagnostics.freetype_version >= MIN_FREETYPE_VERSION);
+        assert_eq!(diagnostics.freetype_version, freetype_version());
+
+        let font = Font::from_path(PCF_FONT_PATH, 0).unwrap();
+        let diagnostics = font.diagnostics();
+        assert!(!diagnostics.scalable);
+        assert!(diagnostics.fixed_sizes);
+        assert!(diagnostics.num_fixed_sizes > 0);
+        assert_eq!(diagnostics.font_format.as_deref(), Some("PCF"));
+    }
+
+    #[test]
+    fn load_bitmap_only_font() {
+        let font = Font::from_path(PCF_FONT_PATH, 0).unwrap();
+        let glyph = font.glyph_for_char('A').unwrap();
+
+        let mut builder = OutlineBuilder::new();
+        assert_eq!(
+            font.outline(glyph, HintingOptions::None, &mut builder),
+            Err(GlyphLoadingError::NoOutline)
+        );
+        assert_eq!(
+            font.outline(font.glyph_count(), HintingOptions::None, &mut builder),
+            Err(GlyphLoadingError::NoSuchGlyph)
+        );
+
+        // Metrics are in pixels of the font's only strike, which is 17 pixels tall.
+        let metrics = font.metrics();
+        assert_eq!(metrics.units_per_em, PCF_FONT_STRIKE_SIZE);
+        assert!(metrics.ascent > 0.0);
+        assert!(metrics.descent < 0.0);
+        assert!(metrics.ascent - metrics.descent <= PCF_FONT_STRIKE_SIZE as f32);
+
+        let point_size = PCF_FONT_STRIKE_SIZE as f32;
+        let transform = Transform2F::from_translation(Vector2F::new(4.0, 20.0));
+        let raster_bounds = font
+            .raster_bounds(
+                glyph,
+                point_size,
+                transform,
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+        let rasterized_glyph = font
+            .rasterize_glyph(
+                &mut canvas,
+                glyph,
+                point_size,
+                transform,
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        assert_eq!(rasterized_glyph.source, GlyphSource::EmbeddedBitmap);
+        assert_eq!(rasterized_glyph.ink_rect, raster_bounds);
+        assert!(canvas.pixels.iter().any(|&value| value != 0));
+
+        // Other sizes draw the nearest strike without panicking.
+        let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph,
+            12.0,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::Bilevel,
+        )
+        .unwrap();
+        assert!(canvas.pixels.iter().any(|&value| value != 0));
+        assert_eq!(font.metrics().units_per_em, PCF_FONT_STRIKE_SIZE);
+    }
+
+    #[test]
+    fn rasterize_mirrored_bitmap_glyph() {
+        let font = Font::from_path(PCF_FONT_PATH, 0).unwrap();
+        let glyph = font.glyph_for_char('L').unwrap();
+        let point_size = PCF_FONT_STRIKE_SIZE as f32;
+        let rasterize = |transform: Transform2F| {
+            let transform = Transform2F::from_translation(Vector2F::splat(16.0)) * transform;
+            let raster_bounds = font
+                .raster_bounds(
+                    glyph,
+                    point_size,
+                    transform,
+                    HintingOptions::None,
+                    RasterizationOptions::GrayscaleAa,
+                )
+                .unwrap();
+            let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+            let rasterized_glyph = font
+                .rasterize_glyph(
+                    &mut canvas,
+                    glyph,
+                    point_size,
+                    transform,
+                    HintingOptions::None,
+                    RasterizationOptions::GrayscaleAa,
+                )
+                .unwrap();
+            assert_eq!(rasterized_glyph.ink_rect, raster_bounds);
+            canvas
+        };
+
+        // Mirroring about the origin at (16, 16) maps pixel x to 31 - x.
+        let canvas = rasterize(Transform2F::default());
+        let mirrored_canvas = rasterize(Transform2F::from_scale(Vector2F::new(-1.0, 1.0)));
+        assert!(canvas.pixels.iter().any(|&value| value != 0));
+        for y in 0..32 {
+            for x in 0..32 {
+                assert_eq!(
+                    canvas.pixels[y * canvas.stride + x],
+                    mirrored_canvas.pixels[y * canvas.stride + 31 - x],
+                    "at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
 }
@@ -12,23 +12,43 @@
 
 #[cfg(all(
     any(target_os = "macos", target_os = "ios"),
+    feature = "coretext",
     not(feature = "loader-freetype-default")
 ))]
 pub use crate::loaders::core_text as default;
 
-#[cfg(all(target_family = "windows", not(feature = "loader-freetype-default")))]
+#[cfg(all(
+    target_family = "windows",
+    feature = "directwrite",
+    not(feature = "loader-freetype-default")
+))]
 pub use crate::loaders::directwrite as default;
 
 #[cfg(any(
     not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    all(any(target_os = "macos", target_os = "ios"), not(feature = "coretext")),
+    all(target_family = "windows", not(feature = "directwrite")),
     feature = "loader-freetype-default"
 ))]
 pub use crate::loaders::freetype as default;
 
-#[cfg(any(target_os = "macos", target_os = "ios"))]
+// Without its native loader, macOS and Windows fall back to FreeType, which must be built.
+#[cfg(all(
+    any(
+        all(any(target_os = "macos", target_os = "ios"), not(feature = "coretext")),
+        all(target_family = "windows", not(feature = "directwrite")),
+    ),
+    not(feature = "loader-freetype")
+))]
+compile_error!(
+    "no font loader is enabled; enable the native loader's feature (`coretext` or `directwrite`) \
+     or `loader-freetype`"
+);
+
+#[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "coretext"))]
 pub mod core_text;
 
-#[cfg(target_family = "windows")]
+#[cfg(all(target_family = "windows", feature = "directwrite"))]
 pub mod directwrite;
 
 #[cfg(any(
@@ -13,7 +13,47 @@
 use float_ord::FloatOrd;
 
 use crate::error::SelectionError;
-use crate::properties::{Properties, Stretch, Style, Weight};
+use crate::properties::{Properties, PropertiesQuery, Stretch, Style, Weight};
+
+/// A font's properties, along with the range of weights and stretches it can be instanced at.
+///
+/// A static font covers only the weight and stretch in its properties. A variable font covers the
+/// ranges of its `wght` and `wdth` axes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candidate {
+    /// The properties of the font's default instance.
+    pub properties: Properties,
+    /// The lightest and heaviest weights that the font covers.
+    pub weight_range: (Weight, Weight),
+    /// The narrowest and widest stretches that the font covers.
+    pub stretch_range: (Stretch, Stretch),
+}
+
+impl Candidate {
+    /// Creates a candidate for a static font, which covers only the weight and stretch in
+    /// `properties`.
+    pub fn new(properties: Properties) -> Candidate {
+        Candidate {
+            properties,
+            weight_range: (properties.weight, properties.weight),
+            stretch_range: (properties.stretch, properties.stretch),
+        }
+    }
+
+    // Returns the properties of the instance of this font that is closest to `query`.
+    fn closest_instance(&self, query: &PropertiesQuery) -> Properties {
+        let mut properties = self.properties;
+        if let Some(weight) = query.weight {
+            let (min, max) = self.weight_range;
+            properties.weight = Weight(weight.0.max(min.0).min(max.0));
+        }
+        if let Some(stretch) = query.stretch {
+            let (min, max) = self.stretch_range;
+            properties.stretch = Stretch(stretch.0.max(min.0).min(max.0));
+        }
+        properties
+    }
+}
 
 /// This follows CSS Fonts Level 3 § 5.2 [1].
 ///
@@ -21,6 +61,17 @@ use crate::properties::{Properties, Stretch, Style, Weight};
 pub fn find_best_match(
     candidates: &[Properties],
     query: &Properties,
+) -> Result<usize, SelectionError> {
+    find_best_match_for_query(candidates, &PropertiesQuery::from(*query))
+}
+
+/// Like `find_best_match`, but only the properties specified in `query` are considered.
+///
+/// Each step of the CSS Fonts Level 3 algorithm whose property is `None` in the query is skipped,
+/// so any value of that property is an acceptable match.
+pub fn find_best_match_for_query(
+    candidates: &[Properties],
+    query: &PropertiesQuery,
 ) -> Result<usize, SelectionError> {
     // Step 4.
     let mut matching_set: Vec<usize> = (0..candidates.len()).collect();
@@ -29,24 +80,73 @@ pub fn find_best_match(
     }
 
     // Step 4a (`font-stretch`).
-    let matching_stretch = if matching_set
+    if let Some(stretch) = query.stretch {
+        let matching_stretch = find_matching_stretch(candidates, &matching_set, stretch);
+        matching_set.retain(|&index| candidates[index].stretch == matching_stretch);
+    }
+
+    // Step 4b (`font-style`).
+    if let Some(style) = query.style {
+        let matching_style = find_matching_style(candidates, &matching_set, style);
+        matching_set.retain(|&index| candidates[index].style == matching_style);
+    }
+
+    // Step 4c (`font-weight`).
+    if let Some(weight) = query.weight {
+        let matching_weight = find_matching_weight(candidates, &matching_set, weight);
+        matching_set.retain(|&index| candidates[index].weight == matching_weight);
+    }
+
+    // Step 4d concerns `font-size`, but fonts in `font-kit` are unsized, so we ignore that.
+
+    // Return the result.
+    matching_set
+        .into_iter()
+        .next()
+        .ok_or(SelectionError::NotFound)
+}
+
+/// Like `find_best_match_for_query`, but each candidate may cover a range of weights and
+/// stretches, as variable fonts do.
+///
+/// A weight or stretch in the query that falls within a candidate's range matches it exactly;
+/// otherwise, the nearest end of the range stands in for the candidate's value. Returns the index
+/// of the best match along with the properties of its instance closest to the query.
+pub fn find_best_match_in_ranges(
+    candidates: &[Candidate],
+    query: &PropertiesQuery,
+) -> Result<(usize, Properties), SelectionError> {
+    let instances: Vec<Properties> = candidates
+        .iter()
+        .map(|candidate| candidate.closest_instance(query))
+        .collect();
+    let index = find_best_match_for_query(&instances, query)?;
+    Ok((index, instances[index]))
+}
+
+fn find_matching_stretch(
+    candidates: &[Properties],
+    matching_set: &[usize],
+    query_stretch: Stretch,
+) -> Stretch {
+    if matching_set
         .iter()
-        .any(|&index| candidates[index].stretch == query.stretch)
+        .any(|&index| candidates[index].stretch == query_stretch)
     {
         // Exact match.
-        query.stretch
-    } else if query.stretch <= Stretch::NORMAL {
+        query_stretch
+    } else if query_stretch <= Stretch::NORMAL {
         // Closest width, first checking narrower values and then wider values.
         match matching_set
             .iter()
-            .filter(|&&index| candidates[index].stretch < query.stretch)
-            .min_by_key(|&&index| FloatOrd(query.stretch.0 - candidates[index].stretch.0))
+            .filter(|&&index| candidates[index].stretch < query_stretch)
+            .min_by_key(|&&index| FloatOrd(query_stretch.0 - candidates[index].stretch.0))
         {
             Some(&matching_index) => candidates[matching_index].stretch,
             None => {
                 let matching_index = *matching_set
                     .iter()
-                    .min_by_key(|&&index| FloatOrd(candidates[index].stretch.0 - query.stretch.0))
+                    .min_by_key(|&&index| FloatOrd(candidates[index].stretch.0 - query_stretch.0))
                     .unwrap();
                 candidates[matching_index].stretch
             }
@@ -55,74 +155,81 @@ pub fn find_best_match(
         // Closest width, first checking wider values and then narrower values.
         match matching_set
             .iter()
-            .filter(|&&index| candidates[index].stretch > query.stretch)
-            .min_by_key(|&&index| FloatOrd(candidates[index].stretch.0 - query.stretch.0))
+            .filter(|&&index| candidates[index].stretch > query_stretch)
+            .min_by_key(|&&index| FloatOrd(candidates[index].stretch.0 - query_stretch.0))
         {
             Some(&matching_index) => candidates[matching_index].stretch,
             None => {
                 let matching_index = *matching_set
                     .iter()
-                    .min_by_key(|&&index| FloatOrd(query.stretch.0 - candidates[index].stretch.0))
+                    .min_by_key(|&&index| FloatOrd(query_stretch.0 - candidates[index].stretch.0))
                     .unwrap();
                 candidates[matching_index].stretch
             }
         }
-    };
-    matching_set.retain(|&index| candidates[index].stretch == matching_stretch);
+    }
+}
 
-    // Step 4b (`font-style`).
-    let style_preference = match query.style {
+fn find_matching_style(
+    candidates: &[Properties],
+    matching_set: &[usize],
+    query_style: Style,
+) -> Style {
+    let style_preference = match query_style {
         Style::Italic => [Style::Italic, Style::Oblique, Style::Normal],
         Style::Oblique => [Style::Oblique, Style::Italic, Style::Normal],
         Style::Normal => [Style::Normal, Style::Oblique, Style::Italic],
     };
-    let matching_style = *style_preference
+    *style_preference
         .iter()
-        .find(|&query_style| {
+        .find(|&preferred_style| {
             matching_set
                 .iter()
-                .any(|&index| candidates[index].style == *query_style)
+                .any(|&index| candidates[index].style == *preferred_style)
         })
-        .unwrap();
-    matching_set.retain(|&index| candidates[index].style == matching_style);
+        .unwrap()
+}
 
-    // Step 4c (`font-weight`).
-    //
+fn find_matching_weight(
+    candidates: &[Properties],
+    matching_set: &[usize],
+    query_weight: Weight,
+) -> Weight {
     // The spec doesn't say what to do if the weight is between 400 and 500 exclusive, so we
     // just use 450 as the cutoff.
-    let matching_weight = if matching_set
+    if matching_set
         .iter()
-        .any(|&index| candidates[index].weight == query.weight)
+        .any(|&index| candidates[index].weight == query_weight)
     {
-        query.weight
-    } else if query.weight >= Weight(400.0)
-        && query.weight < Weight(450.0)
+        query_weight
+    } else if query_weight >= Weight(400.0)
+        && query_weight < Weight(450.0)
         && matching_set
             .iter()
             .any(|&index| candidates[index].weight == Weight(500.0))
     {
         // Check 500 first.
         Weight(500.0)
-    } else if query.weight >= Weight(450.0)
-        && query.weight <= Weight(500.0)
+    } else if query_weight >= Weight(450.0)
+        && query_weight <= Weight(500.0)
         && matching_set
             .iter()
             .any(|&index| candidates[index].weight == Weight(400.0))
     {
         // Check 400 first.
         Weight(400.0)
-    } else if query.weight <= Weight(500.0) {
+    } else if query_weight <= Weight(500.0) {
         // Closest weight, first checking thinner values and then fatter ones.
         match matching_set
             .iter()
-            .filter(|&&index| candidates[index].weight <= query.weight)
-            .min_by_key(|&&index| FloatOrd(query.weight.0 - candidates[index].weight.0))
+            .filter(|&&index| candidates[index].weight <= query_weight)
+            .min_by_key(|&&index| FloatOrd(query_weight.0 - candidates[index].weight.0))
         {
             Some(&matching_index) => candidates[matching_index].weight,
             None => {
                 let matching_index = *matching_set
                     .iter()
-                    .min_by_key(|&&index| FloatOrd(candidates[index].weight.0 - query.weight.0))
+                    .min_by_key(|&&index| FloatOrd(candidates[index].weight.0 - query_weight.0))
                     .unwrap();
                 candidates[matching_index].weight
             }
@@ -131,26 +238,17 @@ pub fn find_best_match(
         // Closest weight, first checking fatter values and then thinner ones.
         match matching_set
             .iter()
-            .filter(|&&index| candidates[index].weight >= query.weight)
-            .min_by_key(|&&index| FloatOrd(candidates[index].weight.0 - query.weight.0))
+            .filter(|&&index| candidates[index].weight >= query_weight)
+            .min_by_key(|&&index| FloatOrd(candidates[index].weight.0 - query_weight.0))
         {
             Some(&matching_index) => candidates[matching_index].weight,
             None => {
                 let matching_index = *matching_set
                     .iter()
-                    .min_by_key(|&&index| FloatOrd(query.weight.0 - candidates[index].weight.0))
+                    .min_by_key(|&&index| FloatOrd(query_weight.0 - candidates[index].weight.0))
                     .unwrap();
                 candidates[matching_index].weight
             }
         }
-    };
-    matching_set.retain(|&index| candidates[index].weight == matching_weight);
-
-    // Step 4d concerns `font-size`, but fonts in `font-kit` are unsized, so we ignore that.
-
-    // Return the result.
-    matching_set
-        .into_iter()
-        .next()
-        .ok_or(SelectionError::NotFound)
+    }
 }
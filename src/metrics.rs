@@ -13,6 +13,10 @@
 //! For OpenType fonts, these mostly come from the `OS/2` table.
 
 use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+
+use crate::loader;
 
 /// Various metrics that apply to the entire font.
 ///
@@ -22,6 +26,8 @@ pub struct Metrics {
     /// The number of font units per em.
     ///
     /// Font sizes are usually expressed in pixels per em; e.g. `12px` means 12 pixels per em.
+    ///
+    /// This is never 0: corrupt fonts that report 0 are treated as having 1000 units per em.
     pub units_per_em: u32,
 
     /// The maximum amount the font rises above the baseline, in font units.
@@ -34,6 +40,19 @@ pub struct Metrics {
     /// beware, as the sign is reversed from what those APIs return.
     pub descent: f32,
 
+    /// The distance above the baseline that Windows clips text to, in font units.
+    ///
+    /// This corresponds to `usWinAscent` in the `OS/2` table. Fonts without that table use the
+    /// `hhea` ascender instead.
+    pub win_ascent: f32,
+
+    /// The distance below the baseline that Windows clips text to, in font units.
+    ///
+    /// Like `descent`, this is negative for a descent below the baseline, which is the opposite
+    /// sign of `usWinDescent` in the `OS/2` table. Fonts without that table use the `hhea`
+    /// descender instead.
+    pub win_descent: f32,
+
     /// Distance between baselines, in font units.
     pub line_gap: f32,
 
@@ -56,3 +75,368 @@ pub struct Metrics {
     /// This corresponds to the `xMin`/`xMax`/`yMin`/`yMax` values in the OpenType `head` table.
     pub bounding_box: RectF,
 }
+
+impl Metrics {
+    /// Returns the scale that converts font units to pixels when the font is drawn at
+    /// `point_size` pixels per em.
+    #[inline]
+    pub fn scale_for(&self, point_size: f32) -> FontUnitScale {
+        FontUnitScale::new(self.units_per_em, point_size)
+    }
+
+    /// Returns `ascent` in pixels at `point_size`.
+    #[inline]
+    pub fn ascent_px(&self, point_size: f32) -> f32 {
+        self.scale_for(point_size).to_px(self.ascent)
+    }
+
+    /// Returns `descent` in pixels at `point_size`. Like `descent`, this is typically negative.
+    #[inline]
+    pub fn descent_px(&self, point_size: f32) -> f32 {
+        self.scale_for(point_size).to_px(self.descent)
+    }
+
+    /// Returns `line_gap` in pixels at `point_size`.
+    #[inline]
+    pub fn line_gap_px(&self, point_size: f32) -> f32 {
+        self.scale_for(point_size).to_px(self.line_gap)
+    }
+
+    /// Returns `cap_height` in pixels at `point_size`.
+    #[inline]
+    pub fn cap_height_px(&self, point_size: f32) -> f32 {
+        self.scale_for(point_size).to_px(self.cap_height)
+    }
+
+    /// Returns `x_height` in pixels at `point_size`.
+    #[inline]
+    pub fn x_height_px(&self, point_size: f32) -> f32 {
+        self.scale_for(point_size).to_px(self.x_height)
+    }
+
+    /// Returns these metrics rescaled to a font with `units_per_em` units per em.
+    ///
+    /// Use this to compare fonts with different em sizes, such as a 1000-unit CFF font and a
+    /// 2048-unit TrueType font, in the same units. A `units_per_em` of 0 is treated as 1000.
+    pub fn normalized(&self, units_per_em: u32) -> Metrics {
+        let units_per_em = loader::sanitize_units_per_em(units_per_em);
+        let scale = units_per_em as f32 / loader::sanitize_units_per_em(self.units_per_em) as f32;
+        Metrics {
+            units_per_em,
+            ascent: self.ascent * scale,
+            descent: self.descent * scale,
+            win_ascent: self.win_ascent * scale,
+            win_descent: self.win_descent * scale,
+            line_gap: self.line_gap * scale,
+            underline_position: self.underline_position * scale,
+            underline_thickness: self.underline_thickness * scale,
+            cap_height: self.cap_height * scale,
+            x_height: self.x_height * scale,
+            bounding_box: self.bounding_box * scale,
+        }
+    }
+
+    /// Returns true if text in a font with `other` metrics can be set on the same lines as text in
+    /// a font with these metrics without the baseline or line height visibly changing.
+    ///
+    /// The ascents, descents, and line gaps are compared relative to each font's em, so fonts with
+    /// different units per em can be compatible. `tolerance` is the largest difference allowed in
+    /// each of them, as a fraction of the em; e.g. 0.05 allows 1 pixel of difference at 20 pixels
+    /// per em. Fallback engines can use this to prefer substitutes that don't make lines jump.
+    pub fn is_compatible_with(&self, other: &Metrics, tolerance: f32) -> bool {
+        let (scale, other_scale) = (self.scale_for(1.0), other.scale_for(1.0));
+        [
+            (self.ascent, other.ascent),
+            (self.descent, other.descent),
+            (self.line_gap, other.line_gap),
+        ]
+        .iter()
+        .all(|&(value, other_value)| {
+            (scale.to_px(value) - other_scale.to_px(other_value)).abs() <= tolerance
+        })
+    }
+}
+
+/// Records which heights returned by `Loader::metrics_with_fallbacks()` were measured from glyph
+/// outlines because the font didn't declare them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MetricsSource {
+    /// True if `cap_height` is the top of the ink of 'H', or of 'O' if the font has no 'H'.
+    pub cap_height_measured: bool,
+    /// True if `x_height` is the top of the ink of 'x', or of 'o' if the font has no 'x'.
+    pub x_height_measured: bool,
+}
+
+/// Converts lengths, points, and rectangles between font units and pixels at one point size.
+///
+/// Obtain one with `Metrics::scale_for()`. Only the scale is applied: the y axis still points up,
+/// as it does in font units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontUnitScale {
+    pixels_per_unit: f32,
+}
+
+impl FontUnitScale {
+    /// Creates the scale for a font with `units_per_em` font units per em drawn at `point_size`
+    /// pixels per em.
+    ///
+    /// A `units_per_em` of 0 is treated as 1000, as `Metrics::units_per_em` is.
+    #[inline]
+    pub fn new(units_per_em: u32, point_size: f32) -> FontUnitScale {
+        let units_per_em = loader::sanitize_units_per_em(units_per_em);
+        FontUnitScale {
+            pixels_per_unit: point_size / units_per_em as f32,
+        }
+    }
+
+    /// Returns the number of pixels in one font unit.
+    #[inline]
+    pub fn pixels_per_unit(&self) -> f32 {
+        self.pixels_per_unit
+    }
+
+    /// Converts a length in font units to pixels.
+    #[inline]
+    pub fn to_px(&self, value: f32) -> f32 {
+        value * self.pixels_per_unit
+    }
+
+    /// Converts a point or vector in font units to pixels.
+    #[inline]
+    pub fn to_px_point(&self, point: Vector2F) -> Vector2F {
+        point * self.pixels_per_unit
+    }
+
+    /// Converts a rectangle in font units to pixels.
+    #[inline]
+    pub fn to_px_rect(&self, rect: RectF) -> RectF {
+        rect * self.pixels_per_unit
+    }
+
+    /// Converts a length in pixels to font units.
+    ///
+    /// The result is infinite or NaN if the point size is 0.
+    #[inline]
+    pub fn to_units(&self, value: f32) -> f32 {
+        value / self.pixels_per_unit
+    }
+
+    /// Converts a point or vector in pixels to font units.
+    #[inline]
+    pub fn to_units_point(&self, point: Vector2F) -> Vector2F {
+        point / self.pixels_per_unit
+    }
+
+    /// Converts a rectangle in pixels to font units.
+    #[inline]
+    pub fn to_units_rect(&self, rect: RectF) -> RectF {
+        RectF::from_points(
+            self.to_units_point(rect.origin()),
+            self.to_units_point(rect.lower_right()),
+        )
+    }
+}
+
+/// The sizes and positions that the font recommends for synthesized subscripts and superscripts.
+///
+/// All values are in font units and come from the `OS/2` table. Offsets follow the crate's y-up
+/// convention: a subscript below the baseline has a negative `subscript_y_offset`, which is the
+/// opposite sign of `ySubscriptYOffset` in the OpenType specification.
+///
+/// To draw a superscript, scale the glyphs by the recommended size relative to the em and then
+/// move them by the recommended offset:
+///
+/// ```
+/// use font_kit::font::Font;
+/// use pathfinder_geometry::transform2d::Transform2F;
+/// use pathfinder_geometry::vector::Vector2F;
+///
+/// let font = Font::from_path("resources/tests/eb-garamond/EBGaramond12-Regular.otf", 0).unwrap();
+/// let units_per_em = font.metrics().units_per_em as f32;
+/// let script_metrics = font.script_metrics().unwrap();
+///
+/// // Maps the outline of a glyph, in font units, to the outline of its superscript form.
+/// let superscript = Transform2F::from_translation(Vector2F::new(
+///     script_metrics.superscript_x_offset,
+///     script_metrics.superscript_y_offset,
+/// )) * Transform2F::from_scale(Vector2F::new(
+///     script_metrics.superscript_x_size / units_per_em,
+///     script_metrics.superscript_y_size / units_per_em,
+/// ));
+/// let top = superscript * Vector2F::new(0.0, units_per_em);
+/// assert!(top.y() > units_per_em);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScriptMetrics {
+    /// The recommended horizontal size of subscripts, in font units. This is the width of the
+    /// subscript em.
+    pub subscript_x_size: f32,
+
+    /// The recommended vertical size of subscripts, in font units. This is the height of the
+    /// subscript em.
+    pub subscript_y_size: f32,
+
+    /// The recommended horizontal offset of subscripts from the position of the base glyph, in
+    /// font units.
+    pub subscript_x_offset: f32,
+
+    /// The recommended vertical offset of the subscript baseline from the baseline, in font units.
+    /// This is negative for subscripts below the baseline.
+    pub subscript_y_offset: f32,
+
+    /// The recommended horizontal size of superscripts, in font units. This is the width of the
+    /// superscript em.
+    pub superscript_x_size: f32,
+
+    /// The recommended vertical size of superscripts, in font units. This is the height of the
+    /// superscript em.
+    pub superscript_y_size: f32,
+
+    /// The recommended horizontal offset of superscripts from the position of the base glyph, in
+    /// font units.
+    pub superscript_x_offset: f32,
+
+    /// The recommended vertical offset of the superscript baseline from the baseline, in font
+    /// units. This is positive for superscripts above the baseline.
+    pub superscript_y_offset: f32,
+}
+
+/// The slope and offset of the text cursor, as specified by the font.
+///
+/// For OpenType fonts, these come from the `hhea` table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CaretMetrics {
+    /// The rise component of the caret slope. Upright fonts have a rise of 1.
+    pub slope_rise: f32,
+
+    /// The run component of the caret slope. Upright fonts have a run of 0.
+    pub slope_run: f32,
+
+    /// The amount by which a slanted highlight should be shifted to produce the best appearance, in
+    /// font units. This is 0 for upright fonts.
+    pub offset: f32,
+}
+
+impl Default for CaretMetrics {
+    #[inline]
+    fn default() -> CaretMetrics {
+        CaretMetrics {
+            slope_rise: 1.0,
+            slope_run: 0.0,
+            offset: 0.0,
+        }
+    }
+}
+
+impl CaretMetrics {
+    /// Returns the transform that maps a vertical caret to the slanted caret for this font.
+    ///
+    /// The result shears the x axis by the caret slope and then shifts it by `offset`, which is in
+    /// font units; scale it to the point size before drawing. Upright fonts produce the identity
+    /// transform.
+    pub fn caret_transform(&self) -> Transform2F {
+        let shear = if self.slope_rise == 0.0 {
+            0.0
+        } else {
+            self.slope_run / self.slope_rise
+        };
+        Transform2F::row_major(1.0, shear, 0.0, 1.0, self.offset, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FontUnitScale, Metrics};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::Vector2F;
+
+    #[test]
+    fn convert_font_units_to_pixels() {
+        let scale = FontUnitScale::new(2048, 16.0);
+        assert_eq!(scale.pixels_per_unit(), 16.0 / 2048.0);
+        assert_eq!(scale.to_px(1024.0), 8.0);
+        assert_eq!(scale.to_units(8.0), 1024.0);
+        assert_eq!(
+            scale.to_px_point(Vector2F::new(512.0, -256.0)),
+            Vector2F::new(4.0, -2.0)
+        );
+
+        // Round trips are exact for power-of-two scales and close for others.
+        let rect = RectF::new(Vector2F::new(-100.0, 250.0), Vector2F::new(600.0, 700.0));
+        assert_eq!(scale.to_units_rect(scale.to_px_rect(rect)), rect);
+        let scale = FontUnitScale::new(1000, 12.0);
+        for value in [-300.0, 0.0, 1.0, 733.0, 1000.0] {
+            assert!((scale.to_units(scale.to_px(value)) - value).abs() < 0.001);
+        }
+        let round_trip = scale.to_units_rect(scale.to_px_rect(rect));
+        assert!((round_trip.origin() - rect.origin()).length() < 0.001);
+        assert!((round_trip.lower_right() - rect.lower_right()).length() < 0.001);
+
+        // Fonts that report 0 units per em are treated as having 1000.
+        assert_eq!(FontUnitScale::new(0, 12.0), scale);
+    }
+
+    fn synthesize_metrics(units_per_em: u32, ascent: f32, descent: f32, line_gap: f32) -> Metrics {
+        Metrics {
+            units_per_em,
+            ascent,
+            descent,
+            win_ascent: ascent,
+            win_descent: descent,
+            line_gap,
+            underline_position: 0.0,
+            underline_thickness: 0.0,
+            cap_height: 0.0,
+            x_height: 0.0,
+            bounding_box: RectF::default(),
+        }
+    }
+
+    #[test]
+    fn normalize_metrics() {
+        let mut metrics = synthesize_metrics(2048, 1638.4, -409.6, 184.32);
+        metrics.cap_height = 1434.0;
+        metrics.bounding_box =
+            RectF::new(Vector2F::new(-100.0, -512.0), Vector2F::new(2200.0, 2560.0));
+        let normalized = metrics.normalized(1000);
+        assert_eq!(normalized.units_per_em, 1000);
+        assert!((normalized.ascent - 800.0).abs() < 0.001);
+        assert!((normalized.descent + 200.0).abs() < 0.001);
+        assert!((normalized.cap_height - 700.195).abs() < 0.001);
+        assert!(metrics.is_compatible_with(&normalized, 0.0001));
+
+        let round_trip = normalized.normalized(2048);
+        assert_eq!(round_trip.units_per_em, 2048);
+        for (value, expected) in [
+            (round_trip.ascent, metrics.ascent),
+            (round_trip.line_gap, metrics.line_gap),
+            (round_trip.cap_height, metrics.cap_height),
+            (
+                round_trip.bounding_box.max_x(),
+                metrics.bounding_box.max_x(),
+            ),
+        ] {
+            assert!((value - expected).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn compare_metrics() {
+        let metrics = synthesize_metrics(1000, 800.0, -200.0, 90.0);
+        assert!(metrics.is_compatible_with(&metrics, 0.0));
+
+        // The same proportions at a different em size are compatible.
+        let scaled = synthesize_metrics(2048, 1638.4, -409.6, 184.32);
+        assert!(metrics.is_compatible_with(&scaled, 0.001));
+        assert!(scaled.is_compatible_with(&metrics, 0.001));
+
+        // Each of the ascent, descent, and line gap must be within the tolerance.
+        let taller = synthesize_metrics(1000, 830.0, -200.0, 90.0);
+        assert!(metrics.is_compatible_with(&taller, 0.05));
+        assert!(!metrics.is_compatible_with(&taller, 0.02));
+        let deeper = synthesize_metrics(1000, 800.0, -260.0, 90.0);
+        assert!(!metrics.is_compatible_with(&deeper, 0.05));
+        let spaced = synthesize_metrics(1000, 800.0, -200.0, 0.0);
+        assert!(!metrics.is_compatible_with(&spaced, 0.05));
+    }
+}
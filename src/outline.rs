@@ -11,6 +11,8 @@
 //! Bézier paths.
 
 use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2F;
 use std::mem;
 
@@ -64,6 +66,57 @@ bitflags! {
     }
 }
 
+/// A reference from a TrueType composite glyph to one of the glyphs it is built from.
+///
+/// See `Loader::composite_components()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Component {
+    /// The ID of the referenced glyph.
+    pub glyph_id: u32,
+    /// The transform from the component's coordinate space to the composite glyph's, in font
+    /// units.
+    ///
+    /// For components positioned by `anchor_points`, the translation is zero, since it depends on
+    /// the outlines of both glyphs.
+    pub transform: Transform2F,
+    /// If the component is positioned by matching points instead of by an offset, the index of the
+    /// point in the composite glyph (counting the points of the previous components) and the index
+    /// of the point in this component that should coincide.
+    pub anchor_points: Option<(u16, u16)>,
+    /// Whether the composite glyph takes its advance width and side bearings from this component.
+    pub use_my_metrics: bool,
+    /// Whether the offset should be rounded to the pixel grid when hinting.
+    pub round_to_grid: bool,
+}
+
+/// The contours of a simple TrueType glyph along with the number of each point, as returned by
+/// `Loader::glyf_contours_with_point_indices()`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct IndexedOutline {
+    /// The contours, as `Loader::glyf_contours()` returns them.
+    pub outline: Outline,
+    /// For each contour, the index of each of its points among all the points of the glyph,
+    /// which is how TrueType instructions refer to points.
+    ///
+    /// On-curve points inserted by `ImpliedPoints::Explicit` aren't stored in the font, so they
+    /// have no index.
+    pub point_indices: Vec<Vec<Option<u16>>>,
+}
+
+/// Whether `Loader::glyf_contours()` inserts the on-curve points that TrueType leaves implied.
+///
+/// A quadratic TrueType contour may store two off-curve points in a row, in which case the
+/// on-curve point midway between them is implied rather than stored.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ImpliedPoints {
+    /// Return the points exactly as stored in the font.
+    #[default]
+    AsStored,
+    /// Insert the implied on-curve points, so that every off-curve point lies between two
+    /// on-curve points.
+    Explicit,
+}
+
 /// Accumulates Bézier path rendering commands into an `Outline` structure.
 #[derive(Clone, Debug)]
 pub struct OutlineBuilder {
@@ -178,7 +231,7 @@ impl OutlineBuilder {
     pub fn take_outline(&mut self) -> Outline {
         assert!(self.current_contour.positions.is_empty());
         self.current_contour = Contour::new();
-        mem::replace(&mut self.outline, Outline::new())
+        mem::take(&mut self.outline)
     }
 }
 
@@ -212,6 +265,232 @@ impl OutlineSink for OutlineBuilder {
     fn close(&mut self) {
         self.outline
             .contours
-            .push(mem::replace(&mut self.current_contour, Contour::new()));
+            .push(mem::take(&mut self.current_contour));
+    }
+}
+
+/// Scales the Bézier path sent to it uniformly about the origin and passes it on to another sink.
+#[derive(Debug)]
+pub(crate) struct ScaledOutlineSink<'a, S> {
+    sink: &'a mut S,
+    scale: f32,
+}
+
+impl<'a, S> ScaledOutlineSink<'a, S> {
+    #[inline]
+    pub(crate) fn new(sink: &'a mut S, scale: f32) -> ScaledOutlineSink<'a, S> {
+        ScaledOutlineSink { sink, scale }
+    }
+}
+
+impl<S> OutlineSink for ScaledOutlineSink<'_, S>
+where
+    S: OutlineSink,
+{
+    #[inline]
+    fn move_to(&mut self, to: Vector2F) {
+        self.sink.move_to(to * self.scale);
+    }
+
+    #[inline]
+    fn line_to(&mut self, to: Vector2F) {
+        self.sink.line_to(to * self.scale);
+    }
+
+    #[inline]
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.sink
+            .quadratic_curve_to(ctrl * self.scale, to * self.scale);
+    }
+
+    #[inline]
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        self.sink.cubic_curve_to(ctrl * self.scale, to * self.scale);
+    }
+
+    #[inline]
+    fn close(&mut self) {
+        self.sink.close();
+    }
+}
+
+/// Applies an affine transform to the Bézier path sent to it and passes it on to another sink.
+#[derive(Debug)]
+pub(crate) struct TransformedOutlineSink<'a, S> {
+    sink: &'a mut S,
+    transform: Transform2F,
+}
+
+impl<'a, S> TransformedOutlineSink<'a, S> {
+    #[inline]
+    pub(crate) fn new(sink: &'a mut S, transform: Transform2F) -> TransformedOutlineSink<'a, S> {
+        TransformedOutlineSink { sink, transform }
+    }
+}
+
+impl<S> OutlineSink for TransformedOutlineSink<'_, S>
+where
+    S: OutlineSink,
+{
+    #[inline]
+    fn move_to(&mut self, to: Vector2F) {
+        self.sink.move_to(self.transform * to);
+    }
+
+    #[inline]
+    fn line_to(&mut self, to: Vector2F) {
+        self.sink.line_to(self.transform * to);
+    }
+
+    #[inline]
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.sink
+            .quadratic_curve_to(self.transform * ctrl, self.transform * to);
+    }
+
+    #[inline]
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        self.sink
+            .cubic_curve_to(self.transform * ctrl, self.transform * to);
+    }
+
+    #[inline]
+    fn close(&mut self) {
+        self.sink.close();
+    }
+}
+
+/// Records whether any path commands were sent to it.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct EmptinessSink {
+    pub(crate) received_commands: bool,
+}
+
+impl OutlineSink for EmptinessSink {
+    #[inline]
+    fn move_to(&mut self, _: Vector2F) {
+        self.received_commands = true;
+    }
+
+    #[inline]
+    fn line_to(&mut self, _: Vector2F) {
+        self.received_commands = true;
+    }
+
+    #[inline]
+    fn quadratic_curve_to(&mut self, _: Vector2F, _: Vector2F) {
+        self.received_commands = true;
+    }
+
+    #[inline]
+    fn cubic_curve_to(&mut self, _: LineSegment2F, _: Vector2F) {
+        self.received_commands = true;
+    }
+
+    #[inline]
+    fn close(&mut self) {
+        self.received_commands = true;
+    }
+}
+
+/// Computes the exact bounding box of the Bézier path sent to it.
+///
+/// Unlike the bounds of the control polygon, curve extrema are evaluated precisely, so the result
+/// is the tight ink box of the path.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct OutlineBoundsSink {
+    bounds: Option<RectF>,
+    current: Vector2F,
+}
+
+impl OutlineBoundsSink {
+    #[inline]
+    pub(crate) fn new() -> OutlineBoundsSink {
+        OutlineBoundsSink::default()
+    }
+
+    /// Returns the accumulated bounds, or an empty rectangle if no points were received.
+    #[inline]
+    pub(crate) fn into_bounds(self) -> RectF {
+        self.bounds.unwrap_or_default()
+    }
+
+    fn add_point(&mut self, point: Vector2F) {
+        self.bounds = Some(match self.bounds {
+            None => RectF::new(point, Vector2F::zero()),
+            Some(bounds) => bounds.union_point(point),
+        });
+    }
+}
+
+impl OutlineSink for OutlineBoundsSink {
+    fn move_to(&mut self, to: Vector2F) {
+        self.add_point(to);
+        self.current = to;
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.add_point(to);
+        self.current = to;
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        let from = self.current;
+        let denom = from - ctrl * 2.0 + to;
+        for (d, n) in [
+            (denom.x(), from.x() - ctrl.x()),
+            (denom.y(), from.y() - ctrl.y()),
+        ] {
+            if d != 0.0 {
+                let t = n / d;
+                if t > 0.0 && t < 1.0 {
+                    let u = 1.0 - t;
+                    self.add_point(from * (u * u) + ctrl * (2.0 * u * t) + to * (t * t));
+                }
+            }
+        }
+        self.add_point(to);
+        self.current = to;
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        let (from, ctrl0, ctrl1) = (self.current, ctrl.from(), ctrl.to());
+        // The derivative is a quadratic `a*t^2 + b*t + c` in each axis.
+        let (d0, d1, d2) = (ctrl0 - from, ctrl1 - ctrl0, to - ctrl1);
+        let a = d0 - d1 * 2.0 + d2;
+        let b = (d1 - d0) * 2.0;
+        for (a, b, c) in [(a.x(), b.x(), d0.x()), (a.y(), b.y(), d0.y())] {
+            for t in quadratic_roots(a, b, c).iter().flatten() {
+                if *t > 0.0 && *t < 1.0 {
+                    let u = 1.0 - t;
+                    self.add_point(
+                        from * (u * u * u)
+                            + ctrl0 * (3.0 * u * u * t)
+                            + ctrl1 * (3.0 * u * t * t)
+                            + to * (t * t * t),
+                    );
+                }
+            }
+        }
+        self.add_point(to);
+        self.current = to;
+    }
+
+    #[inline]
+    fn close(&mut self) {}
+}
+
+fn quadratic_roots(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
+    if a.abs() < f32::EPSILON {
+        if b == 0.0 {
+            return [None, None];
+        }
+        return [Some(-c / b), None];
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return [None, None];
     }
+    let sqrt = discriminant.sqrt();
+    [Some((-b + sqrt) / (2.0 * a)), Some((-b - sqrt) / (2.0 * a))]
 }
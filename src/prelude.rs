@@ -0,0 +1,28 @@
+// font-kit/src/prelude.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The types needed to find, load, and render fonts, for glob importing:
+//!
+//!     use font_kit::prelude::*;
+
+pub use crate::canvas::{Canvas, Format, RasterizationOptions};
+pub use crate::error::{FontLoadingError, GlyphLoadingError, SelectionError};
+pub use crate::family_name::FamilyName;
+pub use crate::font::Font;
+pub use crate::geom::{LineSegment2F, RectF, RectI, Transform2F, Vector2F, Vector2I};
+pub use crate::handle::Handle;
+pub use crate::hinting::HintingOptions;
+pub use crate::loader::Loader;
+pub use crate::metrics::Metrics;
+pub use crate::outline::{Outline, OutlineSink};
+pub use crate::properties::{Properties, Stretch, Style, Weight};
+
+#[cfg(feature = "source")]
+pub use crate::source::{Source, SystemSource};
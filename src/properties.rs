@@ -61,8 +61,65 @@ impl Properties {
     }
 }
 
+/// A font selection query in which each property is optional.
+///
+/// Properties that are `None` are not considered during matching, so any value is acceptable; e.g.
+/// the following matches the boldest-appropriate face regardless of its style or stretch:
+///
+///     # use font_kit::properties::{PropertiesQuery, Weight};
+///     println!("{:?}", PropertiesQuery::new().weight(Weight::BOLD));
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PropertiesQuery {
+    /// The requested font style, or `None` to accept any style.
+    pub style: Option<Style>,
+    /// The requested font weight, or `None` to accept any weight.
+    pub weight: Option<Weight>,
+    /// The requested font stretchiness, or `None` to accept any stretchiness.
+    pub stretch: Option<Stretch>,
+}
+
+impl PropertiesQuery {
+    /// Initializes a query that matches any font.
+    #[inline]
+    pub fn new() -> PropertiesQuery {
+        PropertiesQuery::default()
+    }
+
+    /// Requires the given style and returns this query for method chaining.
+    #[inline]
+    pub fn style(&mut self, style: Style) -> &mut PropertiesQuery {
+        self.style = Some(style);
+        self
+    }
+
+    /// Requires the given weight and returns this query for method chaining.
+    #[inline]
+    pub fn weight(&mut self, weight: Weight) -> &mut PropertiesQuery {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Requires the given stretchiness and returns this query for method chaining.
+    #[inline]
+    pub fn stretch(&mut self, stretch: Stretch) -> &mut PropertiesQuery {
+        self.stretch = Some(stretch);
+        self
+    }
+}
+
+impl From<Properties> for PropertiesQuery {
+    #[inline]
+    fn from(properties: Properties) -> PropertiesQuery {
+        PropertiesQuery {
+            style: Some(properties.style),
+            weight: Some(properties.weight),
+            stretch: Some(properties.stretch),
+        }
+    }
+}
+
 /// Allows italic or oblique faces to be selected.
-#[derive(Clone, Copy, PartialEq, Debug, Hash, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 pub enum Style {
     /// A face that is neither italic not obliqued.
     #[default]
@@ -157,4 +214,81 @@ impl Stretch {
         Stretch::EXTRA_EXPANDED.0,
         Stretch::ULTRA_EXPANDED.0,
     ];
+
+    /// Returns the width for an OpenType `usWidthClass` value, from 1 (ultra-condensed) to 9
+    /// (ultra-expanded), or `None` if the value is out of that range.
+    #[inline]
+    pub fn from_width_class(width_class: u16) -> Option<Stretch> {
+        let index = usize::from(width_class).checked_sub(1)?;
+        Stretch::MAPPING.get(index).map(|&stretch| Stretch(stretch))
+    }
+
+    /// Returns the OpenType `usWidthClass` value, from 1 to 9, whose width is nearest to this one.
+    ///
+    /// A width halfway between two classes goes to the narrower one. Widths out of range map to
+    /// the nearest end, and NaN maps to the normal class, 5.
+    pub fn to_width_class(&self) -> u16 {
+        if self.0.is_nan() {
+            return 5;
+        }
+        let mut nearest = 0;
+        for (index, &stretch) in Stretch::MAPPING.iter().enumerate() {
+            if (stretch - self.0).abs() < (Stretch::MAPPING[nearest] - self.0).abs() {
+                nearest = index;
+            }
+        }
+        nearest as u16 + 1
+    }
+
+    /// Returns the width for a CSS `font-stretch` percentage or OpenType `wdth` axis value, such
+    /// as 100 for the normal width.
+    ///
+    /// The value isn't clamped, since variation axes may extend beyond the CSS range.
+    #[inline]
+    pub fn from_percentage(percentage: f32) -> Stretch {
+        Stretch(percentage / 100.0)
+    }
+
+    /// Returns this width as a CSS `font-stretch` percentage, such as 100 for the normal width.
+    #[inline]
+    pub fn to_percentage(&self) -> f32 {
+        self.0 * 100.0
+    }
+}
+
+/// A named value on one or more of a font's design axes, from the OpenType `STAT` table.
+///
+/// These are the building blocks of style names such as "Bold Condensed": a font picker can name
+/// an instance by joining the names of the values that match its position on each axis, leaving
+/// out the elidable ones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatAxisValue {
+    /// The axes that this value applies to, as pairs of an axis tag (for example, `wght`) and a
+    /// position on that axis in user units.
+    ///
+    /// Most values apply to a single axis. Values that name a combination of positions on several
+    /// axes list all of them.
+    pub coordinates: Vec<(u32, f32)>,
+
+    /// The minimum and maximum of the range of positions that this value covers, if the font
+    /// specifies one. Only meaningful for single-axis values.
+    pub range: Option<(f32, f32)>,
+
+    /// The position of the corresponding style-linked value, such as the bold counterpart of a
+    /// regular weight, if the font specifies one.
+    pub linked_value: Option<f32>,
+
+    /// The ID of this value's name in the `name` table.
+    pub name_id: u16,
+
+    /// This value's name, preferring US English, or `None` if the `name` table has no usable entry.
+    pub name: Option<String>,
+
+    /// Whether this value's name should be left out of style names, as is usual for "Regular" or
+    /// "Normal".
+    pub elidable: bool,
+
+    /// Whether this value describes a font other than the one that contains it. Such values are
+    /// listed for style linking only.
+    pub older_sibling_font_attribute: bool,
 }
@@ -16,16 +16,27 @@ use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
 use crate::font::Font;
 use crate::handle::Handle;
-use crate::matching;
-use crate::properties::Properties;
+use crate::loader::{self, Loader};
+use crate::matching::{self, Candidate};
+use crate::properties::{Properties, PropertiesQuery, Stretch, Weight};
+use crate::tables;
 use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::slice;
 
 #[cfg(all(
     any(target_os = "macos", target_os = "ios"),
+    feature = "coretext",
     not(feature = "loader-freetype-default")
 ))]
 pub use crate::sources::core_text::CoreTextSource as SystemSource;
-#[cfg(all(target_family = "windows", not(feature = "source-fontconfig-default")))]
+#[cfg(all(
+    target_family = "windows",
+    feature = "directwrite",
+    not(feature = "source-fontconfig-default")
+))]
 pub use crate::sources::directwrite::DirectWriteSource as SystemSource;
 #[cfg(all(
     any(
@@ -41,6 +52,14 @@ pub use crate::sources::directwrite::DirectWriteSource as SystemSource;
     not(target_env = "ohos")
 ))]
 pub use crate::sources::fontconfig::FontconfigSource as SystemSource;
+#[cfg(all(
+    any(
+        all(any(target_os = "macos", target_os = "ios"), not(feature = "coretext")),
+        all(target_family = "windows", not(feature = "directwrite")),
+    ),
+    not(feature = "source-fontconfig-default")
+))]
+pub use crate::sources::fs::FsSource as SystemSource;
 #[cfg(all(target_os = "android", not(feature = "source-fontconfig-default")))]
 pub use crate::sources::fs::FsSource as SystemSource;
 
@@ -86,6 +105,47 @@ const DEFAULT_FONT_FAMILY_CURSIVE: &str = "cursive";
 #[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
 const DEFAULT_FONT_FAMILY_FANTASY: &str = "fantasy";
 
+/// The kinds of user interface font that a platform may configure separately.
+///
+/// Platforms that don't distinguish a kind fall back to the plain system UI font for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UiFontKind {
+    /// The font used for most user interface text, such as menus and labels.
+    System,
+    /// The fixed-pitch font the user has chosen for code and terminals.
+    Monospace,
+    /// The font used for small captions, such as palette and tool window titles.
+    SmallCaption,
+}
+
+/// How closely a font found by `Source::find_matching()` matches the font it was asked about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MatchQuality {
+    /// The fonts have the same PostScript name and identical `head` table fingerprints, so they are
+    /// the same build of the same font.
+    Exact,
+    /// The fonts have the same PostScript name, but they are different versions or their versions
+    /// couldn't be compared.
+    PostScriptName,
+    /// The font is the face in the same family that best matches the other font's properties.
+    Family,
+}
+
+/// A font chosen by `Source::select_best_match_with_variations()` or
+/// `Source::select_by_postscript_name_with_variations()`, along with the variation axis values that
+/// produce the requested instance of it.
+#[derive(Clone, Debug)]
+pub struct Selection {
+    /// The handle of the selected font.
+    pub handle: Handle,
+    /// The user-space value of each variation axis, as `(tag, value)` pairs, that makes the font
+    /// match the requested weight and stretch, or the requested named instance.
+    ///
+    /// When matching properties, only the `wght` and `wdth` axes are set. This is empty for static
+    /// fonts. Pass these to `Font::with_variations()` to instance the loaded font.
+    pub variations: Vec<(u32, f32)>,
+}
+
 /// A database of installed fonts that can be queried.
 ///
 /// This trait is object-safe.
@@ -101,8 +161,8 @@ pub trait Source: Any {
 
     /// Selects a font by PostScript name, which should be a unique identifier.
     ///
-    /// The default implementation, which is used by the DirectWrite and the filesystem backends,
-    /// does a brute-force search of installed fonts to find the one that matches.
+    /// The default implementation does a brute-force search of the fonts in the source to find the
+    /// one that matches. The built-in sources override it with indexed or native lookups.
     fn select_by_postscript_name(&self, postscript_name: &str) -> Result<Handle, SelectionError> {
         // TODO(pcwalton): Optimize this by searching for families with similar names first.
         for family_name in self.all_families()? {
@@ -121,6 +181,24 @@ pub trait Source: Any {
         Err(SelectionError::NotFound)
     }
 
+    /// Selects a font by PostScript name like `select_by_postscript_name()`, also returning the
+    /// variation axis values of the named instance of a variable font that the name refers to.
+    ///
+    /// Handles only select members of collections, so a named instance such as "Inter-Bold" is
+    /// returned as the handle of its variable font, along with the instance's axis values. The
+    /// default implementation returns no variations; sources that list named instances as fonts of
+    /// their own override it.
+    fn select_by_postscript_name_with_variations(
+        &self,
+        postscript_name: &str,
+    ) -> Result<Selection, SelectionError> {
+        self.select_by_postscript_name(postscript_name)
+            .map(|handle| Selection {
+                handle,
+                variations: vec![],
+            })
+    }
+
     // FIXME(pcwalton): This only returns one family instead of multiple families for the generic
     // family names.
     #[doc(hidden)]
@@ -138,6 +216,43 @@ pub trait Source: Any {
         }
     }
 
+    /// Looks up the family for a family name, choosing the family appropriate to `language` if
+    /// the name is generic.
+    ///
+    /// The `language` argument is a BCP 47 language tag such as `"ja"` or `"zh-Hans"`. It matters
+    /// most for Han characters, which are drawn differently in Chinese, Japanese, and Korean text,
+    /// and which platforms often supply from separate per-language families (for example, "PingFang
+    /// SC" and "PingFang TC").
+    ///
+    /// The default implementation, which is used by the Core Text and DirectWrite backends, asks
+    /// the generic family's font for its fallback for a character typical of the language.
+    /// Languages whose text the generic family already covers get the generic family itself.
+    fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        let family_handle = self.select_family_by_generic_name(family_name)?;
+        if let FamilyName::Title(_) = *family_name {
+            return Ok(family_handle);
+        }
+        let sample_char = match sample_char_for_language(language) {
+            Some(sample_char) => sample_char,
+            None => return Ok(family_handle),
+        };
+        let font = match family_handle.fonts().first().map(Font::from_handle) {
+            Some(Ok(font)) => font,
+            _ => return Ok(family_handle),
+        };
+        let fallback_result = Loader::get_fallbacks(&font, &sample_char.to_string(), language);
+        match fallback_result.fonts.first() {
+            Some(fallback_font) => self
+                .select_family_by_name(&fallback_font.font.family_name())
+                .or(Ok(family_handle)),
+            None => Ok(family_handle),
+        }
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -146,11 +261,324 @@ pub trait Source: Any {
         family_names: &[FamilyName],
         properties: &Properties,
     ) -> Result<Handle, SelectionError> {
+        self.select_best_match_with_variations(family_names, properties)
+            .map(|selection| selection.handle)
+    }
+
+    /// Resolves a CSS `font-family` list: returns the best match for `properties` in the first
+    /// family in `family_names` that is available, trying concrete and generic names alike in
+    /// order.
+    ///
+    /// Unlike `select_best_match()`, an error while matching in one family, such as a family whose
+    /// fonts can't be read, moves on to the next family instead of ending the search. Returns
+    /// `SelectionError::NotFound` if no family in the list yields a font.
+    fn select_family_from_list(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        family_names
+            .iter()
+            .find_map(|family_name| {
+                self.select_best_match(slice::from_ref(family_name), properties)
+                    .ok()
+            })
+            .ok_or(SelectionError::NotFound)
+    }
+
+    /// Performs font matching like `select_best_match()`, also returning the variation axis values
+    /// that instance the selected font closest to `properties`.
+    ///
+    /// A variable font matches any weight or stretch within the range of its `wght` or `wdth` axis
+    /// exactly, so one variable face can satisfy requests for many weights.
+    fn select_best_match_with_variations(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Selection, SelectionError> {
+        let query = PropertiesQuery::from(*properties);
         for family_name in family_names {
             if let Ok(family_handle) = self.select_family_by_generic_name(family_name) {
-                let candidates = self.select_descriptions_in_family(&family_handle)?;
-                if let Ok(index) = matching::find_best_match(&candidates, properties) {
-                    return Ok(family_handle.fonts[index].clone());
+                if let Some(selection) = select_in_family(self, &family_handle, &query)? {
+                    return Ok(selection);
+                }
+            }
+        }
+        Err(SelectionError::NotFound)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification, considering only
+    /// the properties specified in `query`, and returns the handle.
+    fn select_best_match_for_query(
+        &self,
+        family_names: &[FamilyName],
+        query: &PropertiesQuery,
+    ) -> Result<Handle, SelectionError> {
+        for family_name in family_names {
+            if let Ok(family_handle) = self.select_family_by_generic_name(family_name) {
+                if let Some(selection) = select_in_family(self, &family_handle, query)? {
+                    return Ok(selection.handle);
+                }
+            }
+        }
+        Err(SelectionError::NotFound)
+    }
+
+    /// Finds the font in this source that is the same as, or most similar to, `font`, along with
+    /// how closely it matches.
+    ///
+    /// This is useful for fonts loaded from memory, to prefer an installed copy that takes part in
+    /// system fallback and may be better hinted. Candidates are tried by PostScript name first,
+    /// and a candidate with the same name is reported as an exact match if its `head` table
+    /// fingerprint (revision, checksum, and dates) is identical too. Failing that, the best match
+    /// for the font's properties in its family is returned.
+    fn find_matching(&self, font: &Font) -> Option<(Handle, MatchQuality)> {
+        if let Some(postscript_name) = font.postscript_name() {
+            if let Ok(handle) = self.select_by_postscript_name(&postscript_name) {
+                let quality = match Font::from_handle(&handle) {
+                    Ok(candidate) if loader::same_head_fingerprint(font, &candidate) => {
+                        MatchQuality::Exact
+                    }
+                    _ => MatchQuality::PostScriptName,
+                };
+                return Some((handle, quality));
+            }
+        }
+
+        let family_handle = self.select_family_by_name(&font.family_name()).ok()?;
+        let candidates = self.select_descriptions_in_family(&family_handle).ok()?;
+        let index = matching::find_best_match(&candidates, &font.properties()).ok()?;
+        Some((family_handle.fonts[index].clone(), MatchQuality::Family))
+    }
+
+    /// Splits `text` into runs that can each be drawn with a single font, starting from `primary`
+    /// and falling back to other fonts only for the characters it lacks.
+    ///
+    /// Each run is a byte range of `text` and the handle of the font to draw it with. Runs covered
+    /// by `primary` use its handle. For the rest, a single fallback is chosen per run, preferring
+    /// one that covers the whole run, so that a word doesn't switch fonts midway. Spaces and
+    /// punctuation between characters that need a fallback stay in the fallback run. Characters
+    /// that no font covers are assigned to `primary`.
+    ///
+    /// Fallback candidates come from the platform's fallback list for `locale` (see
+    /// `Loader::get_fallbacks()`) and, failing that, from the fonts in this source, using the face
+    /// that best matches `properties` in each family. Coverage is checked against each font's
+    /// character map. Searching this source loads its fonts one by one until every character is
+    /// covered, so it can be slow for text that no installed font supports.
+    ///
+    /// This does not itemize by script or bidirectional level; that is left to the caller.
+    /// Returns an empty list if `primary` has no handle.
+    fn analyze_string_coverage(
+        &self,
+        text: &str,
+        primary: &Font,
+        properties: &Properties,
+        locale: &str,
+    ) -> Vec<(Range<usize>, Handle)> {
+        let primary_handle = match primary.handle() {
+            Some(handle) => handle,
+            None => return vec![],
+        };
+
+        // Runs refer to fallbacks by index, with `None` standing for the primary font.
+        let mut runs: Vec<(Range<usize>, Option<usize>)> = vec![];
+        let mut fallbacks: Vec<(Handle, Font)> = vec![];
+        let mut candidates = FallbackCandidates::new();
+        for (range, covered) in primary_coverage_segments(text, primary) {
+            if covered {
+                push_coverage_run(&mut runs, range, None);
+                continue;
+            }
+
+            let segment = &text[range.clone()];
+            add_fallbacks_for_segment(
+                self,
+                segment,
+                Some(primary),
+                properties,
+                locale,
+                &mut candidates,
+                &mut fallbacks,
+            );
+
+            let mut position = range.start;
+            while position < range.end {
+                // Prefer the font of the previous run on ties so as to switch fonts less.
+                let previous = runs.last().and_then(|&(_, fallback_index)| fallback_index);
+                let mut best: Option<(usize, usize)> = None;
+                for fallback_index in previous.into_iter().chain(0..fallbacks.len()) {
+                    let (_, ref font) = fallbacks[fallback_index];
+                    let length = covered_prefix_len(font, &text[position..range.end]);
+                    if length > best.map_or(0, |(_, best_length)| best_length) {
+                        best = Some((fallback_index, length));
+                    }
+                }
+                match best {
+                    Some((fallback_index, length)) => {
+                        push_coverage_run(
+                            &mut runs,
+                            position..(position + length),
+                            Some(fallback_index),
+                        );
+                        position += length;
+                    }
+                    None => {
+                        let length = cluster_len(&text[position..range.end]);
+                        push_coverage_run(&mut runs, position..(position + length), None);
+                        position += length;
+                    }
+                }
+            }
+        }
+
+        runs.into_iter()
+            .map(|(range, fallback_index)| {
+                let handle = match fallback_index {
+                    Some(fallback_index) => fallbacks[fallback_index].0.clone(),
+                    None => primary_handle.clone(),
+                };
+                (range, handle)
+            })
+            .collect()
+    }
+
+    /// Returns a font that can draw `character`, for use when the font the text asked for can't.
+    ///
+    /// The face of the generic sans-serif family that best matches `properties` is preferred if it
+    /// covers the character. Otherwise the candidates are the same as for
+    /// `analyze_string_coverage()`: the platform's fallback list for `locale`, then every font in
+    /// this source. Returns `SelectionError::NotFound` if no font covers the character.
+    ///
+    /// This can be slow when the character is rare; wrap the source in a
+    /// `sources::fallback_cache::FallbackCacheSource` to remember the results.
+    fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        let primary = self
+            .select_best_match_for_language(&[FamilyName::SansSerif], properties, locale)
+            .ok()
+            .and_then(|handle| Font::from_handle(&handle).ok().map(|font| (handle, font)));
+        if let Some((ref handle, ref font)) = primary {
+            if font.glyph_for_char(character).is_some() {
+                return Ok(handle.clone());
+            }
+        }
+
+        let mut fallbacks = vec![];
+        let segment = character.to_string();
+        let primary = primary.as_ref().map(|(_, font)| font);
+        add_fallbacks_for_segment(
+            self,
+            &segment,
+            primary,
+            properties,
+            locale,
+            &mut FallbackCandidates::new(),
+            &mut fallbacks,
+        );
+        match fallbacks.into_iter().next() {
+            Some((handle, _)) => Ok(handle),
+            None => Err(SelectionError::NotFound),
+        }
+    }
+
+    /// Returns one monospace font from each family that contains monospace fonts.
+    ///
+    /// The font returned for each family is the best match for the default properties among its
+    /// monospace faces. Families are de-duplicated by the name the fonts report, so a family
+    /// reachable under several names is returned once.
+    ///
+    /// This loads every installed font to check its metrics, so it can be slow on systems with
+    /// many fonts.
+    fn select_monospace_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        let mut seen_families = HashSet::new();
+        let mut handles = vec![];
+        for family_name in self.all_families()? {
+            let family_handle = match self.select_family_by_name(&family_name) {
+                Ok(family_handle) => family_handle,
+                Err(_) => continue,
+            };
+
+            let (mut candidates, mut candidate_properties) = (vec![], vec![]);
+            for handle in family_handle.fonts() {
+                let font = match Font::from_handle(handle) {
+                    Ok(font) if font.is_monospace() => font,
+                    _ => continue,
+                };
+                if seen_families.contains(&font.family_name()) {
+                    continue;
+                }
+                candidates.push((handle, font.family_name()));
+                candidate_properties.push(font.properties());
+            }
+
+            if let Ok(index) =
+                matching::find_best_match(&candidate_properties, &Properties::default())
+            {
+                let (handle, family_name) = &candidates[index];
+                seen_families.insert(family_name.clone());
+                handles.push((*handle).clone());
+            }
+        }
+        Ok(handles)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    ///
+    /// The family is the platform's UI font, such as Segoe UI on Windows, the system font on
+    /// macOS, or the Fontconfig `system-ui` alias, so callers needn't hardcode font names.
+    #[inline]
+    fn default_ui_font(&self, properties: &Properties) -> Result<Handle, SelectionError> {
+        self.default_ui_font_of_kind(UiFontKind::System, properties)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    ///
+    /// This is the same as `default_ui_font()`, named to sit alongside the other `select_*`
+    /// methods.
+    #[inline]
+    fn select_default_ui_font(&self, properties: &Properties) -> Result<Handle, SelectionError> {
+        self.default_ui_font(properties)
+    }
+
+    /// Returns the font that the system uses for the given kind of user interface text, picking
+    /// the face in its family that best matches `properties`.
+    ///
+    /// The default implementation, which is used by sources without a notion of a UI font, returns
+    /// the best match in the generic sans-serif family (or monospace family, for
+    /// `UiFontKind::Monospace`).
+    fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        let family_name = match kind {
+            UiFontKind::System | UiFontKind::SmallCaption => FamilyName::SansSerif,
+            UiFontKind::Monospace => FamilyName::Monospace,
+        };
+        self.select_best_match(&[family_name], properties)
+    }
+
+    /// Performs font matching like `select_best_match()`, resolving generic family names to the
+    /// families appropriate to `language`, a BCP 47 language tag.
+    fn select_best_match_for_language(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        language: &str,
+    ) -> Result<Handle, SelectionError> {
+        for family_name in family_names {
+            if let Ok(family_handle) =
+                self.select_family_by_generic_name_for_language(family_name, language)
+            {
+                let query = PropertiesQuery::from(*properties);
+                if let Some(selection) = select_in_family(self, &family_handle, &query)? {
+                    return Ok(selection.handle);
                 }
             }
         }
@@ -172,6 +600,21 @@ pub trait Source: Any {
         Ok(fields)
     }
 
+    #[doc(hidden)]
+    fn select_candidates_in_family(
+        &self,
+        family: &FamilyHandle,
+    ) -> Result<Vec<Candidate>, SelectionError> {
+        let mut candidates = vec![];
+        for font_handle in family.fonts() {
+            match Font::from_handle(font_handle) {
+                Ok(font) => candidates.push(candidate_for_font(&font)),
+                Err(e) => log::warn!("Error loading font from handle: {:?}", e),
+            }
+        }
+        Ok(candidates)
+    }
+
     /// Accesses this `Source` as `Any`, which allows downcasting back to a concrete type from a
     /// trait object.
     fn as_any(&self) -> &dyn Any;
@@ -180,3 +623,341 @@ pub trait Source: Any {
     /// trait object.
     fn as_mut_any(&mut self) -> &mut dyn Any;
 }
+
+// Picks the font in `family` that best matches `query`, treating variable fonts as covering the
+// ranges of their `wght` and `wdth` axes.
+fn select_in_family<S>(
+    source: &S,
+    family: &FamilyHandle,
+    query: &PropertiesQuery,
+) -> Result<Option<Selection>, SelectionError>
+where
+    S: Source + ?Sized,
+{
+    let candidates = source.select_candidates_in_family(family)?;
+    let (index, instance) = match matching::find_best_match_in_ranges(&candidates, query) {
+        Ok(result) => result,
+        Err(_) => return Ok(None),
+    };
+    let candidate = &candidates[index];
+    let mut variations = vec![];
+    if candidate.weight_range.0 != candidate.weight_range.1 {
+        variations.push((tables::fvar::WEIGHT_AXIS, instance.weight.0));
+    }
+    if candidate.stretch_range.0 != candidate.stretch_range.1 {
+        variations.push((tables::fvar::WIDTH_AXIS, instance.stretch.0 * 100.0));
+    }
+    Ok(Some(Selection {
+        handle: family.fonts[index].clone(),
+        variations,
+    }))
+}
+
+// Describes `font` for matching, widening its weight and stretch to the ranges of its `wght` and
+// `wdth` axes if it is a variable font.
+fn candidate_for_font(font: &Font) -> Candidate {
+    let mut candidate = Candidate::new(font.properties());
+    let axes = font
+        .load_font_table(tables::fvar::TAG)
+        .and_then(|fvar| tables::fvar::axes(&fvar))
+        .unwrap_or_default();
+    for axis in axes {
+        if axis.min_value > axis.max_value {
+            continue;
+        }
+        if axis.tag == tables::fvar::WEIGHT_AXIS {
+            candidate.weight_range = (Weight(axis.min_value), Weight(axis.max_value));
+        } else if axis.tag == tables::fvar::WIDTH_AXIS {
+            // `wdth` is a percentage of the normal width, while `Stretch` is a ratio.
+            candidate.stretch_range = (
+                Stretch::from_percentage(axis.min_value),
+                Stretch::from_percentage(axis.max_value),
+            );
+        }
+    }
+    candidate
+}
+
+// Splits `text` into maximal runs that `primary` does or doesn't cover. Neutral characters, such
+// as spaces, that sit between two characters it doesn't cover join the uncovered run, and
+// characters that continue a cluster, such as variation selectors, go with the character before
+// them.
+fn primary_coverage_segments(text: &str, primary: &Font) -> Vec<(Range<usize>, bool)> {
+    let mut chars: Vec<(usize, char, bool)> = vec![];
+    for (index, character) in text.char_indices() {
+        let covered = match chars.last() {
+            Some(&(_, _, previous_covered)) if is_cluster_continuation(character) => {
+                previous_covered
+            }
+            _ => primary.glyph_for_char(character).is_some(),
+        };
+        chars.push((index, character, covered));
+    }
+
+    let is_uncovered_strong =
+        |&(_, character, covered): &(usize, char, bool)| !covered && !is_neutral(character);
+    for index in 0..chars.len() {
+        let (_, character, covered) = chars[index];
+        if !covered || !is_neutral(character) {
+            continue;
+        }
+        let previous_strong = chars[..index]
+            .iter()
+            .rev()
+            .find(|&&(_, character, _)| !is_neutral(character));
+        let next_strong = chars[(index + 1)..]
+            .iter()
+            .find(|&&(_, character, _)| !is_neutral(character));
+        if previous_strong.is_some_and(is_uncovered_strong)
+            && next_strong.is_some_and(is_uncovered_strong)
+        {
+            chars[index].2 = false;
+        }
+    }
+
+    let mut segments: Vec<(Range<usize>, bool)> = vec![];
+    for (index, character, covered) in chars {
+        let end = index + character.len_utf8();
+        match segments.last_mut() {
+            Some((range, segment_covered)) if *segment_covered == covered => range.end = end,
+            _ => segments.push((index..end, covered)),
+        }
+    }
+    segments
+}
+
+// The fonts of a source and the characters that each one covers, gathered on first use while
+// looking for fallbacks. A query that needs fallbacks for several segments of text loads each
+// font once, rather than once per segment.
+#[derive(Default)]
+struct FallbackCandidates {
+    handles: Option<Vec<Handle>>,
+    coverage: Vec<Option<Vec<Range<u32>>>>,
+}
+
+impl FallbackCandidates {
+    fn new() -> FallbackCandidates {
+        FallbackCandidates::default()
+    }
+
+    fn handles<S>(&mut self, source: &S) -> &[Handle]
+    where
+        S: Source + ?Sized,
+    {
+        self.handles
+            .get_or_insert_with(|| source.all_fonts().unwrap_or_default())
+    }
+
+    // Returns true if the font at `index` of `handles()` maps any of `characters`, loading it to
+    // read its character map the first time.
+    fn covers_any(&mut self, index: usize, characters: &[char]) -> bool {
+        let handles = self.handles.as_deref().unwrap_or_default();
+        if self.coverage.len() < handles.len() {
+            self.coverage.resize(handles.len(), None);
+        }
+        let coverage = self.coverage[index].get_or_insert_with(|| {
+            Font::from_handle(&handles[index])
+                .map(|font| coverage_ranges(&font))
+                .unwrap_or_default()
+        });
+        characters.iter().any(|&character| {
+            let character = character as u32;
+            coverage
+                .binary_search_by(|range| {
+                    if range.end <= character {
+                        Ordering::Less
+                    } else if range.start > character {
+                        Ordering::Greater
+                    } else {
+                        Ordering::Equal
+                    }
+                })
+                .is_ok()
+        })
+    }
+}
+
+// Returns the code points that `font` maps, as sorted, disjoint ranges.
+fn coverage_ranges(font: &Font) -> Vec<Range<u32>> {
+    let mut code_points: Vec<u32> = font
+        .char_mappings()
+        .map(|(character, _)| character as u32)
+        .collect();
+    code_points.sort_unstable();
+    let mut ranges: Vec<Range<u32>> = vec![];
+    for code_point in code_points {
+        match ranges.last_mut() {
+            Some(range) if range.end >= code_point => range.end = code_point + 1,
+            _ => ranges.push(code_point..(code_point + 1)),
+        }
+    }
+    ranges
+}
+
+// Adds fonts that cover the characters of `segment` that none of `fallbacks` covers yet, trying the
+// platform fallback list of `primary`, if there is one, first and then every font in the source.
+fn add_fallbacks_for_segment<S>(
+    source: &S,
+    segment: &str,
+    primary: Option<&Font>,
+    properties: &Properties,
+    locale: &str,
+    candidates: &mut FallbackCandidates,
+    fallbacks: &mut Vec<(Handle, Font)>,
+) where
+    S: Source + ?Sized,
+{
+    let is_needed = |character: char, fallbacks: &[(Handle, Font)]| {
+        !is_neutral(character)
+            && !is_cluster_continuation(character)
+            && fallbacks
+                .iter()
+                .all(|(_, font)| font.glyph_for_char(character).is_none())
+    };
+    let mut needed: Vec<char> = segment
+        .chars()
+        .filter(|&character| is_needed(character, fallbacks))
+        .collect();
+    if needed.is_empty() {
+        return;
+    }
+
+    // Adds `font` if it covers any needed characters, and returns true once all are covered.
+    let add_fallback = |font: Font, needed: &mut Vec<char>, fallbacks: &mut Vec<(Handle, Font)>| {
+        if !needed
+            .iter()
+            .any(|&character| font.glyph_for_char(character).is_some())
+        {
+            return false;
+        }
+        // Use the face of the family that best matches the requested properties, if the source
+        // has it and it covers the same characters.
+        let family_name = FamilyName::Title(font.family_name());
+        let best_match = source
+            .select_best_match(&[family_name], properties)
+            .ok()
+            .and_then(|handle| Font::from_handle(&handle).ok().map(|font| (handle, font)))
+            .filter(|(_, best_font)| {
+                needed.iter().all(|&character| {
+                    font.glyph_for_char(character).is_none()
+                        || best_font.glyph_for_char(character).is_some()
+                })
+            });
+        let (handle, font) = match best_match {
+            Some(best_match) => best_match,
+            None => match font.handle() {
+                Some(handle) => (handle, font),
+                None => return false,
+            },
+        };
+        needed.retain(|&character| font.glyph_for_char(character).is_none());
+        fallbacks.push((handle, font));
+        needed.is_empty()
+    };
+
+    if let Some(primary) = primary {
+        for fallback in Loader::get_fallbacks(primary, segment, locale).fonts {
+            if add_fallback(fallback.font, &mut needed, fallbacks) {
+                return;
+            }
+        }
+    }
+
+    for index in 0..candidates.handles(source).len() {
+        if !candidates.covers_any(index, &needed) {
+            continue;
+        }
+        let handle = &candidates.handles(source)[index];
+        if let Ok(font) = Font::from_handle(handle) {
+            if add_fallback(font, &mut needed, fallbacks) {
+                return;
+            }
+        }
+    }
+}
+
+// Returns the length in bytes of the longest prefix of `text` that `font` covers, without
+// splitting clusters.
+fn covered_prefix_len(font: &Font, text: &str) -> usize {
+    let mut length = 0;
+    for (index, character) in text.char_indices() {
+        if is_cluster_continuation(character) {
+            if length == index {
+                length = index + character.len_utf8();
+            }
+            continue;
+        }
+        if font.glyph_for_char(character).is_none() {
+            break;
+        }
+        length = index + character.len_utf8();
+    }
+    length
+}
+
+// Returns the length in bytes of the first character of `text` and any characters that continue
+// its cluster.
+fn cluster_len(text: &str) -> usize {
+    let mut chars = text.char_indices();
+    let mut length = chars
+        .next()
+        .map_or(0, |(_, character)| character.len_utf8());
+    for (index, character) in chars {
+        if !is_cluster_continuation(character) {
+            break;
+        }
+        length = index + character.len_utf8();
+    }
+    length
+}
+
+fn push_coverage_run(
+    runs: &mut Vec<(Range<usize>, Option<usize>)>,
+    range: Range<usize>,
+    fallback_index: Option<usize>,
+) {
+    match runs.last_mut() {
+        Some((last_range, last_fallback_index))
+            if *last_fallback_index == fallback_index && last_range.end == range.start =>
+        {
+            last_range.end = range.end
+        }
+        _ => runs.push((range, fallback_index)),
+    }
+}
+
+// Characters that any font is likely to have and that shouldn't cause a font switch on their own.
+fn is_neutral(character: char) -> bool {
+    !is_cluster_continuation(character)
+        && (character.is_whitespace()
+            || character.is_ascii_punctuation()
+            || ('\u{2000}'..='\u{206f}').contains(&character))
+}
+
+// Characters that modify the character before them and must be drawn with the same font.
+fn is_cluster_continuation(character: char) -> bool {
+    matches!(
+        character,
+        '\u{0300}'..='\u{036f}'
+            | '\u{200c}'..='\u{200d}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{1f3fb}'..='\u{1f3ff}'
+            | '\u{e0020}'..='\u{e007f}'
+            | '\u{e0100}'..='\u{e01ef}'
+    )
+}
+
+// Returns a character that only fonts supporting the language are likely to have, for languages
+// whose text platforms typically supply from a separate family.
+fn sample_char_for_language(language: &str) -> Option<char> {
+    let primary_subtag = language.split(['-', '_']).next().unwrap_or("");
+    match &*primary_subtag.to_ascii_lowercase() {
+        // U+6F22 is a Han character whose preferred form differs between the CJK locales.
+        "zh" | "ja" | "ko" | "yue" => Some('\u{6f22}'),
+        "ar" | "fa" | "ur" => Some('\u{0628}'),
+        "he" | "yi" => Some('\u{05d0}'),
+        "hi" | "mr" | "ne" => Some('\u{0915}'),
+        "th" => Some('\u{0e01}'),
+        _ => None,
+    }
+}
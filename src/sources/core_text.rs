@@ -14,24 +14,30 @@ use core_foundation::array::CFArray;
 use core_foundation::base::{CFType, TCFType};
 use core_foundation::dictionary::CFDictionary;
 use core_foundation::string::CFString;
-use core_text::font::new_from_descriptor;
+use core_text::font::{
+    kCTFontSmallSystemFontType, kCTFontSystemFontType, kCTFontUserFixedPitchFontType,
+    new_from_descriptor, new_from_name, new_ui_font_for_language,
+};
 use core_text::font_collection::{self, CTFontCollection};
 use core_text::font_descriptor::{self, CTFontDescriptor};
 use core_text::font_manager;
 use std::any::Any;
 use std::f32;
 use std::fs::File;
+use std::ops::Range;
 use std::sync::Arc;
 
-use crate::error::SelectionError;
+use crate::error::{self, SelectionError};
 use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
 use crate::file_type::FileType;
 use crate::font::Font;
 use crate::handle::Handle;
 use crate::loaders::core_text::{self as core_text_loader, FONT_WEIGHT_MAPPING};
-use crate::properties::{Properties, Stretch, Weight};
-use crate::source::Source;
+use crate::properties::{Properties, PropertiesQuery, Stretch, Weight};
+use crate::source::{MatchQuality, Selection, Source, UiFontKind};
+#[cfg(feature = "notify")]
+use crate::sources::watch::{self, FontWatcher};
 use crate::utils;
 
 /// A source that contains the installed fonts on macOS.
@@ -80,21 +86,17 @@ impl CoreTextSource {
     }
 
     /// Selects a font by PostScript name, which should be a unique identifier.
+    ///
+    /// Core Text resolves the name directly, which is much faster than matching it against the
+    /// installed fonts and also finds fonts that were activated after the font list was cached.
+    /// Matching is only used if the direct lookup fails.
     pub fn select_by_postscript_name(
         &self,
         postscript_name: &str,
     ) -> Result<Handle, SelectionError> {
-        let attributes: CFDictionary<CFString, CFType> = CFDictionary::from_CFType_pairs(&[(
-            CFString::new("NSFontNameAttribute"),
-            CFString::new(postscript_name).as_CFType(),
-        )]);
-
-        let descriptor = font_descriptor::new_from_attributes(&attributes);
-        let descriptors = CFArray::from_CFTypes(&[descriptor]);
-        let collection = font_collection::new_from_descriptors(&descriptors);
-        match collection.get_descriptors() {
-            None => Err(SelectionError::NotFound),
-            Some(descriptors) => create_handle_from_descriptor(&*descriptors.get(0).unwrap()),
+        match select_by_postscript_name_directly(postscript_name) {
+            Ok(handle) => Ok(handle),
+            Err(_) => select_by_postscript_name_from_collection(postscript_name),
         }
     }
 
@@ -108,6 +110,160 @@ impl CoreTextSource {
     ) -> Result<Handle, SelectionError> {
         <Self as Source>::select_best_match(self, family_names, properties)
     }
+
+    /// Resolves a CSS `font-family` list, returning the best match for `properties` in the first
+    /// available family.
+    #[inline]
+    pub fn select_family_from_list(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_family_from_list(self, family_names, properties)
+    }
+
+    /// Performs font matching like `select_best_match()`, also returning the variation axis values
+    /// that instance the selected font closest to `properties`.
+    #[inline]
+    pub fn select_best_match_with_variations(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Selection, SelectionError> {
+        <Self as Source>::select_best_match_with_variations(self, family_names, properties)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification, considering only
+    /// the properties specified in `query`, and returns the handle.
+    #[inline]
+    pub fn select_best_match_for_query(
+        &self,
+        family_names: &[FamilyName],
+        query: &PropertiesQuery,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_query(self, family_names, query)
+    }
+
+    /// Finds the font in this source that is the same as, or most similar to, `font`, along with
+    /// how closely it matches.
+    #[inline]
+    pub fn find_matching(&self, font: &Font) -> Option<(Handle, MatchQuality)> {
+        <Self as Source>::find_matching(self, font)
+    }
+
+    /// Splits `text` into runs that can each be drawn with a single font, starting from `primary`
+    /// and falling back to other fonts only for the characters it lacks.
+    #[inline]
+    pub fn analyze_string_coverage(
+        &self,
+        text: &str,
+        primary: &Font,
+        properties: &Properties,
+        locale: &str,
+    ) -> Vec<(Range<usize>, Handle)> {
+        <Self as Source>::analyze_string_coverage(self, text, primary, properties, locale)
+    }
+
+    /// Calls `callback` on a background thread whenever fonts are installed in or removed from the
+    /// system font directories, until the returned watcher is dropped.
+    ///
+    /// Core Text picks up newly installed fonts on its own, so this source reflects them
+    /// immediately; `callback` is the cue to refresh any font lists shown to the user.
+    ///
+    /// Fails if the operating system's file change notifications can't be set up.
+    ///
+    /// This is only available with the Cargo feature `notify`.
+    #[cfg(feature = "notify")]
+    pub fn watch<F>(&self, callback: F) -> Result<FontWatcher, SelectionError>
+    where
+        F: Fn() + Send + 'static,
+    {
+        watch::watch_system_fonts(callback)
+    }
+
+    /// Looks up the family for a family name, choosing the family appropriate to `language` if
+    /// the name is generic.
+    #[inline]
+    pub fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        <Self as Source>::select_family_by_generic_name_for_language(self, family_name, language)
+    }
+
+    /// Performs font matching like `select_best_match()`, resolving generic family names to the
+    /// families appropriate to `language`, a BCP 47 language tag.
+    #[inline]
+    pub fn select_best_match_for_language(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        language: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_language(self, family_names, properties, language)
+    }
+
+    /// Returns a font that can draw `character`, for use when the font the text asked for can't.
+    #[inline]
+    pub fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_fallback_for_character(self, character, properties, locale)
+    }
+
+    /// Returns one monospace font from each family that contains monospace fonts.
+    #[inline]
+    pub fn select_monospace_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        <Self as Source>::select_monospace_fonts(self)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font(&self, properties: &Properties) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    ///
+    /// This is the same as `default_ui_font()`.
+    #[inline]
+    pub fn select_default_ui_font(
+        &self,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for the given kind of user interface text, picking
+    /// the face in its family that best matches `properties`.
+    ///
+    /// This asks Core Text for the UI font via `CTFontCreateUIFontForLanguage()`. If the system UI
+    /// family is hidden from family enumeration, as San Francisco is, the UI font itself is
+    /// returned as a native handle, ignoring `properties`.
+    pub fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        let ui_type = match kind {
+            UiFontKind::System => kCTFontSystemFontType,
+            UiFontKind::Monospace => kCTFontUserFixedPitchFontType,
+            UiFontKind::SmallCaption => kCTFontSmallSystemFontType,
+        };
+        let ui_font = new_ui_font_for_language(ui_type, 0.0, None);
+        let family_name = FamilyName::Title(ui_font.family_name());
+        if let Ok(handle) = self.select_best_match(&[family_name], properties) {
+            return Ok(handle);
+        }
+        let font = unsafe { Font::from_core_text_font_no_path(ui_font) };
+        Ok(Handle::from_native(&font))
+    }
 }
 
 impl Source for CoreTextSource {
@@ -127,6 +283,15 @@ impl Source for CoreTextSource {
         self.select_by_postscript_name(postscript_name)
     }
 
+    #[inline]
+    fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        self.default_ui_font_of_kind(kind, properties)
+    }
+
     #[inline]
     fn as_any(&self) -> &dyn Any {
         self
@@ -152,6 +317,34 @@ fn css_stretchiness_to_core_text_width(css_stretchiness: Stretch) -> f32 {
     0.25 * core_text_loader::piecewise_linear_find_index(css_stretchiness, &Stretch::MAPPING) - 1.0
 }
 
+// Looks up a PostScript name with `CTFontCreateWithName()`. That substitutes a default font for
+// names it doesn't know, so the name of the font it returns has to be checked.
+fn select_by_postscript_name_directly(postscript_name: &str) -> Result<Handle, SelectionError> {
+    let core_text_font =
+        new_from_name(postscript_name, 16.0).map_err(|_| SelectionError::NotFound)?;
+    if core_text_font.postscript_name() != postscript_name {
+        return Err(SelectionError::NotFound);
+    }
+    create_handle_from_descriptor(&core_text_font.copy_descriptor())
+}
+
+fn select_by_postscript_name_from_collection(
+    postscript_name: &str,
+) -> Result<Handle, SelectionError> {
+    let attributes: CFDictionary<CFString, CFType> = CFDictionary::from_CFType_pairs(&[(
+        CFString::new("NSFontNameAttribute"),
+        CFString::new(postscript_name).as_CFType(),
+    )]);
+
+    let descriptor = font_descriptor::new_from_attributes(&attributes);
+    let descriptors = CFArray::from_CFTypes(&[descriptor]);
+    let collection = font_collection::new_from_descriptors(&descriptors);
+    match collection.get_descriptors() {
+        None => Err(SelectionError::NotFound),
+        Some(descriptors) => create_handle_from_descriptor(&*descriptors.get(0).unwrap()),
+    }
+}
+
 fn create_handles_from_core_text_collection(
     collection: CTFontCollection,
 ) -> Result<Vec<Handle>, SelectionError> {
@@ -173,23 +366,23 @@ fn create_handles_from_core_text_collection(
 }
 
 fn create_handle_from_descriptor(descriptor: &CTFontDescriptor) -> Result<Handle, SelectionError> {
-    let font_path = descriptor.font_path().unwrap();
-
-    let mut file = if let Ok(file) = File::open(&font_path) {
-        file
-    } else {
-        return Err(SelectionError::CannotAccessSource { reason: None });
+    let postscript_name = descriptor.font_name();
+    let font_path = match descriptor.font_path() {
+        Some(font_path) => font_path,
+        None => {
+            return Err(SelectionError::CannotAccessSource {
+                reason: Some(format!("no file for font {}", postscript_name).into()),
+            })
+        }
     };
 
-    let font_data = if let Ok(font_data) = utils::slurp_file(&mut file) {
-        Arc::new(font_data)
-    } else {
-        return Err(SelectionError::CannotAccessSource { reason: None });
-    };
+    let font_data = File::open(&font_path)
+        .and_then(|mut file| utils::slurp_file(&mut file))
+        .map_err(|io_error| error::io_error_with_path(&font_path, io_error))?;
+    let font_data = Arc::new(font_data);
 
     match Font::analyze_bytes(Arc::clone(&font_data)) {
         Ok(FileType::Collection(font_count)) => {
-            let postscript_name = descriptor.font_name();
             for font_index in 0..font_count {
                 if let Ok(font) = Font::from_bytes(Arc::clone(&font_data), font_index) {
                     if let Some(font_postscript_name) = font.postscript_name() {
@@ -204,15 +397,42 @@ fn create_handle_from_descriptor(descriptor: &CTFontDescriptor) -> Result<Handle
         }
         Ok(FileType::Single) => Ok(Handle::from_memory(font_data, 0)),
         Err(e) => Err(SelectionError::CannotAccessSource {
-            reason: Some(format!("{:?} error on path {:?}", e, font_path).into()),
+            reason: Some(
+                format!(
+                    "{} in {:?}, the file of font {}",
+                    e, font_path, postscript_name
+                )
+                .into(),
+            ),
         }),
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::error::SelectionError;
     use crate::properties::{Stretch, Weight};
 
+    #[test]
+    fn select_by_postscript_name_directly_matches_collection() {
+        // Helvetica lives in a collection, so this also checks that the font index round-trips.
+        for &postscript_name in &["ArialMT", "Helvetica", "Helvetica-Bold", "Menlo-Regular"] {
+            let direct = super::select_by_postscript_name_directly(postscript_name).unwrap();
+            let matched =
+                super::select_by_postscript_name_from_collection(postscript_name).unwrap();
+            for handle in [direct, matched] {
+                let font = handle.load().unwrap();
+                assert_eq!(font.postscript_name().as_deref(), Some(postscript_name));
+            }
+        }
+
+        // Core Text substitutes a default font for unknown names, which mustn't be returned.
+        assert!(matches!(
+            super::select_by_postscript_name_directly("zxhjfgkadsfhg"),
+            Err(SelectionError::NotFound)
+        ));
+    }
+
     #[test]
     fn test_css_to_core_text_font_weight() {
         // Exact matches
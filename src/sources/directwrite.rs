@@ -12,14 +12,31 @@
 
 use dwrote::Font as DWriteFont;
 use dwrote::FontCollection as DWriteFontCollection;
+use dwrote::FontFile as DWriteFontFile;
+use dwrote::InformationalStringId;
 use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::ops::Range;
+use std::ptr;
+use winapi::shared::minwindef::UINT;
+use winapi::shared::winerror::S_OK;
+use winapi::um::dwrite_3::{IDWriteFontCollection1, IDWriteFontSet, DWRITE_FONT_PROPERTY};
+use winapi::um::dwrite_3::{IDWriteFontFaceReference, DWRITE_FONT_PROPERTY_ID_POSTSCRIPT_NAME};
+use winapi::um::winuser::{self, NONCLIENTMETRICSW, SPI_GETNONCLIENTMETRICS};
+use winapi::Interface;
+use wio::com::ComPtr;
 
 use crate::error::SelectionError;
 use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
+use crate::file_type::FileType;
+use crate::font::Font;
 use crate::handle::Handle;
-use crate::properties::Properties;
-use crate::source::Source;
+use crate::properties::{Properties, PropertiesQuery};
+use crate::source::{MatchQuality, Selection, Source, UiFontKind};
+#[cfg(feature = "notify")]
+use crate::sources::watch::{self, FontWatcher};
 
 /// A source that contains the installed fonts on Windows.
 #[allow(missing_debug_implementations)]
@@ -79,13 +96,118 @@ impl DirectWriteSource {
 
     /// Selects a font by PostScript name, which should be a unique identifier.
     ///
-    /// On the DirectWrite backend, this does a brute-force search of installed fonts to find the
-    /// one that matches.
+    /// On the DirectWrite backend, this asks the system font set for the face with this PostScript
+    /// name, which covers every face of collection files. Before Windows 10, which has no font
+    /// sets, it compares the PostScript names that DirectWrite already knows for each installed
+    /// font instead. DirectWrite doesn't always enumerate every face of a collection file, so if
+    /// that fails, the remaining faces of each installed collection are opened and their own
+    /// PostScript names compared. The returned handle refers to the matching face's index within
+    /// its collection.
     pub fn select_by_postscript_name(
         &self,
         postscript_name: &str,
     ) -> Result<Handle, SelectionError> {
-        <Self as Source>::select_by_postscript_name(self, postscript_name)
+        if let Some(result) = self.select_by_postscript_name_in_font_set(postscript_name) {
+            return result;
+        }
+        for dwrite_family in self.system_font_collection.families_iter() {
+            for font_index in 0..dwrite_family.get_font_count() {
+                let dwrite_font = dwrite_family.get_font(font_index);
+                let font_postscript_name =
+                    dwrite_font.informational_string(InformationalStringId::PostscriptName);
+                if font_postscript_name.as_deref() == Some(postscript_name) {
+                    return Ok(self.create_handle_from_dwrite_font(dwrite_font));
+                }
+            }
+        }
+        self.select_collection_face_by_postscript_name(postscript_name)
+    }
+
+    // Filters the system font set by PostScript name. Returns `None` if font sets aren't available
+    // or the matching face isn't a local file.
+    fn select_by_postscript_name_in_font_set(
+        &self,
+        postscript_name: &str,
+    ) -> Option<Result<Handle, SelectionError>> {
+        unsafe {
+            let mut collection: *mut IDWriteFontCollection1 = ptr::null_mut();
+            let hr = (*self.system_font_collection.as_ptr()).QueryInterface(
+                &IDWriteFontCollection1::uuidof(),
+                &mut collection as *mut *mut IDWriteFontCollection1 as *mut _,
+            );
+            if hr != S_OK {
+                return None;
+            }
+            let collection = ComPtr::from_raw(collection);
+            let mut font_set: *mut IDWriteFontSet = ptr::null_mut();
+            if collection.GetFontSet(&mut font_set) != S_OK {
+                return None;
+            }
+            let font_set = ComPtr::from_raw(font_set);
+
+            let postscript_name: Vec<u16> = postscript_name.encode_utf16().chain(Some(0)).collect();
+            let locale_name = [0];
+            let property = DWRITE_FONT_PROPERTY {
+                propertyId: DWRITE_FONT_PROPERTY_ID_POSTSCRIPT_NAME,
+                propertyValue: postscript_name.as_ptr(),
+                localeName: locale_name.as_ptr(),
+            };
+            let mut matching_fonts: *mut IDWriteFontSet = ptr::null_mut();
+            if font_set.GetMatchingFonts_1(&property, 1, &mut matching_fonts) != S_OK {
+                return None;
+            }
+            let matching_fonts = ComPtr::from_raw(matching_fonts);
+            if matching_fonts.GetFontCount() == 0 {
+                return Some(Err(SelectionError::NotFound));
+            }
+
+            let mut face_reference: *mut IDWriteFontFaceReference = ptr::null_mut();
+            if matching_fonts.GetFontFaceReference(0, &mut face_reference) != S_OK {
+                return None;
+            }
+            let face_reference = ComPtr::from_raw(face_reference);
+            let mut font_file = ptr::null_mut();
+            if face_reference.GetFontFile(&mut font_file) != S_OK {
+                return None;
+            }
+            let path = DWriteFontFile::take(ComPtr::from_raw(font_file)).get_font_file_path()?;
+            Some(Ok(Handle::from_path(
+                path,
+                face_reference.GetFontFaceIndex(),
+            )))
+        }
+    }
+
+    fn select_collection_face_by_postscript_name(
+        &self,
+        postscript_name: &str,
+    ) -> Result<Handle, SelectionError> {
+        // The indices of the faces that DirectWrite enumerates, by file.
+        let mut enumerated_faces: HashMap<_, HashSet<u32>> = HashMap::new();
+        for handle in self.all_fonts()? {
+            if let Handle::Path { path, font_index } = handle {
+                enumerated_faces.entry(path).or_default().insert(font_index);
+            }
+        }
+
+        for (path, enumerated_faces) in enumerated_faces {
+            let face_count = match Font::analyze_path(&path) {
+                Ok(FileType::Collection(face_count)) => face_count,
+                _ => continue,
+            };
+            if enumerated_faces.len() as u32 >= face_count {
+                continue;
+            }
+            for font_index in (0..face_count).filter(|index| !enumerated_faces.contains(index)) {
+                match Font::from_path(&path, font_index) {
+                    Ok(font) if font.postscript_name().as_deref() == Some(postscript_name) => {
+                        return Ok(Handle::from_path(path, font_index));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Err(SelectionError::NotFound)
     }
 
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
@@ -99,6 +221,162 @@ impl DirectWriteSource {
         <Self as Source>::select_best_match(self, family_names, properties)
     }
 
+    /// Resolves a CSS `font-family` list, returning the best match for `properties` in the first
+    /// available family.
+    #[inline]
+    pub fn select_family_from_list(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_family_from_list(self, family_names, properties)
+    }
+
+    /// Performs font matching like `select_best_match()`, also returning the variation axis values
+    /// that instance the selected font closest to `properties`.
+    #[inline]
+    pub fn select_best_match_with_variations(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Selection, SelectionError> {
+        <Self as Source>::select_best_match_with_variations(self, family_names, properties)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification, considering only
+    /// the properties specified in `query`, and returns the handle.
+    #[inline]
+    pub fn select_best_match_for_query(
+        &self,
+        family_names: &[FamilyName],
+        query: &PropertiesQuery,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_query(self, family_names, query)
+    }
+
+    /// Finds the font in this source that is the same as, or most similar to, `font`, along with
+    /// how closely it matches.
+    #[inline]
+    pub fn find_matching(&self, font: &Font) -> Option<(Handle, MatchQuality)> {
+        <Self as Source>::find_matching(self, font)
+    }
+
+    /// Splits `text` into runs that can each be drawn with a single font, starting from `primary`
+    /// and falling back to other fonts only for the characters it lacks.
+    #[inline]
+    pub fn analyze_string_coverage(
+        &self,
+        text: &str,
+        primary: &Font,
+        properties: &Properties,
+        locale: &str,
+    ) -> Vec<(Range<usize>, Handle)> {
+        <Self as Source>::analyze_string_coverage(self, text, primary, properties, locale)
+    }
+
+    /// Calls `callback` on a background thread whenever fonts are installed in or removed from the
+    /// system font directories, until the returned watcher is dropped.
+    ///
+    /// This source only reflects the system font collection as it was when the source was created,
+    /// so create a new one in `callback` to pick up the changes.
+    ///
+    /// Fails if the operating system's file change notifications can't be set up.
+    ///
+    /// This is only available with the Cargo feature `notify`.
+    #[cfg(feature = "notify")]
+    pub fn watch<F>(&self, callback: F) -> Result<FontWatcher, SelectionError>
+    where
+        F: Fn() + Send + 'static,
+    {
+        watch::watch_system_fonts(callback)
+    }
+
+    /// Looks up the family for a family name, choosing the family appropriate to `language` if
+    /// the name is generic.
+    #[inline]
+    pub fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        <Self as Source>::select_family_by_generic_name_for_language(self, family_name, language)
+    }
+
+    /// Performs font matching like `select_best_match()`, resolving generic family names to the
+    /// families appropriate to `language`, a BCP 47 language tag.
+    #[inline]
+    pub fn select_best_match_for_language(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        language: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_language(self, family_names, properties, language)
+    }
+
+    /// Returns a font that can draw `character`, for use when the font the text asked for can't.
+    #[inline]
+    pub fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_fallback_for_character(self, character, properties, locale)
+    }
+
+    /// Returns one monospace font from each family that contains monospace fonts.
+    #[inline]
+    pub fn select_monospace_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        <Self as Source>::select_monospace_fonts(self)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font(&self, properties: &Properties) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    ///
+    /// This is the same as `default_ui_font()`.
+    #[inline]
+    pub fn select_default_ui_font(
+        &self,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for the given kind of user interface text, picking
+    /// the face in its family that best matches `properties`.
+    ///
+    /// The family comes from the message and small caption fonts in the system's nonclient
+    /// metrics. Windows has no setting for a monospace UI font, so `UiFontKind::Monospace` selects
+    /// the generic monospace family.
+    pub fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        let log_font = match kind {
+            UiFontKind::Monospace => {
+                return self.select_best_match(&[FamilyName::Monospace], properties)
+            }
+            UiFontKind::System => nonclient_metrics()?.lfMessageFont,
+            UiFontKind::SmallCaption => nonclient_metrics()?.lfSmCaptionFont,
+        };
+        let face_name_len = log_font
+            .lfFaceName
+            .iter()
+            .position(|&ch| ch == 0)
+            .unwrap_or(log_font.lfFaceName.len());
+        let family_name = String::from_utf16_lossy(&log_font.lfFaceName[..face_name_len]);
+        self.select_best_match(&[FamilyName::Title(family_name)], properties)
+    }
+
     fn create_handle_from_dwrite_font(&self, dwrite_font: DWriteFont) -> Handle {
         let dwrite_font_face = dwrite_font.create_font_face();
         let dwrite_font_files = dwrite_font_face.get_files();
@@ -125,6 +403,20 @@ impl Source for DirectWriteSource {
         self.select_family_by_name(family_name)
     }
 
+    #[inline]
+    fn select_by_postscript_name(&self, postscript_name: &str) -> Result<Handle, SelectionError> {
+        self.select_by_postscript_name(postscript_name)
+    }
+
+    #[inline]
+    fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        self.default_ui_font_of_kind(kind, properties)
+    }
+
     #[inline]
     fn as_any(&self) -> &dyn Any {
         self
@@ -135,3 +427,22 @@ impl Source for DirectWriteSource {
         self
     }
 }
+
+fn nonclient_metrics() -> Result<NONCLIENTMETRICSW, SelectionError> {
+    unsafe {
+        let mut metrics: NONCLIENTMETRICSW = mem::zeroed();
+        metrics.cbSize = mem::size_of::<NONCLIENTMETRICSW>() as UINT;
+        if winuser::SystemParametersInfoW(
+            SPI_GETNONCLIENTMETRICS,
+            metrics.cbSize,
+            &mut metrics as *mut NONCLIENTMETRICSW as *mut _,
+            0,
+        ) == 0
+        {
+            return Err(SelectionError::CannotAccessSource {
+                reason: Some("SystemParametersInfoW failed".into()),
+            });
+        }
+        Ok(metrics)
+    }
+}
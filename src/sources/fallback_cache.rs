@@ -0,0 +1,266 @@
+// font-kit/src/sources/fallback_cache.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A source that remembers the fonts chosen by `select_fallback_for_character()`.
+//!
+//! Text layout asks for the fallback font of the same characters over and over, and answering
+//! can mean loading many fonts. Wrapping a source in a `FallbackCacheSource` makes repeated
+//! queries cheap, and the cache can be shared between threads.
+
+use crate::error::SelectionError;
+use crate::family_handle::FamilyHandle;
+use crate::family_name::FamilyName;
+use crate::handle::Handle;
+use crate::properties::{Properties, Style};
+use crate::source::{Source, UiFontKind};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// The number of fallback queries that a cache remembers by default.
+pub const DEFAULT_CAPACITY: usize = 4096;
+
+// Everything that `Source::select_fallback_for_character()` depends on besides the fonts in the
+// source.
+//
+// The character is kept whole rather than bucketed by Unicode block, because fonts often cover
+// only part of a block. Weight and stretch are compared by their exact bits rather than rounded,
+// because face selection can pick a different face for any difference in them. The locale is
+// kept exactly as given, since platforms differ in how they normalize it.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FallbackKey {
+    character: char,
+    locale: String,
+    style: Style,
+    weight: u32,
+    stretch: u32,
+}
+
+impl FallbackKey {
+    fn new(character: char, properties: &Properties, locale: &str) -> FallbackKey {
+        FallbackKey {
+            character,
+            locale: locale.to_owned(),
+            style: properties.style,
+            weight: properties.weight.0.to_bits(),
+            stretch: properties.stretch.0.to_bits(),
+        }
+    }
+}
+
+// Characters that no font covers are cached as `None`, since those are the most expensive
+// queries to repeat.
+type FallbackMap = HashMap<FallbackKey, Option<Handle>>;
+
+/// A handle to the cache of a `FallbackCacheSource` that can clear it from another thread.
+///
+/// Pass `move || invalidator.invalidate()` as the callback of a system source's `watch()` method
+/// to forget cached fallbacks when fonts are installed or removed.
+#[derive(Clone)]
+pub struct FallbackCacheInvalidator {
+    entries: Arc<RwLock<FallbackMap>>,
+}
+
+impl FallbackCacheInvalidator {
+    /// Forgets every cached fallback.
+    pub fn invalidate(&self) {
+        self.entries.write().unwrap().clear();
+    }
+}
+
+impl fmt::Debug for FallbackCacheInvalidator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FallbackCacheInvalidator").finish()
+    }
+}
+
+/// A source that caches the results of `select_fallback_for_character()` of the source it wraps.
+///
+/// Results are keyed by the exact character, locale, style, weight, and stretch of the query,
+/// which is everything the lookup depends on besides the fonts in the wrapped source. When the
+/// fonts in that source change, call `invalidate()` or `replace_source()`, or use an
+/// `invalidator()` from another thread, so that stale fallbacks aren't returned.
+///
+/// The cache holds at most `capacity` entries. It is emptied when it fills up, which keeps lookups
+/// cheap at the cost of recomputing fallbacks that are still in use.
+///
+/// Other queries are answered from the wrapped source uncached, through the methods that sources
+/// implement themselves.
+pub struct FallbackCacheSource<S> {
+    source: S,
+    entries: Arc<RwLock<FallbackMap>>,
+    capacity: usize,
+}
+
+impl<S> FallbackCacheSource<S>
+where
+    S: Source,
+{
+    /// Wraps `source`, remembering up to `DEFAULT_CAPACITY` fallbacks.
+    #[inline]
+    pub fn new(source: S) -> FallbackCacheSource<S> {
+        FallbackCacheSource::with_capacity(source, DEFAULT_CAPACITY)
+    }
+
+    /// Wraps `source`, remembering up to `capacity` fallbacks.
+    pub fn with_capacity(source: S, capacity: usize) -> FallbackCacheSource<S> {
+        FallbackCacheSource {
+            source,
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            capacity,
+        }
+    }
+
+    /// Returns the wrapped source.
+    #[inline]
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Replaces the wrapped source, such as with one that reflects newly installed fonts, and
+    /// forgets every cached fallback.
+    pub fn replace_source(&mut self, source: S) -> S {
+        self.invalidate();
+        std::mem::replace(&mut self.source, source)
+    }
+
+    /// Unwraps the wrapped source, discarding the cache.
+    #[inline]
+    pub fn into_source(self) -> S {
+        self.source
+    }
+
+    /// Forgets every cached fallback.
+    #[inline]
+    pub fn invalidate(&self) {
+        self.invalidator().invalidate()
+    }
+
+    /// Returns a handle that can clear the cache from another thread.
+    #[inline]
+    pub fn invalidator(&self) -> FallbackCacheInvalidator {
+        FallbackCacheInvalidator {
+            entries: self.entries.clone(),
+        }
+    }
+
+    /// Returns the number of fallbacks currently cached.
+    #[inline]
+    pub fn cached_len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Returns a font that can draw `character`, consulting the cache first.
+    pub fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        let key = FallbackKey::new(character, properties, locale);
+        if let Some(cached) = self.entries.read().unwrap().get(&key) {
+            return cached.clone().ok_or(SelectionError::NotFound);
+        }
+
+        // The lock isn't held during the lookup, which can take a while and may reenter the
+        // cache. Threads racing on the same key compute the same result, so either write is fine.
+        let result = self
+            .source
+            .select_fallback_for_character(character, properties, locale);
+        let cached = match result {
+            Ok(ref handle) => Some(handle.clone()),
+            Err(SelectionError::NotFound) => None,
+            Err(_) => return result,
+        };
+
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= self.capacity {
+            entries.clear();
+        }
+        if self.capacity > 0 {
+            entries.insert(key, cached);
+        }
+        result
+    }
+}
+
+// Only the methods that sources have to implement, and those that the built-in sources specialize,
+// are passed through. The default implementations of the others are built on these, so that, for
+// example, `analyze_string_coverage()` goes through the cache too.
+impl<S> Source for FallbackCacheSource<S>
+where
+    S: Source,
+{
+    #[inline]
+    fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        self.source.all_fonts()
+    }
+
+    #[inline]
+    fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        self.source.all_families()
+    }
+
+    #[inline]
+    fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        self.source.select_family_by_name(family_name)
+    }
+
+    #[inline]
+    fn select_by_postscript_name(&self, postscript_name: &str) -> Result<Handle, SelectionError> {
+        self.source.select_by_postscript_name(postscript_name)
+    }
+
+    #[inline]
+    fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        self.source
+            .select_family_by_generic_name_for_language(family_name, language)
+    }
+
+    #[inline]
+    fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        self.select_fallback_for_character(character, properties, locale)
+    }
+
+    #[inline]
+    fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        self.source.default_ui_font_of_kind(kind, properties)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<S> fmt::Debug for FallbackCacheSource<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FallbackCacheSource")
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
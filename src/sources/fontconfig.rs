@@ -18,10 +18,19 @@
 use crate::error::SelectionError;
 use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
+use crate::font::Font;
 use crate::handle::Handle;
-use crate::properties::Properties;
-use crate::source::Source;
+use crate::properties::{Properties, PropertiesQuery};
+use crate::source::{MatchQuality, Selection, Source, UiFontKind};
+#[cfg(feature = "notify")]
+use crate::sources::watch::{self, FontWatcher};
+use crate::tables;
 use std::any::Any;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::process::Command;
+use std::sync::{Mutex, PoisonError};
 
 /// A source that contains the fonts installed on the system, as reported by the Fontconfig
 /// library.
@@ -62,21 +71,28 @@ impl FontconfigSource {
             .map_err(|_| SelectionError::NotFound)?;
 
         let mut handles = vec![];
+        let mut seen = HashSet::new();
         for patt in patterns {
             let path = match patt.get_string(fc::Object::File) {
                 Some(v) => v,
                 None => continue,
             };
 
-            let index = match patt.get_integer(fc::Object::Index) {
-                Some(v) => v,
+            let font_index = match patt
+                .get_integer(fc::Object::Index)
+                .and_then(split_font_index)
+            {
+                Some((font_index, _)) => font_index,
                 None => continue,
             };
 
-            handles.push(Handle::Path {
-                path: path.into(),
-                font_index: index as u32,
-            });
+            // Named instances share the handle of their variable font.
+            if seen.insert((path.clone(), font_index)) {
+                handles.push(Handle::Path {
+                    path: path.into(),
+                    font_index,
+                });
+            }
         }
 
         if !handles.is_empty() {
@@ -137,11 +153,20 @@ impl FontconfigSource {
             .map_err(|_| SelectionError::NotFound)?;
 
         let mut handles = vec![];
+        let mut seen = HashSet::new();
         for patt in patterns {
             let font_path = patt.get_string(fc::Object::File).unwrap();
-            let font_index = patt.get_integer(fc::Object::Index).unwrap() as u32;
-            let handle = Handle::from_path(std::path::PathBuf::from(font_path), font_index);
-            handles.push(handle);
+            let font_index = match split_font_index(patt.get_integer(fc::Object::Index).unwrap()) {
+                Some((font_index, _)) => font_index,
+                None => continue,
+            };
+
+            // Named instances share the handle of their variable font, and are matched through its
+            // variations.
+            if seen.insert((font_path.clone(), font_index)) {
+                let handle = Handle::from_path(std::path::PathBuf::from(font_path), font_index);
+                handles.push(handle);
+            }
         }
 
         if !handles.is_empty() {
@@ -155,7 +180,21 @@ impl FontconfigSource {
     ///
     /// Accepts: serif, sans-serif, monospace, cursive and fantasy.
     fn select_generic_font(&self, name: &str) -> Result<String, SelectionError> {
-        let mut pattern = fc::Pattern::from_name(name);
+        self.select_generic_font_for_language(name, None)
+    }
+
+    /// Selects a font by a generic name, preferring fonts that support `language` if supplied.
+    fn select_generic_font_for_language(
+        &self,
+        name: &str,
+        language: Option<&str>,
+    ) -> Result<String, SelectionError> {
+        let mut pattern = match language {
+            Some(language) => {
+                fc::Pattern::from_name(&format!("{}:lang={}", name, fontconfig_language(language)))
+            }
+            None => fc::Pattern::from_name(name),
+        };
         pattern.config_substitute(fc::MatchKind::Pattern);
         pattern.default_substitute();
 
@@ -174,12 +213,53 @@ impl FontconfigSource {
 
     /// Selects a font by PostScript name, which should be a unique identifier.
     ///
-    /// The default implementation, which is used by the DirectWrite and the filesystem backends,
-    /// does a brute-force search of installed fonts to find the one that matches.
+    /// If the name is that of a named instance of a variable font, such as "Inter-Bold", this
+    /// returns the handle of the variable font; `select_by_postscript_name_with_variations()` also
+    /// returns the instance's variation axis values.
     pub fn select_by_postscript_name(
         &self,
         postscript_name: &str,
     ) -> Result<Handle, SelectionError> {
+        let (font_path, font_index, _) = self.find_postscript_name(postscript_name)?;
+        Ok(Handle::from_path(
+            std::path::PathBuf::from(font_path),
+            font_index,
+        ))
+    }
+
+    /// Selects a font by PostScript name like `select_by_postscript_name()`, also returning the
+    /// variation axis values of the named instance that the name refers to, if any.
+    pub fn select_by_postscript_name_with_variations(
+        &self,
+        postscript_name: &str,
+    ) -> Result<Selection, SelectionError> {
+        let (font_path, font_index, instance_index) = self.find_postscript_name(postscript_name)?;
+        let handle = Handle::from_path(std::path::PathBuf::from(&font_path), font_index);
+        let variations = match instance_index {
+            Some(instance_index) => Font::from_handle(&handle)
+                .ok()
+                .and_then(|font| font.load_font_table(tables::fvar::TAG))
+                .and_then(|table| tables::fvar::instance_coordinates(&table, instance_index))
+                .ok_or_else(|| SelectionError::CannotAccessSource {
+                    reason: Some(
+                        format!(
+                            "no named instance {} of font {} in {:?}",
+                            instance_index, postscript_name, font_path
+                        )
+                        .into(),
+                    ),
+                })?,
+            None => vec![],
+        };
+        Ok(Selection { handle, variations })
+    }
+
+    // Looks up the file, font index and named instance, if any, of the font named
+    // `postscript_name`.
+    fn find_postscript_name(
+        &self,
+        postscript_name: &str,
+    ) -> Result<(String, u32, Option<u16>), SelectionError> {
         let mut pattern = fc::Pattern::new();
         pattern.push_string(fc::Object::PostScriptName, postscript_name.to_owned());
 
@@ -192,14 +272,15 @@ impl FontconfigSource {
             .list(&self.config, object_set)
             .map_err(|_| SelectionError::NotFound)?;
 
-        if let Some(patt) = patterns.into_iter().next() {
+        for patt in patterns {
             let font_path = patt.get_string(fc::Object::File).unwrap();
-            let font_index = patt.get_integer(fc::Object::Index).unwrap() as u32;
-            let handle = Handle::from_path(std::path::PathBuf::from(font_path), font_index);
-            Ok(handle)
-        } else {
-            Err(SelectionError::NotFound)
+            if let Some((font_index, instance_index)) =
+                split_font_index(patt.get_integer(fc::Object::Index).unwrap())
+            {
+                return Ok((font_path, font_index, instance_index));
+            }
         }
+        Err(SelectionError::NotFound)
     }
 
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
@@ -212,6 +293,203 @@ impl FontconfigSource {
     ) -> Result<Handle, SelectionError> {
         <Self as Source>::select_best_match(self, family_names, properties)
     }
+
+    /// Resolves a CSS `font-family` list, returning the best match for `properties` in the first
+    /// available family.
+    #[inline]
+    pub fn select_family_from_list(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_family_from_list(self, family_names, properties)
+    }
+
+    /// Performs font matching like `select_best_match()`, also returning the variation axis values
+    /// that instance the selected font closest to `properties`.
+    #[inline]
+    pub fn select_best_match_with_variations(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Selection, SelectionError> {
+        <Self as Source>::select_best_match_with_variations(self, family_names, properties)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification, considering only
+    /// the properties specified in `query`, and returns the handle.
+    #[inline]
+    pub fn select_best_match_for_query(
+        &self,
+        family_names: &[FamilyName],
+        query: &PropertiesQuery,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_query(self, family_names, query)
+    }
+
+    /// Finds the font in this source that is the same as, or most similar to, `font`, along with
+    /// how closely it matches.
+    #[inline]
+    pub fn find_matching(&self, font: &Font) -> Option<(Handle, MatchQuality)> {
+        <Self as Source>::find_matching(self, font)
+    }
+
+    /// Splits `text` into runs that can each be drawn with a single font, starting from `primary`
+    /// and falling back to other fonts only for the characters it lacks.
+    #[inline]
+    pub fn analyze_string_coverage(
+        &self,
+        text: &str,
+        primary: &Font,
+        properties: &Properties,
+        locale: &str,
+    ) -> Vec<(Range<usize>, Handle)> {
+        <Self as Source>::analyze_string_coverage(self, text, primary, properties, locale)
+    }
+
+    /// Calls `callback` on a background thread whenever fonts are installed in or removed from the
+    /// system font directories, until the returned watcher is dropped.
+    ///
+    /// Like Fontconfig's own configuration, this source only reflects the fonts that were
+    /// installed when it was created, so create a new one in `callback` to pick up the changes.
+    ///
+    /// Fails if the operating system's file change notifications can't be set up.
+    ///
+    /// This is only available with the Cargo feature `notify`.
+    #[cfg(feature = "notify")]
+    pub fn watch<F>(&self, callback: F) -> Result<FontWatcher, SelectionError>
+    where
+        F: Fn() + Send + 'static,
+    {
+        watch::watch_system_fonts(callback)
+    }
+
+    /// Looks up the family for a family name, choosing the family appropriate to `language` if
+    /// the name is generic.
+    ///
+    /// This matches the generic name with a Fontconfig `:lang=` property, so per-language families
+    /// such as "Noto Sans CJK JP" and "Noto Sans CJK SC" are distinguished.
+    pub fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        let generic_name = match *family_name {
+            FamilyName::Title(ref title) => return self.select_family_by_name(title),
+            FamilyName::Serif => "serif",
+            FamilyName::SansSerif => "sans-serif",
+            FamilyName::Monospace => "monospace",
+            FamilyName::Cursive => "cursive",
+            FamilyName::Fantasy => "fantasy",
+        };
+        let family_name = self.select_generic_font_for_language(generic_name, Some(language))?;
+        self.select_family_by_name(&family_name)
+    }
+
+    /// Performs font matching like `select_best_match()`, resolving generic family names to the
+    /// families appropriate to `language`, a BCP 47 language tag.
+    #[inline]
+    pub fn select_best_match_for_language(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        language: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_language(self, family_names, properties, language)
+    }
+
+    /// Returns a font that can draw `character`, for use when the font the text asked for can't.
+    #[inline]
+    pub fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_fallback_for_character(self, character, properties, locale)
+    }
+
+    /// Returns one monospace font from each family that contains monospace fonts.
+    #[inline]
+    pub fn select_monospace_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        <Self as Source>::select_monospace_fonts(self)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font(&self, properties: &Properties) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    ///
+    /// This is the same as `default_ui_font()`.
+    #[inline]
+    pub fn select_default_ui_font(
+        &self,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for the given kind of user interface text, picking
+    /// the face in its family that best matches `properties`.
+    ///
+    /// The family is chosen by trying, in order:
+    ///
+    /// 1. The Fontconfig `system-ui` alias (not consulted for `UiFontKind::Monospace`), if the
+    ///    configuration maps it to something other than the default sans-serif family.
+    ///
+    /// 2. The GNOME desktop interface font setting (`font-name`, or `monospace-font-name` for
+    ///    `UiFontKind::Monospace`), as reported by `gsettings`.
+    ///
+    /// 3. The Fontconfig `sans-serif` (or `monospace`) alias.
+    ///
+    /// Fontconfig doesn't distinguish small caption fonts, so `UiFontKind::SmallCaption` selects
+    /// the same family as `UiFontKind::System`.
+    pub fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        let (generic_name, settings_key) = match kind {
+            UiFontKind::System | UiFontKind::SmallCaption => ("sans-serif", "font-name"),
+            UiFontKind::Monospace => ("monospace", "monospace-font-name"),
+        };
+
+        if kind != UiFontKind::Monospace {
+            if let Ok(family_name) = self.select_generic_font("system-ui") {
+                if self.select_generic_font(generic_name).ok().as_ref() != Some(&family_name) {
+                    if let Ok(handle) =
+                        self.select_best_match(&[FamilyName::Title(family_name)], properties)
+                    {
+                        return Ok(handle);
+                    }
+                }
+            }
+        }
+
+        if let Some(font_description) = gnome_interface_setting(settings_key) {
+            // The setting is a Pango font description such as "Cantarell Bold 11". Try the longest
+            // prefix that names an installed family, so that family names containing spaces work.
+            let words: Vec<&str> = font_description.split_whitespace().collect();
+            for word_count in (1..=words.len()).rev() {
+                let family_name = words[0..word_count].join(" ");
+                if self.select_family_by_name(&family_name).is_ok() {
+                    if let Ok(handle) =
+                        self.select_best_match(&[FamilyName::Title(family_name)], properties)
+                    {
+                        return Ok(handle);
+                    }
+                }
+            }
+        }
+
+        let family_name = self.select_generic_font(generic_name)?;
+        self.select_best_match(&[FamilyName::Title(family_name)], properties)
+    }
 }
 
 impl Source for FontconfigSource {
@@ -235,6 +513,32 @@ impl Source for FontconfigSource {
         self.select_by_postscript_name(postscript_name)
     }
 
+    #[inline]
+    fn select_by_postscript_name_with_variations(
+        &self,
+        postscript_name: &str,
+    ) -> Result<Selection, SelectionError> {
+        self.select_by_postscript_name_with_variations(postscript_name)
+    }
+
+    #[inline]
+    fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        self.default_ui_font_of_kind(kind, properties)
+    }
+
+    #[inline]
+    fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        self.select_family_by_generic_name_for_language(family_name, language)
+    }
+
     #[inline]
     fn as_any(&self) -> &dyn Any {
         self
@@ -246,6 +550,83 @@ impl Source for FontconfigSource {
     }
 }
 
+// Splits a Fontconfig font index, which is a FreeType face index, into a font-kit font index and
+// the index of a named instance, if any.
+//
+// Fontconfig lists each named instance of a variable font as a font of its own, with the instance
+// number, counting from 1, in the upper 16 bits of the index. Font indices only select members of
+// collections, so the instance is returned separately, as the index that `Font::with_named_instance()`
+// and the `fvar` table use.
+fn split_font_index(fontconfig_index: i32) -> Option<(u32, Option<u16>)> {
+    let index = u32::try_from(fontconfig_index).ok()?;
+    Some((index & 0xffff, ((index >> 16) as u16).checked_sub(1)))
+}
+
+// Converts a BCP 47 language tag to the form Fontconfig uses for its `lang` property, which is a
+// lowercase ISO 639 language code optionally followed by an ISO 3166 territory, such as "zh-tw".
+fn fontconfig_language(language: &str) -> String {
+    let language = language.replace('_', "-").to_ascii_lowercase();
+    let mut subtags = language
+        .split('-')
+        .filter(|subtag| subtag.chars().all(|ch| ch.is_ascii_alphanumeric()));
+    let primary_subtag = subtags.next().unwrap_or("und");
+    let (mut script, mut region) = (None, None);
+    for subtag in subtags {
+        match subtag.len() {
+            4 if script.is_none() && region.is_none() => script = Some(subtag),
+            2 if region.is_none() => region = Some(subtag),
+            _ => {}
+        }
+    }
+
+    // Fontconfig distinguishes Chinese orthographies by territory rather than by script.
+    if primary_subtag == "zh" {
+        let region = match (script, region) {
+            (_, Some(region @ ("cn" | "sg" | "tw" | "hk" | "mo"))) => region,
+            (Some("hant"), _) => "tw",
+            _ => "cn",
+        };
+        return format!("zh-{}", region);
+    }
+
+    match region {
+        Some(region) => format!("{}-{}", primary_subtag, region),
+        None => primary_subtag.to_owned(),
+    }
+}
+
+// Reads a string from the `org.gnome.desktop.interface` settings schema, if `gsettings` is
+// installed and the key is set.
+//
+// Each key is read once per process, since running `gsettings` takes a while.
+fn gnome_interface_setting(key: &'static str) -> Option<String> {
+    static SETTINGS: Mutex<Vec<(&str, Option<String>)>> = Mutex::new(Vec::new());
+    let mut settings = SETTINGS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some((_, value)) = settings.iter().find(|&&(cached_key, _)| cached_key == key) {
+        return value.clone();
+    }
+    let value = read_gnome_interface_setting(key);
+    settings.push((key, value.clone()));
+    value
+}
+
+fn read_gnome_interface_setting(key: &str) -> Option<String> {
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?;
+    let value = value.trim().trim_matches('\'').trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_owned())
+    }
+}
+
 // A minimal fontconfig wrapper.
 mod fc {
     #![allow(dead_code)]
@@ -633,3 +1014,30 @@ mod fc {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{fontconfig_language, split_font_index};
+
+    #[test]
+    fn convert_language_tags() {
+        assert_eq!(fontconfig_language("ja"), "ja");
+        assert_eq!(fontconfig_language("en-US"), "en-us");
+        assert_eq!(fontconfig_language("pt_BR"), "pt-br");
+        assert_eq!(fontconfig_language("sr-Latn-RS"), "sr-rs");
+        assert_eq!(fontconfig_language("zh"), "zh-cn");
+        assert_eq!(fontconfig_language("zh-Hans"), "zh-cn");
+        assert_eq!(fontconfig_language("zh-Hant"), "zh-tw");
+        assert_eq!(fontconfig_language("zh-Hant-HK"), "zh-hk");
+        assert_eq!(fontconfig_language("zh-SG"), "zh-sg");
+    }
+
+    #[test]
+    fn split_named_instance_indices() {
+        assert_eq!(split_font_index(0), Some((0, None)));
+        assert_eq!(split_font_index(3), Some((3, None)));
+        assert_eq!(split_font_index(0x1_0000), Some((0, Some(0))));
+        assert_eq!(split_font_index(0x5_0002), Some((2, Some(4))));
+        assert_eq!(split_font_index(-1), None);
+    }
+}
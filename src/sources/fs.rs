@@ -16,6 +16,7 @@
 
 use std::any::Any;
 use std::fs::File;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
@@ -25,6 +26,13 @@ use dirs;
 use std::ffi::OsString;
 #[cfg(target_family = "windows")]
 use std::os::windows::ffi::OsStringExt;
+#[cfg(not(any(
+    target_os = "android",
+    target_family = "windows",
+    target_os = "macos",
+    target_env = "ohos"
+)))]
+use std::{collections::HashSet, env, ffi::OsString, fs};
 #[cfg(target_family = "windows")]
 use winapi::shared::minwindef::{MAX_PATH, UINT};
 #[cfg(target_family = "windows")]
@@ -36,9 +44,11 @@ use crate::family_name::FamilyName;
 use crate::file_type::FileType;
 use crate::font::Font;
 use crate::handle::Handle;
-use crate::properties::Properties;
-use crate::source::Source;
+use crate::properties::{Properties, PropertiesQuery};
+use crate::source::{MatchQuality, Selection, Source, UiFontKind};
 use crate::sources::mem::MemSource;
+#[cfg(feature = "notify")]
+use crate::sources::watch::{self, FontWatcher};
 
 /// A source that loads fonts from a directory or directories on disk.
 ///
@@ -62,10 +72,39 @@ impl FsSource {
     /// Do not rely on this function for systems other than Android or OpenHarmony. It makes a best
     /// effort to locate fonts in the typical platform directories, but it is too simple to pick up
     /// fonts that are stored in unusual locations but nevertheless properly installed.
+    ///
+    /// On Linux and other Unix-like systems, the directories are scanned in this order:
+    ///
+    /// 1. `fonts` in `$XDG_DATA_HOME`, or in `~/.local/share` if that is unset.
+    ///
+    /// 2. `fonts` in each directory in `$XDG_DATA_DIRS`, or in `/usr/local/share` and then
+    ///    `/usr/share` if that is unset.
+    ///
+    /// 3. The directories named by `<dir>` elements in `$FONTCONFIG_FILE`, or in
+    ///    `/etc/fonts/fonts.conf` if that is unset, in the order they appear. This reads the file
+    ///    directly, without the Fontconfig library, and doesn't follow `<include>` elements.
+    ///
+    /// 4. The legacy `~/.fonts` and `~/local/share/fonts` directories, followed by the host's font
+    ///    directories under `/var/run/host` when running in a Flatpak sandbox. The sandbox is
+    ///    detected by `$FLATPAK_ID` being set, so the host directories are skipped elsewhere.
+    ///
+    /// Relative paths in the XDG variables are ignored, as the XDG Base Directory Specification
+    /// requires. A directory listed more than once is scanned only at its first position. Use
+    /// `with_directories()` to choose the directories explicitly instead.
     pub fn new() -> FsSource {
+        FsSource::with_directories(default_font_directories())
+    }
+
+    /// Indexes the fonts found in each of `directories`, in order, instead of the platform's
+    /// default directories.
+    pub fn with_directories<I, P>(directories: I) -> FsSource
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
         let mut fonts = vec![];
-        for font_directory in default_font_directories() {
-            fonts.extend(Self::discover_fonts(&font_directory));
+        for font_directory in directories {
+            fonts.extend(Self::discover_fonts(font_directory.as_ref()));
         }
 
         FsSource {
@@ -126,8 +165,8 @@ impl FsSource {
 
     /// Selects a font by PostScript name, which should be a unique identifier.
     ///
-    /// This implementation does a brute-force search of installed fonts to find the one that
-    /// matches.
+    /// The first lookup by name builds an index of the fonts found in the scanned directories, so
+    /// repeated lookups don't search them again.
     pub fn select_by_postscript_name(
         &self,
         postscript_name: &str,
@@ -135,6 +174,15 @@ impl FsSource {
         self.mem_source.select_by_postscript_name(postscript_name)
     }
 
+    /// Selects a font by its full name, such as "Inconsolata Bold".
+    ///
+    /// Like `select_by_postscript_name()`, this is answered from an index built on the first
+    /// lookup by name.
+    #[inline]
+    pub fn select_by_full_name(&self, full_name: &str) -> Result<Handle, SelectionError> {
+        self.mem_source.select_by_full_name(full_name)
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -145,6 +193,146 @@ impl FsSource {
     ) -> Result<Handle, SelectionError> {
         <Self as Source>::select_best_match(self, family_names, properties)
     }
+
+    /// Resolves a CSS `font-family` list, returning the best match for `properties` in the first
+    /// available family.
+    #[inline]
+    pub fn select_family_from_list(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_family_from_list(self, family_names, properties)
+    }
+
+    /// Performs font matching like `select_best_match()`, also returning the variation axis values
+    /// that instance the selected font closest to `properties`.
+    #[inline]
+    pub fn select_best_match_with_variations(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Selection, SelectionError> {
+        <Self as Source>::select_best_match_with_variations(self, family_names, properties)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification, considering only
+    /// the properties specified in `query`, and returns the handle.
+    #[inline]
+    pub fn select_best_match_for_query(
+        &self,
+        family_names: &[FamilyName],
+        query: &PropertiesQuery,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_query(self, family_names, query)
+    }
+
+    /// Finds the font in this source that is the same as, or most similar to, `font`, along with
+    /// how closely it matches.
+    #[inline]
+    pub fn find_matching(&self, font: &Font) -> Option<(Handle, MatchQuality)> {
+        <Self as Source>::find_matching(self, font)
+    }
+
+    /// Splits `text` into runs that can each be drawn with a single font, starting from `primary`
+    /// and falling back to other fonts only for the characters it lacks.
+    #[inline]
+    pub fn analyze_string_coverage(
+        &self,
+        text: &str,
+        primary: &Font,
+        properties: &Properties,
+        locale: &str,
+    ) -> Vec<(Range<usize>, Handle)> {
+        <Self as Source>::analyze_string_coverage(self, text, primary, properties, locale)
+    }
+
+    /// Calls `callback` on a background thread whenever fonts are installed in or removed from the
+    /// system font directories, until the returned watcher is dropped.
+    ///
+    /// This source only reflects the fonts that were installed when it was created, so create a
+    /// new one in `callback` to pick up the changes.
+    ///
+    /// Fails if the operating system's file change notifications can't be set up.
+    ///
+    /// This is only available with the Cargo feature `notify`.
+    #[cfg(feature = "notify")]
+    pub fn watch<F>(&self, callback: F) -> Result<FontWatcher, SelectionError>
+    where
+        F: Fn() + Send + 'static,
+    {
+        watch::watch_system_fonts(callback)
+    }
+
+    /// Looks up the family for a family name, choosing the family appropriate to `language` if
+    /// the name is generic.
+    #[inline]
+    pub fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        <Self as Source>::select_family_by_generic_name_for_language(self, family_name, language)
+    }
+
+    /// Performs font matching like `select_best_match()`, resolving generic family names to the
+    /// families appropriate to `language`, a BCP 47 language tag.
+    #[inline]
+    pub fn select_best_match_for_language(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        language: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_language(self, family_names, properties, language)
+    }
+
+    /// Returns a font that can draw `character`, for use when the font the text asked for can't.
+    #[inline]
+    pub fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_fallback_for_character(self, character, properties, locale)
+    }
+
+    /// Returns one monospace font from each family that contains monospace fonts.
+    #[inline]
+    pub fn select_monospace_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        <Self as Source>::select_monospace_fonts(self)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font(&self, properties: &Properties) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    ///
+    /// This is the same as `default_ui_font()`.
+    #[inline]
+    pub fn select_default_ui_font(
+        &self,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for the given kind of user interface text, picking
+    /// the face in its family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font_of_kind(self, kind, properties)
+    }
 }
 
 impl Source for FsSource {
@@ -178,12 +366,12 @@ impl Source for FsSource {
 }
 
 #[cfg(any(target_os = "android", target_env = "ohos"))]
-fn default_font_directories() -> Vec<PathBuf> {
+pub(crate) fn default_font_directories() -> Vec<PathBuf> {
     vec![PathBuf::from("/system/fonts")]
 }
 
 #[cfg(target_family = "windows")]
-fn default_font_directories() -> Vec<PathBuf> {
+pub(crate) fn default_font_directories() -> Vec<PathBuf> {
     unsafe {
         let mut buffer = vec![0; MAX_PATH];
         let len = sysinfoapi::GetWindowsDirectoryW(buffer.as_mut_ptr(), buffer.len() as UINT);
@@ -197,7 +385,7 @@ fn default_font_directories() -> Vec<PathBuf> {
 }
 
 #[cfg(target_os = "macos")]
-fn default_font_directories() -> Vec<PathBuf> {
+pub(crate) fn default_font_directories() -> Vec<PathBuf> {
     let mut directories = vec![
         PathBuf::from("/System/Library/Fonts"),
         PathBuf::from("/Library/Fonts"),
@@ -217,20 +405,248 @@ fn default_font_directories() -> Vec<PathBuf> {
     target_os = "macos",
     target_env = "ohos"
 )))]
-fn default_font_directories() -> Vec<PathBuf> {
-    let mut directories = vec![
-        PathBuf::from("/usr/share/fonts"),
-        PathBuf::from("/usr/local/share/fonts"),
-        PathBuf::from("/var/run/host/usr/share/fonts"), // Flatpak specific
-        PathBuf::from("/var/run/host/usr/local/share/fonts"),
-    ];
-    if let Some(path) = dirs::home_dir() {
+pub(crate) fn default_font_directories() -> Vec<PathBuf> {
+    font_directories(|name| env::var_os(name), dirs::home_dir())
+}
+
+// Lists the font directories given the environment variables that `var` looks up and the user's
+// home directory, in the order documented on `FsSource::new()`.
+#[cfg(not(any(
+    target_os = "android",
+    target_family = "windows",
+    target_os = "macos",
+    target_env = "ohos"
+)))]
+fn font_directories<V>(var: V, home_dir: Option<PathBuf>) -> Vec<PathBuf>
+where
+    V: Fn(&str) -> Option<OsString>,
+{
+    // The XDG Base Directory Specification says to ignore relative paths.
+    let absolute_path = |path: PathBuf| Some(path).filter(|path| path.is_absolute());
+
+    let data_home = var("XDG_DATA_HOME")
+        .and_then(|path| absolute_path(PathBuf::from(path)))
+        .or_else(|| Some(home_dir.as_ref()?.join(".local").join("share")));
+    let data_dirs: Vec<PathBuf> = match var("XDG_DATA_DIRS") {
+        Some(paths) if !paths.is_empty() => {
+            env::split_paths(&paths).filter_map(absolute_path).collect()
+        }
+        _ => vec![
+            PathBuf::from("/usr/local/share"),
+            PathBuf::from("/usr/share"),
+        ],
+    };
+
+    let mut directories = vec![];
+    directories.extend(data_home.iter().map(|path| path.join("fonts")));
+    directories.extend(data_dirs.iter().map(|path| path.join("fonts")));
+
+    let fontconfig_file = var("FONTCONFIG_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/etc/fonts/fonts.conf"));
+    if let Ok(config) = fs::read_to_string(&fontconfig_file) {
+        directories.extend(fonts_conf_directories(
+            &config,
+            home_dir.as_deref(),
+            data_home.as_deref(),
+        ));
+    }
+
+    if let Some(ref path) = home_dir {
         directories.push(path.join(".fonts")); // ~/.fonts is deprecated
         directories.push(path.join("local").join("share").join("fonts")); // Flatpak specific
     }
-    if let Some(mut path) = dirs::data_dir() {
-        path.push("fonts");
-        directories.push(path);
+    if var("FLATPAK_ID").is_some() {
+        directories.push(PathBuf::from("/var/run/host/usr/share/fonts"));
+        directories.push(PathBuf::from("/var/run/host/usr/local/share/fonts"));
+    }
+
+    let mut seen = HashSet::new();
+    directories.retain(|directory| seen.insert(directory.clone()));
+    directories
+}
+
+// Extracts the directories named by `<dir>` elements in the text of a `fonts.conf` file, in the
+// order they appear. Included files aren't followed, and relative paths without a `prefix` are
+// skipped, since they depend on the working directory.
+#[cfg(not(any(
+    target_os = "android",
+    target_family = "windows",
+    target_os = "macos",
+    target_env = "ohos"
+)))]
+fn fonts_conf_directories(
+    config: &str,
+    home_dir: Option<&Path>,
+    data_home: Option<&Path>,
+) -> Vec<PathBuf> {
+    let mut directories = vec![];
+    let mut rest = config;
+    while let Some(start) = rest.find("<dir") {
+        rest = &rest[start + 4..];
+        // Skip elements like `<dirs>` whose names only start with `dir`.
+        let tag_end = match rest.find('>') {
+            Some(tag_end) if rest.starts_with(['>', ' ', '\t', '\n', '\r']) => tag_end,
+            _ => continue,
+        };
+        let attributes = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+        if attributes.ends_with('/') {
+            continue;
+        }
+        let end = match rest.find("</dir>") {
+            Some(end) => end,
+            None => break,
+        };
+        let path = unescape_xml(rest[..end].trim());
+        rest = &rest[end..];
+
+        let directory = if attributes.contains("prefix=\"xdg\"") {
+            data_home.map(|data_home| data_home.join(&path))
+        } else if let Some(path) = path.strip_prefix('~') {
+            home_dir.map(|home_dir| home_dir.join(path.trim_start_matches('/')))
+        } else {
+            Some(PathBuf::from(path)).filter(|path| path.is_absolute())
+        };
+        directories.extend(directory);
     }
     directories
 }
+
+#[cfg(not(any(
+    target_os = "android",
+    target_family = "windows",
+    target_os = "macos",
+    target_env = "ohos"
+)))]
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(all(
+    test,
+    not(any(
+        target_os = "android",
+        target_family = "windows",
+        target_os = "macos",
+        target_env = "ohos"
+    ))
+))]
+mod test {
+    use super::{font_directories, FsSource};
+    use std::env;
+    use std::ffi::OsString;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+
+    #[test]
+    fn font_directory_precedence() {
+        let directory = env::temp_dir().join(format!("font-kit-fonts-conf-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let fontconfig_file = directory.join("fonts.conf");
+        fs::write(
+            &fontconfig_file,
+            r#"<?xml version="1.0"?>
+<fontconfig>
+    <dir>/opt/fonts</dir>
+    <dir prefix="xdg">custom-fonts</dir>
+    <dir>~/.fonts</dir>
+    <dir>/data/share/fonts</dir>
+    <dir>relative/fonts</dir>
+    <include ignore_missing="yes">conf.d</include>
+</fontconfig>
+"#,
+        )
+        .unwrap();
+
+        let var = |name: &str| match name {
+            "XDG_DATA_HOME" => Some(OsString::from("/data/home")),
+            "XDG_DATA_DIRS" => Some(OsString::from("/data/share:relative/share:/opt/share")),
+            "FONTCONFIG_FILE" => Some(fontconfig_file.clone().into_os_string()),
+            _ => None,
+        };
+        let directories = font_directories(var, Some(PathBuf::from("/home/user")));
+        fs::remove_dir_all(&directory).unwrap();
+
+        let expected = [
+            "/data/home/fonts",
+            "/data/share/fonts",
+            "/opt/share/fonts",
+            "/opt/fonts",
+            "/data/home/custom-fonts",
+            "/home/user/.fonts",
+            "/home/user/local/share/fonts",
+        ];
+        assert_eq!(
+            directories,
+            expected.iter().map(PathBuf::from).collect::<Vec<_>>()
+        );
+
+        // Unset variables fall back to the defaults in the specification.
+        let directories = font_directories(|_| None, Some(PathBuf::from("/home/user")));
+        assert_eq!(
+            directories[0],
+            PathBuf::from("/home/user/.local/share/fonts")
+        );
+        assert_eq!(directories[1], PathBuf::from("/usr/local/share/fonts"));
+        assert_eq!(directories[2], PathBuf::from("/usr/share/fonts"));
+    }
+
+    // The host's fonts are only visible under `/var/run/host` inside a Flatpak sandbox.
+    #[test]
+    fn flatpak_host_font_directories() {
+        let host_directories = [
+            PathBuf::from("/var/run/host/usr/share/fonts"),
+            PathBuf::from("/var/run/host/usr/local/share/fonts"),
+        ];
+        let var = |name: &str| match name {
+            "FONTCONFIG_FILE" => Some(OsString::from("/nonexistent/fonts.conf")),
+            _ => None,
+        };
+        let directories = font_directories(var, None);
+        assert!(!directories
+            .iter()
+            .any(|directory| host_directories.contains(directory)));
+
+        let var = |name: &str| match name {
+            "FONTCONFIG_FILE" => Some(OsString::from("/nonexistent/fonts.conf")),
+            "FLATPAK_ID" => Some(OsString::from("org.example.App")),
+            _ => None,
+        };
+        let directories = font_directories(var, None);
+        assert!(directories.ends_with(&host_directories));
+    }
+
+    #[test]
+    fn discover_fonts_in_xdg_data_home() {
+        let directory = env::temp_dir().join(format!("font-kit-xdg-{}", process::id()));
+        let font_directory = directory.join("fonts");
+        fs::create_dir_all(&font_directory).unwrap();
+        fs::copy(
+            "resources/tests/inconsolata/Inconsolata-Regular.ttf",
+            font_directory.join("Inconsolata-Regular.ttf"),
+        )
+        .unwrap();
+
+        let var = |name: &str| match name {
+            "XDG_DATA_HOME" => Some(directory.clone().into_os_string()),
+            "XDG_DATA_DIRS" => Some(directory.join("system").into_os_string()),
+            "FONTCONFIG_FILE" => Some(directory.join("fonts.conf").into_os_string()),
+            _ => None,
+        };
+        let directories = font_directories(var, None);
+        assert_eq!(
+            directories,
+            [font_directory, directory.join("system/fonts")]
+        );
+
+        let source = FsSource::with_directories(&directories);
+        assert_eq!(source.all_families().unwrap(), ["Inconsolata"]);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
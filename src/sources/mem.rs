@@ -15,20 +15,28 @@ use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
 use crate::font::Font;
 use crate::handle::Handle;
-use crate::properties::Properties;
-use crate::source::Source;
+use crate::properties::{Properties, PropertiesQuery};
+use crate::source::{MatchQuality, Selection, Source, UiFontKind};
 use std::any::Any;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
 
 /// A source that keeps fonts in memory.
 #[allow(missing_debug_implementations)]
 pub struct MemSource {
     families: Vec<FamilyEntry>,
+    // Built on the first lookup by name and discarded whenever fonts are added.
+    name_index: OnceLock<NameIndex>,
 }
 
 impl MemSource {
     /// Creates a new empty memory source.
     pub fn empty() -> MemSource {
-        MemSource { families: vec![] }
+        MemSource {
+            families: vec![],
+            name_index: OnceLock::new(),
+        }
     }
 
     /// Creates a new memory source that contains the given set of font handles.
@@ -43,7 +51,10 @@ impl MemSource {
             add_font(handle, &mut families)?;
         }
         families.sort_by(|a, b| a.family_name.cmp(&b.family_name));
-        Ok(MemSource { families })
+        Ok(MemSource {
+            families,
+            name_index: OnceLock::new(),
+        })
     }
 
     /// Add an existing font handle to a `MemSource`.
@@ -57,6 +68,7 @@ impl MemSource {
         let font = add_font(handle, &mut self.families)?;
         self.families
             .sort_by(|a, b| a.family_name.cmp(&b.family_name));
+        self.name_index.take();
         Ok(font)
     }
 
@@ -69,6 +81,8 @@ impl MemSource {
         &mut self,
         handles: impl Iterator<Item = Handle>,
     ) -> Result<(), FontLoadingError> {
+        // Fonts added before an error are kept, so the index must be discarded either way.
+        self.name_index.take();
         for handle in handles {
             add_font(handle, &mut self.families)?;
         }
@@ -129,20 +143,36 @@ impl MemSource {
 
     /// Selects a font by PostScript name, which should be a unique identifier.
     ///
-    /// The default implementation, which is used by the DirectWrite and the filesystem backends,
-    /// does a brute-force search of installed fonts to find the one that matches.
+    /// The first lookup by name builds an index of the fonts in this source, which makes later
+    /// lookups take constant time. Adding fonts discards the index. If several fonts share a
+    /// PostScript name, the first one in family order is returned.
     pub fn select_by_postscript_name(
         &self,
         postscript_name: &str,
     ) -> Result<Handle, SelectionError> {
-        self.families
-            .iter()
-            .filter(|family_entry| family_entry.postscript_name == postscript_name)
-            .map(|family_entry| family_entry.font.clone())
-            .next()
+        self.name_index()
+            .postscript_names
+            .get(postscript_name)
+            .map(|&index| self.families[index].font.clone())
+            .ok_or(SelectionError::NotFound)
+    }
+
+    /// Selects a font by its full name, such as "Inconsolata Bold".
+    ///
+    /// This uses the same index as `select_by_postscript_name()`.
+    pub fn select_by_full_name(&self, full_name: &str) -> Result<Handle, SelectionError> {
+        self.name_index()
+            .full_names
+            .get(full_name)
+            .map(|&index| self.families[index].font.clone())
             .ok_or(SelectionError::NotFound)
     }
 
+    fn name_index(&self) -> &NameIndex {
+        self.name_index
+            .get_or_init(|| NameIndex::new(&self.families))
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -153,6 +183,129 @@ impl MemSource {
     ) -> Result<Handle, SelectionError> {
         <Self as Source>::select_best_match(self, family_names, properties)
     }
+
+    /// Resolves a CSS `font-family` list, returning the best match for `properties` in the first
+    /// available family.
+    #[inline]
+    pub fn select_family_from_list(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_family_from_list(self, family_names, properties)
+    }
+
+    /// Performs font matching like `select_best_match()`, also returning the variation axis values
+    /// that instance the selected font closest to `properties`.
+    #[inline]
+    pub fn select_best_match_with_variations(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Selection, SelectionError> {
+        <Self as Source>::select_best_match_with_variations(self, family_names, properties)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification, considering only
+    /// the properties specified in `query`, and returns the handle.
+    #[inline]
+    pub fn select_best_match_for_query(
+        &self,
+        family_names: &[FamilyName],
+        query: &PropertiesQuery,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_query(self, family_names, query)
+    }
+
+    /// Finds the font in this source that is the same as, or most similar to, `font`, along with
+    /// how closely it matches.
+    #[inline]
+    pub fn find_matching(&self, font: &Font) -> Option<(Handle, MatchQuality)> {
+        <Self as Source>::find_matching(self, font)
+    }
+
+    /// Splits `text` into runs that can each be drawn with a single font, starting from `primary`
+    /// and falling back to other fonts only for the characters it lacks.
+    #[inline]
+    pub fn analyze_string_coverage(
+        &self,
+        text: &str,
+        primary: &Font,
+        properties: &Properties,
+        locale: &str,
+    ) -> Vec<(Range<usize>, Handle)> {
+        <Self as Source>::analyze_string_coverage(self, text, primary, properties, locale)
+    }
+
+    /// Looks up the family for a family name, choosing the family appropriate to `language` if
+    /// the name is generic.
+    #[inline]
+    pub fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        <Self as Source>::select_family_by_generic_name_for_language(self, family_name, language)
+    }
+
+    /// Performs font matching like `select_best_match()`, resolving generic family names to the
+    /// families appropriate to `language`, a BCP 47 language tag.
+    #[inline]
+    pub fn select_best_match_for_language(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        language: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_language(self, family_names, properties, language)
+    }
+
+    /// Returns a font that can draw `character`, for use when the font the text asked for can't.
+    #[inline]
+    pub fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_fallback_for_character(self, character, properties, locale)
+    }
+
+    /// Returns one monospace font from each family that contains monospace fonts.
+    #[inline]
+    pub fn select_monospace_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        <Self as Source>::select_monospace_fonts(self)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font(&self, properties: &Properties) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    ///
+    /// This is the same as `default_ui_font()`.
+    #[inline]
+    pub fn select_default_ui_font(
+        &self,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for the given kind of user interface text, picking
+    /// the face in its family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font_of_kind(self, kind, properties)
+    }
 }
 
 impl Source for MemSource {
@@ -192,6 +345,7 @@ fn add_font(handle: Handle, families: &mut Vec<FamilyEntry>) -> Result<Font, Fon
         families.push(FamilyEntry {
             family_name: font.family_name(),
             postscript_name,
+            full_name: font.full_name(),
             font: handle,
         })
     }
@@ -201,5 +355,33 @@ fn add_font(handle: Handle, families: &mut Vec<FamilyEntry>) -> Result<Font, Fon
 struct FamilyEntry {
     family_name: String,
     postscript_name: String,
+    full_name: String,
     font: Handle,
 }
+
+// Maps names to positions in `MemSource::families`.
+struct NameIndex {
+    postscript_names: HashMap<String, usize>,
+    full_names: HashMap<String, usize>,
+}
+
+impl NameIndex {
+    fn new(families: &[FamilyEntry]) -> NameIndex {
+        let mut index = NameIndex {
+            postscript_names: HashMap::with_capacity(families.len()),
+            full_names: HashMap::with_capacity(families.len()),
+        };
+        // Keep the first font with each name, as a linear search would find.
+        for (position, family_entry) in families.iter().enumerate() {
+            index
+                .postscript_names
+                .entry(family_entry.postscript_name.clone())
+                .or_insert(position);
+            index
+                .full_names
+                .entry(family_entry.full_name.clone())
+                .or_insert(position);
+        }
+        index
+    }
+}
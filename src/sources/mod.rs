@@ -12,14 +12,17 @@
 //!
 //! The system-specific sources (Core Text, DirectWrite, and Fontconfig) contain the fonts that are
 //! installed on the system. The remaining databases (`fs`, `mem`, and `multi`) allow `font-kit` to
-//! query fonts not installed on the system.
+//! query fonts not installed on the system, and `fallback_cache` speeds up fallback queries on any
+//! of them.
 
-#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "coretext"))]
 pub mod core_text;
 
-#[cfg(target_family = "windows")]
+#[cfg(all(target_family = "windows", feature = "directwrite"))]
 pub mod directwrite;
 
+pub mod fallback_cache;
+
 #[cfg(any(
     not(any(
         target_os = "macos",
@@ -38,3 +41,6 @@ pub mod fs;
 pub mod mem;
 
 pub mod multi;
+
+#[cfg(all(feature = "notify", not(target_arch = "wasm32")))]
+pub mod watch;
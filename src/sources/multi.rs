@@ -16,13 +16,14 @@
 use crate::error::SelectionError;
 use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
+use crate::font::Font;
 use crate::handle::Handle;
-use crate::properties::Properties;
-use crate::source::Source;
+use crate::properties::{Properties, PropertiesQuery};
+use crate::source::{MatchQuality, Selection, Source, UiFontKind};
 use std::{
     any::Any,
     fmt,
-    ops::{Index, IndexMut},
+    ops::{Index, IndexMut, Range},
     slice,
 };
 
@@ -45,7 +46,7 @@ impl MultiSource {
     pub fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
         let mut handles = vec![];
         for subsource in &self.subsources {
-            handles.extend(subsource.all_fonts()?.into_iter())
+            handles.extend(subsource.all_fonts()?)
         }
         Ok(handles)
     }
@@ -54,7 +55,7 @@ impl MultiSource {
     pub fn all_families(&self) -> Result<Vec<String>, SelectionError> {
         let mut families = vec![];
         for subsource in &self.subsources {
-            families.extend(subsource.all_families()?.into_iter())
+            families.extend(subsource.all_families()?)
         }
         Ok(families)
     }
@@ -86,6 +87,22 @@ impl MultiSource {
         Err(SelectionError::NotFound)
     }
 
+    /// Selects a font by PostScript name like `select_by_postscript_name()`, also returning the
+    /// variation axis values of the named instance that the name refers to, if any.
+    pub fn select_by_postscript_name_with_variations(
+        &self,
+        postscript_name: &str,
+    ) -> Result<Selection, SelectionError> {
+        for subsource in &self.subsources {
+            match subsource.select_by_postscript_name_with_variations(postscript_name) {
+                Ok(selection) => return Ok(selection),
+                Err(SelectionError::NotFound) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Err(SelectionError::NotFound)
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -97,6 +114,129 @@ impl MultiSource {
         <Self as Source>::select_best_match(self, family_names, properties)
     }
 
+    /// Resolves a CSS `font-family` list, returning the best match for `properties` in the first
+    /// available family.
+    #[inline]
+    pub fn select_family_from_list(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_family_from_list(self, family_names, properties)
+    }
+
+    /// Performs font matching like `select_best_match()`, also returning the variation axis values
+    /// that instance the selected font closest to `properties`.
+    #[inline]
+    pub fn select_best_match_with_variations(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Selection, SelectionError> {
+        <Self as Source>::select_best_match_with_variations(self, family_names, properties)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification, considering only
+    /// the properties specified in `query`, and returns the handle.
+    #[inline]
+    pub fn select_best_match_for_query(
+        &self,
+        family_names: &[FamilyName],
+        query: &PropertiesQuery,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_query(self, family_names, query)
+    }
+
+    /// Finds the font in this source that is the same as, or most similar to, `font`, along with
+    /// how closely it matches.
+    #[inline]
+    pub fn find_matching(&self, font: &Font) -> Option<(Handle, MatchQuality)> {
+        <Self as Source>::find_matching(self, font)
+    }
+
+    /// Splits `text` into runs that can each be drawn with a single font, starting from `primary`
+    /// and falling back to other fonts only for the characters it lacks.
+    #[inline]
+    pub fn analyze_string_coverage(
+        &self,
+        text: &str,
+        primary: &Font,
+        properties: &Properties,
+        locale: &str,
+    ) -> Vec<(Range<usize>, Handle)> {
+        <Self as Source>::analyze_string_coverage(self, text, primary, properties, locale)
+    }
+
+    /// Looks up the family for a family name, choosing the family appropriate to `language` if
+    /// the name is generic.
+    #[inline]
+    pub fn select_family_by_generic_name_for_language(
+        &self,
+        family_name: &FamilyName,
+        language: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        <Self as Source>::select_family_by_generic_name_for_language(self, family_name, language)
+    }
+
+    /// Performs font matching like `select_best_match()`, resolving generic family names to the
+    /// families appropriate to `language`, a BCP 47 language tag.
+    #[inline]
+    pub fn select_best_match_for_language(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        language: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match_for_language(self, family_names, properties, language)
+    }
+
+    /// Returns a font that can draw `character`, for use when the font the text asked for can't.
+    #[inline]
+    pub fn select_fallback_for_character(
+        &self,
+        character: char,
+        properties: &Properties,
+        locale: &str,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_fallback_for_character(self, character, properties, locale)
+    }
+
+    /// Returns one monospace font from each family that contains monospace fonts.
+    #[inline]
+    pub fn select_monospace_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        <Self as Source>::select_monospace_fonts(self)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font(&self, properties: &Properties) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for user interface text, picking the face in its
+    /// family that best matches `properties`.
+    ///
+    /// This is the same as `default_ui_font()`.
+    #[inline]
+    pub fn select_default_ui_font(
+        &self,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_default_ui_font(self, properties)
+    }
+
+    /// Returns the font that the system uses for the given kind of user interface text, picking
+    /// the face in its family that best matches `properties`.
+    #[inline]
+    pub fn default_ui_font_of_kind(
+        &self,
+        kind: UiFontKind,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::default_ui_font_of_kind(self, kind, properties)
+    }
+
     /// Returns an iterator over the contained sources.
     #[inline]
     pub fn iter(&self) -> MultiIter<'_> {
@@ -145,6 +285,13 @@ impl Source for MultiSource {
         self.select_by_postscript_name(postscript_name)
     }
 
+    fn select_by_postscript_name_with_variations(
+        &self,
+        postscript_name: &str,
+    ) -> Result<Selection, SelectionError> {
+        self.select_by_postscript_name_with_variations(postscript_name)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -0,0 +1,125 @@
+// font-kit/src/sources/watch.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Notification of changes to the set of installed fonts.
+//!
+//! This module is only available with the Cargo feature `notify`.
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fmt::{self, Debug, Formatter};
+use std::path::PathBuf;
+
+use crate::error::SelectionError;
+use crate::sources::fs;
+
+/// Watches the system font directories for fonts being installed or removed.
+///
+/// Returned by the `watch()` method of the system sources. The watcher stops when this value is
+/// dropped.
+pub struct FontWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl Debug for FontWatcher {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("FontWatcher").finish_non_exhaustive()
+    }
+}
+
+/// Calls `callback` on a background thread whenever the contents of `directories` change.
+///
+/// This uses the operating system's file change notifications, so nothing is rescanned while
+/// the directories stay the same. Directories that don't exist are skipped.
+pub(crate) fn watch_directories<F>(
+    directories: Vec<PathBuf>,
+    callback: F,
+) -> Result<FontWatcher, SelectionError>
+where
+    F: Fn() + Send + 'static,
+{
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if let Ok(event) = event {
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                callback();
+            }
+        }
+    })
+    .map_err(watch_error)?;
+    for directory in directories.iter().filter(|directory| directory.is_dir()) {
+        watcher
+            .watch(directory, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+    }
+    Ok(FontWatcher { _watcher: watcher })
+}
+
+/// Calls `callback` on a background thread whenever fonts are installed or removed in the
+/// platform's font directories.
+pub(crate) fn watch_system_fonts<F>(callback: F) -> Result<FontWatcher, SelectionError>
+where
+    F: Fn() + Send + 'static,
+{
+    watch_directories(system_font_directories(), callback)
+}
+
+#[cfg(target_family = "windows")]
+fn system_font_directories() -> Vec<PathBuf> {
+    let mut directories = fs::default_font_directories();
+    // Fonts installed without administrator rights go here.
+    if let Some(local_app_data) = std::env::var_os("LOCALAPPDATA") {
+        let mut path = PathBuf::from(local_app_data);
+        path.extend(["Microsoft", "Windows", "Fonts"]);
+        directories.push(path);
+    }
+    directories
+}
+
+#[cfg(not(target_family = "windows"))]
+fn system_font_directories() -> Vec<PathBuf> {
+    fs::default_font_directories()
+}
+
+fn watch_error(error: notify::Error) -> SelectionError {
+    SelectionError::CannotAccessSource {
+        reason: Some(error.to_string().into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::watch_directories;
+    use std::env;
+    use std::fs;
+    use std::process;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn notify_when_font_added() {
+        let directory = env::temp_dir().join(format!("font-kit-watch-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher = watch_directories(vec![directory.clone()], move || {
+            let _ = sender.send(());
+        })
+        .unwrap();
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+
+        fs::write(directory.join("NewFont.ttf"), b"").unwrap();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        drop(watcher);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
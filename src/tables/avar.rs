@@ -0,0 +1,167 @@
+// font-kit/src/tables/avar.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The axis variations table, which remaps default-normalized coordinates nonlinearly.
+//!
+//! See: https://docs.microsoft.com/en-us/typography/opentype/spec/avar
+
+use super::{fvar, tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"avar");
+
+/// The piecewise-linear segment map for a single axis, as (from, to) pairs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SegmentMap(pub(crate) Vec<(f32, f32)>);
+
+impl SegmentMap {
+    /// Applies the mapping to a default-normalized coordinate.
+    pub(crate) fn map(&self, value: f32) -> f32 {
+        Self::interpolate(self.0.iter().cloned(), value)
+    }
+
+    /// Applies the inverse mapping, taking an `avar`-mapped coordinate back to a
+    /// default-normalized one.
+    pub(crate) fn unmap(&self, value: f32) -> f32 {
+        Self::interpolate(self.0.iter().map(|&(from, to)| (to, from)), value)
+    }
+
+    fn interpolate<I>(pairs: I, value: f32) -> f32
+    where
+        I: Iterator<Item = (f32, f32)> + Clone,
+    {
+        let mut previous: Option<(f32, f32)> = None;
+        for (from, to) in pairs.clone() {
+            if value == from {
+                return to;
+            }
+            if value < from {
+                return match previous {
+                    // Values outside the map are passed through unchanged, shifted so the map
+                    // stays continuous.
+                    None => value + (to - from),
+                    Some((previous_from, previous_to)) => {
+                        previous_to
+                            + (value - previous_from) * (to - previous_to) / (from - previous_from)
+                    }
+                };
+            }
+            previous = Some((from, to));
+        }
+        match previous {
+            None => value,
+            Some((from, to)) => value + (to - from),
+        }
+    }
+}
+
+/// Returns the segment maps for each axis, in `fvar` axis order.
+pub(crate) fn segment_maps(table: &[u8]) -> Option<Vec<SegmentMap>> {
+    let table = TableData(table);
+    if table.u16(0)? != 1 {
+        return None;
+    }
+    let axis_count = table.u16(6)? as usize;
+    let mut offset = 8;
+    let mut maps = Vec::with_capacity(axis_count);
+    for _ in 0..axis_count {
+        let pair_count = table.u16(offset)? as usize;
+        offset += 2;
+        let mut pairs = Vec::with_capacity(pair_count);
+        for _ in 0..pair_count {
+            pairs.push((table.f2dot14(offset)?, table.f2dot14(offset + 2)?));
+            offset += 4;
+        }
+        maps.push(SegmentMap(pairs));
+    }
+    Some(maps)
+}
+
+/// Converts a user-space axis value to a normalized coordinate, applying the `avar` segment map
+/// for the axis if there is one.
+///
+/// This is the coordinate that variation data in the font is interpolated with, so two user values
+/// that map to the same normalized coordinate produce identical instances.
+pub(crate) fn normalize(axis: &fvar::Axis, segment_map: Option<&SegmentMap>, value: f32) -> f32 {
+    let normalized = axis.normalize(value);
+    let mapped = match segment_map {
+        Some(segment_map) => segment_map.map(normalized),
+        None => normalized,
+    };
+    // Coordinates are stored as 2.14 fixed point, so quantize the same way the rasterizers do.
+    (mapped * 16384.0).round() / 16384.0
+}
+
+/// The inverse of `normalize()`.
+pub(crate) fn denormalize(axis: &fvar::Axis, segment_map: Option<&SegmentMap>, value: f32) -> f32 {
+    let unmapped = match segment_map {
+        Some(segment_map) => segment_map.unmap(value),
+        None => value,
+    };
+    axis.denormalize(unmapped)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{denormalize, normalize, segment_maps, SegmentMap};
+    use crate::tables::fvar::{self, test as fvar_test};
+    use crate::tables::tag;
+
+    /// Builds an `avar` table from per-axis (from, to) pairs.
+    pub(crate) fn synthesize_table(maps: &[&[(f32, f32)]]) -> Vec<u8> {
+        let mut table = vec![];
+        for value in [1u16, 0, 0, maps.len() as u16] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        for pairs in maps {
+            table.extend_from_slice(&(pairs.len() as u16).to_be_bytes());
+            for &(from, to) in pairs.iter() {
+                table.extend_from_slice(&((from * 16384.0) as i16).to_be_bytes());
+                table.extend_from_slice(&((to * 16384.0) as i16).to_be_bytes());
+            }
+        }
+        table
+    }
+
+    #[test]
+    fn normalize_with_segment_map() {
+        let fvar_table = fvar_test::synthesize_table(&[(tag(b"wght"), 100.0, 400.0, 900.0)]);
+        let axis = fvar::axes(&fvar_table).unwrap()[0];
+        let avar_table = synthesize_table(&[&[
+            (-1.0, -1.0),
+            (-0.5, -0.25),
+            (0.0, 0.0),
+            (0.5, 0.75),
+            (1.0, 1.0),
+        ]]);
+        let maps = segment_maps(&avar_table).unwrap();
+        let map = Some(&maps[0]);
+
+        // Segment boundaries map exactly.
+        assert_eq!(normalize(&axis, map, 100.0), -1.0);
+        assert_eq!(normalize(&axis, map, 250.0), -0.25);
+        assert_eq!(normalize(&axis, map, 400.0), 0.0);
+        assert_eq!(normalize(&axis, map, 650.0), 0.75);
+        assert_eq!(normalize(&axis, map, 900.0), 1.0);
+
+        // Mid-segment values are interpolated: 525 is halfway between 400 and 650.
+        assert_eq!(normalize(&axis, map, 525.0), 0.375);
+        assert_eq!(normalize(&axis, None, 525.0), 0.25);
+
+        assert_eq!(denormalize(&axis, map, 0.75), 650.0);
+        assert_eq!(denormalize(&axis, map, 0.375), 525.0);
+    }
+
+    #[test]
+    fn empty_segment_map_is_identity() {
+        let map = SegmentMap::default();
+        assert_eq!(map.map(0.3), 0.3);
+        assert_eq!(map.unmap(-0.3), -0.3);
+    }
+}
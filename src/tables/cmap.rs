@@ -0,0 +1,463 @@
+// font-kit/src/tables/cmap.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The character-to-glyph index mapping table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/cmap
+
+use super::{tag, TableData};
+use std::collections::BTreeMap;
+
+pub(crate) const TAG: u32 = tag(b"cmap");
+
+const PLATFORM_UNICODE: u16 = 0;
+const PLATFORM_WINDOWS: u16 = 3;
+
+const UNICODE_ENCODING_FULL: u16 = 4;
+const UNICODE_ENCODING_FULL_REPERTOIRE: u16 = 6;
+const WINDOWS_ENCODING_SYMBOL: u16 = 0;
+const WINDOWS_ENCODING_UNICODE_BMP: u16 = 1;
+const WINDOWS_ENCODING_UNICODE_FULL: u16 = 10;
+
+const MAX_CODE_POINT: u32 = 0x10ffff;
+
+/// Returns every code point that the best Unicode subtable maps to a glyph other than `.notdef`,
+/// paired with its glyph ID and sorted by code point.
+///
+/// Unlike `Cursor`, this reads every range before sorting, so ranges that are out of order, as
+/// some fonts ship them, still contribute all of their code points; where ranges overlap, the one
+/// listed first wins. Surrogate code points are kept. See `Cursor::new()` for how the subtable is
+/// chosen. Returns `None` if the table has no usable subtable or the subtable is truncated.
+pub(crate) fn mappings(table: &[u8]) -> Option<Vec<(u32, u32)>> {
+    let cursor = Cursor::new(table)?;
+    let subtable = TableData(table.get(cursor.subtable_offset..)?);
+    // The code points that earlier ranges cover, as disjoint ranges keyed by their start. Only the
+    // parts of each range outside them are read, so every code point is visited at most once, no
+    // matter how many ranges overlap it.
+    let mut covered: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut mappings = vec![];
+    for range_index in 0..cursor.range_count {
+        let (range_start, range_end) = cursor.range(subtable, range_index)?;
+        if range_start > range_end {
+            continue;
+        }
+        let overlapping: Vec<(u32, u32)> = covered
+            .range(..=range_end)
+            .rev()
+            .take_while(|&(_, &end)| end >= range_start)
+            .map(|(&start, &end)| (start, end))
+            .collect();
+        let (mut merged_start, mut merged_end) = (range_start, range_end);
+        let mut uncovered = vec![];
+        let mut next_code_point = range_start;
+        for &(start, end) in overlapping.iter().rev() {
+            covered.remove(&start);
+            if start > next_code_point {
+                uncovered.push(next_code_point..=(start - 1));
+            }
+            next_code_point = next_code_point.max(end + 1);
+            merged_start = merged_start.min(start);
+            merged_end = merged_end.max(end);
+        }
+        if next_code_point <= range_end {
+            uncovered.push(next_code_point..=range_end);
+        }
+        covered.insert(merged_start, merged_end);
+
+        for code_point in uncovered.into_iter().flatten() {
+            match cursor.glyph_id(subtable, range_index, range_start, code_point)? {
+                0 => {}
+                glyph_id => mappings.push((code_point, glyph_id)),
+            }
+        }
+    }
+    mappings.sort_unstable_by_key(|&(code_point, _)| code_point);
+    Some(mappings)
+}
+
+/// A position in the best Unicode subtable of a `cmap` table, which visits its mappings lazily,
+/// one character at a time, so that even subtables with huge ranges are walked without
+/// allocating.
+///
+/// Subtables covering all of Unicode are preferred over those limited to the Basic Multilingual
+/// Plane. Formats 4, 6, 12, and 13 are supported; subtables in other formats are skipped.
+///
+/// Characters are returned in ascending order, which relies on the segments or groups of the
+/// subtable being sorted as the specification requires. A range that starts below a character
+/// already returned only contributes the characters past it. Surrogates and code points mapped
+/// to `.notdef` are skipped, and a malformed subtable ends the walk at the first bad range.
+#[derive(Clone)]
+pub(crate) struct Cursor {
+    subtable_offset: usize,
+    format: u16,
+    range_count: usize,
+    // The next segment or group to walk once the current one is done.
+    next_range_index: usize,
+    // The start of the current segment or group, and the next code point in it.
+    range_start: u32,
+    code_point: u32,
+    range_end: u32,
+    // The last character returned, to keep the order ascending.
+    last_code_point: Option<u32>,
+}
+
+impl Cursor {
+    /// Positions a cursor before the first mapping of the best supported Unicode subtable.
+    pub(crate) fn new(table: &[u8]) -> Option<Cursor> {
+        let table = TableData(table);
+        let subtable_count = table.u16(2)? as usize;
+
+        let mut subtables = vec![];
+        for record_index in 0..subtable_count {
+            let record_offset = 4 + record_index * 8;
+            let platform_id = table.u16(record_offset)?;
+            let encoding_id = table.u16(record_offset + 2)?;
+            let rank = match (platform_id, encoding_id) {
+                (PLATFORM_WINDOWS, WINDOWS_ENCODING_UNICODE_FULL) => 0,
+                (PLATFORM_UNICODE, UNICODE_ENCODING_FULL)
+                | (PLATFORM_UNICODE, UNICODE_ENCODING_FULL_REPERTOIRE) => 1,
+                (PLATFORM_WINDOWS, WINDOWS_ENCODING_UNICODE_BMP) => 2,
+                (PLATFORM_UNICODE, _) => 3,
+                (PLATFORM_WINDOWS, WINDOWS_ENCODING_SYMBOL) => 4,
+                _ => continue,
+            };
+            subtables.push((rank, table.u32(record_offset + 4)? as usize));
+        }
+        subtables.sort_by_key(|&(rank, _)| rank);
+
+        subtables.into_iter().find_map(|(_, subtable_offset)| {
+            let format = table.u16(subtable_offset)?;
+            let range_count = match format {
+                4 => table.u16(subtable_offset + 6)? as usize / 2,
+                6 => (table.u16(subtable_offset + 8)? > 0) as usize,
+                12 | 13 => table.u32(subtable_offset + 12)? as usize,
+                _ => return None,
+            };
+            Some(Cursor {
+                subtable_offset,
+                format,
+                range_count,
+                next_range_index: 0,
+                range_start: 0,
+                code_point: 1,
+                range_end: 0,
+                last_code_point: None,
+            })
+        })
+    }
+
+    /// Returns the next character that the subtable maps to a glyph, with its glyph ID.
+    pub(crate) fn next(&mut self, table: &[u8]) -> Option<(char, u32)> {
+        let subtable = TableData(table.get(self.subtable_offset..)?);
+        loop {
+            if self.code_point > self.range_end {
+                if self.next_range_index >= self.range_count {
+                    return None;
+                }
+                match self.range(subtable, self.next_range_index) {
+                    Some((start, end)) => {
+                        self.range_start = start;
+                        self.code_point = start;
+                        self.range_end = end;
+                        self.next_range_index += 1;
+                    }
+                    None => {
+                        self.next_range_index = self.range_count;
+                        return None;
+                    }
+                }
+                if let Some(last_code_point) = self.last_code_point {
+                    self.code_point = self.code_point.max(last_code_point + 1);
+                }
+                continue;
+            }
+
+            let code_point = self.code_point;
+            self.code_point += 1;
+            let range_index = self.next_range_index - 1;
+            let glyph_id = match self.glyph_id(subtable, range_index, self.range_start, code_point)
+            {
+                Some(glyph_id) => glyph_id,
+                None => {
+                    self.next_range_index = self.range_count;
+                    self.code_point = self.range_end + 1;
+                    return None;
+                }
+            };
+            if glyph_id == 0 {
+                continue;
+            }
+            if let Some(character) = char::from_u32(code_point) {
+                self.last_code_point = Some(code_point);
+                return Some((character, glyph_id));
+            }
+        }
+    }
+
+    // Returns the first and last code points of a segment or group. Ranges past the end of
+    // Unicode are clipped, which can leave them empty.
+    fn range(&self, subtable: TableData, range_index: usize) -> Option<(u32, u32)> {
+        match self.format {
+            4 => {
+                let end_code = subtable.u16(14 + range_index * 2)? as u32;
+                let start_codes_offset = 16 + self.range_count * 2;
+                let start_code = subtable.u16(start_codes_offset + range_index * 2)? as u32;
+                // The final segment maps only U+FFFF, which is not a character.
+                Some((start_code, end_code.min(0xfffe)))
+            }
+            6 => {
+                let first_code = subtable.u16(6)? as u32;
+                let entry_count = subtable.u16(8)? as u32;
+                Some((first_code, first_code + entry_count - 1))
+            }
+            _ => {
+                let group_offset = 16 + range_index * 12;
+                let start_char_code = subtable.u32(group_offset)?;
+                let end_char_code = subtable.u32(group_offset + 4)?.min(MAX_CODE_POINT);
+                Some((start_char_code, end_char_code))
+            }
+        }
+    }
+
+    // Returns the glyph that the range at `range_index`, which starts at `range_start`, maps
+    // `code_point` to, or `None` if the subtable is truncated.
+    fn glyph_id(
+        &self,
+        subtable: TableData,
+        range_index: usize,
+        range_start: u32,
+        code_point: u32,
+    ) -> Option<u32> {
+        let offset = code_point - range_start;
+        match self.format {
+            // Segment mapping to delta values.
+            4 => {
+                let segment = range_index;
+                let id_deltas_offset = 16 + self.range_count * 4;
+                let id_delta = subtable.u16(id_deltas_offset + segment * 2)?;
+                let id_range_offset_offset = id_deltas_offset + self.range_count * 2 + segment * 2;
+                let id_range_offset = subtable.u16(id_range_offset_offset)? as usize;
+                let glyph_id = if id_range_offset == 0 {
+                    (code_point as u16).wrapping_add(id_delta)
+                } else {
+                    let glyph_id_offset =
+                        id_range_offset_offset + id_range_offset + offset as usize * 2;
+                    match subtable.u16(glyph_id_offset)? {
+                        0 => 0,
+                        glyph_id => glyph_id.wrapping_add(id_delta),
+                    }
+                };
+                Some(glyph_id as u32)
+            }
+            // Trimmed table mapping.
+            6 => subtable.u16(10 + offset as usize * 2).map(u32::from),
+            // Segmented coverage, where each group maps to consecutive glyphs.
+            12 => {
+                let start_glyph_id = subtable.u32(16 + range_index * 12 + 8)?;
+                start_glyph_id.checked_add(offset)
+            }
+            // Many-to-one range mappings, where each group maps to a single glyph.
+            _ => subtable.u32(16 + range_index * 12 + 8),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{mappings, Cursor};
+    use std::iter;
+
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    fn synthesize_table(subtables: &[(u16, u16, Vec<u8>)]) -> Vec<u8> {
+        let mut table = u16s(&[0, subtables.len() as u16]);
+        let mut offset = 4 + 8 * subtables.len();
+        for (platform_id, encoding_id, subtable) in subtables {
+            table.extend(u16s(&[*platform_id, *encoding_id]));
+            table.extend(u32s(&[offset as u32]));
+            offset += subtable.len();
+        }
+        for (_, _, subtable) in subtables {
+            table.extend(subtable);
+        }
+        table
+    }
+
+    // Maps 'A'..='C' by delta, 'a'..='b' through the glyph ID array, and 'x' to `.notdef`.
+    fn format_4_subtable() -> Vec<u8> {
+        let mut subtable = u16s(&[4, 0, 0, 8, 4, 1, 4]);
+        subtable.extend(u16s(&[0x43, 0x62, 0x78, 0xffff, 0]));
+        subtable.extend(u16s(&[0x41, 0x61, 0x78, 0xffff]));
+        subtable.extend(u16s(&[(-0x40i16) as u16, 5, (-0x78i16) as u16, 1]));
+        subtable.extend(u16s(&[0, 6, 0, 0]));
+        subtable.extend(u16s(&[10, 0]));
+        subtable
+    }
+
+    #[test]
+    fn format_4() {
+        let table = synthesize_table(&[(3, 1, format_4_subtable())]);
+        assert_eq!(
+            mappings(&table),
+            Some(vec![(0x41, 1), (0x42, 2), (0x43, 3), (0x61, 15)])
+        );
+    }
+
+    #[test]
+    fn format_6() {
+        let mut subtable = u16s(&[6, 0, 0, 0x30, 3]);
+        subtable.extend(u16s(&[7, 0, 9]));
+        let table = synthesize_table(&[(0, 3, subtable)]);
+        assert_eq!(mappings(&table), Some(vec![(0x30, 7), (0x32, 9)]));
+    }
+
+    #[test]
+    fn prefer_full_unicode_subtable() {
+        let mut format_12 = u16s(&[12, 0]);
+        format_12.extend(u32s(&[40, 0, 2, 0x41, 0x42, 20, 0x1f600, 0x1f601, 30]));
+        let table = synthesize_table(&[(3, 1, format_4_subtable()), (3, 10, format_12)]);
+        assert_eq!(
+            mappings(&table),
+            Some(vec![(0x41, 20), (0x42, 21), (0x1f600, 30), (0x1f601, 31)])
+        );
+    }
+
+    #[test]
+    fn format_13() {
+        let mut format_13 = u16s(&[13, 0]);
+        format_13.extend(u32s(&[40, 0, 2, 0x30, 0x32, 5, 0x2190, 0x2191, 0]));
+        let table = synthesize_table(&[(3, 10, format_13)]);
+        assert_eq!(
+            mappings(&table),
+            Some(vec![(0x30, 5), (0x31, 5), (0x32, 5)])
+        );
+    }
+
+    #[test]
+    fn walk_huge_ranges_lazily() {
+        // One group maps all of Unicode, including the surrogates and code points past U+10FFFF.
+        let mut format_12 = u16s(&[12, 0]);
+        format_12.extend(u32s(&[28, 0, 1, 0, u32::MAX, 1]));
+        let table = synthesize_table(&[(3, 10, format_12)]);
+        let mut cursor = Cursor::new(&table).unwrap();
+        let characters: Vec<_> = iter::from_fn(|| cursor.next(&table)).collect();
+        assert_eq!(characters.len(), 0x110000 - 0x800);
+        assert_eq!(characters[0], ('\0', 1));
+        assert_eq!(characters[0xd7ff], ('\u{d7ff}', 0xd800));
+        assert_eq!(characters[0xd800], ('\u{e000}', 0xe001));
+        assert_eq!(characters.last(), Some(&('\u{10ffff}', 0x110000)));
+    }
+
+    // Walks every mapping with a cursor, as `Loader::char_mappings()` does.
+    fn cursor_mappings(table: &[u8]) -> Vec<(u32, u32)> {
+        let mut cursor = Cursor::new(table).unwrap();
+        iter::from_fn(|| cursor.next(table))
+            .map(|(character, glyph_id)| (character as u32, glyph_id))
+            .collect()
+    }
+
+    // The second group overlaps the first and the third goes backward.
+    fn unordered_format_12_subtable() -> Vec<u8> {
+        let mut format_12 = u16s(&[12, 0]);
+        format_12.extend(u32s(&[
+            52, 0, 3, 0x41, 0x43, 1, 0x42, 0x45, 10, 0x30, 0x31, 20,
+        ]));
+        format_12
+    }
+
+    #[test]
+    fn sort_unordered_ranges() {
+        let table = synthesize_table(&[(3, 10, unordered_format_12_subtable())]);
+        assert_eq!(
+            mappings(&table),
+            Some(vec![
+                (0x30, 20),
+                (0x31, 21),
+                (0x41, 1),
+                (0x42, 2),
+                (0x43, 3),
+                (0x44, 12),
+                (0x45, 13)
+            ])
+        );
+    }
+
+    #[test]
+    fn keep_cursor_characters_ascending() {
+        let table = synthesize_table(&[(3, 10, unordered_format_12_subtable())]);
+        assert_eq!(
+            cursor_mappings(&table),
+            [(0x41, 1), (0x42, 2), (0x43, 3), (0x44, 12), (0x45, 13)]
+        );
+    }
+
+    #[test]
+    fn keep_surrogates() {
+        let mut format_12 = u16s(&[12, 0]);
+        format_12.extend(u32s(&[28, 0, 1, 0xd7ff, 0xd800, 5]));
+        let table = synthesize_table(&[(3, 10, format_12)]);
+        assert_eq!(mappings(&table), Some(vec![(0xd7ff, 5), (0xd800, 6)]));
+        assert_eq!(cursor_mappings(&table), [(0xd7ff, 5)]);
+    }
+
+    #[test]
+    fn read_overlapping_ranges_once() {
+        let group_count = 10_000;
+        let mut format_12 = u16s(&[12, 0]);
+        format_12.extend(u32s(&[16 + group_count * 12, 0, group_count]));
+        for group_index in 0..group_count {
+            format_12.extend(u32s(&[0, 0x10ffff, 1 + group_index]));
+        }
+        let table = synthesize_table(&[(3, 10, format_12)]);
+        let mappings = mappings(&table).unwrap();
+        assert_eq!(mappings.len(), 0x110000);
+        assert_eq!(mappings[0x41], (0x41, 0x42));
+        assert_eq!(mappings.last(), Some(&(0x10ffff, 0x110000)));
+    }
+
+    #[test]
+    fn reject_truncated_ranges() {
+        let table = synthesize_table(&[(3, 1, format_4_subtable())]);
+        let truncated = &table[..table.len() - 4];
+        assert_eq!(mappings(truncated), None);
+        assert_eq!(
+            cursor_mappings(truncated),
+            [(0x41, 1), (0x42, 2), (0x43, 3)]
+        );
+        let mut format_12 = u16s(&[12, 0]);
+        format_12.extend(u32s(&[40, 0, 2, 0x41, 0x42, 20, 0x1f600]));
+        let table = synthesize_table(&[(3, 10, format_12)]);
+        assert_eq!(mappings(&table), None);
+        assert_eq!(cursor_mappings(&table), [(0x41, 20), (0x42, 21)]);
+    }
+
+    #[test]
+    fn skip_unsupported_formats() {
+        let format_0 = u16s(&[0, 262, 0]);
+        let table = synthesize_table(&[(3, 10, format_0), (3, 1, format_4_subtable())]);
+        assert_eq!(mappings(&table).unwrap().len(), 4);
+        assert_eq!(
+            mappings(&synthesize_table(&[(1, 0, u16s(&[6, 0, 0, 0, 0]))])),
+            None
+        );
+    }
+}
@@ -0,0 +1,710 @@
+// font-kit/src/tables/colr.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The color table: version 0 layers of solid colors and version 1 paint graphs.
+//!
+//! Variable paints are read with their default values, since the deltas in the item variation
+//! store are not applied.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/colr
+
+use super::{tag, TableData};
+use crate::color::ColorLayer;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+use std::f32::consts::PI;
+
+pub(crate) const TAG: u32 = tag(b"COLR");
+
+// How deeply paints may nest, which also breaks cycles through `PaintColrGlyph`.
+const MAX_PAINT_DEPTH: usize = 64;
+// How many paints a single glyph may expand to, so that shared subgraphs can't blow up.
+const MAX_PAINT_COUNT: usize = 16384;
+
+/// A node of a version 1 paint graph, with layer and glyph references resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Paint {
+    /// Paints drawn bottom to top, composited with source-over.
+    Layers(Vec<Paint>),
+    Solid(PaletteColor),
+    LinearGradient {
+        color_line: ColorLine,
+        p0: Vector2F,
+        p1: Vector2F,
+        p2: Vector2F,
+    },
+    RadialGradient {
+        color_line: ColorLine,
+        c0: Vector2F,
+        r0: f32,
+        c1: Vector2F,
+        r1: f32,
+    },
+    /// Angles are in counterclockwise degrees.
+    SweepGradient {
+        color_line: ColorLine,
+        center: Vector2F,
+        start_angle: f32,
+        end_angle: f32,
+    },
+    /// Fills the outline of `glyph_id` with `paint`.
+    Glyph {
+        glyph_id: u16,
+        paint: Box<Paint>,
+    },
+    /// Draws `paint` with `transform` applied to the font-unit coordinate space.
+    Transform {
+        transform: Transform2F,
+        paint: Box<Paint>,
+    },
+    Composite {
+        source: Box<Paint>,
+        mode: CompositeMode,
+        backdrop: Box<Paint>,
+    },
+}
+
+/// An entry of the selected palette with an extra alpha multiplier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct PaletteColor {
+    pub(crate) palette_index: u16,
+    pub(crate) alpha: f32,
+}
+
+/// A color line, whose stops are sorted by offset.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ColorLine {
+    pub(crate) extend: Extend,
+    pub(crate) stops: Vec<ColorStop>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ColorStop {
+    pub(crate) offset: f32,
+    pub(crate) color: PaletteColor,
+}
+
+/// How a color line continues outside the range of its stops.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Extend {
+    Pad,
+    Repeat,
+    Reflect,
+}
+
+/// How a composite paint combines its source with its backdrop, numbered as in the table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CompositeMode {
+    Clear = 0,
+    Src = 1,
+    Dest = 2,
+    SrcOver = 3,
+    DestOver = 4,
+    SrcIn = 5,
+    DestIn = 6,
+    SrcOut = 7,
+    DestOut = 8,
+    SrcAtop = 9,
+    DestAtop = 10,
+    Xor = 11,
+    Plus = 12,
+    Screen = 13,
+    Overlay = 14,
+    Darken = 15,
+    Lighten = 16,
+    ColorDodge = 17,
+    ColorBurn = 18,
+    HardLight = 19,
+    SoftLight = 20,
+    Difference = 21,
+    Exclusion = 22,
+    Multiply = 23,
+    Hue = 24,
+    Saturation = 25,
+    Color = 26,
+    Luminosity = 27,
+}
+
+impl CompositeMode {
+    fn from_u8(value: u8) -> Option<CompositeMode> {
+        use CompositeMode::*;
+        const MODES: [CompositeMode; 28] = [
+            Clear, Src, Dest, SrcOver, DestOver, SrcIn, DestIn, SrcOut, DestOut, SrcAtop, DestAtop,
+            Xor, Plus, Screen, Overlay, Darken, Lighten, ColorDodge, ColorBurn, HardLight,
+            SoftLight, Difference, Exclusion, Multiply, Hue, Saturation, Color, Luminosity,
+        ];
+        MODES.get(value as usize).cloned()
+    }
+}
+
+/// Returns the version 0 layers of `glyph_id`, or `None` if it isn't a version 0 color glyph.
+pub(crate) fn base_glyph_layers(table: &[u8], glyph_id: u16) -> Option<Vec<ColorLayer>> {
+    let table = TableData(table);
+    let base_glyph_count = table.u16(2)? as usize;
+    let base_glyphs_offset = table.u32(4)? as usize;
+    let layers_offset = table.u32(8)? as usize;
+
+    let (mut low, mut high) = (0, base_glyph_count);
+    while low < high {
+        let mid = (low + high) / 2;
+        let record_offset = base_glyphs_offset + mid * 6;
+        let base_glyph_id = table.u16(record_offset)?;
+        if base_glyph_id < glyph_id {
+            low = mid + 1;
+        } else if base_glyph_id > glyph_id {
+            high = mid;
+        } else {
+            let first_layer = table.u16(record_offset + 2)? as usize;
+            let layer_count = table.u16(record_offset + 4)? as usize;
+            let mut layers = Vec::with_capacity(layer_count);
+            for layer_index in first_layer..(first_layer + layer_count) {
+                let layer_offset = layers_offset + layer_index * 4;
+                layers.push(ColorLayer {
+                    glyph_id: table.u16(layer_offset)? as u32,
+                    palette_index: table.u16(layer_offset + 2)?,
+                });
+            }
+            return Some(layers);
+        }
+    }
+    None
+}
+
+/// Returns the version 1 paint graph of `glyph_id`, or `None` if it isn't a version 1 color glyph
+/// or its graph is malformed, cyclic, or too large.
+pub(crate) fn base_glyph_paint(table: &[u8], glyph_id: u16) -> Option<Paint> {
+    let table = TableData(table);
+    if table.u16(0)? < 1 {
+        return None;
+    }
+    let mut parser = PaintParser {
+        table,
+        base_glyph_list_offset: table.u32(14)? as usize,
+        layer_list_offset: table.u32(18)? as usize,
+        remaining_paints: MAX_PAINT_COUNT,
+    };
+    let paint_offset = parser.base_glyph_paint_offset(glyph_id)?;
+    parser.parse(paint_offset, 0)
+}
+
+/// Returns the box in font units that the paint graph of `glyph_id` is clipped to, or `None` if
+/// the table has no clip list or the list doesn't cover the glyph.
+pub(crate) fn clip_box(table: &[u8], glyph_id: u16) -> Option<RectF> {
+    let table = TableData(table);
+    if table.u16(0)? < 1 {
+        return None;
+    }
+    let clip_list_offset = match table.u32(22)? {
+        0 => return None,
+        offset => offset as usize,
+    };
+    let clip_count = table.u32(clip_list_offset + 1)? as usize;
+
+    // The clips are sorted by glyph range, and the ranges don't overlap.
+    let (mut low, mut high) = (0, clip_count);
+    while low < high {
+        let mid = (low + high) / 2;
+        let record_offset = clip_list_offset + 5 + mid * 7;
+        if table.u16(record_offset + 2)? < glyph_id {
+            low = mid + 1;
+        } else if table.u16(record_offset)? > glyph_id {
+            high = mid;
+        } else {
+            // Both formats start with the box; the variable one has a trailing variation index.
+            let box_offset = clip_list_offset + table.u24(record_offset + 4)? as usize;
+            let (x_min, y_min) = (table.i16(box_offset + 1)?, table.i16(box_offset + 3)?);
+            let (x_max, y_max) = (table.i16(box_offset + 5)?, table.i16(box_offset + 7)?);
+            return Some(RectF::from_points(
+                Vector2F::new(x_min as f32, y_min as f32),
+                Vector2F::new(x_max as f32, y_max as f32),
+            ));
+        }
+    }
+    None
+}
+
+struct PaintParser<'a> {
+    table: TableData<'a>,
+    base_glyph_list_offset: usize,
+    layer_list_offset: usize,
+    remaining_paints: usize,
+}
+
+impl<'a> PaintParser<'a> {
+    fn base_glyph_paint_offset(&self, glyph_id: u16) -> Option<usize> {
+        if self.base_glyph_list_offset == 0 {
+            return None;
+        }
+        let list_offset = self.base_glyph_list_offset;
+        let record_count = self.table.u32(list_offset)? as usize;
+        let (mut low, mut high) = (0, record_count);
+        while low < high {
+            let mid = (low + high) / 2;
+            let record_offset = list_offset + 4 + mid * 6;
+            let base_glyph_id = self.table.u16(record_offset)?;
+            if base_glyph_id < glyph_id {
+                low = mid + 1;
+            } else if base_glyph_id > glyph_id {
+                high = mid;
+            } else {
+                return Some(list_offset + self.table.u32(record_offset + 2)? as usize);
+            }
+        }
+        None
+    }
+
+    fn parse(&mut self, offset: usize, depth: usize) -> Option<Paint> {
+        if depth > MAX_PAINT_DEPTH || self.remaining_paints == 0 {
+            return None;
+        }
+        self.remaining_paints -= 1;
+
+        let table = self.table;
+        let format = table.u8(offset)?;
+        let point = |field_offset: usize| {
+            Some(Vector2F::new(
+                table.i16(offset + field_offset)? as f32,
+                table.i16(offset + field_offset + 2)? as f32,
+            ))
+        };
+
+        let paint = match format {
+            1 => {
+                let layer_count = table.u8(offset + 1)? as usize;
+                let first_layer = table.u32(offset + 2)? as usize;
+                let mut layers = Vec::with_capacity(layer_count);
+                for layer_index in first_layer..(first_layer + layer_count) {
+                    let layer_offset = self.layer_list_offset
+                        + table.u32(self.layer_list_offset + 4 + layer_index * 4)? as usize;
+                    layers.push(self.parse(layer_offset, depth + 1)?);
+                }
+                Paint::Layers(layers)
+            }
+            2 | 3 => Paint::Solid(PaletteColor {
+                palette_index: table.u16(offset + 1)?,
+                alpha: table.f2dot14(offset + 3)?,
+            }),
+            4 | 5 => Paint::LinearGradient {
+                color_line: self.color_line(offset, format == 5)?,
+                p0: point(4)?,
+                p1: point(8)?,
+                p2: point(12)?,
+            },
+            6 | 7 => Paint::RadialGradient {
+                color_line: self.color_line(offset, format == 7)?,
+                c0: point(4)?,
+                r0: table.u16(offset + 8)? as f32,
+                c1: point(10)?,
+                r1: table.u16(offset + 14)? as f32,
+            },
+            // Sweep angles are stored biased by -180°.
+            8 | 9 => Paint::SweepGradient {
+                color_line: self.color_line(offset, format == 9)?,
+                center: point(4)?,
+                start_angle: (table.f2dot14(offset + 8)? + 1.0) * 180.0,
+                end_angle: (table.f2dot14(offset + 10)? + 1.0) * 180.0,
+            },
+            10 => Paint::Glyph {
+                glyph_id: table.u16(offset + 4)?,
+                paint: self.child(offset, 1, depth)?,
+            },
+            11 => {
+                let paint_offset = self.base_glyph_paint_offset(table.u16(offset + 1)?)?;
+                return self.parse(paint_offset, depth + 1);
+            }
+            12 | 13 => {
+                let transform_offset = offset + table.u24(offset + 4)? as usize;
+                let field = |index: usize| table.fixed(transform_offset + index * 4);
+                let (xx, yx, xy, yy) = (field(0)?, field(1)?, field(2)?, field(3)?);
+                let (dx, dy) = (field(4)?, field(5)?);
+                self.transform(
+                    offset,
+                    depth,
+                    Transform2F::row_major(xx, xy, yx, yy, dx, dy),
+                )?
+            }
+            14 | 15 => {
+                let translation = point(4)?;
+                self.transform(offset, depth, Transform2F::from_translation(translation))?
+            }
+            16..=23 => {
+                let uniform = format >= 20;
+                let scale = if uniform {
+                    Vector2F::splat(table.f2dot14(offset + 4)?)
+                } else {
+                    Vector2F::new(table.f2dot14(offset + 4)?, table.f2dot14(offset + 6)?)
+                };
+                let transform = Transform2F::from_scale(scale);
+                let transform = match format {
+                    18 | 19 => around_center(transform, point(8)?),
+                    22 | 23 => around_center(transform, point(6)?),
+                    _ => transform,
+                };
+                self.transform(offset, depth, transform)?
+            }
+            24..=27 => {
+                let angle = table.f2dot14(offset + 4)? * PI;
+                let (sin, cos) = angle.sin_cos();
+                let transform = Transform2F::row_major(cos, -sin, sin, cos, 0.0, 0.0);
+                let transform = match format {
+                    26 | 27 => around_center(transform, point(6)?),
+                    _ => transform,
+                };
+                self.transform(offset, depth, transform)?
+            }
+            28..=31 => {
+                // Positive angles skew counterclockwise.
+                let x_skew = (table.f2dot14(offset + 4)? * PI).tan();
+                let y_skew = (table.f2dot14(offset + 6)? * PI).tan();
+                let transform = Transform2F::row_major(1.0, -x_skew, y_skew, 1.0, 0.0, 0.0);
+                let transform = match format {
+                    30 | 31 => around_center(transform, point(8)?),
+                    _ => transform,
+                };
+                self.transform(offset, depth, transform)?
+            }
+            32 => {
+                let source = self.child(offset, 1, depth)?;
+                let mode = CompositeMode::from_u8(table.u8(offset + 4)?)?;
+                let backdrop = self.child(offset, 5, depth)?;
+                Paint::Composite {
+                    source,
+                    mode,
+                    backdrop,
+                }
+            }
+            _ => return None,
+        };
+        Some(paint)
+    }
+
+    // Parses the paint at the 24-bit offset stored at `field_offset` in the paint at `offset`.
+    fn child(&mut self, offset: usize, field_offset: usize, depth: usize) -> Option<Box<Paint>> {
+        let child_offset = offset + self.table.u24(offset + field_offset)? as usize;
+        self.parse(child_offset, depth + 1).map(Box::new)
+    }
+
+    fn transform(&mut self, offset: usize, depth: usize, transform: Transform2F) -> Option<Paint> {
+        Some(Paint::Transform {
+            transform,
+            paint: self.child(offset, 1, depth)?,
+        })
+    }
+
+    // Reads the color line at the 24-bit offset that immediately follows the format of a
+    // gradient paint, sorting its stops by offset. Variable color stops have a trailing variation
+    // index.
+    fn color_line(&self, offset: usize, variable: bool) -> Option<ColorLine> {
+        let table = self.table;
+        let color_line_offset = offset + table.u24(offset + 1)? as usize;
+        let extend = match table.u8(color_line_offset)? {
+            1 => Extend::Repeat,
+            2 => Extend::Reflect,
+            _ => Extend::Pad,
+        };
+        let stop_count = table.u16(color_line_offset + 1)? as usize;
+        let stop_size = if variable { 10 } else { 6 };
+        let mut stops = Vec::with_capacity(stop_count);
+        for stop_index in 0..stop_count {
+            let stop_offset = color_line_offset + 3 + stop_index * stop_size;
+            stops.push(ColorStop {
+                offset: table.f2dot14(stop_offset)?,
+                color: PaletteColor {
+                    palette_index: table.u16(stop_offset + 2)?,
+                    alpha: table.f2dot14(stop_offset + 4)?,
+                },
+            });
+        }
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Some(ColorLine { extend, stops })
+    }
+}
+
+fn around_center(transform: Transform2F, center: Vector2F) -> Transform2F {
+    Transform2F::from_translation(center) * transform * Transform2F::from_translation(-center)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{base_glyph_layers, base_glyph_paint, clip_box, ColorLine, ColorStop, Extend};
+    use super::{CompositeMode, Paint, PaletteColor};
+    use crate::color::ColorLayer;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+
+    // Assembles a table, patching offsets in once their targets are known.
+    #[derive(Default)]
+    struct Builder(Vec<u8>);
+
+    impl Builder {
+        fn pos(&self) -> usize {
+            self.0.len()
+        }
+
+        fn u8(&mut self, value: u8) -> usize {
+            self.0.push(value);
+            self.pos() - 1
+        }
+
+        fn u16(&mut self, value: u16) -> usize {
+            self.0.extend_from_slice(&value.to_be_bytes());
+            self.pos() - 2
+        }
+
+        fn u24(&mut self, value: u32) -> usize {
+            self.0.extend_from_slice(&value.to_be_bytes()[1..]);
+            self.pos() - 3
+        }
+
+        fn u32(&mut self, value: u32) -> usize {
+            self.0.extend_from_slice(&value.to_be_bytes());
+            self.pos() - 4
+        }
+
+        // Points the 24-bit field at `field` to the current position, relative to `base`.
+        fn link_u24(&mut self, field: usize, base: usize) {
+            let value = (self.pos() - base) as u32;
+            self.0[field..(field + 3)].copy_from_slice(&value.to_be_bytes()[1..]);
+        }
+
+        fn link_u32(&mut self, field: usize, base: usize) {
+            let value = (self.pos() - base) as u32;
+            self.0[field..(field + 4)].copy_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    // Glyph 3 has two version 0 layers. Glyph 5 has a version 1 paint graph: the multiply
+    // composite of a translated linear gradient clipped to glyph 1 over the layers of glyph 6,
+    // which are a translucent solid fill; its gradient's stops are stored out of order. Glyph 7
+    // refers to itself, and glyph 8 is a solid fill of the foreground color rotated around a
+    // center. Glyphs 5 and 6 share a clip box, and glyph 8 has one of its own.
+    fn synthesize_table() -> Vec<u8> {
+        let mut b = Builder::default();
+        b.u16(1);
+        b.u16(1);
+        let base_glyph_records = b.u32(0);
+        let layer_records = b.u32(0);
+        b.u16(2);
+        let base_glyph_list = b.u32(0);
+        let layer_list = b.u32(0);
+        let clip_list = b.u32(0);
+        for _ in 0..2 {
+            b.u32(0);
+        }
+
+        b.link_u32(base_glyph_records, 0);
+        for value in [3, 0, 2] {
+            b.u16(value);
+        }
+        b.link_u32(layer_records, 0);
+        for value in [1, 0, 2, 0xffff] {
+            b.u16(value);
+        }
+
+        b.link_u32(base_glyph_list, 0);
+        let list_start = b.u32(4);
+        let mut paint_fields = vec![];
+        for glyph_id in [5, 6, 7, 8] {
+            b.u16(glyph_id);
+            paint_fields.push(b.u32(0));
+        }
+
+        b.link_u32(layer_list, 0);
+        let layer_list_start = b.u32(1);
+        let layer_field = b.u32(0);
+
+        // The layer: a solid fill of palette entry 1 at half opacity.
+        b.link_u32(layer_field, layer_list_start);
+        b.u8(2);
+        b.u16(1);
+        b.u16(0x2000);
+
+        // Glyph 6: the layer list.
+        b.link_u32(paint_fields[1], list_start);
+        b.u8(1);
+        b.u8(1);
+        b.u32(0);
+
+        // Glyph 5.
+        b.link_u32(paint_fields[0], list_start);
+        let composite = b.u8(32);
+        let source_field = b.u24(0);
+        b.u8(CompositeMode::Multiply as u8);
+        let backdrop_field = b.u24(0);
+
+        b.link_u24(backdrop_field, composite);
+        b.u8(11);
+        b.u16(6);
+
+        b.link_u24(source_field, composite);
+        let translate = b.u8(14);
+        let translate_field = b.u24(0);
+        b.u16(10);
+        b.u16(-20i16 as u16);
+
+        b.link_u24(translate_field, translate);
+        let glyph = b.u8(10);
+        let glyph_field = b.u24(0);
+        b.u16(1);
+
+        b.link_u24(glyph_field, glyph);
+        let gradient = b.u8(4);
+        let color_line_field = b.u24(0);
+        for value in [0, 0, 100, 0, 0, 100] {
+            b.u16(value);
+        }
+        b.link_u24(color_line_field, gradient);
+        b.u8(2);
+        b.u16(2);
+        for (offset, palette_index) in [(0x4000, 1), (0, 0)] {
+            b.u16(offset);
+            b.u16(palette_index);
+            b.u16(0x4000);
+        }
+
+        // Glyph 7.
+        b.link_u32(paint_fields[2], list_start);
+        b.u8(11);
+        b.u16(7);
+
+        // Glyph 8: a quarter turn around (10, 10).
+        b.link_u32(paint_fields[3], list_start);
+        let rotate = b.u8(26);
+        let rotate_field = b.u24(0);
+        b.u16(0x2000);
+        b.u16(10);
+        b.u16(10);
+        b.link_u24(rotate_field, rotate);
+        b.u8(2);
+        b.u16(0xffff);
+        b.u16(0x4000);
+
+        b.link_u32(clip_list, 0);
+        let clip_list_start = b.u8(1);
+        b.u32(2);
+        let mut box_fields = vec![];
+        for (start, end) in [(5, 6), (8, 8)] {
+            b.u16(start);
+            b.u16(end);
+            box_fields.push(b.u24(0));
+        }
+        b.link_u24(box_fields[0], clip_list_start);
+        b.u8(1);
+        for value in [-10i16, -20, 110, 120] {
+            b.u16(value as u16);
+        }
+        b.link_u24(box_fields[1], clip_list_start);
+        b.u8(2);
+        for value in [0, 0, 20, 20] {
+            b.u16(value);
+        }
+        b.u32(0);
+
+        b.0
+    }
+
+    #[test]
+    fn read_version_0_layers() {
+        let table = synthesize_table();
+        assert_eq!(
+            base_glyph_layers(&table, 3),
+            Some(vec![
+                ColorLayer {
+                    glyph_id: 1,
+                    palette_index: 0
+                },
+                ColorLayer {
+                    glyph_id: 2,
+                    palette_index: 0xffff
+                },
+            ])
+        );
+        assert_eq!(base_glyph_layers(&table, 5), None);
+        assert_eq!(base_glyph_paint(&table, 3), None);
+    }
+
+    #[test]
+    fn read_version_1_paint_graph() {
+        let table = synthesize_table();
+        let solid = |palette_index, alpha| {
+            Paint::Solid(PaletteColor {
+                palette_index,
+                alpha,
+            })
+        };
+        let stop = |offset, palette_index| ColorStop {
+            offset,
+            color: PaletteColor {
+                palette_index,
+                alpha: 1.0,
+            },
+        };
+        assert_eq!(
+            base_glyph_paint(&table, 5),
+            Some(Paint::Composite {
+                source: Box::new(Paint::Transform {
+                    transform: Transform2F::from_translation(Vector2F::new(10.0, -20.0)),
+                    paint: Box::new(Paint::Glyph {
+                        glyph_id: 1,
+                        paint: Box::new(Paint::LinearGradient {
+                            color_line: ColorLine {
+                                extend: Extend::Reflect,
+                                stops: vec![stop(0.0, 0), stop(1.0, 1)],
+                            },
+                            p0: Vector2F::new(0.0, 0.0),
+                            p1: Vector2F::new(100.0, 0.0),
+                            p2: Vector2F::new(0.0, 100.0),
+                        }),
+                    }),
+                }),
+                mode: CompositeMode::Multiply,
+                backdrop: Box::new(Paint::Layers(vec![solid(1, 0.5)])),
+            })
+        );
+        assert_eq!(
+            base_glyph_paint(&table, 6),
+            Some(Paint::Layers(vec![solid(1, 0.5)]))
+        );
+
+        // Cycles are broken rather than followed forever.
+        assert_eq!(base_glyph_paint(&table, 7), None);
+
+        match base_glyph_paint(&table, 8) {
+            Some(Paint::Transform { transform, paint }) => {
+                assert_eq!(*paint, solid(0xffff, 1.0));
+                let rotated = transform * Vector2F::new(20.0, 10.0);
+                assert!((rotated - Vector2F::new(10.0, 20.0)).length() < 0.001);
+                assert_eq!(
+                    transform * Vector2F::new(10.0, 10.0),
+                    Vector2F::new(10.0, 10.0)
+                );
+            }
+            paint => panic!("unexpected paint: {:?}", paint),
+        }
+        assert_eq!(base_glyph_paint(&table, 9), None);
+    }
+
+    #[test]
+    fn read_clip_boxes() {
+        let table = synthesize_table();
+        let clip = RectF::from_points(Vector2F::new(-10.0, -20.0), Vector2F::new(110.0, 120.0));
+        assert_eq!(clip_box(&table, 5), Some(clip));
+        assert_eq!(clip_box(&table, 6), Some(clip));
+        assert_eq!(clip_box(&table, 7), None);
+        assert_eq!(
+            clip_box(&table, 8),
+            Some(RectF::from_points(Vector2F::zero(), Vector2F::splat(20.0)))
+        );
+        assert_eq!(clip_box(&table, 9), None);
+        assert_eq!(clip_box(&table, 3), None);
+    }
+}
@@ -0,0 +1,145 @@
+// font-kit/src/tables/cpal.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The color palette table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/cpal
+
+use super::{tag, TableData};
+use crate::color::ColorU;
+
+pub(crate) const TAG: u32 = tag(b"CPAL");
+
+pub(crate) const USABLE_WITH_LIGHT_BACKGROUND: u32 = 0x0001;
+pub(crate) const USABLE_WITH_DARK_BACKGROUND: u32 = 0x0002;
+
+/// Returns the colors of the palette at `palette_index`, or `None` if there is no such palette.
+pub(crate) fn palette(table: &[u8], palette_index: u16) -> Option<Vec<ColorU>> {
+    let table = TableData(table);
+    let entry_count = table.u16(2)? as usize;
+    let palette_count = table.u16(4)?;
+    if palette_index >= palette_count {
+        return None;
+    }
+    let color_records_offset = table.u32(8)? as usize;
+    let first_record_index = table.u16(12 + palette_index as usize * 2)? as usize;
+
+    let mut colors = Vec::with_capacity(entry_count);
+    for entry_index in 0..entry_count {
+        // Records are stored in BGRA order.
+        let record = table.bytes(
+            color_records_offset + (first_record_index + entry_index) * 4,
+            4,
+        )?;
+        colors.push(ColorU::new(record[2], record[1], record[0], record[3]));
+    }
+    Some(colors)
+}
+
+/// Returns the index of the first palette whose type has all of the bits in `flags` set.
+///
+/// Only version 1 tables have palette types, so this always returns `None` for version 0.
+pub(crate) fn palette_with_type(table: &[u8], flags: u32) -> Option<u16> {
+    let table = TableData(table);
+    if table.u16(0)? < 1 {
+        return None;
+    }
+    let palette_count = table.u16(4)?;
+    let types_offset = table.u32(12 + palette_count as usize * 2)? as usize;
+    if types_offset == 0 {
+        return None;
+    }
+    (0..palette_count).find(|&palette_index| {
+        table
+            .u32(types_offset + palette_index as usize * 4)
+            .is_some_and(|palette_type| palette_type & flags == flags)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{palette, palette_with_type};
+    use super::{USABLE_WITH_DARK_BACKGROUND, USABLE_WITH_LIGHT_BACKGROUND};
+    use crate::color::ColorU;
+
+    #[test]
+    fn read_palettes() {
+        let mut table = vec![];
+        // Two entries, two palettes sharing three records, starting at records 0 and 1.
+        for value in [0u16, 2, 2, 3] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        table.extend_from_slice(&16u32.to_be_bytes());
+        for value in [0u16, 1] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        table.extend_from_slice(&[
+            0x00, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00, 0x80, 0x00, 0xff, 0x00, 0xff,
+        ]);
+
+        assert_eq!(
+            palette(&table, 0),
+            Some(vec![
+                ColorU::new(0xff, 0x00, 0x00, 0xff),
+                ColorU::new(0x00, 0x00, 0xff, 0x80)
+            ])
+        );
+        assert_eq!(
+            palette(&table, 1),
+            Some(vec![
+                ColorU::new(0x00, 0x00, 0xff, 0x80),
+                ColorU::new(0x00, 0xff, 0x00, 0xff)
+            ])
+        );
+        assert_eq!(palette(&table, 2), None);
+        assert_eq!(palette(&table[..24], 1), None);
+        assert_eq!(palette_with_type(&table, USABLE_WITH_DARK_BACKGROUND), None);
+    }
+
+    #[test]
+    fn find_palettes_by_type() {
+        let mut table = vec![];
+        // Version 1, one entry, three palettes of one record each, with types at 30.
+        for value in [1u16, 1, 3, 3] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        table.extend_from_slice(&42u32.to_be_bytes());
+        for value in [0u16, 1, 2] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        for value in [30u32, 0, 0] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        for value in [0u32, 1, 2] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        table.extend_from_slice(&[0; 12]);
+
+        assert_eq!(
+            palette_with_type(&table, USABLE_WITH_LIGHT_BACKGROUND),
+            Some(1)
+        );
+        assert_eq!(
+            palette_with_type(&table, USABLE_WITH_DARK_BACKGROUND),
+            Some(2)
+        );
+        assert_eq!(
+            palette_with_type(
+                &table,
+                USABLE_WITH_LIGHT_BACKGROUND | USABLE_WITH_DARK_BACKGROUND
+            ),
+            None
+        );
+        assert_eq!(
+            palette_with_type(&table[..36], USABLE_WITH_DARK_BACKGROUND),
+            None
+        );
+    }
+}
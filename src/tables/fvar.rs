@@ -0,0 +1,191 @@
+// font-kit/src/tables/fvar.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The font variations table.
+//!
+//! See: https://docs.microsoft.com/en-us/typography/opentype/spec/fvar
+
+use super::{tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"fvar");
+
+/// The registered weight axis.
+pub(crate) const WEIGHT_AXIS: u32 = tag(b"wght");
+/// The registered width axis, in percent of the normal width.
+pub(crate) const WIDTH_AXIS: u32 = tag(b"wdth");
+
+const AXIS_RECORD_SIZE: usize = 20;
+
+/// A variation axis, with values in user (design) units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Axis {
+    pub(crate) tag: u32,
+    pub(crate) min_value: f32,
+    pub(crate) default_value: f32,
+    pub(crate) max_value: f32,
+    pub(crate) flags: u16,
+    pub(crate) name_id: u16,
+}
+
+impl Axis {
+    /// Maps a user-space value to the default-normalized range -1..1, before any `avar` mapping.
+    pub(crate) fn normalize(&self, user_value: f32) -> f32 {
+        let value = user_value.max(self.min_value).min(self.max_value);
+        if value < self.default_value && self.default_value > self.min_value {
+            (value - self.default_value) / (self.default_value - self.min_value)
+        } else if value > self.default_value && self.max_value > self.default_value {
+            (value - self.default_value) / (self.max_value - self.default_value)
+        } else {
+            0.0
+        }
+    }
+
+    /// The inverse of `normalize()`.
+    pub(crate) fn denormalize(&self, normalized_value: f32) -> f32 {
+        let value = normalized_value.clamp(-1.0, 1.0);
+        if value < 0.0 {
+            self.default_value + value * (self.default_value - self.min_value)
+        } else {
+            self.default_value + value * (self.max_value - self.default_value)
+        }
+    }
+}
+
+/// Returns the variation axes of the font, in the order the font lists them.
+pub(crate) fn axes(table: &[u8]) -> Option<Vec<Axis>> {
+    let table = TableData(table);
+    if table.u16(0)? != 1 {
+        return None;
+    }
+    let axes_offset = table.u16(4)? as usize;
+    let axis_count = table.u16(8)? as usize;
+    let axis_size = table.u16(10)? as usize;
+    if axis_size < AXIS_RECORD_SIZE {
+        return None;
+    }
+
+    let mut axes = Vec::with_capacity(axis_count);
+    for axis_index in 0..axis_count {
+        let offset = axes_offset + axis_index * axis_size;
+        axes.push(Axis {
+            tag: table.u32(offset)?,
+            min_value: table.fixed(offset + 4)?,
+            default_value: table.fixed(offset + 8)?,
+            max_value: table.fixed(offset + 12)?,
+            flags: table.u16(offset + 16)?,
+            name_id: table.u16(offset + 18)?,
+        });
+    }
+    Some(axes)
+}
+
+/// Returns the coordinates of the named instance at `instance_index` as `(tag, value)` pairs in
+/// user units, in the order the font lists its axes, or `None` if there is no such instance.
+pub(crate) fn instance_coordinates(table: &[u8], instance_index: u16) -> Option<Vec<(u32, f32)>> {
+    let axes = axes(table)?;
+    let table = TableData(table);
+    let axes_offset = table.u16(4)? as usize;
+    let axis_size = table.u16(10)? as usize;
+    let instance_count = table.u16(12)?;
+    let instance_size = table.u16(14)? as usize;
+    // Each record starts with a name ID and flags, followed by a coordinate for every axis.
+    if instance_index >= instance_count || instance_size < 4 + axes.len() * 4 {
+        return None;
+    }
+    let instance_offset =
+        axes_offset + axes.len() * axis_size + instance_index as usize * instance_size;
+    axes.iter()
+        .enumerate()
+        .map(|(axis_index, axis)| {
+            let value = table.fixed(instance_offset + 4 + axis_index * 4)?;
+            Some((axis.tag, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{axes, instance_coordinates, Axis};
+    use crate::tables::tag;
+
+    /// Builds an `fvar` table with the given axes and no named instances.
+    pub(crate) fn synthesize_table(axes: &[(u32, f32, f32, f32)]) -> Vec<u8> {
+        let mut table = vec![];
+        for value in [1u16, 0, 16, 2, axes.len() as u16, 20, 0, 0] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        for &(tag, min_value, default_value, max_value) in axes {
+            table.extend_from_slice(&tag.to_be_bytes());
+            for value in [min_value, default_value, max_value] {
+                table.extend_from_slice(&((value * 65536.0) as i32).to_be_bytes());
+            }
+            table.extend_from_slice(&0u16.to_be_bytes());
+            table.extend_from_slice(&256u16.to_be_bytes());
+        }
+        table
+    }
+
+    #[test]
+    fn parse_instances() {
+        let mut table = synthesize_table(&[
+            (tag(b"wght"), 100.0, 400.0, 900.0),
+            (tag(b"wdth"), 75.0, 100.0, 100.0),
+        ]);
+        // One instance, whose records have a trailing PostScript name ID.
+        table[12..14].copy_from_slice(&1u16.to_be_bytes());
+        table[14..16].copy_from_slice(&14u16.to_be_bytes());
+        table.extend_from_slice(&[0x01, 0x02, 0, 0]);
+        for value in [700.0f32, 75.0] {
+            table.extend_from_slice(&((value * 65536.0) as i32).to_be_bytes());
+        }
+        table.extend_from_slice(&[0x01, 0x03]);
+
+        assert_eq!(
+            instance_coordinates(&table, 0),
+            Some(vec![(tag(b"wght"), 700.0), (tag(b"wdth"), 75.0)])
+        );
+        assert_eq!(instance_coordinates(&table, 1), None);
+        assert_eq!(instance_coordinates(&table[..60], 0), None);
+    }
+
+    #[test]
+    fn parse_axes() {
+        let table = synthesize_table(&[
+            (tag(b"wght"), 100.0, 400.0, 900.0),
+            (tag(b"wdth"), 75.0, 100.0, 100.0),
+        ]);
+        let axes = axes(&table).unwrap();
+        assert_eq!(axes.len(), 2);
+        assert_eq!(
+            axes[0],
+            Axis {
+                tag: tag(b"wght"),
+                min_value: 100.0,
+                default_value: 400.0,
+                max_value: 900.0,
+                flags: 0,
+                name_id: 256,
+            }
+        );
+
+        let weight = axes[0];
+        assert_eq!(weight.normalize(100.0), -1.0);
+        assert_eq!(weight.normalize(250.0), -0.5);
+        assert_eq!(weight.normalize(400.0), 0.0);
+        assert_eq!(weight.normalize(650.0), 0.5);
+        assert_eq!(weight.normalize(1000.0), 1.0);
+        assert_eq!(weight.denormalize(0.5), 650.0);
+
+        // An axis whose default is its maximum can only go negative.
+        let width = axes[1];
+        assert_eq!(width.normalize(87.5), -0.5);
+        assert_eq!(width.normalize(120.0), 0.0);
+    }
+}
@@ -0,0 +1,113 @@
+// font-kit/src/tables/gasp.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The grid-fitting and scan-conversion procedure table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/gasp
+
+use super::{tag, TableData};
+use crate::hinting::GaspBehavior;
+
+pub(crate) const TAG: u32 = tag(b"gasp");
+
+const GASP_GRIDFIT: u16 = 0x0001;
+const GASP_DOGRAY: u16 = 0x0002;
+const GASP_SYMMETRIC_GRIDFIT: u16 = 0x0004;
+const GASP_SYMMETRIC_SMOOTHING: u16 = 0x0008;
+
+/// Returns the behavior of the first range whose upper limit is at least `pixels_per_em`.
+///
+/// Returns `None` if the table is malformed or no range covers the size. The symmetric flags are
+/// only defined in version 1 tables, so they are ignored in version 0 ones.
+pub(crate) fn behavior(table: &[u8], pixels_per_em: u16) -> Option<GaspBehavior> {
+    let table = TableData(table);
+    let version = table.u16(0)?;
+    let range_count = table.u16(2)? as usize;
+    for range_index in 0..range_count {
+        let range_offset = 4 + range_index * 4;
+        if table.u16(range_offset)? < pixels_per_em {
+            continue;
+        }
+        let mut flags = table.u16(range_offset + 2)?;
+        if version == 0 {
+            flags &= GASP_GRIDFIT | GASP_DOGRAY;
+        }
+        return Some(GaspBehavior {
+            grid_fit: flags & GASP_GRIDFIT != 0,
+            grayscale: flags & GASP_DOGRAY != 0,
+            symmetric_grid_fit: flags & GASP_SYMMETRIC_GRIDFIT != 0,
+            symmetric_smoothing: flags & GASP_SYMMETRIC_SMOOTHING != 0,
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::behavior;
+    use crate::hinting::GaspBehavior;
+
+    fn synthesize_table(version: u16, ranges: &[(u16, u16)]) -> Vec<u8> {
+        let mut table = vec![];
+        table.extend_from_slice(&version.to_be_bytes());
+        table.extend_from_slice(&(ranges.len() as u16).to_be_bytes());
+        for &(max_pixels_per_em, flags) in ranges {
+            table.extend_from_slice(&max_pixels_per_em.to_be_bytes());
+            table.extend_from_slice(&flags.to_be_bytes());
+        }
+        table
+    }
+
+    #[test]
+    fn decode_ranges() {
+        let table = synthesize_table(1, &[(8, 0x2), (16, 0x5), (0xffff, 0xf)]);
+        let smoothed = GaspBehavior {
+            grid_fit: false,
+            grayscale: true,
+            symmetric_grid_fit: false,
+            symmetric_smoothing: false,
+        };
+        assert_eq!(behavior(&table, 0), Some(smoothed));
+        assert_eq!(behavior(&table, 8), Some(smoothed));
+        assert_eq!(
+            behavior(&table, 9),
+            Some(GaspBehavior {
+                grid_fit: true,
+                grayscale: false,
+                symmetric_grid_fit: true,
+                symmetric_smoothing: false,
+            })
+        );
+        assert_eq!(
+            behavior(&table, 200),
+            Some(GaspBehavior {
+                grid_fit: true,
+                grayscale: true,
+                symmetric_grid_fit: true,
+                symmetric_smoothing: true,
+            })
+        );
+    }
+
+    #[test]
+    fn ignore_symmetric_flags_in_version_0() {
+        let table = synthesize_table(0, &[(0xffff, 0xf)]);
+        let behavior = behavior(&table, 12).unwrap();
+        assert!(behavior.grid_fit && behavior.grayscale);
+        assert!(!behavior.symmetric_grid_fit && !behavior.symmetric_smoothing);
+    }
+
+    #[test]
+    fn uncovered_size() {
+        let table = synthesize_table(1, &[(8, 0x3)]);
+        assert_eq!(behavior(&table, 9), None);
+        assert_eq!(behavior(&table[..6], 4), None);
+    }
+}
@@ -0,0 +1,725 @@
+// font-kit/src/tables/glyf.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The TrueType glyph data table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/glyf
+
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
+use pathfinder_geometry::vector::Vector2F;
+use std::iter;
+
+use super::{tag, TableData};
+use crate::error::GlyphLoadingError;
+use crate::outline::{Component, Contour, PointFlags};
+
+pub(crate) const TAG: u32 = tag(b"glyf");
+
+/// The nesting depth of composite glyphs allowed when the `maxp` table doesn't specify one. This
+/// also caps the depth that a font may claim, which bounds the recursion when flattening.
+pub(crate) const MAX_COMPONENT_DEPTH: u16 = 16;
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+const ROUND_XY_TO_GRID: u16 = 0x0004;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+const USE_MY_METRICS: u16 = 0x0200;
+const SCALED_COMPONENT_OFFSET: u16 = 0x0800;
+
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const REPEAT_FLAG: u8 = 0x08;
+const X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+
+/// Returns the length in bytes of the hinting instructions of a glyph, given its data from the
+/// `glyf` table.
+///
+/// Composite glyphs only have instructions if their last component says so. Empty glyphs have
+/// none. Returns `None` for malformed data.
+pub(crate) fn instruction_length(glyph: &[u8]) -> Option<usize> {
+    if glyph.is_empty() {
+        return Some(0);
+    }
+    let glyph = TableData(glyph);
+    let contour_count = glyph.i16(0)?;
+    if contour_count >= 0 {
+        return glyph.u16(10 + contour_count as usize * 2).map(usize::from);
+    }
+
+    let mut offset = 10;
+    loop {
+        let flags = glyph.u16(offset)?;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            8
+        } else {
+            6
+        };
+        offset += if flags & WE_HAVE_A_SCALE != 0 {
+            2
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            if flags & WE_HAVE_INSTRUCTIONS == 0 {
+                return Some(0);
+            }
+            return glyph.u16(offset).map(usize::from);
+        }
+    }
+}
+
+/// Returns the contours of a simple glyph, given its data from the `glyf` table, with the points
+/// in font units exactly as stored.
+///
+/// On-curve points have no flags, and off-curve points are marked `CONTROL_POINT_0`. Returns an
+/// empty list for empty glyphs, and `None` for composite glyphs and malformed data.
+pub(crate) fn simple_contours(glyph: &[u8]) -> Option<Vec<Contour>> {
+    if glyph.is_empty() {
+        return Some(vec![]);
+    }
+    let glyph = TableData(glyph);
+    let contour_count = glyph.i16(0)?;
+    if contour_count < 0 {
+        return None;
+    }
+    let contour_count = contour_count as usize;
+
+    let mut end_points = Vec::with_capacity(contour_count);
+    for contour_index in 0..contour_count {
+        end_points.push(glyph.u16(10 + contour_index * 2)? as usize);
+    }
+    let point_count = match end_points.last() {
+        Some(&last_end_point) => last_end_point + 1,
+        None => return Some(vec![]),
+    };
+
+    let instruction_length = glyph.u16(10 + contour_count * 2)? as usize;
+    let mut offset = 12 + contour_count * 2 + instruction_length;
+    let mut flags = Vec::with_capacity(point_count);
+    while flags.len() < point_count {
+        let flag = glyph.u8(offset)?;
+        offset += 1;
+        let repeat_count = if flag & REPEAT_FLAG != 0 {
+            offset += 1;
+            glyph.u8(offset - 1)? as usize
+        } else {
+            0
+        };
+        flags.extend(iter::repeat(flag).take(repeat_count + 1));
+    }
+    flags.truncate(point_count);
+
+    let xs = read_coordinates(
+        &glyph,
+        &mut offset,
+        &flags,
+        X_SHORT_VECTOR,
+        X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+    )?;
+    let ys = read_coordinates(
+        &glyph,
+        &mut offset,
+        &flags,
+        Y_SHORT_VECTOR,
+        Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+    )?;
+
+    let mut contours = Vec::with_capacity(contour_count);
+    let mut start = 0;
+    for end_point in end_points {
+        if end_point < start || end_point >= point_count {
+            return None;
+        }
+        let mut contour = Contour::new();
+        for point_index in start..=end_point {
+            let point_flags = if flags[point_index] & ON_CURVE_POINT != 0 {
+                PointFlags::empty()
+            } else {
+                PointFlags::CONTROL_POINT_0
+            };
+            contour.push(
+                Vector2F::new(xs[point_index] as f32, ys[point_index] as f32),
+                point_flags,
+            );
+        }
+        contours.push(contour);
+        start = end_point + 1;
+    }
+    Some(contours)
+}
+
+// Reads one coordinate array of a simple glyph, accumulating the stored deltas.
+fn read_coordinates(
+    glyph: &TableData,
+    offset: &mut usize,
+    flags: &[u8],
+    short_vector: u8,
+    same_or_positive: u8,
+) -> Option<Vec<i32>> {
+    let mut coordinates = Vec::with_capacity(flags.len());
+    let mut coordinate = 0;
+    for &flag in flags {
+        if flag & short_vector != 0 {
+            let delta = glyph.u8(*offset)? as i32;
+            *offset += 1;
+            coordinate += if flag & same_or_positive != 0 {
+                delta
+            } else {
+                -delta
+            };
+        } else if flag & same_or_positive == 0 {
+            coordinate += glyph.i16(*offset)? as i32;
+            *offset += 2;
+        }
+        coordinates.push(coordinate);
+    }
+    Some(coordinates)
+}
+
+/// Inserts the on-curve point midway between each pair of consecutive off-curve points, including
+/// the pair formed by the last and first points, which TrueType leaves implied.
+pub(crate) fn insert_implied_points(contour: &Contour) -> Contour {
+    let point_count = contour.positions.len();
+    let mut result = Contour::new();
+    for (point_index, (&position, flags)) in contour
+        .positions
+        .iter()
+        .zip(contour.flags.iter())
+        .enumerate()
+    {
+        result.push(position, flags.clone());
+        if has_implied_point_after(contour, point_index) {
+            let next_position = contour.positions[(point_index + 1) % point_count];
+            result.push(position.lerp(next_position, 0.5), PointFlags::empty());
+        }
+    }
+    result
+}
+
+/// Returns the index of each point of the contours of a simple glyph among all of its points, as
+/// TrueType instructions number them.
+///
+/// If `explicit` is true, the indices are for the points that `insert_implied_points()` produces,
+/// and the inserted points, which aren't stored in the font, have no index.
+pub(crate) fn point_indices(contours: &[Contour], explicit: bool) -> Vec<Vec<Option<u16>>> {
+    let mut next_point_index = 0u16;
+    contours
+        .iter()
+        .map(|contour| {
+            let mut indices = Vec::with_capacity(contour.flags.len());
+            for point_index in 0..contour.flags.len() {
+                indices.push(Some(next_point_index));
+                next_point_index = next_point_index.wrapping_add(1);
+                if explicit && has_implied_point_after(contour, point_index) {
+                    indices.push(None);
+                }
+            }
+            indices
+        })
+        .collect()
+}
+
+// Two off-curve points in a row imply an on-curve point between them.
+fn has_implied_point_after(contour: &Contour, point_index: usize) -> bool {
+    let next_index = (point_index + 1) % contour.flags.len();
+    !contour.flags[point_index].is_empty() && !contour.flags[next_index].is_empty()
+}
+
+/// Returns the components of a composite glyph, given its data from the `glyf` table.
+///
+/// Returns `None` for simple glyphs, empty glyphs, and truncated data.
+pub(crate) fn components(glyph: &[u8]) -> Option<Vec<Component>> {
+    let glyph = TableData(glyph);
+    if glyph.i16(0)? >= 0 {
+        return None;
+    }
+
+    let mut components = vec![];
+    let mut offset = 10;
+    loop {
+        let flags = glyph.u16(offset)?;
+        let glyph_id = glyph.u16(offset + 2)? as u32;
+        offset += 4;
+
+        let (arg_1, arg_2) = match (
+            flags & ARG_1_AND_2_ARE_WORDS != 0,
+            flags & ARGS_ARE_XY_VALUES != 0,
+        ) {
+            (true, true) => (glyph.i16(offset)? as i32, glyph.i16(offset + 2)? as i32),
+            (true, false) => (glyph.u16(offset)? as i32, glyph.u16(offset + 2)? as i32),
+            (false, true) => (
+                glyph.u8(offset)? as i8 as i32,
+                glyph.u8(offset + 1)? as i8 as i32,
+            ),
+            (false, false) => (glyph.u8(offset)? as i32, glyph.u8(offset + 1)? as i32),
+        };
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+
+        // The matrix maps component coordinates to composite coordinates: x' = xscale * x +
+        // scale10 * y and y' = scale01 * x + yscale * y.
+        let matrix = if flags & WE_HAVE_A_SCALE != 0 {
+            let scale = glyph.f2dot14(offset)?;
+            offset += 2;
+            Matrix2x2F::from_scale(scale)
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            let scale = Vector2F::new(glyph.f2dot14(offset)?, glyph.f2dot14(offset + 2)?);
+            offset += 4;
+            Matrix2x2F::from_scale(scale)
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            let (xscale, scale01) = (glyph.f2dot14(offset)?, glyph.f2dot14(offset + 2)?);
+            let (scale10, yscale) = (glyph.f2dot14(offset + 4)?, glyph.f2dot14(offset + 6)?);
+            offset += 8;
+            Matrix2x2F::row_major(xscale, scale10, scale01, yscale)
+        } else {
+            Matrix2x2F::from_scale(1.0)
+        };
+
+        let (translation, anchor_points) = if flags & ARGS_ARE_XY_VALUES != 0 {
+            let translation = Vector2F::new(arg_1 as f32, arg_2 as f32);
+            // Apple's convention, which some fonts request explicitly, is to scale the offset
+            // along with the outline. As in FreeType, each coordinate is scaled by the length of
+            // the corresponding row of the matrix, so rotations and flips leave it in place.
+            if flags & SCALED_COMPONENT_OFFSET != 0 {
+                (translation * matrix.extract_scale(), None)
+            } else {
+                (translation, None)
+            }
+        } else {
+            (Vector2F::zero(), Some((arg_1 as u16, arg_2 as u16)))
+        };
+
+        components.push(Component {
+            glyph_id,
+            transform: Transform2F {
+                matrix,
+                vector: translation,
+            },
+            anchor_points,
+            use_my_metrics: flags & USE_MY_METRICS != 0,
+            round_to_grid: flags & ROUND_XY_TO_GRID != 0,
+        });
+
+        if flags & MORE_COMPONENTS == 0 {
+            return Some(components);
+        }
+    }
+}
+
+/// Returns the contours of a glyph with the components of composite glyphs transformed into
+/// place, in font units with implied on-curve points left implied.
+///
+/// `glyph_data` returns the data of a glyph from the `glyf` table, or `None` if there is no such
+/// glyph. Composites may nest `max_depth` levels deep, where 1 means that all their components are
+/// simple glyphs. Composites that refer to themselves, nest more deeply, refer to glyphs that don't
+/// exist, or are otherwise malformed fail with `GlyphLoadingError::Parse`.
+///
+/// `ROUND_XY_TO_GRID` rounds component offsets to the pixel grid when hinting. The offsets here
+/// are in font units, to which it doesn't apply, so it is ignored.
+pub(crate) fn flattened_contours<'a, F>(
+    glyph_id: u32,
+    max_depth: u16,
+    glyph_data: F,
+) -> Result<Vec<Contour>, GlyphLoadingError>
+where
+    F: Fn(u32) -> Option<&'a [u8]>,
+{
+    let glyph = glyph_data(glyph_id).ok_or(GlyphLoadingError::NoSuchGlyph)?;
+    let mut contours = vec![];
+    flatten(
+        glyph_id,
+        glyph,
+        max_depth,
+        &glyph_data,
+        &mut vec![],
+        &mut contours,
+    )?;
+    Ok(contours)
+}
+
+// Appends the contours of a glyph to `contours`. `composites` holds the composite glyphs that
+// (transitively) contain it.
+fn flatten<'a, F>(
+    glyph_id: u32,
+    glyph: &[u8],
+    max_depth: u16,
+    glyph_data: &F,
+    composites: &mut Vec<u32>,
+    contours: &mut Vec<Contour>,
+) -> Result<(), GlyphLoadingError>
+where
+    F: Fn(u32) -> Option<&'a [u8]>,
+{
+    let is_composite = TableData(glyph).i16(0).is_some_and(|count| count < 0);
+    if !is_composite {
+        contours.extend(simple_contours(glyph).ok_or(GlyphLoadingError::Parse)?);
+        return Ok(());
+    }
+    if composites.contains(&glyph_id) || composites.len() >= max_depth as usize {
+        return Err(GlyphLoadingError::Parse);
+    }
+    let components = components(glyph).ok_or(GlyphLoadingError::Parse)?;
+
+    composites.push(glyph_id);
+    for component in components {
+        let component_glyph = glyph_data(component.glyph_id).ok_or(GlyphLoadingError::Parse)?;
+        let mut component_contours = vec![];
+        flatten(
+            component.glyph_id,
+            component_glyph,
+            max_depth,
+            glyph_data,
+            composites,
+            &mut component_contours,
+        )?;
+
+        // Anchored components are moved so that their point lands on the composite's point. Both
+        // are numbered as stored, the composite's over the components placed so far.
+        let mut transform = component.transform;
+        if let Some((composite_point, component_point)) = component.anchor_points {
+            let target = nth_point(contours, composite_point).ok_or(GlyphLoadingError::Parse)?;
+            let source =
+                nth_point(&component_contours, component_point).ok_or(GlyphLoadingError::Parse)?;
+            transform.vector = target - transform.matrix * source;
+        }
+
+        for mut contour in component_contours {
+            for position in &mut contour.positions {
+                *position = transform * *position;
+            }
+            contours.push(contour);
+        }
+    }
+    composites.pop();
+    Ok(())
+}
+
+fn nth_point(contours: &[Contour], index: u16) -> Option<Vector2F> {
+    contours
+        .iter()
+        .flat_map(|contour| contour.positions.iter())
+        .nth(index as usize)
+        .cloned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        components, flattened_contours, insert_implied_points, instruction_length, simple_contours,
+    };
+    use crate::error::GlyphLoadingError;
+    use crate::outline::{Contour, PointFlags};
+    use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
+    use pathfinder_geometry::vector::Vector2F;
+
+    // A composite glyph header followed by the given component records.
+    fn synthesize_composite(records: &[&[u8]]) -> Vec<u8> {
+        let mut glyph = vec![];
+        glyph.extend_from_slice(&(-1i16).to_be_bytes()); // numberOfContours
+        glyph.extend_from_slice(&[0; 8]); // bounding box
+        for record in records {
+            glyph.extend_from_slice(record);
+        }
+        glyph
+    }
+
+    #[test]
+    fn parse_components() {
+        // Glyph 10 offset by (4, -3) with byte arguments, using its metrics.
+        let base = [0x02, 0x26, 0, 10, 4, 0xfd];
+        // Glyph 300 offset by (500, 1000) with word arguments and a uniform scale of 0.5.
+        let scaled = [0x00, 0x2b, 0x01, 0x2c, 0x01, 0xf4, 0x03, 0xe8, 0x20, 0x00];
+        // Glyph 11 attached by matching point 7 of the composite to point 2 of the component,
+        // with a 2x2 matrix of (1, 0.5, 0, -1).
+        let anchored = [
+            0x00, 0xa0, 0, 11, 7, 2, 0x40, 0x00, 0x20, 0x00, 0x00, 0x00, 0xc0, 0x00,
+        ];
+        // Glyph 12 offset by (10, 0) with a scale of 1.5, with the offset scaled too.
+        let scaled_offset = [0x08, 0x0a, 0, 12, 10, 0, 0x60, 0x00];
+        let glyph = synthesize_composite(&[&base, &scaled, &anchored, &scaled_offset]);
+
+        let components = components(&glyph).unwrap();
+        assert_eq!(components.len(), 4);
+
+        assert_eq!(components[0].glyph_id, 10);
+        assert_eq!(
+            components[0].transform,
+            Transform2F::from_translation(Vector2F::new(4.0, -3.0))
+        );
+        assert_eq!(components[0].anchor_points, None);
+        assert!(components[0].use_my_metrics);
+        assert!(components[0].round_to_grid);
+
+        assert_eq!(components[1].glyph_id, 300);
+        assert_eq!(
+            components[1].transform,
+            Transform2F::from_scale(0.5).translate(Vector2F::new(500.0, 1000.0))
+        );
+        assert!(!components[1].use_my_metrics);
+
+        assert_eq!(components[2].glyph_id, 11);
+        assert_eq!(components[2].anchor_points, Some((7, 2)));
+        assert_eq!(components[2].transform.vector, Vector2F::zero());
+        assert_eq!(
+            components[2].transform.matrix,
+            Matrix2x2F::row_major(1.0, 0.0, 0.5, -1.0)
+        );
+        assert_eq!(
+            components[2].transform * Vector2F::new(2.0, 3.0),
+            Vector2F::new(2.0, -2.0)
+        );
+
+        assert_eq!(components[3].transform.vector, Vector2F::new(15.0, 0.0));
+    }
+
+    #[test]
+    fn scale_component_offsets_by_matrix_row_lengths() {
+        // Glyph 13 offset by (10, 20) with a 2x2 matrix of (0, 1, -0.5, 0), with the offset
+        // scaled too. Like FreeType, the offset is scaled by (0.5, 1) rather than transformed to
+        // (-10, 10).
+        let rotated = [
+            0x08, 0x82, 0, 13, 10, 20, 0x00, 0x00, 0x40, 0x00, 0xe0, 0x00, 0x00, 0x00,
+        ];
+        let components = components(&synthesize_composite(&[&rotated])).unwrap();
+        assert_eq!(
+            components[0].transform.matrix,
+            Matrix2x2F::row_major(0.0, -0.5, 1.0, 0.0)
+        );
+        assert_eq!(components[0].transform.vector, Vector2F::new(5.0, 20.0));
+    }
+
+    #[test]
+    fn simple_and_truncated_glyphs() {
+        let mut simple = vec![];
+        simple.extend_from_slice(&1i16.to_be_bytes());
+        simple.extend_from_slice(&[0; 8]);
+        assert_eq!(components(&simple), None);
+        assert_eq!(components(&[]), None);
+
+        let glyph = synthesize_composite(&[&[0x00, 0x2b, 0x01, 0x2c, 0x01]]);
+        assert_eq!(components(&glyph), None);
+    }
+
+    // Two contours: a diagonal with one on-curve point and three off-curve points, stored with a
+    // repeated flag and short vectors, and a vertical pair of off-curve points, stored with a
+    // negative word, a negative short vector, and a repeated coordinate.
+    fn synthesize_simple() -> Vec<u8> {
+        let mut glyph = vec![];
+        glyph.extend_from_slice(&2i16.to_be_bytes()); // numberOfContours
+        glyph.extend_from_slice(&[0; 8]); // bounding box
+        glyph.extend_from_slice(&[0, 3, 0, 5]); // endPtsOfContours
+        glyph.extend_from_slice(&1u16.to_be_bytes()); // instructionLength
+        glyph.push(0xb0); // instructions
+        glyph.extend_from_slice(&[0x31, 0x3e, 0x02, 0x04, 0x14]); // flags
+        glyph.extend_from_slice(&[100, 100, 100, 0xff, 0xce]); // x
+        glyph.extend_from_slice(&[100, 100, 100, 250, 100]); // y
+        glyph
+    }
+
+    #[test]
+    fn read_instruction_lengths() {
+        assert_eq!(instruction_length(&synthesize_simple()), Some(1));
+        assert_eq!(instruction_length(&[]), Some(0));
+        assert_eq!(instruction_length(&[0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]), None);
+
+        // A scaled component without instructions, then a 2x2 component followed by 3 bytes of
+        // instructions.
+        let scaled = [0x00, 0x2b, 0x01, 0x2c, 0x01, 0xf4, 0x03, 0xe8, 0x20, 0x00];
+        let anchored = [
+            0x01, 0x80, 0, 11, 7, 2, 0x40, 0x00, 0x20, 0x00, 0x00, 0x00, 0xc0, 0x00,
+        ];
+        let mut glyph = synthesize_composite(&[&scaled, &anchored]);
+        glyph.extend_from_slice(&[0, 3, 0xb0, 0x00, 0x2f]);
+        assert_eq!(instruction_length(&glyph), Some(3));
+        assert_eq!(instruction_length(&glyph[..glyph.len() - 4]), None);
+
+        // Only the last component's flag counts.
+        let mut scaled = scaled;
+        scaled[0] |= 0x01;
+        let mut anchored = anchored;
+        anchored[0] = 0x00;
+        let glyph = synthesize_composite(&[&scaled, &anchored]);
+        assert_eq!(instruction_length(&glyph), Some(0));
+    }
+
+    #[test]
+    fn parse_simple_contours() {
+        let contours = simple_contours(&synthesize_simple()).unwrap();
+        assert_eq!(contours.len(), 2);
+        assert_eq!(
+            contours[0].positions,
+            [
+                Vector2F::new(0.0, 0.0),
+                Vector2F::new(100.0, 100.0),
+                Vector2F::new(200.0, 200.0),
+                Vector2F::new(300.0, 300.0),
+            ]
+        );
+        assert_eq!(
+            contours[0].flags,
+            [
+                PointFlags::empty(),
+                PointFlags::CONTROL_POINT_0,
+                PointFlags::CONTROL_POINT_0,
+                PointFlags::CONTROL_POINT_0,
+            ]
+        );
+        assert_eq!(
+            contours[1].positions,
+            [Vector2F::new(250.0, 50.0), Vector2F::new(250.0, -50.0)]
+        );
+
+        assert_eq!(simple_contours(&[]).unwrap().len(), 0);
+        let glyph = synthesize_composite(&[&[0x00, 0x2b, 0x01, 0x2c, 0x01]]);
+        assert!(simple_contours(&glyph).is_none());
+        let glyph = synthesize_simple();
+        assert!(simple_contours(&glyph[..glyph.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn insert_implied_on_curve_points() {
+        let contours = simple_contours(&synthesize_simple()).unwrap();
+        let contour = insert_implied_points(&contours[0]);
+        assert_eq!(
+            contour.positions,
+            [
+                Vector2F::new(0.0, 0.0),
+                Vector2F::new(100.0, 100.0),
+                Vector2F::new(150.0, 150.0),
+                Vector2F::new(200.0, 200.0),
+                Vector2F::new(250.0, 250.0),
+                Vector2F::new(300.0, 300.0),
+            ]
+        );
+        let on_curve: Vec<bool> = contour.flags.iter().map(PointFlags::is_empty).collect();
+        assert_eq!(on_curve, [true, false, true, false, true, false]);
+
+        // The last point is followed by the first, so an all-off-curve contour gets a midpoint
+        // after every point.
+        let contour = insert_implied_points(&contours[1]);
+        assert_eq!(
+            contour.positions,
+            [
+                Vector2F::new(250.0, 50.0),
+                Vector2F::new(250.0, 0.0),
+                Vector2F::new(250.0, -50.0),
+                Vector2F::new(250.0, 0.0),
+            ]
+        );
+    }
+
+    // A simple glyph with one contour of on-curve points.
+    fn synthesize_polygon(points: &[(i16, i16)]) -> Vec<u8> {
+        let mut glyph = vec![];
+        glyph.extend_from_slice(&1i16.to_be_bytes()); // numberOfContours
+        glyph.extend_from_slice(&[0; 8]); // bounding box
+        glyph.extend_from_slice(&(points.len() as u16 - 1).to_be_bytes()); // endPtsOfContours
+        glyph.extend_from_slice(&0u16.to_be_bytes()); // instructionLength
+        glyph.extend(points.iter().map(|_| 0x01)); // flags
+        let mut previous = (0, 0);
+        for &(x, _) in points {
+            glyph.extend_from_slice(&(x - previous.0).to_be_bytes());
+            previous.0 = x;
+        }
+        for &(_, y) in points {
+            glyph.extend_from_slice(&(y - previous.1).to_be_bytes());
+            previous.1 = y;
+        }
+        glyph
+    }
+
+    fn positions(contours: &[Contour]) -> Vec<Vec<(f32, f32)>> {
+        contours
+            .iter()
+            .map(|contour| {
+                let positions = contour.positions.iter();
+                positions
+                    .map(|position| (position.x(), position.y()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flatten_composites() {
+        // A quarter turn counterclockwise, as F2Dot14 xscale, scale01, scale10, and yscale.
+        const ROTATION: [u8; 8] = [0x00, 0x00, 0x40, 0x00, 0xc0, 0x00, 0x00, 0x00];
+        let rotated = |flags: u16, glyph_id: u8| {
+            let mut record = flags.to_be_bytes().to_vec();
+            record.extend_from_slice(&[0, glyph_id, 0, 100, 0, 0]);
+            record.extend_from_slice(&ROTATION);
+            record
+        };
+        let glyphs = [
+            vec![],
+            synthesize_polygon(&[(0, 0), (10, 0), (10, 20)]),
+            // Glyph 1 rotated and offset by (100, 0), then glyph 1 again, attached by matching
+            // point 1 of the composite to point 0 of the component.
+            synthesize_composite(&[&rotated(0x00a3, 1), &[0x00, 0x00, 0, 1, 1, 0]]),
+            // Glyph 3 refers to itself.
+            synthesize_composite(&[&[0x00, 0x02, 0, 3, 0, 0]]),
+            // Glyph 2 offset by (0, 5), nesting two levels deep.
+            synthesize_composite(&[&[0x00, 0x02, 0, 2, 0, 5]]),
+            // Glyphs 5 and 6 refer to each other.
+            synthesize_composite(&[&[0x00, 0x02, 0, 6, 0, 0]]),
+            synthesize_composite(&[&[0x00, 0x02, 0, 5, 0, 0]]),
+            // Glyph 1 attached by a point that doesn't exist yet.
+            synthesize_composite(&[&[0x00, 0x00, 0, 1, 9, 0]]),
+            // A glyph that doesn't exist.
+            synthesize_composite(&[&[0x00, 0x02, 0, 99, 0, 0]]),
+            // Glyph 1 rotated, with the offset scaled by the matrix, which doesn't rotate it.
+            synthesize_composite(&[&rotated(0x0883, 1)]),
+        ];
+        let flatten = |glyph_id, max_depth| {
+            flattened_contours(glyph_id, max_depth, |glyph_id| {
+                glyphs.get(glyph_id as usize).map(|glyph| &glyph[..])
+            })
+            .map(|contours| positions(&contours))
+        };
+
+        assert_eq!(flatten(0, 1), Ok(vec![]));
+        assert_eq!(
+            flatten(1, 1),
+            Ok(vec![vec![(0.0, 0.0), (10.0, 0.0), (10.0, 20.0)]])
+        );
+        let composite = vec![
+            vec![(100.0, 0.0), (100.0, 10.0), (80.0, 10.0)],
+            vec![(100.0, 10.0), (110.0, 10.0), (110.0, 30.0)],
+        ];
+        assert_eq!(flatten(2, 1), Ok(composite.clone()));
+        assert_eq!(flatten(9, 1), Ok(composite[..1].to_vec()));
+
+        let nested = composite
+            .iter()
+            .map(|contour| contour.iter().map(|&(x, y)| (x, y + 5.0)).collect())
+            .collect();
+        assert_eq!(flatten(4, 2), Ok(nested));
+        assert_eq!(flatten(4, 1), Err(GlyphLoadingError::Parse));
+
+        assert_eq!(flatten(3, 16), Err(GlyphLoadingError::Parse));
+        assert_eq!(flatten(5, 16), Err(GlyphLoadingError::Parse));
+        assert_eq!(flatten(7, 16), Err(GlyphLoadingError::Parse));
+        assert_eq!(flatten(8, 16), Err(GlyphLoadingError::Parse));
+        assert_eq!(flatten(99, 16), Err(GlyphLoadingError::NoSuchGlyph));
+    }
+}
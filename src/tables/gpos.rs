@@ -0,0 +1,312 @@
+// font-kit/src/tables/gpos.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The glyph positioning table, of which only pair adjustment (kerning) is read.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/gpos
+
+use super::{tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"GPOS");
+
+const KERN_FEATURE_TAG: u32 = tag(b"kern");
+
+const PAIR_ADJUSTMENT_LOOKUP_TYPE: u16 = 2;
+const EXTENSION_LOOKUP_TYPE: u16 = 9;
+
+const VALUE_X_PLACEMENT: u16 = 0x0001;
+const VALUE_Y_PLACEMENT: u16 = 0x0002;
+const VALUE_X_ADVANCE: u16 = 0x0004;
+
+/// Returns the horizontal kerning between `left` and `right`, in font units.
+///
+/// This sums the advance adjustment of the first glyph over every pair adjustment lookup that the
+/// `kern` feature uses, in any script or language. Returns `None` if no lookup covers the pair.
+pub(crate) fn pair_kerning(table: &[u8], left: u16, right: u16) -> Option<f32> {
+    let table = TableData(table);
+    let mut kerning = None;
+    for lookup_offset in kern_lookup_offsets(table)? {
+        if let Some(value) = lookup_kerning(table, lookup_offset, left, right) {
+            kerning = Some(kerning.unwrap_or(0.0) + value);
+        }
+    }
+    kerning
+}
+
+/// Returns true if any lookup of the `kern` feature has a pair adjustment subtable, without
+/// checking which glyphs it covers.
+pub(crate) fn has_pair_kerning(table: &[u8]) -> bool {
+    let table = TableData(table);
+    let lookup_offsets = kern_lookup_offsets(table).unwrap_or_default();
+    lookup_offsets.into_iter().any(|lookup_offset| {
+        pair_adjustment_subtables(table, lookup_offset)
+            .is_some_and(|subtable_offsets| !subtable_offsets.is_empty())
+    })
+}
+
+// Returns the offsets of the lookups that the `kern` features of every script and language use.
+fn kern_lookup_offsets(table: TableData) -> Option<Vec<usize>> {
+    let feature_list_offset = table.u16(6)? as usize;
+    let lookup_list_offset = table.u16(8)? as usize;
+
+    // The same lookups are typically shared by the `kern` features of every script.
+    let mut lookup_indices = vec![];
+    let feature_count = table.u16(feature_list_offset)? as usize;
+    for feature_index in 0..feature_count {
+        let record_offset = feature_list_offset + 2 + feature_index * 6;
+        if table.u32(record_offset)? != KERN_FEATURE_TAG {
+            continue;
+        }
+        let feature_offset = feature_list_offset + table.u16(record_offset + 4)? as usize;
+        let lookup_index_count = table.u16(feature_offset + 2)? as usize;
+        for index in 0..lookup_index_count {
+            lookup_indices.push(table.u16(feature_offset + 4 + index * 2)?);
+        }
+    }
+    lookup_indices.sort_unstable();
+    lookup_indices.dedup();
+
+    lookup_indices
+        .into_iter()
+        .map(|lookup_index| {
+            let record_offset = lookup_list_offset + 2 + lookup_index as usize * 2;
+            Some(lookup_list_offset + table.u16(record_offset)? as usize)
+        })
+        .collect()
+}
+
+// Applies the first subtable of a lookup that covers the pair, as a shaper would.
+fn lookup_kerning(table: TableData, lookup_offset: usize, left: u16, right: u16) -> Option<f32> {
+    pair_adjustment_subtables(table, lookup_offset)?
+        .into_iter()
+        .find_map(|subtable_offset| pair_adjustment(table, subtable_offset, left, right))
+}
+
+// Returns the offsets of the pair adjustment subtables of a lookup, looking through extension
+// subtables.
+fn pair_adjustment_subtables(table: TableData, lookup_offset: usize) -> Option<Vec<usize>> {
+    let lookup_type = table.u16(lookup_offset)?;
+    let subtable_count = table.u16(lookup_offset + 4)? as usize;
+    let mut subtable_offsets = vec![];
+    for subtable_index in 0..subtable_count {
+        let mut subtable_offset =
+            lookup_offset + table.u16(lookup_offset + 6 + subtable_index * 2)? as usize;
+        let mut subtable_type = lookup_type;
+        if subtable_type == EXTENSION_LOOKUP_TYPE {
+            subtable_type = table.u16(subtable_offset + 2)?;
+            subtable_offset += table.u32(subtable_offset + 4)? as usize;
+        }
+        if subtable_type == PAIR_ADJUSTMENT_LOOKUP_TYPE {
+            subtable_offsets.push(subtable_offset);
+        }
+    }
+    Some(subtable_offsets)
+}
+
+fn pair_adjustment(table: TableData, offset: usize, left: u16, right: u16) -> Option<f32> {
+    let coverage_offset = offset + table.u16(offset + 2)? as usize;
+    let coverage_index = coverage_index(table, coverage_offset, left)?;
+    let value_format_1 = table.u16(offset + 4)?;
+    let value_format_2 = table.u16(offset + 6)?;
+    let record_size = value_record_size(value_format_1) + value_record_size(value_format_2);
+
+    match table.u16(offset)? {
+        1 => {
+            let pair_set_offset =
+                offset + table.u16(offset + 10 + coverage_index as usize * 2)? as usize;
+            let pair_count = table.u16(pair_set_offset)? as usize;
+            let pair_size = 2 + record_size;
+            let (mut low, mut high) = (0, pair_count);
+            while low < high {
+                let mid = (low + high) / 2;
+                let pair_offset = pair_set_offset + 2 + mid * pair_size;
+                let second_glyph = table.u16(pair_offset)?;
+                if second_glyph < right {
+                    low = mid + 1;
+                } else if second_glyph > right {
+                    high = mid;
+                } else {
+                    return x_advance(table, pair_offset + 2, value_format_1);
+                }
+            }
+            None
+        }
+        2 => {
+            let class_def_1_offset = offset + table.u16(offset + 8)? as usize;
+            let class_def_2_offset = offset + table.u16(offset + 10)? as usize;
+            let class_1_count = table.u16(offset + 12)? as usize;
+            let class_2_count = table.u16(offset + 14)? as usize;
+            let class_1 = glyph_class(table, class_def_1_offset, left)? as usize;
+            let class_2 = glyph_class(table, class_def_2_offset, right)? as usize;
+            if class_1 >= class_1_count || class_2 >= class_2_count {
+                return None;
+            }
+            let record_offset = offset + 16 + (class_1 * class_2_count + class_2) * record_size;
+            x_advance(table, record_offset, value_format_1)
+        }
+        _ => None,
+    }
+}
+
+// Reads the x advance of a value record, which is zero if the record doesn't include one.
+fn x_advance(table: TableData, offset: usize, value_format: u16) -> Option<f32> {
+    if value_format & VALUE_X_ADVANCE == 0 {
+        return Some(0.0);
+    }
+    let preceding = value_format & (VALUE_X_PLACEMENT | VALUE_Y_PLACEMENT);
+    let field_offset = offset + preceding.count_ones() as usize * 2;
+    table.i16(field_offset).map(|value| value as f32)
+}
+
+#[inline]
+fn value_record_size(value_format: u16) -> usize {
+    (value_format & 0x00ff).count_ones() as usize * 2
+}
+
+// Returns the index of `glyph_id` in a coverage table, or `None` if it isn't covered.
+fn coverage_index(table: TableData, offset: usize, glyph_id: u16) -> Option<u16> {
+    match table.u16(offset)? {
+        1 => {
+            let glyph_count = table.u16(offset + 2)? as usize;
+            let (mut low, mut high) = (0, glyph_count);
+            while low < high {
+                let mid = (low + high) / 2;
+                let glyph = table.u16(offset + 4 + mid * 2)?;
+                if glyph < glyph_id {
+                    low = mid + 1;
+                } else if glyph > glyph_id {
+                    high = mid;
+                } else {
+                    return Some(mid as u16);
+                }
+            }
+            None
+        }
+        2 => {
+            let range_count = table.u16(offset + 2)? as usize;
+            for range_index in 0..range_count {
+                let range_offset = offset + 4 + range_index * 6;
+                let (start, end) = (table.u16(range_offset)?, table.u16(range_offset + 2)?);
+                if (start..=end).contains(&glyph_id) {
+                    let start_index = table.u16(range_offset + 4)?;
+                    return start_index.checked_add(glyph_id - start);
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+// Returns the class of `glyph_id` in a class definition table. Unlisted glyphs are in class 0.
+fn glyph_class(table: TableData, offset: usize, glyph_id: u16) -> Option<u16> {
+    match table.u16(offset)? {
+        1 => {
+            let start_glyph = table.u16(offset + 2)?;
+            let glyph_count = table.u16(offset + 4)?;
+            match glyph_id.checked_sub(start_glyph) {
+                Some(index) if index < glyph_count => table.u16(offset + 6 + index as usize * 2),
+                _ => Some(0),
+            }
+        }
+        2 => {
+            let range_count = table.u16(offset + 2)? as usize;
+            for range_index in 0..range_count {
+                let range_offset = offset + 4 + range_index * 6;
+                let (start, end) = (table.u16(range_offset)?, table.u16(range_offset + 2)?);
+                if (start..=end).contains(&glyph_id) {
+                    return table.u16(range_offset + 4);
+                }
+            }
+            Some(0)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{has_pair_kerning, pair_kerning};
+
+    fn push_u16s(table: &mut Vec<u8>, values: &[u16]) {
+        for value in values {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    // A table with a `kern` feature using two lookups. The first holds a format 1 subtable
+    // kerning the pairs (1, 2) by -50 and (1, 3) by -20. The second holds a format 2 subtable that
+    // covers glyphs 1 through 4, puts glyph 1 in first class 1 and glyphs 2 through 4 in second
+    // class 1, and kerns those classes by -5.
+    fn synthesize_table() -> Vec<u8> {
+        let mut table = vec![];
+        // Header: the script list is empty, the feature list is at 10, the lookup list at 26.
+        push_u16s(&mut table, &[1, 0, 10, 10, 26]);
+        // Feature list at 10: one `kern` feature at 8 from the list, using lookups 0 and 1.
+        push_u16s(&mut table, &[1]);
+        table.extend_from_slice(b"kern");
+        push_u16s(&mut table, &[8, 0, 2, 0, 1]);
+        // Lookup list at 26: two lookups, at 6 and 14 from the list.
+        push_u16s(&mut table, &[2, 6, 14]);
+        // Lookup 0 at 32, with its subtable at 16 from the lookup.
+        push_u16s(&mut table, &[2, 0, 1, 16]);
+        // Lookup 1 at 40, with its subtable at 36 from the lookup.
+        push_u16s(&mut table, &[2, 0, 1, 36]);
+
+        // Format 1 subtable at 48: coverage at 12, x advance only, one pair set at 18.
+        push_u16s(&mut table, &[1, 12, 0x0004, 0, 1, 18]);
+        // Coverage at 60: glyph 1.
+        push_u16s(&mut table, &[1, 1, 1]);
+        // Pair set at 66: (2, -50), (3, -20).
+        push_u16s(&mut table, &[2, 2, (-50i16) as u16, 3, (-20i16) as u16]);
+
+        // Format 2 subtable at 76: coverage at 24, x advance only, class defs at 34 and 42,
+        // two first classes and two second classes.
+        push_u16s(&mut table, &[2, 24, 0x0004, 0, 34, 42, 2, 2]);
+        // Class values, in rows of first classes: only (1, 1) is kerned.
+        push_u16s(&mut table, &[0, 0, 0, (-5i16) as u16]);
+        // Coverage at 100: glyphs 1 through 4.
+        push_u16s(&mut table, &[2, 1, 1, 4, 0]);
+        // Class def 1 at 110: glyph 1 is class 1.
+        push_u16s(&mut table, &[1, 1, 1, 1]);
+        // Class def 2 at 118: glyphs 2 through 4 are class 1.
+        push_u16s(&mut table, &[2, 1, 2, 4, 1]);
+        table
+    }
+
+    #[test]
+    fn sum_pair_adjustments() {
+        let table = synthesize_table();
+        assert_eq!(pair_kerning(&table, 1, 2), Some(-55.0));
+        assert_eq!(pair_kerning(&table, 1, 3), Some(-25.0));
+        assert_eq!(pair_kerning(&table, 1, 4), Some(-5.0));
+        // Glyph 1 is covered by the class-based subtable, which applies even for class 0.
+        assert_eq!(pair_kerning(&table, 1, 5), Some(0.0));
+        assert_eq!(pair_kerning(&table, 5, 1), None);
+        assert!(has_pair_kerning(&table));
+    }
+
+    #[test]
+    fn ignore_features_other_than_kern() {
+        let mut table = synthesize_table();
+        table[12..16].copy_from_slice(b"liga");
+        assert_eq!(pair_kerning(&table, 1, 2), None);
+        assert!(!has_pair_kerning(&table));
+    }
+
+    #[test]
+    fn truncated_table() {
+        let table = synthesize_table();
+        assert_eq!(pair_kerning(&table[..60], 1, 3), None);
+        assert_eq!(pair_kerning(&[], 1, 2), None);
+        assert!(!has_pair_kerning(&table[..30]));
+        assert!(!has_pair_kerning(&[]));
+    }
+}
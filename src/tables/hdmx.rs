@@ -0,0 +1,56 @@
+// font-kit/src/tables/hdmx.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The horizontal device metrics table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/hdmx
+
+use super::{tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"hdmx");
+
+/// Returns the advance width of `glyph_id` in whole pixels at `pixels_per_em`, or `None` if no
+/// device record covers that size.
+pub(crate) fn advance(table: &[u8], glyph_id: u16, pixels_per_em: u8) -> Option<u8> {
+    let table = TableData(table);
+    let record_count = table.u16(2)? as usize;
+    let record_size = table.u32(4)? as usize;
+    // Each record starts with the size and the maximum width, followed by the widths.
+    if glyph_id as usize + 2 >= record_size {
+        return None;
+    }
+    (0..record_count)
+        .map(|record_index| 8 + record_index * record_size)
+        .find(|&record_offset| table.u8(record_offset) == Some(pixels_per_em))
+        .and_then(|record_offset| table.u8(record_offset + 2 + glyph_id as usize))
+}
+
+#[cfg(test)]
+mod test {
+    use super::advance;
+
+    #[test]
+    fn device_advances() {
+        let mut table = vec![];
+        table.extend_from_slice(&0u16.to_be_bytes());
+        table.extend_from_slice(&2u16.to_be_bytes());
+        table.extend_from_slice(&8u32.to_be_bytes());
+        // Five glyphs at 12 and 16 pixels per em, padded to a multiple of four bytes.
+        table.extend_from_slice(&[12, 9, 0, 7, 9, 6, 6, 0]);
+        table.extend_from_slice(&[16, 12, 0, 9, 12, 8, 8, 0]);
+
+        assert_eq!(advance(&table, 1, 12), Some(7));
+        assert_eq!(advance(&table, 2, 16), Some(12));
+        assert_eq!(advance(&table, 0, 16), Some(0));
+        assert_eq!(advance(&table, 1, 14), None);
+        assert_eq!(advance(&table, 6, 12), None);
+        assert_eq!(advance(&table[..18], 1, 16), None);
+    }
+}
@@ -0,0 +1,116 @@
+// font-kit/src/tables/head.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The font header table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/head
+
+use super::{tag, TableData};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(crate) const TAG: u32 = tag(b"head");
+
+// Seconds from the `LONGDATETIME` epoch, midnight on January 1, 1904, to the Unix epoch.
+const SECONDS_FROM_1904_TO_UNIX_EPOCH: i64 = 2_082_844_800;
+// Seconds from the Unix epoch to the end of the year 9999. Later dates are treated as garbage.
+const MAX_UNIX_SECONDS: i64 = 253_402_300_799;
+
+/// Returns the font revision set by the manufacturer.
+pub(crate) fn font_revision(table: &[u8]) -> Option<f32> {
+    TableData(table).fixed(4)
+}
+
+/// Returns the date the font was created.
+pub(crate) fn created(table: &[u8]) -> Option<SystemTime> {
+    long_date_time(TableData(table).i64(20)?)
+}
+
+/// Returns the date the font was last modified.
+pub(crate) fn modified(table: &[u8]) -> Option<SystemTime> {
+    long_date_time(TableData(table).i64(28)?)
+}
+
+// Converts seconds since 1904 to a system time, rejecting unset and implausible values.
+fn long_date_time(seconds_since_1904: i64) -> Option<SystemTime> {
+    if seconds_since_1904 <= 0 {
+        return None;
+    }
+    let unix_seconds = seconds_since_1904 - SECONDS_FROM_1904_TO_UNIX_EPOCH;
+    if unix_seconds > MAX_UNIX_SECONDS {
+        return None;
+    }
+    if unix_seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(unix_seconds as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(unix_seconds.unsigned_abs()))
+    }
+}
+
+/// Returns true if the `loca` table uses 32-bit offsets and false if it uses 16-bit ones.
+pub(crate) fn has_long_loca_offsets(table: &[u8]) -> Option<bool> {
+    match TableData(table).i16(50)? {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+/// Returns the fields that identify a particular build of a font: its revision, checksum
+/// adjustment, flags, units per em, and creation and modification dates.
+///
+/// Two fonts with equal fingerprints are almost certainly the same file.
+pub(crate) fn fingerprint(table: &[u8]) -> Option<&[u8]> {
+    TableData(table).bytes(4, 32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{created, font_revision, modified};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn synthesize_table(revision: u32, created: i64, modified: i64) -> Vec<u8> {
+        let mut table = vec![0, 1, 0, 0];
+        table.extend(revision.to_be_bytes());
+        table.extend([0; 12]);
+        table.extend(created.to_be_bytes());
+        table.extend(modified.to_be_bytes());
+        table.extend([0; 18]);
+        table
+    }
+
+    #[test]
+    fn revision_and_dates() {
+        let table = synthesize_table(0x00018000, 2_082_844_800, 3_600_000_000);
+        assert_eq!(font_revision(&table), Some(1.5));
+        assert_eq!(created(&table), Some(UNIX_EPOCH));
+        assert_eq!(
+            modified(&table),
+            Some(UNIX_EPOCH + Duration::from_secs(3_600_000_000 - 2_082_844_800))
+        );
+
+        // Dates between 1904 and 1970 are still valid.
+        let table = synthesize_table(0x00010000, 86_400, 86_400);
+        assert_eq!(
+            created(&table),
+            Some(UNIX_EPOCH - Duration::from_secs(2_082_844_800 - 86_400))
+        );
+    }
+
+    #[test]
+    fn malformed_dates() {
+        let table = synthesize_table(0x00010000, 0, i64::MAX);
+        assert_eq!(created(&table), None);
+        assert_eq!(modified(&table), None);
+        let table = synthesize_table(0x00010000, -1, i64::MIN);
+        assert_eq!(created(&table), None);
+        assert_eq!(modified(&table), None);
+        assert_eq!(modified(&table[..30]), None);
+    }
+}
@@ -0,0 +1,42 @@
+// font-kit/src/tables/hhea.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The horizontal header table.
+//!
+//! See: https://docs.microsoft.com/en-us/typography/opentype/spec/hhea
+
+use super::{tag, TableData};
+use crate::metrics::CaretMetrics;
+
+pub(crate) const TAG: u32 = tag(b"hhea");
+
+/// Reads the ascender and descender. The descender is negative below the baseline.
+pub(crate) fn ascent_descent(table: &[u8]) -> Option<(f32, f32)> {
+    let table = TableData(table);
+    Some((table.i16(4)? as f32, table.i16(6)? as f32))
+}
+
+/// Reads the caret slope and offset.
+///
+/// Returns `None` if the table is truncated or specifies a degenerate (zero-length) slope.
+pub(crate) fn caret_metrics(table: &[u8]) -> Option<CaretMetrics> {
+    let table = TableData(table);
+    let slope_rise = table.i16(18)?;
+    let slope_run = table.i16(20)?;
+    let offset = table.i16(22)?;
+    if slope_rise == 0 && slope_run == 0 {
+        return None;
+    }
+    Some(CaretMetrics {
+        slope_rise: slope_rise as f32,
+        slope_run: slope_run as f32,
+        offset: offset as f32,
+    })
+}
@@ -0,0 +1,256 @@
+// font-kit/src/tables/kern.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The classic kerning table, in both its OpenType and Apple versions, and the AAT extended
+//! kerning table. Only format 0 (ordered pair lists) subtables are read.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/kern
+//! See: https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6kern.html
+//! See: https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6kerx.html
+
+use super::{tag, TableData};
+
+pub(crate) const KERN_TAG: u32 = tag(b"kern");
+pub(crate) const KERX_TAG: u32 = tag(b"kerx");
+
+const OPENTYPE_HORIZONTAL: u16 = 0x0001;
+const OPENTYPE_MINIMUM: u16 = 0x0002;
+const OPENTYPE_CROSS_STREAM: u16 = 0x0004;
+const OPENTYPE_OVERRIDE: u16 = 0x0008;
+
+const APPLE_VERTICAL: u16 = 0x8000;
+const APPLE_CROSS_STREAM: u16 = 0x4000;
+const APPLE_VARIATION: u16 = 0x2000;
+
+const KERX_VERTICAL: u32 = 0x8000_0000;
+const KERX_CROSS_STREAM: u32 = 0x4000_0000;
+const KERX_VARIATION: u32 = 0x2000_0000;
+
+/// Returns the horizontal kerning between `left` and `right` from a `kern` table, in font units.
+///
+/// The values of all horizontal format 0 subtables that list the pair are summed. Returns `None`
+/// if none of them do.
+pub(crate) fn kern_pair_kerning(table: &[u8], left: u16, right: u16) -> Option<f32> {
+    let table = TableData(table);
+    let mut kerning = None;
+    for (offset, overrides) in kern_format_0_subtables(table)? {
+        if let Some(value) = format_0_value(table, offset, 2, left, right) {
+            let accumulated = if overrides {
+                0.0
+            } else {
+                kerning.unwrap_or(0.0)
+            };
+            kerning = Some(accumulated + value);
+        }
+    }
+    kerning
+}
+
+/// Returns true if any of the subtables that `kern_pair_kerning()` reads has pairs.
+pub(crate) fn kern_has_pairs(table: &[u8]) -> bool {
+    let table = TableData(table);
+    kern_format_0_subtables(table).is_some_and(|subtables| {
+        subtables
+            .iter()
+            .any(|&(offset, _)| table.u16(offset).is_some_and(|pair_count| pair_count > 0))
+    })
+}
+
+/// Returns the horizontal kerning between `left` and `right` from a `kerx` table, in font units.
+///
+/// The values of all horizontal format 0 subtables that list the pair are summed. Returns `None`
+/// if none of them do.
+pub(crate) fn kerx_pair_kerning(table: &[u8], left: u16, right: u16) -> Option<f32> {
+    let table = TableData(table);
+    let mut kerning = None;
+    for offset in kerx_format_0_subtables(table)? {
+        if let Some(value) = format_0_value(table, offset, 4, left, right) {
+            kerning = Some(kerning.unwrap_or(0.0) + value);
+        }
+    }
+    kerning
+}
+
+/// Returns true if any of the subtables that `kerx_pair_kerning()` reads has pairs.
+pub(crate) fn kerx_has_pairs(table: &[u8]) -> bool {
+    let table = TableData(table);
+    kerx_format_0_subtables(table).is_some_and(|subtables| {
+        subtables
+            .iter()
+            .any(|&offset| table.u32(offset).is_some_and(|pair_count| pair_count > 0))
+    })
+}
+
+// Returns the offset of the format 0 data of each horizontal subtable of a `kern` table that
+// adjusts advances, and whether it overrides the kerning accumulated before it.
+fn kern_format_0_subtables(table: TableData) -> Option<Vec<(usize, bool)>> {
+    let mut subtables = vec![];
+    if table.u16(0)? == 0 {
+        let subtable_count = table.u16(2)? as usize;
+        let mut subtable_offset = 4;
+        for _ in 0..subtable_count {
+            let length = table.u16(subtable_offset + 2)? as usize;
+            let coverage = table.u16(subtable_offset + 4)?;
+            let applies = coverage & OPENTYPE_HORIZONTAL != 0
+                && coverage & (OPENTYPE_MINIMUM | OPENTYPE_CROSS_STREAM) == 0
+                && coverage >> 8 == 0;
+            if applies {
+                subtables.push((subtable_offset + 6, coverage & OPENTYPE_OVERRIDE != 0));
+            }
+            if length < 6 {
+                break;
+            }
+            subtable_offset += length;
+        }
+    } else if table.u32(0)? == 0x0001_0000 {
+        let subtable_count = table.u32(4)? as usize;
+        let mut subtable_offset = 8;
+        for _ in 0..subtable_count {
+            let length = table.u32(subtable_offset)? as usize;
+            let coverage = table.u16(subtable_offset + 4)?;
+            let applies = coverage & (APPLE_VERTICAL | APPLE_CROSS_STREAM | APPLE_VARIATION) == 0
+                && coverage & 0x00ff == 0;
+            if applies {
+                subtables.push((subtable_offset + 8, false));
+            }
+            if length < 8 {
+                break;
+            }
+            subtable_offset += length;
+        }
+    }
+    Some(subtables)
+}
+
+// Returns the offset of the format 0 data of each horizontal subtable of a `kerx` table that
+// adjusts advances.
+fn kerx_format_0_subtables(table: TableData) -> Option<Vec<usize>> {
+    let mut subtables = vec![];
+    let subtable_count = table.u32(4)? as usize;
+    let mut subtable_offset = 8;
+    for _ in 0..subtable_count {
+        let length = table.u32(subtable_offset)? as usize;
+        let coverage = table.u32(subtable_offset + 4)?;
+        let applies = coverage & (KERX_VERTICAL | KERX_CROSS_STREAM | KERX_VARIATION) == 0
+            && coverage & 0xff == 0;
+        if applies {
+            subtables.push(subtable_offset + 12);
+        }
+        if length < 12 {
+            break;
+        }
+        subtable_offset += length;
+    }
+    Some(subtables)
+}
+
+// Binary searches the sorted pairs of a format 0 subtable, whose header fields are `field_size`
+// bytes long in `kern` and twice that in `kerx`.
+fn format_0_value(
+    table: TableData,
+    offset: usize,
+    field_size: usize,
+    left: u16,
+    right: u16,
+) -> Option<f32> {
+    let pair_count = match field_size {
+        2 => table.u16(offset)? as usize,
+        _ => table.u32(offset)? as usize,
+    };
+    let pairs_offset = offset + 4 * field_size;
+    let key = (left as u32) << 16 | right as u32;
+    let (mut low, mut high) = (0, pair_count);
+    while low < high {
+        let mid = (low + high) / 2;
+        let pair_offset = pairs_offset + mid * 6;
+        let pair_key = table.u32(pair_offset)?;
+        if pair_key < key {
+            low = mid + 1;
+        } else if pair_key > key {
+            high = mid;
+        } else {
+            return table.i16(pair_offset + 4).map(|value| value as f32);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{kern_has_pairs, kern_pair_kerning, kerx_has_pairs, kerx_pair_kerning};
+
+    const PAIRS: [(u16, u16, i16); 3] = [(1, 2, -40), (1, 3, 25), (4, 1, -10)];
+
+    fn push_pairs(table: &mut Vec<u8>) {
+        for (left, right, value) in PAIRS {
+            table.extend_from_slice(&left.to_be_bytes());
+            table.extend_from_slice(&right.to_be_bytes());
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    fn opentype_kern_table(coverage: u16) -> Vec<u8> {
+        let mut table = vec![];
+        for value in [0u16, 1, 0, 14 + 18, coverage, 3, 12, 1, 6] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        push_pairs(&mut table);
+        table
+    }
+
+    #[test]
+    fn read_opentype_kern() {
+        let table = opentype_kern_table(0x0001);
+        assert_eq!(kern_pair_kerning(&table, 1, 2), Some(-40.0));
+        assert_eq!(kern_pair_kerning(&table, 1, 3), Some(25.0));
+        assert_eq!(kern_pair_kerning(&table, 4, 1), Some(-10.0));
+        assert_eq!(kern_pair_kerning(&table, 2, 1), None);
+
+        // Cross-stream and minimum subtables don't affect advances.
+        assert_eq!(kern_pair_kerning(&opentype_kern_table(0x0005), 1, 2), None);
+        assert_eq!(kern_pair_kerning(&opentype_kern_table(0x0003), 1, 2), None);
+
+        assert!(kern_has_pairs(&table));
+        assert!(!kern_has_pairs(&opentype_kern_table(0x0005)));
+        assert!(!kern_has_pairs(&table[..4]));
+    }
+
+    #[test]
+    fn read_apple_kern() {
+        let mut table = vec![];
+        table.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        table.extend_from_slice(&1u32.to_be_bytes());
+        table.extend_from_slice(&(16u32 + 18).to_be_bytes());
+        for value in [0u16, 0, 3, 12, 1, 6] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        push_pairs(&mut table);
+        assert_eq!(kern_pair_kerning(&table, 1, 3), Some(25.0));
+        assert_eq!(kern_pair_kerning(&table, 3, 1), None);
+    }
+
+    #[test]
+    fn read_kerx() {
+        let mut table = vec![];
+        table.extend_from_slice(&2u16.to_be_bytes());
+        table.extend_from_slice(&0u16.to_be_bytes());
+        table.extend_from_slice(&1u32.to_be_bytes());
+        for value in [28u32 + 18, 0, 0, 3, 12, 1, 6] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        push_pairs(&mut table);
+        assert_eq!(kerx_pair_kerning(&table, 1, 2), Some(-40.0));
+        assert_eq!(kerx_pair_kerning(&table, 4, 1), Some(-10.0));
+        assert_eq!(kerx_pair_kerning(&table, 1, 4), None);
+        assert_eq!(kerx_pair_kerning(&table[..30], 1, 2), None);
+        assert!(kerx_has_pairs(&table));
+        assert!(!kerx_has_pairs(&table[..20]));
+    }
+}
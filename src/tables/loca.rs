@@ -0,0 +1,75 @@
+// font-kit/src/tables/loca.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The index-to-location table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/loca
+
+use super::{tag, TableData};
+use std::ops::Range;
+
+pub(crate) const TAG: u32 = tag(b"loca");
+
+/// Returns the byte range of a glyph's data within the `glyf` table.
+///
+/// `long_offsets` comes from `head::has_long_loca_offsets()`. The range is empty for glyphs with
+/// no outline, such as the space.
+pub(crate) fn glyph_range(table: &[u8], long_offsets: bool, glyph_id: u32) -> Option<Range<usize>> {
+    let table = TableData(table);
+    let glyph_id = glyph_id as usize;
+    let (start, end) = if long_offsets {
+        (
+            table.u32(glyph_id * 4)? as usize,
+            table.u32(glyph_id * 4 + 4)? as usize,
+        )
+    } else {
+        (
+            table.u16(glyph_id * 2)? as usize * 2,
+            table.u16(glyph_id * 2 + 2)? as usize * 2,
+        )
+    };
+    if end < start {
+        return None;
+    }
+    Some(start..end)
+}
+
+#[cfg(test)]
+mod test {
+    use super::glyph_range;
+
+    #[test]
+    fn short_and_long_offsets() {
+        let table: Vec<u8> = [0u16, 10, 10, 24]
+            .iter()
+            .flat_map(|offset| offset.to_be_bytes())
+            .collect();
+        assert_eq!(glyph_range(&table, false, 0), Some(0..20));
+        assert_eq!(glyph_range(&table, false, 1), Some(20..20));
+        assert_eq!(glyph_range(&table, false, 2), Some(20..48));
+        assert_eq!(glyph_range(&table, false, 3), None);
+
+        let table: Vec<u8> = [0u32, 20, 20, 48]
+            .iter()
+            .flat_map(|offset| offset.to_be_bytes())
+            .collect();
+        assert_eq!(glyph_range(&table, true, 2), Some(20..48));
+        assert_eq!(glyph_range(&table, true, 3), None);
+    }
+
+    #[test]
+    fn decreasing_offsets() {
+        let table: Vec<u8> = [10u16, 0]
+            .iter()
+            .flat_map(|offset| offset.to_be_bytes())
+            .collect();
+        assert_eq!(glyph_range(&table, false, 0), None);
+    }
+}
@@ -0,0 +1,47 @@
+// font-kit/src/tables/maxp.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The maximum profile table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/maxp
+
+use super::{tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"maxp");
+
+/// Reads the maximum nesting depth of composite glyphs, which is 1 when composites only refer to
+/// simple glyphs.
+///
+/// Returns `None` for the version 0.5 table used by CFF-based fonts, which has no such field.
+pub(crate) fn max_component_depth(table: &[u8]) -> Option<u16> {
+    let table = TableData(table);
+    if table.u32(0)? != 0x0001_0000 {
+        return None;
+    }
+    table.u16(30)
+}
+
+#[cfg(test)]
+mod test {
+    use super::max_component_depth;
+
+    #[test]
+    fn read_max_component_depth() {
+        let mut table = 0x0001_0000u32.to_be_bytes().to_vec();
+        table.extend_from_slice(&[0; 26]);
+        table.extend_from_slice(&3u16.to_be_bytes());
+        assert_eq!(max_component_depth(&table), Some(3));
+        assert_eq!(max_component_depth(&table[..31]), None);
+
+        let mut table = 0x0000_5000u32.to_be_bytes().to_vec();
+        table.extend_from_slice(&100u16.to_be_bytes());
+        assert_eq!(max_component_depth(&table), None);
+    }
+}
@@ -0,0 +1,101 @@
+// font-kit/src/tables/mod.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Portable parsers for OpenType and AAT tables.
+//!
+//! These operate on the raw bytes returned by `Loader::load_font_table()`, so they behave the
+//! same regardless of which loader is in use.
+
+use byteorder::{BigEndian, ByteOrder};
+
+pub(crate) mod avar;
+pub(crate) mod cmap;
+pub(crate) mod colr;
+pub(crate) mod cpal;
+pub(crate) mod fvar;
+pub(crate) mod gasp;
+pub(crate) mod glyf;
+pub(crate) mod gpos;
+pub(crate) mod hdmx;
+pub(crate) mod head;
+pub(crate) mod hhea;
+pub(crate) mod kern;
+pub(crate) mod loca;
+pub(crate) mod maxp;
+pub(crate) mod name;
+pub(crate) mod os2;
+pub(crate) mod post;
+pub(crate) mod stat;
+pub(crate) mod trak;
+pub(crate) mod vdmx;
+
+/// Builds a table tag from its four-character code.
+#[inline]
+pub(crate) const fn tag(code: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*code)
+}
+
+/// A bounds-checked big-endian reader over the bytes of a table.
+#[derive(Clone, Copy)]
+pub(crate) struct TableData<'a>(pub(crate) &'a [u8]);
+
+impl<'a> TableData<'a> {
+    #[inline]
+    pub(crate) fn bytes(&self, offset: usize, len: usize) -> Option<&'a [u8]> {
+        self.0.get(offset..offset.checked_add(len)?)
+    }
+
+    #[inline]
+    pub(crate) fn u8(&self, offset: usize) -> Option<u8> {
+        self.0.get(offset).cloned()
+    }
+
+    #[inline]
+    pub(crate) fn u16(&self, offset: usize) -> Option<u16> {
+        self.bytes(offset, 2).map(BigEndian::read_u16)
+    }
+
+    #[inline]
+    pub(crate) fn i16(&self, offset: usize) -> Option<i16> {
+        self.bytes(offset, 2).map(BigEndian::read_i16)
+    }
+
+    #[inline]
+    pub(crate) fn u24(&self, offset: usize) -> Option<u32> {
+        self.bytes(offset, 3).map(BigEndian::read_u24)
+    }
+
+    #[inline]
+    pub(crate) fn u32(&self, offset: usize) -> Option<u32> {
+        self.bytes(offset, 4).map(BigEndian::read_u32)
+    }
+
+    #[inline]
+    pub(crate) fn i32(&self, offset: usize) -> Option<i32> {
+        self.bytes(offset, 4).map(BigEndian::read_i32)
+    }
+
+    #[inline]
+    pub(crate) fn i64(&self, offset: usize) -> Option<i64> {
+        self.bytes(offset, 8).map(BigEndian::read_i64)
+    }
+
+    /// Reads a 2.14 fixed-point number.
+    #[inline]
+    pub(crate) fn f2dot14(&self, offset: usize) -> Option<f32> {
+        self.i16(offset).map(|value| value as f32 / 16384.0)
+    }
+
+    /// Reads a 16.16 fixed-point number.
+    #[inline]
+    pub(crate) fn fixed(&self, offset: usize) -> Option<f32> {
+        self.i32(offset).map(|value| value as f32 / 65536.0)
+    }
+}
@@ -0,0 +1,253 @@
+// font-kit/src/tables/name.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The OpenType naming table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/name
+
+use super::{tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"name");
+
+const PLATFORM_UNICODE: u16 = 0;
+const PLATFORM_MACINTOSH: u16 = 1;
+const PLATFORM_WINDOWS: u16 = 3;
+
+const MACINTOSH_ENCODING_ROMAN: u16 = 0;
+const MACINTOSH_LANGUAGE_ENGLISH: u16 = 0;
+const WINDOWS_ENCODING_UNICODE_BMP: u16 = 1;
+const WINDOWS_ENCODING_UNICODE_FULL: u16 = 10;
+const WINDOWS_LANGUAGE_ENGLISH_US: u16 = 0x0409;
+
+// The primary language IDs of Windows language IDs, which are their low 10 bits, for common ISO
+// 639-1 language codes.
+const WINDOWS_PRIMARY_LANGUAGES: [(&str, u16); 36] = [
+    ("ar", 0x01),
+    ("bg", 0x02),
+    ("ca", 0x03),
+    ("zh", 0x04),
+    ("cs", 0x05),
+    ("da", 0x06),
+    ("de", 0x07),
+    ("el", 0x08),
+    ("en", 0x09),
+    ("es", 0x0a),
+    ("fi", 0x0b),
+    ("fr", 0x0c),
+    ("he", 0x0d),
+    ("hu", 0x0e),
+    ("is", 0x0f),
+    ("it", 0x10),
+    ("ja", 0x11),
+    ("ko", 0x12),
+    ("nl", 0x13),
+    ("nb", 0x14),
+    ("nn", 0x14),
+    ("no", 0x14),
+    ("pl", 0x15),
+    ("pt", 0x16),
+    ("ro", 0x18),
+    ("ru", 0x19),
+    ("hr", 0x1a),
+    ("sk", 0x1b),
+    ("sv", 0x1d),
+    ("th", 0x1e),
+    ("tr", 0x1f),
+    ("id", 0x21),
+    ("uk", 0x22),
+    ("vi", 0x2a),
+    ("hi", 0x39),
+    ("ms", 0x3e),
+];
+
+const WINDOWS_PRIMARY_LANGUAGE_MASK: u16 = 0x03ff;
+const WINDOWS_PRIMARY_LANGUAGE_ENGLISH: u16 = 0x09;
+
+/// Returns the string with the given name ID, preferring US English.
+///
+/// Windows Unicode records are preferred over Unicode-platform records, which are preferred over
+/// Macintosh Roman records. Returns `None` if there is no record in a supported encoding.
+#[inline]
+pub(crate) fn lookup(table: &[u8], name_id: u16) -> Option<String> {
+    lookup_for_language(table, name_id, None)
+}
+
+/// Returns the string with the given name ID, preferring `language`, an ISO 639-1 code such as
+/// `"de"`, then US English, then any other English.
+///
+/// Only Windows records are matched against `language`. Otherwise this works like `lookup()`.
+pub(crate) fn lookup_for_language(
+    table: &[u8],
+    name_id: u16,
+    language: Option<&str>,
+) -> Option<String> {
+    let preferred_language = language.and_then(|language| {
+        WINDOWS_PRIMARY_LANGUAGES
+            .iter()
+            .find(|&&(code, _)| code == language)
+            .map(|&(_, primary_language)| primary_language)
+    });
+
+    let table = TableData(table);
+    let count = table.u16(2)? as usize;
+    let storage_offset = table.u16(4)? as usize;
+
+    let mut best: Option<(u32, u16, usize, usize)> = None;
+    for record_index in 0..count {
+        let record_offset = 6 + record_index * 12;
+        if table.u16(record_offset + 6)? != name_id {
+            continue;
+        }
+        let platform_id = table.u16(record_offset)?;
+        let encoding_id = table.u16(record_offset + 2)?;
+        let language_id = table.u16(record_offset + 4)?;
+        let rank = match (platform_id, encoding_id) {
+            (PLATFORM_WINDOWS, WINDOWS_ENCODING_UNICODE_BMP)
+            | (PLATFORM_WINDOWS, WINDOWS_ENCODING_UNICODE_FULL) => {
+                let primary_language = language_id & WINDOWS_PRIMARY_LANGUAGE_MASK;
+                // The default sublanguage, such as US English, comes first within a language.
+                let is_default_sublanguage = language_id >> 10 == 1;
+                if Some(primary_language) == preferred_language {
+                    if is_default_sublanguage {
+                        0
+                    } else {
+                        1
+                    }
+                } else if language_id == WINDOWS_LANGUAGE_ENGLISH_US {
+                    2
+                } else if primary_language == WINDOWS_PRIMARY_LANGUAGE_ENGLISH {
+                    3
+                } else {
+                    4
+                }
+            }
+            (PLATFORM_UNICODE, _) => 5,
+            (PLATFORM_MACINTOSH, MACINTOSH_ENCODING_ROMAN)
+                if language_id == MACINTOSH_LANGUAGE_ENGLISH =>
+            {
+                6
+            }
+            _ => continue,
+        };
+        if best.map_or(true, |(best_rank, ..)| rank < best_rank) {
+            let length = table.u16(record_offset + 8)? as usize;
+            let offset = storage_offset + table.u16(record_offset + 10)? as usize;
+            best = Some((rank, platform_id, offset, length));
+        }
+    }
+
+    let (_, platform_id, offset, length) = best?;
+    let bytes = table.bytes(offset, length)?;
+    if platform_id == PLATFORM_MACINTOSH {
+        // Mac Roman agrees with ASCII in the lower half, which covers nearly all English names.
+        return Some(
+            bytes
+                .iter()
+                .map(|&byte| {
+                    if byte < 0x80 {
+                        byte as char
+                    } else {
+                        char::REPLACEMENT_CHARACTER
+                    }
+                })
+                .collect(),
+        );
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{lookup, lookup_for_language};
+
+    /// Builds a table from `(platform ID, encoding ID, language ID, name ID, encoded string)`
+    /// records.
+    pub(crate) fn synthesize_table(records: &[(u16, u16, u16, u16, &[u8])]) -> Vec<u8> {
+        let storage_offset = 6 + records.len() * 12;
+        let mut table = vec![];
+        table.extend_from_slice(&0u16.to_be_bytes()); // version
+        table.extend_from_slice(&(records.len() as u16).to_be_bytes());
+        table.extend_from_slice(&(storage_offset as u16).to_be_bytes());
+        let mut storage = vec![];
+        for &(platform_id, encoding_id, language_id, name_id, string) in records {
+            for value in [platform_id, encoding_id, language_id, name_id] {
+                table.extend_from_slice(&value.to_be_bytes());
+            }
+            table.extend_from_slice(&(string.len() as u16).to_be_bytes());
+            table.extend_from_slice(&(storage.len() as u16).to_be_bytes());
+            storage.extend_from_slice(string);
+        }
+        table.extend_from_slice(&storage);
+        table
+    }
+
+    pub(crate) fn utf16(string: &str) -> Vec<u8> {
+        string.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    #[test]
+    fn prefer_windows_english() {
+        let german = utf16("Fett");
+        let english = utf16("Bold");
+        let table = synthesize_table(&[
+            (1, 0, 0, 256, b"Bold (Mac)"),
+            (3, 1, 0x0407, 256, &german),
+            (3, 1, 0x0409, 256, &english),
+            (3, 1, 0x0409, 257, &german),
+        ]);
+        assert_eq!(lookup(&table, 256).as_deref(), Some("Bold"));
+        assert_eq!(lookup(&table, 257).as_deref(), Some("Fett"));
+        assert_eq!(lookup(&table, 258), None);
+
+        let table = synthesize_table(&[(1, 0, 0, 256, b"Bold (Mac)")]);
+        assert_eq!(lookup(&table, 256).as_deref(), Some("Bold (Mac)"));
+    }
+
+    #[test]
+    fn prefer_requested_language() {
+        let german = utf16("Fett");
+        let british = utf16("Bold (UK)");
+        let english = utf16("Bold");
+        let table = synthesize_table(&[
+            (3, 1, 0x0407, 256, &german),
+            (3, 1, 0x0809, 256, &british),
+            (3, 1, 0x0409, 256, &english),
+            (3, 1, 0x0407, 257, &german),
+            (3, 1, 0x0809, 257, &british),
+        ]);
+        assert_eq!(
+            lookup_for_language(&table, 256, Some("de")).as_deref(),
+            Some("Fett")
+        );
+        assert_eq!(
+            lookup_for_language(&table, 256, Some("fr")).as_deref(),
+            Some("Bold")
+        );
+        assert_eq!(
+            lookup_for_language(&table, 256, Some("en")).as_deref(),
+            Some("Bold")
+        );
+
+        // Without US English, other English is preferred over other languages.
+        assert_eq!(lookup(&table, 257).as_deref(), Some("Bold (UK)"));
+    }
+
+    #[test]
+    fn truncated_table() {
+        let english = utf16("Bold");
+        let table = synthesize_table(&[(3, 1, 0x0409, 256, &english)]);
+        assert_eq!(lookup(&table[..table.len() - 1], 256), None);
+        assert_eq!(lookup(&[], 256), None);
+    }
+}
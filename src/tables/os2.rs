@@ -0,0 +1,139 @@
+// font-kit/src/tables/os2.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The OS/2 and Windows metrics table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/os2
+
+use super::{tag, TableData};
+use crate::metrics::ScriptMetrics;
+
+pub(crate) const TAG: u32 = tag(b"OS/2");
+
+/// Reads `usWeightClass` and `usWidthClass`.
+pub(crate) fn weight_and_width_classes(table: &[u8]) -> Option<(u16, u16)> {
+    let table = TableData(table);
+    Some((table.u16(4)?, table.u16(6)?))
+}
+
+/// Reads `usWinAscent` and `usWinDescent`, returning the descent as a negative value.
+pub(crate) fn win_metrics(table: &[u8]) -> Option<(f32, f32)> {
+    let table = TableData(table);
+    let win_ascent = table.u16(74)?;
+    let win_descent = table.u16(76)?;
+    Some((win_ascent as f32, -(win_descent as f32)))
+}
+
+/// Reads the subscript and superscript sizes and offsets, negating `ySubscriptYOffset` so that
+/// subscripts below the baseline have a negative offset.
+pub(crate) fn script_metrics(table: &[u8]) -> Option<ScriptMetrics> {
+    let table = TableData(table);
+    Some(ScriptMetrics {
+        subscript_x_size: table.i16(10)? as f32,
+        subscript_y_size: table.i16(12)? as f32,
+        subscript_x_offset: table.i16(14)? as f32,
+        subscript_y_offset: -(table.i16(16)? as f32),
+        superscript_x_size: table.i16(18)? as f32,
+        superscript_y_size: table.i16(20)? as f32,
+        superscript_x_offset: table.i16(22)? as f32,
+        superscript_y_offset: table.i16(24)? as f32,
+    })
+}
+
+/// Reads `sxHeight` and `sCapHeight`, which only tables of version 2 and later have.
+pub(crate) fn x_and_cap_heights(table: &[u8]) -> Option<(i16, i16)> {
+    let table = TableData(table);
+    if table.u16(0)? < 2 {
+        return None;
+    }
+    Some((table.i16(86)?, table.i16(88)?))
+}
+
+/// Reads whether the PANOSE classification describes a monospaced font.
+///
+/// Latin text faces, and faces of unspecified family, are monospaced if their proportion is 9.
+/// Latin hand written and symbol faces are monospaced if their spacing is 3. Returns `None` for
+/// other families, which don't classify spacing.
+pub(crate) fn panose_monospaced(table: &[u8]) -> Option<bool> {
+    let table = TableData(table);
+    let family_type = table.u8(32)?;
+    let proportion = table.u8(35)?;
+    match family_type {
+        0 | 2 => Some(proportion == 9),
+        3 | 5 => Some(proportion == 3),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        panose_monospaced, script_metrics, weight_and_width_classes, win_metrics, x_and_cap_heights,
+    };
+
+    #[test]
+    fn weight_and_width() {
+        let mut table = vec![0; 8];
+        table[4..6].copy_from_slice(&700u16.to_be_bytes());
+        table[6..8].copy_from_slice(&3u16.to_be_bytes());
+        assert_eq!(weight_and_width_classes(&table), Some((700, 3)));
+        assert_eq!(weight_and_width_classes(&table[..7]), None);
+    }
+
+    #[test]
+    fn win_ascent_and_descent() {
+        let mut table = vec![0; 78];
+        table[74..76].copy_from_slice(&1900u16.to_be_bytes());
+        table[76..78].copy_from_slice(&500u16.to_be_bytes());
+        assert_eq!(win_metrics(&table), Some((1900.0, -500.0)));
+        assert_eq!(win_metrics(&table[..76]), None);
+    }
+
+    #[test]
+    fn subscript_and_superscript_metrics() {
+        let mut table = vec![0; 26];
+        for (index, value) in [650i16, 600, 0, 75, 650, 600, -10, 350].iter().enumerate() {
+            let offset = 10 + index * 2;
+            table[offset..(offset + 2)].copy_from_slice(&value.to_be_bytes());
+        }
+        let metrics = script_metrics(&table).unwrap();
+        assert_eq!(metrics.subscript_y_size, 600.0);
+        assert_eq!(metrics.subscript_y_offset, -75.0);
+        assert_eq!(metrics.superscript_x_offset, -10.0);
+        assert_eq!(metrics.superscript_y_offset, 350.0);
+        assert!(script_metrics(&table[..25]).is_none());
+    }
+
+    #[test]
+    fn x_and_cap_height() {
+        let mut table = vec![0; 90];
+        table[0..2].copy_from_slice(&2u16.to_be_bytes());
+        table[86..88].copy_from_slice(&457i16.to_be_bytes());
+        table[88..90].copy_from_slice(&623i16.to_be_bytes());
+        assert_eq!(x_and_cap_heights(&table), Some((457, 623)));
+        assert_eq!(x_and_cap_heights(&table[..89]), None);
+        table[0..2].copy_from_slice(&1u16.to_be_bytes());
+        assert_eq!(x_and_cap_heights(&table), None);
+    }
+
+    #[test]
+    fn panose_spacing() {
+        let mut table = vec![0; 42];
+        table[32..36].copy_from_slice(&[2, 11, 6, 9]);
+        assert_eq!(panose_monospaced(&table), Some(true));
+        table[35] = 3;
+        assert_eq!(panose_monospaced(&table), Some(false));
+        table[32] = 5;
+        assert_eq!(panose_monospaced(&table), Some(true));
+        table[32] = 4;
+        assert_eq!(panose_monospaced(&table), None);
+        assert_eq!(panose_monospaced(&table[..35]), None);
+    }
+}
@@ -0,0 +1,38 @@
+// font-kit/src/tables/post.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The PostScript table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/post
+
+use super::{tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"post");
+
+/// Reads the `isFixedPitch` field, which is nonzero if the font is monospaced.
+pub(crate) fn is_fixed_pitch(table: &[u8]) -> Option<bool> {
+    TableData(table)
+        .u32(12)
+        .map(|is_fixed_pitch| is_fixed_pitch != 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_fixed_pitch;
+
+    #[test]
+    fn fixed_pitch_flag() {
+        let mut table = vec![0; 32];
+        assert_eq!(is_fixed_pitch(&table), Some(false));
+        table[15] = 1;
+        assert_eq!(is_fixed_pitch(&table), Some(true));
+        assert_eq!(is_fixed_pitch(&table[..14]), None);
+    }
+}
@@ -0,0 +1,220 @@
+// font-kit/src/tables/stat.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The OpenType style attributes table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/stat
+
+use super::{tag, TableData};
+use crate::properties::StatAxisValue;
+
+pub(crate) const TAG: u32 = tag(b"STAT");
+
+const OLDER_SIBLING_FONT_ATTRIBUTE: u16 = 0x0001;
+const ELIDABLE_AXIS_VALUE_NAME: u16 = 0x0002;
+
+/// Returns the axis values listed in the table, in order.
+///
+/// The `name` field of each value is left empty; see `tables::name::lookup()`. Axis value tables
+/// in unknown formats, or that refer to axes that don't exist, are skipped.
+pub(crate) fn axis_values(table: &[u8]) -> Option<Vec<StatAxisValue>> {
+    let table = TableData(table);
+    if table.u16(0)? != 1 {
+        return None;
+    }
+    let design_axis_size = table.u16(4)? as usize;
+    let design_axis_count = table.u16(6)? as usize;
+    let design_axes_offset = table.u32(8)? as usize;
+    let axis_value_count = table.u16(12)? as usize;
+    let axis_value_offsets_offset = table.u32(14)? as usize;
+
+    let axis_tag = |axis_index: u16| {
+        let axis_index = axis_index as usize;
+        if axis_index >= design_axis_count {
+            return None;
+        }
+        table.u32(design_axes_offset + axis_index * design_axis_size)
+    };
+
+    let mut values = Vec::with_capacity(axis_value_count);
+    for value_index in 0..axis_value_count {
+        let value_offset = axis_value_offsets_offset
+            + table.u16(axis_value_offsets_offset + value_index * 2)? as usize;
+        let format = table.u16(value_offset)?;
+        let (coordinates, range, linked_value) = match format {
+            1..=3 => {
+                let axis_tag = match axis_tag(table.u16(value_offset + 2)?) {
+                    Some(axis_tag) => axis_tag,
+                    None => continue,
+                };
+                let value = table.fixed(value_offset + 8)?;
+                let range = if format == 2 {
+                    Some((
+                        table.fixed(value_offset + 12)?,
+                        table.fixed(value_offset + 16)?,
+                    ))
+                } else {
+                    None
+                };
+                let linked_value = if format == 3 {
+                    Some(table.fixed(value_offset + 12)?)
+                } else {
+                    None
+                };
+                (vec![(axis_tag, value)], range, linked_value)
+            }
+            4 => {
+                let axis_count = table.u16(value_offset + 2)? as usize;
+                let mut coordinates = Vec::with_capacity(axis_count);
+                for axis in 0..axis_count {
+                    let record_offset = value_offset + 8 + axis * 6;
+                    if let Some(axis_tag) = axis_tag(table.u16(record_offset)?) {
+                        coordinates.push((axis_tag, table.fixed(record_offset + 2)?));
+                    }
+                }
+                (coordinates, None, None)
+            }
+            _ => continue,
+        };
+
+        // Every format stores the flags and name ID in the same place.
+        let flags = table.u16(value_offset + 4)?;
+        values.push(StatAxisValue {
+            coordinates,
+            range,
+            linked_value,
+            name_id: table.u16(value_offset + 6)?,
+            name: None,
+            elidable: flags & ELIDABLE_AXIS_VALUE_NAME != 0,
+            older_sibling_font_attribute: flags & OLDER_SIBLING_FONT_ATTRIBUTE != 0,
+        });
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::axis_values;
+    use crate::tables::tag;
+
+    fn fixed(value: f32) -> [u8; 4] {
+        ((value * 65536.0) as i32).to_be_bytes()
+    }
+
+    // A table with `wght` and `wdth` axes and one axis value table of each format.
+    fn synthesize_table() -> Vec<u8> {
+        let mut axis_value_tables: Vec<Vec<u8>> = vec![];
+
+        // Format 1: wght 400 "Regular", elidable.
+        let mut value = vec![];
+        value.extend_from_slice(&1u16.to_be_bytes());
+        value.extend_from_slice(&0u16.to_be_bytes());
+        value.extend_from_slice(&2u16.to_be_bytes());
+        value.extend_from_slice(&258u16.to_be_bytes());
+        value.extend_from_slice(&fixed(400.0));
+        axis_value_tables.push(value);
+
+        // Format 2: wdth 75 "Condensed", covering 62.5 to 87.5.
+        let mut value = vec![];
+        value.extend_from_slice(&2u16.to_be_bytes());
+        value.extend_from_slice(&1u16.to_be_bytes());
+        value.extend_from_slice(&0u16.to_be_bytes());
+        value.extend_from_slice(&259u16.to_be_bytes());
+        for position in [75.0, 62.5, 87.5] {
+            value.extend_from_slice(&fixed(position));
+        }
+        axis_value_tables.push(value);
+
+        // Format 3: wght 400 linked to 700.
+        let mut value = vec![];
+        value.extend_from_slice(&3u16.to_be_bytes());
+        value.extend_from_slice(&0u16.to_be_bytes());
+        value.extend_from_slice(&0u16.to_be_bytes());
+        value.extend_from_slice(&258u16.to_be_bytes());
+        value.extend_from_slice(&fixed(400.0));
+        value.extend_from_slice(&fixed(700.0));
+        axis_value_tables.push(value);
+
+        // Format 4: wght 700 and wdth 75 "Bold Condensed".
+        let mut value = vec![];
+        value.extend_from_slice(&4u16.to_be_bytes());
+        value.extend_from_slice(&2u16.to_be_bytes());
+        value.extend_from_slice(&0u16.to_be_bytes());
+        value.extend_from_slice(&260u16.to_be_bytes());
+        value.extend_from_slice(&0u16.to_be_bytes());
+        value.extend_from_slice(&fixed(700.0));
+        value.extend_from_slice(&1u16.to_be_bytes());
+        value.extend_from_slice(&fixed(75.0));
+        axis_value_tables.push(value);
+
+        // An unknown format, which should be skipped.
+        axis_value_tables.push(vec![0, 9, 0, 0, 0, 0, 0, 0]);
+
+        let design_axes_offset = 20u32;
+        let axis_value_offsets_offset = design_axes_offset + 2 * 8;
+
+        let mut table = vec![];
+        table.extend_from_slice(&1u16.to_be_bytes()); // majorVersion
+        table.extend_from_slice(&1u16.to_be_bytes()); // minorVersion
+        table.extend_from_slice(&8u16.to_be_bytes()); // designAxisSize
+        table.extend_from_slice(&2u16.to_be_bytes()); // designAxisCount
+        table.extend_from_slice(&design_axes_offset.to_be_bytes());
+        table.extend_from_slice(&(axis_value_tables.len() as u16).to_be_bytes());
+        table.extend_from_slice(&axis_value_offsets_offset.to_be_bytes());
+        table.extend_from_slice(&2u16.to_be_bytes()); // elidedFallbackNameID
+
+        for (ordering, axis_tag) in [b"wght", b"wdth"].iter().enumerate() {
+            table.extend_from_slice(*axis_tag);
+            table.extend_from_slice(&256u16.to_be_bytes());
+            table.extend_from_slice(&(ordering as u16).to_be_bytes());
+        }
+
+        let mut offset = axis_value_tables.len() * 2;
+        for value in &axis_value_tables {
+            table.extend_from_slice(&(offset as u16).to_be_bytes());
+            offset += value.len();
+        }
+        for value in &axis_value_tables {
+            table.extend_from_slice(value);
+        }
+        table
+    }
+
+    #[test]
+    fn parse_axis_values() {
+        let values = axis_values(&synthesize_table()).unwrap();
+        assert_eq!(values.len(), 4);
+
+        assert_eq!(values[0].coordinates, [(tag(b"wght"), 400.0)]);
+        assert_eq!(values[0].name_id, 258);
+        assert!(values[0].elidable);
+        assert!(!values[0].older_sibling_font_attribute);
+
+        assert_eq!(values[1].coordinates, [(tag(b"wdth"), 75.0)]);
+        assert_eq!(values[1].range, Some((62.5, 87.5)));
+        assert!(!values[1].elidable);
+
+        assert_eq!(values[2].linked_value, Some(700.0));
+
+        assert_eq!(
+            values[3].coordinates,
+            [(tag(b"wght"), 700.0), (tag(b"wdth"), 75.0)]
+        );
+        assert_eq!(values[3].name_id, 260);
+        assert!(values.iter().all(|value| value.name.is_none()));
+    }
+
+    #[test]
+    fn truncated_table() {
+        let table = synthesize_table();
+        assert_eq!(axis_values(&table[..16]), None);
+        assert_eq!(axis_values(&[]), None);
+    }
+}
@@ -0,0 +1,121 @@
+// font-kit/src/tables/trak.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The AAT tracking table.
+//!
+//! See: https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6trak.html
+
+use super::{tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"trak");
+
+/// Returns the horizontal tracking value of the "normal" track (track value 0) at `point_size`,
+/// in font units.
+///
+/// Values between the listed sizes are linearly interpolated; sizes outside the listed range use
+/// the value of the nearest entry.
+pub(crate) fn tracking(table: &[u8], point_size: f32) -> Option<f32> {
+    let table = TableData(table);
+    if table.u16(4)? != 0 {
+        return None;
+    }
+    let horiz_offset = table.u16(6)? as usize;
+    if horiz_offset == 0 {
+        return None;
+    }
+
+    let track_count = table.u16(horiz_offset)? as usize;
+    let size_count = table.u16(horiz_offset + 2)? as usize;
+    let size_table_offset = table.u32(horiz_offset + 4)? as usize;
+    if size_count == 0 {
+        return None;
+    }
+
+    let mut values_offset = None;
+    for track_index in 0..track_count {
+        let entry_offset = horiz_offset + 8 + track_index * 8;
+        if table.fixed(entry_offset)? == 0.0 {
+            values_offset = Some(table.u16(entry_offset + 6)? as usize);
+            break;
+        }
+    }
+    let values_offset = values_offset?;
+
+    let size = |index: usize| table.fixed(size_table_offset + index * 4);
+    let value = |index: usize| {
+        table
+            .i16(values_offset + index * 2)
+            .map(|value| value as f32)
+    };
+
+    if point_size <= size(0)? {
+        return value(0);
+    }
+    for index in 1..size_count {
+        let (size_0, size_1) = (size(index - 1)?, size(index)?);
+        if point_size <= size_1 {
+            if size_1 <= size_0 {
+                return value(index);
+            }
+            let t = (point_size - size_0) / (size_1 - size_0);
+            return Some(value(index - 1)? + (value(index)? - value(index - 1)?) * t);
+        }
+    }
+    value(size_count - 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::tracking;
+
+    // A horizontal-only table with a single normal track and the sizes 9, 12, and 24 points.
+    fn synthesize_table(values: [i16; 3]) -> Vec<u8> {
+        let mut table = vec![];
+        table.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // version
+        table.extend_from_slice(&0u16.to_be_bytes()); // format
+        table.extend_from_slice(&12u16.to_be_bytes()); // horizOffset
+        table.extend_from_slice(&0u16.to_be_bytes()); // vertOffset
+        table.extend_from_slice(&0u16.to_be_bytes()); // reserved
+
+        // Track data: one track entry, three sizes. The size table follows the track entry
+        // at 12 + 8 + 8 = 28, and the per-size values follow the size table at 28 + 12 = 40.
+        table.extend_from_slice(&1u16.to_be_bytes());
+        table.extend_from_slice(&3u16.to_be_bytes());
+        table.extend_from_slice(&28u32.to_be_bytes());
+        table.extend_from_slice(&0i32.to_be_bytes()); // track
+        table.extend_from_slice(&256u16.to_be_bytes()); // nameIndex
+        table.extend_from_slice(&40u16.to_be_bytes()); // offset
+        for size in [9i32, 12, 24] {
+            table.extend_from_slice(&(size << 16).to_be_bytes());
+        }
+        for value in values {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        table
+    }
+
+    #[test]
+    fn interpolate_tracking() {
+        let table = synthesize_table([30, 0, -60]);
+        assert_eq!(tracking(&table, 9.0), Some(30.0));
+        assert_eq!(tracking(&table, 10.5), Some(15.0));
+        assert_eq!(tracking(&table, 12.0), Some(0.0));
+        assert_eq!(tracking(&table, 18.0), Some(-30.0));
+        assert_eq!(tracking(&table, 6.0), Some(30.0));
+        assert_eq!(tracking(&table, 72.0), Some(-60.0));
+    }
+
+    #[test]
+    fn truncated_table() {
+        let table = synthesize_table([30, 0, -60]);
+        assert_eq!(tracking(&table[..30], 12.0), None);
+        assert_eq!(tracking(&[], 12.0), None);
+    }
+}
@@ -0,0 +1,87 @@
+// font-kit/src/tables/vdmx.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The vertical device metrics table.
+//!
+//! See: https://learn.microsoft.com/en-us/typography/opentype/spec/vdmx
+
+use super::{tag, TableData};
+
+pub(crate) const TAG: u32 = tag(b"VDMX");
+
+/// Returns the maximum and minimum y values of the glyphs at `pixels_per_em`, in pixels, for a
+/// display with square pixels.
+///
+/// The first aspect ratio record that covers a 1:1 ratio is used. Returns `None` if there is no
+/// such record or its group has no entry for the size.
+pub(crate) fn metrics(table: &[u8], pixels_per_em: u16) -> Option<(i16, i16)> {
+    let table = TableData(table);
+    let ratio_count = table.u16(4)? as usize;
+    let ratio_index = (0..ratio_count).find(|&ratio_index| {
+        let ratio_offset = 6 + ratio_index * 4;
+        let (x_ratio, y_start_ratio, y_end_ratio) = (
+            table.u8(ratio_offset + 1).unwrap_or(0),
+            table.u8(ratio_offset + 2).unwrap_or(0),
+            table.u8(ratio_offset + 3).unwrap_or(0),
+        );
+        // A record with all ratios zero is the default, which matches any display.
+        x_ratio == 0 || (y_start_ratio..=y_end_ratio).contains(&x_ratio)
+    })?;
+    let group_offset = table.u16(6 + ratio_count * 4 + ratio_index * 2)? as usize;
+
+    let entry_count = table.u16(group_offset)? as usize;
+    let (start_size, end_size) = (table.u8(group_offset + 2)?, table.u8(group_offset + 3)?);
+    if pixels_per_em < start_size as u16 || pixels_per_em > end_size as u16 {
+        return None;
+    }
+    for entry_index in 0..entry_count {
+        let entry_offset = group_offset + 4 + entry_index * 6;
+        if table.u16(entry_offset)? == pixels_per_em {
+            return Some((table.i16(entry_offset + 2)?, table.i16(entry_offset + 4)?));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::metrics;
+
+    fn push_u16s(table: &mut Vec<u8>, values: &[u16]) {
+        for value in values {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    #[test]
+    fn square_pixel_metrics() {
+        let mut table = vec![];
+        // Two ratios: 2:1 only, then the default.
+        push_u16s(&mut table, &[1, 2, 2]);
+        table.extend_from_slice(&[1, 2, 1, 1, 0, 0, 0, 0]);
+        push_u16s(&mut table, &[18, 28]);
+        // The 2:1 group at 18, with one entry at 12 pixels per em.
+        push_u16s(&mut table, &[1]);
+        table.extend_from_slice(&[12, 12]);
+        push_u16s(&mut table, &[12, 20, (-10i16) as u16]);
+        // The default group at 28, with entries at 12 and 13 pixels per em.
+        push_u16s(&mut table, &[2]);
+        table.extend_from_slice(&[12, 13]);
+        push_u16s(
+            &mut table,
+            &[12, 10, (-3i16) as u16, 13, 11, (-4i16) as u16],
+        );
+
+        assert_eq!(metrics(&table, 12), Some((10, -3)));
+        assert_eq!(metrics(&table, 13), Some((11, -4)));
+        assert_eq!(metrics(&table, 14), None);
+        assert_eq!(metrics(&table[..36], 13), None);
+    }
+}
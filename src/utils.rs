@@ -13,7 +13,7 @@
 #![allow(dead_code)]
 
 use std::fs::File;
-use std::io::{Error as IOError, Read};
+use std::io::{Error as IOError, Read, Seek, SeekFrom};
 
 pub(crate) static SFNT_VERSIONS: [[u8; 4]; 4] = [
     [0x00, 0x01, 0x00, 0x00],
@@ -39,7 +39,19 @@ pub(crate) fn lerp(a: f32, b: f32, t: f32) -> f32 {
 
 #[inline]
 pub(crate) fn div_round_up(a: usize, b: usize) -> usize {
-    (a + b - 1) / b
+    a.div_ceil(b)
+}
+
+pub(crate) fn slurp_stream<R>(stream: &mut R) -> Result<Vec<u8>, IOError>
+where
+    R: Read + Seek,
+{
+    // The buffer isn't sized from the length that the stream reports, since a corrupt or hostile
+    // archive can claim any length it likes. It grows only as data is actually read.
+    stream.seek(SeekFrom::Start(0))?;
+    let mut data = vec![];
+    stream.read_to_end(&mut data)?;
+    Ok(data)
 }
 
 pub(crate) fn slurp_file(file: &mut File) -> Result<Vec<u8>, IOError> {
@@ -50,3 +62,64 @@ pub(crate) fn slurp_file(file: &mut File) -> Result<Vec<u8>, IOError> {
     file.read_to_end(&mut data)?;
     Ok(data)
 }
+
+/// Returns the ISO 639 code of the user's preferred language, such as `"de"`, or `None` if it
+/// can't be determined.
+pub(crate) fn system_language() -> Option<String> {
+    let locale = preferred_locale()?;
+    let language = locale.split(['-', '_', '.', '@']).next()?;
+    // This also rules out the "C" and "POSIX" locales.
+    if (2..=3).contains(&language.len()) && language.bytes().all(|byte| byte.is_ascii_alphabetic())
+    {
+        Some(language.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+#[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "coretext"))]
+fn preferred_locale() -> Option<String> {
+    use core_foundation::array::{CFArray, CFArrayRef};
+    use core_foundation::base::TCFType;
+    use core_foundation::string::CFString;
+
+    // Not exposed by the `core-foundation` crate.
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFLocaleCopyPreferredLanguages() -> CFArrayRef;
+    }
+
+    let languages: CFArray<CFString> =
+        unsafe { CFArray::wrap_under_create_rule(CFLocaleCopyPreferredLanguages()) };
+    let language = languages.get(0)?;
+    Some(language.to_string())
+}
+
+#[cfg(target_family = "windows")]
+fn preferred_locale() -> Option<String> {
+    use winapi::um::winnls::GetUserDefaultLocaleName;
+    use winapi::um::winnt::LOCALE_NAME_MAX_LENGTH;
+
+    let mut locale_name = [0; LOCALE_NAME_MAX_LENGTH];
+    let length =
+        unsafe { GetUserDefaultLocaleName(locale_name.as_mut_ptr(), locale_name.len() as i32) };
+    // The length includes the terminating null.
+    if length <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(
+        &locale_name[..(length as usize - 1)],
+    ))
+}
+
+#[cfg(not(any(
+    target_family = "windows",
+    all(any(target_os = "macos", target_os = "ios"), feature = "coretext")
+)))]
+fn preferred_locale() -> Option<String> {
+    // These are consulted in the order that POSIX gives them precedence for messages.
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
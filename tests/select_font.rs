@@ -13,11 +13,11 @@ extern crate font_kit;
 use font_kit::error::SelectionError;
 use font_kit::family_name::FamilyName;
 use font_kit::handle::Handle;
-use font_kit::properties::Properties;
+use font_kit::properties::{Properties, Weight};
 use std::ffi::OsStr;
 
 #[cfg(feature = "source")]
-use font_kit::source::SystemSource;
+use font_kit::source::{SystemSource, UiFontKind};
 
 #[cfg(all(feature = "source", any(target_os = "windows", target_os = "macos")))]
 macro_rules! match_handle {
@@ -172,6 +172,82 @@ mod test {
         }
     }
 
+    #[test]
+    fn select_monospace_fonts() {
+        let handles = SystemSource::new().select_monospace_fonts().unwrap();
+        assert!(!handles.is_empty());
+        let mut family_names = vec![];
+        for handle in &handles {
+            let font = handle.load().unwrap();
+            assert!(font.is_monospace());
+            family_names.push(font.family_name());
+        }
+        let family_count = family_names.len();
+        family_names.sort();
+        family_names.dedup();
+        assert_eq!(family_names.len(), family_count);
+    }
+
+    #[test]
+    fn select_generic_family_for_language() {
+        let source = SystemSource::new();
+        let family_name_for_language = |language| {
+            let family_handle = source
+                .select_family_by_generic_name_for_language(&FamilyName::SansSerif, language)
+                .unwrap();
+            family_handle.fonts()[0].load().unwrap().family_name()
+        };
+        let default_family_name = family_name_for_language("en");
+        let simplified_chinese_family_name = family_name_for_language("zh-Hans");
+        let japanese_family_name = family_name_for_language("ja");
+
+        // Skip the comparison if no fonts for these languages are installed.
+        if simplified_chinese_family_name != default_family_name
+            && japanese_family_name != default_family_name
+        {
+            assert_ne!(simplified_chinese_family_name, japanese_family_name);
+        }
+
+        assert!(source
+            .select_best_match_for_language(&[FamilyName::SansSerif], &Properties::new(), "ja")
+            .is_ok());
+    }
+
+    #[test]
+    fn select_default_ui_font() {
+        let source = SystemSource::new();
+        for kind in [
+            UiFontKind::System,
+            UiFontKind::Monospace,
+            UiFontKind::SmallCaption,
+        ] {
+            let handle = source
+                .default_ui_font_of_kind(kind, &Properties::default())
+                .unwrap();
+            let font = handle.load().unwrap();
+            assert!(!font.family_name().is_empty());
+        }
+        let regular = source.default_ui_font(&Properties::default()).unwrap();
+        let bold = source
+            .default_ui_font(Properties::new().weight(Weight::BOLD))
+            .unwrap();
+        assert_eq!(
+            bold.load().unwrap().family_name(),
+            regular.load().unwrap().family_name()
+        );
+    }
+
+    #[test]
+    fn select_by_postscript_name_in_collection() {
+        // Cambria Math is the second face of `cambria.ttc`.
+        let handle = SystemSource::new()
+            .select_by_postscript_name("CambriaMath")
+            .unwrap();
+        match_handle!(handle, "C:\\WINDOWS\\FONTS\\cambria.ttc", 1);
+        let font = handle.load().unwrap();
+        assert_eq!(font.postscript_name().unwrap(), "CambriaMath");
+    }
+
     // This test fails on TravisCI's Windows environment.
     #[test]
     #[ignore]
@@ -243,6 +319,18 @@ mod test {
         check_filename(&handle, "DejaVuSans.ttf");
     }
 
+    #[test]
+    fn select_monospace_fonts_dejavu() {
+        let family_names: Vec<String> = SystemSource::new()
+            .select_monospace_fonts()
+            .unwrap()
+            .iter()
+            .map(|handle| handle.load().unwrap().family_name())
+            .collect();
+        assert!(family_names.iter().any(|name| name == "DejaVu Sans Mono"));
+        assert!(!family_names.iter().any(|name| name == "DejaVu Sans"));
+    }
+
     #[test]
     fn select_family_by_name_dejavu() {
         let family = SystemSource::new()
@@ -281,6 +369,16 @@ mod test {
         assert_eq!(font.postscript_name().unwrap(), "DejaVuSans");
     }
 
+    #[test]
+    fn select_by_postscript_name_with_variations_of_static_font() {
+        let selection = SystemSource::new()
+            .select_by_postscript_name_with_variations("DejaVuSans")
+            .unwrap();
+        assert!(selection.variations.is_empty());
+        let font = selection.handle.load().unwrap();
+        assert_eq!(font.postscript_name().unwrap(), "DejaVuSans");
+    }
+
     #[test]
     fn select_by_postscript_name_invalid() {
         match SystemSource::new().select_by_postscript_name("zxhjfgkadsfhg") {
@@ -289,6 +387,85 @@ mod test {
         }
     }
 
+    #[test]
+    fn select_monospace_fonts() {
+        let handles = SystemSource::new().select_monospace_fonts().unwrap();
+        assert!(!handles.is_empty());
+        let mut family_names = vec![];
+        for handle in &handles {
+            let font = handle.load().unwrap();
+            assert!(font.is_monospace());
+            family_names.push(font.family_name());
+        }
+        let family_count = family_names.len();
+        family_names.sort();
+        family_names.dedup();
+        assert_eq!(family_names.len(), family_count);
+    }
+
+    #[test]
+    fn select_generic_family_for_language() {
+        // Which families serve which languages depends on the installed fonts, so just check that
+        // language-sensitive selection always resolves generic families.
+        let source = SystemSource::new();
+        for language in ["en", "zh-Hans", "zh-Hant", "ja", "ko"] {
+            let family_handle = source
+                .select_family_by_generic_name_for_language(&FamilyName::SansSerif, language)
+                .unwrap();
+            assert!(!family_handle.is_empty());
+        }
+
+        assert!(source
+            .select_best_match_for_language(&[FamilyName::SansSerif], &Properties::new(), "ja")
+            .is_ok());
+    }
+
+    #[test]
+    fn select_default_ui_font() {
+        let source = SystemSource::new();
+        for kind in [
+            UiFontKind::System,
+            UiFontKind::Monospace,
+            UiFontKind::SmallCaption,
+        ] {
+            let handle = source
+                .default_ui_font_of_kind(kind, &Properties::default())
+                .unwrap();
+            let font = handle.load().unwrap();
+            assert!(!font.family_name().is_empty());
+        }
+        let regular = source.default_ui_font(&Properties::default()).unwrap();
+        let bold = source
+            .default_ui_font(Properties::new().weight(Weight::BOLD))
+            .unwrap();
+        assert_eq!(
+            bold.load().unwrap().family_name(),
+            regular.load().unwrap().family_name()
+        );
+    }
+
+    #[test]
+    fn select_default_ui_font_with_properties() {
+        use font_kit::properties::Style;
+
+        let source = SystemSource::new();
+        let regular = source
+            .select_default_ui_font(&Properties::default())
+            .unwrap()
+            .load()
+            .unwrap();
+        let bold_italic = source
+            .select_default_ui_font(Properties::new().weight(Weight::BOLD).style(Style::Italic))
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(bold_italic.family_name(), regular.family_name());
+        assert_eq!(regular.properties().weight, Weight::NORMAL);
+        assert_eq!(regular.properties().style, Style::Normal);
+        assert_eq!(bold_italic.properties().weight, Weight::BOLD);
+        assert_ne!(bold_italic.properties().style, Style::Normal);
+    }
+
     #[test]
     fn select_localized_family_name() {
         if let Ok(handle) = SystemSource::new().select_best_match(
@@ -403,6 +580,71 @@ mod test {
         }
     }
 
+    #[test]
+    fn select_monospace_fonts() {
+        let handles = SystemSource::new().select_monospace_fonts().unwrap();
+        assert!(!handles.is_empty());
+        let mut family_names = vec![];
+        for handle in &handles {
+            let font = handle.load().unwrap();
+            assert!(font.is_monospace());
+            family_names.push(font.family_name());
+        }
+        let family_count = family_names.len();
+        family_names.sort();
+        family_names.dedup();
+        assert_eq!(family_names.len(), family_count);
+    }
+
+    #[test]
+    fn select_generic_family_for_language() {
+        let source = SystemSource::new();
+        let family_name_for_language = |language| {
+            let family_handle = source
+                .select_family_by_generic_name_for_language(&FamilyName::SansSerif, language)
+                .unwrap();
+            family_handle.fonts()[0].load().unwrap().family_name()
+        };
+        let default_family_name = family_name_for_language("en");
+        let simplified_chinese_family_name = family_name_for_language("zh-Hans");
+        let japanese_family_name = family_name_for_language("ja");
+
+        // Skip the comparison if no fonts for these languages are installed.
+        if simplified_chinese_family_name != default_family_name
+            && japanese_family_name != default_family_name
+        {
+            assert_ne!(simplified_chinese_family_name, japanese_family_name);
+        }
+
+        assert!(source
+            .select_best_match_for_language(&[FamilyName::SansSerif], &Properties::new(), "ja")
+            .is_ok());
+    }
+
+    #[test]
+    fn select_default_ui_font() {
+        let source = SystemSource::new();
+        for kind in [
+            UiFontKind::System,
+            UiFontKind::Monospace,
+            UiFontKind::SmallCaption,
+        ] {
+            let handle = source
+                .default_ui_font_of_kind(kind, &Properties::default())
+                .unwrap();
+            let font = handle.load().unwrap();
+            assert!(!font.family_name().is_empty());
+        }
+        let regular = source.default_ui_font(&Properties::default()).unwrap();
+        let bold = source
+            .default_ui_font(Properties::new().weight(Weight::BOLD))
+            .unwrap();
+        assert_eq!(
+            bold.load().unwrap().family_name(),
+            regular.load().unwrap().family_name()
+        );
+    }
+
     #[test]
     fn select_localized_family_name() {
         let handle = SystemSource::new()
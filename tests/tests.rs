@@ -10,22 +10,50 @@
 
 // General tests.
 
-use font_kit::canvas::{Canvas, Format, RasterizationOptions};
+use font_kit::canvas::{
+    AlphaMode, BlendSpace, Canvas, Channel, Format, GlyphSource, RasterizationOptions,
+    RenderSettings, SubpixelGeometry,
+};
+use font_kit::color::{
+    ColorLayer, ColorU, PaletteChoice, PaletteSelector, FOREGROUND_PALETTE_INDEX,
+};
+use font_kit::error::{CanvasError, FontLoadingError, GlyphLoadingError, PlatformErrorCode};
 use font_kit::family_name::FamilyName;
-use font_kit::file_type::FileType;
+use font_kit::file_type::{self, FileType, FontFormat};
 use font_kit::font::Font;
-use font_kit::hinting::HintingOptions;
-use font_kit::outline::{Contour, Outline, OutlineBuilder, PointFlags};
-use font_kit::properties::{Properties, Stretch, Weight};
+use font_kit::handle::Handle;
+use font_kit::hinting::{GaspBehavior, HintingOptions};
+use font_kit::loader::{PitchInfo, ShapingTables, VariationCoordinate};
+use font_kit::matching;
+use font_kit::metrics::{CaretMetrics, MetricsSource, ScriptMetrics};
+use font_kit::outline::{Contour, ImpliedPoints, Outline, OutlineBuilder, PointFlags};
+use font_kit::properties::{Properties, PropertiesQuery, Stretch, Style, Weight};
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
-use std::fs::File;
-use std::io::Read;
+use std::cmp;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::iter;
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
 
 #[cfg(feature = "source")]
-use font_kit::source::SystemSource;
+use font_kit::error::SelectionError;
+#[cfg(feature = "source")]
+use font_kit::family_handle::FamilyHandle;
+#[cfg(feature = "source")]
+use font_kit::source::{MatchQuality, Source, SystemSource};
+#[cfg(feature = "source")]
+use font_kit::sources::fallback_cache::FallbackCacheSource;
+#[cfg(feature = "source")]
+use font_kit::sources::mem::MemSource;
+#[cfg(feature = "source")]
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 static TEST_FONT_FILE_PATH: &str = "resources/tests/eb-garamond/EBGaramond12-Regular.otf";
 static TEST_FONT_POSTSCRIPT_NAME: &str = "EBGaramond12-Regular";
@@ -34,6 +62,8 @@ static TEST_FONT_COLLECTION_POSTSCRIPT_NAME: [&str; 2] =
     ["EBGaramond12-Regular", "EBGaramond12-Italic"];
 
 static FILE_PATH_EB_GARAMOND_TTF: &str = "resources/tests/eb-garamond/EBGaramond12-Regular.ttf";
+static FILE_PATH_EB_GARAMOND_ITALIC_OTF: &str =
+    "resources/tests/eb-garamond/EBGaramond12-Italic.otf";
 static FILE_PATH_INCONSOLATA_TTF: &str = "resources/tests/inconsolata/Inconsolata-Regular.ttf";
 
 #[cfg(not(target_os = "linux"))]
@@ -41,6 +71,7 @@ static KNOWN_SYSTEM_FONT_NAME: &'static str = "Arial";
 #[cfg(target_os = "linux")]
 static KNOWN_SYSTEM_FONT_NAME: &str = "DejaVu Sans";
 
+#[allow(dead_code)]
 static SFNT_VERSIONS: [[u8; 4]; 4] = [
     [0x00, 0x01, 0x00, 0x00],
     [b'O', b'T', b'T', b'O'],
@@ -80,6 +111,121 @@ pub fn get_font_full_name_from_lowercase_family_name() {
     assert_eq!(font.full_name(), KNOWN_SYSTEM_FONT_NAME);
 }
 
+#[test]
+pub fn read_device_metrics() {
+    // Glyph 1 is 7 pixels wide at 12 pixels per em, rather than the 12 its advance of an em scales
+    // to.
+    let mut hdmx = vec![];
+    hdmx.extend(0u16.to_be_bytes());
+    hdmx.extend(1u16.to_be_bytes());
+    hdmx.extend(4u32.to_be_bytes());
+    hdmx.extend([12, 7, 0, 7]);
+    let mut vdmx = vec![];
+    for value in [1u16, 1, 1] {
+        vdmx.extend(value.to_be_bytes());
+    }
+    vdmx.extend([1, 0, 0, 0]);
+    for value in [12u16, 1] {
+        vdmx.extend(value.to_be_bytes());
+    }
+    vdmx.extend([12, 12]);
+    for value in [12i16, 10, -3] {
+        vdmx.extend(value.to_be_bytes());
+    }
+    let data = synthesize_font_with_tables(
+        1000,
+        &[(0x41, 1)],
+        &[],
+        vec![(b"hdmx", hdmx), (b"VDMX", vdmx)],
+    );
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+
+    // The tables win at the sizes that they cover, and the scaled values apply elsewhere.
+    assert_eq!(font.hdmx_advance(1, 12), Some(7));
+    assert_eq!(font.hdmx_advance(1, 13), None);
+    assert_eq!(font.hdmx_advance(2, 12), None);
+    assert_eq!(font.gdi_compatible_advance(1, 12).unwrap(), 7.0);
+    assert_eq!(font.gdi_compatible_advance(1, 13).unwrap(), 13.0);
+    assert_eq!(font.vdmx_metrics(12), Some((10, -3)));
+    assert_eq!(font.vdmx_metrics(13), None);
+
+    // Fonts without the tables have no device metrics.
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('A').unwrap();
+    assert_eq!(font.hdmx_advance(glyph_id, 12), None);
+    assert_eq!(font.vdmx_metrics(12), None);
+    let advance = font.advance(glyph_id).unwrap().x();
+    let scale = font.metrics().scale_for(12.0);
+    assert_eq!(
+        font.gdi_compatible_advance(glyph_id, 12).unwrap(),
+        scale.to_px(advance).round()
+    );
+}
+
+#[test]
+pub fn get_name_strings() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    assert_eq!(font.name_string(5).as_deref(), Some("Version 2.001"));
+    assert_eq!(font.name_string(9).as_deref(), Some("Raph Levien, Cyreal"));
+    assert_eq!(
+        font.name_string(3).as_deref(),
+        Some("2.001;CYRE;Inconsolata-Regular")
+    );
+    assert_eq!(font.name_string(300), None);
+
+    // The convenience accessors agree with the `name` table.
+    assert_eq!(font.full_name(), font.name_string(4).unwrap());
+    assert_eq!(font.postscript_name(), font.name_string(6));
+}
+
+// The convenience names are in English whatever the system locale, which only `name_string()`
+// follows. Elsewhere the locale comes from the system rather than the environment.
+#[cfg(not(any(target_os = "macos", target_os = "ios", target_family = "windows")))]
+#[test]
+pub fn get_full_name_independently_of_locale() {
+    use std::env;
+
+    let names = [
+        (0x0407, 4, "Synthetisch Normal"),
+        (0x0409, 1, "Synthesized"),
+        (0x0409, 2, "Regular"),
+        (0x0409, 4, "Synthesized Regular"),
+        (0x0409, 6, "Synthesized-Regular"),
+    ];
+    let mut name: Vec<u8> = [0, names.len() as u16, 6 + 12 * names.len() as u16]
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect();
+    let mut strings = vec![];
+    for &(language_id, name_id, string) in &names {
+        let string: Vec<u8> = string.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        for value in [
+            3,
+            1,
+            language_id,
+            name_id,
+            string.len() as u16,
+            strings.len() as u16,
+        ] {
+            name.extend(value.to_be_bytes());
+        }
+        strings.extend(string);
+    }
+    name.extend(strings);
+    let data = synthesize_font_with_tables(1000, &[(0x41, 1)], &[b"name"], vec![(b"name", name)]);
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+
+    let locale = env::var_os("LC_ALL");
+    env::set_var("LC_ALL", "de_DE.UTF-8");
+    let (full_name, localized_full_name) = (font.full_name(), font.name_string(4));
+    match locale {
+        Some(locale) => env::set_var("LC_ALL", locale),
+        None => env::remove_var("LC_ALL"),
+    }
+    assert_eq!(full_name, "Synthesized Regular");
+    assert_eq!(localized_full_name.as_deref(), Some("Synthetisch Normal"));
+}
+
 #[test]
 pub fn load_font_from_file() {
     let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
@@ -96,6 +242,272 @@ pub fn load_font_from_memory() {
     assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
 }
 
+#[test]
+pub fn load_font_from_stream() {
+    let font_data = fs::read(TEST_FONT_FILE_PATH).unwrap();
+    let mut cursor = Cursor::new(font_data.clone());
+    cursor.seek(SeekFrom::End(0)).unwrap();
+    let font = Font::from_stream(&mut cursor, 0).unwrap();
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+
+    // A font stored inside a zip archive, between two other entries.
+    let mut writer = ZipWriter::new(Cursor::new(vec![]));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    writer.start_file("README", options).unwrap();
+    writer.write_all(&[0xaa; 100]).unwrap();
+    writer.start_file("fonts/font.ttf", options).unwrap();
+    writer.write_all(&font_data).unwrap();
+    writer.start_file("LICENSE", options).unwrap();
+    writer.write_all(&[0xbb; 100]).unwrap();
+    let mut archive = ZipArchive::new(writer.finish().unwrap()).unwrap();
+    let mut entry = archive.by_name_seek("fonts/font.ttf").unwrap();
+    entry.seek(SeekFrom::Start(1000)).unwrap();
+    let font = Font::from_stream(&mut entry, 0).unwrap();
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+}
+
+#[test]
+pub fn load_font_from_missing_path() {
+    let path = "resources/tests/does-not-exist.ttf";
+    let error = Font::from_path(path, 0).unwrap_err();
+    assert!(matches!(error, FontLoadingError::Io(_)));
+    assert!(error.to_string().contains(path));
+
+    let source = error.source().unwrap();
+    let io_error = source.downcast_ref::<io::Error>().unwrap();
+    assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+
+    let error = Font::analyze_path(path).unwrap_err();
+    assert!(error.to_string().contains(path));
+}
+
+#[test]
+pub fn errors_are_thread_safe() {
+    fn assert_error<E: Error + Send + Sync + 'static>() {}
+    assert_error::<FontLoadingError>();
+    assert_error::<GlyphLoadingError>();
+    #[cfg(feature = "source")]
+    assert_error::<SelectionError>();
+}
+
+#[test]
+pub fn glyph_loading_errors_keep_platform_error_codes() {
+    let error = GlyphLoadingError::PlatformErrorCode(PlatformErrorCode(0x8007_0002u32 as i32));
+    assert!(error.to_string().contains("0x80070002"));
+    let source = error.source().unwrap();
+    assert_eq!(
+        source.downcast_ref::<PlatformErrorCode>(),
+        Some(&PlatformErrorCode(0x8007_0002u32 as i32))
+    );
+    assert!(GlyphLoadingError::NoSuchGlyph.source().is_none());
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn selection_errors_keep_their_io_source() {
+    let error = SelectionError::from(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+    assert!(error.to_string().contains("denied"));
+    let source = error.source().unwrap();
+    let io_error = source.downcast_ref::<io::Error>().unwrap();
+    assert_eq!(io_error.kind(), io::ErrorKind::PermissionDenied);
+    assert_eq!(error.clone(), error);
+    assert_ne!(error, SelectionError::NotFound);
+}
+
+#[test]
+pub fn reject_out_of_range_glyph_ids() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert!(font.glyph_count() > 0x41);
+    assert!(font.typographic_bounds(0x41).is_ok());
+
+    // Truncating this to 16 bits would give glyph 0x41.
+    let glyph_id = 0x1_0041;
+    let error = GlyphLoadingError::NoSuchGlyph;
+    assert_eq!(font.typographic_bounds(glyph_id).unwrap_err(), error);
+    assert_eq!(font.advance(glyph_id).unwrap_err(), error);
+    assert_eq!(font.origin(glyph_id).unwrap_err(), error);
+    assert_eq!(
+        font.outline(glyph_id, HintingOptions::None, &mut OutlineBuilder::new())
+            .unwrap_err(),
+        error
+    );
+    assert_eq!(
+        font.raster_bounds(
+            glyph_id,
+            16.0,
+            Transform2F::default(),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap_err(),
+        error
+    );
+    let mut canvas = Canvas::new(Vector2I::splat(16), Format::A8);
+    let result = font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        16.0,
+        Transform2F::default(),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    );
+    assert_eq!(result.unwrap_err(), error);
+    assert!(canvas.pixels.iter().all(|&value| value == 0));
+
+    // IDs that fit in 16 bits are still checked against the glyph count.
+    let glyph_id = font.glyph_count();
+    assert_eq!(font.advance(glyph_id), Err(GlyphLoadingError::NoSuchGlyph));
+}
+
+#[cfg(any(target_family = "windows", target_os = "macos", target_os = "ios"))]
+#[test]
+pub fn load_font_from_native_font_with_data() {
+    let font_data = Arc::new(fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap());
+    let font = Font::from_bytes(font_data.clone(), 1).unwrap();
+    let native_font = font.native_font();
+
+    let font = Font::from_native_font_with_data(&native_font, font_data.clone()).unwrap();
+    assert_eq!(
+        font.postscript_name().unwrap(),
+        TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1]
+    );
+    assert!(Arc::ptr_eq(&font.copy_font_data().unwrap(), &font_data));
+
+    let garbage = Arc::new(vec![0; 64]);
+    assert!(Font::from_native_font_with_data(&native_font, garbage).is_err());
+}
+
+#[test]
+pub fn analyze_stream() {
+    let font_data = fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    assert_eq!(
+        Font::analyze_stream(&mut Cursor::new(font_data)).unwrap(),
+        FileType::Collection(2)
+    );
+}
+
+#[test]
+pub fn copy_font_data_from_path() {
+    let font_data = fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    let font = Font::from_path(TEST_FONT_COLLECTION_FILE_PATH, 0).unwrap();
+    let copied_data = font.copy_font_data().unwrap();
+    assert_eq!(copied_data.len(), font_data.len());
+
+    let font_data = fs::read(TEST_FONT_FILE_PATH).unwrap();
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(*font.copy_font_data().unwrap(), font_data);
+}
+
+#[test]
+pub fn get_handle_format() {
+    for (path, format) in [
+        (FILE_PATH_EB_GARAMOND_TTF, FontFormat::TrueType),
+        (TEST_FONT_FILE_PATH, FontFormat::OpenTypeCff),
+        (TEST_FONT_COLLECTION_FILE_PATH, FontFormat::Collection),
+    ] {
+        assert_eq!(Handle::from_path(path.into(), 0).format().unwrap(), format);
+    }
+
+    for (magic, format) in [(b"wOFF", FontFormat::Woff), (b"wOF2", FontFormat::Woff2)] {
+        let mut bytes = magic.to_vec();
+        bytes.extend_from_slice(&[0; 40]);
+        let handle = Handle::from_memory(Arc::new(bytes), 0);
+        assert_eq!(handle.format().unwrap(), format);
+    }
+    for bytes in [&b"OTT"[..], &b"GIF89a"[..]] {
+        let handle = Handle::from_memory(Arc::new(bytes.to_vec()), 0);
+        assert!(matches!(
+            handle.format(),
+            Err(FontLoadingError::UnknownFormat)
+        ));
+    }
+    assert!(matches!(
+        Handle::from_path("resources/tests/nonexistent.ttf".into(), 0).format(),
+        Err(FontLoadingError::Io(_))
+    ));
+}
+
+#[test]
+pub fn round_trip_font_handle() {
+    let font = Handle::from_path(FILE_PATH_EB_GARAMOND_TTF.into(), 0)
+        .load()
+        .unwrap();
+    assert_eq!(
+        font.source_path(),
+        Some(std::path::Path::new(FILE_PATH_EB_GARAMOND_TTF))
+    );
+    assert_eq!(font.clone().source_path(), font.source_path());
+
+    let handle = font.handle().unwrap();
+    match handle {
+        Handle::Path {
+            ref path,
+            font_index,
+        } => {
+            assert_eq!(path, std::path::Path::new(FILE_PATH_EB_GARAMOND_TTF));
+            assert_eq!(font_index, 0);
+        }
+        _ => panic!("expected a path handle"),
+    }
+    let reloaded = handle.load().unwrap();
+    assert_eq!(
+        reloaded.load_font_table(OPENTYPE_TABLE_TAG_HEAD),
+        font.load_font_table(OPENTYPE_TABLE_TAG_HEAD)
+    );
+
+    let font_data = Arc::new(fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap());
+    let font = Font::from_bytes(font_data, 1).unwrap();
+    assert_eq!(font.source_path(), None);
+    match font.clone().handle().unwrap() {
+        Handle::Memory { font_index, .. } => assert_eq!(font_index, 1),
+        _ => panic!("expected a memory handle"),
+    }
+}
+
+#[test]
+pub fn get_collection_face_offsets() {
+    let font_data = fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    let offsets = file_type::collection_face_offsets(&font_data).unwrap();
+    assert_eq!(offsets.len(), 2);
+    for &offset in &offsets {
+        assert_eq!(&font_data[offset as usize..][..4], b"OTTO");
+    }
+
+    let font = Font::from_bytes(Arc::new(font_data.clone()), 1).unwrap();
+    let copied_data = font.copy_font_data().unwrap();
+    let offset = font.face_byte_offset().unwrap();
+    assert_eq!(&copied_data[offset as usize..][..4], b"OTTO");
+    // Loaders that can't open collections directly may rearrange their copy of the data.
+    if copied_data[..4] == *b"ttcf" {
+        assert_eq!(offset, offsets[1]);
+    }
+
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(font.face_byte_offset(), Some(0));
+    let font_data = fs::read(TEST_FONT_FILE_PATH).unwrap();
+    assert_eq!(
+        file_type::collection_face_offsets(&font_data).unwrap(),
+        vec![0]
+    );
+
+    let mut truncated = fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    truncated.truncate(16);
+    assert!(matches!(
+        file_type::collection_face_offsets(&truncated),
+        Err(FontLoadingError::Parse)
+    ));
+    let mut misdirected = fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    misdirected[12..16].copy_from_slice(&0xffff_fff0u32.to_be_bytes());
+    assert!(matches!(
+        file_type::collection_face_offsets(&misdirected),
+        Err(FontLoadingError::Parse)
+    ));
+    assert!(matches!(
+        file_type::collection_face_offsets(b"not a font"),
+        Err(FontLoadingError::UnknownFormat)
+    ));
+}
+
 #[test]
 pub fn analyze_file() {
     let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
@@ -430,6 +842,60 @@ pub fn get_fully_hinted_glyph_outline() {
     );
 }
 
+// `supports_hinting_options()` must agree with what the loader does: asking for hinting changes
+// the outline or rasterized glyph exactly when the loader claims to support it.
+#[test]
+pub fn hinting_support_matches_hinted_output() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+    let size = 11.0;
+    let rasterize = |hinting_options| {
+        let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            size,
+            Transform2F::from_translation(Vector2F::new(8.3, 20.6)),
+            hinting_options,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+        canvas.pixels
+    };
+    let outline = |hinting_options| {
+        let mut outline_builder = OutlineBuilder::new();
+        font.outline(glyph_id, hinting_options, &mut outline_builder)
+            .unwrap();
+        outline_builder.into_outline()
+    };
+
+    // DirectWrite's natural rendering mode grid-fits vertically whatever the options, so there
+    // unhinted glyphs are rasterized as vertically hinted ones are.
+    let always_vertically_hinted = cfg!(all(
+        target_family = "windows",
+        not(feature = "loader-freetype-default")
+    ));
+
+    assert!(font.supports_hinting_options(HintingOptions::None, true));
+    assert!(font.supports_hinting_options(HintingOptions::None, false));
+    for &hinting_options in &[HintingOptions::Vertical(size), HintingOptions::Full(size)] {
+        let vertically_hinted =
+            always_vertically_hinted && matches!(hinting_options, HintingOptions::Vertical(_));
+        assert_eq!(
+            vertically_hinted || rasterize(hinting_options) != rasterize(HintingOptions::None),
+            font.supports_hinting_options(hinting_options, true),
+            "rasterizing with {:?}",
+            hinting_options
+        );
+        assert_eq!(
+            outline(hinting_options) != outline(HintingOptions::None),
+            font.supports_hinting_options(hinting_options, false),
+            "outlining with {:?}",
+            hinting_options
+        );
+    }
+}
+
 #[test]
 pub fn get_empty_glyph_outline() {
     let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
@@ -453,8 +919,11 @@ pub fn get_glyph_raster_bounds() {
     let size = 32.0;
     let hinting_options = HintingOptions::None;
     let rasterization_options = RasterizationOptions::GrayscaleAa;
-    #[cfg(not(target_family = "windows"))]
+    #[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
     let expected_rect = RectI::new(Vector2I::new(1, -20), Vector2I::new(14, 21));
+    // Core Text leaves a pixel on each side for font smoothing.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    let expected_rect = RectI::new(Vector2I::new(0, -21), Vector2I::new(16, 23));
     #[cfg(target_family = "windows")]
     let expected_rect = RectI::new(Vector2I::new(1, -20), Vector2I::new(14, 20));
     assert_eq!(
@@ -622,6 +1091,20 @@ pub fn get_font_properties() {
     assert_eq!(properties.stretch, Stretch(1.0));
 }
 
+#[test]
+pub fn debug_font() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let debug = format!("{:?}", font);
+    assert!(debug.starts_with("Font {"));
+    assert!(debug.contains("family_name: \"Inconsolata\""));
+    assert!(debug.contains("postscript_name: Some(\"Inconsolata-Regular\")"));
+    assert!(debug.contains(&format!("{:?}", font.properties())));
+    assert!(debug.contains("units_per_em: 1000"));
+    assert!(debug.contains("is_variable: false"));
+    assert!(debug.contains("is_color: false"));
+    assert!(debug.contains("is_monospace: true"));
+}
+
 #[cfg(feature = "source")]
 #[test]
 pub fn load_font_table() {
@@ -785,13 +1268,13 @@ pub fn rasterize_glyph_with_full_hinting() {
         top_row = &canvas.pixels[canvas.stride..(2 * canvas.stride)];
     }
 
-    assert!(top_row.iter().any(|&value| value == 0xff));
+    assert!(top_row.contains(&0xff));
     for y in (0..(canvas.size.y() as usize)).rev() {
         let bottom_row = &canvas.pixels[(y * canvas.stride)..((y + 1) * canvas.stride)];
         if bottom_row.iter().all(|&value| value == 0) {
             continue;
         }
-        assert!(bottom_row.iter().any(|&value| value == 0xff));
+        assert!(bottom_row.contains(&0xff));
         break;
     }
 }
@@ -911,63 +1394,3457 @@ pub fn font_transform() {
 }
 
 #[test]
-fn load_fonts_from_opentype_collection() {
-    let mut file = File::open(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
-    {
-        let font = Font::from_file(&mut file, 0).unwrap();
-        assert_eq!(
-            font.postscript_name().unwrap(),
-            TEST_FONT_COLLECTION_POSTSCRIPT_NAME[0]
-        );
+pub fn rasterize_glyph_with_non_square_scale() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('o').unwrap();
+    let rasterize = |size: Vector2F| {
+        let raster_rect = font
+            .raster_bounds_scaled(
+                glyph_id,
+                size,
+                Transform2F::default(),
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+        font.rasterize_glyph_scaled(
+            &mut canvas,
+            glyph_id,
+            size,
+            Transform2F::from_translation(-raster_rect.origin().to_f32()),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+        ink_size(&canvas)
+    };
+
+    let square = rasterize(Vector2F::splat(16.0));
+    let tall = rasterize(Vector2F::new(16.0, 32.0));
+    assert!((tall.x() - square.x()).abs() <= 1);
+    assert!((tall.y() - square.y() * 2).abs() <= 2);
+}
+
+// The bounds at a non-square size must hold the glyph as hinted at that size.
+#[test]
+pub fn raster_bounds_scaled_contain_hinted_glyph() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+    for &size in &[Vector2F::new(13.0, 31.0), Vector2F::new(31.0, 13.0)] {
+        let hinting_options = HintingOptions::Full(size.y());
+        let transform = Transform2F::from_translation(Vector2F::new(0.4, 0.6));
+        let raster_rect = font
+            .raster_bounds_scaled(
+                glyph_id,
+                size,
+                transform,
+                hinting_options,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        let padding = Vector2I::splat(8);
+        let mut canvas = Canvas::new(raster_rect.size() + padding * 2, Format::A8);
+        font.rasterize_glyph_scaled(
+            &mut canvas,
+            glyph_id,
+            size,
+            Transform2F::from_translation((padding - raster_rect.origin()).to_f32()) * transform,
+            hinting_options,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+        let inner = RectI::new(padding, raster_rect.size());
+        for y in 0..canvas.size.y() {
+            for x in 0..canvas.size.x() {
+                let point = Vector2I::new(x, y);
+                let value = canvas.pixels[y as usize * canvas.stride + x as usize];
+                assert!(
+                    value == 0 || inner.contains_point(point),
+                    "{:?} at {:?}",
+                    size,
+                    point
+                );
+            }
+        }
     }
-    let font = Font::from_file(&mut file, 1).unwrap();
-    assert_eq!(
-        font.postscript_name().unwrap(),
-        TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1]
-    );
 }
 
 #[test]
-fn get_glyph_count() {
-    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
-    assert_eq!(font.glyph_count(), 3084);
+pub fn rasterize_glyph_with_pixels_per_dip() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let rasterize = |size: f32, pixels_per_dip: f32| {
+        let raster_rect = font
+            .raster_bounds_with_pixels_per_dip(
+                glyph_id,
+                size,
+                pixels_per_dip,
+                Transform2F::default(),
+                HintingOptions::Full(size),
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+        font.rasterize_glyph_with_pixels_per_dip(
+            &mut canvas,
+            glyph_id,
+            size,
+            pixels_per_dip,
+            Transform2F::from_translation(-raster_rect.origin().to_f32()),
+            HintingOptions::Full(size),
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+        (raster_rect, canvas)
+    };
+
+    // At 200%, a 16-pixel glyph covers the same pixels as a 32-pixel glyph at 100%.
+    let (scaled_rect, scaled_canvas) = rasterize(16.0, 2.0);
+    let (large_rect, large_canvas) = rasterize(32.0, 1.0);
+    assert_eq!(scaled_rect, large_rect);
+    assert_eq!(scaled_canvas.pixels, large_canvas.pixels);
+    check_L_shape(&scaled_canvas);
+
+    let (small_rect, _) = rasterize(16.0, 1.0);
+    assert!(scaled_rect.height() > small_rect.height() * 3 / 2);
 }
 
-// The initial off-curve point used to cause an assertion in the FreeType backend.
 #[test]
-fn get_glyph_outline_eb_garamond_exclam() {
-    let mut file = File::open(FILE_PATH_EB_GARAMOND_TTF).unwrap();
-    let font = Font::from_file(&mut file, 0).unwrap();
-    let glyph = font.glyph_for_char('!').expect("No glyph for char!");
-    let mut outline_builder = OutlineBuilder::new();
-    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+pub fn render_glyph_with_settings() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let mut settings = RenderSettings::new();
+    settings
+        .hinting_options(HintingOptions::Full(16.0))
+        .pixels_per_dip(2.0);
+
+    // Rendering matches rasterizing with the same options by hand.
+    let rendered = font
+        .render(glyph_id, 16.0, Vector2F::zero(), &settings)
+        .unwrap();
+    let raster_rect = font
+        .raster_bounds_with_pixels_per_dip(
+            glyph_id,
+            16.0,
+            2.0,
+            Transform2F::default(),
+            HintingOptions::Full(16.0),
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+    let rasterized_glyph = font
+        .rasterize_glyph_with_pixels_per_dip(
+            &mut canvas,
+            glyph_id,
+            16.0,
+            2.0,
+            Transform2F::from_translation(-raster_rect.origin().to_f32()),
+            HintingOptions::Full(16.0),
+            RasterizationOptions::GrayscaleAa,
+        )
         .unwrap();
+    assert_eq!(rendered.bounds, raster_rect);
+    assert_eq!(rendered.canvas.format, Format::A8);
+    assert_eq!(rendered.canvas.pixels, canvas.pixels);
+    assert_eq!(rendered.rasterized_glyph, rasterized_glyph);
+    check_L_shape(&rendered.canvas);
 
-    // The TrueType spec doesn't specify the rounding method for midpoints, as far as I can tell.
-    // So we are lenient and accept either values rounded down (what Core Text provides if the
-    // first point is off-curve, it seems) or precise floating-point values (what our FreeType
-    // loader provides).
-    let mut outline = outline_builder.into_outline();
-    for contour in &mut outline.contours {
-        for position in &mut contour.positions {
-            *position = position.floor();
+    // A subpixel origin moves the bounds along with it.
+    let shifted = font
+        .render(glyph_id, 16.0, Vector2F::new(10.5, 0.0), &settings)
+        .unwrap();
+    assert!((shifted.bounds.origin_x() - raster_rect.origin_x() - 10).abs() <= 1);
+
+    // The format and antialiasing carry through to the canvas.
+    settings
+        .rasterization_options(RasterizationOptions::SubpixelAa)
+        .format(Format::Rgb24);
+    let rendered = font
+        .render(glyph_id, 16.0, Vector2F::zero(), &settings)
+        .unwrap();
+    assert_eq!(rendered.canvas.format, Format::Rgb24);
+    assert_eq!(rendered.canvas.size, rendered.bounds.size());
+    assert!(!rendered.rasterized_glyph.premultiplied);
+}
+
+#[test]
+pub fn rasterize_glyph_as_white_coverage() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let transform = Transform2F::from_translation(Vector2F::new(4.0, 28.0));
+    let mut a8_canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+    let a8_glyph = font
+        .rasterize_glyph(
+            &mut a8_canvas,
+            glyph_id,
+            24.0,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    check_L_shape(&a8_canvas);
+
+    // Color channels are white within the ink rectangle, and the alpha channel holds the same
+    // coverage as an `A8` canvas.
+    let mut canvas = Canvas::new(Vector2I::splat(32), Format::Rgba32);
+    let rasterized_glyph = font
+        .rasterize_glyph_with_alpha_mode(
+            &mut canvas,
+            glyph_id,
+            24.0,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+            AlphaMode::WhiteCoverage,
+        )
+        .unwrap();
+    assert!(!rasterized_glyph.premultiplied);
+    assert_eq!(rasterized_glyph.ink_rect, a8_glyph.ink_rect);
+    assert_eq!(rasterized_glyph.source, GlyphSource::Outline);
+    assert_eq!(
+        canvas.extract_channel(Channel::Alpha).pixels,
+        a8_canvas.pixels
+    );
+    let ink_rect = rasterized_glyph.ink_rect;
+    for y in ink_rect.min_y()..ink_rect.max_y() {
+        for x in ink_rect.min_x()..ink_rect.max_x() {
+            let offset = y as usize * canvas.stride + x as usize * 4;
+            assert_eq!(canvas.pixels[offset..(offset + 3)], [255; 3]);
         }
     }
 
-    println!("{:#?}", outline);
-    assert_eq!(
-        outline,
-        Outline {
-            contours: vec![
-                Contour {
-                    positions: vec![
-                        Vector2F::new(114.0, 598.0),
-                        Vector2F::new(114.0, 619.0),
-                        Vector2F::new(127.0, 634.0),
-                        Vector2F::new(141.0, 649.0),
-                        Vector2F::new(161.0, 649.0),
-                        Vector2F::new(181.0, 649.0),
-                        Vector2F::new(193.0, 634.0),
+    // A glyph drawn over another keeps the coverage of both.
+    let shifted_transform = Transform2F::from_translation(Vector2F::new(2.0, 0.0)) * transform;
+    let mut shifted_canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+    font.rasterize_glyph(
+        &mut shifted_canvas,
+        glyph_id,
+        24.0,
+        shifted_transform,
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+    font.rasterize_glyph_with_alpha_mode(
+        &mut canvas,
+        glyph_id,
+        24.0,
+        shifted_transform,
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+        AlphaMode::WhiteCoverage,
+    )
+    .unwrap();
+    let alpha = canvas.extract_channel(Channel::Alpha).pixels;
+    let coverages = a8_canvas.pixels.iter().zip(&shifted_canvas.pixels);
+    for (&alpha, (&first, &second)) in alpha.iter().zip(coverages) {
+        assert_eq!(alpha, first.max(second));
+    }
+
+    // Glyphs extending past the edges of the canvas are clipped.
+    let mut canvas = Canvas::new(Vector2I::splat(8), Format::Rgba32);
+    let rasterized_glyph = font
+        .rasterize_glyph_with_alpha_mode(
+            &mut canvas,
+            glyph_id,
+            24.0,
+            Transform2F::from_translation(Vector2F::new(0.0, 20.0)),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+            AlphaMode::WhiteCoverage,
+        )
+        .unwrap();
+    let ink_rect = rasterized_glyph.ink_rect;
+    assert!(ink_rect.width() > 0 && ink_rect.max_x() <= 8 && ink_rect.max_y() <= 8);
+    assert!(canvas.pixels.chunks(4).any(|pixel| pixel == [255; 4]));
+
+    // Rendering fills the whole canvas with white.
+    let mut settings = RenderSettings::new();
+    settings
+        .format(Format::Rgba32)
+        .alpha_mode(AlphaMode::WhiteCoverage);
+    let rendered = font
+        .render(glyph_id, 24.0, Vector2F::zero(), &settings)
+        .unwrap();
+    let a8_rendered = font
+        .render(
+            glyph_id,
+            24.0,
+            Vector2F::zero(),
+            RenderSettings::new().format(Format::A8),
+        )
+        .unwrap();
+    assert!(!rendered.rasterized_glyph.premultiplied);
+    assert_eq!(rendered.bounds, a8_rendered.bounds);
+    assert_eq!(
+        rendered.canvas.extract_channel(Channel::Alpha).pixels,
+        a8_rendered.canvas.pixels
+    );
+    assert!(rendered
+        .canvas
+        .pixels
+        .chunks(4)
+        .all(|pixel| pixel[..3] == [255; 3]));
+}
+
+#[test]
+pub fn render_glyph_at_layout_position() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let mut settings = RenderSettings::new();
+    let position = Vector2F::new(3.3, 20.0);
+    let unscaled = font.render_at(glyph_id, 16.0, position, &settings).unwrap();
+    settings.pixels_per_dip(1.5);
+    let scaled = font.render_at(glyph_id, 16.0, position, &settings).unwrap();
+
+    // The raster is about 1.5 times as large.
+    let (unscaled_size, scaled_size) = (
+        unscaled.rendered_glyph.canvas.size.to_f32(),
+        scaled.rendered_glyph.canvas.size.to_f32(),
+    );
+    assert!((scaled_size.x() - unscaled_size.x() * 1.5).abs() <= 2.0);
+    assert!((scaled_size.y() - unscaled_size.y() * 1.5).abs() <= 2.0);
+
+    // The pen position is split into a whole device pixel and a residue without losing anything,
+    // and the foot of the "L" sits on the baseline in layout pixels at both scales.
+    for (placed, pixels_per_dip) in [(&unscaled, 1.0), (&scaled, 1.5)] {
+        let bounds = placed.rendered_glyph.bounds;
+        let pen = (placed.device_origin - bounds.origin()).to_f32() + placed.residue;
+        assert!((pen - position * pixels_per_dip).length() < 0.001);
+        assert_eq!(
+            placed.layout_origin,
+            placed.device_origin.to_f32() * (1.0 / pixels_per_dip)
+        );
+        let ink_bottom =
+            placed.device_origin.y() + placed.rendered_glyph.rasterized_glyph.ink_rect.max_y();
+        assert!((ink_bottom as f32 / pixels_per_dip - position.y()).abs() <= 1.0);
+    }
+
+    // Glyphs placed along a line at 125% stay exactly where their layout positions put them.
+    settings.pixels_per_dip(1.25);
+    for x in [0.0, 7.25, 14.5, 21.75] {
+        let placed = font
+            .render_at(glyph_id, 16.0, Vector2F::new(x, 0.0), &settings)
+            .unwrap();
+        let pen_x = (placed.device_origin.x() - placed.rendered_glyph.bounds.origin_x()) as f32
+            + placed.residue.x();
+        assert!((pen_x - x * 1.25).abs() < 0.001);
+        assert!(placed.residue.x() >= 0.0 && placed.residue.x() < 1.0);
+    }
+}
+
+#[test]
+pub fn render_with_subpixel_geometry_gamma_and_contrast() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('a').unwrap();
+    let render = |settings: &RenderSettings| {
+        font.render(glyph_id, 16.0, Vector2F::new(0.3, 0.0), settings)
+            .unwrap()
+            .canvas
+    };
+
+    // BGR displays get the same subpixel coverage with red and blue swapped.
+    let mut settings = RenderSettings::new();
+    settings
+        .rasterization_options(RasterizationOptions::SubpixelAa)
+        .format(Format::Rgb24);
+    let rgb = render(&settings);
+    let bgr = render(settings.subpixel_geometry(SubpixelGeometry::Bgr));
+    assert_eq!(rgb.size, bgr.size);
+    assert!(rgb.pixels.chunks(3).any(|pixel| pixel[0] != pixel[2]));
+    for (rgb, bgr) in rgb.pixels.chunks(3).zip(bgr.pixels.chunks(3)) {
+        assert_eq!([rgb[2], rgb[1], rgb[0]], bgr);
+    }
+
+    // Gamma above 1.0 and contrast both raise partial coverage, leaving none and full alone.
+    let plain = render(&RenderSettings::new());
+    for settings in [
+        RenderSettings::new().gamma(2.2),
+        RenderSettings::new().contrast(0.5),
+    ] {
+        let adjusted = render(settings);
+        assert_eq!(plain.size, adjusted.size);
+        for (&plain, &adjusted) in plain.pixels.iter().zip(adjusted.pixels.iter()) {
+            match plain {
+                0 | 255 => assert_eq!(adjusted, plain),
+                _ => assert!(adjusted >= plain),
+            }
+        }
+        let total = |canvas: &Canvas| canvas.pixels.iter().map(|&value| value as u32).sum::<u32>();
+        assert!(total(&adjusted) > total(&plain));
+    }
+}
+
+#[test]
+pub fn glyph_is_empty_matches_outline() {
+    for path in [
+        TEST_FONT_FILE_PATH,
+        FILE_PATH_EB_GARAMOND_TTF,
+        FILE_PATH_INCONSOLATA_TTF,
+    ] {
+        let font = Font::from_path(path, 0).unwrap();
+        for character in [' ', '\r', '\u{200d}', 'a'] {
+            let glyph_id = match font.glyph_for_char(character) {
+                Some(glyph_id) => glyph_id,
+                None => continue,
+            };
+            let mut builder = OutlineBuilder::new();
+            font.outline(glyph_id, HintingOptions::None, &mut builder)
+                .unwrap();
+            let is_empty = builder.into_outline().contours.is_empty();
+            assert_eq!(font.glyph_is_empty(glyph_id), Ok(is_empty));
+            assert_eq!(is_empty, character != 'a');
+        }
+    }
+}
+
+#[test]
+pub fn outline_at_size_overlays_rasterized_glyph() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+    for &point_size in &[9.0, 16.0, 31.5, 72.0] {
+        for &hinting in &[HintingOptions::None, HintingOptions::Full(point_size)] {
+            let origin = Vector2F::new(4.25, point_size * 1.5);
+            let mut builder = OutlineBuilder::new();
+            font.outline_at_size(glyph_id, point_size, origin, hinting, &mut builder)
+                .unwrap();
+            let outline = builder.into_outline();
+
+            let size = Vector2I::splat((point_size * 2.5) as i32);
+            let mut canvas = Canvas::new(size, Format::A8);
+            font.rasterize_glyph(
+                &mut canvas,
+                glyph_id,
+                point_size,
+                Transform2F::from_translation(origin),
+                hinting,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+            let mut ink: Option<RectF> = None;
+            for y in 0..size.y() {
+                for x in 0..size.x() {
+                    if canvas.pixels[(y * size.x() + x) as usize] != 0 {
+                        let pixel = RectI::new(Vector2I::new(x, y), Vector2I::splat(1)).to_f32();
+                        ink = Some(ink.map_or(pixel, |ink| ink.union_rect(pixel)));
+                    }
+                }
+            }
+            let ink = ink.unwrap();
+
+            // The glyph's extremes are points on the curve, so the inked pixels are exactly the
+            // ones that those points reach into.
+            let mut on_curve = outline.contours.iter().flat_map(|contour| {
+                contour
+                    .positions
+                    .iter()
+                    .zip(&contour.flags)
+                    .filter(|(_, flags)| flags.is_empty())
+                    .map(|(&position, _)| position)
+            });
+            let first = on_curve.next().unwrap();
+            let bounds =
+                on_curve.fold(RectF::new(first, Vector2F::zero()), |r, p| r.union_point(p));
+            assert_eq!(bounds.round_out(), ink);
+        }
+    }
+}
+
+#[test]
+pub fn scale_glyphs_with_unusual_units_per_em() {
+    // The synthesized square is half an em wide and advances by an em, so at 32 points it should
+    // cover the same 16 by 16 pixels whatever the em is divided into.
+    let point_size = 32.0;
+    let origin = Vector2F::new(2.0, 40.0);
+    let transform = Transform2F::from_translation(origin);
+    let expected_rect = RectI::new(Vector2I::new(2, 24), Vector2I::splat(16));
+    let mut expected_canvases: Vec<Canvas> = vec![];
+    for units_per_em in [16, 256, 1000, 2048, 16384] {
+        let data = synthesize_font(units_per_em, &[(0x41, 1)]);
+        let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+        let scale = font.metrics().scale_for(point_size);
+        assert_eq!(scale.to_px(font.advance(1).unwrap().x()), point_size);
+        assert_eq!(font.gdi_compatible_advance(1, 32), Ok(32.0));
+
+        for (index, &hinting) in [HintingOptions::None, HintingOptions::Full(point_size)]
+            .iter()
+            .enumerate()
+        {
+            let rasterization_options = RasterizationOptions::GrayscaleAa;
+            let bounds =
+                font.raster_bounds(1, point_size, transform, hinting, rasterization_options);
+            assert_eq!(bounds, Ok(expected_rect));
+
+            let mut canvas = Canvas::new(Vector2I::splat(48), Format::A8);
+            let rasterized_glyph = font
+                .rasterize_glyph(
+                    &mut canvas,
+                    1,
+                    point_size,
+                    transform,
+                    hinting,
+                    rasterization_options,
+                )
+                .unwrap();
+            assert_eq!(rasterized_glyph.ink_rect, expected_rect);
+            match expected_canvases.get(index) {
+                Some(expected_canvas) => assert!(canvas.pixels == expected_canvas.pixels),
+                None => expected_canvases.push(canvas),
+            }
+
+            let mut builder = OutlineBuilder::new();
+            font.outline_at_size(1, point_size, origin, hinting, &mut builder)
+                .unwrap();
+            let outline = builder.into_outline();
+            let positions: Vec<_> = outline.contours[0]
+                .positions
+                .iter()
+                .map(|position| (position.x(), position.y()))
+                .collect();
+            assert_eq!(
+                positions,
+                [(2.0, 40.0), (2.0, 24.0), (18.0, 24.0), (18.0, 40.0)]
+            );
+        }
+    }
+}
+
+#[test]
+pub fn get_glyph_outline_bounds() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('o').unwrap();
+    let outline_bounds = font.outline_bounds(glyph_id).unwrap();
+
+    // The round bowl overshoots the baseline.
+    assert!(outline_bounds.min_y() < 0.0);
+
+    // The bounds must be enclosed by the control polygon.
+    let mut builder = OutlineBuilder::new();
+    font.outline(glyph_id, HintingOptions::None, &mut builder)
+        .unwrap();
+    let outline = builder.into_outline();
+    let mut points = outline.contours.iter().flat_map(|c| c.positions.iter());
+    let first = *points.next().unwrap();
+    let hull = points.fold(RectF::new(first, Vector2F::zero()), |r, &p| {
+        r.union_point(p)
+    });
+    assert!(hull.contains_rect(outline_bounds));
+
+    let typographic_bounds = font.typographic_bounds(glyph_id).unwrap();
+    assert!((typographic_bounds.min_y() - outline_bounds.min_y()).abs() <= 1.0);
+    assert!((typographic_bounds.max_y() - outline_bounds.max_y()).abs() <= 1.0);
+
+    let space = font.glyph_for_char(' ').unwrap();
+    assert_eq!(font.outline_bounds(space), Ok(RectF::default()));
+}
+
+#[test]
+pub fn get_tracking_without_trak_table() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(font.tracking(12.0), None);
+}
+
+#[test]
+pub fn match_properties_query_with_unspecified_fields() {
+    let candidates = [
+        *Properties::new().stretch(Stretch::CONDENSED),
+        *Properties::new()
+            .stretch(Stretch::CONDENSED)
+            .style(Style::Italic)
+            .weight(Weight::BOLD),
+        *Properties::new().weight(Weight::LIGHT),
+    ];
+
+    // Full properties prefer normal stretch, even at the wrong weight.
+    let properties = *Properties::new().weight(Weight::BOLD);
+    assert_eq!(matching::find_best_match(&candidates, &properties), Ok(2));
+
+    // Leaving stretch and style unspecified lets the weight decide.
+    let query = *PropertiesQuery::new().weight(Weight::BOLD);
+    assert_eq!(
+        matching::find_best_match_for_query(&candidates, &query),
+        Ok(1)
+    );
+
+    // An empty query matches the first candidate.
+    assert_eq!(
+        matching::find_best_match_for_query(&candidates, &PropertiesQuery::new()),
+        Ok(0)
+    );
+    assert!(matching::find_best_match_for_query(&[], &PropertiesQuery::new()).is_err());
+}
+
+#[test]
+pub fn get_caret_metrics() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let caret_metrics = font.caret_metrics();
+    assert_eq!(caret_metrics, CaretMetrics::default());
+    assert_eq!(caret_metrics.caret_transform(), Transform2F::default());
+
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_ITALIC_OTF, 0).unwrap();
+    let caret_metrics = font.caret_metrics();
+    assert_eq!(caret_metrics.slope_rise, 100.0);
+    assert_eq!(caret_metrics.slope_run, 31.0);
+    assert_eq!(caret_metrics.offset, 0.0);
+    let top = caret_metrics.caret_transform() * Vector2F::new(0.0, 1000.0);
+    assert_eq!(top, Vector2F::new(310.0, 1000.0));
+}
+
+#[test]
+pub fn normalize_font_units() {
+    // The CFF and TrueType builds of a face agree once both are measured against the same em.
+    let cff_font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let truetype_font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let (cff, truetype) = (cff_font.normalized(2048), truetype_font.normalized(2048));
+    assert_eq!(cff.metrics().units_per_em, 2048);
+    assert!((cff.metrics().cap_height - truetype.metrics().cap_height).abs() < 2.0);
+    assert!((cff.metrics().x_height - truetype.metrics().x_height).abs() < 2.0);
+
+    // Synthesized fonts draw a square half an em wide, advancing by an em, at any em size.
+    let glyph_id = 1;
+    let small_font = Font::from_bytes(Arc::new(synthesize_font(1000, &[(0x41, 1)])), 0).unwrap();
+    let large_font = Font::from_bytes(Arc::new(synthesize_font(2048, &[(0x41, 1)])), 0).unwrap();
+    let (small, large) = (small_font.normalized(1000), large_font.normalized(1000));
+    assert_eq!(small.scale(), 1.0);
+    assert_eq!(large.scale(), 1000.0 / 2048.0);
+    assert_eq!(large.metrics().cap_height, small.metrics().cap_height);
+    assert_eq!(large.advance(glyph_id), Ok(Vector2F::new(1000.0, 0.0)));
+    assert_eq!(
+        large.typographic_bounds(glyph_id),
+        small.typographic_bounds(glyph_id)
+    );
+    assert_eq!(
+        large.outline_bounds(glyph_id),
+        small.outline_bounds(glyph_id)
+    );
+    let mut large_outline = OutlineBuilder::new();
+    large
+        .outline(glyph_id, HintingOptions::None, &mut large_outline)
+        .unwrap();
+    let mut small_outline = OutlineBuilder::new();
+    small_font
+        .outline(glyph_id, HintingOptions::None, &mut small_outline)
+        .unwrap();
+    assert_eq!(large_outline.into_outline(), small_outline.into_outline());
+    assert_eq!(
+        large.glyf_outline(glyph_id, ImpliedPoints::AsStored),
+        small.glyf_outline(glyph_id, ImpliedPoints::AsStored)
+    );
+    assert_eq!(
+        large.glyf_contours(glyph_id, ImpliedPoints::AsStored),
+        small_font.glyf_contours(glyph_id, ImpliedPoints::AsStored)
+    );
+    assert_eq!(
+        large.metrics_with_fallbacks().0.cap_height,
+        small.metrics_with_fallbacks().0.cap_height
+    );
+    assert_eq!(
+        large.string_ink_bounds("A", 16.0, false),
+        large_font.string_ink_bounds("A", 16.0, false)
+    );
+
+    // Scaling back to the font's own em recovers its metrics.
+    let metrics = truetype_font.metrics();
+    let round_trip = truetype_font.metrics_normalized(2048).normalized(1000);
+    for (value, expected) in [
+        (round_trip.ascent, metrics.ascent),
+        (round_trip.descent, metrics.descent),
+        (round_trip.cap_height, metrics.cap_height),
+        (
+            round_trip.bounding_box.min_y(),
+            metrics.bounding_box.min_y(),
+        ),
+    ] {
+        assert!((value - expected).abs() < 0.001);
+    }
+}
+
+#[test]
+pub fn get_script_metrics() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let script_metrics = font.script_metrics().unwrap();
+    assert_eq!(
+        script_metrics,
+        ScriptMetrics {
+            subscript_x_size: 650.0,
+            subscript_y_size: 699.0,
+            subscript_x_offset: 0.0,
+            subscript_y_offset: -140.0,
+            superscript_x_size: 650.0,
+            superscript_y_size: 699.0,
+            superscript_x_offset: 0.0,
+            superscript_y_offset: 479.0,
+        }
+    );
+
+    let data = synthesize_font_without_tables(1000, &[(0x41, 1)], &[b"OS/2"]);
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+    assert!(font.script_metrics().is_none());
+}
+
+#[test]
+pub fn get_glyph_typographic_bounds_y_extents() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let metrics = font.metrics();
+    for character in ['x', 'H', 'g'] {
+        let glyph_id = font.glyph_for_char(character).unwrap();
+        let typographic_bounds = font.typographic_bounds(glyph_id).unwrap();
+        let outline_bounds = font.outline_bounds(glyph_id).unwrap();
+        assert!((typographic_bounds.min_y() - outline_bounds.min_y()).abs() <= 1.0);
+        assert!((typographic_bounds.max_y() - outline_bounds.max_y()).abs() <= 1.0);
+
+        match character {
+            'x' => {
+                assert!(typographic_bounds.min_y().abs() <= 10.0);
+                assert!((typographic_bounds.max_y() - metrics.x_height).abs() <= 20.0);
+            }
+            'H' => {
+                assert!(typographic_bounds.min_y().abs() <= 10.0);
+                assert!((typographic_bounds.max_y() - metrics.cap_height).abs() <= 20.0);
+            }
+            _ => {
+                assert!(typographic_bounds.min_y() < -100.0);
+                assert!(typographic_bounds.max_y() > 0.0);
+            }
+        }
+    }
+}
+
+#[test]
+pub fn rasterize_glyph_reports_ink_rect() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+    let size = 32.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::default(),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+    let rasterized_glyph = font
+        .rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            size,
+            Transform2F::from_translation(-raster_rect.origin().to_f32()),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    assert_eq!(rasterized_glyph.source, GlyphSource::Outline);
+    assert!(!rasterized_glyph.premultiplied);
+    assert_eq!(
+        rasterized_glyph.ink_rect,
+        RectI::new(Vector2I::default(), raster_rect.size())
+    );
+}
+
+#[test]
+pub fn rasterize_glyph_clipped() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+    let size = 32.0;
+    let transform = Transform2F::from_translation(Vector2F::new(4.0, 28.0));
+    let canvas_size = Vector2I::splat(40);
+    let clip = RectI::new(Vector2I::new(6, 20), Vector2I::new(10, 12));
+
+    for &rasterization_options in &[
+        RasterizationOptions::GrayscaleAa,
+        RasterizationOptions::Bilevel,
+    ] {
+        let mut full_canvas = Canvas::new(canvas_size, Format::A8);
+        font.rasterize_glyph(
+            &mut full_canvas,
+            glyph_id,
+            size,
+            transform,
+            HintingOptions::None,
+            rasterization_options,
+        )
+        .unwrap();
+
+        let mut clipped_canvas = Canvas::new(canvas_size, Format::A8);
+        clipped_canvas
+            .pixels
+            .iter_mut()
+            .for_each(|pixel| *pixel = 7);
+        let rasterized_glyph = font
+            .rasterize_glyph_clipped(
+                &mut clipped_canvas,
+                glyph_id,
+                size,
+                transform,
+                HintingOptions::None,
+                rasterization_options,
+                Some(clip),
+            )
+            .unwrap();
+        assert_eq!(
+            clip.intersection(rasterized_glyph.ink_rect),
+            Some(rasterized_glyph.ink_rect)
+        );
+
+        // Pixels of the glyph inside the clip are copied from the unclipped rendering, replacing
+        // what was there; the rest are untouched.
+        let glyph_bounds = font
+            .raster_bounds(
+                glyph_id,
+                size,
+                transform,
+                HintingOptions::None,
+                rasterization_options,
+            )
+            .unwrap();
+        for y in 0..canvas_size.y() {
+            for x in 0..canvas_size.x() {
+                let index = (y * canvas_size.x() + x) as usize;
+                let point = Vector2I::new(x, y);
+                let expected = if clip.contains_point(point) && glyph_bounds.contains_point(point) {
+                    full_canvas.pixels[index]
+                } else {
+                    7
+                };
+                assert_eq!(clipped_canvas.pixels[index], expected, "at ({}, {})", x, y);
+            }
+        }
+    }
+}
+
+#[test]
+pub fn create_canvas_fallibly() {
+    let canvas = Canvas::try_new(Vector2I::new(3, 2), Format::Rgba32).unwrap();
+    assert_eq!(canvas.stride, 12);
+    assert_eq!(canvas.pixels, vec![0; 24]);
+    assert!(Canvas::try_new(Vector2I::zero(), Format::A8).is_ok());
+
+    assert_eq!(
+        Canvas::try_new(Vector2I::new(-1, 4), Format::A8).err(),
+        Some(CanvasError::InvalidSize)
+    );
+    assert_eq!(
+        Canvas::try_new(Vector2I::new(16384, 16385), Format::Rgba32).err(),
+        Some(CanvasError::TooLarge)
+    );
+    assert_eq!(
+        Canvas::try_new(Vector2I::splat(i32::MAX), Format::Rgba32).err(),
+        Some(CanvasError::TooLarge)
+    );
+}
+
+#[test]
+pub fn composite_canvas() {
+    // Coverage formats take the maximum, and the source is clipped to the destination.
+    let mut dest = Canvas::new(Vector2I::splat(4), Format::A8);
+    dest.pixels[0] = 200;
+    let mut src = Canvas::new(Vector2I::splat(2), Format::A8);
+    src.pixels.copy_from_slice(&[100, 50, 25, 255]);
+    dest.composite(&src, Vector2I::splat(-1));
+    assert_eq!(dest.pixels[0], 255);
+    dest.composite(&src, Vector2I::splat(3));
+    assert_eq!(dest.pixels[15], 100);
+    assert_eq!(dest.pixels.iter().filter(|&&value| value != 0).count(), 2);
+
+    // RGBA uses premultiplied source-over.
+    let mut dest = Canvas::new(Vector2I::splat(1), Format::Rgba32);
+    dest.pixels.copy_from_slice(&[0, 0, 255, 255]);
+    let mut src = Canvas::new(Vector2I::splat(1), Format::Rgba32);
+    src.pixels.copy_from_slice(&[128, 0, 0, 128]);
+    dest.composite(&src, Vector2I::default());
+    assert_eq!(dest.pixels, [128, 0, 127, 255]);
+
+    src.pixels.copy_from_slice(&[0, 0, 0, 0]);
+    dest.composite(&src, Vector2I::default());
+    assert_eq!(dest.pixels, [128, 0, 127, 255]);
+}
+
+#[test]
+pub fn extract_canvas_channel() {
+    // A 2x2 RGBA canvas with padding at the end of each row.
+    let mut canvas = Canvas::with_stride(Vector2I::splat(2), 10, Format::Rgba32);
+    canvas.pixels[0..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    canvas.pixels[10..18].copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+    let green = canvas.extract_channel(Channel::Green);
+    assert_eq!(green.format, Format::A8);
+    assert_eq!(green.size, canvas.size);
+    assert_eq!(green.stride, 2);
+    assert_eq!(green.pixels, [2, 6, 10, 14]);
+    assert_eq!(
+        canvas.extract_channel(Channel::Alpha).pixels,
+        [4, 8, 12, 16]
+    );
+
+    let mut canvas = Canvas::new(Vector2I::new(2, 1), Format::Rgb24);
+    canvas.pixels.copy_from_slice(&[10, 20, 30, 40, 50, 60]);
+    assert_eq!(canvas.extract_channel(Channel::Red).pixels, [10, 40]);
+    assert_eq!(canvas.extract_channel(Channel::Blue).pixels, [30, 60]);
+    assert_eq!(canvas.extract_channel(Channel::Alpha).pixels, [255, 255]);
+
+    // Coverage canvases have a single channel.
+    let mut canvas = Canvas::new(Vector2I::new(2, 1), Format::A16);
+    canvas.pixels.copy_from_slice(&[0xff, 0xff, 0x00, 0x80]);
+    assert_eq!(canvas.extract_channel(Channel::Red).pixels, [255, 128]);
+    let canvas = Canvas::new(Vector2I::new(0, 3), Format::A8);
+    assert!(canvas.extract_channel(Channel::Alpha).pixels.is_empty());
+}
+
+#[test]
+pub fn composite_a16_canvas() {
+    let mut dest = Canvas::new(Vector2I::new(3, 2), Format::A16);
+    assert_eq!(dest.format.bits_per_pixel(), 16);
+    assert_eq!(dest.stride, 6);
+    assert_eq!(dest.pixels.len(), 12);
+
+    // Coverage takes the maximum of the whole little-endian 16-bit values, not of each byte.
+    dest.pixels[0..2].copy_from_slice(&0x12ffu16.to_le_bytes());
+    let mut src = Canvas::new(Vector2I::splat(1), Format::A16);
+    src.pixels.copy_from_slice(&0x3400u16.to_le_bytes());
+    dest.composite(&src, Vector2I::default());
+    assert_eq!(dest.pixels[0..2], 0x3400u16.to_le_bytes());
+    src.pixels.copy_from_slice(&0x8000u16.to_le_bytes());
+    dest.composite(&src, Vector2I::new(2, 1));
+    assert_eq!(dest.pixels[10..12], 0x8000u16.to_le_bytes());
+
+    dest.fill_rect(
+        RectI::new(Vector2I::new(1, 0), Vector2I::new(1, 2)),
+        &0xabcdu16.to_le_bytes(),
+    );
+    assert_eq!(dest.pixels[2..4], 0xabcdu16.to_le_bytes());
+    assert_eq!(dest.pixels[8..10], 0xabcdu16.to_le_bytes());
+}
+
+#[test]
+pub fn rasterize_glyph_into_a16_canvas() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    for &rasterization_options in &[
+        RasterizationOptions::GrayscaleAa,
+        RasterizationOptions::Bilevel,
+    ] {
+        let raster_rect = font
+            .raster_bounds(
+                glyph_id,
+                16.0,
+                Transform2F::default(),
+                HintingOptions::None,
+                rasterization_options,
+            )
+            .unwrap();
+        let transform = Transform2F::from_translation(-raster_rect.origin().to_f32());
+        let mut a8_canvas = Canvas::new(raster_rect.size(), Format::A8);
+        let mut a16_canvas = Canvas::new(raster_rect.size(), Format::A16);
+        for canvas in [&mut a8_canvas, &mut a16_canvas].iter_mut() {
+            font.rasterize_glyph(
+                canvas,
+                glyph_id,
+                16.0,
+                transform,
+                HintingOptions::None,
+                rasterization_options,
+            )
+            .unwrap();
+        }
+        check_L_shape(&a8_canvas);
+
+        // 8-bit coverage is widened so that full coverage stays full.
+        let widened: Vec<u8> = a8_canvas
+            .pixels
+            .iter()
+            .flat_map(|&value| (value as u16 * 257).to_le_bytes().to_vec())
+            .collect();
+        assert_eq!(a16_canvas.pixels, widened);
+    }
+}
+
+#[test]
+pub fn composite_canvas_in_linear_space() {
+    // Half-transparent white over opaque black is noticeably lighter when blended linearly.
+    let mut src = Canvas::new(Vector2I::splat(1), Format::Rgba32);
+    src.pixels.copy_from_slice(&[128, 128, 128, 128]);
+    let mut srgb_dest = Canvas::new(Vector2I::splat(1), Format::Rgba32);
+    srgb_dest.pixels.copy_from_slice(&[0, 0, 0, 255]);
+    let mut linear_dest = Canvas::new(Vector2I::splat(1), Format::Rgba32);
+    linear_dest.pixels.copy_from_slice(&[0, 0, 0, 255]);
+    srgb_dest.composite_in(&src, Vector2I::default(), BlendSpace::Srgb);
+    linear_dest.composite_in(&src, Vector2I::default(), BlendSpace::Linear);
+    assert_eq!(srgb_dest.pixels, [128, 128, 128, 255]);
+    assert_eq!(linear_dest.pixels, [188, 188, 188, 255]);
+
+    // Opaque and fully transparent sources behave the same in both spaces.
+    src.pixels.copy_from_slice(&[10, 20, 30, 255]);
+    linear_dest.composite_in(&src, Vector2I::default(), BlendSpace::Linear);
+    assert_eq!(linear_dest.pixels, [10, 20, 30, 255]);
+    src.pixels.copy_from_slice(&[0, 0, 0, 0]);
+    linear_dest.composite_in(&src, Vector2I::default(), BlendSpace::Linear);
+    assert_eq!(linear_dest.pixels, [10, 20, 30, 255]);
+
+    // Blending over a transparent destination leaves the source unchanged.
+    let mut dest = Canvas::new(Vector2I::splat(1), Format::Rgba32);
+    src.pixels.copy_from_slice(&[64, 32, 0, 128]);
+    dest.composite_in(&src, Vector2I::default(), BlendSpace::Linear);
+    assert_eq!(dest.pixels, [64, 32, 0, 128]);
+}
+
+#[test]
+pub fn fill_canvas_rect() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let (size, baseline) = (32.0, 36.0);
+    let mut canvas = Canvas::new(Vector2I::new(40, 48), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        size,
+        Transform2F::from_translation(Vector2F::new(4.0, baseline)),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+    let glyph_pixels = canvas.pixels.clone();
+
+    // A strikeout through the glyph is clipped to the canvas and keeps the glyph's coverage.
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(-5, 26), Vector2I::new(100, 4)),
+        &[128],
+    );
+    for y in 0..48 {
+        for x in 0..40 {
+            let index = y * canvas.stride + x;
+            let expected = if (26..30).contains(&y) {
+                cmp::max(glyph_pixels[index], 128)
+            } else {
+                glyph_pixels[index]
+            };
+            assert_eq!(canvas.pixels[index], expected, "at ({}, {})", x, y);
+        }
+    }
+    assert!((26..30).any(
+        |y| canvas.pixels[y * canvas.stride..(y + 1) * canvas.stride]
+            .iter()
+            .any(|&value| value > 128)
+    ));
+
+    // An underline from the font metrics lands below the baseline.
+    let metrics = font.metrics();
+    let scale = size / metrics.units_per_em as f32;
+    let top = baseline - metrics.underline_position * scale;
+    canvas.fill_horizontal_line(-10, 50, top, metrics.underline_thickness * scale, &[255]);
+    let underline_row = top.round() as usize;
+    assert!(underline_row > baseline as usize);
+    assert!(
+        canvas.pixels[underline_row * canvas.stride..(underline_row + 1) * canvas.stride]
+            .iter()
+            .all(|&value| value == 255)
+    );
+
+    // RGBA fills are blended source-over and clipped.
+    let mut canvas = Canvas::new(Vector2I::new(4, 1), Format::Rgba32);
+    canvas.fill_rect(
+        RectI::new(Vector2I::default(), Vector2I::new(3, 1)),
+        &[0, 0, 255, 255],
+    );
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(1, -1), Vector2I::new(10, 3)),
+        &[128, 0, 0, 128],
+    );
+    assert_eq!(
+        canvas.pixels,
+        [0, 0, 255, 255, 128, 0, 127, 255, 128, 0, 127, 255, 128, 0, 0, 128]
+    );
+}
+
+#[test]
+pub fn copy_canvas_rects() {
+    for format in [Format::Rgba32, Format::Rgb24, Format::A8, Format::A16] {
+        let bytes_per_pixel = format.bytes_per_pixel() as usize;
+        // Rows are padded by three bytes, and every byte holds its own offset.
+        let mut canvas = Canvas::with_stride(Vector2I::new(7, 5), 7 * bytes_per_pixel + 3, format);
+        for (offset, value) in canvas.pixels.iter_mut().enumerate() {
+            *value = offset as u8;
+        }
+        let expected = |rect: RectI| -> Vec<u8> {
+            (rect.min_y()..rect.max_y())
+                .flat_map(|y| {
+                    let start =
+                        y as usize * canvas.stride + rect.min_x() as usize * bytes_per_pixel;
+                    canvas.pixels[start..(start + rect.width() as usize * bytes_per_pixel)].to_vec()
+                })
+                .collect()
+        };
+
+        let rect = RectI::new(Vector2I::new(3, 1), Vector2I::new(3, 3));
+        let copy = canvas.copy_rect(rect);
+        assert_eq!(copy.format, format);
+        assert_eq!(copy.size, rect.size());
+        assert_eq!(copy.stride, 3 * bytes_per_pixel);
+        assert_eq!(copy.pixels, expected(rect));
+
+        // Rects extending past the edges are clipped.
+        let copy = canvas.copy_rect(RectI::new(Vector2I::new(-2, 3), Vector2I::new(4, 5)));
+        let clipped = RectI::new(Vector2I::new(0, 3), Vector2I::new(2, 2));
+        assert_eq!(copy.size, clipped.size());
+        assert_eq!(copy.pixels, expected(clipped));
+        let copy = canvas.copy_rect(RectI::new(Vector2I::new(7, 0), Vector2I::new(2, 2)));
+        assert_eq!(copy.size, Vector2I::zero());
+        assert!(copy.pixels.is_empty());
+        let copy = canvas.copy_rect(RectI::new(Vector2I::new(5, 2), Vector2I::new(-3, 2)));
+        assert_eq!(copy.size, Vector2I::zero());
+
+        // Copying into a wider buffer leaves the bytes past each row alone.
+        let dest_stride = 4 * bytes_per_pixel;
+        let mut dest = vec![0xee; dest_stride * 3];
+        let copied = canvas.copy_rect_into(
+            RectI::new(Vector2I::new(5, 2), Vector2I::new(3, 4)),
+            &mut dest,
+            dest_stride,
+        );
+        assert_eq!(copied, RectI::new(Vector2I::new(5, 2), Vector2I::new(2, 3)));
+        let copied_rows: Vec<u8> = dest
+            .chunks(dest_stride)
+            .flat_map(|row| row[..(2 * bytes_per_pixel)].to_vec())
+            .collect();
+        assert_eq!(copied_rows, expected(copied));
+        assert!(dest
+            .chunks(dest_stride)
+            .all(|row| row[(2 * bytes_per_pixel)..]
+                .iter()
+                .all(|&value| value == 0xee)));
+    }
+}
+
+#[test]
+pub fn convert_canvas_alpha() {
+    let mut canvas = Canvas::new(Vector2I::new(3, 1), Format::Rgba32);
+    canvas
+        .pixels
+        .copy_from_slice(&[64, 32, 0, 128, 10, 20, 30, 0, 255, 255, 255, 255]);
+    let rect = RectI::new(Vector2I::default(), canvas.size);
+    canvas.unpremultiply_alpha(rect);
+    assert_eq!(
+        canvas.pixels,
+        [128, 64, 0, 128, 0, 0, 0, 0, 255, 255, 255, 255]
+    );
+    canvas.premultiply_alpha(rect);
+    assert_eq!(
+        canvas.pixels,
+        [64, 32, 0, 128, 0, 0, 0, 0, 255, 255, 255, 255]
+    );
+}
+
+#[cfg(all(feature = "source", target_os = "macos"))]
+#[test]
+pub fn rasterize_emoji_premultiplied() {
+    let font = SystemSource::new()
+        .select_by_postscript_name("AppleColorEmoji")
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_id = font.glyph_for_char('😀').unwrap();
+    let size = 32.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::default(),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::Rgba32);
+    let rasterized_glyph = font
+        .rasterize_glyph_with_alpha_mode(
+            &mut canvas,
+            glyph_id,
+            size,
+            Transform2F::from_translation(-raster_rect.origin().to_f32()),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+            font_kit::canvas::AlphaMode::Premultiplied,
+        )
+        .unwrap();
+    assert!(rasterized_glyph.premultiplied);
+    assert!(canvas.pixels.iter().any(|&value| value != 0));
+    for pixel in canvas.pixels.chunks(4) {
+        assert!(pixel[0..3].iter().all(|&color| color <= pixel[3]));
+    }
+}
+
+#[test]
+pub fn get_glyph_advance_with_cache() {
+    let uncached_font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0)
+        .unwrap()
+        .with_advance_cache();
+    for character in "aAaa".chars() {
+        let glyph = font.glyph_for_char(character).unwrap();
+        assert_eq!(
+            font.advance(glyph).unwrap(),
+            uncached_font.advance(glyph).unwrap()
+        );
+    }
+
+    // Clones keep the cached advances.
+    let glyph = font.glyph_for_char('a').unwrap();
+    assert_eq!(font.clone().advance(glyph), uncached_font.advance(glyph));
+}
+
+#[test]
+pub fn get_glyph_outline_with_cache() {
+    let uncached_font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0)
+        .unwrap()
+        .with_outline_cache();
+    let outline = |font: &Font, character| {
+        let glyph = font.glyph_for_char(character).unwrap();
+        let mut builder = OutlineBuilder::new();
+        font.outline(glyph, HintingOptions::None, &mut builder)
+            .unwrap();
+        builder.into_outline()
+    };
+    for character in "aAaa".chars() {
+        assert_eq!(
+            outline(&font, character),
+            outline(&uncached_font, character)
+        );
+    }
+
+    // Clearing the cache refetches outlines rather than losing them.
+    font.clear_outline_cache();
+    assert_eq!(outline(&font, 'a'), outline(&uncached_font, 'a'));
+    assert_eq!(outline(&font.clone(), 'A'), outline(&uncached_font, 'A'));
+}
+
+#[test]
+pub fn load_fonts_from_many_threads() {
+    let font_data = Arc::new(fs::read(TEST_FONT_FILE_PATH).unwrap());
+    let threads: Vec<_> = (0..16)
+        .map(|_| {
+            let font_data = font_data.clone();
+            thread::spawn(move || {
+                for _ in 0..32 {
+                    let font = Font::from_bytes(font_data.clone(), 0).unwrap();
+                    let font_clone = font.clone();
+                    drop(font);
+                    let glyph = font_clone.glyph_for_char('A').unwrap();
+                    let mut canvas = Canvas::new(Vector2I::splat(16), Format::A8);
+                    font_clone
+                        .rasterize_glyph(
+                            &mut canvas,
+                            glyph,
+                            12.0,
+                            Transform2F::from_translation(Vector2F::new(0.0, 12.0)),
+                            HintingOptions::None,
+                            RasterizationOptions::GrayscaleAa,
+                        )
+                        .unwrap();
+                    assert!(canvas.pixels.iter().any(|&value| value != 0));
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+pub fn get_style_attributes_without_stat_table() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert!(font.style_attributes().is_empty());
+}
+
+#[test]
+pub fn get_glyf_contours() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let o = font.glyph_for_char('o').unwrap();
+    let stored = font.glyf_contours(o, ImpliedPoints::AsStored).unwrap();
+    let explicit = font.glyf_contours(o, ImpliedPoints::Explicit).unwrap();
+    assert_eq!(stored.contours.len(), 2);
+    assert_eq!(explicit.contours.len(), 2);
+
+    let is_off_curve =
+        |contour: &Contour, index: usize| !contour.flags[index % contour.flags.len()].is_empty();
+    for (stored, explicit) in stored.contours.iter().zip(explicit.contours.iter()) {
+        let stored_len = stored.positions.len();
+        let implied_count = (0..stored_len)
+            .filter(|&index| is_off_curve(stored, index) && is_off_curve(stored, index + 1))
+            .count();
+        assert!(implied_count > 0);
+        assert_eq!(explicit.positions.len(), stored_len + implied_count);
+
+        // Every off-curve point now lies between two on-curve points.
+        let explicit_len = explicit.positions.len();
+        for index in 0..explicit_len {
+            assert!(!is_off_curve(explicit, index) || !is_off_curve(explicit, index + 1));
+        }
+
+        // The stored points are kept in order.
+        let mut explicit_positions = explicit.positions.iter();
+        for position in &stored.positions {
+            assert!(explicit_positions.any(|explicit| explicit == position));
+        }
+    }
+
+    // Composite glyphs and CFF-based fonts have no raw contours.
+    let e_acute = font.glyph_for_char('é').unwrap();
+    assert_eq!(font.glyf_contours(e_acute, ImpliedPoints::AsStored), None);
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(
+        font.glyf_contours(font.glyph_for_char('o').unwrap(), ImpliedPoints::Explicit),
+        None
+    );
+}
+
+#[test]
+pub fn get_glyf_point_indices() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let o = font.glyph_for_char('o').unwrap();
+
+    // Stored points are numbered consecutively across contours.
+    let stored = font
+        .glyf_contours_with_point_indices(o, ImpliedPoints::AsStored)
+        .unwrap();
+    assert_eq!(
+        Some(&stored.outline),
+        font.glyf_contours(o, ImpliedPoints::AsStored).as_ref()
+    );
+    let stored_positions: Vec<Vector2F> = stored
+        .outline
+        .contours
+        .iter()
+        .flat_map(|contour| contour.positions.iter().cloned())
+        .collect();
+    let stored_indices: Vec<Option<u16>> = stored.point_indices.iter().flatten().cloned().collect();
+    let expected_indices: Vec<Option<u16>> = (0..stored_positions.len() as u16).map(Some).collect();
+    assert_eq!(stored_indices, expected_indices);
+
+    // Implied points have no index, and the stored points keep theirs.
+    let explicit = font
+        .glyf_contours_with_point_indices(o, ImpliedPoints::Explicit)
+        .unwrap();
+    assert_eq!(
+        Some(&explicit.outline),
+        font.glyf_contours(o, ImpliedPoints::Explicit).as_ref()
+    );
+    let mut implied_count = 0;
+    for (contour, indices) in explicit
+        .outline
+        .contours
+        .iter()
+        .zip(&explicit.point_indices)
+    {
+        assert_eq!(contour.positions.len(), indices.len());
+        for (position_index, point_index) in indices.iter().enumerate() {
+            match *point_index {
+                Some(point_index) => assert_eq!(
+                    contour.positions[position_index],
+                    stored_positions[point_index as usize]
+                ),
+                None => {
+                    assert!(contour.flags[position_index].is_empty());
+                    implied_count += 1;
+                }
+            }
+        }
+    }
+    assert!(implied_count > 0);
+
+    // CFF-based fonts have no point numbering.
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let o = font.glyph_for_char('o').unwrap();
+    assert_eq!(
+        font.glyf_contours_with_point_indices(o, ImpliedPoints::AsStored),
+        None
+    );
+}
+
+#[test]
+pub fn get_composite_components() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let e = font.glyph_for_char('e').unwrap();
+    assert_eq!(font.composite_components(e), None);
+
+    let e_acute = font.glyph_for_char('é').unwrap();
+    let components = font.composite_components(e_acute).unwrap();
+    assert_eq!(components.len(), 2);
+    assert!(components.iter().any(|component| component.glyph_id == e));
+    assert!(components
+        .iter()
+        .all(|component| component.glyph_id < font.glyph_count()));
+
+    // CFF-based fonts have no composite glyphs.
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(
+        font.composite_components(font.glyph_for_char('é').unwrap()),
+        None
+    );
+}
+
+// Every composite glyph of a bundled font, covering scaled, 2x2, and nested components, must
+// flatten to the same points as FreeType's outline.
+#[cfg(any(
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    feature = "loader-freetype"
+))]
+#[test]
+pub fn flatten_composite_glyphs_like_freetype() {
+    use font_kit::loaders::freetype::Font as FreeTypeFont;
+
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let freetype_font = FreeTypeFont::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let (mut scaled, mut two_by_two, mut nested) = (false, false, false);
+    let mut composite_count = 0;
+    for glyph_id in 0..font.glyph_count() {
+        let components = match font.composite_components(glyph_id) {
+            Some(components) => components,
+            None => continue,
+        };
+        composite_count += 1;
+        for component in &components {
+            let matrix = component.transform.matrix;
+            scaled |= matrix.m11() != 1.0 || matrix.m22() != 1.0;
+            two_by_two |= matrix.m12() != 0.0 || matrix.m21() != 0.0;
+            nested |= font.composite_components(component.glyph_id).is_some();
+        }
+
+        let outline = font
+            .glyf_outline(glyph_id, ImpliedPoints::Explicit)
+            .unwrap();
+        let mut builder = OutlineBuilder::new();
+        freetype_font
+            .outline(glyph_id, HintingOptions::None, &mut builder)
+            .unwrap();
+        assert_same_points(&outline, &builder.into_outline(), glyph_id);
+    }
+    assert!(composite_count > 100);
+    assert!(scaled && two_by_two && nested);
+
+    let e_acute = font.glyph_for_char('é').unwrap();
+    let outline = font.glyf_outline(e_acute, ImpliedPoints::AsStored).unwrap();
+    let e = font.glyph_for_char('e').unwrap();
+    let e_outline = font.glyf_contours(e, ImpliedPoints::AsStored).unwrap();
+    assert!(outline.contours.len() > e_outline.contours.len());
+    assert_eq!(
+        font.glyf_outline(font.glyph_count(), ImpliedPoints::AsStored),
+        Err(GlyphLoadingError::NoSuchGlyph)
+    );
+
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(
+        font.glyf_outline(e_acute, ImpliedPoints::AsStored),
+        Err(GlyphLoadingError::NoOutline)
+    );
+}
+
+// Raw `glyf` access has to read `loca` in the format that `head` declares: Inconsolata stores
+// offsets halved in 16 bits and EB Garamond stores them whole in 32 bits.
+#[cfg(any(
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    feature = "loader-freetype"
+))]
+#[test]
+pub fn read_glyf_with_short_and_long_loca() {
+    use font_kit::loaders::freetype::Font as FreeTypeFont;
+
+    for (path, long_offsets) in [
+        (FILE_PATH_INCONSOLATA_TTF, false),
+        (FILE_PATH_EB_GARAMOND_TTF, true),
+    ] {
+        let font = Font::from_path(path, 0).unwrap();
+        let head = font.load_font_table(OPENTYPE_TABLE_TAG_HEAD).unwrap();
+        assert_eq!(head[51] == 1, long_offsets);
+
+        let freetype_font = FreeTypeFont::from_path(path, 0).unwrap();
+        for glyph_id in 0..font.glyph_count() {
+            let outline = font
+                .glyf_outline(glyph_id, ImpliedPoints::Explicit)
+                .unwrap();
+            let mut builder = OutlineBuilder::new();
+            freetype_font
+                .outline(glyph_id, HintingOptions::None, &mut builder)
+                .unwrap();
+            assert_same_points(&outline, &builder.into_outline(), glyph_id);
+            assert_eq!(
+                font.glyph_is_empty(glyph_id),
+                Ok(outline.contours.is_empty())
+            );
+        }
+    }
+
+    // The same glyphs read back the same whichever format their offsets are stored in.
+    let short_data = synthesize_font(1000, &[(0x41, 1), (0x42, 2)]);
+    let short_font = Font::from_bytes(Arc::new(short_data), 0).unwrap();
+    let mut head = short_font.load_font_table(OPENTYPE_TABLE_TAG_HEAD).unwrap();
+    head[51] = 1;
+    let loca: Vec<u8> = short_font
+        .load_font_table(u32::from_be_bytes(*b"loca"))
+        .unwrap()
+        .chunks(2)
+        .flat_map(|offset| (u16::from_be_bytes([offset[0], offset[1]]) as u32 * 2).to_be_bytes())
+        .collect();
+    let long_data = synthesize_font_with_tables(
+        1000,
+        &[(0x41, 1), (0x42, 2)],
+        &[b"head", b"loca"],
+        vec![(b"head", head.to_vec()), (b"loca", loca)],
+    );
+    let long_font = Font::from_bytes(Arc::new(long_data), 0).unwrap();
+    for glyph_id in 0..3 {
+        assert_eq!(
+            long_font.glyf_contours(glyph_id, ImpliedPoints::AsStored),
+            short_font.glyf_contours(glyph_id, ImpliedPoints::AsStored)
+        );
+        assert_eq!(
+            long_font.glyph_is_empty(glyph_id),
+            short_font.glyph_is_empty(glyph_id)
+        );
+    }
+    assert!(long_font
+        .glyf_contours(2, ImpliedPoints::AsStored)
+        .is_some());
+}
+
+// Asserts that the contours of two outlines have the same points, regardless of which point each
+// contour starts from and whether it repeats its first point at the end. FreeType rounds the
+// points of transformed components to whole font units, at each level of nesting, so coordinates
+// may differ by up to a unit.
+#[cfg(any(
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    feature = "loader-freetype"
+))]
+fn assert_same_points(outline: &Outline, expected: &Outline, glyph_id: u32) {
+    fn points(contour: &Contour) -> Vec<(Vector2F, bool)> {
+        let flags = contour.flags.iter().map(PointFlags::is_empty);
+        let mut points: Vec<_> = contour.positions.iter().cloned().zip(flags).collect();
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        points
+    }
+
+    assert_eq!(
+        outline.contours.len(),
+        expected.contours.len(),
+        "glyph {}",
+        glyph_id
+    );
+    for (contour, expected) in outline.contours.iter().zip(expected.contours.iter()) {
+        let (points, expected) = (points(contour), points(expected));
+        assert_eq!(points.len(), expected.len(), "glyph {}", glyph_id);
+        let matches = |start: usize| {
+            expected
+                .iter()
+                .enumerate()
+                .all(|(index, &(position, on_curve))| {
+                    let point = points[(start + index) % points.len()];
+                    let difference = point.0 - position;
+                    point.1 == on_curve
+                        && difference.x().abs() <= 1.0
+                        && difference.y().abs() <= 1.0
+                })
+        };
+        assert!((0..points.len()).any(matches), "glyph {}", glyph_id);
+    }
+}
+
+#[test]
+pub fn compare_fonts_by_face() {
+    let font = Font::from_path(TEST_FONT_COLLECTION_FILE_PATH, 0).unwrap();
+    let clone = font.clone();
+    assert!(clone.copy_font_data().is_some());
+    assert_eq!(font, clone);
+    assert_eq!(clone, font);
+
+    // Loading the same face again gives an equal font, unlike loading another face.
+    let font_data = Arc::new(fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap());
+    assert_eq!(Font::from_bytes(font_data.clone(), 0).unwrap(), font);
+    assert_ne!(Font::from_bytes(font_data, 1).unwrap(), font);
+
+    // Instances are equal only at the same variation values. The parser loader only reads the
+    // default instance.
+    {
+        let wght = u32::from_be_bytes(*b"wght");
+        let font_data =
+            synthesize_varying_font(1000, &[(0x41, 1)], &[(b"wght", 100.0, 400.0, 900.0)], 100);
+        let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+        let bold = font.with_variations(&[(wght, 700.0)]).unwrap();
+        assert_ne!(bold, font);
+        assert_eq!(bold, font.with_variations(&[(wght, 700.0)]).unwrap());
+    }
+}
+
+#[test]
+pub fn compare_handles() {
+    let path = Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 1);
+    assert_eq!(path, path.clone());
+    assert_ne!(
+        path,
+        Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 0)
+    );
+
+    let font_data = fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    let memory = Handle::from_memory(Arc::new(font_data.clone()), 1);
+    assert_eq!(memory, Handle::from_memory(Arc::new(font_data), 1));
+    assert_ne!(memory, path);
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn share_memory_font_data_through_source() {
+    let font_data = Arc::new(fs::read(TEST_FONT_FILE_PATH).unwrap());
+    let source =
+        MemSource::from_fonts(iter::once(Handle::from_memory(font_data.clone(), 0))).unwrap();
+
+    let handle = source
+        .select_by_postscript_name(TEST_FONT_POSTSCRIPT_NAME)
+        .unwrap();
+    match handle {
+        Handle::Memory { ref bytes, .. } => assert!(Arc::ptr_eq(bytes, &font_data)),
+        _ => panic!("expected a memory handle"),
+    }
+    let font = handle.load().unwrap();
+    assert!(Arc::ptr_eq(&font.copy_font_data().unwrap(), &font_data));
+
+    let handle = source
+        .select_best_match(
+            &[FamilyName::Title("EB Garamond".into())],
+            &Properties::new(),
+        )
+        .unwrap();
+    let font = handle.load().unwrap();
+    assert!(Arc::ptr_eq(&font.copy_font_data().unwrap(), &font_data));
+}
+
+// A source that wraps a `MemSource` and also lists a family whose fonts can't be read.
+#[cfg(feature = "source")]
+struct UnreadableFamilySource {
+    source: MemSource,
+    unreadable_family_name: &'static str,
+}
+
+#[cfg(feature = "source")]
+impl Source for UnreadableFamilySource {
+    fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        self.source.all_fonts()
+    }
+
+    fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        self.source.all_families()
+    }
+
+    fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        if family_name == self.unreadable_family_name {
+            return Ok(FamilyHandle::new());
+        }
+        self.source.select_family_by_name(family_name)
+    }
+
+    // Only the unreadable family is empty.
+    fn select_candidates_in_family(
+        &self,
+        family: &FamilyHandle,
+    ) -> Result<Vec<matching::Candidate>, SelectionError> {
+        if family.is_empty() {
+            return Err(SelectionError::CannotAccessSource { reason: None });
+        }
+        self.source.select_candidates_in_family(family)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn select_family_from_font_family_list() {
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+            Handle::from_path(FILE_PATH_EB_GARAMOND_ITALIC_OTF.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+
+    // Families that aren't installed, whether concrete or generic, are skipped.
+    let family_names = [
+        FamilyName::Title("Not A Real Font Family".into()),
+        FamilyName::Fantasy,
+        FamilyName::Title("EB Garamond".into()),
+        FamilyName::Serif,
+    ];
+    let handle = source
+        .select_family_from_list(&family_names, Properties::new().style(Style::Italic))
+        .unwrap();
+    assert_eq!(
+        Font::from_handle(&handle)
+            .unwrap()
+            .postscript_name()
+            .unwrap(),
+        "EBGaramond12-Italic"
+    );
+
+    assert_eq!(
+        source.select_family_from_list(&family_names[..2], &Properties::new()),
+        Err(SelectionError::NotFound)
+    );
+    assert_eq!(
+        source.select_family_from_list(&[], &Properties::new()),
+        Err(SelectionError::NotFound)
+    );
+
+    // A family whose fonts can't be read moves the search on to the next family, while
+    // `select_best_match()` stops at it.
+    let source = UnreadableFamilySource {
+        source,
+        unreadable_family_name: "Unreadable",
+    };
+    let family_names = [
+        FamilyName::Title("Unreadable".into()),
+        FamilyName::Title("EB Garamond".into()),
+    ];
+    let handle = source
+        .select_family_from_list(&family_names, &Properties::new())
+        .unwrap();
+    assert_eq!(
+        Font::from_handle(&handle)
+            .unwrap()
+            .postscript_name()
+            .unwrap(),
+        TEST_FONT_POSTSCRIPT_NAME
+    );
+    assert_eq!(
+        source.select_best_match(&family_names, &Properties::new()),
+        Err(SelectionError::CannotAccessSource { reason: None })
+    );
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn find_matching_font() {
+    let regular_handle = Handle::from_path(TEST_FONT_FILE_PATH.into(), 0);
+    let italic_handle = Handle::from_path(FILE_PATH_EB_GARAMOND_ITALIC_OTF.into(), 0);
+    let font = Font::from_bytes(Arc::new(fs::read(TEST_FONT_FILE_PATH).unwrap()), 0).unwrap();
+
+    let source =
+        MemSource::from_fonts(vec![regular_handle, italic_handle.clone()].into_iter()).unwrap();
+    let (handle, quality) = source.find_matching(&font).unwrap();
+    assert_eq!(quality, MatchQuality::Exact);
+    assert_eq!(
+        Font::from_handle(&handle).unwrap().postscript_name(),
+        font.postscript_name()
+    );
+
+    // Only another face of the same family is available.
+    let source = MemSource::from_fonts(iter::once(italic_handle)).unwrap();
+    let (handle, quality) = source.find_matching(&font).unwrap();
+    assert_eq!(quality, MatchQuality::Family);
+    assert_eq!(
+        Font::from_handle(&handle).unwrap().properties().style,
+        Style::Italic
+    );
+
+    assert!(MemSource::empty().find_matching(&font).is_none());
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn select_by_postscript_and_full_name() {
+    // The OTF and TTF builds of EB Garamond share their names, so the first one must win.
+    let mut source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+            Handle::from_path(FILE_PATH_EB_GARAMOND_TTF.into(), 0),
+            Handle::from_path(FILE_PATH_EB_GARAMOND_ITALIC_OTF.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+
+    let scan = |source: &MemSource, matches: &dyn Fn(&Font) -> bool| {
+        source
+            .all_fonts()
+            .unwrap()
+            .into_iter()
+            .find(|handle| matches(&Font::from_handle(handle).unwrap()))
+            .map(|handle| format!("{:?}", handle))
+    };
+    let check_agrees_with_scan = |source: &MemSource| {
+        for handle in source.all_fonts().unwrap() {
+            let font = Font::from_handle(&handle).unwrap();
+            let postscript_name = font.postscript_name().unwrap();
+            let full_name = font.full_name();
+            assert_eq!(
+                source
+                    .select_by_postscript_name(&postscript_name)
+                    .ok()
+                    .map(|handle| format!("{:?}", handle)),
+                scan(source, &|font| font.postscript_name().as_ref()
+                    == Some(&postscript_name)),
+            );
+            assert_eq!(
+                source
+                    .select_by_full_name(&full_name)
+                    .ok()
+                    .map(|handle| format!("{:?}", handle)),
+                scan(source, &|font| font.full_name() == full_name),
+            );
+        }
+    };
+    check_agrees_with_scan(&source);
+    assert!(matches!(
+        source.select_by_postscript_name("Inconsolata-Regular"),
+        Err(SelectionError::NotFound)
+    ));
+
+    // Adding a font makes it visible to lookups that already built the index.
+    source
+        .add_font(Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0))
+        .unwrap();
+    check_agrees_with_scan(&source);
+    let handle = source
+        .select_by_postscript_name("Inconsolata-Regular")
+        .unwrap();
+    assert_eq!(
+        Font::from_handle(&handle).unwrap().family_name(),
+        "Inconsolata"
+    );
+}
+
+// Lookups served from the index give the same handle as the first one, which builds it.
+#[cfg(feature = "source")]
+#[test]
+pub fn repeat_postscript_name_lookups() {
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(FILE_PATH_EB_GARAMOND_ITALIC_OTF.into(), 0),
+            Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0),
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+
+    for _ in 0..3 {
+        match source
+            .select_by_postscript_name(TEST_FONT_POSTSCRIPT_NAME)
+            .unwrap()
+        {
+            Handle::Path {
+                ref path,
+                font_index,
+            } => {
+                assert_eq!(path, std::path::Path::new(TEST_FONT_FILE_PATH));
+                assert_eq!(font_index, 0);
+            }
+            _ => panic!("expected a path handle"),
+        }
+    }
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn analyze_string_coverage() {
+    let primary_handle = Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0);
+    let primary = Font::from_handle(&primary_handle).unwrap();
+    let source = MemSource::from_fonts(
+        vec![
+            primary_handle,
+            Handle::from_path(FILE_PATH_EB_GARAMOND_ITALIC_OTF.into(), 0),
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+
+    // Inconsolata has no Greek, which EB Garamond has, and neither has Han characters or emoji.
+    let text = "abc αβγ δ 漢字😀";
+    let runs = source.analyze_string_coverage(text, &primary, &Properties::new(), "en-US");
+    let runs: Vec<_> = runs
+        .into_iter()
+        .map(|(range, handle)| (range, Font::from_handle(&handle).unwrap()))
+        .collect();
+    assert_eq!(runs.len(), 3);
+    assert_eq!(runs[0].0, 0..4);
+    assert_eq!(runs[1].0, 4..text.find('漢').unwrap());
+    assert_eq!(runs[2].0, text.find('漢').unwrap()..text.len());
+    assert_eq!(runs[0].1.postscript_name(), primary.postscript_name());
+    assert_eq!(
+        runs[1].1.postscript_name().unwrap(),
+        TEST_FONT_POSTSCRIPT_NAME
+    );
+    assert_eq!(runs[2].1.postscript_name(), primary.postscript_name());
+
+    // Text that the primary font covers is a single run.
+    let runs = source.analyze_string_coverage("abc def", &primary, &Properties::new(), "en-US");
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].0, 0..7);
+    assert!(source
+        .analyze_string_coverage("", &primary, &Properties::new(), "en-US")
+        .is_empty());
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn select_fallback_for_character() {
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0),
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+
+    let handle = source
+        .select_fallback_for_character('β', &Properties::new(), "en-US")
+        .unwrap();
+    let font = Font::from_handle(&handle).unwrap();
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+    assert_eq!(
+        source
+            .select_fallback_for_character('漢', &Properties::new(), "en-US")
+            .unwrap_err(),
+        SelectionError::NotFound
+    );
+}
+
+// A source whose fallback lookups count how often they are made instead of searching fonts.
+#[cfg(feature = "source")]
+#[derive(Default)]
+struct CountingSource {
+    fallback_queries: AtomicUsize,
+}
+
+#[cfg(feature = "source")]
+impl Source for CountingSource {
+    fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        Ok(vec![])
+    }
+
+    fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        Ok(vec![])
+    }
+
+    fn select_family_by_name(&self, _: &str) -> Result<FamilyHandle, SelectionError> {
+        Err(SelectionError::NotFound)
+    }
+
+    fn select_fallback_for_character(
+        &self,
+        character: char,
+        _: &Properties,
+        _: &str,
+    ) -> Result<Handle, SelectionError> {
+        self.fallback_queries.fetch_add(1, Ordering::SeqCst);
+        if character.is_ascii() {
+            Ok(Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0))
+        } else {
+            Err(SelectionError::NotFound)
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn cache_fallbacks_for_characters() {
+    let source = FallbackCacheSource::new(CountingSource::default());
+    let queries = || source.source().fallback_queries.load(Ordering::SeqCst);
+    let properties = Properties::new();
+
+    assert!(source
+        .select_fallback_for_character('a', &properties, "en-US")
+        .is_ok());
+    assert!(source
+        .select_fallback_for_character('a', &properties, "en-US")
+        .is_ok());
+    assert_eq!(queries(), 1);
+
+    // Failed lookups are cached too.
+    for _ in 0..2 {
+        assert_eq!(
+            source
+                .select_fallback_for_character('漢', &properties, "ja")
+                .unwrap_err(),
+            SelectionError::NotFound
+        );
+    }
+    assert_eq!(queries(), 2);
+
+    // Everything the lookup depends on is part of the key.
+    let bold = *Properties::new().weight(Weight::BOLD);
+    let italic = *Properties::new().style(Style::Italic);
+    let condensed = *Properties::new().stretch(Stretch::CONDENSED);
+    for properties in [bold, italic, condensed].iter() {
+        source
+            .select_fallback_for_character('a', properties, "en-US")
+            .unwrap();
+    }
+    source
+        .select_fallback_for_character('a', &properties, "fr")
+        .unwrap();
+    source
+        .select_fallback_for_character('b', &properties, "en-US")
+        .unwrap();
+    assert_eq!(queries(), 7);
+    assert_eq!(source.cached_len(), 7);
+
+    source.invalidate();
+    assert_eq!(source.cached_len(), 0);
+    source
+        .select_fallback_for_character('a', &properties, "en-US")
+        .unwrap();
+    assert_eq!(queries(), 8);
+
+    // The cache never grows past its capacity.
+    let source = FallbackCacheSource::with_capacity(CountingSource::default(), 4);
+    for character in 'a'..='z' {
+        source
+            .select_fallback_for_character(character, &properties, "en-US")
+            .unwrap();
+        assert!(source.cached_len() <= 4);
+    }
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn cache_fallbacks_from_many_threads() {
+    let source = Arc::new(FallbackCacheSource::with_capacity(
+        CountingSource::default(),
+        64,
+    ));
+    let invalidator = source.invalidator();
+    let threads: Vec<_> = (0..8)
+        .map(|thread_index| {
+            let source = source.clone();
+            let invalidator = invalidator.clone();
+            thread::spawn(move || {
+                let properties = Properties::new();
+                for iteration in 0..500 {
+                    let character = char::from(b' ' + ((thread_index * 7 + iteration) % 95) as u8);
+                    source
+                        .select_fallback_for_character(character, &properties, "en-US")
+                        .unwrap();
+                    if iteration % 97 == 0 {
+                        invalidator.invalidate();
+                    }
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert!(source.cached_len() <= 64);
+}
+
+#[test]
+pub fn load_font_with_zero_units_per_em() {
+    let is_finite = |outline: &Outline| {
+        outline.contours.iter().all(|contour| {
+            contour
+                .positions
+                .iter()
+                .all(|position| position.x().is_finite() && position.y().is_finite())
+        })
+    };
+
+    // FreeType refuses to load such fonts; the other loaders substitute a sane value.
+    let data = synthesize_font(0, &[(0x41, 1)]);
+    if let Ok(font) = Font::from_bytes(Arc::new(data), 0) {
+        assert!(font.metrics().units_per_em > 0);
+        let mut builder = OutlineBuilder::new();
+        font.outline(1, HintingOptions::None, &mut builder).unwrap();
+        assert!(is_finite(&builder.into_outline()));
+    }
+
+    // Grid fitting at a size of 0 mustn't produce NaN coordinates either.
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let mut builder = OutlineBuilder::new();
+    font.outline(glyph_id, HintingOptions::Full(0.0), &mut builder)
+        .unwrap();
+    let outline = builder.into_outline();
+    assert!(!outline.contours.is_empty());
+    assert!(is_finite(&outline));
+}
+
+#[test]
+pub fn get_glyph_for_astral_char() {
+    let data = synthesize_font(1000, &[(0x1f600, 1), (0x20000, 2)]);
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+    assert_eq!(font.glyph_for_char('\u{1f600}'), Some(1));
+    assert_eq!(font.glyph_for_char('\u{20000}'), Some(2));
+    assert_eq!(font.glyph_for_char('\u{20001}'), None);
+    assert_eq!(font.glyph_for_char('\u{f600}'), None);
+}
+
+#[test]
+pub fn get_glyphs_for_str() {
+    let data = synthesize_font(1000, &[(0x41, 3), (0x1f600, 1), (0x20000, 2)]);
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+    assert_eq!(
+        font.glyphs_for_str("A\u{1f600}b\u{20000}A"),
+        [Some(3), Some(1), None, Some(2), Some(3)]
+    );
+    assert_eq!(font.glyphs_for_str(""), []);
+
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let text = "Quick brown fox, über Straße — ✓";
+    let expected: Vec<_> = text
+        .chars()
+        .map(|character| font.glyph_for_char(character))
+        .collect();
+    assert_eq!(font.glyphs_for_str(text), expected);
+}
+
+#[test]
+pub fn get_char_to_glyph_mappings() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let mappings = font.char_to_glyph_mappings();
+    assert!(mappings.len() > 200);
+    assert!(mappings.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    for &(code_point, glyph_id) in &mappings {
+        let character = char::from_u32(code_point).unwrap();
+        assert_eq!(font.glyph_for_char(character), Some(glyph_id));
+    }
+    let a = mappings
+        .iter()
+        .find(|&&(code_point, _)| code_point == 'A' as u32);
+    assert_eq!(a.map(|&(_, glyph_id)| glyph_id), font.glyph_for_char('A'));
+
+    let data = synthesize_font(1000, &[(0x1f600, 1), (0x20000, 2)]);
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+    assert_eq!(font.char_to_glyph_mappings(), [(0x1f600, 1), (0x20000, 2)]);
+}
+
+#[test]
+pub fn iterate_char_mappings() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let mappings: Vec<(char, u32)> = font.char_mappings().collect();
+    assert!(mappings.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    let code_points: Vec<_> = mappings
+        .iter()
+        .map(|&(character, glyph_id)| (character as u32, glyph_id))
+        .collect();
+    assert_eq!(code_points, font.char_to_glyph_mappings());
+
+    // Every character of the Basic Multilingual Plane, and a sample of the others, is mapped
+    // exactly when `glyph_for_char()` finds a glyph for it.
+    let probed: Vec<(char, u32)> = (0..0x10000)
+        .chain((0x10000..=0x10ffff).step_by(97))
+        .filter_map(char::from_u32)
+        .filter_map(|character| Some((character, font.glyph_for_char(character)?)))
+        .collect();
+    let sampled: Vec<(char, u32)> = mappings
+        .iter()
+        .cloned()
+        .filter(|&(character, _)| character < '\u{10000}' || (character as u32 - 0x10000) % 97 == 0)
+        .collect();
+    assert_eq!(sampled, probed);
+
+    // Characters past the Basic Multilingual Plane come from a format 12 subtable.
+    let data = synthesize_font(1000, &[(0x41, 1), (0x1f600, 2), (0x20000, 3)]);
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+    let mut char_mappings = font.char_mappings();
+    assert_eq!(char_mappings.next(), Some(('A', 1)));
+    assert_eq!(char_mappings.next(), Some(('\u{1f600}', 2)));
+    assert_eq!(char_mappings.next(), Some(('\u{20000}', 3)));
+    assert_eq!(char_mappings.next(), None);
+}
+
+#[test]
+pub fn get_head_revision_and_dates() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    assert_eq!((font.font_revision().unwrap() * 1000.0).round(), 2001.0);
+    assert_eq!(
+        font.created_date().unwrap(),
+        UNIX_EPOCH + Duration::from_secs(1_485_946_471)
+    );
+    assert_eq!(
+        font.modified_date().unwrap(),
+        UNIX_EPOCH + Duration::from_secs(1_490_012_432)
+    );
+}
+
+#[test]
+pub fn get_metrics_with_fallbacks() {
+    // Declared heights are kept.
+    let mut font_data = fs::read(FILE_PATH_EB_GARAMOND_TTF).unwrap();
+    let font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    let (metrics, source) = font.metrics_with_fallbacks();
+    assert_eq!(source, MetricsSource::default());
+    assert_eq!(metrics.x_height, font.metrics().x_height);
+    assert_eq!(metrics.cap_height, font.metrics().cap_height);
+
+    // Without them, they're measured from 'x' and 'H', which are flat-topped, so they come out
+    // close to what the font declared.
+    let os_2_offset = table_offset(&font_data, b"OS/2");
+    font_data[os_2_offset + 86..os_2_offset + 90].fill(0);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let (metrics, source) = font.metrics_with_fallbacks();
+    assert!(source.x_height_measured && source.cap_height_measured);
+    let units_per_em = metrics.units_per_em as f32;
+    assert!(metrics.x_height > 0.4 * units_per_em && metrics.x_height < 0.8 * units_per_em);
+    assert!((metrics.x_height - 410.0).abs() < 10.0);
+    assert!((metrics.cap_height - 650.0).abs() < 10.0);
+
+    // Fonts with none of the glyphs to measure report heights as `metrics()` does.
+    let font = Font::from_bytes(Arc::new(synthesize_font(1000, &[(0x41, 1)])), 0).unwrap();
+    let (metrics, source) = font.metrics_with_fallbacks();
+    assert_eq!(source, MetricsSource::default());
+    assert_eq!(metrics.x_height, font.metrics().x_height);
+}
+
+#[test]
+pub fn get_win_metrics() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let metrics = font.metrics();
+    assert_eq!(metrics.win_ascent, 910.0);
+    assert_eq!(metrics.win_descent, -324.0);
+
+    // Without an `OS/2` table, the `hhea` ascender and descender are used.
+    let data = synthesize_font_without_tables(1000, &[(0x41, 1)], &[b"OS/2"]);
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+    let metrics = font.metrics();
+    assert_eq!(metrics.win_ascent, 800.0);
+    assert_eq!(metrics.win_descent, -200.0);
+}
+
+#[test]
+pub fn rasterize_notdef_glyph() {
+    // EB Garamond's `.notdef` is a box, which renders like any other glyph.
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert_eq!(font.notdef_glyph(), 0);
+    let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        font.notdef_glyph(),
+        24.0,
+        Transform2F::from_translation(Vector2F::new(4.0, 28.0)),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+    assert!(canvas.pixels.iter().any(|&value| value != 0));
+}
+
+#[test]
+pub fn synthesize_notdef_for_blank_notdef() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert!(!font.notdef_is_blank());
+
+    // Inconsolata's `.notdef` is empty.
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    assert!(font.notdef_is_blank());
+
+    let mut builder = OutlineBuilder::new();
+    let advance = font.synthesize_notdef(100.0, &mut builder);
+    let outline = builder.into_outline();
+    assert_eq!(advance, Vector2F::new(50.0, 0.0));
+    assert_eq!(outline.contours.len(), 2);
+
+    // The box spans the baseline to the ascender, with 1/20 em side bearings and strokes.
+    let metrics = font.metrics();
+    let ascent = metrics.ascent * 100.0 / metrics.units_per_em as f32;
+    let expected_bounds = [
+        RectF::from_points(Vector2F::new(5.0, 0.0), Vector2F::new(45.0, ascent)),
+        RectF::from_points(Vector2F::new(10.0, 5.0), Vector2F::new(40.0, ascent - 5.0)),
+    ];
+    for (contour, expected_bounds) in outline.contours.iter().zip(expected_bounds.iter()) {
+        assert_eq!(contour.positions.len(), 4);
+        let bounds = contour.positions[1..].iter().fold(
+            RectF::new(contour.positions[0], Vector2F::zero()),
+            |bounds, &point| bounds.union_point(point),
+        );
+        assert!((bounds.origin() - expected_bounds.origin()).length() < 0.001);
+        assert!((bounds.lower_right() - expected_bounds.lower_right()).length() < 0.001);
+    }
+}
+
+#[test]
+pub fn rasterize_mirrored_glyph() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let size = 32.0;
+    let rasterize = |transform: Transform2F| {
+        let raster_rect = font
+            .raster_bounds(
+                glyph_id,
+                size,
+                transform,
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+        let rasterized_glyph = font
+            .rasterize_glyph(
+                &mut canvas,
+                glyph_id,
+                size,
+                Transform2F::from_translation(-raster_rect.origin().to_f32()) * transform,
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        (raster_rect, rasterized_glyph.ink_rect, canvas)
+    };
+
+    let (rect, ink_rect, canvas) = rasterize(Transform2F::default());
+    for (scale, mirror_x, mirror_y) in [
+        (Vector2F::new(-1.0, 1.0), true, false),
+        (Vector2F::new(1.0, -1.0), false, true),
+        (Vector2F::new(-1.0, -1.0), true, true),
+    ] {
+        let (mirrored_rect, mirrored_ink_rect, mirrored_canvas) =
+            rasterize(Transform2F::from_scale(scale));
+        assert_eq!(mirrored_rect.size(), rect.size());
+        assert_eq!(mirrored_ink_rect.size(), ink_rect.size());
+        let mut mismatches = 0;
+        for y in 0..rect.height() {
+            for x in 0..rect.width() {
+                let mirrored_x = if mirror_x { rect.width() - 1 - x } else { x };
+                let mirrored_y = if mirror_y { rect.height() - 1 - y } else { y };
+                let value = canvas.pixels[y as usize * canvas.stride + x as usize];
+                let mirrored_value = mirrored_canvas.pixels
+                    [mirrored_y as usize * mirrored_canvas.stride + mirrored_x as usize];
+                if (value as i32 - mirrored_value as i32).abs() > 1 {
+                    mismatches += 1;
+                }
+            }
+        }
+        assert_eq!(mismatches, 0, "mirrored by {:?}", scale);
+    }
+}
+
+#[test]
+pub fn convert_font_units_to_pixels() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let metrics = font.metrics();
+    let scale = metrics.scale_for(18.0);
+    let pixels_per_unit = 18.0 / metrics.units_per_em as f32;
+    assert_eq!(scale.pixels_per_unit(), pixels_per_unit);
+    assert_eq!(metrics.ascent_px(18.0), metrics.ascent * pixels_per_unit);
+    assert_eq!(metrics.descent_px(18.0), metrics.descent * pixels_per_unit);
+    assert_eq!(
+        metrics.line_gap_px(18.0),
+        metrics.line_gap * pixels_per_unit
+    );
+    assert_eq!(
+        metrics.cap_height_px(18.0),
+        metrics.cap_height * pixels_per_unit
+    );
+    assert_eq!(
+        metrics.x_height_px(18.0),
+        metrics.x_height * pixels_per_unit
+    );
+    assert_eq!(
+        scale.to_px_rect(metrics.bounding_box),
+        metrics.bounding_box * pixels_per_unit
+    );
+}
+
+// Without hinting or the LCD filter, FreeType covers exactly the pixels that the typographic
+// bounds, scaled with `FontUnitScale`, touch.
+#[cfg(any(
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    feature = "loader-freetype"
+))]
+#[test]
+pub fn raster_bounds_scale_typographic_bounds() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let pixels_per_unit = 24.0 / font.metrics().units_per_em as f32;
+    for character in "AgÅfj".chars() {
+        let glyph_id = font.glyph_for_char(character).unwrap();
+        let bounds = font.typographic_bounds(glyph_id).unwrap() * pixels_per_unit;
+        let expected = RectF::new(
+            Vector2F::new(bounds.origin_x(), -bounds.origin_y() - bounds.height()),
+            bounds.size(),
+        )
+        .round_out()
+        .to_i32();
+        assert_eq!(
+            font.raster_bounds(
+                glyph_id,
+                24.0,
+                Transform2F::default(),
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            ),
+            Ok(expected)
+        );
+    }
+}
+
+#[test]
+pub fn get_string_ink_bounds() {
+    let font_data = synthesize_font(1000, &[(0x41, 1)]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+
+    // Each glyph is a square half an em wide, and the advance is a full em. Characters without a
+    // glyph take up no space.
+    let expected = RectF::new(Vector2F::new(0.0, 0.0), Vector2F::new(15.0, 5.0));
+    assert_eq!(font.string_ink_bounds("AA", 10.0, false), expected);
+    assert_eq!(
+        font.string_ink_bounds("A\u{10ffff}A", 10.0, false),
+        expected
+    );
+    assert_eq!(
+        font.string_ink_bounds("\u{10ffff}", 10.0, false),
+        RectF::default()
+    );
+    assert_eq!(font.string_ink_bounds("", 10.0, false), RectF::default());
+
+    // Kerning pulls "AV" together.
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let (a, v) = (
+        font.glyph_for_char('A').unwrap(),
+        font.glyph_for_char('V').unwrap(),
+    );
+    let kerning = font.pair_kerning(a, v).unwrap();
+    assert!(kerning < 0.0);
+    assert_eq!(font.pair_kerning(a, a).unwrap_or(0.0), 0.0);
+    let scale = 36.0 / font.metrics().units_per_em as f32;
+    let unkerned = font.string_ink_bounds("AV", 36.0, false);
+    let kerned = font.string_ink_bounds("AV", 36.0, true);
+    assert!((kerned.width() - (unkerned.width() + kerning * scale)).abs() < 0.01);
+    assert_eq!(kerned.origin(), unkerned.origin());
+}
+
+#[test]
+pub fn get_aat_pair_kerning() {
+    // A `kerx` table with one format 0 subtable kerning "AV" by -80 units.
+    let mut kerx = vec![];
+    kerx.extend(2u16.to_be_bytes());
+    kerx.extend(0u16.to_be_bytes());
+    kerx.extend(1u32.to_be_bytes());
+    for value in [28u32 + 6, 0, 0, 1, 6, 0, 0] {
+        kerx.extend(value.to_be_bytes());
+    }
+    for value in [1u16, 2, (-80i16) as u16] {
+        kerx.extend(value.to_be_bytes());
+    }
+    // An empty `morx` table, which is enough to mark the font as AAT.
+    let mut morx = vec![];
+    for value in [2u16, 0] {
+        morx.extend(value.to_be_bytes());
+    }
+    morx.extend(0u32.to_be_bytes());
+
+    let font_data = synthesize_font_with_tables(
+        1000,
+        &[(0x41, 1), (0x56, 2)],
+        &[],
+        vec![(b"kerx", kerx), (b"morx", morx)],
+    );
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let shaping_tables = font.shaping_tables();
+    assert_eq!(
+        shaping_tables,
+        ShapingTables {
+            gsub: false,
+            gpos: false,
+            morx: true,
+            kerx: true,
+            kern: false,
+        }
+    );
+    assert!(shaping_tables.is_aat());
+
+    let (a, v) = (
+        font.glyph_for_char('A').unwrap(),
+        font.glyph_for_char('V').unwrap(),
+    );
+    assert_eq!(font.pair_kerning(a, v), Some(-80.0));
+    assert_eq!(font.pair_kerning(v, a), None);
+    assert_eq!(
+        font.string_ink_bounds("AV", 10.0, true).width(),
+        font.string_ink_bounds("AV", 10.0, false).width() - 0.8
+    );
+
+    // OpenType fonts report their layout tables too.
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let shaping_tables = font.shaping_tables();
+    assert!(shaping_tables.gsub && shaping_tables.gpos);
+    assert!(!shaping_tables.is_aat());
+    for code in [b"GSUB", b"GPOS", b"morx", b"head"] {
+        let tag = u32::from_be_bytes(*code);
+        assert_eq!(
+            font.has_font_table(tag),
+            font.load_font_table(tag).is_some()
+        );
+    }
+}
+
+#[test]
+pub fn detect_kerning_data() {
+    // OpenType fonts kern with the `kern` feature of their `GPOS` table.
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert!(font.has_kerning());
+
+    // A classic `kern` table with one format 0 subtable kerning "AV" by -80 units.
+    let mut kern = vec![];
+    for value in [0u16, 1, 0, 20, 0x0001, 1, 6, 0, 0, 1, 2, (-80i16) as u16] {
+        kern.extend(value.to_be_bytes());
+    }
+    let font_data =
+        synthesize_font_with_tables(1000, &[(0x41, 1), (0x56, 2)], &[], vec![(b"kern", kern)]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert!(font.has_kerning());
+    assert!(font.clone().has_kerning());
+    assert_eq!(font.pair_kerning(1, 2), Some(-80.0));
+
+    let font = Font::from_bytes(Arc::new(synthesize_font(1000, &[(0x41, 1)])), 0).unwrap();
+    assert!(!font.has_kerning());
+}
+
+#[test]
+pub fn pitch_info_of_coding_and_text_fonts() {
+    // Every loader reads the same signals from the font itself, so these agree across platforms.
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    assert_eq!(
+        font.pitch_info(),
+        PitchInfo {
+            post_flag: Some(true),
+            os2_panose_mono: Some(true),
+            measured_uniform: Some(true),
+        }
+    );
+    assert!(font.is_monospace());
+
+    for path in [TEST_FONT_FILE_PATH, FILE_PATH_EB_GARAMOND_TTF] {
+        let font = Font::from_path(path, 0).unwrap();
+        assert_eq!(
+            font.pitch_info(),
+            PitchInfo {
+                post_flag: Some(false),
+                os2_panose_mono: Some(false),
+                measured_uniform: Some(false),
+            }
+        );
+        assert!(!font.is_monospace());
+    }
+
+    // A fixed-pitch font with proportional digits, as some coding fonts have: only
+    // the letters are measured, so it is monospaced.
+    let fixed_pitch_font = |advances: &[u16]| {
+        let mut post = vec![0; 32];
+        post[0..4].copy_from_slice(&0x00030000u32.to_be_bytes());
+        post[15] = 1;
+        let hmtx = advances
+            .iter()
+            .flat_map(|&advance| [advance, 0])
+            .flat_map(|value| value.to_be_bytes())
+            .collect();
+        let data = synthesize_font_with_tables(
+            1000,
+            &[(0x31, 3), (0x41, 1), (0x42, 2)],
+            &[b"post", b"hmtx"],
+            vec![(b"post", post), (b"hmtx", hmtx)],
+        );
+        Font::from_bytes(Arc::new(data), 0).unwrap()
+    };
+    let font = fixed_pitch_font(&[600, 600, 600, 400]);
+    let pitch_info = font.pitch_info();
+    assert_eq!(pitch_info.post_flag, Some(true));
+    assert_eq!(pitch_info.measured_uniform, Some(true));
+    assert!(pitch_info.is_monospace());
+    assert!(font.is_monospace());
+
+    // A font that claims to be fixed-pitch but whose letters' advances differ is only
+    // monospaced by its flag.
+    let font = fixed_pitch_font(&[600, 1000, 600, 600]);
+    let pitch_info = font.pitch_info();
+    assert_eq!(pitch_info.post_flag, Some(true));
+    assert_eq!(pitch_info.measured_uniform, Some(false));
+    assert!(!pitch_info.is_monospace());
+    assert!(font.is_monospace());
+
+    // Without a `post` table, uniform advances are enough.
+    let data = synthesize_font_without_tables(1000, &[(0x41, 1), (0x42, 2)], &[b"post"]);
+    let font = Font::from_bytes(Arc::new(data), 0).unwrap();
+    assert_eq!(font.pitch_info().post_flag, None);
+    assert!(font.pitch_info().is_monospace());
+}
+
+#[test]
+pub fn rasterize_colr_glyphs() {
+    // Glyph 3 is a version 0 color glyph: glyph 1 in red. Glyph 2 is a version 1 color glyph:
+    // glyph 1 filled with a gradient from red on the left to blue on the right.
+    let colr = synthesize_colr_table();
+    let mut cpal = vec![];
+    for value in [0u16, 2, 1, 2, 0, 14, 0] {
+        cpal.extend(value.to_be_bytes());
+    }
+    cpal.extend([0x00, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00, 0xff]);
+
+    let font_data = synthesize_font_with_tables(
+        1000,
+        &[(0x41, 1), (0x42, 2), (0x43, 3)],
+        &[],
+        vec![(b"COLR", colr), (b"CPAL", cpal)],
+    );
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(
+        font.palette(0),
+        Some(vec![
+            ColorU::new(0xff, 0x00, 0x00, 0xff),
+            ColorU::new(0x00, 0x00, 0xff, 0xff)
+        ])
+    );
+    assert_eq!(
+        font.color_glyph_layers(3),
+        Some(vec![ColorLayer {
+            glyph_id: 1,
+            palette_index: 0
+        }])
+    );
+    assert_eq!(font.color_glyph_layers(2), None);
+
+    // The glyphs are 50 pixels square, with their bottom left corner at (5, 55).
+    let rasterize = |character, format| {
+        let mut canvas = Canvas::new(Vector2I::splat(60), format);
+        let rasterized_glyph = font
+            .rasterize_color_glyph(
+                &mut canvas,
+                font.glyph_for_char(character).unwrap(),
+                100.0,
+                Transform2F::from_translation(Vector2F::new(5.0, 55.0)),
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        (canvas, rasterized_glyph)
+    };
+    let pixel = |canvas: &Canvas, x: usize, y: usize| {
+        let offset = y * canvas.stride + x * 4;
+        canvas.pixels[offset..(offset + 4)].to_vec()
+    };
+
+    let (canvas, rasterized_glyph) = rasterize('C', Format::Rgba32);
+    assert_eq!(rasterized_glyph.source, GlyphSource::ColorLayers);
+    assert_eq!(
+        rasterized_glyph.ink_rect,
+        RectI::new(Vector2I::splat(5), Vector2I::splat(50))
+    );
+    assert_eq!(pixel(&canvas, 30, 30), [0xff, 0x00, 0x00, 0xff]);
+    assert_eq!(pixel(&canvas, 2, 30), [0x00; 4]);
+
+    let (canvas, rasterized_glyph) = rasterize('B', Format::Rgba32);
+    assert_eq!(rasterized_glyph.source, GlyphSource::ColorLayers);
+    let (left, middle, right) = (
+        pixel(&canvas, 7, 30),
+        pixel(&canvas, 30, 30),
+        pixel(&canvas, 52, 30),
+    );
+    assert!(left[0] > 0xf0 && left[2] < 0x10 && left[3] == 0xff);
+    assert!(middle[0].abs_diff(0x80) < 4 && middle[2].abs_diff(0x80) < 4);
+    assert!(right[0] < 0x10 && right[2] > 0xf0 && right[3] == 0xff);
+
+    // Other canvas formats get the glyph's coverage.
+    let (canvas, rasterized_glyph) = rasterize('B', Format::A8);
+    assert_eq!(rasterized_glyph.source, GlyphSource::Outline);
+    assert_eq!(canvas.pixels[30 * canvas.stride + 30], 0xff);
+}
+
+#[test]
+pub fn rasterize_colr_glyphs_with_palette() {
+    // Palette 0 is red and blue, and palette 1, which is marked as usable with a dark
+    // background, is green and yellow.
+    let mut cpal = vec![];
+    for value in [1u16, 2, 2, 4, 0, 36, 0, 2, 0, 28, 0, 0, 0, 0] {
+        cpal.extend(value.to_be_bytes());
+    }
+    for value in [0u32, 2] {
+        cpal.extend(value.to_be_bytes());
+    }
+    cpal.extend([
+        0x00, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0xff,
+        0xff,
+    ]);
+    let font_data = synthesize_font_with_tables(
+        1000,
+        &[(0x41, 1), (0x42, 2), (0x43, 3)],
+        &[],
+        vec![(b"COLR", synthesize_colr_table()), (b"CPAL", cpal)],
+    );
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+
+    let (red, green, yellow, magenta) = (
+        ColorU::new(0xff, 0x00, 0x00, 0xff),
+        ColorU::new(0x00, 0xff, 0x00, 0xff),
+        ColorU::new(0xff, 0xff, 0x00, 0xff),
+        ColorU::new(0xff, 0x00, 0xff, 0xff),
+    );
+    let mut dark = PaletteSelector::new();
+    dark.palette(PaletteChoice::UsableWithDarkBackground);
+    assert_eq!(font.selected_palette(&dark), [green, yellow]);
+    let mut light = PaletteSelector::new();
+    light.palette(PaletteChoice::UsableWithLightBackground);
+    assert_eq!(font.selected_palette(&light), font.palette(0).unwrap());
+    let mut overridden = PaletteSelector::new();
+    overridden.override_color(0, magenta).override_color(3, red);
+    assert_eq!(
+        font.selected_palette(&overridden),
+        [
+            magenta,
+            ColorU::new(0x00, 0x00, 0xff, 0xff),
+            ColorU::transparent_black(),
+            red
+        ]
+    );
+    assert_eq!(
+        font.color_glyph_layers_with_palette(3, &dark),
+        Some(vec![(
+            ColorLayer {
+                glyph_id: 1,
+                palette_index: 0
+            },
+            green
+        )])
+    );
+
+    // The glyphs are 50 pixels square, with their bottom left corner at (5, 55).
+    let center_pixel = |character, selector: &PaletteSelector| {
+        let mut canvas = Canvas::new(Vector2I::splat(60), Format::Rgba32);
+        font.rasterize_color_glyph_with_palette(
+            &mut canvas,
+            font.glyph_for_char(character).unwrap(),
+            100.0,
+            Transform2F::from_translation(Vector2F::new(5.0, 55.0)),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+            selector,
+        )
+        .unwrap();
+        let offset = 30 * canvas.stride + 30 * 4;
+        canvas.pixels[offset..(offset + 4)].to_vec()
+    };
+    assert_eq!(
+        center_pixel('C', &PaletteSelector::new()),
+        [0xff, 0x00, 0x00, 0xff]
+    );
+    assert_eq!(center_pixel('C', &dark), [0x00, 0xff, 0x00, 0xff]);
+    assert_eq!(center_pixel('C', &overridden), [0xff, 0x00, 0xff, 0xff]);
+
+    // The gradient of the version 1 glyph runs from green to yellow in the dark palette.
+    let middle = center_pixel('B', &dark);
+    assert!(middle[0].abs_diff(0x80) < 4 && middle[1] == 0xff && middle[2] == 0x00);
+}
+
+#[test]
+pub fn rasterize_colr_glyphs_in_foreground_color() {
+    // A version 0 `COLR` table in which glyph 3 is glyph 1 in the foreground color. Like many
+    // icon fonts, the font has no `CPAL` table, so the palette is empty.
+    let mut colr = vec![];
+    for value in [
+        0u16,
+        1,
+        0,
+        14,
+        0,
+        20,
+        1,
+        3,
+        0,
+        1,
+        1,
+        FOREGROUND_PALETTE_INDEX,
+    ] {
+        colr.extend(value.to_be_bytes());
+    }
+    let font_data =
+        synthesize_font_with_tables(1000, &[(0x41, 1), (0x43, 3)], &[], vec![(b"COLR", colr)]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+
+    let teal = ColorU::new(0x00, 0x80, 0x80, 0xff);
+    let mut themed = PaletteSelector::new();
+    themed.foreground(teal);
+    assert_eq!(
+        font.color_glyph_layers_with_palette(3, &themed),
+        Some(vec![(
+            ColorLayer {
+                glyph_id: 1,
+                palette_index: FOREGROUND_PALETTE_INDEX
+            },
+            teal
+        )])
+    );
+
+    // The glyph is 50 pixels square, with its bottom left corner at (5, 55).
+    let center_pixel = |selector: &PaletteSelector| {
+        let mut canvas = Canvas::new(Vector2I::splat(60), Format::Rgba32);
+        let rasterized_glyph = font
+            .rasterize_color_glyph_with_palette(
+                &mut canvas,
+                3,
+                100.0,
+                Transform2F::from_translation(Vector2F::new(5.0, 55.0)),
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+                selector,
+            )
+            .unwrap();
+        assert_eq!(rasterized_glyph.source, GlyphSource::ColorLayers);
+        let offset = 30 * canvas.stride + 30 * 4;
+        canvas.pixels[offset..(offset + 4)].to_vec()
+    };
+    assert_eq!(center_pixel(&PaletteSelector::new()), [0xff; 4]);
+    assert_eq!(center_pixel(&themed), [0x00, 0x80, 0x80, 0xff]);
+
+    // Translucent foreground colors are composited with premultiplied alpha, and palette
+    // overrides don't replace the foreground color.
+    let mut translucent = PaletteSelector::new();
+    translucent
+        .foreground(ColorU::new(0x00, 0x00, 0xff, 0x80))
+        .override_color(FOREGROUND_PALETTE_INDEX, teal);
+    assert_eq!(center_pixel(&translucent), [0x00, 0x00, 0x80, 0x80]);
+}
+
+// The parser loader only reads the default instance of variable fonts.
+#[test]
+pub fn instance_font_with_variations() {
+    let wght = u32::from_be_bytes(*b"wght");
+    let font_data = synthesize_varying_font(
+        1000,
+        &[(0x41, 1)],
+        &[
+            (b"wght", 100.0, 400.0, 900.0),
+            (b"wdth", 50.0, 100.0, 100.0),
+        ],
+        100,
+    );
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let glyph_id = font.glyph_for_char('A').unwrap();
+    assert_eq!(font.outline_bounds(glyph_id).unwrap().width(), 500.0);
+
+    // 700 is 60% of the way from the default weight to the maximum.
+    let bold = font.with_variations(&[(wght, 700.0)]).unwrap();
+    let bold_width = bold.outline_bounds(glyph_id).unwrap().width();
+    assert!((bold_width - 560.0).abs() < 1.0, "{}", bold_width);
+    assert_eq!(font.outline_bounds(glyph_id).unwrap().width(), 500.0);
+
+    // Rasterization follows the same instance as the outline.
+    let ink_width = |font: &Font| {
+        let mut canvas = Canvas::new(Vector2I::new(80, 60), Format::A8);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            100.0,
+            Transform2F::from_translation(Vector2F::new(0.0, 55.0)),
+            HintingOptions::None,
+            RasterizationOptions::Bilevel,
+        )
+        .unwrap();
+        (0..canvas.size.x() as usize)
+            .filter(|&x| {
+                (0..canvas.size.y() as usize).any(|y| canvas.pixels[y * canvas.stride + x] != 0)
+            })
+            .count()
+    };
+    assert_eq!(ink_width(&font), 50);
+    assert_eq!(ink_width(&bold), 56);
+
+    // Values are clamped, and unlisted axes keep their defaults.
+    let heaviest = font
+        .with_variations(&[(wght, 2000.0), (u32::from_be_bytes(*b"XXXX"), 1.0)])
+        .unwrap();
+    assert_eq!(heaviest.outline_bounds(glyph_id).unwrap().width(), 600.0);
+
+    // Static fonts are unchanged.
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    assert_eq!(
+        font.with_variations(&[(wght, 700.0)])
+            .unwrap()
+            .outline_bounds(glyph_id)
+            .unwrap(),
+        font.outline_bounds(glyph_id).unwrap()
+    );
+}
+
+// The platform loaders must instance variable fonts the same way FreeType does.
+#[cfg(all(
+    any(target_os = "macos", target_os = "ios", target_family = "windows"),
+    feature = "loader-freetype"
+))]
+#[test]
+pub fn instance_font_with_variations_like_freetype() {
+    use font_kit::loaders::freetype::Font as FreeTypeFont;
+
+    let wght = u32::from_be_bytes(*b"wght");
+    let font_data = Arc::new(synthesize_varying_font(
+        1000,
+        &[(0x41, 1)],
+        &[(b"wght", 100.0, 400.0, 900.0)],
+        100,
+    ));
+    let font = Font::from_bytes(font_data.clone(), 0)
+        .unwrap()
+        .with_variations(&[(wght, 700.0)])
+        .unwrap();
+    let freetype_font = FreeTypeFont::from_bytes(font_data, 0)
+        .unwrap()
+        .with_variations(&[(wght, 700.0)])
+        .unwrap();
+    let glyph_id = font.glyph_for_char('A').unwrap();
+    let bounds = font.outline_bounds(glyph_id).unwrap();
+    let freetype_bounds = freetype_font.outline_bounds(glyph_id).unwrap();
+    assert!((bounds.width() - freetype_bounds.width()).abs() < 1.0);
+    assert!((bounds.height() - freetype_bounds.height()).abs() < 1.0);
+}
+
+#[test]
+pub fn normalize_variations_with_avar() {
+    let (wght, wdth) = (u32::from_be_bytes(*b"wght"), u32::from_be_bytes(*b"wdth"));
+    // The weight axis maps -0.5 to -0.25 and 0.5 to 0.75; the width axis is linear.
+    let mut avar = vec![];
+    for value in [1u16, 0, 0, 2, 5] {
+        avar.extend(value.to_be_bytes());
+    }
+    for value in [-1.0, -1.0, -0.5, -0.25, 0.0, 0.0, 0.5, 0.75, 1.0, 1.0] {
+        avar.extend(((value * 16384.0) as i16).to_be_bytes());
+    }
+    avar.extend(0u16.to_be_bytes());
+    let axes = [
+        (b"wght", 100.0, 400.0, 900.0),
+        (b"wdth", 50.0, 100.0, 100.0),
+    ];
+    let font_data = synthesize_font_with_tables(
+        1000,
+        &[(0x41, 1)],
+        &[],
+        vec![(b"fvar", fvar_table(&axes)), (b"avar", avar)],
+    );
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+
+    // Segment boundaries map exactly, and values between them are interpolated.
+    assert_eq!(font.normalize_variation(wght, 100.0), -1.0);
+    assert_eq!(font.normalize_variation(wght, 250.0), -0.25);
+    assert_eq!(font.normalize_variation(wght, 400.0), 0.0);
+    assert_eq!(font.normalize_variation(wght, 650.0), 0.75);
+    assert_eq!(font.normalize_variation(wght, 900.0), 1.0);
+    assert_eq!(font.normalize_variation(wght, 525.0), 0.375);
+    assert_eq!(font.normalize_variation(wght, 1000.0), 1.0);
+    assert_eq!(font.normalize_variation(wdth, 75.0), -0.5);
+    assert_eq!(
+        font.normalize_variation(u32::from_be_bytes(*b"opsz"), 12.0),
+        0.0
+    );
+
+    assert_eq!(font.denormalize_variation(wght, -0.25), Some(250.0));
+    assert_eq!(font.denormalize_variation(wght, 0.75), Some(650.0));
+    assert_eq!(font.denormalize_variation(wght, 0.375), Some(525.0));
+    assert_eq!(font.denormalize_variation(wdth, -0.5), Some(75.0));
+    assert_eq!(
+        font.denormalize_variation(u32::from_be_bytes(*b"opsz"), 0.5),
+        None
+    );
+}
+
+// The parser loader only reads the default instance of variable fonts.
+// Instances must follow the `avar` mapping, and not interpolate linearly between the axis range
+// and its default.
+#[test]
+pub fn instance_font_with_avar() {
+    let wght = u32::from_be_bytes(*b"wght");
+    // The weight axis maps 0.5 to 0.75.
+    let mut avar = vec![];
+    for value in [1u16, 0, 0, 1, 4] {
+        avar.extend(value.to_be_bytes());
+    }
+    for value in [-1.0, -1.0, 0.0, 0.0, 0.5, 0.75, 1.0, 1.0] {
+        avar.extend(((value * 16384.0) as i16).to_be_bytes());
+    }
+    let axes = [(b"wght", 100.0, 400.0, 900.0)];
+    let linear_font = Font::from_bytes(
+        Arc::new(synthesize_varying_font(1000, &[(0x41, 1)], &axes, 100)),
+        0,
+    )
+    .unwrap();
+    let font_data =
+        synthesize_varying_font_with_tables(1000, &[(0x41, 1)], &axes, 100, vec![(b"avar", avar)]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let glyph_id = font.glyph_for_char('A').unwrap();
+
+    // 650 is halfway from the default weight to the maximum, which `avar` moves to 75%.
+    let width = |font: &Font| {
+        font.with_variations(&[(wght, 650.0)])
+            .unwrap()
+            .outline_bounds(glyph_id)
+            .unwrap()
+            .width()
+    };
+    assert!((width(&linear_font) - 550.0).abs() < 1.0);
+    assert!((width(&font) - 575.0).abs() < 1.0, "{}", width(&font));
+    assert_eq!(font.normalize_variation(wght, 650.0), 0.75);
+}
+
+// The parser loader only reads the default instance of variable fonts.
+#[test]
+pub fn get_variation_coordinates() {
+    let (wght, wdth) = (u32::from_be_bytes(*b"wght"), u32::from_be_bytes(*b"wdth"));
+    let font_data = synthesize_varying_font(
+        1000,
+        &[(0x41, 1)],
+        &[
+            (b"wght", 100.0, 400.0, 900.0),
+            (b"wdth", 50.0, 100.0, 100.0),
+        ],
+        100,
+    );
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(
+        font.variation_coordinates(),
+        [
+            VariationCoordinate {
+                tag: wght,
+                user_value: 400.0,
+                normalized_value: 0.0,
+            },
+            VariationCoordinate {
+                tag: wdth,
+                user_value: 100.0,
+                normalized_value: 0.0,
+            },
+        ]
+    );
+
+    let instance = font
+        .with_variations(&[(wght, 700.0), (wdth, 75.0)])
+        .unwrap();
+    let coordinates = instance.variation_coordinates();
+    assert_eq!(coordinates.len(), 2);
+    assert_eq!(coordinates[0].tag, wght);
+    assert!((coordinates[0].user_value - 700.0).abs() < 0.01);
+    assert!((coordinates[0].normalized_value - 0.6).abs() < 0.001);
+    assert_eq!(coordinates[1].tag, wdth);
+    assert!((coordinates[1].user_value - 75.0).abs() < 0.01);
+    assert!((coordinates[1].normalized_value + 0.5).abs() < 0.001);
+
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert!(font.variation_coordinates().is_empty());
+}
+
+#[cfg(feature = "source")]
+#[test]
+fn select_weights_from_variable_font() {
+    let font_data = synthesize_variable_font(1000, &[(0x41, 1)], &[(b"wght", 100.0, 400.0, 900.0)]);
+    let source =
+        MemSource::from_fonts(iter::once(Handle::from_memory(Arc::new(font_data), 0))).unwrap();
+    let family_names = [FamilyName::Title("Synthesized".to_owned())];
+    for &weight in &[Weight::LIGHT, Weight::NORMAL, Weight::EXTRA_BOLD] {
+        let selection = source
+            .select_best_match_with_variations(&family_names, Properties::new().weight(weight))
+            .unwrap();
+        assert_eq!(
+            selection.variations,
+            [(u32::from_be_bytes(*b"wght"), weight.0)]
+        );
+        assert!(source
+            .select_best_match(&family_names, Properties::new().weight(weight))
+            .is_ok());
+    }
+
+    // Weights outside the axis range select the nearest end of it.
+    let selection = source
+        .select_best_match_with_variations(&family_names, Properties::new().weight(Weight(950.0)))
+        .unwrap();
+    assert_eq!(
+        selection.variations,
+        [(u32::from_be_bytes(*b"wght"), 900.0)]
+    );
+}
+
+#[test]
+fn convert_stretch_width_classes() {
+    let classes = [
+        Stretch::ULTRA_CONDENSED,
+        Stretch::EXTRA_CONDENSED,
+        Stretch::CONDENSED,
+        Stretch::SEMI_CONDENSED,
+        Stretch::NORMAL,
+        Stretch::SEMI_EXPANDED,
+        Stretch::EXPANDED,
+        Stretch::EXTRA_EXPANDED,
+        Stretch::ULTRA_EXPANDED,
+    ];
+    for (index, &stretch) in classes.iter().enumerate() {
+        let width_class = index as u16 + 1;
+        assert_eq!(Stretch::from_width_class(width_class), Some(stretch));
+        assert_eq!(stretch.to_width_class(), width_class);
+        assert_eq!(Stretch::from_percentage(stretch.to_percentage()), stretch);
+    }
+    for width_class in [0, 10, u16::MAX] {
+        assert_eq!(Stretch::from_width_class(width_class), None);
+    }
+
+    assert_eq!(Stretch::NORMAL.to_percentage(), 100.0);
+    assert_eq!(Stretch::from_percentage(62.5), Stretch::EXTRA_CONDENSED);
+    assert_eq!(Stretch(0.8).to_width_class(), 3);
+    assert_eq!(Stretch(0.8125).to_width_class(), 3);
+    assert_eq!(Stretch(1.8).to_width_class(), 9);
+    assert_eq!(Stretch(0.1).to_width_class(), 1);
+    assert_eq!(Stretch(5.0).to_width_class(), 9);
+    assert_eq!(Stretch(f32::NAN).to_width_class(), 5);
+
+    // A width class of 0 in the `OS/2` table falls back to the normal width.
+    let mut font_data = synthesize_font(1000, &[(0x41, 1)]);
+    let os_2_offset = table_offset(&font_data, b"OS/2");
+    font_data[os_2_offset + 6..os_2_offset + 8].copy_from_slice(&0u16.to_be_bytes());
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.properties().stretch, Stretch::NORMAL);
+}
+
+#[test]
+fn properties_from_variable_font_defaults() {
+    let axes = [(b"wght", 100.0, 300.0, 900.0), (b"wdth", 75.0, 87.5, 100.0)];
+
+    // Without an `OS/2` table, the axis defaults give the weight and stretch.
+    let font_data = synthesize_font_with_tables(
+        1000,
+        &[(0x41, 1)],
+        &[b"OS/2"],
+        vec![(b"fvar", fvar_table(&axes))],
+    );
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let properties = font.properties();
+    assert_eq!(properties.weight, Weight::LIGHT);
+    assert_eq!(properties.stretch, Stretch::SEMI_CONDENSED);
+
+    // An `OS/2` table with usable classes takes precedence.
+    let font_data = synthesize_variable_font(1000, &[(0x41, 1)], &axes);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let properties = font.properties();
+    assert_eq!(properties.weight, Weight::NORMAL);
+    assert_eq!(properties.stretch, Stretch::NORMAL);
+}
+
+// The parser loader only reads the default instance of variable fonts.
+#[test]
+fn properties_from_variable_font_instances() {
+    let wght = u32::from_be_bytes(*b"wght");
+    let font_data =
+        synthesize_varying_font(1000, &[(0x41, 1)], &[(b"wght", 100.0, 400.0, 900.0)], 100);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.properties().weight, Weight::NORMAL);
+
+    // Away from the default instance, the current axis values win over the `OS/2` table.
+    let bold = font.with_variations(&[(wght, 700.0)]).unwrap();
+    assert_eq!(bold.properties().weight, Weight::BOLD);
+    assert_eq!(bold.properties().stretch, Stretch::NORMAL);
+    assert_eq!(font.properties().weight, Weight::NORMAL);
+}
+
+#[cfg(feature = "source")]
+#[test]
+fn prefer_variable_font_covering_weight() {
+    let static_data = synthesize_font(1000, &[(0x41, 1)]);
+    let variable_data =
+        synthesize_variable_font(1000, &[(0x41, 1)], &[(b"wght", 600.0, 600.0, 900.0)]);
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_memory(Arc::new(static_data), 0),
+            Handle::from_memory(Arc::new(variable_data), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+    let family_names = [FamilyName::Title("Synthesized".to_owned())];
+
+    let selection = source
+        .select_best_match_with_variations(&family_names, Properties::new().weight(Weight::BOLD))
+        .unwrap();
+    assert_eq!(
+        selection.variations,
+        [(u32::from_be_bytes(*b"wght"), 700.0)]
+    );
+
+    // The static font's exact weight still wins when the request falls outside the axis range.
+    let selection = source
+        .select_best_match_with_variations(&family_names, &Properties::new())
+        .unwrap();
+    assert!(selection.variations.is_empty());
+}
+
+#[cfg(any(
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    feature = "loader-freetype"
+))]
+#[test]
+fn rasterize_glyph_preferring_bitmaps() {
+    let font_data =
+        synthesize_font_with_tables(1000, &[(0x41, 1)], &[], synthesize_bitmap_strike_tables(16));
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let glyph_id = font.glyph_for_char('A').unwrap();
+    let rasterize = |size: f32, prefer_bitmaps: bool| {
+        let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+        font.rasterize_glyph_with_bitmap_preference(
+            &mut canvas,
+            glyph_id,
+            size,
+            Transform2F::from_translation(Vector2F::new(4.0, 24.0)),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+            prefer_bitmaps,
+        )
+        .unwrap()
+    };
+
+    // Without a preference for bitmaps, the glyph is drawn from its outline, an 8-pixel square at
+    // 16 pixels per em.
+    let rasterized_glyph = rasterize(16.0, false);
+    assert_eq!(rasterized_glyph.source, GlyphSource::Outline);
+    assert_eq!(
+        rasterized_glyph.ink_rect,
+        RectI::new(Vector2I::new(4, 16), Vector2I::splat(8))
+    );
+
+    let rasterized_glyph = rasterize(16.0, true);
+    assert_eq!(rasterized_glyph.source, GlyphSource::EmbeddedBitmap);
+    assert_eq!(
+        rasterized_glyph.ink_rect,
+        RectI::new(Vector2I::new(4, 20), Vector2I::splat(4))
+    );
+    assert_eq!(rasterize(16.2, true).source, GlyphSource::EmbeddedBitmap);
+
+    // There's no strike at 24 pixels per em.
+    assert_eq!(rasterize(24.0, true).source, GlyphSource::Outline);
+
+    // `rasterize_glyph()` uses matching strikes, as FreeType does.
+    let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+    let rasterized_glyph = font
+        .rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            16.0,
+            Transform2F::from_translation(Vector2F::new(4.0, 24.0)),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    assert_eq!(rasterized_glyph.source, GlyphSource::EmbeddedBitmap);
+}
+
+#[test]
+fn get_gasp_behavior() {
+    // Smooth but unhinted up to 8 pixels per em, and hinted but bilevel above that.
+    let gasp: Vec<u8> = [1u16, 2, 8, 0x0002, 0xffff, 0x0001]
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect();
+    let font_data = synthesize_font_with_tables(1000, &[(0x41, 1)], &[], vec![(b"gasp", gasp)]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let small = font.gasp_behavior(8);
+    assert!(!small.grid_fit && small.grayscale);
+    let large = font.gasp_behavior(9);
+    assert!(large.grid_fit && !large.grayscale);
+
+    // Offset the glyph by half a pixel so that antialiasing leaves partially covered pixels.
+    let glyph_id = font.glyph_for_char('A').unwrap();
+    let rasterize = |size: f32| {
+        let mut canvas = Canvas::new(Vector2I::splat(16), Format::A8);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            size,
+            Transform2F::from_translation(Vector2F::new(2.5, 12.5)),
+            HintingOptions::FontDefault(size),
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+        canvas.pixels
+    };
+    let pixels = rasterize(8.0);
+    assert!(pixels.iter().any(|&value| value != 0 && value != 255));
+    let pixels = rasterize(9.0);
+    assert!(pixels.contains(&255));
+    assert!(pixels.iter().all(|&value| value == 0 || value == 255));
+
+    // Fonts without a `gasp` table are hinted and smoothed at every size.
+    let font_data = synthesize_font(1000, &[(0x41, 1)]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.gasp_behavior(12), GaspBehavior::default());
+    assert!(GaspBehavior::default().grid_fit && GaspBehavior::default().grayscale);
+
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let behavior = font.gasp_behavior(12);
+    assert!(behavior.grid_fit && behavior.grayscale);
+    assert!(behavior.symmetric_grid_fit && behavior.symmetric_smoothing);
+}
+
+#[test]
+fn detect_hinting_instructions() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert!(font.has_hinting_instructions());
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert!(!font.has_hinting_instructions());
+
+    // Without its font and control value programs, the glyphs still carry instructions.
+    let mut font_data = fs::read(FILE_PATH_EB_GARAMOND_TTF).unwrap();
+    for tag in [b"fpgm", b"prep"] {
+        let record = table_record(&font_data, tag);
+        font_data[record + 3] = b'_';
+    }
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert!(font.load_font_table(u32::from_be_bytes(*b"fpgm")).is_none());
+    assert!(font.has_hinting_instructions());
+
+    // Synthesized glyphs have no instructions, as in fonts that have been stripped of them.
+    let font = Font::from_bytes(Arc::new(synthesize_font(1000, &[(0x41, 1)])), 0).unwrap();
+    assert!(!font.has_hinting_instructions());
+    let font_data =
+        synthesize_font_with_tables(1000, &[(0x41, 1)], &[], vec![(b"prep", vec![0xb0, 0x01])]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert!(font.has_hinting_instructions());
+}
+
+#[test]
+fn load_fonts_from_opentype_collection() {
+    let mut file = File::open(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    {
+        let font = Font::from_file(&mut file, 0).unwrap();
+        assert_eq!(
+            font.postscript_name().unwrap(),
+            TEST_FONT_COLLECTION_POSTSCRIPT_NAME[0]
+        );
+    }
+    let font = Font::from_file(&mut file, 1).unwrap();
+    assert_eq!(
+        font.postscript_name().unwrap(),
+        TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1]
+    );
+}
+
+#[test]
+fn font_indices_select_collection_members() {
+    let font_data = Arc::new(fs::read(TEST_FONT_COLLECTION_FILE_PATH).unwrap());
+    let font = Font::from_bytes(font_data.clone(), 1).unwrap();
+    assert_eq!(
+        font.postscript_name().unwrap(),
+        TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1]
+    );
+    assert!(matches!(
+        Font::from_bytes(font_data.clone(), 2),
+        Err(FontLoadingError::NoSuchFontInCollection)
+    ));
+
+    // FreeType-style face indices with a named instance in the upper 16 bits aren't font indices.
+    for font_index in [0x1_0000, 0x1_0001] {
+        assert!(matches!(
+            Font::from_bytes(font_data.clone(), font_index),
+            Err(FontLoadingError::NoSuchFontInCollection)
+        ));
+    }
+
+    // A single font only has index 0.
+    assert!(matches!(
+        Font::from_path(FILE_PATH_INCONSOLATA_TTF, 1),
+        Err(FontLoadingError::NoSuchFontInCollection)
+    ));
+}
+
+// The parser loader only reads the default instance of variable fonts.
+#[test]
+fn select_named_instances() {
+    let axes = [
+        (b"wght", 100.0, 400.0, 900.0),
+        (b"wdth", 75.0, 100.0, 100.0),
+    ];
+    let instances: [&[f32]; 2] = [&[700.0, 100.0], &[300.0, 75.0]];
+    // FreeType 2.12 only supports variations of TrueType outlines with a `gvar` table, so add one
+    // with no variations for either glyph.
+    let mut gvar = vec![];
+    for value in [1u16, 0, axes.len() as u16, 0, 0, 26, 2, 0, 0, 26, 0, 0, 0] {
+        gvar.extend(value.to_be_bytes());
+    }
+    let font_data = synthesize_font_with_tables(
+        1000,
+        &[(0x41, 1)],
+        &[],
+        vec![
+            (b"fvar", fvar_table_with_instances(&axes, &instances)),
+            (b"gvar", gvar),
+        ],
+    );
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+
+    for (instance_index, instance) in instances.iter().enumerate() {
+        let instanced = font.with_named_instance(instance_index as u16).unwrap();
+        let user_values: Vec<f32> = instanced
+            .variation_coordinates()
+            .iter()
+            .map(|coordinate| coordinate.user_value)
+            .collect();
+        assert_eq!(user_values, *instance);
+    }
+    assert!(matches!(
+        font.with_named_instance(2),
+        Err(FontLoadingError::NoSuchFontInCollection)
+    ));
+}
+
+#[test]
+fn get_glyph_count() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(font.glyph_count(), 3084);
+}
+
+// The initial off-curve point used to cause an assertion in the FreeType backend.
+#[test]
+fn get_glyph_outline_eb_garamond_exclam() {
+    let mut file = File::open(FILE_PATH_EB_GARAMOND_TTF).unwrap();
+    let font = Font::from_file(&mut file, 0).unwrap();
+    let glyph = font.glyph_for_char('!').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+        .unwrap();
+
+    // The TrueType spec doesn't specify the rounding method for midpoints, as far as I can tell.
+    // So we are lenient and accept either values rounded down (what Core Text provides if the
+    // first point is off-curve, it seems) or precise floating-point values (what our FreeType
+    // loader provides).
+    let mut outline = outline_builder.into_outline();
+    for contour in &mut outline.contours {
+        for position in &mut contour.positions {
+            *position = position.floor();
+        }
+    }
+
+    println!("{:#?}", outline);
+    assert_eq!(
+        outline,
+        Outline {
+            contours: vec![
+                Contour {
+                    positions: vec![
+                        Vector2F::new(114.0, 598.0),
+                        Vector2F::new(114.0, 619.0),
+                        Vector2F::new(127.0, 634.0),
+                        Vector2F::new(141.0, 649.0),
+                        Vector2F::new(161.0, 649.0),
+                        Vector2F::new(181.0, 649.0),
+                        Vector2F::new(193.0, 634.0),
                         Vector2F::new(206.0, 619.0),
                         Vector2F::new(206.0, 598.0),
                         Vector2F::new(206.0, 526.0),
@@ -1185,6 +5062,135 @@ fn check_L_shape(canvas: &Canvas) {
     assert_eq!(y, canvas.size.y());
 }
 
+// A canvas sized from `raster_bounds()` must hold everything that `rasterize_glyph()` draws, with
+// any hinting, antialiasing and subpixel position.
+#[test]
+pub fn raster_bounds_contain_rasterized_coverage() {
+    fn total_coverage(canvas: &Canvas) -> u64 {
+        canvas.pixels.iter().map(|&value| value as u64).sum()
+    }
+
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let size = 17.0;
+    let options = [
+        (
+            HintingOptions::None,
+            RasterizationOptions::Bilevel,
+            Format::A8,
+        ),
+        (
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+            Format::A8,
+        ),
+        (
+            HintingOptions::Full(size),
+            RasterizationOptions::GrayscaleAa,
+            Format::A8,
+        ),
+        (
+            HintingOptions::Vertical(size),
+            RasterizationOptions::GrayscaleAa,
+            Format::A8,
+        ),
+        (
+            HintingOptions::None,
+            RasterizationOptions::SubpixelAa,
+            Format::Rgb24,
+        ),
+    ];
+    for (index, character) in "AgÅfjW@".chars().enumerate() {
+        let glyph_id = font.glyph_for_char(character).unwrap();
+        let transform = Transform2F::from_translation(Vector2F::new(index as f32 * 0.3, 0.7));
+        for &(hinting_options, rasterization_options, format) in &options {
+            let raster_rect = font
+                .raster_bounds(
+                    glyph_id,
+                    size,
+                    transform,
+                    hinting_options,
+                    rasterization_options,
+                )
+                .unwrap();
+            let mut canvas = Canvas::new(raster_rect.size(), format);
+            font.rasterize_glyph(
+                &mut canvas,
+                glyph_id,
+                size,
+                Transform2F::from_translation(-raster_rect.origin().to_f32()) * transform,
+                hinting_options,
+                rasterization_options,
+            )
+            .unwrap();
+
+            // Draw the glyph again with plenty of room around it, and make sure nothing was
+            // clipped the first time.
+            let padding = Vector2I::splat(16);
+            let mut padded_canvas = Canvas::new(raster_rect.size() + padding * 2, format);
+            font.rasterize_glyph(
+                &mut padded_canvas,
+                glyph_id,
+                size,
+                Transform2F::from_translation((padding - raster_rect.origin()).to_f32())
+                    * transform,
+                hinting_options,
+                rasterization_options,
+            )
+            .unwrap();
+            assert_ne!(total_coverage(&padded_canvas), 0);
+            assert_eq!(
+                total_coverage(&canvas),
+                total_coverage(&padded_canvas),
+                "{:?} lost coverage with {:?} and {:?}",
+                character,
+                hinting_options,
+                rasterization_options
+            );
+        }
+    }
+}
+
+// DirectWrite's raster bounds come from the same glyph run analysis as its rasterization, so
+// they are exactly the pixels that get drawn.
+#[cfg(target_family = "windows")]
+#[test]
+pub fn raster_bounds_match_ink() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    for (index, character) in "AgÅfj@%".chars().enumerate() {
+        let glyph_id = font.glyph_for_char(character).unwrap();
+        let transform = Transform2F::from_translation(Vector2F::new(index as f32 * 0.25, 0.5));
+        for &rasterization_options in &[
+            RasterizationOptions::Bilevel,
+            RasterizationOptions::GrayscaleAa,
+        ] {
+            let raster_rect = font
+                .raster_bounds(
+                    glyph_id,
+                    24.0,
+                    transform,
+                    HintingOptions::None,
+                    rasterization_options,
+                )
+                .unwrap();
+            let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+            let rasterized_glyph = font
+                .rasterize_glyph(
+                    &mut canvas,
+                    glyph_id,
+                    24.0,
+                    Transform2F::from_translation(-raster_rect.origin().to_f32()) * transform,
+                    HintingOptions::None,
+                    rasterization_options,
+                )
+                .unwrap();
+            assert_eq!(
+                rasterized_glyph.ink_rect,
+                RectI::new(Vector2I::default(), raster_rect.size())
+            );
+        }
+    }
+}
+
 // Makes sure that a canvas has an "{" shape in it. This is used to test rasterization.
 #[cfg(target_family = "windows")]
 fn check_curly_shape(canvas: &Canvas) {
@@ -1233,6 +5239,19 @@ fn stride_pixel_start(pixels: &[u8]) -> Option<u32> {
     None
 }
 
+fn ink_size(canvas: &Canvas) -> Vector2I {
+    let (mut min, mut max) = (canvas.size, Vector2I::splat(-1));
+    for y in 0..canvas.size.y() {
+        for x in 0..canvas.size.x() {
+            if canvas.pixels[y as usize * canvas.stride + x as usize] != 0 {
+                min = min.min(Vector2I::new(x, y));
+                max = max.max(Vector2I::new(x, y));
+            }
+        }
+    }
+    max - min + Vector2I::splat(1)
+}
+
 fn stripe_width(pixels: &[u8]) -> Option<u32> {
     let mut x = 0;
     // Find the initial empty part.
@@ -1255,3 +5274,393 @@ fn stripe_width(pixels: &[u8]) -> Option<u32> {
     assert_eq!(x, pixels.len());
     Some(stripe_width)
 }
+
+// Returns `EBLC` and `EBDT` tables with a strike at `ppem` pixels per em in which glyph 1 is a
+// solid 4×4 pixel square sitting on the baseline.
+#[cfg(any(
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    feature = "loader-freetype"
+))]
+fn synthesize_bitmap_strike_tables(ppem: u8) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+    let mut eblc = vec![];
+    for value in [2u16, 0] {
+        eblc.extend(value.to_be_bytes());
+    }
+    // One strike, whose index subtable array at 56 holds 24 bytes.
+    for value in [1u32, 56, 24, 1, 0] {
+        eblc.extend(value.to_be_bytes());
+    }
+    let line_metrics = [
+        ppem - ppem / 4,
+        (ppem / 4).wrapping_neg(),
+        4,
+        1,
+        0,
+        0,
+        0,
+        4,
+        4,
+        0,
+        0,
+        0,
+    ];
+    eblc.extend(line_metrics);
+    eblc.extend(line_metrics);
+    for value in [1u16, 1] {
+        eblc.extend(value.to_be_bytes());
+    }
+    eblc.extend([ppem, ppem, 1, 0x01]);
+    // Glyph 1 is covered by a format 1 index subtable at 8 from the array, whose images are
+    // byte-aligned with small metrics and start at 4 in `EBDT`.
+    for value in [1u16, 1, 0, 8, 1, 1] {
+        eblc.extend(value.to_be_bytes());
+    }
+    for value in [4u32, 0, 9] {
+        eblc.extend(value.to_be_bytes());
+    }
+
+    let mut ebdt = vec![];
+    for value in [2u16, 0] {
+        ebdt.extend(value.to_be_bytes());
+    }
+    // Height, width, x bearing, y bearing, and advance, then one byte per row.
+    ebdt.extend([4, 4, 0, 4, 4]);
+    ebdt.extend([0xf0; 4]);
+
+    vec![(b"EBDT", ebdt), (b"EBLC", eblc)]
+}
+
+// Returns a `COLR` table in which glyph 3 is a version 0 color glyph, glyph 1 in palette entry 0,
+// and glyph 2 is a version 1 color glyph, glyph 1 filled with a gradient from palette entry 0 on
+// the left to entry 1 on the right.
+fn synthesize_colr_table() -> Vec<u8> {
+    let mut colr = vec![];
+    for value in [1u16, 1, 0, 34, 0, 40, 1, 0, 44] {
+        colr.extend(value.to_be_bytes());
+    }
+    colr.extend([0; 16]);
+    for value in [3u16, 0, 1, 1, 0] {
+        colr.extend(value.to_be_bytes());
+    }
+    colr.extend(1u32.to_be_bytes());
+    colr.extend(2u16.to_be_bytes());
+    colr.extend(10u32.to_be_bytes());
+    colr.extend([10, 0, 0, 6, 0, 1]);
+    colr.extend([4, 0, 0, 16]);
+    for value in [0u16, 0, 500, 0, 0, 500] {
+        colr.extend(value.to_be_bytes());
+    }
+    colr.extend([0, 0, 2]);
+    for value in [0u16, 0, 0x4000, 0x4000, 1, 0x4000] {
+        colr.extend(value.to_be_bytes());
+    }
+    colr
+}
+
+// Builds a minimal TrueType font whose glyphs after `.notdef` are squares, mapping each
+// `(character, glyph_id)` pair through a format 12 `cmap` subtable.
+fn synthesize_font(units_per_em: u16, mappings: &[(u32, u16)]) -> Vec<u8> {
+    synthesize_font_without_tables(units_per_em, mappings, &[])
+}
+
+// Like `synthesize_font()`, but leaves out the tables in `omitted`.
+fn synthesize_font_without_tables(
+    units_per_em: u16,
+    mappings: &[(u32, u16)],
+    omitted: &[&[u8; 4]],
+) -> Vec<u8> {
+    synthesize_font_with_tables(units_per_em, mappings, omitted, vec![])
+}
+
+// Like `synthesize_font()`, but a variable font with an `fvar` table listing `axes` as
+// `(tag, min, default, max)`.
+fn synthesize_variable_font(
+    units_per_em: u16,
+    mappings: &[(u32, u16)],
+    axes: &[(&[u8; 4], f32, f32, f32)],
+) -> Vec<u8> {
+    synthesize_font_with_tables(
+        units_per_em,
+        mappings,
+        &[],
+        vec![(b"fvar", fvar_table(axes))],
+    )
+}
+
+// Like `synthesize_variable_font()`, but with a `gvar` table that moves the right edge of each
+// square glyph `max_delta` units to the right at the maximum of the first axis.
+fn synthesize_varying_font(
+    units_per_em: u16,
+    mappings: &[(u32, u16)],
+    axes: &[(&[u8; 4], f32, f32, f32)],
+    max_delta: i8,
+) -> Vec<u8> {
+    synthesize_varying_font_with_tables(units_per_em, mappings, axes, max_delta, vec![])
+}
+
+// Like `synthesize_varying_font()`, but adds the tables in `added`.
+fn synthesize_varying_font_with_tables(
+    units_per_em: u16,
+    mappings: &[(u32, u16)],
+    axes: &[(&[u8; 4], f32, f32, f32)],
+    max_delta: i8,
+    added: Vec<(&'static [u8; 4], Vec<u8>)>,
+) -> Vec<u8> {
+    let glyph_count = mappings
+        .iter()
+        .map(|&(_, glyph_id)| glyph_id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+
+    // One tuple, peaking at the maximum of the first axis, with deltas for all points: the four
+    // corners of the square followed by the four phantom points.
+    let mut glyph_variations = vec![];
+    glyph_variations.extend((0x8000u16 | 1).to_be_bytes());
+    glyph_variations.extend((8 + 2 * axes.len() as u16).to_be_bytes());
+    glyph_variations.extend(6u16.to_be_bytes());
+    glyph_variations.extend(0x8000u16.to_be_bytes());
+    for axis_index in 0..axes.len() {
+        let peak: i16 = if axis_index == 0 { 0x4000 } else { 0 };
+        glyph_variations.extend(peak.to_be_bytes());
+    }
+    glyph_variations.push(0x00);
+    glyph_variations.extend([0x81, 0x01, max_delta as u8, max_delta as u8, 0x83]);
+    glyph_variations.push(0x87);
+    glyph_variations.resize((glyph_variations.len() + 1) & !1, 0);
+
+    let offsets_size = 2 * (glyph_count as u32 + 1);
+    let mut gvar = vec![];
+    for value in [1u16, 0, axes.len() as u16, 0] {
+        gvar.extend(value.to_be_bytes());
+    }
+    gvar.extend((20 + offsets_size).to_be_bytes());
+    gvar.extend(glyph_count.to_be_bytes());
+    gvar.extend(0u16.to_be_bytes());
+    gvar.extend((20 + offsets_size).to_be_bytes());
+    // The `.notdef` glyph is empty, so it has no variations.
+    for glyph_id in 0..=glyph_count {
+        let offset = glyph_id.saturating_sub(1) as usize * glyph_variations.len();
+        gvar.extend(((offset / 2) as u16).to_be_bytes());
+    }
+    gvar.extend(glyph_variations.repeat(glyph_count as usize - 1));
+
+    let mut tables = vec![(b"fvar", fvar_table(axes)), (b"gvar", gvar)];
+    tables.extend(added);
+    synthesize_font_with_tables(units_per_em, mappings, &[], tables)
+}
+
+// An `fvar` table listing `axes` as `(tag, min, default, max)`, with no named instances.
+fn fvar_table(axes: &[(&[u8; 4], f32, f32, f32)]) -> Vec<u8> {
+    fvar_table_with_instances(axes, &[])
+}
+
+// Like `fvar_table()`, but with named instances at the coordinates in `instances`.
+fn fvar_table_with_instances(axes: &[(&[u8; 4], f32, f32, f32)], instances: &[&[f32]]) -> Vec<u8> {
+    let mut fvar = vec![];
+    let instance_size = 4 + 4 * axes.len() as u16;
+    for value in [
+        1u16,
+        0,
+        16,
+        2,
+        axes.len() as u16,
+        20,
+        instances.len() as u16,
+        instance_size,
+    ] {
+        fvar.extend(value.to_be_bytes());
+    }
+    for &(tag, min_value, default_value, max_value) in axes {
+        fvar.extend(tag);
+        for value in [min_value, default_value, max_value] {
+            fvar.extend(((value * 65536.0) as i32).to_be_bytes());
+        }
+        fvar.extend([0; 4]);
+    }
+    for coordinates in instances {
+        fvar.extend([0, 2, 0, 0]);
+        for value in coordinates.iter() {
+            fvar.extend(((value * 65536.0) as i32).to_be_bytes());
+        }
+    }
+    fvar
+}
+
+// Like `synthesize_font()`, but leaves out the tables in `omitted` and adds those in `added`.
+fn synthesize_font_with_tables(
+    units_per_em: u16,
+    mappings: &[(u32, u16)],
+    omitted: &[&[u8; 4]],
+    added: Vec<(&[u8; 4], Vec<u8>)>,
+) -> Vec<u8> {
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    let glyph_count = mappings
+        .iter()
+        .map(|&(_, glyph_id)| glyph_id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let size = units_per_em / 2;
+
+    let mut head = u32s(&[0x00010000, 0x00010000, 0, 0x5f0f3cf5]);
+    head.extend(u16s(&[0x000b, units_per_em]));
+    head.extend([0; 16]);
+    head.extend(u16s(&[0, 0, size, size, 0, 8, 2, 0, 0]));
+
+    let mut hhea = u32s(&[0x00010000]);
+    let ascent = units_per_em - units_per_em / 5;
+    let descent = (units_per_em / 5).wrapping_neg();
+    hhea.extend(u16s(&[
+        ascent,
+        descent,
+        0,
+        units_per_em,
+        0,
+        0,
+        size,
+        1,
+        0,
+        0,
+    ]));
+    hhea.extend(u16s(&[0, 0, 0, 0, 0, glyph_count]));
+
+    let mut maxp = u32s(&[0x00010000]);
+    maxp.extend(u16s(&[glyph_count, 4, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0]));
+
+    let hmtx = u16s(&[units_per_em, 0].repeat(glyph_count as usize));
+
+    // A square with on-curve points at (0, 0), (0, size), (size, size), and (size, 0).
+    let mut square = u16s(&[1, 0, 0, size, size, 3, 0]);
+    square.extend([0x01; 4]);
+    square.extend(u16s(&[0, 0, size, 0, 0, size, 0, size.wrapping_neg()]));
+    let glyf = square.repeat(glyph_count as usize - 1);
+    let loca_offsets: Vec<u16> = (0..=glyph_count)
+        .map(|glyph_id| glyph_id.saturating_sub(1) * square.len() as u16 / 2)
+        .collect();
+    let loca = u16s(&loca_offsets);
+
+    let mut cmap_format_4 = u16s(&[4, 24, 0, 2, 2, 0, 0]);
+    cmap_format_4.extend(u16s(&[0xffff, 0, 0xffff, 1, 0]));
+    let mut cmap_format_12 = u16s(&[12, 0]);
+    cmap_format_12.extend(u32s(&[
+        16 + 12 * mappings.len() as u32,
+        0,
+        mappings.len() as u32,
+    ]));
+    for &(character, glyph_id) in mappings {
+        cmap_format_12.extend(u32s(&[character, character, glyph_id as u32]));
+    }
+    let mut cmap = u16s(&[0, 2, 3, 1]);
+    cmap.extend(u32s(&[20]));
+    cmap.extend(u16s(&[3, 10]));
+    cmap.extend(u32s(&[20 + cmap_format_4.len() as u32]));
+    cmap.extend(cmap_format_4);
+    cmap.extend(cmap_format_12);
+
+    let names = [
+        (1, "Synthesized"),
+        (2, "Regular"),
+        (3, "Synthesized-Regular"),
+        (4, "Synthesized Regular"),
+        (6, "Synthesized-Regular"),
+    ];
+    let mut name = u16s(&[0, names.len() as u16, 6 + 12 * names.len() as u16]);
+    let mut strings = vec![];
+    for &(name_id, string) in &names {
+        let string = u16s(&string.encode_utf16().collect::<Vec<_>>());
+        name.extend(u16s(&[3, 1, 0x409, name_id]));
+        name.extend(u16s(&[string.len() as u16, strings.len() as u16]));
+        strings.extend(string);
+    }
+    name.extend(strings);
+
+    let mut post = u32s(&[0x00030000, 0]);
+    post.extend(u16s(&[
+        (units_per_em / 10).wrapping_neg(),
+        units_per_em / 20,
+    ]));
+    post.extend([0; 20]);
+
+    let mut os_2 = u16s(&[4, units_per_em, 400, 5, 0]);
+    os_2.extend([0; 22]);
+    os_2.extend([0; 10]);
+    os_2.extend([0; 16]);
+    os_2.extend(b"NONE");
+    os_2.extend(u16s(&[0x0040, 0xffff, 0xffff, ascent, descent, 0, ascent]));
+    os_2.extend(u16s(&[units_per_em / 5, 0, 0, 0, 0, size, size, 0, 32, 0]));
+
+    let tables = vec![
+        (b"OS/2", os_2),
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+        (b"name", name),
+        (b"post", post),
+    ];
+    let mut tables: Vec<(&[u8; 4], Vec<u8>)> = tables
+        .into_iter()
+        .filter(|(tag, _)| !omitted.contains(tag))
+        .chain(added)
+        .collect();
+    tables.sort_by_key(|&(tag, _)| *tag);
+    let entry_selector = (tables.len() as u16).ilog2() as u16;
+    let search_range = 16 << entry_selector;
+    let range_shift = tables.len() as u16 * 16 - search_range;
+    let mut font = u32s(&[0x00010000]);
+    font.extend(u16s(&[
+        tables.len() as u16,
+        search_range,
+        entry_selector,
+        range_shift,
+    ]));
+    let mut offset = 12 + 16 * tables.len();
+    let mut data = vec![];
+    for (tag, table) in &tables {
+        let mut padded = table.clone();
+        padded.resize((table.len() + 3) & !3, 0);
+        let checksum = padded
+            .chunks(4)
+            .map(|word| u32::from_be_bytes([word[0], word[1], word[2], word[3]]))
+            .fold(0u32, u32::wrapping_add);
+        font.extend(*tag);
+        font.extend(u32s(&[checksum, offset as u32, table.len() as u32]));
+        offset += padded.len();
+        data.extend(padded);
+    }
+    font.extend(data);
+    font
+}
+
+// Returns the offset of the table tagged `tag` in the data of a single font.
+fn table_offset(font_data: &[u8], tag: &[u8; 4]) -> usize {
+    let record = table_record(font_data, tag);
+    let offset = &font_data[record + 8..record + 12];
+    u32::from_be_bytes([offset[0], offset[1], offset[2], offset[3]]) as usize
+}
+
+// Returns the offset of the table directory record for the table tagged `tag` in the data of a
+// single font.
+fn table_record(font_data: &[u8], tag: &[u8; 4]) -> usize {
+    let table_count = u16::from_be_bytes([font_data[4], font_data[5]]) as usize;
+    (0..table_count)
+        .map(|index| 12 + index * 16)
+        .find(|&record| &font_data[record..record + 4] == tag)
+        .unwrap()
+}